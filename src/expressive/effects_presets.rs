@@ -1,5 +1,9 @@
-use crate::midi::{EffectConfig, EffectType};
+use crate::midi::{EffectConfig, EffectType, ReverbAlgorithm};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const USER_PRESETS_FILE: &str = "effects_presets.json";
 
 /// Effects preset library for common audio scenarios
 pub struct EffectsPresetLibrary {
@@ -7,8 +11,13 @@ pub struct EffectsPresetLibrary {
 }
 
 impl EffectsPresetLibrary {
-    /// Create a new effects preset library with all standard presets
+    /// Create a new effects preset library with all standard presets plus the user's saved presets
     pub fn new() -> Self {
+        Self::with_user_presets(&Self::user_presets_path())
+    }
+
+    /// Create a library with the standard presets and the user presets stored at `path`
+    fn with_user_presets(path: &Path) -> Self {
         let mut library = EffectsPresetLibrary {
             presets: HashMap::new(),
         };
@@ -19,9 +28,73 @@ impl EffectsPresetLibrary {
         library.load_vintage_presets();
         library.load_creative_presets();
 
+        // User presets win over built-ins with the same name
+        for (name, effects) in Self::read_user_presets(path) {
+            library.insert_user_preset(name, effects);
+        }
+
         library
     }
 
+    /// Location of the user's saved effects presets in the mcp-muse data directory
+    pub fn user_presets_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("mcp-muse")
+            .join(USER_PRESETS_FILE)
+    }
+
+    /// Save a custom effects chain under `name` and persist it for future sessions
+    pub fn save_effects_preset(
+        &mut self,
+        name: &str,
+        effects: Vec<EffectConfig>,
+    ) -> Result<(), String> {
+        self.save_effects_preset_to(&Self::user_presets_path(), name, effects)
+    }
+
+    fn save_effects_preset_to(
+        &mut self,
+        path: &Path,
+        name: &str,
+        effects: Vec<EffectConfig>,
+    ) -> Result<(), String> {
+        let mut user_presets = Self::read_user_presets(path);
+        user_presets.insert(name.to_string(), effects.clone());
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create preset directory {:?}: {}", dir, e))?;
+        }
+        let content = serde_json::to_string_pretty(&user_presets)
+            .map_err(|e| format!("Failed to serialize effects presets: {}", e))?;
+        fs::write(path, content)
+            .map_err(|e| format!("Failed to write effects presets to {:?}: {}", path, e))?;
+
+        self.insert_user_preset(name.to_string(), effects);
+        tracing::info!("Saved effects preset '{}' to {:?}", name, path);
+        Ok(())
+    }
+
+    /// Read saved user presets, treating a missing or unreadable file as empty
+    fn read_user_presets(path: &Path) -> HashMap<String, Vec<EffectConfig>> {
+        let Ok(content) = fs::read_to_string(path) else {
+            return HashMap::new();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring unreadable effects presets in {:?}: {}", path, e);
+            HashMap::new()
+        })
+    }
+
+    /// Insert a user preset, replacing any preset whose name matches case-insensitively
+    fn insert_user_preset(&mut self, name: String, effects: Vec<EffectConfig>) {
+        let name_lower = name.to_lowercase();
+        self.presets
+            .retain(|existing, _| existing.to_lowercase() != name_lower);
+        self.presets.insert(name, effects);
+    }
+
     /// Get effects preset by name
     pub fn get_preset(&self, name: &str) -> Option<&Vec<EffectConfig>> {
         // Try exact match first
@@ -57,9 +130,11 @@ impl EffectsPresetLibrary {
                         ratio: 3.0,
                         attack: 0.005,
                         release: 0.05,
+                        parallel: 1.0,
                     },
                     intensity: 0.6,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Reverb {
@@ -67,9 +142,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.4,
                         wet_level: 0.15,
                         pre_delay: 0.02,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.4,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -84,9 +162,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.2,
                         wet_level: 0.4,
                         pre_delay: 0.05,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.7,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Compressor {
@@ -94,9 +175,11 @@ impl EffectsPresetLibrary {
                         ratio: 2.0,
                         attack: 0.01,
                         release: 0.1,
+                        parallel: 1.0,
                     },
                     intensity: 0.3,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -111,9 +194,11 @@ impl EffectsPresetLibrary {
                         ratio: 4.0,
                         attack: 0.003,
                         release: 0.03,
+                        parallel: 1.0,
                     },
                     intensity: 0.7,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Reverb {
@@ -121,9 +206,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.3,
                         wet_level: 0.25,
                         pre_delay: 0.03,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.5,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -138,9 +226,11 @@ impl EffectsPresetLibrary {
                         ratio: 6.0,
                         attack: 0.001,
                         release: 0.02,
+                        parallel: 1.0,
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Reverb {
@@ -148,9 +238,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.6,
                         wet_level: 0.1,
                         pre_delay: 0.01,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.3,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -168,9 +261,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.1,
                         wet_level: 0.6,
                         pre_delay: 0.08,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Chorus {
@@ -181,6 +277,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.7,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Delay {
@@ -191,6 +288,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.5,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -205,9 +303,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.2,
                         wet_level: 0.5,
                         pre_delay: 0.06,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Chorus {
@@ -218,6 +319,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.6,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Filter {
@@ -228,6 +330,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.4,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -242,9 +345,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.25,
                         wet_level: 0.45,
                         pre_delay: 0.07,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.75,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Delay {
@@ -255,6 +361,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.4,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -274,6 +381,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.3,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Chorus {
@@ -284,6 +392,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.5,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Reverb {
@@ -291,9 +400,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.6,
                         wet_level: 0.25,
                         pre_delay: 0.03,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.6,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -310,6 +422,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.25,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Compressor {
@@ -317,9 +430,11 @@ impl EffectsPresetLibrary {
                         ratio: 2.5,
                         attack: 0.01,
                         release: 0.08,
+                        parallel: 1.0,
                     },
                     intensity: 0.5,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Filter {
@@ -330,6 +445,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.3,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -347,6 +463,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.7,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Filter {
@@ -357,6 +474,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.4,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Distortion {
@@ -366,6 +484,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.2,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -386,6 +505,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Delay {
@@ -396,6 +516,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.7,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Filter {
@@ -406,6 +527,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.6,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -422,6 +544,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.7,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Compressor {
@@ -429,9 +552,11 @@ impl EffectsPresetLibrary {
                         ratio: 8.0,
                         attack: 0.001,
                         release: 0.01,
+                        parallel: 1.0,
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Filter {
@@ -442,6 +567,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.5,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -459,6 +585,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Chorus {
@@ -469,6 +596,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.4,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Reverb {
@@ -476,9 +604,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.4,
                         wet_level: 0.2,
                         pre_delay: 0.02,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.5,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -496,6 +627,7 @@ impl EffectsPresetLibrary {
                     },
                     intensity: 0.8,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Reverb {
@@ -503,9 +635,12 @@ impl EffectsPresetLibrary {
                         dampening: 0.3,
                         wet_level: 0.3,
                         pre_delay: 0.04,
+                        modulation: 0.15,
+                        algorithm: ReverbAlgorithm::Schroeder,
                     },
                     intensity: 0.6,
                     enabled: true,
+                    wet_only: false,
                 },
                 EffectConfig {
                     effect: EffectType::Compressor {
@@ -513,9 +648,11 @@ impl EffectsPresetLibrary {
                         ratio: 2.0,
                         attack: 0.008,
                         release: 0.06,
+                        parallel: 1.0,
                     },
                     intensity: 0.4,
                     enabled: true,
+                    wet_only: false,
                 },
             ],
         );
@@ -527,3 +664,51 @@ impl Default for EffectsPresetLibrary {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_effects_preset_resolves_after_reload() {
+        let path = std::env::temp_dir().join(format!(
+            "mcp-muse-effects-presets-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let chain = vec![EffectConfig {
+            effect: EffectType::Distortion {
+                drive: 6.0,
+                tone: 0.2,
+                output_level: 0.8,
+            },
+            intensity: 0.9,
+            enabled: true,
+            wet_only: false,
+        }];
+
+        let mut library = EffectsPresetLibrary::with_user_presets(&path);
+        library
+            .save_effects_preset_to(&path, "my_crunch", chain.clone())
+            .unwrap();
+        // A user preset named like a built-in replaces it
+        library
+            .save_effects_preset_to(&path, "Studio", chain)
+            .unwrap();
+
+        let reloaded = EffectsPresetLibrary::with_user_presets(&path);
+        let crunch = reloaded.get_preset("my_crunch").unwrap();
+        assert_eq!(crunch.len(), 1);
+        assert!(matches!(
+            crunch[0].effect,
+            EffectType::Distortion { drive, .. } if drive == 6.0
+        ));
+        assert!(matches!(
+            reloaded.get_preset("studio").unwrap()[0].effect,
+            EffectType::Distortion { .. }
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
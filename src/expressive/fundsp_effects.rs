@@ -1,6 +1,28 @@
-use crate::midi::{EffectConfig, EffectType, FilterType};
+use crate::midi::{CompressorBand, EffectConfig, EffectType, FilterType, ReverbAlgorithm};
 use anyhow::Result;
 
+/// Maximum comb delay swing for reverb modulation, in seconds
+const REVERB_MODULATION_DEPTH: f32 = 0.002;
+/// LFO rates (Hz) for the four reverb comb filters
+const REVERB_MODULATION_RATES: [f32; 4] = [0.63, 0.79, 0.97, 1.13];
+
+/// Maximum boost or cut of the transient shaper at full attack/sustain gain, in dB
+const TRANSIENT_SHAPER_RANGE_DB: f32 = 12.0;
+
+/// Crossfade time when a shared chain switches settings, short enough to follow notes
+/// but long enough to avoid zipper noise
+const PARAMETER_RAMP_MS: f32 = 5.0;
+
+/// Flanger center delay range in milliseconds, from manual 0.0 to 1.0
+const FLANGER_MANUAL_MS: (f32, f32) = (0.5, 8.0);
+
+/// Phaser all-pass break frequency range in Hz covered at full depth; the sweep is centered
+/// on their geometric mean
+const PHASER_SWEEP_HZ: (f32, f32) = (100.0, 4000.0);
+
+/// Mutually prime FDN delay line lengths in milliseconds, before room-size scaling
+const FDN_DELAYS_MS: [f32; 8] = [29.7, 37.1, 41.1, 43.7, 53.9, 59.3, 67.1, 73.3];
+
 /// FunDSP-based effects processor for professional audio quality
 pub struct FunDSPEffectsProcessor {
     sample_rate: f64,
@@ -12,13 +34,15 @@ impl FunDSPEffectsProcessor {
         Self { sample_rate }
     }
 
-    /// Process audio samples through an effects chain
+    /// Process audio samples through an effects chain.
+    /// Disabled and zero-intensity effects are skipped without being built, so a chain
+    /// of bypassed effects returns the dry signal bit for bit.
     pub fn process_effects(
         &self,
         input_samples: &[f32],
         effects: &[EffectConfig],
     ) -> Result<Vec<f32>> {
-        if effects.is_empty() {
+        if effects.iter().all(EffectConfig::is_bypassed) {
             return Ok(input_samples.to_vec());
         }
 
@@ -26,7 +50,7 @@ impl FunDSPEffectsProcessor {
         let mut processed_samples = input_samples.to_vec();
 
         for effect in effects {
-            if !effect.enabled {
+            if effect.is_bypassed() {
                 continue;
             }
 
@@ -36,9 +60,49 @@ impl FunDSPEffectsProcessor {
         Ok(processed_samples)
     }
 
-    /// Apply a single effect to audio samples
+    /// Process a shared effects chain whose settings change partway through, e.g. per note.
+    ///
+    /// `changes` lists `(sample offset, chain)` in ascending order; the input is dry until
+    /// the first. Each setting runs one chain from its own offset until the next setting
+    /// has faded in, so the work grows with the input length rather than with the number of
+    /// changes times the length. Consecutive settings crossfade over `PARAMETER_RAMP_MS`
+    /// instead of jumping at the boundary, which also covers the new chain starting up.
+    pub fn process_effects_with_changes(
+        &self,
+        input_samples: &[f32],
+        changes: &[(usize, Vec<EffectConfig>)],
+    ) -> Result<Vec<f32>> {
+        let ramp = ((PARAMETER_RAMP_MS / 1000.0) * self.sample_rate as f32).max(1.0) as usize;
+        let mut output = input_samples.to_vec();
+
+        for (index, (start, chain)) in changes.iter().enumerate() {
+            let start = (*start).min(input_samples.len());
+            // A setting is only heard until the next one has finished fading in
+            let end = changes
+                .get(index + 1)
+                .map_or(input_samples.len(), |&(next, _)| {
+                    (next + ramp).min(input_samples.len())
+                })
+                .max(start);
+            let segment = self.process_effects(&input_samples[start..end], chain)?;
+            // Nothing sounds before a chain that starts the input
+            let ramp = if start == 0 { 1 } else { ramp };
+            for (offset, (out, &new)) in output[start..end].iter_mut().zip(&segment).enumerate() {
+                let blend = ((offset + 1) as f32 / ramp as f32).min(1.0);
+                *out += (new - *out) * blend;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Apply a single effect to audio samples.
+    /// Wet-only reverb, delay and chorus drop their dry path; insert effects (filter,
+    /// dynamics, distortion) have no separate wet signal, so they output fully processed.
     fn apply_single_effect(&self, samples: &[f32], effect: &EffectConfig) -> Result<Vec<f32>> {
         let _output: Vec<f32> = Vec::with_capacity(samples.len());
+        let wet_only = effect.wet_only;
+        let intensity = if wet_only { 1.0 } else { effect.intensity };
 
         match &effect.effect {
             EffectType::Reverb {
@@ -46,14 +110,29 @@ impl FunDSPEffectsProcessor {
                 dampening,
                 wet_level,
                 pre_delay,
-            } => self.apply_reverb(
-                samples,
-                *room_size,
-                *dampening,
-                *wet_level,
-                *pre_delay,
-                effect.intensity,
-            ),
+                modulation,
+                algorithm,
+            } => match algorithm {
+                ReverbAlgorithm::Schroeder => self.apply_reverb(
+                    samples,
+                    *room_size,
+                    *dampening,
+                    *wet_level,
+                    *pre_delay,
+                    *modulation,
+                    effect.intensity,
+                    wet_only,
+                ),
+                ReverbAlgorithm::Fdn => self.apply_fdn_reverb(
+                    samples,
+                    *room_size,
+                    *dampening,
+                    *wet_level,
+                    *pre_delay,
+                    effect.intensity,
+                    wet_only,
+                ),
+            },
             EffectType::Delay {
                 delay_time,
                 feedback,
@@ -65,41 +144,86 @@ impl FunDSPEffectsProcessor {
                 *feedback,
                 *wet_level,
                 effect.intensity,
+                wet_only,
             ),
             EffectType::Chorus {
                 rate,
                 depth,
                 feedback,
                 stereo_width: _,
-            } => self.apply_chorus(samples, *rate, *depth, *feedback, effect.intensity),
+            } => self.apply_chorus(
+                samples,
+                *rate,
+                *depth,
+                *feedback,
+                effect.intensity,
+                wet_only,
+            ),
+            EffectType::Flanger {
+                rate,
+                depth,
+                feedback,
+                manual,
+            } => Ok(self.apply_flanger(
+                samples,
+                *rate,
+                *depth,
+                *feedback,
+                *manual,
+                effect.intensity,
+                wet_only,
+            )),
+            EffectType::Phaser {
+                rate,
+                depth,
+                feedback,
+                stages,
+            } => Ok(self.apply_phaser(
+                samples,
+                *rate,
+                *depth,
+                *feedback,
+                *stages,
+                effect.intensity,
+                wet_only,
+            )),
             EffectType::Filter {
                 filter_type,
                 cutoff,
                 resonance,
                 envelope_amount: _,
-            } => self.apply_filter(samples, filter_type, *cutoff, *resonance, effect.intensity),
+            } => self.apply_filter(samples, filter_type, *cutoff, *resonance, intensity),
             EffectType::Compressor {
                 threshold,
                 ratio,
                 attack,
                 release,
+                parallel,
             } => self.apply_compressor(
-                samples,
-                *threshold,
-                *ratio,
-                *attack,
-                *release,
-                effect.intensity,
+                samples, *threshold, *ratio, *attack, *release, *parallel, intensity,
             ),
             EffectType::Distortion {
                 drive,
                 tone,
                 output_level,
-            } => self.apply_distortion(samples, *drive, *tone, *output_level, effect.intensity),
+            } => self.apply_distortion(samples, *drive, *tone, *output_level, intensity),
+            EffectType::MultibandCompressor { crossovers, bands } => {
+                self.apply_multiband_compressor(samples, crossovers, bands, intensity)
+            }
+            EffectType::TransientShaper {
+                attack_gain,
+                sustain_gain,
+            } => Ok(self.apply_transient_shaper(samples, *attack_gain, *sustain_gain, intensity)),
+            // A mono signal has no width to narrow; the channel mixer runs `BassMono` on frames
+            EffectType::BassMono { .. } => Ok(samples.to_vec()),
         }
     }
 
-    /// Apply high-quality reverb using Schroeder reverb algorithm
+    /// Apply high-quality reverb using Schroeder reverb algorithm.
+    ///
+    /// `modulation` slowly sweeps each comb delay length with its own LFO so the
+    /// combs' fixed resonances smear into a smoother, less metallic tail.
+    #[allow(clippy::too_many_arguments)]
     fn apply_reverb(
         &self,
         samples: &[f32],
@@ -107,7 +231,9 @@ impl FunDSPEffectsProcessor {
         dampening: f32,
         wet_level: f32,
         pre_delay: f32,
+        modulation: f32,
         intensity: f32,
+        wet_only: bool,
     ) -> Result<Vec<f32>> {
         // Schroeder reverb parameters (classic algorithm used in professional reverbs)
         let reverb_time = (room_size * 3.0 + 0.5).clamp(0.5, 8.0);
@@ -127,9 +253,18 @@ impl FunDSPEffectsProcessor {
             (0.017 * self.sample_rate as f32) as usize, // 17ms
         ];
 
-        // Initialize delay buffers
-        let mut comb_buffers: Vec<Vec<f32>> =
-            comb_delays.iter().map(|&delay| vec![0.0; delay]).collect();
+        // Comb delay modulation depth in samples, with an LFO per comb at unrelated rates
+        let modulation_depth =
+            modulation.clamp(0.0, 1.0) * REVERB_MODULATION_DEPTH * self.sample_rate as f32;
+        let lfo_increments = REVERB_MODULATION_RATES
+            .map(|rate| 2.0 * std::f32::consts::PI * rate / self.sample_rate as f32);
+        let mut lfo_phases = [0.0, 0.5, 1.0, 1.5].map(|turn| turn * std::f32::consts::PI);
+
+        // Initialize delay buffers (comb buffers leave headroom for the modulated delay)
+        let mut comb_buffers: Vec<Vec<f32>> = comb_delays
+            .iter()
+            .map(|&delay| vec![0.0; delay + modulation_depth.ceil() as usize + 2])
+            .collect();
         let mut allpass_buffers: Vec<Vec<f32>> = allpass_delays
             .iter()
             .map(|&delay| vec![0.0; delay])
@@ -144,7 +279,7 @@ impl FunDSPEffectsProcessor {
 
         let mut output = Vec::with_capacity(samples.len());
         let wet_gain = wet_level * intensity;
-        let dry_gain = 1.0 - wet_gain;
+        let dry_gain = if wet_only { 0.0 } else { 1.0 - wet_gain };
 
         for &sample in samples {
             // Apply pre-delay
@@ -160,14 +295,22 @@ impl FunDSPEffectsProcessor {
             // Parallel comb filters with feedback and damping
             let mut comb_sum = 0.0;
             for i in 0..4 {
-                let delayed = comb_buffers[i][comb_indices[i]];
+                // Read behind the write position by the (possibly modulated) delay length
+                let buffer_len = comb_buffers[i].len();
+                let delay = comb_delays[i] as f32 + modulation_depth * lfo_phases[i].sin();
+                lfo_phases[i] = (lfo_phases[i] + lfo_increments[i]) % std::f32::consts::TAU;
+                let read_position = (comb_indices[i] as f32 - delay).rem_euclid(buffer_len as f32);
+                let read_index = read_position.floor() as usize % buffer_len;
+                let fraction = read_position - read_position.floor();
+                let delayed = comb_buffers[i][read_index] * (1.0 - fraction)
+                    + comb_buffers[i][(read_index + 1) % buffer_len] * fraction;
 
                 // High-frequency damping in feedback loop
                 let damped = delayed * (1.0 - damping_factor * 0.5);
                 let feedback_gain = 0.7 * (1.0 - damping_factor * 0.2); // Reduce feedback with damping
 
                 comb_buffers[i][comb_indices[i]] = delayed_input + damped * feedback_gain;
-                comb_indices[i] = (comb_indices[i] + 1) % comb_delays[i];
+                comb_indices[i] = (comb_indices[i] + 1) % buffer_len;
 
                 comb_sum += delayed;
             }
@@ -191,6 +334,127 @@ impl FunDSPEffectsProcessor {
         Ok(output)
     }
 
+    /// Apply reverb using an 8-line feedback delay network.
+    ///
+    /// Every line feeds every other through a Householder matrix, so echoes multiply
+    /// far faster than in parallel combs and the tail builds into a dense, smooth wash.
+    /// Each line's gain is set so the tail falls 60dB over the room's reverb time.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_fdn_reverb(
+        &self,
+        samples: &[f32],
+        room_size: f32,
+        dampening: f32,
+        wet_level: f32,
+        pre_delay: f32,
+        intensity: f32,
+        wet_only: bool,
+    ) -> Result<Vec<f32>> {
+        const LINES: usize = FDN_DELAYS_MS.len();
+
+        // Same room size to decay time mapping as the Schroeder reverb
+        let reverb_time = (room_size * 3.0 + 0.5).clamp(0.5, 8.0);
+        let damping_factor = dampening.clamp(0.0, 0.9);
+        let size_scale = 0.6 + room_size.clamp(0.0, 1.0) * 0.8;
+
+        let delays = FDN_DELAYS_MS
+            .map(|ms| ((ms * size_scale / 1000.0) * self.sample_rate as f32).max(1.0) as usize);
+        // Per-line gain for a 60dB decay over reverb_time
+        let gains = delays
+            .map(|delay| 10f32.powf(-3.0 * delay as f32 / (reverb_time * self.sample_rate as f32)));
+
+        let mut buffers: Vec<Vec<f32>> = delays.iter().map(|&delay| vec![0.0; delay]).collect();
+        let mut indices = [0usize; LINES];
+        let mut lowpass = [0.0f32; LINES];
+
+        let pre_delay_samples = (pre_delay * self.sample_rate as f32) as usize;
+        let mut pre_delay_buffer = vec![0.0f32; std::cmp::max(pre_delay_samples, 1)];
+        let mut pre_delay_index = 0;
+
+        let mut output = Vec::with_capacity(samples.len());
+        let wet_gain = wet_level * intensity;
+        let dry_gain = if wet_only { 0.0 } else { 1.0 - wet_gain };
+
+        for &sample in samples {
+            let delayed_input = if pre_delay_samples > 0 {
+                let delayed = pre_delay_buffer[pre_delay_index];
+                pre_delay_buffer[pre_delay_index] = sample;
+                pre_delay_index = (pre_delay_index + 1) % pre_delay_samples;
+                delayed
+            } else {
+                sample
+            };
+
+            // Read each line and damp its high end in the feedback path
+            let mut line_outputs = [0.0f32; LINES];
+            for i in 0..LINES {
+                let delayed = buffers[i][indices[i]];
+                lowpass[i] += (1.0 - damping_factor) * (delayed - lowpass[i]);
+                line_outputs[i] = lowpass[i] * gains[i];
+            }
+
+            // Householder feedback matrix: I - 2/N * ones, orthogonal so it preserves energy
+            let line_sum: f32 = line_outputs.iter().sum();
+            let reflection = line_sum * 2.0 / LINES as f32;
+            for i in 0..LINES {
+                let feedback = line_outputs[i] - reflection;
+                // Alternate input polarity so the lines start decorrelated
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                buffers[i][indices[i]] = delayed_input * sign + feedback;
+                indices[i] = (indices[i] + 1) % delays[i];
+            }
+
+            let wet = line_outputs.iter().sum::<f32>() / LINES as f32;
+            output.push(sample * dry_gain + wet * wet_gain);
+        }
+
+        Ok(output)
+    }
+
+    /// Shape attack and sustain independently with two envelope followers.
+    ///
+    /// The fast follower jumps on each hit while the slow one lags behind, so the gap
+    /// between them marks the attack; once the fast follower falls below the slow one
+    /// the signal is in its body. Gains ride on that split without any threshold.
+    fn apply_transient_shaper(
+        &self,
+        samples: &[f32],
+        attack_gain: f32,
+        sustain_gain: f32,
+        intensity: f32,
+    ) -> Vec<f32> {
+        let coefficient = |seconds: f32| (-1.0 / (seconds * self.sample_rate as f32)).exp();
+        let (fast_attack, fast_release) = (coefficient(0.0005), coefficient(0.02));
+        let (slow_attack, slow_release) = (coefficient(0.025), coefficient(0.1));
+
+        let mut fast = 0.0f32;
+        let mut slow = 0.0f32;
+        samples
+            .iter()
+            .map(|&sample| {
+                let level = sample.abs();
+                let follow = |envelope: f32, attack: f32, release: f32| {
+                    let coeff = if level > envelope { attack } else { release };
+                    level + (envelope - level) * coeff
+                };
+                fast = follow(fast, fast_attack, fast_release);
+                slow = follow(slow, slow_attack, slow_release);
+
+                // Share of the current level that belongs to the attack
+                let transient = if fast > 1e-6 {
+                    ((fast - slow) / fast).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let gain_db = TRANSIENT_SHAPER_RANGE_DB
+                    * (attack_gain * transient + sustain_gain * (1.0 - transient));
+                let shaped = sample * 10f32.powf(gain_db / 20.0);
+
+                sample * (1.0 - intensity) + shaped * intensity
+            })
+            .collect()
+    }
+
     /// Apply professional delay effect using manual implementation with proper feedback
     fn apply_delay(
         &self,
@@ -199,6 +463,7 @@ impl FunDSPEffectsProcessor {
         feedback: f32,
         wet_level: f32,
         intensity: f32,
+        wet_only: bool,
     ) -> Result<Vec<f32>> {
         let delay_samples = (delay_time * self.sample_rate as f32) as usize;
         let feedback_gain = (feedback * intensity).clamp(0.0, 0.95);
@@ -207,7 +472,7 @@ impl FunDSPEffectsProcessor {
 
         let mut output = Vec::with_capacity(samples.len());
         let wet_gain = wet_level * intensity;
-        let dry_gain = 1.0 - wet_gain * 0.7;
+        let dry_gain = if wet_only { 0.0 } else { 1.0 - wet_gain * 0.7 };
 
         // High-frequency damping coefficient for analog character
         let damping = 0.7; // Simulates tape/analog delay character
@@ -244,6 +509,7 @@ impl FunDSPEffectsProcessor {
         depth: f32,
         feedback: f32,
         intensity: f32,
+        wet_only: bool,
     ) -> Result<Vec<f32>> {
         // Chorus parameters
         let base_delay_ms = 20.0; // 20ms base delay
@@ -275,7 +541,7 @@ impl FunDSPEffectsProcessor {
         let mut output = Vec::with_capacity(samples.len());
 
         let wet_gain = intensity * 0.6;
-        let dry_gain = 1.0 - wet_gain * 0.5;
+        let dry_gain = if wet_only { 0.0 } else { 1.0 - wet_gain * 0.5 };
 
         for (i, &sample) in samples.iter().enumerate() {
             let time = i as f32 / self.sample_rate as f32;
@@ -329,6 +595,107 @@ impl FunDSPEffectsProcessor {
         Ok(output)
     }
 
+    /// Apply a flanger: one delay line swept by a sine LFO around the `manual` delay, read
+    /// with linear interpolation so the sweep is smooth. Feedback goes back into the line;
+    /// negative feedback flips the comb so the notches sit where the peaks were.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_flanger(
+        &self,
+        samples: &[f32],
+        rate: f32,
+        depth: f32,
+        feedback: f32,
+        manual: f32,
+        intensity: f32,
+        wet_only: bool,
+    ) -> Vec<f32> {
+        let sample_rate = self.sample_rate as f32;
+        let (min_ms, max_ms) = FLANGER_MANUAL_MS;
+        let center = (min_ms + (max_ms - min_ms) * manual.clamp(0.0, 1.0)) * sample_rate / 1000.0;
+        // The sweep never reaches zero delay, where the comb would collapse
+        let swing = center * depth.clamp(0.0, 1.0) * 0.9;
+        let feedback = feedback.clamp(-0.95, 0.95);
+
+        let mut buffer = vec![0.0f32; (center + swing) as usize + 2];
+        let mut write = 0usize;
+
+        let wet_gain = intensity * 0.7;
+        let dry_gain = if wet_only { 0.0 } else { 1.0 - wet_gain * 0.5 };
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, &sample)| {
+                let lfo = (2.0 * std::f32::consts::PI * rate * i as f32 / sample_rate).sin();
+                let delay = (center + swing * lfo).max(1.0);
+                let position = write as f32 - delay;
+                let base = position.floor();
+                let fraction = position - base;
+                let len = buffer.len() as isize;
+                let older = buffer[(base as isize).rem_euclid(len) as usize];
+                let newer = buffer[(base as isize + 1).rem_euclid(len) as usize];
+                let delayed = older + (newer - older) * fraction;
+
+                buffer[write] = sample + delayed * feedback;
+                write = (write + 1) % buffer.len();
+
+                sample * dry_gain + delayed * wet_gain
+            })
+            .collect()
+    }
+
+    /// Apply a phaser: `stages` first-order all-pass filters share one break frequency,
+    /// swept exponentially by a sine LFO. Mixing their output with the dry signal cancels
+    /// wherever the cascade shifts the phase by an odd multiple of 180 degrees, one notch
+    /// per two stages; feedback from the last stage sharpens the notches.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_phaser(
+        &self,
+        samples: &[f32],
+        rate: f32,
+        depth: f32,
+        feedback: f32,
+        stages: u8,
+        intensity: f32,
+        wet_only: bool,
+    ) -> Vec<f32> {
+        let sample_rate = self.sample_rate as f32;
+        let (min_hz, max_hz) = PHASER_SWEEP_HZ;
+        let center = (min_hz * max_hz).sqrt();
+        let octaves = (max_hz / min_hz).log2() / 2.0 * depth.clamp(0.0, 1.0);
+        let feedback = feedback.clamp(0.0, 0.95);
+
+        // Input and output of each stage from the previous sample
+        let mut state = vec![(0.0f32, 0.0f32); stages.clamp(2, 12) as usize];
+        let mut last = 0.0f32;
+
+        // Equal dry and wet at full intensity for the deepest notches
+        let wet_gain = intensity * 0.5;
+        let dry_gain = if wet_only { 0.0 } else { 1.0 - wet_gain };
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, &sample)| {
+                let lfo = (2.0 * std::f32::consts::PI * rate * i as f32 / sample_rate).sin();
+                let break_hz = (center * (octaves * lfo).exp2()).min(sample_rate * 0.45);
+                let tan = (std::f32::consts::PI * break_hz / sample_rate).tan();
+                let coefficient = (tan - 1.0) / (tan + 1.0);
+
+                let mut signal = sample + last * feedback;
+                for (input, output) in state.iter_mut() {
+                    let shifted = coefficient * signal + *input - coefficient * *output;
+                    *input = signal;
+                    *output = shifted;
+                    signal = shifted;
+                }
+                last = signal;
+
+                sample * dry_gain + signal * wet_gain
+            })
+            .collect()
+    }
+
     /// Apply professional filters using state variable filter implementation
     fn apply_filter(
         &self,
@@ -392,7 +759,9 @@ impl FunDSPEffectsProcessor {
         Ok(output)
     }
 
-    /// Apply professional compressor using FunDSP's dynamics processing
+    /// Apply professional compressor using FunDSP's dynamics processing. `parallel` blends
+    /// the compressed signal over the dry one, New York style, before `intensity` applies.
+    #[allow(clippy::too_many_arguments)]
     fn apply_compressor(
         &self,
         samples: &[f32],
@@ -400,8 +769,84 @@ impl FunDSPEffectsProcessor {
         ratio: f32,
         attack: f32,
         release: f32,
+        parallel: f32,
         intensity: f32,
     ) -> Result<Vec<f32>> {
+        let gains = self.compressor_gains(
+            samples.iter().map(|sample| sample.abs()),
+            threshold,
+            ratio,
+            attack,
+            release,
+        );
+
+        // Blend compressed over dry, then mix with the untouched signal based on intensity
+        Ok(samples
+            .iter()
+            .zip(gains)
+            .map(|(&sample, gain)| {
+                let wet = sample * (1.0 - parallel) + sample * gain * parallel;
+                sample * (1.0 - intensity) + wet * intensity
+            })
+            .collect())
+    }
+
+    /// Compress a stereo bus as one signal. Both sides share a gain driven by the louder
+    /// side, read `lookahead` seconds late so it is already down when a transient lands,
+    /// and the image does not shift. `effect` must be a compressor.
+    pub fn compress_stereo_bus(
+        &self,
+        left: &[f32],
+        right: &[f32],
+        effect: &EffectConfig,
+        lookahead: f32,
+    ) -> Result<(Vec<f32>, Vec<f32>)> {
+        let EffectType::Compressor {
+            threshold,
+            ratio,
+            attack,
+            release,
+            parallel,
+        } = effect.effect
+        else {
+            anyhow::bail!("Stereo bus compression needs a compressor effect");
+        };
+        let levels = left
+            .iter()
+            .zip(right)
+            .map(|(left, right)| left.abs().max(right.abs()));
+        let gains = self.compressor_gains(levels, threshold, ratio, attack, release);
+        let lookahead = (lookahead * self.sample_rate as f32) as usize;
+        let gain_at = |index: usize| {
+            let gain = gains
+                .get(index + lookahead)
+                .or(gains.last())
+                .copied()
+                .unwrap_or(1.0);
+            1.0 - parallel + gain * parallel
+        };
+
+        let intensity = effect.intensity;
+        let apply = |side: &[f32]| -> Vec<f32> {
+            side.iter()
+                .enumerate()
+                .map(|(index, &sample)| {
+                    sample * (1.0 - intensity) + sample * gain_at(index) * intensity
+                })
+                .collect()
+        };
+        Ok((apply(left), apply(right)))
+    }
+
+    /// Gain per sample for a compressor following the given input levels
+    fn compressor_gains(
+        &self,
+        levels: impl Iterator<Item = f32>,
+        threshold: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+    ) -> Vec<f32> {
         // Convert dB threshold to linear
         let threshold_linear = 10f32.powf(threshold / 20.0);
         let attack_coeff = (-1.0 / (attack * self.sample_rate as f32)).exp();
@@ -409,13 +854,11 @@ impl FunDSPEffectsProcessor {
 
         // FunDSP doesn't have a built-in compressor, so we'll use a well-implemented algorithm
         // with smooth gain reduction and proper envelope following
-        let mut output = Vec::with_capacity(samples.len());
+        let mut gains = Vec::with_capacity(levels.size_hint().0);
         let mut envelope = 0.0;
         let mut gain_reduction = 1.0;
 
-        for &sample in samples {
-            let input_level = sample.abs();
-
+        for input_level in levels {
             // Smooth envelope follower with proper attack/release
             let target_envelope = input_level;
             let coeff = if target_envelope > envelope {
@@ -443,16 +886,10 @@ impl FunDSPEffectsProcessor {
             };
 
             gain_reduction = target_gain + (gain_reduction - target_gain) * gain_coeff;
-
-            // Apply compression
-            let compressed_sample = sample * gain_reduction;
-
-            // Mix compressed and dry signal based on intensity
-            let final_sample = sample * (1.0 - intensity) + compressed_sample * intensity;
-            output.push(final_sample);
+            gains.push(gain_reduction);
         }
 
-        Ok(output)
+        gains
     }
 
     /// Apply professional distortion using waveshaping with pre/post filtering
@@ -505,4 +942,852 @@ impl FunDSPEffectsProcessor {
 
         Ok(output)
     }
+
+    /// Apply a multiband compressor: split with Linkwitz-Riley crossovers, compress each band
+    /// independently, apply per-band makeup gain and sum the bands back together
+    fn apply_multiband_compressor(
+        &self,
+        samples: &[f32],
+        crossovers: &[f32],
+        bands: &[CompressorBand],
+        intensity: f32,
+    ) -> Result<Vec<f32>> {
+        if bands.len() != crossovers.len() + 1 {
+            anyhow::bail!(
+                "Multiband compressor needs {} bands for {} crossovers, got {}",
+                crossovers.len() + 1,
+                crossovers.len(),
+                bands.len()
+            );
+        }
+
+        let sample_rate = self.sample_rate as f32;
+        let mut remainder = samples.to_vec();
+        let mut wet = vec![0.0; samples.len()];
+
+        for (index, band) in bands.iter().enumerate() {
+            // Peel the lowest band off the remaining signal at each crossover
+            let band_samples = match crossovers.get(index) {
+                Some(&crossover) => {
+                    let low = linkwitz_riley(&remainder, crossover, sample_rate, false);
+                    remainder = linkwitz_riley(&remainder, crossover, sample_rate, true);
+                    low
+                }
+                None => std::mem::take(&mut remainder),
+            };
+
+            let compressed = self.apply_compressor(
+                &band_samples,
+                band.threshold,
+                band.ratio,
+                band.attack,
+                band.release,
+                1.0,
+                1.0,
+            )?;
+
+            let makeup_gain = 10f32.powf(band.makeup_gain_db / 20.0);
+            for (out, sample) in wet.iter_mut().zip(compressed) {
+                *out += sample * makeup_gain;
+            }
+        }
+
+        // Mix compressed and dry signal based on intensity
+        Ok(samples
+            .iter()
+            .zip(wet)
+            .map(|(&dry, wet)| dry * (1.0 - intensity) + wet * intensity)
+            .collect())
+    }
+}
+
+/// Second-order Butterworth section (RBJ cookbook coefficients)
+#[derive(Clone)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn butterworth(cutoff: f32, sample_rate: f32, highpass: bool) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * cutoff / sample_rate;
+        let cos_w0 = w0.cos();
+        let alpha = w0.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let a0 = 1.0 + alpha;
+
+        let (b0, b1, b2) = if highpass {
+            ((1.0 + cos_w0) / 2.0, -(1.0 + cos_w0), (1.0 + cos_w0) / 2.0)
+        } else {
+            ((1.0 - cos_w0) / 2.0, 1.0 - cos_w0, (1.0 - cos_w0) / 2.0)
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: -2.0 * cos_w0 / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let output = self.b0 * input + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = input;
+        self.y2 = self.y1;
+        self.y1 = output;
+        output
+    }
+}
+
+/// Stereo mono-maker: the side signal runs through a Linkwitz-Riley highpass at the
+/// crossover, so lows end up identical left and right while highs keep their width
+#[derive(Clone)]
+pub struct BassMono {
+    first: Biquad,
+    second: Biquad,
+}
+
+impl BassMono {
+    pub fn new(crossover: f32, sample_rate: f32) -> Self {
+        let first = Biquad::butterworth(crossover, sample_rate, true);
+        Self {
+            second: first.clone(),
+            first,
+        }
+    }
+
+    /// Process one stereo frame
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let mid = (left + right) * 0.5;
+        let side = self
+            .second
+            .process(self.first.process((left - right) * 0.5));
+        (mid + side, mid - side)
+    }
+}
+
+/// 4th-order Linkwitz-Riley filter (two cascaded Butterworth sections), so the low and high
+/// outputs at a crossover sum back to a flat magnitude response
+fn linkwitz_riley(samples: &[f32], cutoff: f32, sample_rate: f32, highpass: bool) -> Vec<f32> {
+    let mut first = Biquad::butterworth(cutoff, sample_rate, highpass);
+    let mut second = first.clone();
+    samples
+        .iter()
+        .map(|&sample| second.process(first.process(sample)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |max, s| max.max(s.abs()))
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_multiband_compressor_low_band_only() {
+        // 50Hz sub-bass alternating loud/quiet every 250ms, plus a steady 5kHz tone
+        let segment = (SAMPLE_RATE * 0.25) as usize;
+        let input: Vec<f32> = (0..segment * 4)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE;
+                let sub_level = if (i / segment).is_multiple_of(2) {
+                    0.6
+                } else {
+                    0.06
+                };
+                sub_level * (2.0 * std::f32::consts::PI * 50.0 * t).sin()
+                    + 0.2 * (2.0 * std::f32::consts::PI * 5000.0 * t).sin()
+            })
+            .collect();
+
+        let effect = EffectConfig {
+            effect: EffectType::MultibandCompressor {
+                crossovers: vec![200.0],
+                bands: vec![
+                    CompressorBand {
+                        threshold: -30.0,
+                        ratio: 20.0,
+                        ..CompressorBand::default()
+                    },
+                    CompressorBand {
+                        threshold: 0.0,
+                        ratio: 1.0,
+                        ..CompressorBand::default()
+                    },
+                ],
+            },
+            intensity: 1.0,
+            enabled: true,
+            wet_only: false,
+        };
+
+        let processor = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64);
+        let output = processor.process_effects(&input, &[effect]).unwrap();
+
+        // Compare the settled second half of a loud and a quiet segment
+        let settled = |signal: &[f32], index: usize| {
+            signal[index * segment + segment / 2..(index + 1) * segment].to_vec()
+        };
+
+        let sub_in = linkwitz_riley(&input, 200.0, SAMPLE_RATE, false);
+        let sub_out = linkwitz_riley(&output, 200.0, SAMPLE_RATE, false);
+        let range_in = peak(&settled(&sub_in, 2)) / peak(&settled(&sub_in, 3));
+        let range_out = peak(&settled(&sub_out, 2)) / peak(&settled(&sub_out, 3));
+        assert!(
+            range_out < range_in * 0.5,
+            "sub-bass dynamics not reduced: {} -> {}",
+            range_in,
+            range_out
+        );
+
+        let highs_in = linkwitz_riley(&input, 1000.0, SAMPLE_RATE, true);
+        let highs_out = linkwitz_riley(&output, 1000.0, SAMPLE_RATE, true);
+        let tail = segment..input.len();
+        let ratio = rms(&highs_out[tail.clone()]) / rms(&highs_in[tail]);
+        assert!(
+            (0.95..=1.05).contains(&ratio),
+            "highs changed by factor {}",
+            ratio
+        );
+    }
+
+    /// Ratio of the largest to the mean magnitude over the low end of the spectrum
+    fn spectral_crest(samples: &[f32]) -> f32 {
+        let n = samples.len();
+        let magnitudes: Vec<f32> = (1..n / 8)
+            .map(|bin| {
+                let omega = 2.0 * std::f32::consts::PI * bin as f32 / n as f32;
+                let (re, im) = samples
+                    .iter()
+                    .enumerate()
+                    .fold((0.0, 0.0), |(re, im), (i, &x)| {
+                        let angle = omega * i as f32;
+                        (re + x * angle.cos(), im + x * angle.sin())
+                    });
+                (re * re + im * im).sqrt()
+            })
+            .collect();
+        let mean = magnitudes.iter().sum::<f32>() / magnitudes.len() as f32;
+        magnitudes.iter().fold(0.0f32, |max, &m| max.max(m)) / mean
+    }
+
+    #[test]
+    fn test_reverb_modulation_smooths_tail_spectrum() {
+        let mut impulse = vec![0.0; (SAMPLE_RATE * 1.0) as usize];
+        impulse[0] = 1.0;
+        let processor = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64);
+
+        let tail_crest = |modulation: f32| {
+            let effect = EffectConfig {
+                effect: EffectType::Reverb {
+                    room_size: 0.5,
+                    dampening: 0.1,
+                    wet_level: 1.0,
+                    pre_delay: 0.0,
+                    modulation,
+                    algorithm: ReverbAlgorithm::Schroeder,
+                },
+                intensity: 1.0,
+                enabled: true,
+                wet_only: false,
+            };
+            let output = processor.process_effects(&impulse, &[effect]).unwrap();
+            let tail_start = (SAMPLE_RATE * 0.3) as usize;
+            spectral_crest(&output[tail_start..tail_start + 4096])
+        };
+
+        let static_crest = tail_crest(0.0);
+        let modulated_crest = tail_crest(1.0);
+        assert!(
+            modulated_crest < static_crest * 0.8,
+            "static crest {} vs modulated {}",
+            static_crest,
+            modulated_crest
+        );
+    }
+
+    fn reverb_impulse_response(algorithm: ReverbAlgorithm, room_size: f32) -> Vec<f32> {
+        let mut impulse = vec![0.0; (SAMPLE_RATE * 4.0) as usize];
+        impulse[0] = 1.0;
+        let effect = EffectConfig {
+            effect: EffectType::Reverb {
+                room_size,
+                dampening: 0.2,
+                wet_level: 1.0,
+                pre_delay: 0.0,
+                modulation: 0.0,
+                algorithm,
+            },
+            intensity: 1.0,
+            enabled: true,
+            wet_only: false,
+        };
+        FunDSPEffectsProcessor::new(SAMPLE_RATE as f64)
+            .process_effects(&impulse, &[effect])
+            .unwrap()
+    }
+
+    /// Seconds until the 50ms RMS envelope falls 30dB below its loudest window
+    fn decay_time(response: &[f32]) -> f32 {
+        let window = (SAMPLE_RATE * 0.05) as usize;
+        let levels: Vec<f32> = response.chunks(window).map(rms).collect();
+        let loudest = levels.iter().fold(0.0f32, |max, &level| max.max(level));
+        let peak_index = levels.iter().position(|&level| level == loudest).unwrap();
+        let quiet_index = levels[peak_index..]
+            .iter()
+            .position(|&level| level < loudest * 10f32.powf(-30.0 / 20.0))
+            .map_or(levels.len(), |offset| peak_index + offset);
+        quiet_index as f32 * 0.05
+    }
+
+    /// Magnitude spectrum in 64 bands up to ~5.5kHz, normalized to unit sum
+    fn band_spectrum(samples: &[f32]) -> Vec<f32> {
+        let n = samples.len();
+        let bands: Vec<f32> = (1..=64)
+            .map(|band| {
+                let omega = 2.0 * std::f32::consts::PI * (band * 4) as f32 / n as f32;
+                let (re, im) = samples
+                    .iter()
+                    .enumerate()
+                    .fold((0.0, 0.0), |(re, im), (i, &x)| {
+                        let angle = omega * i as f32;
+                        (re + x * angle.cos(), im + x * angle.sin())
+                    });
+                (re * re + im * im).sqrt()
+            })
+            .collect();
+        let total: f32 = bands.iter().sum();
+        bands.iter().map(|band| band / total).collect()
+    }
+
+    #[test]
+    fn test_schroeder_and_fdn_tails_follow_room_size_with_different_spectra() {
+        for algorithm in [ReverbAlgorithm::Schroeder, ReverbAlgorithm::Fdn] {
+            let small = reverb_impulse_response(algorithm, 0.2);
+            let large = reverb_impulse_response(algorithm, 0.9);
+
+            // Both tails die away well before the end of the render
+            let end = large.len() - (SAMPLE_RATE * 0.5) as usize;
+            assert!(
+                rms(&large[end..]) < rms(&large[..(SAMPLE_RATE * 0.5) as usize]) * 0.05,
+                "{:?} tail does not decay",
+                algorithm
+            );
+
+            let (small_decay, large_decay) = (decay_time(&small), decay_time(&large));
+            assert!(
+                large_decay > small_decay * 1.5,
+                "{:?} decay {}s for room 0.9 vs {}s for room 0.2",
+                algorithm,
+                large_decay,
+                small_decay
+            );
+        }
+
+        let tail = |algorithm| {
+            let response = reverb_impulse_response(algorithm, 0.7);
+            let start = (SAMPLE_RATE * 0.2) as usize;
+            band_spectrum(&response[start..start + 2048])
+        };
+        let difference: f32 = tail(ReverbAlgorithm::Schroeder)
+            .iter()
+            .zip(tail(ReverbAlgorithm::Fdn))
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        assert!(
+            difference > 0.2,
+            "tail spectra differ by only {}",
+            difference
+        );
+    }
+
+    #[test]
+    fn test_wet_only_reverb_drops_the_dry_transient_and_keeps_the_tail() {
+        // 5ms click followed by silence
+        let click_len = (SAMPLE_RATE * 0.005) as usize;
+        let mut click = vec![0.0; SAMPLE_RATE as usize];
+        click[..click_len].fill(0.8);
+
+        let render = |wet_only| {
+            let effect = EffectConfig {
+                effect: EffectType::Reverb {
+                    room_size: 0.5,
+                    dampening: 0.3,
+                    wet_level: 0.4,
+                    pre_delay: 0.0,
+                    modulation: 0.0,
+                    algorithm: ReverbAlgorithm::Schroeder,
+                },
+                intensity: 0.8,
+                enabled: true,
+                wet_only,
+            };
+            FunDSPEffectsProcessor::new(SAMPLE_RATE as f64)
+                .process_effects(&click, &[effect])
+                .unwrap()
+        };
+        let blended = render(false);
+        let wet = render(true);
+
+        // The blend carries the click; the wet return stays silent until the first reflection
+        assert!(rms(&blended[..click_len]) > 0.5);
+        assert!(rms(&wet[..click_len]) < 1e-6);
+
+        // After the click both carry the same reverberant tail
+        let tail = click_len..SAMPLE_RATE as usize / 2;
+        assert!(rms(&wet[tail.clone()]) > 0.01);
+        for (wet, blended) in wet[tail.clone()].iter().zip(&blended[tail]) {
+            assert!((wet - blended).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_half_parallel_compression_lands_between_dry_and_compressed() {
+        // 100Hz tone alternating loud/quiet every 250ms, like accented and ghost hits
+        let segment = (SAMPLE_RATE * 0.25) as usize;
+        let input: Vec<f32> = (0..segment * 4)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE;
+                let level = if (i / segment).is_multiple_of(2) {
+                    0.8
+                } else {
+                    0.05
+                };
+                level * (2.0 * std::f32::consts::PI * 100.0 * t).sin()
+            })
+            .collect();
+        let compress = |parallel: f32| {
+            let effect = EffectConfig {
+                effect: EffectType::Compressor {
+                    threshold: -30.0,
+                    ratio: 10.0,
+                    attack: 0.005,
+                    release: 0.05,
+                    parallel,
+                },
+                intensity: 1.0,
+                enabled: true,
+                wet_only: false,
+            };
+            FunDSPEffectsProcessor::new(SAMPLE_RATE as f64)
+                .process_effects(&input, &[effect])
+                .unwrap()
+        };
+        // Peak-to-RMS over the settled second half of each segment, past the attack overshoot
+        let crest = |signal: &[f32]| {
+            let settled: Vec<f32> = signal
+                .chunks(segment)
+                .flat_map(|chunk| chunk[segment / 2..].to_vec())
+                .collect();
+            peak(&settled) / rms(&settled)
+        };
+
+        let dry = crest(&compress(0.0));
+        let blended = crest(&compress(0.5));
+        let squashed = crest(&compress(1.0));
+        assert!((dry - crest(&input)).abs() < 1e-4, "0.0 should be dry");
+        assert!(
+            squashed < blended && blended < dry,
+            "peak-to-RMS: dry {}, parallel 0.5 {}, compressed {}",
+            dry,
+            blended,
+            squashed
+        );
+    }
+
+    #[test]
+    fn test_bass_mono_centers_the_lows_and_keeps_the_highs_wide() {
+        // 40Hz only on the left, plus a 3kHz tone in opposite polarity on each side
+        let tone = |frequency: f32, i: usize| {
+            (2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE).sin()
+        };
+        let frames = SAMPLE_RATE as usize;
+        let left: Vec<f32> = (0..frames)
+            .map(|i| 0.5 * tone(40.0, i) + 0.3 * tone(3000.0, i))
+            .collect();
+        let right: Vec<f32> = (0..frames).map(|i| -0.3 * tone(3000.0, i)).collect();
+
+        let mut mono_maker = BassMono::new(120.0, SAMPLE_RATE);
+        let (out_left, out_right): (Vec<f32>, Vec<f32>) = left
+            .iter()
+            .zip(&right)
+            .map(|(&l, &r)| mono_maker.process(l, r))
+            .unzip();
+
+        // Compare the settled second half, split at the crossover
+        let settled = frames / 2..;
+        let band = |signal: &[f32], highpass: bool| {
+            linkwitz_riley(signal, 500.0, SAMPLE_RATE, highpass)[settled.clone()].to_vec()
+        };
+        let width = |left: &[f32], right: &[f32]| {
+            let side: Vec<f32> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+            rms(&side)
+        };
+
+        let low_width_in = width(&band(&left, false), &band(&right, false));
+        let low_width_out = width(&band(&out_left, false), &band(&out_right, false));
+        assert!(
+            low_width_out < low_width_in * 0.02,
+            "lows should be mono: side {} -> {}",
+            low_width_in,
+            low_width_out
+        );
+        // The lows are centered, not removed
+        assert!(rms(&band(&out_left, false)) > 0.15);
+
+        let high_width_in = width(&band(&left, true), &band(&right, true));
+        let high_width_out = width(&band(&out_left, true), &band(&out_right, true));
+        let ratio = high_width_out / high_width_in;
+        assert!(
+            (0.95..=1.05).contains(&ratio),
+            "high stereo width changed by {}",
+            ratio
+        );
+    }
+
+    #[test]
+    fn test_positive_attack_gain_lifts_transient_over_body() {
+        // Kick-like hit: 60Hz sine that starts at full level and decays
+        let input: Vec<f32> = (0..(SAMPLE_RATE * 0.4) as usize)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE;
+                0.8 * (-t * 8.0).exp() * (2.0 * std::f32::consts::PI * 60.0 * t).sin()
+            })
+            .collect();
+        let shaper = EffectConfig {
+            effect: EffectType::TransientShaper {
+                attack_gain: 1.0,
+                sustain_gain: 0.0,
+            },
+            intensity: 1.0,
+            enabled: true,
+            wet_only: false,
+        };
+        let output = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64)
+            .process_effects(&input, &[shaper])
+            .unwrap();
+
+        let attack = ..(SAMPLE_RATE * 0.01) as usize;
+        let body = (SAMPLE_RATE * 0.1) as usize..(SAMPLE_RATE * 0.3) as usize;
+        let punch = |signal: &[f32]| peak(&signal[attack]) / rms(&signal[body.clone()]);
+        assert!(
+            punch(&output) > punch(&input) * 1.5,
+            "attack/body {} -> {}",
+            punch(&input),
+            punch(&output)
+        );
+        // The body is left at its original level
+        let body_ratio = rms(&output[body.clone()]) / rms(&input[body.clone()]);
+        assert!(
+            (0.9..=1.1).contains(&body_ratio),
+            "body changed by {}",
+            body_ratio
+        );
+    }
+
+    #[test]
+    fn test_filter_sweep_across_notes_has_no_discontinuities() {
+        let note_len = (SAMPLE_RATE * 0.25) as usize;
+        let frequencies = [100.0, 150.0, 120.0, 180.0];
+        // Cutoffs near the notes shift their level and phase, far above they pass them through
+        let cutoffs = [150.0, 2000.0, 200.0, 3000.0];
+
+        // Back-to-back notes with continuous phase, so the input itself never jumps
+        let mut phase = 0.0f32;
+        let input: Vec<f32> = (0..note_len * frequencies.len())
+            .map(|i| {
+                phase += std::f32::consts::TAU * frequencies[i / note_len] / SAMPLE_RATE;
+                phase.sin() * 0.5
+            })
+            .collect();
+        let filter = |cutoff: f32| {
+            vec![EffectConfig {
+                effect: EffectType::Filter {
+                    filter_type: FilterType::LowPass,
+                    cutoff,
+                    resonance: 0.7,
+                    envelope_amount: 0.0,
+                },
+                intensity: 1.0,
+                enabled: true,
+                wet_only: false,
+            }]
+        };
+        let changes: Vec<(usize, Vec<EffectConfig>)> = cutoffs
+            .iter()
+            .enumerate()
+            .map(|(index, &cutoff)| (index * note_len, filter(cutoff)))
+            .collect();
+
+        let processor = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64);
+        let output = processor
+            .process_effects_with_changes(&input, &changes)
+            .unwrap();
+        let steps: Vec<f32> = output
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .collect();
+
+        // Largest step well inside the notes, away from any change
+        let guard = (SAMPLE_RATE * 0.02) as usize;
+        let near_boundary =
+            |i: usize| (1..frequencies.len()).any(|note| i.abs_diff(note * note_len) < guard);
+        let within_notes = (0..steps.len())
+            .filter(|&i| !near_boundary(i))
+            .map(|i| steps[i])
+            .fold(0.0f32, f32::max);
+
+        for note in 1..frequencies.len() {
+            let boundary = note * note_len;
+            let boundary_step = steps[boundary - guard..boundary + guard]
+                .iter()
+                .fold(0.0f32, |max, &step| max.max(step));
+            assert!(
+                boundary_step <= within_notes * 1.5,
+                "step {} at note {} vs {} within notes",
+                boundary_step,
+                note + 1,
+                within_notes
+            );
+        }
+
+        // Restarting the filter per note instead clicks at the boundaries
+        let restarted: Vec<f32> = input
+            .chunks(note_len)
+            .zip(cutoffs)
+            .flat_map(|(note, cutoff)| processor.process_effects(note, &filter(cutoff)).unwrap())
+            .collect();
+        let restart_step = (restarted[note_len * 2] - restarted[note_len * 2 - 1]).abs();
+        assert!(
+            restart_step > within_notes * 2.0,
+            "restart step {}",
+            restart_step
+        );
+    }
+
+    #[test]
+    fn test_settings_run_from_their_own_change_onward() {
+        let processor = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64);
+        let input: Vec<f32> = (0..44100).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let filter = |cutoff: f32| {
+            vec![EffectConfig {
+                effect: EffectType::Filter {
+                    filter_type: FilterType::LowPass,
+                    cutoff,
+                    resonance: 0.7,
+                    envelope_amount: 0.0,
+                },
+                intensity: 1.0,
+                enabled: true,
+                wet_only: false,
+            }]
+        };
+        let (first, second) = (11025, 22050);
+        let output = processor
+            .process_effects_with_changes(
+                &input,
+                &[(first, filter(300.0)), (second, filter(3000.0))],
+            )
+            .unwrap();
+
+        // Dry before the first note carrying settings
+        assert_eq!(output[..first], input[..first]);
+        // Once faded in, the last setting is its chain run from its change, not from sample 0
+        let ramp = (PARAMETER_RAMP_MS / 1000.0 * SAMPLE_RATE) as usize;
+        let alone = processor
+            .process_effects(&input[second..], &filter(3000.0))
+            .unwrap();
+        for (got, want) in output[second + ramp..].iter().zip(&alone[ramp..]) {
+            assert!((got - want).abs() < 1e-6, "{} vs {}", got, want);
+        }
+    }
+
+    #[test]
+    fn test_zero_intensity_chain_is_a_bit_identical_bypass() {
+        let processor = FunDSPEffectsProcessor::new(44100.0);
+        let input: Vec<f32> = (0..44100).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let chain = |intensity: f32| {
+            [
+                EffectType::Reverb {
+                    room_size: 0.8,
+                    dampening: 0.3,
+                    wet_level: 0.5,
+                    pre_delay: 0.02,
+                    modulation: 0.15,
+                    algorithm: ReverbAlgorithm::Schroeder,
+                },
+                EffectType::Delay {
+                    delay_time: 0.25,
+                    feedback: 0.5,
+                    wet_level: 0.4,
+                    sync_tempo: false,
+                },
+                EffectType::Chorus {
+                    rate: 1.0,
+                    depth: 0.5,
+                    feedback: 0.2,
+                    stereo_width: 0.7,
+                },
+            ]
+            .map(|effect| EffectConfig {
+                effect,
+                intensity,
+                enabled: true,
+                wet_only: false,
+            })
+        };
+
+        let bits = |samples: Vec<f32>| samples.iter().map(|s| s.to_bits()).collect::<Vec<_>>();
+        let dry = bits(processor.process_effects(&input, &[]).unwrap());
+
+        // Zero intensity passes the dry signal through, while the faintest intensity does not
+        let bypassed = processor.process_effects(&input, &chain(0.0)).unwrap();
+        assert_eq!(bits(bypassed), dry);
+        let faint = processor.process_effects(&input, &chain(0.01)).unwrap();
+        assert_ne!(bits(faint), dry);
+
+        // A bypassed effect in the middle of a chain leaves the rest as if it were absent
+        let mut effects = chain(0.5).to_vec();
+        effects[1].intensity = 0.0;
+        let with_bypassed = processor.process_effects(&input, &effects).unwrap();
+        effects.remove(1);
+        let without = processor.process_effects(&input, &effects).unwrap();
+        assert_eq!(bits(with_bypassed), bits(without));
+    }
+
+    #[test]
+    fn test_inverted_flanger_feedback_stays_finite_and_flips_the_comb() {
+        let processor = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64);
+        let flanger = |depth: f32, feedback: f32| EffectConfig {
+            effect: EffectType::Flanger {
+                rate: 10.0,
+                depth,
+                feedback,
+                manual: 0.5,
+            },
+            intensity: 1.0,
+            enabled: true,
+            wet_only: false,
+        };
+
+        // Two seconds of noise through the fastest, deepest sweep at both feedback extremes
+        let mut state = 0x2545_f491_u32;
+        let noise: Vec<f32> = (0..SAMPLE_RATE as usize * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state as f32 / u32::MAX as f32 - 0.5) * 0.5
+            })
+            .collect();
+        for feedback in [-0.95, 0.95] {
+            let output = processor
+                .process_effects(&noise, &[flanger(1.0, feedback)])
+                .unwrap();
+            assert!(
+                output.iter().all(|s| s.is_finite()),
+                "feedback {}",
+                feedback
+            );
+            assert!(
+                peak(&output) < 10.0,
+                "feedback {}: {}",
+                feedback,
+                peak(&output)
+            );
+        }
+
+        // With the sweep stopped, a tone whose period is the delay rings up under positive
+        // feedback and is cancelled under negative feedback
+        let (min_ms, max_ms) = FLANGER_MANUAL_MS;
+        let delay = (min_ms + max_ms) / 2.0 / 1000.0;
+        let tone: Vec<f32> = (0..SAMPLE_RATE as usize)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * i as f32 / SAMPLE_RATE / delay).sin())
+            .collect();
+        let settled = SAMPLE_RATE as usize / 2..;
+        let positive = processor
+            .process_effects(&tone, &[flanger(0.0, 0.9)])
+            .unwrap();
+        let negative = processor
+            .process_effects(&tone, &[flanger(0.0, -0.9)])
+            .unwrap();
+        assert!(
+            rms(&positive[settled.clone()]) > rms(&negative[settled.clone()]) * 4.0,
+            "positive {} vs negative {}",
+            rms(&positive[settled.clone()]),
+            rms(&negative[settled])
+        );
+    }
+
+    #[test]
+    fn test_more_phaser_stages_cut_more_notches() {
+        let processor = FunDSPEffectsProcessor::new(SAMPLE_RATE as f64);
+        let phaser = |stages: u8| EffectConfig {
+            effect: EffectType::Phaser {
+                rate: 0.5,
+                depth: 0.0,
+                feedback: 0.0,
+                stages,
+            },
+            intensity: 1.0,
+            enabled: true,
+            wet_only: false,
+        };
+        let mut impulse = vec![0.0f32; 8192];
+        impulse[0] = 1.0;
+
+        // Magnitude response of the stopped sweep on a log grid from 20Hz to 20kHz; the
+        // notches are its local minima
+        let notches = |stages: u8| {
+            let response = processor
+                .process_effects(&impulse, &[phaser(stages)])
+                .unwrap();
+            let magnitudes: Vec<f32> = (0..300)
+                .map(|step| {
+                    let hz = 20.0 * 1000.0f32.powf(step as f32 / 299.0);
+                    let omega = 2.0 * std::f32::consts::PI * hz / SAMPLE_RATE;
+                    let (re, im) =
+                        response
+                            .iter()
+                            .enumerate()
+                            .fold((0.0, 0.0), |(re, im), (n, s)| {
+                                (
+                                    re + s * (omega * n as f32).cos(),
+                                    im - s * (omega * n as f32).sin(),
+                                )
+                            });
+                    (re * re + im * im).sqrt()
+                })
+                .collect();
+            magnitudes
+                .windows(3)
+                .filter(|w| w[1] < w[0] && w[1] < w[2] && w[1] < 0.5)
+                .count()
+        };
+
+        let counts: Vec<usize> = [2, 4, 8, 12].map(notches).to_vec();
+        assert_eq!(counts, vec![1, 2, 4, 6]);
+    }
 }
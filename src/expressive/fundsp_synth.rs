@@ -1,5 +1,9 @@
+use crate::expressive::seed::random_bipolar;
+use crate::expressive::synth::EnvelopeParams;
 use anyhow::Result;
-use rand::Rng;
+
+/// Highest harmonic used by the band-limited morph oscillator
+const MORPH_MAX_HARMONICS: usize = 64;
 
 /// FunDSP-based synthesizer for high-quality audio synthesis
 pub struct FunDSPSynth {
@@ -13,6 +17,7 @@ pub struct FunDSPParams {
     pub frequency: f32,
     pub amplitude: f32,
     pub duration: f32,
+    pub envelope: EnvelopeParams,
 }
 
 /// Synthesis types handled by FunDSP for quality improvement
@@ -42,6 +47,12 @@ pub enum FunDSPSynthType {
         strike_intensity: f32,
     },
 
+    // Basic oscillator blending
+    Morph {
+        position: f32,
+        end_position: Option<f32>,
+    },
+
     // Advanced synthesis
     FM {
         modulator_freq: f32,
@@ -78,6 +89,13 @@ pub enum FunDSPSynthType {
         spectral_tilt: f32,
         modulation_depth: f32,
     },
+    Drone {
+        overtone_spread: f32,
+        modulation: f32,
+        harmonics: u8,
+        evolution_rate: f32,
+        detune: f32,
+    },
 }
 
 impl FunDSPSynth {
@@ -143,6 +161,17 @@ impl FunDSPSynth {
                 params.amplitude,
                 sample_count,
             ),
+            FunDSPSynthType::Morph {
+                position,
+                end_position,
+            } => self.generate_morph_samples(
+                params.frequency,
+                *position,
+                end_position.unwrap_or(*position),
+                &params.envelope,
+                params.amplitude,
+                sample_count,
+            ),
             FunDSPSynthType::FM {
                 modulator_freq,
                 modulation_index,
@@ -216,6 +245,23 @@ impl FunDSPSynth {
                 params.amplitude,
                 sample_count,
             ),
+            FunDSPSynthType::Drone {
+                overtone_spread,
+                modulation,
+                harmonics,
+                evolution_rate,
+                detune,
+            } => Ok(self.generate_drone_samples(
+                params.frequency,
+                *overtone_spread,
+                *modulation,
+                *harmonics,
+                *evolution_rate,
+                *detune,
+                &params.envelope,
+                params.amplitude,
+                sample_count,
+            )),
         }
     }
 
@@ -288,8 +334,7 @@ impl FunDSPSynth {
             let tonal = (2.0 * std::f32::consts::PI * tone_freq * t).sin() * (1.0 - noise_amount);
 
             // Noise component for snare buzz
-            let mut rng = rand::rng();
-            let noise = (rng.random::<f32>() - 0.5) * 2.0;
+            let noise = random_bipolar();
             // Simple highpass filter for noise
             let filtered_noise = if tone_freq > 1000.0 {
                 noise
@@ -340,8 +385,7 @@ impl FunDSPSynth {
             let metallic3 = (2.0 * std::f32::consts::PI * freq3 * t).sin() * (0.2 * metallic);
 
             // Filtered noise component
-            let mut rng = rand::rng();
-            let noise = (rng.random::<f32>() - 0.5) * 2.0;
+            let noise = random_bipolar();
             let filtered_noise = noise * (1.0 - metallic * 0.5);
 
             let sample =
@@ -397,8 +441,7 @@ impl FunDSPSynth {
             let strike_factor = 0.7 + strike_intensity * 0.3;
 
             // Filtered noise for cymbal texture
-            let mut rng = rand::rng();
-            let noise = (rng.random::<f32>() - 0.5) * 2.0;
+            let noise = random_bipolar();
             let high_freq_noise = noise * (1.0 - metallic * 0.3) * 0.3;
 
             let harmonic_sum = (harm1 + harm2 + harm3 + harm4 + harm5 + harm6) * shimmer_lfo;
@@ -419,6 +462,12 @@ impl FunDSPSynth {
         amplitude: f32,
         sample_count: usize,
     ) -> Result<Vec<f32>> {
+        // Sidebands reach about carrier + (index + 1) * modulator (Carson's rule), so
+        // shrink the index as that edge nears Nyquist instead of letting them fold back
+        let nyquist = self.sample_rate / 2.0;
+        let max_index = ((nyquist - carrier_freq) / modulator_freq.abs().max(1.0) - 1.0).max(0.0);
+        let modulation_index = modulation_index.min(max_index);
+
         let mut samples = Vec::with_capacity(sample_count);
 
         for i in 0..sample_count {
@@ -491,8 +540,7 @@ impl FunDSPSynth {
             let overtone3 = (2.0 * std::f32::consts::PI * current_freq * 3.7 * t).sin() * 0.4;
 
             // Aggressive noise component
-            let mut rng = rand::rng();
-            let aggressive_noise = (rng.random::<f32>() - 0.5) * 2.0;
+            let aggressive_noise = random_bipolar();
             let noise_envelope = (-t * 25.0 * energy).exp();
 
             let harmonic_content_factor = harmonic_content * 10.0;
@@ -535,8 +583,7 @@ impl FunDSPSynth {
             let sweep_freq = frequency_sweep.0 + (frequency_sweep.1 - frequency_sweep.0) * progress;
 
             // Filtered noise for swoosh character
-            let mut rng = rand::rng();
-            let noise = (rng.random::<f32>() - 0.5) * 2.0;
+            let noise = random_bipolar();
             // Simple bandpass approximation
             let filtered_noise = noise * (sweep_freq / 1000.0).min(1.0);
 
@@ -547,6 +594,90 @@ impl FunDSPSynth {
         Ok(samples)
     }
 
+    /// Band-limited oscillator morphing sine -> triangle -> sawtooth -> square.
+    ///
+    /// Each shape is built additively from its Fourier series, capped below Nyquist,
+    /// and adjacent shapes are crossfaded by blending their harmonic amplitudes.
+    #[allow(clippy::too_many_arguments)]
+    fn generate_morph_samples(
+        &self,
+        frequency: f32,
+        start_position: f32,
+        end_position: f32,
+        envelope: &EnvelopeParams,
+        amplitude: f32,
+        sample_count: usize,
+    ) -> Result<Vec<f32>> {
+        use std::f32::consts::PI;
+
+        let nyquist_limit = (self.sample_rate / 2.0 / frequency.max(1.0)) as usize;
+        let harmonic_count = nyquist_limit.clamp(1, MORPH_MAX_HARMONICS);
+
+        // Harmonic amplitudes (sine phase) for each of the four shapes
+        let shapes: [Vec<f32>; 4] = [
+            (1..=harmonic_count)
+                .map(|n| if n == 1 { 1.0 } else { 0.0 })
+                .collect(),
+            (1..=harmonic_count)
+                .map(|n| {
+                    if n % 2 == 0 {
+                        0.0
+                    } else {
+                        let sign = if (n / 2) % 2 == 0 { 1.0 } else { -1.0 };
+                        sign * 8.0 / (PI * PI * (n * n) as f32)
+                    }
+                })
+                .collect(),
+            (1..=harmonic_count)
+                .map(|n| {
+                    let sign = if n % 2 == 1 { 1.0 } else { -1.0 };
+                    sign * 2.0 / (PI * n as f32)
+                })
+                .collect(),
+            (1..=harmonic_count)
+                .map(|n| {
+                    if n % 2 == 0 {
+                        0.0
+                    } else {
+                        4.0 / (PI * n as f32)
+                    }
+                })
+                .collect(),
+        ];
+
+        let duration = sample_count as f32 / self.sample_rate;
+        let mut samples = Vec::with_capacity(sample_count);
+
+        for i in 0..sample_count {
+            let t = i as f32 / self.sample_rate;
+            let progress = if sample_count > 1 {
+                i as f32 / (sample_count - 1) as f32
+            } else {
+                0.0
+            };
+            let position =
+                (start_position + (end_position - start_position) * progress).clamp(0.0, 1.0);
+
+            // Locate the pair of adjacent shapes and the crossfade between them
+            let scaled = position * 3.0;
+            let lower = (scaled.floor() as usize).min(2);
+            let blend = scaled - lower as f32;
+
+            let phase = 2.0 * PI * ((frequency * t) % 1.0);
+            let mut sample = 0.0;
+            for (index, (a, b)) in shapes[lower].iter().zip(&shapes[lower + 1]).enumerate() {
+                let harmonic_amplitude = a + (b - a) * blend;
+                if harmonic_amplitude != 0.0 {
+                    sample += harmonic_amplitude * (phase * (index + 1) as f32).sin();
+                }
+            }
+
+            samples.push(sample * envelope.level(t, duration) * amplitude);
+        }
+
+        Ok(samples)
+    }
+
     /// Pad synthesis for ambient textures
     #[allow(clippy::too_many_arguments)]
     fn generate_pad_samples(
@@ -632,8 +763,7 @@ impl FunDSPSynth {
             let sine_component =
                 (2.0 * std::f32::consts::PI * frequency * t).sin() * (1.0 - roughness);
 
-            let mut rng = rand::rng();
-            let noise_component = (rng.random::<f32>() - 0.5) * 2.0 * roughness;
+            let noise_component = random_bipolar() * roughness;
             // Simple lowpass filter
             let filtered_noise = noise_component * (frequency / 2000.0).min(1.0);
 
@@ -643,6 +773,61 @@ impl FunDSPSynth {
 
         Ok(samples)
     }
+
+    /// Drone synthesis for long evolving beds.
+    ///
+    /// Each harmonic swells and fades on its own slow cycle, at rates spread around
+    /// `evolution_rate`, so the harmonic balance keeps shifting without any one partial
+    /// dropping out. Every harmonic is a pair of oscillators detuned by `detune` cents.
+    #[allow(clippy::too_many_arguments)]
+    fn generate_drone_samples(
+        &self,
+        frequency: f32,
+        overtone_spread: f32,
+        modulation: f32,
+        harmonics: u8,
+        evolution_rate: f32,
+        detune: f32,
+        envelope: &EnvelopeParams,
+        amplitude: f32,
+        sample_count: usize,
+    ) -> Vec<f32> {
+        let duration = sample_count as f32 / self.sample_rate;
+        let harmonics = harmonics.clamp(1, 16) as usize;
+        let detune_ratio = 2f32.powf(detune / 1200.0);
+        let partials: Vec<(f32, f32, f32)> = (1..=harmonics)
+            .map(|harmonic| {
+                let n = harmonic as f32;
+                let partial_freq = frequency * n * (1.0 + overtone_spread * 0.01 * (n - 1.0));
+                // Partial frequency, level, and its own evolution rate so the partials drift apart
+                (
+                    partial_freq,
+                    1.0 / n,
+                    evolution_rate * (1.0 + 0.29 * (n - 1.0)),
+                )
+            })
+            .collect();
+        let normalization = 1.0 / partials.iter().map(|&(_, level, _)| level).sum::<f32>();
+
+        (0..sample_count)
+            .map(|i| {
+                let t = i as f32 / self.sample_rate;
+                let mut sample = 0.0;
+                for (harmonic, &(partial_freq, level, rate)) in partials.iter().enumerate() {
+                    let weight = 0.55
+                        + 0.45
+                            * (2.0 * std::f32::consts::PI * rate * t + 1.7 * harmonic as f32).sin();
+                    let phase = 2.0 * std::f32::consts::PI * partial_freq * t;
+                    let pair = 0.5 * ((phase * detune_ratio).sin() + (phase / detune_ratio).sin());
+                    sample += pair * level * weight;
+                }
+
+                // Very slow amplitude breathing for an organic feel
+                let breath = 1.0 + (2.0 * std::f32::consts::PI * 0.1 * t).sin() * 0.1 * modulation;
+                sample * normalization * breath * envelope.level(t, duration) * amplitude
+            })
+            .collect()
+    }
 }
 
 /// Convert from main SynthParams to FunDSP parameters
@@ -689,6 +874,13 @@ impl From<crate::expressive::synth::SynthParams> for FunDSPParams {
                 metallic,
                 strike_intensity,
             },
+            crate::expressive::synth::SynthType::Morph {
+                position,
+                end_position,
+            } => FunDSPSynthType::Morph {
+                position,
+                end_position,
+            },
             crate::expressive::synth::SynthType::FM {
                 modulator_freq,
                 modulation_index,
@@ -745,6 +937,20 @@ impl From<crate::expressive::synth::SynthParams> for FunDSPParams {
                 spectral_tilt,
                 modulation_depth,
             },
+            crate::expressive::synth::SynthType::Drone {
+                fundamental: _,
+                overtone_spread,
+                modulation,
+                harmonics,
+                evolution_rate,
+                detune,
+            } => FunDSPSynthType::Drone {
+                overtone_spread,
+                modulation,
+                harmonics,
+                evolution_rate,
+                detune,
+            },
             // For other synthesis types, fall back to simple implementations
             _ => {
                 // This shouldn't happen since we only route appropriate types to FunDSP
@@ -762,6 +968,144 @@ impl From<crate::expressive::synth::SynthParams> for FunDSPParams {
             frequency: params.frequency,
             amplitude: params.amplitude,
             duration: params.duration,
+            envelope: params.envelope,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressive::synth::EnvelopeCurve;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+    // 441 Hz gives exactly 100 samples per period
+    const FREQUENCY: f32 = 441.0;
+
+    fn render_morph(position: f32) -> Vec<f32> {
+        let synth = FunDSPSynth::new().unwrap();
+        let params = FunDSPParams {
+            synth_type: FunDSPSynthType::Morph {
+                position,
+                end_position: None,
+            },
+            frequency: FREQUENCY,
+            amplitude: 1.0,
+            duration: 0.1,
+            envelope: EnvelopeParams {
+                attack: 0.0,
+                decay: 0.0,
+                sustain: 1.0,
+                release: 0.0,
+                curve: EnvelopeCurve::Linear,
+            },
+        };
+        synth.generate_samples(&params).unwrap()
+    }
+
+    /// Magnitude of the n-th harmonic over a whole number of periods
+    fn harmonic_magnitude(samples: &[f32], harmonic: usize) -> f32 {
+        let period = (SAMPLE_RATE / FREQUENCY) as usize;
+        let samples = &samples[..samples.len() / period * period];
+        let omega = 2.0 * std::f64::consts::PI * (FREQUENCY * harmonic as f32 / SAMPLE_RATE) as f64;
+        let (re, im) = samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (i, &x)| {
+                let angle = omega * i as f64;
+                (re + x as f64 * angle.cos(), im + x as f64 * angle.sin())
+            });
+        (2.0 * (re * re + im * im).sqrt() / samples.len() as f64) as f32
+    }
+
+    fn third_harmonic_ratio(samples: &[f32]) -> f32 {
+        harmonic_magnitude(samples, 3) / harmonic_magnitude(samples, 1)
+    }
+
+    #[test]
+    fn test_morph_spans_sine_to_square() {
+        // Position 0 is a pure sine
+        let sine = render_morph(0.0);
+        for (i, &sample) in sine.iter().enumerate() {
+            let expected = (2.0 * std::f32::consts::PI * FREQUENCY * i as f32 / SAMPLE_RATE).sin();
+            assert!((sample - expected).abs() < 1e-3, "sample {} differs", i);
+        }
+
+        // Position 1 has the square's odd-harmonic 1/n series
+        let square = render_morph(1.0);
+        let fundamental = harmonic_magnitude(&square, 1);
+        assert!((fundamental - 4.0 / std::f32::consts::PI).abs() < 1e-2);
+        for harmonic in [3, 5, 7] {
+            let ratio = harmonic_magnitude(&square, harmonic) / fundamental;
+            assert!((ratio - 1.0 / harmonic as f32).abs() < 1e-2);
+        }
+        for harmonic in [2, 4, 6] {
+            assert!(harmonic_magnitude(&square, harmonic) / fundamental < 1e-2);
         }
+
+        // Intermediate positions fall between sine and square
+        let square_ratio = third_harmonic_ratio(&square);
+        let mut previous = third_harmonic_ratio(&sine);
+        assert!(previous < 1e-3);
+        for position in [0.25, 0.5] {
+            let ratio = third_harmonic_ratio(&render_morph(position));
+            assert!(
+                ratio > previous && ratio < square_ratio,
+                "position {}",
+                position
+            );
+            previous = ratio;
+        }
+    }
+
+    fn render_drone(evolution_rate: f32) -> Vec<f32> {
+        let synth = FunDSPSynth::new().unwrap();
+        let params = FunDSPParams {
+            synth_type: FunDSPSynthType::Drone {
+                overtone_spread: 0.0,
+                modulation: 0.0,
+                harmonics: 6,
+                evolution_rate,
+                detune: 0.0,
+            },
+            frequency: FREQUENCY,
+            amplitude: 1.0,
+            duration: 10.0,
+            envelope: EnvelopeParams {
+                attack: 0.0,
+                decay: 0.0,
+                sustain: 1.0,
+                release: 0.0,
+                curve: EnvelopeCurve::Linear,
+            },
+        };
+        synth.generate_samples(&params).unwrap()
+    }
+
+    /// Second-to-first harmonic ratio over each second of a render
+    fn harmonic_balance_per_second(samples: &[f32]) -> Vec<f32> {
+        samples
+            .chunks(SAMPLE_RATE as usize)
+            .map(|second| harmonic_magnitude(second, 2) / harmonic_magnitude(second, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_drone_harmonic_balance_evolves_over_ten_seconds() {
+        let spread = |balance: Vec<f32>| {
+            let max = balance.iter().fold(f32::MIN, |m, &b| m.max(b));
+            let min = balance.iter().fold(f32::MAX, |m, &b| m.min(b));
+            max / min
+        };
+
+        let evolving = spread(harmonic_balance_per_second(&render_drone(0.1)));
+        assert!(evolving > 1.5, "balance only moved by {}x", evolving);
+
+        let static_drone = spread(harmonic_balance_per_second(&render_drone(0.0)));
+        assert!(
+            static_drone < 1.01,
+            "static drone moved by {}x",
+            static_drone
+        );
     }
 }
@@ -3,6 +3,7 @@ pub mod fundsp_effects;
 pub mod fundsp_synth;
 pub mod presets;
 pub mod r2d2;
+pub mod seed;
 pub mod synth;
 pub mod voice;
 
@@ -10,5 +11,6 @@ pub use effects_presets::*;
 pub use fundsp_effects::*;
 pub use presets::*;
 pub use r2d2::*;
+pub use seed::*;
 pub use synth::*;
 pub use voice::*;
@@ -56,13 +56,16 @@ impl PresetLibrary {
                 frequency: 110.0, // Will be overridden by note frequency
                 amplitude: 0.8,
                 duration: 1.0, // Will be overridden
-                envelope: PresetLibrary::create_envelope(0.01, 0.3, 0.7, 0.5),
+                phase: 0.0,
+                envelope: PresetLibrary::create_analog_envelope(0.01, 0.3, 0.7, 0.5),
                 filter: Some(PresetLibrary::create_filter(
                     700.0, // Lower cutoff for classic bass filtering
                     0.05,  // Near-zero resonance for authentic clean bass
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.15)],
+                vibrato: None,
+                tremolo: None,
             },
             variations,
             signature_effects: PresetLibrary::create_signature_effects_for_bass(),
@@ -99,10 +102,14 @@ impl PresetLibrary {
                 "resonant".to_string(),
             ],
             synth_params: SynthParams {
-                synth_type: SynthType::Square { pulse_width: 0.5 },
+                synth_type: SynthType::Square {
+                    pulse_width: 0.5,
+                    pwm: None,
+                },
                 frequency: 110.0,
                 amplitude: 0.75,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.005, 0.2, 0.4, 0.3),
                 filter: Some(PresetLibrary::create_filter(
                     700.0,
@@ -110,6 +117,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.1)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: tb303_variations,
             signature_effects: PresetLibrary::create_signature_effects_for_acid_bass(),
@@ -133,6 +142,7 @@ impl PresetLibrary {
                 frequency: 110.0,
                 amplitude: 0.85,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.005, 0.15, 0.6, 0.4),
                 filter: Some(PresetLibrary::create_filter(
                     900.0,
@@ -140,6 +150,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.08)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_vintage_warmth(),
@@ -164,6 +176,7 @@ impl PresetLibrary {
                 frequency: 110.0,
                 amplitude: 0.8,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.02, 0.4, 0.8, 0.6),
                 filter: Some(PresetLibrary::create_filter(
                     750.0, // Slightly higher cutoff for Jupiter-8 character
@@ -174,6 +187,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.3),
                     PresetLibrary::create_reverb(0.2),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -204,6 +219,7 @@ impl PresetLibrary {
                 frequency: 110.0,
                 amplitude: 0.85,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.005, 0.2, 0.6, 0.4),
                 filter: Some(PresetLibrary::create_filter(
                     1000.0,
@@ -211,6 +227,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.12)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -259,13 +277,16 @@ impl PresetLibrary {
                 frequency: 55.0, // Bass frequency
                 amplitude: 0.8,  // Standardized bass level
                 duration: 1.2,
-                envelope: PresetLibrary::create_envelope(0.001, 0.06, 0.6, 4.0), // Extended release for smooth note transitions
+                phase: 0.0,
+                envelope: PresetLibrary::create_analog_envelope(0.001, 0.06, 0.6, 4.0), // Extended release for smooth note transitions
                 filter: Some(PresetLibrary::create_filter(
                     1800.0, // Slightly lower cutoff to emphasize fundamental
                     0.05,   // Very minimal resonance for clean bass
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.05)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -291,6 +312,7 @@ impl PresetLibrary {
                 frequency: 110.0,
                 amplitude: 0.8,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.01, 0.25, 0.65, 0.45),
                 filter: Some(PresetLibrary::create_filter(
                     850.0,
@@ -298,6 +320,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.15)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -327,10 +351,14 @@ impl PresetLibrary {
                 "clean".to_string(),
             ],
             synth_params: SynthParams {
-                synth_type: SynthType::Square { pulse_width: 0.6 },
+                synth_type: SynthType::Square {
+                    pulse_width: 0.6,
+                    pwm: None,
+                },
                 frequency: 110.0,
                 amplitude: 0.75,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.01, 0.3, 0.7, 0.5),
                 filter: Some(PresetLibrary::create_filter(
                     900.0,
@@ -338,6 +366,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.12)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: square_variations,
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -364,6 +394,7 @@ impl PresetLibrary {
                 frequency: 55.0, // Sub-bass frequency
                 amplitude: 0.9,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.02, 0.1, 0.9, 0.8),
                 filter: Some(PresetLibrary::create_filter(
                     120.0,
@@ -371,6 +402,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.05)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -394,6 +427,7 @@ impl PresetLibrary {
                 frequency: 110.0,
                 amplitude: 0.8,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.08, 0.4, 0.5, 0.6), // Slower attack for "bouncy" feel
                 filter: Some(PresetLibrary::create_filter(
                     600.0,
@@ -404,6 +438,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.4),
                     PresetLibrary::create_reverb(0.18),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_modern_clarity(),
@@ -28,6 +28,7 @@ impl PresetLibrary {
                 frequency: 60.0,
                 amplitude: 0.9,
                 duration: 1.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.005, 0.1, 0.3, 0.6),
                 filter: Some(PresetLibrary::create_filter(
                     120.0,
@@ -35,6 +36,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::LowPass,
                 )),
                 effects: vec![],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -63,6 +66,7 @@ impl PresetLibrary {
                 frequency: 200.0,
                 amplitude: 0.8,
                 duration: 0.3,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.001, 0.05, 0.2, 0.15),
                 filter: Some(PresetLibrary::create_filter(
                     2000.0,
@@ -70,6 +74,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::HighPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.1)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -97,6 +103,7 @@ impl PresetLibrary {
                 frequency: 8000.0,
                 amplitude: 0.6,
                 duration: 0.15, // Very short
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.001, 0.01, 0.1, 0.1),
                 filter: Some(PresetLibrary::create_filter(
                     12000.0, // High-pass for hi-hat character
@@ -104,6 +111,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::HighPass,
                 )),
                 effects: vec![],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -131,6 +140,7 @@ impl PresetLibrary {
                 frequency: 3000.0,
                 amplitude: 0.7,
                 duration: 2.0, // Long decay like real cymbal
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.001, 0.2, 0.4, 1.5),
                 filter: Some(PresetLibrary::create_filter(
                     8000.0,
@@ -138,6 +148,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::HighPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.3)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -165,6 +177,7 @@ impl PresetLibrary {
                 frequency: 9000.0,
                 amplitude: 0.5,
                 duration: 0.08, // Extremely short
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.001, 0.005, 0.05, 0.05),
                 filter: Some(PresetLibrary::create_filter(
                     15000.0,
@@ -172,6 +185,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::HighPass,
                 )),
                 effects: vec![],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -27,9 +27,12 @@ impl PresetLibrary {
                 frequency: 800.0,
                 amplitude: 0.8,
                 duration: 0.4,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.001, 0.05, 0.3, 0.2),
                 filter: None,
                 effects: vec![PresetLibrary::create_reverb(0.2)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -57,6 +60,7 @@ impl PresetLibrary {
                 frequency: 200.0,
                 amplitude: 0.8, // Standardized effect level
                 duration: 2.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.1, 0.5, 0.8, 1.0),
                 filter: Some(PresetLibrary::create_filter(
                     1500.0,
@@ -64,6 +68,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.4)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_empty_signature_effects(),
@@ -54,13 +54,16 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.8, // Standardized level for proper audibility
                 duration: 3.0,
-                envelope: PresetLibrary::create_envelope(0.005, 0.3, 0.6, 3.0), // Even longer release to prevent cut-off
+                phase: 0.0,
+                envelope: PresetLibrary::create_analog_envelope(0.005, 0.3, 0.6, 3.0), // Even longer release to prevent cut-off
                 filter: Some(PresetLibrary::create_filter(
                     2800.0, // Lower cutoff for warmer, less bell-like tone
                     0.02,   // Very minimal resonance for clean piano sound
                     crate::expressive::FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.15)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_keys(),
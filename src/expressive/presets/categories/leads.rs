@@ -22,6 +22,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.8,
                 duration: 2.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.05, 0.3, 0.6, 0.5),
                 filter: Some(PresetLibrary::create_filter(
                     1200.0,
@@ -29,6 +30,8 @@ impl PresetLibrary {
                     crate::expressive::FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.25)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_lead(),
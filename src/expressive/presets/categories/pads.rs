@@ -1,7 +1,7 @@
 use crate::expressive::presets::{
     ClassicSynthPreset, PresetCategory, PresetLibrary, PresetVariation,
 };
-use crate::expressive::{FilterType, SynthParams, SynthType};
+use crate::expressive::{FilterType, Pwm, SynthParams, SynthType};
 use std::collections::HashMap;
 
 impl PresetLibrary {
@@ -44,6 +44,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.75, // Increased for better audibility
                 duration: 4.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.8, 0.3, 0.8, 1.5), // Slow attack for pad character
                 filter: Some(PresetLibrary::create_filter(
                     1200.0,
@@ -54,6 +55,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.5),
                     PresetLibrary::create_reverb(0.4),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: jp8_variations,
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -82,6 +85,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.75, // Standardized pad level
                 duration: 4.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.5, 0.4, 0.7, 1.2),
                 filter: Some(PresetLibrary::create_filter(
                     900.0, // Slightly warmer filter for Oberheim character
@@ -92,6 +96,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.3),
                     PresetLibrary::create_reverb(0.3),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -120,6 +126,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.75, // Increased for better presence
                 duration: 8.0,   // Long pad duration
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(1.2, 0.8, 0.85, 2.0),
                 filter: Some(PresetLibrary::create_filter(
                     900.0,
@@ -130,6 +137,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.4),
                     PresetLibrary::create_reverb(0.6),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -163,6 +172,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.75, // Standardized level
                 duration: 6.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.3, 0.8, 0.8, 1.8), // Quick attack then evolving
                 filter: Some(PresetLibrary::create_filter(
                     1500.0,
@@ -173,6 +183,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.6),
                     PresetLibrary::create_reverb(0.7),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -196,6 +208,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.75, // Standardized level
                 duration: 5.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(0.6, 0.2, 0.9, 1.5),
                 filter: Some(PresetLibrary::create_filter(
                     2000.0,
@@ -206,6 +219,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.3),
                     PresetLibrary::create_reverb(0.5),
                 ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -236,6 +251,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.7, // Atmospheric but still present
                 duration: 10.0, // Very long atmospheric pad
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(2.0, 1.0, 0.9, 3.0), // Very slow attack
                 filter: Some(PresetLibrary::create_filter(
                     800.0,
@@ -243,6 +259,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )),
                 effects: vec![PresetLibrary::create_reverb(0.8)], // Heavy reverb for space
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -266,6 +284,7 @@ impl PresetLibrary {
                 frequency: 220.0, // Lower frequency for darkness
                 amplitude: 0.75,  // Standardized level
                 duration: 6.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(1.5, 0.8, 0.7, 2.5),
                 filter: Some(PresetLibrary::create_filter(
                     400.0,
@@ -273,6 +292,8 @@ impl PresetLibrary {
                     FilterType::LowPass,
                 )), // Dark filter
                 effects: vec![PresetLibrary::create_reverb(0.6)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -297,6 +318,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.75, // Standardized level
                 duration: 8.0,
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(1.0, 0.6, 0.8, 2.0),
                 filter: Some(PresetLibrary::create_filter(
                     1100.0,
@@ -307,6 +329,8 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.5),
                     PresetLibrary::create_reverb(0.7),
                 ], // Choir-like effects
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -332,6 +356,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.65, // Softer for wind character but still audible
                 duration: 12.0,  // Very long for ambience
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(3.0, 2.0, 0.6, 4.0), // Very slow development
                 filter: Some(PresetLibrary::create_filter(
                     600.0,
@@ -339,6 +364,8 @@ impl PresetLibrary {
                     FilterType::BandPass,
                 )), // Shaped noise
                 effects: vec![PresetLibrary::create_reverb(0.8)],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
@@ -368,6 +395,7 @@ impl PresetLibrary {
                 frequency: 440.0,
                 amplitude: 0.7, // Dreamy but audible
                 duration: 15.0, // Very long dreamy pad
+                phase: 0.0,
                 envelope: PresetLibrary::create_envelope(4.0, 3.0, 0.8, 5.0), // Extremely slow
                 filter: Some(PresetLibrary::create_filter(
                     1000.0,
@@ -378,6 +406,51 @@ impl PresetLibrary {
                     PresetLibrary::create_chorus(0.7),
                     PresetLibrary::create_reverb(0.9),
                 ], // Maximum dreaminess
+                vibrato: None,
+                tremolo: None,
+            },
+            variations: HashMap::new(),
+            signature_effects: PresetLibrary::create_signature_effects_for_pad(),
+        });
+
+        // 11. PWM Strings - Pulse-width modulated string machine
+        self.add_preset(ClassicSynthPreset {
+            name: "PWM Strings".to_string(),
+            category: PresetCategory::Pad,
+            subcategory: "Warm Analog".to_string(),
+            description: "Slowly swept pulse wave strings with a built-in ensemble shimmer"
+                .to_string(),
+            inspiration: "Roland Juno-60".to_string(),
+            tags: vec![
+                "strings".to_string(),
+                "pwm".to_string(),
+                "analog".to_string(),
+                "vintage".to_string(),
+            ],
+            synth_params: SynthParams {
+                synth_type: SynthType::Square {
+                    pulse_width: 0.5,
+                    pwm: Some(Pwm {
+                        rate: 0.8,
+                        depth: 0.3,
+                    }),
+                },
+                frequency: 440.0,
+                amplitude: 0.7,
+                duration: 4.0,
+                phase: 0.0,
+                envelope: PresetLibrary::create_envelope(0.6, 0.4, 0.8, 1.2),
+                filter: Some(PresetLibrary::create_filter(
+                    2500.0,
+                    0.15,
+                    FilterType::LowPass,
+                )),
+                effects: vec![
+                    PresetLibrary::create_chorus(0.4),
+                    PresetLibrary::create_reverb(0.4),
+                ],
+                vibrato: None,
+                tremolo: None,
             },
             variations: HashMap::new(),
             signature_effects: PresetLibrary::create_signature_effects_for_pad(),
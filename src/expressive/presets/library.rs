@@ -1,11 +1,22 @@
+use crate::expressive::seed::with_rng;
 use crate::expressive::{
-    EffectParams, EffectType, EnvelopeParams, FilterParams, FilterType, SynthParams,
+    DEFAULT_FILTER_SOFT_START, EffectParams, EffectType, EnvelopeCurve, EnvelopeParams,
+    FilterParams, FilterType, SynthParams, SynthType,
 };
 use crate::midi::EffectConfig;
 use rand::prelude::IndexedRandom;
-use rand::rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    /// Presets added with `define_preset`, keyed by lowercase name. They last for the
+    /// server session and every `PresetLibrary` loads them over the built-ins.
+    static ref USER_PRESETS: Mutex<HashMap<String, ClassicSynthPreset>> =
+        Mutex::new(HashMap::new());
+}
 
 /// Classic synthesizer preset inspired by vintage hardware
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +46,48 @@ pub enum PresetCategory {
     Effects,
 }
 
+impl PresetCategory {
+    /// Every category, in the order the library loads them
+    pub const ALL: [PresetCategory; 8] = [
+        PresetCategory::Bass,
+        PresetCategory::Pad,
+        PresetCategory::Lead,
+        PresetCategory::Keys,
+        PresetCategory::Organ,
+        PresetCategory::Arp,
+        PresetCategory::Drums,
+        PresetCategory::Effects,
+    ];
+
+    /// Name used for `preset_category` on notes
+    pub fn name(&self) -> &'static str {
+        match self {
+            PresetCategory::Bass => "bass",
+            PresetCategory::Pad => "pad",
+            PresetCategory::Lead => "lead",
+            PresetCategory::Keys => "keys",
+            PresetCategory::Organ => "organ",
+            PresetCategory::Arp => "arp",
+            PresetCategory::Drums => "drums",
+            PresetCategory::Effects => "effects",
+        }
+    }
+
+    /// Category for a `preset_category` name, ignoring case
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown preset category '{}'. Valid categories: {}",
+                    name,
+                    Self::ALL.map(|category| category.name()).join(", ")
+                )
+            })
+    }
+}
+
 /// Preset variations allow slight modifications to base presets
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresetVariation {
@@ -69,9 +122,108 @@ impl PresetLibrary {
         library.load_drum_presets();
         library.load_effects_presets();
 
+        // User presets last, so one named like a built-in replaces it
+        let user_presets: Vec<ClassicSynthPreset> = USER_PRESETS
+            .lock()
+            .map(|presets| presets.values().cloned().collect())
+            .unwrap_or_default();
+        for preset in user_presets {
+            library.insert_user_preset(preset);
+        }
+
         library
     }
 
+    /// Store a user preset for the rest of the session. It takes precedence over a built-in
+    /// preset with the same name (ignoring case) and replaces an earlier user preset of
+    /// that name.
+    pub fn define_user_preset(preset: ClassicSynthPreset) -> Result<(), String> {
+        Self::validate_user_preset(&preset)?;
+        let mut presets = USER_PRESETS
+            .lock()
+            .map_err(|_| "User preset store is unavailable".to_string())?;
+        presets.insert(preset.name.to_lowercase(), preset);
+        Ok(())
+    }
+
+    /// Check a user preset's name and synthesis parameters against the ranges notes accept
+    fn validate_user_preset(preset: &ClassicSynthPreset) -> Result<(), String> {
+        if preset.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        let params = &preset.synth_params;
+        if !(0.0..=1.0).contains(&params.amplitude) {
+            return Err(format!(
+                "Preset amplitude {} is out of range (0.0-1.0)",
+                params.amplitude
+            ));
+        }
+
+        let envelope = &params.envelope;
+        for (label, value, max) in [
+            ("attack", envelope.attack, 5.0),
+            ("decay", envelope.decay, 5.0),
+            ("release", envelope.release, 10.0),
+        ] {
+            if !(0.0..=max).contains(&value) {
+                return Err(format!(
+                    "Preset envelope {} {} is out of range (0.0-{} seconds)",
+                    label, value, max
+                ));
+            }
+        }
+        if !(0.0..=1.0).contains(&envelope.sustain) {
+            return Err(format!(
+                "Preset envelope sustain {} is out of range (0.0-1.0)",
+                envelope.sustain
+            ));
+        }
+
+        if let Some(filter) = &params.filter {
+            if !(20.0..=20000.0).contains(&filter.cutoff) {
+                return Err(format!(
+                    "Preset filter cutoff {} is out of range (20-20000 Hz)",
+                    filter.cutoff
+                ));
+            }
+            if !(0.0..=1.0).contains(&filter.resonance) {
+                return Err(format!(
+                    "Preset filter resonance {} is out of range (0.0-1.0)",
+                    filter.resonance
+                ));
+            }
+        }
+
+        for effect in &params.effects {
+            if !(0.0..=1.0).contains(&effect.intensity) {
+                return Err(format!(
+                    "Preset effect intensity {} is out of range (0.0-1.0)",
+                    effect.intensity
+                ));
+            }
+            if let EffectType::Delay { delay_time } = effect.effect_type
+                && !(0.01..=2.0).contains(&delay_time)
+            {
+                return Err(format!(
+                    "Preset delay time {} is out of range (0.01-2.0 seconds)",
+                    delay_time
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a user preset, replacing any preset whose name matches case-insensitively
+    fn insert_user_preset(&mut self, preset: ClassicSynthPreset) {
+        let name_lower = preset.name.to_lowercase();
+        let same_name = |existing: &String| existing.to_lowercase() == name_lower;
+        self.presets.retain(|existing, _| !same_name(existing));
+        for names in self.categories.values_mut().chain(self.tags.values_mut()) {
+            names.retain(|existing| !same_name(existing));
+        }
+        self.add_preset(preset);
+    }
+
     /// Load a preset by name with fuzzy matching
     pub fn load_preset(&self, name: &str) -> Option<&ClassicSynthPreset> {
         // First try exact match
@@ -152,15 +304,32 @@ impl PresetLibrary {
         &self,
         category: Option<PresetCategory>,
     ) -> Option<&ClassicSynthPreset> {
-        let mut rng = rng();
-
-        if let Some(cat) = category {
-            let presets = self.get_by_category(cat);
-            presets.choose(&mut rng).copied()
+        let mut presets = if let Some(cat) = category {
+            self.get_by_category(cat)
         } else {
-            let all_presets: Vec<&ClassicSynthPreset> = self.presets.values().collect();
-            all_presets.choose(&mut rng).copied()
-        }
+            self.presets.values().collect()
+        };
+
+        // HashMap order varies between runs, so sort before drawing for seeded reproducibility
+        presets.sort_by(|a, b| a.name.cmp(&b.name));
+        with_rng(|rng| presets.choose(rng).copied())
+    }
+
+    /// Presets of one category, or of every category in library order, sorted by name
+    /// within each category
+    pub fn list_presets(&self, category: Option<PresetCategory>) -> Vec<&ClassicSynthPreset> {
+        let categories = match category {
+            Some(category) => vec![category],
+            None => PresetCategory::ALL.to_vec(),
+        };
+        categories
+            .into_iter()
+            .flat_map(|category| {
+                let mut presets = self.get_by_category(category);
+                presets.sort_by(|a, b| a.name.cmp(&b.name));
+                presets
+            })
+            .collect()
     }
 
     /// List all available preset names
@@ -175,6 +344,47 @@ impl PresetLibrary {
         self.categories.get(&category).cloned().unwrap_or_default()
     }
 
+    /// Up to three preset names closest to `name` (ignoring case), best match first,
+    /// for suggesting what was meant when a lookup fails
+    pub fn similar_preset_names(&self, name: &str) -> Vec<String> {
+        let wanted = name.to_lowercase();
+        let mut scored: Vec<(usize, &String)> = self
+            .presets
+            .keys()
+            .filter_map(|candidate| {
+                let lowered = candidate.to_lowercase();
+                let distance = if lowered.contains(&wanted) || wanted.contains(&lowered) {
+                    0
+                } else {
+                    edit_distance(&wanted, &lowered)
+                };
+                (distance <= (lowered.len() / 3).max(2)).then_some((distance, candidate))
+            })
+            .collect();
+        scored.sort();
+        scored
+            .into_iter()
+            .take(3)
+            .map(|(_, candidate)| candidate.clone())
+            .collect()
+    }
+
+    /// List the variation names `apply_variation` accepts for a preset, with their descriptions
+    pub fn list_variations(&self, preset_name: &str) -> Result<Vec<(String, String)>, String> {
+        let preset = self
+            .presets
+            .get(preset_name)
+            .ok_or_else(|| format!("Unknown preset '{}'", preset_name))?;
+
+        let mut variations: Vec<(String, String)> = preset
+            .variations
+            .iter()
+            .map(|(name, variation)| (name.clone(), variation.description.clone()))
+            .collect();
+        variations.sort();
+        Ok(variations)
+    }
+
     /// Apply a preset variation to get modified parameters
     pub fn apply_variation(&self, preset_name: &str, variation_name: &str) -> Option<SynthParams> {
         if let Some(preset) = self.presets.get(preset_name)
@@ -209,6 +419,135 @@ impl PresetLibrary {
         None
     }
 
+    /// Jitter a preset's envelope, filter and effect parameters for sound-design exploration.
+    ///
+    /// `amount` (0.0-1.0) scales how far each parameter may move: at full amount times
+    /// swing up to an octave either way, the cutoff two octaves, and levels by 0.3.
+    /// The same seed and amount always give the same result; amount 0 returns the base.
+    pub fn randomize(&self, base: &str, amount: f32, seed: u64) -> Option<SynthParams> {
+        let mut params = self.presets.get(base)?.synth_params.clone();
+        let amount = amount.clamp(0.0, 1.0);
+        if amount == 0.0 {
+            return Some(params);
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut jitter = || rng.random_range(-1.0f32..=1.0) * amount;
+        let scale_time = |time: f32, jitter: f32| (time * 2f32.powf(jitter)).clamp(0.001, 10.0);
+
+        let envelope = &mut params.envelope;
+        envelope.attack = scale_time(envelope.attack, jitter());
+        envelope.decay = scale_time(envelope.decay, jitter());
+        envelope.sustain = (envelope.sustain + 0.3 * jitter()).clamp(0.0, 1.0);
+        envelope.release = scale_time(envelope.release, jitter());
+
+        if let Some(filter) = &mut params.filter {
+            filter.cutoff = (filter.cutoff * 2f32.powf(2.0 * jitter())).clamp(20.0, 18000.0);
+            filter.resonance = (filter.resonance + 0.3 * jitter()).clamp(0.0, 0.95);
+        }
+
+        for effect in &mut params.effects {
+            effect.intensity = (effect.intensity + 0.3 * jitter()).clamp(0.0, 1.0);
+            if let EffectType::Delay { delay_time } = &mut effect.effect_type {
+                *delay_time = (*delay_time * 2f32.powf(0.5 * jitter())).clamp(0.01, 2.0);
+            }
+        }
+
+        Some(params)
+    }
+
+    /// Blend of presets `a` and `b`, `t` of the way from `a` to `b` (clamped to 0.0-1.0).
+    /// Amplitude, envelope times, filter cutoff and resonance, effect intensities and the
+    /// synth type's own numbers are interpolated linearly. Anything that cannot be blended
+    /// (the synth type itself, envelope curve, a filter or effect only one side has) comes
+    /// from whichever preset `t` is nearer, so two different kinds of synthesis switch over
+    /// at the midpoint rather than failing.
+    pub fn morph(&self, a: &str, b: &str, t: f32) -> Option<SynthParams> {
+        let from = &self.load_preset(a)?.synth_params;
+        let to = &self.load_preset(b)?.synth_params;
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |x: f32, y: f32| x + (y - x) * t;
+        let mut params = if t < 0.5 { from.clone() } else { to.clone() };
+
+        params.amplitude = lerp(from.amplitude, to.amplitude);
+        let envelope = &mut params.envelope;
+        envelope.attack = lerp(from.envelope.attack, to.envelope.attack);
+        envelope.decay = lerp(from.envelope.decay, to.envelope.decay);
+        envelope.sustain = lerp(from.envelope.sustain, to.envelope.sustain);
+        envelope.release = lerp(from.envelope.release, to.envelope.release);
+
+        match (&mut params.synth_type, &from.synth_type, &to.synth_type) {
+            (
+                SynthType::Square { pulse_width, .. },
+                SynthType::Square { pulse_width: a, .. },
+                SynthType::Square { pulse_width: b, .. },
+            ) => *pulse_width = lerp(*a, *b),
+            (
+                SynthType::FM {
+                    modulator_freq,
+                    modulation_index,
+                },
+                SynthType::FM {
+                    modulator_freq: freq_a,
+                    modulation_index: index_a,
+                },
+                SynthType::FM {
+                    modulator_freq: freq_b,
+                    modulation_index: index_b,
+                },
+            ) => {
+                *modulator_freq = lerp(*freq_a, *freq_b);
+                *modulation_index = lerp(*index_a, *index_b);
+            }
+            (
+                SynthType::Morph { position, .. },
+                SynthType::Morph { position: a, .. },
+                SynthType::Morph { position: b, .. },
+            ) => *position = lerp(*a, *b),
+            _ => {}
+        }
+
+        if let (Some(filter), Some(a), Some(b)) = (&mut params.filter, &from.filter, &to.filter) {
+            filter.cutoff = lerp(a.cutoff, b.cutoff);
+            filter.resonance = lerp(a.resonance, b.resonance);
+            filter.soft_start = lerp(a.soft_start, b.soft_start);
+        }
+
+        // Effects both presets use blend; the rest follow the nearer preset
+        let kind = std::mem::discriminant::<EffectType>;
+        for effect in &mut params.effects {
+            let find = |effects: &[EffectParams]| {
+                effects
+                    .iter()
+                    .find(|other| kind(&other.effect_type) == kind(&effect.effect_type))
+                    .cloned()
+            };
+            if let (Some(a), Some(b)) = (find(&from.effects), find(&to.effects)) {
+                effect.intensity = lerp(a.intensity, b.intensity);
+                if let (
+                    EffectType::Delay { delay_time },
+                    EffectType::Delay { delay_time: time_a },
+                    EffectType::Delay { delay_time: time_b },
+                ) = (&mut effect.effect_type, a.effect_type, b.effect_type)
+                {
+                    *delay_time = lerp(time_a, time_b);
+                }
+            }
+        }
+
+        for (lfo, a, b) in [
+            (&mut params.vibrato, from.vibrato, to.vibrato),
+            (&mut params.tremolo, from.tremolo, to.tremolo),
+        ] {
+            if let (Some(lfo), Some(a), Some(b)) = (lfo, a, b) {
+                lfo.rate = lerp(a.rate, b.rate);
+                lfo.depth = lerp(a.depth, b.depth);
+            }
+        }
+
+        Some(params)
+    }
+
     /// Add a preset to the library
     pub(crate) fn add_preset(&mut self, preset: ClassicSynthPreset) {
         let name = preset.name.clone();
@@ -237,6 +576,20 @@ impl PresetLibrary {
             decay,
             sustain,
             release,
+            curve: EnvelopeCurve::Linear,
+        }
+    }
+
+    /// Helper to create an envelope with analog-style exponential segments
+    pub fn create_analog_envelope(
+        attack: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+    ) -> EnvelopeParams {
+        EnvelopeParams {
+            curve: EnvelopeCurve::Exponential,
+            ..Self::create_envelope(attack, decay, sustain, release)
         }
     }
 
@@ -246,6 +599,7 @@ impl PresetLibrary {
             cutoff,
             resonance,
             filter_type,
+            soft_start: DEFAULT_FILTER_SOFT_START,
         }
     }
 
@@ -272,9 +626,11 @@ impl PresetLibrary {
                 ratio: 3.0,
                 attack: 0.003,
                 release: 0.05,
+                parallel: 1.0,
             },
             intensity: 0.4,
             enabled: true,
+            wet_only: false,
         }]
     }
 
@@ -289,6 +645,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.6,
                 enabled: true,
+                wet_only: false,
             },
             EffectConfig {
                 effect: crate::midi::EffectType::Delay {
@@ -299,6 +656,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.3,
                 enabled: true,
+                wet_only: false,
             },
         ]
     }
@@ -313,6 +671,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.2,
                 enabled: true,
+                wet_only: false,
             },
             EffectConfig {
                 effect: crate::midi::EffectType::Compressor {
@@ -320,9 +679,11 @@ impl PresetLibrary {
                     ratio: 2.5,
                     attack: 0.01,
                     release: 0.08,
+                    parallel: 1.0,
                 },
                 intensity: 0.5,
                 enabled: true,
+                wet_only: false,
             },
         ]
     }
@@ -335,9 +696,11 @@ impl PresetLibrary {
                     ratio: 4.0,
                     attack: 0.001,
                     release: 0.03,
+                    parallel: 1.0,
                 },
                 intensity: 0.6,
                 enabled: true,
+                wet_only: false,
             },
             EffectConfig {
                 effect: crate::midi::EffectType::Filter {
@@ -348,6 +711,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.4,
                 enabled: true,
+                wet_only: false,
             },
         ]
     }
@@ -360,9 +724,12 @@ impl PresetLibrary {
                     dampening: 0.3,
                     wet_level: 0.4,
                     pre_delay: 0.04,
+                    modulation: 0.15,
+                    algorithm: crate::midi::ReverbAlgorithm::Schroeder,
                 },
                 intensity: 0.6,
                 enabled: true,
+                wet_only: false,
             },
             EffectConfig {
                 effect: crate::midi::EffectType::Chorus {
@@ -373,6 +740,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.5,
                 enabled: true,
+                wet_only: false,
             },
         ]
     }
@@ -388,6 +756,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.5,
                 enabled: true,
+                wet_only: false,
             },
             EffectConfig {
                 effect: crate::midi::EffectType::Chorus {
@@ -398,6 +767,7 @@ impl PresetLibrary {
                 },
                 intensity: 0.4,
                 enabled: true,
+                wet_only: false,
             },
         ]
     }
@@ -409,9 +779,12 @@ impl PresetLibrary {
                 dampening: 0.4,
                 wet_level: 0.2,
                 pre_delay: 0.02,
+                modulation: 0.15,
+                algorithm: crate::midi::ReverbAlgorithm::Schroeder,
             },
             intensity: 0.5,
             enabled: true,
+            wet_only: false,
         }]
     }
 
@@ -419,3 +792,185 @@ impl PresetLibrary {
         Vec::new()
     }
 }
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_preset(name: &str, category: PresetCategory) -> ClassicSynthPreset {
+        ClassicSynthPreset {
+            name: name.to_string(),
+            category,
+            subcategory: "user".to_string(),
+            description: String::new(),
+            inspiration: "User preset".to_string(),
+            tags: vec!["user".to_string()],
+            synth_params: SynthParams {
+                synth_type: crate::expressive::SynthType::Sawtooth,
+                frequency: 440.0,
+                amplitude: 0.6,
+                duration: 1.0,
+                phase: 0.0,
+                envelope: PresetLibrary::create_envelope(0.01, 0.2, 0.5, 0.3),
+                filter: Some(PresetLibrary::create_filter(
+                    600.0,
+                    0.4,
+                    FilterType::LowPass,
+                )),
+                effects: vec![PresetLibrary::create_chorus(0.3)],
+                vibrato: None,
+                tremolo: None,
+            },
+            variations: HashMap::new(),
+            signature_effects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_defined_user_preset_loads_in_every_library_and_bad_envelope_is_rejected() {
+        PresetLibrary::define_user_preset(user_preset("Session Wobble", PresetCategory::Bass))
+            .unwrap();
+
+        let library = PresetLibrary::new();
+        let preset = library.load_preset("session wobble").unwrap();
+        assert_eq!(preset.category, PresetCategory::Bass);
+        assert_eq!(preset.synth_params.filter.as_ref().unwrap().cutoff, 600.0);
+        assert!(
+            library
+                .list_category_presets(PresetCategory::Bass)
+                .contains(&"Session Wobble".to_string())
+        );
+
+        let mut loud = user_preset("Session Swell", PresetCategory::Pad);
+        loud.synth_params.envelope.sustain = 1.5;
+        let error = PresetLibrary::define_user_preset(loud).unwrap_err();
+        assert!(error.contains("sustain 1.5"), "{}", error);
+        assert!(PresetLibrary::new().load_preset("Session Swell").is_none());
+    }
+
+    #[test]
+    fn test_morph_interpolates_shared_numbers_and_switches_synth_type_at_the_midpoint() {
+        let library = PresetLibrary::new();
+        let params = |name: &str| library.load_preset(name).unwrap().synth_params.clone();
+        let as_json = |params: &SynthParams| serde_json::to_value(params).unwrap();
+        let (minimoog, jupiter) = (params("Minimoog Bass"), params("Jupiter Bass"));
+
+        let blend = library.morph("Minimoog Bass", "Jupiter Bass", 0.4).unwrap();
+        let expected = |a: f32, b: f32| a + (b - a) * 0.4;
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(close(
+            blend.envelope.attack,
+            expected(minimoog.envelope.attack, jupiter.envelope.attack)
+        ));
+        assert!(close(
+            blend.envelope.sustain,
+            expected(minimoog.envelope.sustain, jupiter.envelope.sustain)
+        ));
+        let cutoff = |params: &SynthParams| params.filter.as_ref().unwrap().cutoff;
+        assert!(close(
+            cutoff(&blend),
+            expected(cutoff(&minimoog), cutoff(&jupiter))
+        ));
+
+        // The amount is clamped to the two ends
+        let ends = |t: f32| as_json(&library.morph("Minimoog Bass", "Jupiter Bass", t).unwrap());
+        assert_eq!(ends(-1.0), as_json(&minimoog));
+        assert_eq!(ends(1.5), as_json(&jupiter));
+
+        // Subtractive and DX7 FM cannot blend, so the nearer preset's type wins
+        let piano = |t: f32| library.morph("Minimoog Bass", "DX7 E.Piano", t).unwrap();
+        assert!(matches!(piano(0.3).synth_type, SynthType::Sawtooth));
+        assert!(matches!(piano(0.7).synth_type, SynthType::DX7FM { .. }));
+
+        assert!(
+            library
+                .morph("Minimoog Bass", "Not A Preset", 0.5)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_user_preset_named_like_a_built_in_replaces_it() {
+        let mut library = PresetLibrary::new();
+        let count = library.list_preset_names().len();
+
+        library.insert_user_preset(user_preset("tb-303 acid", PresetCategory::Lead));
+
+        assert_eq!(
+            library.load_preset("TB-303 Acid").unwrap().name,
+            "tb-303 acid"
+        );
+        assert_eq!(library.list_preset_names().len(), count);
+        assert!(
+            !library
+                .list_category_presets(PresetCategory::Bass)
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case("TB-303 Acid"))
+        );
+        assert!(
+            library
+                .list_category_presets(PresetCategory::Lead)
+                .contains(&"tb-303 acid".to_string())
+        );
+    }
+
+    #[test]
+    fn test_list_variations_reports_squelchy_and_rejects_unknown_preset() {
+        let library = PresetLibrary::new();
+
+        let variations = library.list_variations("TB-303 Acid").unwrap();
+        assert!(variations.iter().any(|(name, _)| name == "squelchy"));
+        for (name, _) in &variations {
+            assert!(library.apply_variation("TB-303 Acid", name).is_some());
+        }
+
+        let error = library.list_variations("Not A Preset").unwrap_err();
+        assert!(error.contains("Unknown preset"), "{}", error);
+    }
+
+    #[test]
+    fn test_randomize_is_identity_at_zero_and_reproducible_per_seed() {
+        let library = PresetLibrary::new();
+        let base = library
+            .load_preset("TB-303 Acid")
+            .unwrap()
+            .synth_params
+            .clone();
+        let as_json = |params: &SynthParams| serde_json::to_value(params).unwrap();
+
+        let unchanged = library.randomize("TB-303 Acid", 0.0, 7).unwrap();
+        assert_eq!(as_json(&unchanged), as_json(&base));
+
+        let first = library.randomize("TB-303 Acid", 0.6, 7).unwrap();
+        let again = library.randomize("TB-303 Acid", 0.6, 7).unwrap();
+        let other_seed = library.randomize("TB-303 Acid", 0.6, 8).unwrap();
+        assert_eq!(as_json(&first), as_json(&again));
+        assert_ne!(as_json(&first), as_json(&base));
+        assert_ne!(as_json(&first), as_json(&other_seed));
+
+        // Jitter stays within the musical bounds for this amount
+        let (cutoff, varied_cutoff) = (
+            base.filter.as_ref().unwrap().cutoff,
+            first.filter.as_ref().unwrap().cutoff,
+        );
+        assert!((cutoff / 2.0f32.powf(1.2)..=cutoff * 2.0f32.powf(1.2)).contains(&varied_cutoff));
+        assert!((0.0..=1.0).contains(&first.envelope.sustain));
+
+        assert!(library.randomize("Not A Preset", 0.5, 7).is_none());
+    }
+}
@@ -28,6 +28,58 @@ pub struct EmotionParameters {
     pub attack_speed: f32,              // Envelope attack characteristic
 }
 
+/// Timbre of one droid, so different characters can share the emotion presets
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct R2D2VoiceCharacter {
+    /// Pitch center relative to the default voice (0.25-4.0, default: 1.0; 0.5 = an octave lower)
+    #[serde(default = "default_voice_ratio")]
+    pub pitch_center: f32,
+    /// Ring modulation against the plain carrier (0.0-1.0, default: 1.0 = fully ring-modulated)
+    #[serde(default = "default_voice_ratio")]
+    pub ring_mod_amount: f32,
+    /// Vocal formant position relative to the default (0.5-2.0, default: 1.0)
+    #[serde(default = "default_voice_ratio")]
+    pub formant_shift: f32,
+}
+
+fn default_voice_ratio() -> f32 {
+    1.0
+}
+
+impl Default for R2D2VoiceCharacter {
+    fn default() -> Self {
+        R2D2VoiceCharacter {
+            pitch_center: 1.0,
+            ring_mod_amount: 1.0,
+            formant_shift: 1.0,
+        }
+    }
+}
+
+impl R2D2VoiceCharacter {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.25..=4.0).contains(&self.pitch_center) {
+            return Err(format!(
+                "R2D2 voice pitch_center must be between 0.25 and 4.0, got {}",
+                self.pitch_center
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.ring_mod_amount) {
+            return Err(format!(
+                "R2D2 voice ring_mod_amount must be between 0.0 and 1.0, got {}",
+                self.ring_mod_amount
+            ));
+        }
+        if !(0.5..=2.0).contains(&self.formant_shift) {
+            return Err(format!(
+                "R2D2 voice formant_shift must be between 0.5 and 2.0, got {}",
+                self.formant_shift
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Complete R2D2 expression with all parameters
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -40,6 +92,7 @@ pub struct R2D2Expression {
     pub pitch_range: (f32, f32), // Hz range
     #[allow(dead_code)]
     pub context: Option<String>, // conversation context
+    pub voice: Option<R2D2VoiceCharacter>, // custom droid timbre; None = the classic voice
 }
 
 /// R2D2 voice generator with emotion-based synthesis
@@ -203,9 +256,11 @@ impl R2D2Voice {
     ) -> Option<R2D2SynthParams> {
         let emotion_params = self.get_emotion_params(&expression.emotion)?;
 
-        // Calculate base frequency from range and intensity
+        // Calculate base frequency from range and intensity, around the voice's pitch center
         let freq_range = emotion_params.carrier_freq_range.1 - emotion_params.carrier_freq_range.0;
-        let base_freq = emotion_params.carrier_freq_range.0 + freq_range * expression.intensity;
+        let pitch_center = expression.voice.map_or(1.0, |voice| voice.pitch_center);
+        let base_freq = (emotion_params.carrier_freq_range.0 + freq_range * expression.intensity)
+            * pitch_center;
 
         // Adjust duration based on emotion and complexity
         let duration = expression.duration
@@ -0,0 +1,51 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Master RNG for the sequence currently being rendered on this thread (None = unseeded)
+    static MASTER_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Installs a seeded master RNG for the current thread until dropped.
+///
+/// Every stochastic code path (preset selection, noise generators, etc.) draws from
+/// this RNG while the scope is alive, so a render with the same seed is reproducible.
+/// With no seed the scope is a no-op and randomness stays non-deterministic.
+pub struct MasterSeedScope {
+    previous: Option<Option<StdRng>>,
+}
+
+impl MasterSeedScope {
+    pub fn new(seed: Option<u64>) -> Self {
+        let previous = seed
+            .map(|seed| MASTER_RNG.with(|slot| slot.replace(Some(StdRng::seed_from_u64(seed)))));
+        Self { previous }
+    }
+}
+
+impl Drop for MasterSeedScope {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            MASTER_RNG.with(|slot| *slot.borrow_mut() = previous);
+        }
+    }
+}
+
+/// Run `f` with the master RNG if one is installed, otherwise with the thread RNG
+pub fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    MASTER_RNG.with(|slot| match slot.borrow_mut().as_mut() {
+        Some(rng) => f(rng),
+        None => f(&mut rand::rng()),
+    })
+}
+
+/// Uniform random value in [0, 1)
+pub fn random_f32() -> f32 {
+    with_rng(|rng| rng.random::<f32>())
+}
+
+/// Uniform random value in [-1, 1) for noise generators
+pub fn random_bipolar() -> f32 {
+    (random_f32() - 0.5) * 2.0
+}
@@ -1,14 +1,45 @@
+use crate::expressive::r2d2::R2D2VoiceCharacter;
+use crate::expressive::seed::{random_bipolar, random_f32};
+use crate::expressive::voice::FilterState;
 use anyhow::Result;
 use rodio::OutputStream;
-// Add rand for noise generation
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+/// Notes with more harmonics than this below Nyquist alias too quietly to matter,
+/// so saw and square oscillators only switch to additive synthesis above it
+const NAIVE_OSCILLATOR_HARMONICS: usize = 64;
+/// Band just below Nyquist, as a fraction of it, over which band-limited harmonics fade out
+const HARMONIC_ROLLOFF: f32 = 0.1;
+
+/// Vocal formant of a custom R2D2 voice before `formant_shift`
+const R2D2_FORMANT_HZ: f32 = 1200.0;
+/// Formant filter damping (1/Q); low enough to give the droid a resonant "throat"
+const R2D2_FORMANT_DAMPING: f32 = 0.5;
+/// Level of the formant resonance added to the voice
+const R2D2_FORMANT_MIX: f32 = 0.5;
+
+/// Seconds over which the synth filter's resonance and cutoff ramp up at voice start
+pub const DEFAULT_FILTER_SOFT_START: f32 = 0.005;
+/// Longest filter soft start a note can ask for, in seconds
+pub const MAX_FILTER_SOFT_START: f32 = 0.1;
+/// Fraction of the cutoff a voice's filter opens from during the soft start
+const FILTER_SOFT_START_CUTOFF: f32 = 0.25;
+/// Slowest and fastest pulse-width LFO rates in Hz
+pub const PWM_RATE_RANGE: std::ops::RangeInclusive<f32> = 0.05..=20.0;
+/// Deepest pulse-width sweep either side of the base width
+pub const MAX_PWM_DEPTH: f32 = 0.4;
+/// Narrowest pulse the sweep may reach; thinner pulses fade to silence
+const MIN_PWM_WIDTH: f32 = 0.05;
+/// Fastest vibrato and tremolo LFO rate in Hz
+pub const MAX_LFO_RATE: f32 = 20.0;
+/// Widest vibrato swing either side of the note, in semitones
+pub const MAX_VIBRATO_DEPTH: f32 = 2.0;
+
 /// Core expressive synthesizer for R2D2-style vocalizations
 /// Using a simpler approach with direct audio generation
 pub struct ExpressiveSynth {
     sample_rate: f32,
-    _stream: OutputStream,
+    _stream: Option<OutputStream>,
 }
 
 /// New synthesis parameters for general music synthesis
@@ -18,9 +49,18 @@ pub struct SynthParams {
     pub frequency: f32,
     pub amplitude: f32,
     pub duration: f32,
+    /// Starting point of the oscillator cycle (0.0-1.0) for the basic waveforms
+    #[serde(default)]
+    pub phase: f32,
     pub envelope: EnvelopeParams,
     pub filter: Option<FilterParams>,
     pub effects: Vec<EffectParams>,
+    /// Pitch LFO; its depth is in semitones either side of the note
+    #[serde(default)]
+    pub vibrato: Option<Lfo>,
+    /// Amplitude LFO; its depth is the fraction of the level it dips by
+    #[serde(default)]
+    pub tremolo: Option<Lfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,12 +69,21 @@ pub enum SynthType {
     Sine,
     Square {
         pulse_width: f32,
+        /// LFO sweeping the pulse width around `pulse_width`
+        #[serde(default)]
+        pwm: Option<Pwm>,
     },
     Sawtooth,
     Triangle,
     Noise {
         color: NoiseColor,
     },
+    /// Continuous blend sine -> triangle -> sawtooth -> square (0.0-1.0),
+    /// optionally swept to `end_position` over the note
+    Morph {
+        position: f32,
+        end_position: Option<f32>,
+    },
 
     // Advanced synthesis techniques
     FM {
@@ -121,24 +170,164 @@ pub enum SynthType {
         fundamental: f32,
         overtone_spread: f32,
         modulation: f32,
+        /// Number of harmonics in the drone (1-16)
+        harmonics: u8,
+        /// How fast the harmonic balance drifts, in Hz (0.0 = static)
+        evolution_rate: f32,
+        /// Detune between each harmonic's paired oscillators in cents
+        detune: f32,
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub enum NoiseColor {
+    /// Equal energy at every frequency
+    #[default]
     White,
-    #[allow(dead_code)]
+    /// Falls 3dB per octave: equal energy per octave, like rain or surf
     Pink,
-    #[allow(dead_code)]
+    /// Falls 6dB per octave: a deep rumble, like wind or distant waves
     Brown,
 }
 
+impl NoiseColor {
+    /// Color names clients use, in variant order
+    pub const NAMES: [&'static str; 3] = ["white", "pink", "brown"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "white" => Some(Self::White),
+            "pink" => Some(Self::Pink),
+            "brown" => Some(Self::Brown),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAMES[*self as usize]
+    }
+}
+
+/// Noise generator that filters white noise into the requested color
+#[derive(Debug, Clone, Default)]
+struct ColoredNoise {
+    color: NoiseColor,
+    /// Pink filter poles (Paul Kellet's refined method)
+    pink: [f32; 7],
+    /// Brown integrator output
+    brown: f32,
+}
+
+impl ColoredNoise {
+    fn new(color: NoiseColor) -> Self {
+        Self {
+            color,
+            ..Default::default()
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let white = random_bipolar();
+        match self.color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => {
+                // Parallel one-pole filters whose sum approximates -3dB/octave across the
+                // audio band, scaled back to roughly the level of the white noise
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b.iter().sum::<f32>() + white * 0.5362;
+                b[6] = white * 0.115926;
+                pink * 0.11
+            }
+            NoiseColor::Brown => {
+                // Leaky integrator: -6dB/octave, with the leak keeping it from drifting off
+                self.brown = (self.brown + 0.02 * white) / 1.02;
+                self.brown * 3.5
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvelopeParams {
     pub attack: f32,
     pub decay: f32,
     pub sustain: f32,
     pub release: f32,
+    /// Shape of the attack, decay and release segments
+    #[serde(default)]
+    pub curve: EnvelopeCurve,
+}
+
+/// How an envelope segment moves between its start and end levels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum EnvelopeCurve {
+    /// Straight-line segments
+    #[default]
+    Linear,
+    /// Fast at first, then settling into the target, like an analog RC envelope
+    Exponential,
+    /// Slow at first, then rushing into the target
+    Logarithmic,
+}
+
+/// Steepness of the exponential and logarithmic curves; at the segment midpoint they are
+/// about 92% and 8% of the way there
+const ENVELOPE_CURVE_STEEPNESS: f32 = 5.0;
+
+impl EnvelopeCurve {
+    /// Curve names clients use, in variant order
+    pub const NAMES: [&'static str; 3] = ["linear", "exponential", "logarithmic"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Self::Linear),
+            "exponential" => Some(Self::Exponential),
+            "logarithmic" => Some(Self::Logarithmic),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAMES[*self as usize]
+    }
+
+    /// Fraction of the way through a segment's level change at `progress` (0.0-1.0) of its time
+    pub fn shape(&self, progress: f32) -> f32 {
+        let progress = progress.clamp(0.0, 1.0);
+        let k = ENVELOPE_CURVE_STEEPNESS;
+        match self {
+            Self::Linear => progress,
+            Self::Exponential => (1.0 - (-k * progress).exp()) / (1.0 - (-k).exp()),
+            Self::Logarithmic => ((k * progress).exp() - 1.0) / (k.exp() - 1.0),
+        }
+    }
+}
+
+impl EnvelopeParams {
+    /// ADSR level at time `t` for a note lasting `duration` seconds
+    pub fn level(&self, t: f32, duration: f32) -> f32 {
+        if t < self.attack {
+            // Attack phase
+            self.curve.shape(t / self.attack)
+        } else if t < self.attack + self.decay {
+            // Decay phase
+            let decay_progress = (t - self.attack) / self.decay;
+            1.0 - self.curve.shape(decay_progress) * (1.0 - self.sustain)
+        } else if t < duration - self.release {
+            // Sustain phase
+            self.sustain
+        } else {
+            // Release phase
+            let release_progress = (t - (duration - self.release)) / self.release;
+            self.sustain * (1.0 - self.curve.shape(release_progress))
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,6 +335,91 @@ pub struct FilterParams {
     pub cutoff: f32,
     pub resonance: f32,
     pub filter_type: FilterType,
+    /// Seconds over which resonance and cutoff ramp up at voice start, so high-resonance
+    /// notes don't thump (0 disables)
+    #[serde(default = "default_filter_soft_start")]
+    pub soft_start: f32,
+}
+
+fn default_filter_soft_start() -> f32 {
+    DEFAULT_FILTER_SOFT_START
+}
+
+/// Pulse-width modulation: a sine LFO swings the square's pulse width, the classic
+/// moving sound of analog string and pad patches
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pwm {
+    /// LFO rate in Hz (0.05-20.0)
+    pub rate: f32,
+    /// How far the pulse width swings either way (0.0-0.4)
+    pub depth: f32,
+}
+
+impl Pwm {
+    pub fn validate(&self) -> Result<(), String> {
+        if !PWM_RATE_RANGE.contains(&self.rate) {
+            return Err(format!(
+                "PWM rate {} is out of range ({}-{} Hz)",
+                self.rate,
+                PWM_RATE_RANGE.start(),
+                PWM_RATE_RANGE.end()
+            ));
+        }
+        if !(0.0..=MAX_PWM_DEPTH).contains(&self.depth) {
+            return Err(format!(
+                "PWM depth {} is out of range (0.0-{})",
+                self.depth, MAX_PWM_DEPTH
+            ));
+        }
+        Ok(())
+    }
+
+    /// Pulse width at `t` seconds into the note, swinging around `pulse_width`
+    pub fn pulse_width_at(&self, pulse_width: f32, t: f32) -> f32 {
+        let swing = self.depth * (std::f32::consts::TAU * self.rate * t).sin();
+        (pulse_width + swing).clamp(MIN_PWM_WIDTH, 1.0 - MIN_PWM_WIDTH)
+    }
+}
+
+/// A sine LFO for vibrato or tremolo. Both start at the note's unmodulated pitch and level
+/// so the modulation fades in without a click.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Lfo {
+    /// LFO rate in Hz (0.0-20.0)
+    pub rate: f32,
+    /// Semitones for vibrato, a 0.0-1.0 fraction of the level for tremolo
+    pub depth: f32,
+}
+
+impl Lfo {
+    /// An LFO from optional note parameters, or None when either is missing or 0 so that
+    /// unmodulated notes skip the per-sample work entirely
+    pub fn new(rate: Option<f32>, depth: Option<f32>) -> Option<Self> {
+        match (rate, depth) {
+            (Some(rate), Some(depth)) if rate > 0.0 && depth > 0.0 => Some(Self { rate, depth }),
+            _ => None,
+        }
+    }
+
+    /// Frequency multiplier at `t` seconds, swinging `depth` semitones either way
+    pub fn pitch_ratio_at(&self, t: f32) -> f32 {
+        (self.depth * (std::f32::consts::TAU * self.rate * t).sin() / 12.0).exp2()
+    }
+
+    /// How far ahead of `t`, in seconds, a vibrato delay line reads at `t`. The offset
+    /// swings between 0 and twice its mean and comes back each cycle, so the pitch bends
+    /// `depth` semitones either way (averaged over the two sides) while the note never
+    /// drifts ahead of its own time.
+    pub fn vibrato_offset_at(&self, t: f32) -> f32 {
+        let omega = std::f32::consts::TAU * self.rate;
+        let swing = ((self.depth / 12.0).exp2() - (-self.depth / 12.0).exp2()) * 0.5;
+        swing / omega * (1.0 - (omega * t).cos())
+    }
+
+    /// Level multiplier at `t` seconds, dipping from 1.0 to `1.0 - depth` and back each cycle
+    pub fn gain_at(&self, t: f32) -> f32 {
+        1.0 - self.depth * 0.5 * (1.0 - (std::f32::consts::TAU * self.rate * t).cos())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -188,22 +462,145 @@ impl Default for DX7Operator {
                 decay: 0.3,
                 sustain: 0.7,
                 release: 0.5,
+                curve: EnvelopeCurve::Linear,
             },
         }
     }
 }
 
+impl SynthType {
+    /// One instance of every synthesis type, with representative parameters
+    pub fn all() -> Vec<SynthType> {
+        vec![
+            SynthType::Sine,
+            SynthType::Square {
+                pulse_width: 0.5,
+                pwm: None,
+            },
+            SynthType::Sawtooth,
+            SynthType::Triangle,
+            SynthType::Noise {
+                color: NoiseColor::White,
+            },
+            SynthType::Morph {
+                position: 0.0,
+                end_position: None,
+            },
+            SynthType::FM {
+                modulator_freq: 880.0,
+                modulation_index: 2.0,
+            },
+            SynthType::DX7FM {
+                algorithm: 1,
+                operators: std::array::from_fn(|_| DX7Operator::default()),
+            },
+            SynthType::Granular {
+                grain_size: 0.1,
+                overlap: 0.5,
+                density: 1.0,
+            },
+            SynthType::Wavetable {
+                position: 0.5,
+                morph_speed: 1.0,
+            },
+            SynthType::Kick {
+                punch: 0.8,
+                sustain: 0.3,
+                click_freq: 8000.0,
+                body_freq: 60.0,
+            },
+            SynthType::Snare {
+                snap: 0.7,
+                buzz: 0.6,
+                tone_freq: 200.0,
+                noise_amount: 0.8,
+            },
+            SynthType::HiHat {
+                metallic: 0.8,
+                decay: 0.1,
+                brightness: 0.7,
+            },
+            SynthType::Cymbal {
+                size: 0.8,
+                metallic: 0.9,
+                strike_intensity: 0.8,
+            },
+            SynthType::Swoosh {
+                direction: 0.0,
+                intensity: 0.7,
+                frequency_sweep: (200.0, 2000.0),
+            },
+            SynthType::Zap {
+                energy: 0.8,
+                decay: 0.3,
+                harmonic_content: 0.7,
+            },
+            SynthType::Chime {
+                fundamental: 440.0,
+                harmonic_count: 5,
+                decay: 0.5,
+                inharmonicity: 0.1,
+            },
+            SynthType::Burst {
+                center_freq: 1000.0,
+                bandwidth: 500.0,
+                intensity: 0.8,
+                shape: 0.5,
+            },
+            SynthType::Pad {
+                warmth: 0.7,
+                movement: 0.3,
+                space: 0.6,
+                harmonic_evolution: 0.4,
+            },
+            SynthType::Texture {
+                roughness: 0.5,
+                evolution: 0.3,
+                spectral_tilt: 0.0,
+                modulation_depth: 0.4,
+            },
+            SynthType::Drone {
+                fundamental: 110.0,
+                overtone_spread: 0.5,
+                modulation: 0.3,
+                harmonics: 6,
+                evolution_rate: 0.05,
+                detune: 4.0,
+            },
+        ]
+    }
+
+    /// The `synth_type` string clients use for this type, derived from the variant name
+    pub fn name(&self) -> String {
+        let variant = match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => name,
+            Ok(serde_json::Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+            _ => String::new(),
+        };
+        variant.to_lowercase()
+    }
+}
+
 impl ExpressiveSynth {
     /// Create a new expressive synthesizer
+    #[allow(dead_code)]
     pub fn new() -> Result<Self> {
         let _stream = rodio::OutputStreamBuilder::open_default_stream()?;
 
         Ok(ExpressiveSynth {
             sample_rate: 44100.0,
-            _stream,
+            _stream: Some(_stream),
         })
     }
 
+    /// Create a synthesizer for sample generation only, without opening an audio device
+    pub fn offline() -> Self {
+        ExpressiveSynth {
+            sample_rate: 44100.0,
+            _stream: None,
+        }
+    }
+
     /// Generate R2D2 samples without creating an audio stream (static method)
     #[allow(dead_code)]
     pub fn generate_r2d2_samples_static(
@@ -323,11 +720,41 @@ impl ExpressiveSynth {
     /// Generate audio samples using hybrid approach: FunDSP for quality-critical synthesis, custom DSP for others
     pub fn generate_synthesized_samples(&self, params: &SynthParams) -> Result<Vec<f32>> {
         // Check if this synthesis type should use FunDSP for higher quality
-        if self.should_use_fundsp(&params.synth_type) {
+        let samples = if self.should_use_fundsp(&params.synth_type) {
             self.generate_with_fundsp(params)
         } else {
             self.generate_with_custom_dsp(params)
+        }?;
+        Ok(self.apply_modulation(samples, params))
+    }
+
+    /// Apply the vibrato and tremolo LFOs to rendered samples. Vibrato reads the samples
+    /// back through a delay line whose offset follows the LFO, so it bends the pitch of
+    /// every synthesis type alike, FunDSP or custom, and keeps the note on time.
+    fn apply_modulation(&self, samples: Vec<f32>, params: &SynthParams) -> Vec<f32> {
+        let mut samples = match params.vibrato {
+            Some(vibrato) if samples.len() > 1 => {
+                let last = (samples.len() - 1) as f32;
+                (0..samples.len())
+                    .map(|i| {
+                        let t = i as f32 / self.sample_rate;
+                        let offset = vibrato.vibrato_offset_at(t) * self.sample_rate;
+                        let index = (i as f32 + offset).min(last);
+                        let lower = index as usize;
+                        let upper = (lower + 1).min(samples.len() - 1);
+                        let fraction = index - lower as f32;
+                        samples[lower] + (samples[upper] - samples[lower]) * fraction
+                    })
+                    .collect()
+            }
+            _ => samples,
+        };
+        if let Some(tremolo) = params.tremolo {
+            for (i, sample) in samples.iter_mut().enumerate() {
+                *sample *= tremolo.gain_at(i as f32 / self.sample_rate);
+            }
         }
+        samples
     }
 
     /// Determine if a synthesis type should use FunDSP for better quality
@@ -340,6 +767,7 @@ impl ExpressiveSynth {
             SynthType::Cymbal { .. } => true,
 
             // Use FunDSP for advanced synthesis techniques that benefit from professional algorithms
+            SynthType::Morph { .. } => true,
             SynthType::FM { .. } => true,
             SynthType::Granular { .. } => true,
 
@@ -350,6 +778,7 @@ impl ExpressiveSynth {
             // Use FunDSP for ambient textures that benefit from rich processing
             SynthType::Pad { .. } => true,
             SynthType::Texture { .. } => true,
+            SynthType::Drone { .. } => true,
 
             // Keep custom DSP for basic oscillators and other synthesis types
             _ => false,
@@ -374,14 +803,22 @@ impl ExpressiveSynth {
     fn generate_with_custom_dsp(&self, params: &SynthParams) -> Result<Vec<f32>> {
         let sample_count = (self.sample_rate * params.duration) as usize;
         let mut samples = Vec::with_capacity(sample_count);
+        let mut filter_state = FilterState::default();
+        let mut noise = match &params.synth_type {
+            SynthType::Noise { color } => Some(ColoredNoise::new(*color)),
+            _ => None,
+        };
 
         for i in 0..sample_count {
             let t = i as f32 / self.sample_rate;
-            let mut sample = self.generate_sample(params, t);
+            let mut sample = match &mut noise {
+                Some(noise) => noise.next_sample(),
+                None => self.generate_sample(params, t),
+            };
 
             // Apply filter if specified
             if let Some(filter) = &params.filter {
-                sample = self.apply_filter(sample, filter, t);
+                sample = self.apply_filter(sample, filter, t, &mut filter_state);
             }
 
             // Apply effects if specified
@@ -402,43 +839,50 @@ impl ExpressiveSynth {
     /// Generate a single sample using direct synthesis (simplified approach)
     fn generate_sample(&self, params: &SynthParams, t: f32) -> f32 {
         let freq = params.frequency;
-        let phase = 2.0 * std::f32::consts::PI * freq * t;
+        let cycles = freq * t + params.phase;
+        let phase = 2.0 * std::f32::consts::PI * cycles;
 
         match &params.synth_type {
             SynthType::Sine => phase.sin(),
-            SynthType::Square { pulse_width } => {
-                let normalized_phase = (freq * t) % 1.0;
-                if normalized_phase < *pulse_width {
-                    1.0
-                } else {
-                    -1.0
-                }
+            SynthType::Square { pulse_width, pwm } => {
+                let width = pwm.map_or(*pulse_width, |pwm| pwm.pulse_width_at(*pulse_width, t));
+                square(cycles.rem_euclid(1.0), width, freq, self.sample_rate)
             }
-            SynthType::Sawtooth => 2.0 * ((freq * t) % 1.0) - 1.0,
+            SynthType::Sawtooth => sawtooth(cycles.rem_euclid(1.0), freq, self.sample_rate),
             SynthType::Triangle => {
-                let x = (freq * t) % 1.0;
+                let x = cycles.rem_euclid(1.0);
                 if x < 0.5 {
                     4.0 * x - 1.0
                 } else {
                     3.0 - 4.0 * x
                 }
             }
-            SynthType::Noise { color } => {
-                let mut rng = rand::rng();
-                match color {
-                    NoiseColor::White => (rng.random::<f32>() - 0.5) * 2.0,
-                    NoiseColor::Pink => {
-                        // Simple pink noise approximation
-                        let white = (rng.random::<f32>() - 0.5) * 2.0;
-                        // Apply simple pink filter (approximation)
-                        white * (1.0 / (1.0 + freq / 500.0).sqrt())
-                    }
-                    NoiseColor::Brown => {
-                        // Simple brown noise approximation
-                        let white = (rng.random::<f32>() - 0.5) * 2.0;
-                        white * (1.0 / (1.0 + freq / 100.0))
-                    }
-                }
+            // Colored noise needs filter memory, so generate_with_custom_dsp renders it
+            // through ColoredNoise; a lone sample can only be white
+            SynthType::Noise { .. } => random_bipolar(),
+            SynthType::Morph {
+                position,
+                end_position,
+            } => {
+                // Naive crossfade of adjacent shapes; the band-limited version lives in fundsp_synth
+                let progress = (t / params.duration.max(f32::EPSILON)).min(1.0);
+                let end = end_position.unwrap_or(*position);
+                let scaled = (position + (end - position) * progress).clamp(0.0, 1.0) * 3.0;
+                let lower = (scaled.floor() as usize).min(2);
+                let blend = scaled - lower as f32;
+
+                let x = cycles.rem_euclid(1.0);
+                let shapes = [
+                    phase.sin(),
+                    if x < 0.5 {
+                        4.0 * x - 1.0
+                    } else {
+                        3.0 - 4.0 * x
+                    },
+                    2.0 * x - 1.0,
+                    if x < 0.5 { 1.0 } else { -1.0 },
+                ];
+                shapes[lower] * (1.0 - blend) + shapes[lower + 1] * blend
             }
             SynthType::FM {
                 modulator_freq,
@@ -527,10 +971,8 @@ impl ExpressiveSynth {
                             0.5 * (1.0 - (2.0 * std::f32::consts::PI * grain_progress).cos());
 
                         // PITCHED GRANULAR: Grains maintain musical pitch relationship
-                        let mut rng = rand::rng();
-
                         // Pitch coherence: blend between pitched and textural
-                        let base_pitch_variation = (rng.random::<f32>() - 0.5) * grain_pitch_spread;
+                        let base_pitch_variation = (random_f32() - 0.5) * grain_pitch_spread;
                         let coherent_pitch = freq; // Musical pitch
                         let random_pitch = freq * (1.0 + base_pitch_variation);
 
@@ -544,7 +986,7 @@ impl ExpressiveSynth {
 
                         // Mix sine wave (tonal) with filtered noise (textural)
                         let tonal_component = grain_phase.sin() * 0.7;
-                        let noise_component = (rng.random::<f32>() - 0.5) * 0.3;
+                        let noise_component = (random_f32() - 0.5) * 0.3;
                         let grain_sample = (tonal_component + noise_component) * envelope;
 
                         // Spatial positioning for grain clouds
@@ -658,8 +1100,7 @@ impl ExpressiveSynth {
                 // 3. Sharp attack, medium decay
 
                 let tone = (2.0 * std::f32::consts::PI * tone_freq * t).sin();
-                let mut rng = rand::rng();
-                let white_noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let white_noise = random_bipolar();
 
                 // Create buzzy noise characteristic of snare wires
                 let buzz_freq = tone_freq * 2.5; // Higher frequency buzz
@@ -683,8 +1124,7 @@ impl ExpressiveSynth {
                 // Professional hi-hat synthesis:
                 // Complex metallic frequencies + filtered noise
 
-                let mut rng = rand::rng();
-                let white_noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let white_noise = random_bipolar();
 
                 // Multiple metallic frequencies for realistic cymbal sound
                 let freq1 = freq * brightness;
@@ -710,8 +1150,7 @@ impl ExpressiveSynth {
                 strike_intensity,
             } => {
                 // Professional cymbal synthesis with complex harmonics
-                let mut rng = rand::rng();
-                let white_noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let white_noise = random_bipolar();
 
                 // Size affects fundamental frequency range
                 let base_freq = freq * (0.5 + size * 0.5);
@@ -750,8 +1189,7 @@ impl ExpressiveSynth {
                 let current_freq = start_freq + (end_freq - start_freq) * progress;
 
                 // Generate filtered noise with frequency sweep
-                let mut rng = rand::rng();
-                let noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let noise = random_bipolar();
 
                 // Simple bandpass filter simulation
                 let filter_center = current_freq;
@@ -798,8 +1236,7 @@ impl ExpressiveSynth {
                 let harm4 = (2.0 * std::f32::consts::PI * current_freq * 5.23 * t).sin() * 0.2; // Chaotic
 
                 // 3. HIGH-FREQUENCY NOISE BURST for "energy" character
-                let mut rng = rand::rng();
-                let aggressive_noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let aggressive_noise = random_bipolar();
                 let noise_envelope = (-t * 12.0).exp(); // Sharp noise burst
                 let energy_burst = aggressive_noise * noise_envelope * energy * 0.5;
 
@@ -866,8 +1303,7 @@ impl ExpressiveSynth {
                 shape,
             } => {
                 // Spectral burst around center frequency
-                let mut rng = rand::rng();
-                let noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let noise = random_bipolar();
 
                 // Create burst envelope
                 let progress = t / params.duration.max(0.1);
@@ -935,8 +1371,7 @@ impl ExpressiveSynth {
                 modulation_depth,
             } => {
                 // Rough, evolving textural sound
-                let mut rng = rand::rng();
-                let noise = (rng.random::<f32>() - 0.5) * 2.0;
+                let noise = random_bipolar();
 
                 // Base oscillator
                 let osc = (2.0 * std::f32::consts::PI * freq * t).sin();
@@ -968,6 +1403,7 @@ impl ExpressiveSynth {
                 fundamental,
                 overtone_spread,
                 modulation,
+                ..
             } => {
                 // Sustained drone with overtones
                 let base_freq = *fundamental;
@@ -1029,24 +1465,7 @@ impl ExpressiveSynth {
     }
 
     fn calculate_synth_envelope(&self, t: f32, params: &SynthParams) -> f32 {
-        let env = &params.envelope;
-        let duration = params.duration;
-
-        if t < env.attack {
-            // Attack phase
-            t / env.attack
-        } else if t < env.attack + env.decay {
-            // Decay phase
-            let decay_progress = (t - env.attack) / env.decay;
-            1.0 - decay_progress * (1.0 - env.sustain)
-        } else if t < duration - env.release {
-            // Sustain phase
-            env.sustain
-        } else {
-            // Release phase
-            let release_progress = (t - (duration - env.release)) / env.release;
-            env.sustain * (1.0 - release_progress)
-        }
+        params.envelope.level(t, params.duration)
     }
 
     /// Generate R2D2-style audio samples with emotion-specific pitch contours
@@ -1056,12 +1475,24 @@ impl ExpressiveSynth {
         emotion_intensity: f32,
         duration: f32,
         pitch_contour: &[f32],
+        voice: Option<&R2D2VoiceCharacter>,
     ) -> Vec<f32> {
         let sample_count = (self.sample_rate * duration) as usize;
         let mut samples = Vec::with_capacity(sample_count);
 
         let dt = 1.0 / self.sample_rate;
 
+        // A custom voice blends ring modulation with the bare carrier and adds a formant
+        let mut formant = voice.map(|voice| {
+            let cutoff = R2D2_FORMANT_HZ * voice.formant_shift;
+            (
+                voice.ring_mod_amount,
+                (std::f32::consts::PI * cutoff / self.sample_rate).sin() * 2.0,
+                0.0f32,
+                0.0f32,
+            )
+        });
+
         // Ben Burtt's approach: ring modulation with dynamic filter sweeps
         let carrier_freq = base_freq;
         let mod_freq = base_freq * 0.618; // Golden ratio for more organic modulation
@@ -1092,7 +1523,17 @@ impl ExpressiveSynth {
             let ring_mod = carrier * modulator;
 
             // Simplified approach: minimal filtering to avoid interference
-            let filtered_voice = ring_mod; // Skip complex filtering for now
+            let filtered_voice = match &mut formant {
+                None => ring_mod, // Skip complex filtering for now
+                Some((ring_mod_amount, frequency, low, band)) => {
+                    let core = ring_mod * *ring_mod_amount + carrier * (1.0 - *ring_mod_amount);
+                    // Chamberlin state-variable band-pass for the vocal formant
+                    *low += *frequency * *band;
+                    let high = core - *low - R2D2_FORMANT_DAMPING * *band;
+                    *band += *frequency * high;
+                    core + *band * R2D2_FORMANT_MIX
+                }
+            };
 
             // Minimal harmonics that definitely follow pitch direction
             let harmonic2 = if pitch_multiplier > 1.5 {
@@ -1285,56 +1726,63 @@ impl ExpressiveSynth {
     }
 
     /// Apply filter to sample
-    fn apply_filter(&self, sample: f32, filter: &FilterParams, t: f32) -> f32 {
-        // Simplified filter implementation
-        let cutoff_normalized = filter.cutoff / (self.sample_rate * 0.5); // Normalize to Nyquist
-        let resonance = filter.resonance.clamp(0.0, 0.9); // Prevent instability
+    fn apply_filter(
+        &self,
+        sample: f32,
+        filter: &FilterParams,
+        t: f32,
+        state: &mut FilterState,
+    ) -> f32 {
+        // Open the filter over the soft start instead of hitting full resonance at once
+        let ramp = if filter.soft_start > 0.0 {
+            (t / filter.soft_start).min(1.0)
+        } else {
+            1.0
+        };
+        let cutoff =
+            filter.cutoff * (FILTER_SOFT_START_CUTOFF + (1.0 - FILTER_SOFT_START_CUTOFF) * ramp);
+        let resonance = filter.resonance.clamp(0.0, 0.9) * ramp; // Prevent instability
+        // One-pole smoothing coefficient for a cutoff, as in the real-time voices
+        let alpha = |cutoff: f32| 1.0 - (-std::f32::consts::TAU * cutoff / self.sample_rate).exp();
+        // This filter used to scale samples by this gain and nothing more. Presets are
+        // voiced against it, so the filtered signal keeps that level.
+        let preset_level =
+            |cutoff: f32| 1.0 - (-std::f32::consts::TAU * cutoff / (self.sample_rate * 0.5)).exp();
 
         match filter.filter_type {
             FilterType::LowPass => {
-                // Simple one-pole lowpass filter
-                let alpha = 1.0 - (-2.0 * std::f32::consts::PI * cutoff_normalized).exp();
-                let filtered = sample * alpha;
+                state.lowpass_history += alpha(cutoff) * (sample - state.lowpass_history);
 
                 // Add resonance (simplified)
-                let resonant_freq = filter.cutoff;
                 let resonance_component =
-                    (2.0 * std::f32::consts::PI * resonant_freq * t).sin() * resonance * 0.1;
+                    (2.0 * std::f32::consts::PI * cutoff * t).sin() * resonance * 0.1;
 
-                filtered + resonance_component
+                state.lowpass_history * preset_level(cutoff) + resonance_component
             }
             FilterType::HighPass => {
-                // Simple one-pole highpass filter (complement of lowpass)
-                let alpha = 1.0 - (-2.0 * std::f32::consts::PI * cutoff_normalized).exp();
-                let lowpass = sample * alpha;
-                let highpass = sample - lowpass;
+                state.highpass_history += alpha(cutoff) * (sample - state.highpass_history);
 
                 // Add resonance
-                let resonant_freq = filter.cutoff;
                 let resonance_component =
-                    (2.0 * std::f32::consts::PI * resonant_freq * t).sin() * resonance * 0.1;
+                    (2.0 * std::f32::consts::PI * cutoff * t).sin() * resonance * 0.1;
 
-                highpass + resonance_component
+                (sample - state.highpass_history) * (1.0 - preset_level(cutoff))
+                    + resonance_component
             }
             FilterType::BandPass => {
-                // Simple bandpass (combination of high and low pass)
-                let bandwidth = filter.cutoff * 0.2; // 20% of cutoff frequency
-                let low_cutoff = filter.cutoff - bandwidth;
-                let high_cutoff = filter.cutoff + bandwidth;
-
-                let low_alpha = 1.0
-                    - (-2.0 * std::f32::consts::PI * (low_cutoff / (self.sample_rate * 0.5))).exp();
-                let high_alpha = 1.0
-                    - (-2.0 * std::f32::consts::PI * (high_cutoff / (self.sample_rate * 0.5)))
-                        .exp();
-
-                let lowpass = sample * low_alpha;
-                let highpass = sample - (sample * high_alpha);
-                let bandpass = lowpass - highpass;
+                // Series lowpass and highpass around the center frequency
+                let bandwidth = cutoff * 0.2; // 20% of cutoff frequency
+                state.lowpass_history +=
+                    alpha(cutoff + bandwidth) * (sample - state.lowpass_history);
+                state.highpass_history +=
+                    alpha(cutoff - bandwidth) * (state.lowpass_history - state.highpass_history);
+                let level =
+                    preset_level(cutoff - bandwidth) + preset_level(cutoff + bandwidth) - 1.0;
+                let bandpass = (state.lowpass_history - state.highpass_history) * level.abs();
 
                 // Add resonance at center frequency
                 let resonance_component =
-                    (2.0 * std::f32::consts::PI * filter.cutoff * t).sin() * resonance * 0.15;
+                    (2.0 * std::f32::consts::PI * cutoff * t).sin() * resonance * 0.15;
 
                 bandpass + resonance_component
             }
@@ -1467,3 +1915,559 @@ impl ExpressiveSynth {
         }
     }
 }
+
+/// Sum a waveform's harmonics that fit below Nyquist, fading out the ones closest to it.
+/// Returns None while the note is low enough for the naive waveform to stay clean.
+fn band_limited(frequency: f32, sample_rate: f32, harmonic: impl Fn(f32) -> f32) -> Option<f32> {
+    let nyquist = sample_rate / 2.0;
+    let frequency = frequency.abs().max(f32::EPSILON);
+    if nyquist / frequency > NAIVE_OSCILLATOR_HARMONICS as f32 {
+        return None;
+    }
+
+    let rolloff = nyquist * HARMONIC_ROLLOFF;
+    Some(
+        (1..=NAIVE_OSCILLATOR_HARMONICS)
+            .map(|n| n as f32)
+            .take_while(|n| n * frequency < nyquist)
+            .map(|n| harmonic(n) * ((nyquist - n * frequency) / rolloff).min(1.0))
+            .sum(),
+    )
+}
+
+/// Sawtooth at `phase` (in cycles), shedding harmonics as the note approaches Nyquist
+pub fn sawtooth(phase: f32, frequency: f32, sample_rate: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+
+    let phase = phase.rem_euclid(1.0);
+    band_limited(frequency, sample_rate, |n| {
+        -2.0 / (PI * n) * (TAU * n * phase).sin()
+    })
+    .unwrap_or(2.0 * phase - 1.0)
+}
+
+/// Pulse wave at `phase` (in cycles), shedding harmonics as the note approaches Nyquist
+pub fn square(phase: f32, pulse_width: f32, frequency: f32, sample_rate: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+
+    let phase = phase.rem_euclid(1.0);
+    band_limited(frequency, sample_rate, |n| {
+        4.0 / (PI * n)
+            * (PI * n * pulse_width).sin()
+            * (TAU * n * (phase - pulse_width / 2.0)).cos()
+    })
+    .map(|harmonics| 2.0 * pulse_width - 1.0 + harmonics)
+    .unwrap_or(if phase < pulse_width { 1.0 } else { -1.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    /// One second of an oscillator at `frequency`
+    fn render(frequency: f32, oscillator: impl Fn(f32) -> f32) -> Vec<f32> {
+        (0..SAMPLE_RATE as usize)
+            .map(|i| oscillator((i as f64 * frequency as f64 / SAMPLE_RATE as f64).fract() as f32))
+            .collect()
+    }
+
+    /// Energy left after removing the fundamental, i.e. everything folded back below Nyquist
+    fn energy_besides_fundamental(samples: &[f32], frequency: f32) -> f32 {
+        let omega = std::f32::consts::TAU * frequency / SAMPLE_RATE;
+        let scale = 2.0 / samples.len() as f32;
+        let (sin, cos) = samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(sin, cos), (i, &sample)| {
+                let angle = omega * i as f32;
+                (sin + sample * angle.sin(), cos + sample * angle.cos())
+            });
+        let (sin, cos) = (sin * scale, cos * scale);
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, &sample)| {
+                let angle = omega * i as f32;
+                (sample - sin * angle.sin() - cos * angle.cos()).powi(2)
+            })
+            .sum::<f32>()
+            / samples.len() as f32
+    }
+
+    #[test]
+    fn test_high_sawtooth_sheds_aliases_and_low_sawtooth_is_unchanged() {
+        let naive = |phase: f32| 2.0 * phase - 1.0;
+
+        // Every harmonic of a 15kHz saw past the fundamental lies above Nyquist and folds back
+        let naive_high = energy_besides_fundamental(&render(15000.0, naive), 15000.0);
+        let limited_high = energy_besides_fundamental(
+            &render(15000.0, |phase| sawtooth(phase, 15000.0, SAMPLE_RATE)),
+            15000.0,
+        );
+        assert!(naive_high > 0.1, "naive alias energy {}", naive_high);
+        assert!(
+            limited_high < naive_high * 0.01,
+            "band-limited alias energy {} vs naive {}",
+            limited_high,
+            naive_high
+        );
+
+        let naive_low = render(200.0, naive);
+        let limited_low = render(200.0, |phase| sawtooth(phase, 200.0, SAMPLE_RATE));
+        assert_eq!(naive_low, limited_low);
+    }
+
+    fn sine_note(phase: f32) -> Vec<f32> {
+        let params = SynthParams {
+            synth_type: SynthType::Sine,
+            frequency: 220.0,
+            amplitude: 0.5,
+            duration: 0.5,
+            phase,
+            envelope: EnvelopeParams {
+                attack: 0.01,
+                decay: 0.1,
+                sustain: 0.8,
+                release: 0.1,
+                curve: EnvelopeCurve::Linear,
+            },
+            filter: None,
+            effects: Vec::new(),
+            vibrato: None,
+            tremolo: None,
+        };
+        ExpressiveSynth::offline()
+            .generate_synthesized_samples(&params)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_aligned_phases_double_and_opposite_phases_cancel() {
+        let single = sine_note(0.0);
+        let peak = single.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.3);
+
+        let aligned: Vec<f32> = single
+            .iter()
+            .zip(&sine_note(0.0))
+            .map(|(a, b)| a + b)
+            .collect();
+        let doubled: Vec<f32> = single.iter().map(|s| 2.0 * s).collect();
+        assert_eq!(aligned, doubled);
+
+        let opposed = single
+            .iter()
+            .zip(&sine_note(0.5))
+            .fold(0.0f32, |peak, (a, b)| peak.max((a + b).abs()));
+        assert!(opposed < 1e-3, "residual {}", opposed);
+    }
+
+    #[test]
+    fn test_max_resonance_note_starts_without_a_thump() {
+        let render_with = |soft_start: f32, resonance: f32| {
+            let params = SynthParams {
+                synth_type: SynthType::Sawtooth,
+                frequency: 110.0,
+                amplitude: 0.8,
+                duration: 0.5,
+                phase: 0.0,
+                envelope: EnvelopeParams {
+                    attack: 0.001,
+                    decay: 0.0,
+                    sustain: 1.0,
+                    release: 0.0,
+                    curve: EnvelopeCurve::Linear,
+                },
+                filter: Some(FilterParams {
+                    cutoff: 700.0,
+                    resonance,
+                    filter_type: FilterType::LowPass,
+                    soft_start,
+                }),
+                effects: Vec::new(),
+                vibrato: None,
+                tremolo: None,
+            };
+            ExpressiveSynth::offline()
+                .generate_synthesized_samples(&params)
+                .unwrap()
+        };
+        let render = |soft_start: f32| render_with(soft_start, 1.0);
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        let samples = render(DEFAULT_FILTER_SOFT_START);
+        let onset = peak(&samples[..441]);
+        let sustained = peak(&samples[8820..17640]);
+        assert!(sustained > 0.05, "sustained peak {}", sustained);
+        assert!(
+            onset <= sustained * 1.5,
+            "onset peak {} vs sustained {}",
+            onset,
+            sustained
+        );
+        // The resonance comes in under the ramp rather than at full strength: compare what
+        // it adds over the first couple of milliseconds with and without the soft start
+        let resonant_onset = |soft_start: f32| {
+            let resonant: Vec<f32> = render(soft_start)
+                .iter()
+                .zip(&render_with(soft_start, 0.0))
+                .take(88)
+                .map(|(with, without)| with - without)
+                .collect();
+            peak(&resonant)
+        };
+        let hard_start = resonant_onset(0.0);
+        let soft_start = resonant_onset(DEFAULT_FILTER_SOFT_START);
+        assert!(
+            soft_start < hard_start * 0.5,
+            "soft start {} vs hard start {}",
+            soft_start,
+            hard_start
+        );
+    }
+
+    /// Share of a signal's energy sitting at `frequency`
+    fn energy_share_at(samples: &[f32], frequency: f32) -> f32 {
+        let total =
+            samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32;
+        1.0 - energy_besides_fundamental(samples, frequency) / total
+    }
+
+    #[test]
+    fn test_filtered_preset_keeps_its_level() {
+        // Presets were voiced when the filter only scaled samples by its passband gain;
+        // filtering the spectrum must not move them away from that level
+        let library = crate::expressive::PresetLibrary::new();
+        let preset = library.load_preset("Minimoog Bass").unwrap();
+        let params = SynthParams {
+            frequency: 65.41,
+            duration: 1.0,
+            ..preset.synth_params.clone()
+        };
+        let cutoff = params.filter.as_ref().unwrap().cutoff;
+        let rms = |params: &SynthParams| {
+            let samples = ExpressiveSynth::offline()
+                .generate_synthesized_samples(params)
+                .unwrap();
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        };
+
+        let filtered = rms(&params);
+        let unfiltered = rms(&SynthParams {
+            filter: None,
+            ..params.clone()
+        });
+        let voiced_gain = 1.0 - (-std::f32::consts::TAU * cutoff / (SAMPLE_RATE * 0.5)).exp();
+        let difference_db = 20.0 * (filtered / (unfiltered * voiced_gain)).log10();
+        assert!(
+            difference_db.abs() < 1.0,
+            "filtered preset is {:+.2} dB off its voiced level",
+            difference_db
+        );
+    }
+
+    #[test]
+    fn test_ring_mod_amount_moves_energy_off_the_carrier() {
+        let expression = crate::expressive::R2D2Expression {
+            emotion: crate::expressive::R2D2Emotion::Affirmative,
+            intensity: 0.5,
+            duration: 1.0,
+            phrase_complexity: 1,
+            pitch_range: (200.0, 800.0),
+            context: None,
+            voice: None,
+        };
+        let params = crate::expressive::R2D2Voice::new()
+            .generate_expression_params(&expression)
+            .unwrap();
+        let synth = ExpressiveSynth::offline();
+        // A flat contour holds the carrier still so its share of the energy can be measured
+        let render = |ring_mod_amount: f32| {
+            let voice = R2D2VoiceCharacter {
+                ring_mod_amount,
+                ..Default::default()
+            };
+            synth.generate_r2d2_samples_with_contour(
+                params.base_freq,
+                expression.intensity,
+                1.0,
+                &[],
+                Some(&voice),
+            )
+        };
+
+        // The first quarter second, before vibrato drift spreads the carrier
+        let window = SAMPLE_RATE as usize / 4;
+        let whistled = energy_share_at(&render(0.0)[..window], params.base_freq);
+        let ring_modulated = energy_share_at(&render(1.0)[..window], params.base_freq);
+        assert!(whistled > 0.5, "whistled share {}", whistled);
+        assert!(
+            ring_modulated < 0.1,
+            "ring-modulated share {}",
+            ring_modulated
+        );
+    }
+
+    #[test]
+    fn test_pwm_moves_the_spectrum_where_a_static_pulse_holds_still() {
+        let render = |pwm: Option<Pwm>| {
+            let params = SynthParams {
+                synth_type: SynthType::Square {
+                    pulse_width: 0.5,
+                    pwm,
+                },
+                frequency: 220.0,
+                amplitude: 0.8,
+                duration: 0.5,
+                phase: 0.0,
+                envelope: EnvelopeParams {
+                    attack: 0.0,
+                    decay: 0.0,
+                    sustain: 1.0,
+                    release: 0.0,
+                    curve: EnvelopeCurve::Linear,
+                },
+                filter: None,
+                effects: Vec::new(),
+                vibrato: None,
+                tremolo: None,
+            };
+            ExpressiveSynth::offline()
+                .generate_synthesized_samples(&params)
+                .unwrap()
+        };
+        // A square has no even harmonics, so the second harmonic tracks the pulse width:
+        // compare 50ms at the start of the note against 50ms a quarter LFO cycle later
+        let second_harmonic =
+            |samples: &[f32], start: usize| energy_share_at(&samples[start..start + 2205], 440.0);
+
+        let fixed = render(None);
+        let early = second_harmonic(&fixed, 0);
+        let late = second_harmonic(&fixed, 11025);
+        assert!((early - late).abs() < 0.01, "static {} vs {}", early, late);
+
+        let swept = render(Some(Pwm {
+            rate: 1.0,
+            depth: 0.3,
+        }));
+        let early = second_harmonic(&swept, 0);
+        let late = second_harmonic(&swept, 11025);
+        assert!(
+            late > early + 0.1,
+            "second harmonic share {} at the start vs {} a quarter cycle in",
+            early,
+            late
+        );
+        assert!(
+            Pwm {
+                rate: 30.0,
+                depth: 0.2
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            Pwm {
+                rate: 1.0,
+                depth: 0.5
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_slow_wide_vibrato_keeps_the_note_on_time() {
+        // A ramp read through the vibrato shows where each output sample was read from
+        let slow = Lfo::new(Some(1.3), Some(2.0)).unwrap();
+        let ramp: Vec<f32> = (0..4 * 44100).map(|i| i as f32).collect();
+        let params = SynthParams {
+            synth_type: SynthType::Sine,
+            frequency: 440.0,
+            amplitude: 1.0,
+            duration: 4.0,
+            phase: 0.0,
+            envelope: EnvelopeParams {
+                attack: 0.0,
+                decay: 0.0,
+                sustain: 1.0,
+                release: 0.0,
+                curve: EnvelopeCurve::Linear,
+            },
+            filter: None,
+            effects: Vec::new(),
+            vibrato: Some(slow),
+            tremolo: None,
+        };
+        let read = ExpressiveSynth::offline().apply_modulation(ramp.clone(), &params);
+
+        // It never lags the note's own time nor leads it by more than the delay line's
+        // swing, and comes back to it every cycle rather than drifting ahead
+        let swing = 44100.0 * slow.vibrato_offset_at(0.5 / 1.3);
+        for (i, position) in read
+            .iter()
+            .enumerate()
+            .take(ramp.len() - swing as usize - 1)
+        {
+            let ahead = position - i as f32;
+            assert!(
+                (-0.01..=swing + 0.01).contains(&ahead),
+                "{} ahead at {}",
+                ahead,
+                i
+            );
+        }
+        let cycle = (3.0 / 1.3 * 44100.0f32).round() as usize;
+        assert!(
+            (read[cycle] - cycle as f32).abs() < 1.0,
+            "{} at {}",
+            read[cycle],
+            cycle
+        );
+    }
+
+    #[test]
+    fn test_vibrato_bends_pitch_and_tremolo_dips_level() {
+        let render = |vibrato: Option<Lfo>, tremolo: Option<Lfo>| {
+            let params = SynthParams {
+                synth_type: SynthType::Sine,
+                frequency: 440.0,
+                amplitude: 1.0,
+                duration: 0.5,
+                phase: 0.0,
+                envelope: EnvelopeParams {
+                    attack: 0.0,
+                    decay: 0.0,
+                    sustain: 1.0,
+                    release: 0.0,
+                    curve: EnvelopeCurve::Linear,
+                },
+                filter: None,
+                effects: Vec::new(),
+                vibrato,
+                tremolo,
+            };
+            ExpressiveSynth::offline()
+                .generate_synthesized_samples(&params)
+                .unwrap()
+        };
+        // Average frequency over 10ms from upward zero crossings
+        let frequency_at = |samples: &[f32], t: f32| {
+            let start = (t * 44100.0) as usize;
+            let crossings: Vec<f32> = (start..start + 441)
+                .filter(|&i| samples[i] < 0.0 && samples[i + 1] >= 0.0)
+                .map(|i| i as f32 + samples[i] / (samples[i] - samples[i + 1]))
+                .collect();
+            let periods = (crossings.len() - 1) as f32;
+            44100.0 * periods / (crossings[crossings.len() - 1] - crossings[0])
+        };
+        let peak_at = |samples: &[f32], t: f32| {
+            let start = (t * 44100.0) as usize;
+            samples[start..start + 441]
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+        };
+
+        // At 5 Hz the LFO peaks at 50ms and bottoms out at 150ms: a semitone either way
+        let vibrato = render(Lfo::new(Some(5.0), Some(1.0)), None);
+        let high = frequency_at(&vibrato, 0.045);
+        let low = frequency_at(&vibrato, 0.145);
+        assert!(
+            (high / 466.16 - 1.0).abs() < 0.01,
+            "LFO peak at {} Hz",
+            high
+        );
+        assert!(
+            (low / 415.30 - 1.0).abs() < 0.01,
+            "LFO trough at {} Hz",
+            low
+        );
+        let plain = render(None, None);
+        assert!((frequency_at(&plain, 0.045) / 440.0 - 1.0).abs() < 0.01);
+        // The modulation starts from the plain note, so the onset is all but unchanged
+        assert_eq!(vibrato[0], plain[0]);
+        for (modulated, plain) in vibrato.iter().zip(&plain).take(20) {
+            assert!(
+                (modulated - plain).abs() < 1e-3,
+                "{} vs {}",
+                modulated,
+                plain
+            );
+        }
+
+        // At 4 Hz the tremolo is deepest at 125ms and back to full level at 250ms
+        let tremolo = render(None, Lfo::new(Some(4.0), Some(0.5)));
+        let dip = peak_at(&tremolo, 0.12);
+        let full = peak_at(&tremolo, 0.245);
+        assert!((dip - 0.5).abs() < 0.02, "tremolo dip peaks at {}", dip);
+        assert!((full - 1.0).abs() < 0.02, "tremolo crest peaks at {}", full);
+
+        // A rate or depth of 0 means no LFO at all
+        assert_eq!(Lfo::new(Some(0.0), Some(1.0)), None);
+        assert_eq!(Lfo::new(Some(5.0), Some(0.0)), None);
+        assert_eq!(Lfo::new(None, Some(1.0)), None);
+    }
+
+    #[test]
+    fn test_pink_and_brown_noise_carry_less_high_frequency_energy_than_white() {
+        let render = |color: NoiseColor| {
+            let params = SynthParams {
+                synth_type: SynthType::Noise { color },
+                frequency: 440.0,
+                amplitude: 1.0,
+                duration: 0.5,
+                phase: 0.0,
+                envelope: EnvelopeParams {
+                    attack: 0.0,
+                    decay: 0.0,
+                    sustain: 1.0,
+                    release: 0.0,
+                    curve: EnvelopeCurve::Linear,
+                },
+                filter: None,
+                effects: Vec::new(),
+                vibrato: None,
+                tremolo: None,
+            };
+            ExpressiveSynth::offline()
+                .generate_synthesized_samples(&params)
+                .unwrap()
+        };
+        // Share of the energy above 5.5kHz, from a 2048-point DFT averaged over 8 frames
+        let high_share = |samples: &[f32]| {
+            const FRAME: usize = 2048;
+            let split = (5500.0 * FRAME as f32 / 44100.0) as usize;
+            let (mut low, mut high) = (0.0f64, 0.0f64);
+            for frame in samples.chunks_exact(FRAME).take(8) {
+                for bin in 1..FRAME / 2 {
+                    let (mut re, mut im) = (0.0f64, 0.0f64);
+                    for (n, &sample) in frame.iter().enumerate() {
+                        let angle = std::f64::consts::TAU * (bin * n) as f64 / FRAME as f64;
+                        re += sample as f64 * angle.cos();
+                        im -= sample as f64 * angle.sin();
+                    }
+                    let power = re * re + im * im;
+                    if bin < split {
+                        low += power;
+                    } else {
+                        high += power;
+                    }
+                }
+            }
+            high / (low + high)
+        };
+
+        let white = high_share(&render(NoiseColor::White));
+        let pink = high_share(&render(NoiseColor::Pink));
+        let brown = high_share(&render(NoiseColor::Brown));
+        // White noise spreads evenly, so about three quarters of it sits above 5.5kHz
+        assert!(white > 0.65, "white high-frequency share {}", white);
+        assert!(pink < white / 2.0, "pink {} vs white {}", pink, white);
+        assert!(brown < pink / 2.0, "brown {} vs pink {}", brown, pink);
+        assert_eq!(NoiseColor::from_name("pink"), Some(NoiseColor::Pink));
+        assert_eq!(NoiseColor::Brown.name(), "brown");
+    }
+}
@@ -1,9 +1,45 @@
-use crate::expressive::{SynthParams, SynthType};
+use crate::expressive::{SynthParams, SynthType, sawtooth, square};
 use anyhow::Result;
 
-/// Maximum number of simultaneous voices
+/// Default maximum number of simultaneous voices
 pub const MAX_VOICES: usize = 32;
 
+/// Highest voice limit `set_max_voices` accepts
+const MAX_VOICE_LIMIT: usize = 256;
+
+/// Default anti-click fade applied to a voice that is retriggered or stolen (seconds)
+pub const DEFAULT_RETRIGGER_FADE_TIME: f32 = 0.003;
+
+/// Allowed range for the retrigger fade time (1-5ms)
+const RETRIGGER_FADE_RANGE: (f32, f32) = (0.001, 0.005);
+
+/// Fade every voice gets over the last moment of its duration, so short releases don't cut off
+const NOTE_OFF_FADE_TIME: f32 = 0.001;
+
+/// Offset between note-offs that land on the same instant on one channel (seconds)
+const NOTE_OFF_STAGGER: f32 = 0.0001;
+
+/// Most note-offs staggered apart; later ones share the last offset, keeping it under 1ms
+const MAX_STAGGERED_NOTE_OFFS: usize = 8;
+
+/// Extra length for a note whose note-off coincides with `simultaneous` earlier ones on its
+/// channel (a stabbed chord), so their release edges don't sum into one click
+pub fn note_off_stagger(simultaneous: usize) -> f32 {
+    simultaneous.min(MAX_STAGGERED_NOTE_OFFS) as f32 * NOTE_OFF_STAGGER
+}
+
+/// Micro-fade the end of a rendered note to zero over `NOTE_OFF_FADE_TIME`, in case its
+/// release is too short to get there
+pub fn apply_note_off_fade(samples: &mut [f32], sample_rate: f32) {
+    let fade = ((NOTE_OFF_FADE_TIME * sample_rate) as usize)
+        .max(1)
+        .min(samples.len());
+    let fade_start = samples.len() - fade;
+    for (index, sample) in samples[fade_start..].iter_mut().enumerate() {
+        *sample *= (fade - 1 - index) as f32 / fade as f32;
+    }
+}
+
 /// Voice state management
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum VoiceState {
@@ -49,6 +85,10 @@ pub struct SynthVoice {
     pub filter_state: FilterState,
     /// Effect state for stateful effects
     pub effect_state: EffectState,
+    /// Time at which an anti-click fade-out started (set when retriggered or stolen)
+    pub fade_out_start: Option<f64>,
+    /// Scheduled note-off time, before any de-click staggering
+    pub note_off_time: f64,
 }
 
 /// Filter state for maintaining filter memory
@@ -89,19 +129,35 @@ pub struct PolyphonicVoiceManager {
     next_voice_id: usize,
     /// Current global time
     current_time: f64,
-    /// Voice allocation strategy
-    allocation_strategy: VoiceAllocationStrategy,
+    /// Most voices that may sound at once before one is stolen
+    max_voices: usize,
+    /// Which voice is stolen when the limit is reached
+    steal_policy: VoiceStealPolicy,
+    /// Fade-out time for voices that are retriggered or stolen (seconds)
+    retrigger_fade_time: f32,
 }
 
-#[derive(Debug, Clone)]
+/// Which voice gives way when a new one is allocated at the voice limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
-pub enum VoiceAllocationStrategy {
-    /// Steal oldest voice when full
-    OldestFirst,
-    /// Steal voice with lowest priority
+pub enum VoiceStealPolicy {
+    /// Steal the voice that started first
+    Oldest,
+    /// Steal the voice with the lowest current level
+    Quietest,
+    /// Steal the voice whose note ends soonest
+    NearestRelease,
+    /// Steal the lowest-priority voice, and only one below the new voice's priority
     LowestPriority,
-    /// Steal voice with lowest volume
-    LowestVolume,
+}
+
+/// Outcome of allocating a voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceAllocation {
+    /// ID of the new voice
+    pub voice_id: usize,
+    /// ID of the voice stolen to make room for it, if the limit was reached
+    pub stolen: Option<usize>,
 }
 
 impl PolyphonicVoiceManager {
@@ -111,11 +167,14 @@ impl PolyphonicVoiceManager {
             sample_rate,
             next_voice_id: 0,
             current_time: 0.0,
-            allocation_strategy: VoiceAllocationStrategy::OldestFirst,
+            max_voices: MAX_VOICES,
+            steal_policy: VoiceStealPolicy::Oldest,
+            retrigger_fade_time: DEFAULT_RETRIGGER_FADE_TIME,
         }
     }
 
-    /// Allocate a new voice for the given parameters
+    /// Allocate a new voice for the given parameters. At the voice limit one voice is stolen
+    /// by the steal policy and faded out; its ID is returned alongside the new voice's.
     pub fn allocate_voice(
         &mut self,
         params: SynthParams,
@@ -123,29 +182,70 @@ impl PolyphonicVoiceManager {
         note: Option<u8>,
         channel: u8,
         priority: u8,
-    ) -> Result<usize> {
+    ) -> Result<VoiceAllocation> {
         let voice_id = self.next_voice_id;
         self.next_voice_id += 1;
 
-        // If we're at max voices, steal a voice
-        if self.voices.len() >= MAX_VOICES {
-            self.steal_voice(priority)?;
+        // Retriggering a sounding note fades the previous voice out instead of stacking it
+        let mut fading = false;
+        if let Some(note_number) = note {
+            for voice in self.voices.iter_mut().filter(|v| {
+                v.note == Some(note_number)
+                    && v.channel == channel
+                    && v.state != VoiceState::Idle
+                    && v.fade_out_start.is_none()
+            }) {
+                voice.fade_out_start = Some(start_time);
+                fading = true;
+                tracing::debug!("Retriggered voice {} for note {}", voice.id, note_number);
+            }
         }
 
+        // If we're at max voices, steal a voice
+        let stolen = if self.sounding_voice_count() >= self.max_voices {
+            self.steal_voice(priority, start_time)?
+        } else {
+            None
+        };
+        fading |= stolen.is_some();
+
+        // Hold the new attack back until the previous output has ramped to zero
+        let start_time = if fading {
+            start_time + self.retrigger_fade_time as f64
+        } else {
+            start_time
+        };
+
+        // Stagger note-offs that coincide with others on this channel
+        let note_off_time = start_time + params.duration as f64;
+        let simultaneous = self
+            .voices
+            .iter()
+            .filter(|v| {
+                v.channel == channel
+                    && v.state != VoiceState::Idle
+                    && v.fade_out_start.is_none()
+                    && (v.note_off_time - note_off_time).abs() < 0.5 / self.sample_rate as f64
+            })
+            .count();
+        let duration = params.duration + note_off_stagger(simultaneous);
+
         let voice = SynthVoice {
             id: voice_id,
             state: VoiceState::Attack,
             params: params.clone(),
             time: 0.0,
             start_time,
-            duration: params.duration,
+            duration,
             envelope_value: 0.0,
             priority,
             note,
             channel,
-            oscillator_phase: 0.0,
+            oscillator_phase: params.phase.rem_euclid(1.0) * 2.0 * std::f32::consts::PI,
             filter_state: FilterState::default(),
             effect_state: EffectState::default(),
+            fade_out_start: None,
+            note_off_time,
         };
 
         self.voices.push(voice);
@@ -155,7 +255,7 @@ impl PolyphonicVoiceManager {
             note,
             channel
         );
-        Ok(voice_id)
+        Ok(VoiceAllocation { voice_id, stolen })
     }
 
     /// Release a voice (trigger release phase)
@@ -190,10 +290,19 @@ impl PolyphonicVoiceManager {
         }
     }
 
+    /// Immediately drop every voice, sounding or scheduled (used by panic/all-notes-off)
+    pub fn all_notes_off(&mut self) {
+        if !self.voices.is_empty() {
+            tracing::debug!("All notes off: dropping {} voices", self.voices.len());
+        }
+        self.voices.clear();
+    }
+
     /// Process all voices and generate audio samples
     pub fn process_voices(&mut self, dt: f32) -> f32 {
         self.current_time += dt as f64;
         let mut output = 0.0;
+        let fade_time = self.retrigger_fade_time as f64;
 
         // Process voices in a single pass to avoid borrowing issues
         for voice in &mut self.voices {
@@ -213,7 +322,7 @@ impl PolyphonicVoiceManager {
                 VoiceState::Idle => 0.0,
                 VoiceState::Attack => {
                     if env.attack > 0.0 {
-                        (t / env.attack).min(1.0)
+                        env.curve.shape(t / env.attack)
                     } else {
                         1.0
                     }
@@ -221,7 +330,7 @@ impl PolyphonicVoiceManager {
                 VoiceState::Decay => {
                     let attack_time = env.attack;
                     let decay_progress = (t - attack_time) / env.decay.max(0.001);
-                    1.0 - decay_progress.min(1.0) * (1.0 - env.sustain)
+                    1.0 - env.curve.shape(decay_progress) * (1.0 - env.sustain)
                 }
                 VoiceState::Sustain => env.sustain,
                 VoiceState::Release => {
@@ -229,7 +338,7 @@ impl PolyphonicVoiceManager {
                     let total_duration = voice.duration;
                     let release_start_time = total_duration - env.release;
                     let release_progress = (t - release_start_time) / env.release.max(0.001);
-                    env.sustain * (1.0 - release_progress.min(1.0))
+                    env.sustain * (1.0 - env.curve.shape(release_progress))
                 }
             };
 
@@ -261,13 +370,31 @@ impl PolyphonicVoiceManager {
                 }
             }
 
+            // Anti-click fade for retriggered or stolen voices
+            let fade_gain = match voice.fade_out_start {
+                Some(fade_start) if self.current_time >= fade_start => {
+                    let fade_progress = (self.current_time - fade_start) / fade_time;
+                    if fade_progress >= 1.0 {
+                        voice.state = VoiceState::Idle;
+                    }
+                    (1.0 - fade_progress).max(0.0) as f32
+                }
+                _ => 1.0,
+            };
+
+            // Micro-fade into the note-off in case the release is too short to reach zero
+            let end_fade = ((voice.duration - t) / NOTE_OFF_FADE_TIME).clamp(0.0, 1.0);
+
             // Skip idle voices
             if voice.state == VoiceState::Idle {
                 continue;
             }
 
             // Generate sample for this voice - inline to avoid borrowing issues
-            let freq = voice.params.frequency;
+            let freq = match voice.params.vibrato {
+                Some(vibrato) => voice.params.frequency * vibrato.pitch_ratio_at(voice.time),
+                None => voice.params.frequency,
+            };
             let phase_increment = 2.0 * std::f32::consts::PI * freq * dt;
             voice.oscillator_phase += phase_increment;
 
@@ -279,17 +406,16 @@ impl PolyphonicVoiceManager {
             // Generate sample based on synthesis type
             let mut sample = match &voice.params.synth_type {
                 SynthType::Sine => voice.oscillator_phase.sin(),
-                SynthType::Square { pulse_width } => {
+                SynthType::Square { pulse_width, pwm } => {
                     let normalized_phase = voice.oscillator_phase / (2.0 * std::f32::consts::PI);
-                    if normalized_phase < *pulse_width {
-                        1.0
-                    } else {
-                        -1.0
-                    }
+                    let width = pwm.map_or(*pulse_width, |pwm| {
+                        pwm.pulse_width_at(*pulse_width, voice.time)
+                    });
+                    square(normalized_phase, width, freq, self.sample_rate)
                 }
                 SynthType::Sawtooth => {
                     let normalized_phase = voice.oscillator_phase / (2.0 * std::f32::consts::PI);
-                    2.0 * normalized_phase - 1.0
+                    sawtooth(normalized_phase, freq, self.sample_rate)
                 }
                 SynthType::Triangle => {
                     let normalized_phase = voice.oscillator_phase / (2.0 * std::f32::consts::PI);
@@ -365,8 +491,12 @@ impl PolyphonicVoiceManager {
                 };
             }
 
+            if let Some(tremolo) = voice.params.tremolo {
+                sample *= tremolo.gain_at(voice.time);
+            }
+
             // Apply envelope and amplitude and add to output
-            output += sample * voice.envelope_value * voice.params.amplitude;
+            output += sample * voice.envelope_value * voice.params.amplitude * fade_gain * end_fade;
         }
 
         // Clean up idle voices
@@ -375,48 +505,70 @@ impl PolyphonicVoiceManager {
         output
     }
 
-    /// Steal a voice when all voices are in use
-    fn steal_voice(&mut self, new_priority: u8) -> Result<()> {
-        let steal_index = match self.allocation_strategy {
-            VoiceAllocationStrategy::OldestFirst => {
+    /// Steal a voice when all voices are in use, fading it out from `fade_start`.
+    /// Returns the stolen voice's ID, if any.
+    fn steal_voice(&mut self, new_priority: u8, fade_start: f64) -> Result<Option<usize>> {
+        // Voices that are already fading out can't be stolen again
+        let candidates = self
+            .voices
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.fade_out_start.is_none());
+
+        let steal_index = match self.steal_policy {
+            VoiceStealPolicy::Oldest => {
                 // Find the oldest voice (lowest start_time)
-                self.voices
-                    .iter()
-                    .enumerate()
-                    .min_by(|(_, a), (_, b)| a.start_time.partial_cmp(&b.start_time).unwrap())
+                candidates
+                    .min_by(|(_, a), (_, b)| a.start_time.total_cmp(&b.start_time))
                     .map(|(i, _)| i)
             }
-            VoiceAllocationStrategy::LowestPriority => {
-                // Find voice with lowest priority
-                self.voices
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, v)| v.priority < new_priority) // Only steal lower priority
-                    .min_by_key(|(_, v)| v.priority)
+            VoiceStealPolicy::Quietest => {
+                // Find the voice with the lowest envelope level times amplitude
+                candidates
+                    .min_by(|(_, a), (_, b)| {
+                        (a.envelope_value * a.params.amplitude)
+                            .total_cmp(&(b.envelope_value * b.params.amplitude))
+                    })
                     .map(|(i, _)| i)
             }
-            VoiceAllocationStrategy::LowestVolume => {
-                // Find voice with lowest envelope value
-                self.voices
-                    .iter()
-                    .enumerate()
+            VoiceStealPolicy::NearestRelease => {
+                // Find the voice whose note ends first
+                candidates
                     .min_by(|(_, a), (_, b)| {
-                        a.envelope_value.partial_cmp(&b.envelope_value).unwrap()
+                        (a.start_time + a.duration as f64)
+                            .total_cmp(&(b.start_time + b.duration as f64))
                     })
                     .map(|(i, _)| i)
             }
+            VoiceStealPolicy::LowestPriority => {
+                // Find voice with lowest priority
+                candidates
+                    .filter(|(_, v)| v.priority < new_priority) // Only steal lower priority
+                    .min_by_key(|(_, v)| v.priority)
+                    .map(|(i, _)| i)
+            }
         };
 
         if let Some(index) = steal_index {
-            let stolen_voice = self.voices.remove(index);
+            let stolen_voice = &mut self.voices[index];
+            stolen_voice.fade_out_start = Some(fade_start);
             tracing::debug!(
                 "Stole voice {} (priority {})",
                 stolen_voice.id,
                 stolen_voice.priority
             );
+            return Ok(Some(stolen_voice.id));
         }
 
-        Ok(())
+        Ok(None)
+    }
+
+    /// Number of voices that still count towards the polyphony limit (not fading out)
+    fn sounding_voice_count(&self) -> usize {
+        self.voices
+            .iter()
+            .filter(|v| v.state != VoiceState::Idle && v.fade_out_start.is_none())
+            .count()
     }
 
     /// Get number of active voices
@@ -427,6 +579,18 @@ impl PolyphonicVoiceManager {
             .count()
     }
 
+    /// Most voices that may sound at once before one is stolen
+    #[allow(dead_code)]
+    pub fn max_voices(&self) -> usize {
+        self.max_voices
+    }
+
+    /// Which voice is stolen when the limit is reached
+    #[allow(dead_code)]
+    pub fn steal_policy(&self) -> VoiceStealPolicy {
+        self.steal_policy
+    }
+
     /// Get voice information for debugging
     pub fn get_voice_info(&self) -> Vec<(usize, VoiceState, Option<u8>, u8)> {
         self.voices
@@ -456,18 +620,32 @@ impl PolyphonicVoiceManager {
             total_voices,
             active_voices,
             idle_voices: total_voices - active_voices,
-            max_voices: MAX_VOICES,
-            voice_utilization: (active_voices as f32 / MAX_VOICES as f32) * 100.0,
+            max_voices: self.max_voices,
+            voice_utilization: (active_voices as f32 / self.max_voices as f32) * 100.0,
             voice_states,
-            allocation_strategy: self.allocation_strategy.clone(),
+            steal_policy: self.steal_policy,
         }
     }
 
-    /// Set voice allocation strategy
+    /// Set the voice limit (clamped to 1-256). Lowering it below the voices already sounding
+    /// doesn't cut them; each later allocation steals one until the count is back under it.
     #[allow(dead_code)]
-    pub fn set_allocation_strategy(&mut self, strategy: VoiceAllocationStrategy) {
-        self.allocation_strategy = strategy.clone();
-        tracing::info!("Voice allocation strategy changed to {:?}", strategy);
+    pub fn set_max_voices(&mut self, max_voices: usize) {
+        self.max_voices = max_voices.clamp(1, MAX_VOICE_LIMIT);
+        tracing::info!("Voice limit changed to {}", self.max_voices);
+    }
+
+    /// Set which voice is stolen when the limit is reached
+    #[allow(dead_code)]
+    pub fn set_steal_policy(&mut self, policy: VoiceStealPolicy) {
+        self.steal_policy = policy;
+        tracing::info!("Voice steal policy changed to {:?}", policy);
+    }
+
+    /// Set the anti-click fade time for retriggered or stolen voices (clamped to 1-5ms)
+    #[allow(dead_code)]
+    pub fn set_retrigger_fade_time(&mut self, seconds: f32) {
+        self.retrigger_fade_time = seconds.clamp(RETRIGGER_FADE_RANGE.0, RETRIGGER_FADE_RANGE.1);
     }
 }
 
@@ -481,7 +659,7 @@ pub struct VoiceStatistics {
     pub max_voices: usize,
     pub voice_utilization: f32,
     pub voice_states: std::collections::HashMap<VoiceState, usize>,
-    pub allocation_strategy: VoiceAllocationStrategy,
+    pub steal_policy: VoiceStealPolicy,
 }
 
 impl VoiceStatistics {
@@ -494,7 +672,7 @@ impl VoiceStatistics {
         );
         println!("   💤 Idle voices: {}", self.idle_voices);
         println!("   📈 Voice utilization: {:.1}%", self.voice_utilization);
-        println!("   🎛️  Allocation strategy: {:?}", self.allocation_strategy);
+        println!("   🎛️  Steal policy: {:?}", self.steal_policy);
 
         if !self.voice_states.is_empty() {
             println!("   📋 Voice states breakdown:");
@@ -504,3 +682,269 @@ impl VoiceStatistics {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressive::{EnvelopeCurve, EnvelopeParams};
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    fn sine_params(amplitude: f32) -> SynthParams {
+        SynthParams {
+            synth_type: SynthType::Sine,
+            frequency: 440.0,
+            amplitude,
+            duration: 1.0,
+            phase: 0.0,
+            envelope: EnvelopeParams {
+                attack: 0.0,
+                decay: 0.0,
+                sustain: 1.0,
+                release: 0.1,
+                curve: EnvelopeCurve::Linear,
+            },
+            filter: None,
+            effects: Vec::new(),
+            vibrato: None,
+            tremolo: None,
+        }
+    }
+
+    fn max_step(samples: &[f32]) -> f32 {
+        samples
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_rapid_retrigger_has_no_discontinuity() {
+        let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+        let dt = 1.0 / SAMPLE_RATE;
+        let mut samples = Vec::new();
+
+        // Retrigger the same note every 7ms, mid-cycle
+        for _ in 0..10 {
+            let now = samples.len() as f64 * dt as f64;
+            manager
+                .allocate_voice(sine_params(0.8), now, Some(60), 0, 100)
+                .unwrap();
+            for _ in 0..(0.007 * SAMPLE_RATE) as usize {
+                samples.push(manager.process_voices(dt));
+            }
+        }
+
+        // A 440Hz sine at 0.8 moves at most ~0.05 per sample
+        assert!(max_step(&samples) < 0.1, "click of {}", max_step(&samples));
+        assert!(manager.active_voice_count() <= 2);
+    }
+
+    #[test]
+    fn test_stolen_voice_fades_out() {
+        let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+        let dt = 1.0 / SAMPLE_RATE;
+
+        // Oldest voice is the only audible one, so stealing it would expose a cut
+        manager
+            .allocate_voice(sine_params(0.8), 0.0, Some(40), 0, 100)
+            .unwrap();
+        for i in 1..MAX_VOICES {
+            manager
+                .allocate_voice(sine_params(0.0), 0.0, Some(40 + i as u8), 0, 100)
+                .unwrap();
+        }
+
+        // Advance to near the sine peak before stealing
+        let mut samples: Vec<f32> = (0..25).map(|_| manager.process_voices(dt)).collect();
+        manager
+            .allocate_voice(sine_params(0.0), 25.0 * dt as f64, Some(100), 0, 100)
+            .unwrap();
+        samples.extend((0..500).map(|_| manager.process_voices(dt)));
+
+        assert!(max_step(&samples) < 0.1, "click of {}", max_step(&samples));
+        assert_eq!(manager.active_voice_count(), MAX_VOICES);
+    }
+
+    #[test]
+    fn test_stabbed_chord_release_has_no_discontinuity() {
+        let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+        let dt = 1.0 / SAMPLE_RATE;
+
+        // Six-note chord with no release, every note ending on the same sample
+        for (index, frequency) in [261.6, 329.6, 392.0, 523.3, 659.3, 784.0]
+            .into_iter()
+            .enumerate()
+        {
+            let params = SynthParams {
+                frequency,
+                duration: 0.1,
+                envelope: EnvelopeParams {
+                    attack: 0.0,
+                    decay: 0.0,
+                    sustain: 1.0,
+                    release: 0.0,
+                    curve: EnvelopeCurve::Linear,
+                },
+                ..sine_params(0.15)
+            };
+            manager
+                .allocate_voice(params, 0.0, Some(60 + index as u8), 0, 100)
+                .unwrap();
+        }
+        let samples: Vec<f32> = (0..(0.12 * SAMPLE_RATE) as usize)
+            .map(|_| manager.process_voices(dt))
+            .collect();
+
+        // Six sines at 0.15 move at most ~0.07 per sample between them
+        let release = (0.09 * SAMPLE_RATE) as usize..(0.11 * SAMPLE_RATE) as usize;
+        assert!(
+            samples[release.clone()].iter().any(|s| s.abs() > 0.3),
+            "chord should be sounding up to the release"
+        );
+        assert!(
+            max_step(&samples[release.clone()]) < 0.1,
+            "click of {}",
+            max_step(&samples[release])
+        );
+        assert_eq!(manager.active_voice_count(), 0);
+    }
+
+    #[test]
+    fn test_all_notes_off_clears_voices() {
+        let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+        for note in 60..64 {
+            manager
+                .allocate_voice(sine_params(0.5), 0.0, Some(note), 0, 100)
+                .unwrap();
+        }
+        for _ in 0..100 {
+            manager.process_voices(1.0 / SAMPLE_RATE);
+        }
+        assert_eq!(manager.active_voice_count(), 4);
+
+        manager.all_notes_off();
+        assert_eq!(manager.active_voice_count(), 0);
+        assert_eq!(manager.process_voices(1.0 / SAMPLE_RATE), 0.0);
+    }
+
+    #[test]
+    fn test_retrigger_fade_time_is_clamped() {
+        let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+        manager.set_retrigger_fade_time(1.0);
+        assert_eq!(manager.retrigger_fade_time, 0.005);
+        manager.set_retrigger_fade_time(0.0);
+        assert_eq!(manager.retrigger_fade_time, 0.001);
+    }
+
+    #[test]
+    fn test_envelope_curves_differ_at_segment_midpoints() {
+        // Level of a voice 50ms into a 100ms attack, then 50ms into a 100ms decay to 0.5
+        let midpoints = |curve: EnvelopeCurve| {
+            let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+            let params = SynthParams {
+                envelope: EnvelopeParams {
+                    attack: 0.1,
+                    decay: 0.1,
+                    sustain: 0.5,
+                    release: 0.1,
+                    curve,
+                },
+                ..sine_params(0.8)
+            };
+            manager
+                .allocate_voice(params, 0.0, Some(60), 0, 100)
+                .unwrap();
+            let mut level_after = |seconds: f32| {
+                for _ in 0..(seconds * SAMPLE_RATE).round() as usize {
+                    manager.process_voices(1.0 / SAMPLE_RATE);
+                }
+                manager.voices[0].envelope_value
+            };
+            (level_after(0.05), level_after(0.1))
+        };
+
+        let (attack, decay) = midpoints(EnvelopeCurve::Linear);
+        assert!((attack - 0.5).abs() < 0.01, "linear attack {}", attack);
+        assert!((decay - 0.75).abs() < 0.01, "linear decay {}", decay);
+
+        // Exponential segments cover most of the distance in their first half
+        let (attack, decay) = midpoints(EnvelopeCurve::Exponential);
+        assert!(attack > 0.9, "exponential attack {}", attack);
+        assert!(decay < 0.55, "exponential decay {}", decay);
+
+        // Logarithmic segments hold back until their second half
+        let (attack, decay) = midpoints(EnvelopeCurve::Logarithmic);
+        assert!(attack < 0.1, "logarithmic attack {}", attack);
+        assert!(decay > 0.95, "logarithmic decay {}", decay);
+
+        // The offline renderer shapes its envelope the same way
+        let envelope = EnvelopeParams {
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.5,
+            release: 0.1,
+            curve: EnvelopeCurve::Exponential,
+        };
+        assert!((envelope.level(0.05, 1.0) - EnvelopeCurve::Exponential.shape(0.5)).abs() < 1e-6);
+        assert_eq!(
+            EnvelopeCurve::from_name("logarithmic"),
+            Some(EnvelopeCurve::Logarithmic)
+        );
+        assert_eq!(EnvelopeCurve::Exponential.name(), "exponential");
+    }
+
+    #[test]
+    fn test_allocating_past_the_limit_steals_one_voice_per_policy() {
+        // Three voices: the first starts earliest, the second ends soonest, the third is quietest
+        // and least important. A fourth, at 50ms, has to take one's place.
+        let steal = |policy: VoiceStealPolicy, priority: u8| {
+            let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+            manager.set_max_voices(3);
+            manager.set_steal_policy(policy);
+            let voices = [
+                (0.0, 1.0, 0.8, 100),
+                (0.01, 0.2, 0.8, 100),
+                (0.02, 1.0, 0.1, 10),
+            ];
+            for (index, (start, duration, amplitude, priority)) in voices.into_iter().enumerate() {
+                let params = SynthParams {
+                    duration,
+                    ..sine_params(amplitude)
+                };
+                let allocation = manager
+                    .allocate_voice(params, start, Some(60 + index as u8), 0, priority)
+                    .unwrap();
+                assert_eq!(allocation.stolen, None);
+            }
+            for _ in 0..(0.05 * SAMPLE_RATE) as usize {
+                manager.process_voices(1.0 / SAMPLE_RATE);
+            }
+
+            let allocation = manager
+                .allocate_voice(sine_params(0.5), 0.05, Some(72), 0, priority)
+                .unwrap();
+            assert_eq!(allocation.voice_id, 3);
+            let fading: Vec<usize> = manager
+                .voices
+                .iter()
+                .filter(|voice| voice.fade_out_start.is_some())
+                .map(|voice| voice.id)
+                .collect();
+            assert_eq!(fading, allocation.stolen.into_iter().collect::<Vec<_>>());
+            allocation.stolen
+        };
+
+        assert_eq!(steal(VoiceStealPolicy::Oldest, 100), Some(0));
+        assert_eq!(steal(VoiceStealPolicy::NearestRelease, 100), Some(1));
+        assert_eq!(steal(VoiceStealPolicy::Quietest, 100), Some(2));
+        assert_eq!(steal(VoiceStealPolicy::LowestPriority, 50), Some(2));
+        // Nothing ranks below a priority-5 voice, so it is added without a steal
+        assert_eq!(steal(VoiceStealPolicy::LowestPriority, 5), None);
+
+        let mut manager = PolyphonicVoiceManager::new(SAMPLE_RATE);
+        assert_eq!(manager.max_voices(), MAX_VOICES);
+        manager.set_max_voices(0);
+        assert_eq!(manager.max_voices(), 1);
+    }
+}
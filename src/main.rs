@@ -82,7 +82,11 @@ pub enum Commands {
 
     /// Test preset functionality
     #[command(name = "test-presets")]
-    TestPresets,
+    TestPresets {
+        /// Resolve and validate every test sequence without an audio device (for CI)
+        #[arg(long)]
+        dry_run: bool,
+    },
 
     /// Test polyphony validation
     #[command(name = "test-polyphony")]
@@ -121,8 +125,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(Commands::Setup) => {
             setup::run_setup();
         }
-        Some(Commands::TestPresets) => {
-            test_preset_integration().await?;
+        Some(Commands::TestPresets { dry_run }) => {
+            test_preset_integration(dry_run).await?;
         }
         Some(Commands::TestPolyphony) => {
             test_polyphony_validation().await?;
@@ -152,13 +156,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// Test the preset integration with actual audio playback
-async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
+/// Play a test sequence and wait `wait_ms`, or with no player only resolve and validate it
+async fn play_or_resolve(
+    player: Option<&MidiPlayer>,
+    sequence: SimpleSequence,
+    wait_ms: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match player {
+        Some(player) => {
+            player.play_enhanced_mixed(sequence)?;
+            tokio::time::sleep(tokio::time::Duration::from_millis(wait_ms)).await;
+        }
+        None => {
+            let timeline = midi::resolve::dry_run(&sequence)?;
+            let end = timeline
+                .iter()
+                .map(|event| event.start_time + event.duration)
+                .fold(0.0, f64::max);
+            println!(
+                "  ✅ Resolved {} events ending at {:.2}s",
+                timeline.len(),
+                end
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Test the preset integration with actual audio playback, or resolution only with `dry_run`
+async fn test_preset_integration(dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
     println!("🎹 Testing Classic Synthesizer Preset Integration!");
     println!("This will test the complete audio pipeline with presets...\n");
 
-    let player =
-        midi::MidiPlayer::new().map_err(|e| format!("Failed to create MIDI player: {}", e))?;
+    let player = if dry_run {
+        None
+    } else {
+        Some(midi::MidiPlayer::new().map_err(|e| format!("Failed to create MIDI player: {}", e))?)
+    };
 
     // Test 0: Specific test for reported non-working presets
     println!("🔧 Test 0: Testing reported problematic presets");
@@ -178,9 +212,9 @@ async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
-    player.play_enhanced_mixed(jp8_sequence)?;
-    tokio::time::sleep(tokio::time::Duration::from_millis(3500)).await;
+    play_or_resolve(player.as_ref(), jp8_sequence, 3500).await?;
 
     println!("  🎹 Testing DX7 E.Piano...");
     let dx7_sequence = SimpleSequence {
@@ -197,9 +231,9 @@ async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
-    player.play_enhanced_mixed(dx7_sequence)?;
-    tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
+    play_or_resolve(player.as_ref(), dx7_sequence, 2500).await?;
 
     println!("✅ Problematic preset test completed\n");
 
@@ -233,10 +267,10 @@ async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
 
-    player.play_enhanced_mixed(minimoog_sequence)?;
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    play_or_resolve(player.as_ref(), minimoog_sequence, 500).await?;
 
     // Test 2: Random preset from bass category
     println!("🎵 Test 2: Playing random bass preset");
@@ -254,10 +288,10 @@ async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
 
-    player.play_enhanced_mixed(random_bass_sequence)?;
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    play_or_resolve(player.as_ref(), random_bass_sequence, 500).await?;
 
     // Test 3: Preset with variation
     println!("🎵 Test 3: Playing TB-303 Acid preset with squelchy variation");
@@ -276,10 +310,10 @@ async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
 
-    player.play_enhanced_mixed(acid_sequence)?;
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    play_or_resolve(player.as_ref(), acid_sequence, 500).await?;
 
     // Test 4: Multiple presets together
     println!("🎵 Test 4: Playing multiple presets together");
@@ -338,10 +372,10 @@ async fn test_preset_integration() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
 
-    player.play_enhanced_mixed(multi_preset_sequence)?;
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    play_or_resolve(player.as_ref(), multi_preset_sequence, 500).await?;
 
     println!("✅ All preset tests completed successfully!");
     println!("🎉 The classic synthesizer preset system is fully operational!");
@@ -361,7 +395,9 @@ async fn test_polyphony_validation() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔧 Test 1: Voice Manager Unit Tests");
     println!("Testing voice manager internals directly...");
 
-    use crate::expressive::{EnvelopeParams, PolyphonicVoiceManager, SynthParams, SynthType};
+    use crate::expressive::{
+        EnvelopeCurve, EnvelopeParams, PolyphonicVoiceManager, SynthParams, SynthType,
+    };
 
     let mut voice_manager = PolyphonicVoiceManager::new(44100.0);
 
@@ -371,21 +407,25 @@ async fn test_polyphony_validation() -> Result<(), Box<dyn std::error::Error>> {
         frequency: 440.0,
         amplitude: 0.5,
         duration: 1.0,
+        phase: 0.0,
         envelope: EnvelopeParams {
             attack: 0.1,
             decay: 0.2,
             sustain: 0.7,
             release: 0.3,
+            curve: EnvelopeCurve::Linear,
         },
         filter: None,
         effects: Vec::new(),
+        vibrato: None,
+        tremolo: None,
     };
 
     println!("  🧪 Testing basic voice allocation...");
-    let voice_id = voice_manager
+    let allocation = voice_manager
         .allocate_voice(synth_params.clone(), 0.0, Some(60), 0, 100)
         .map_err(|e| format!("Voice allocation failed: {}", e))?;
-    println!("     ✅ Allocated voice ID: {}", voice_id);
+    println!("     ✅ Allocated voice ID: {}", allocation.voice_id);
 
     // Voice count tracking
     println!("  🧪 Testing voice count tracking...");
@@ -478,7 +518,11 @@ async fn test_polyphony_validation() -> Result<(), Box<dyn std::error::Error>> {
         });
     }
 
-    let sequence = SimpleSequence { notes, tempo: 120 };
+    let sequence = SimpleSequence {
+        notes,
+        tempo: 120,
+        ..Default::default()
+    };
     println!(
         "▶️  Playing chord progression with {} total notes (up to 8 simultaneous)",
         sequence.notes.len()
@@ -512,7 +556,11 @@ async fn test_polyphony_validation() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let sequence = SimpleSequence { notes, tempo: 120 };
+    let sequence = SimpleSequence {
+        notes,
+        tempo: 120,
+        ..Default::default()
+    };
     println!(
         "▶️  Playing fast arpeggios with {} notes (testing voice stealing)",
         sequence.notes.len()
@@ -575,7 +623,11 @@ async fn test_polyphony_validation() -> Result<(), Box<dyn std::error::Error>> {
         },
     ];
 
-    let sequence = SimpleSequence { notes, tempo: 120 };
+    let sequence = SimpleSequence {
+        notes,
+        tempo: 120,
+        ..Default::default()
+    };
     println!(
         "▶️  Playing mixed audio sequence with {} notes (MIDI + Presets + R2D2 + Synthesis)",
         sequence.notes.len()
@@ -619,6 +671,7 @@ async fn test_dx7_debugging() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(fm_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
@@ -637,6 +690,7 @@ async fn test_dx7_debugging() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(dx7_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
@@ -655,6 +709,7 @@ async fn test_dx7_debugging() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(moog_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
@@ -673,6 +728,7 @@ async fn test_dx7_debugging() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(dx7_keys_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
@@ -729,6 +785,7 @@ async fn test_enhanced_pads() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(jp8_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -759,6 +816,7 @@ async fn test_enhanced_pads() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(ob_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -777,6 +835,7 @@ async fn test_enhanced_pads() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(d50_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -795,6 +854,7 @@ async fn test_enhanced_pads() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(space_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -838,6 +898,7 @@ async fn test_enhanced_pads() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(mixed_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -880,6 +941,7 @@ async fn test_volume_consistency() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(bass_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -897,6 +959,7 @@ async fn test_volume_consistency() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(pad_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -914,6 +977,7 @@ async fn test_volume_consistency() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(keys_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -931,6 +995,7 @@ async fn test_volume_consistency() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(effects_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(750)).await;
@@ -974,6 +1039,7 @@ async fn test_volume_consistency() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(mixed_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -1014,11 +1080,15 @@ impl Default for SimpleNote {
             preset_category: None,
             preset_variation: None,
             preset_random: None,
+            preset_randomize_amount: None,
+            preset_randomize_seed: None,
+            preset_morph: None,
             r2d2_emotion: None,
             r2d2_intensity: None,
             r2d2_complexity: None,
             r2d2_pitch_range: None,
             r2d2_context: None,
+            r2d2_voice: None,
             synth_type: None,
             synth_frequency: None,
             synth_amplitude: None,
@@ -1026,18 +1096,38 @@ impl Default for SimpleNote {
             synth_decay: None,
             synth_sustain: None,
             synth_release: None,
+            synth_envelope_curve: None,
             synth_filter_type: None,
             synth_filter_cutoff: None,
             synth_filter_resonance: None,
+            synth_filter_soft_start: None,
             synth_modulation_index: None,
             synth_modulator_freq: None,
             synth_pulse_width: None,
+            synth_noise_color: None,
+            synth_pwm: None,
+            synth_phase: None,
+            synth_vibrato_rate: None,
+            synth_vibrato_depth: None,
+            synth_tremolo_rate: None,
+            synth_tremolo_depth: None,
             synth_chorus: None,
             synth_reverb: None,
             synth_delay: None,
             synth_delay_time: None,
             synth_grain_size: None,
             synth_texture_roughness: None,
+            synth_morph_position: None,
+            synth_morph_end: None,
+            synth_drone_harmonics: None,
+            synth_drone_evolution_rate: None,
+            synth_drone_detune: None,
+            beats: None,
+            pre_roll: None,
+            roll_accelerate: None,
+            articulation: None,
+            tremolo_pick: None,
+            snap_to_chord: false,
             effects: None,
             effects_preset: None,
         }
@@ -1068,6 +1158,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(kick_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1200)).await;
@@ -1088,6 +1179,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(snare_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(700)).await;
@@ -1108,6 +1200,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(hihat_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
@@ -1128,6 +1221,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(cymbal_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(2200)).await;
@@ -1148,6 +1242,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(hihat808_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
@@ -1168,6 +1263,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(custom_kick_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(1400)).await;
@@ -1277,6 +1373,7 @@ async fn test_drum_synthesis() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(pattern_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
@@ -1311,6 +1408,7 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(dry_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4000)).await;
@@ -1330,13 +1428,17 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
                     dampening: 0.1, // Minimal dampening
                     wet_level: 0.8, // Very wet signal
                     pre_delay: 0.1, // Long pre-delay
+                    modulation: 0.15,
+                    algorithm: crate::midi::ReverbAlgorithm::Schroeder,
                 },
                 intensity: 1.0, // Maximum intensity
                 enabled: true,
+                wet_only: false,
             }]),
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(reverb_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(6000)).await; // Longer to hear reverb tail
@@ -1359,10 +1461,12 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
                 },
                 intensity: 1.0, // Maximum intensity
                 enabled: true,
+                wet_only: false,
             }]),
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(chorus_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4000)).await;
@@ -1384,10 +1488,12 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
                 },
                 intensity: 1.0, // Maximum intensity
                 enabled: true,
+                wet_only: false,
             }]),
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(distortion_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4000)).await;
@@ -1410,10 +1516,12 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
                 },
                 intensity: 1.0, // Maximum intensity
                 enabled: true,
+                wet_only: false,
             }]),
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(delay_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(6000)).await; // Longer to hear delay repeats
@@ -1430,6 +1538,7 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(acid_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4000)).await;
@@ -1449,6 +1558,7 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(effects_preset_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4000)).await;
@@ -1471,6 +1581,7 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(dry_pad_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4500)).await;
@@ -1490,6 +1601,7 @@ async fn test_effects_system() -> Result<(), Box<dyn std::error::Error>> {
             ..Default::default()
         }],
         tempo: 120,
+        ..Default::default()
     };
     player.play_enhanced_mixed(wet_pad_sequence)?;
     tokio::time::sleep(tokio::time::Duration::from_millis(4500)).await;
@@ -0,0 +1,645 @@
+use super::scales::scale_intervals;
+use super::{SimpleNote, SimpleSequence};
+use std::collections::HashMap;
+
+/// Header fields that only carry information (title, composer, lyrics...) and are skipped
+const INFORMATION_FIELDS: &str = "ABCDFGHNORSTWXZw";
+/// ABC mode names, matched on their first three letters, and the scales they select
+const MODES: [(&str, &str); 10] = [
+    ("maj", "major"),
+    ("ion", "major"),
+    ("min", "minor"),
+    ("aeo", "minor"),
+    ("m", "minor"),
+    ("dor", "dorian"),
+    ("phr", "phrygian"),
+    ("lyd", "lydian"),
+    ("mix", "mixolydian"),
+    ("loc", "locrian"),
+];
+/// Pitch classes of the natural notes C-B
+const NATURALS: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+/// Velocity of every parsed note
+const ABC_VELOCITY: u8 = 80;
+/// Longest and shortest length multiplier a note or rest may carry
+const LENGTH_MULTIPLIER_RANGE: (f64, f64) = (1.0 / 64.0, 64.0);
+
+/// Index 0-6 (C-B) of a note letter in either case
+fn letter_index(letter: char) -> Option<usize> {
+    "CDEFGAB".find(letter.to_ascii_uppercase())
+}
+
+/// Semitone offset per letter (C-B) that a `K:` field such as "G", "F#m" or "Ddor" sets
+fn key_signature(value: &str) -> Result<[i8; 7], String> {
+    let value = value.trim();
+    let mut chars = value.chars();
+    let tonic = chars
+        .next()
+        .and_then(|letter| letter.is_ascii_uppercase().then_some(letter))
+        .and_then(letter_index)
+        .ok_or_else(|| format!("Unsupported key '{}': use a tonic A-G with an optional #/b and mode, e.g. K:G, K:F#m, K:Ddor", value))?;
+    let rest = chars.as_str();
+    let (shift, rest) = match rest.chars().next() {
+        Some('#') => (1, &rest[1..]),
+        Some('b') => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let mode = rest.trim().to_lowercase();
+    let scale = if mode.is_empty() {
+        "major"
+    } else {
+        MODES
+            .iter()
+            .find(|(prefix, _)| mode.get(..3).unwrap_or(&mode) == *prefix)
+            .map(|(_, scale)| *scale)
+            .ok_or_else(|| format!("Unsupported key mode '{}' in K:{}", mode, value))?
+    };
+    let intervals = scale_intervals(scale)?;
+
+    // Each degree of the mode lands on the next letter up from the tonic
+    let tonic_class = NATURALS[tonic] as i8 + shift;
+    let mut signature = [0i8; 7];
+    for (degree, &interval) in intervals.iter().enumerate() {
+        let letter = (tonic + degree) % 7;
+        let offset = (tonic_class + interval as i8 - NATURALS[letter] as i8).rem_euclid(12);
+        signature[letter] = if offset > 6 { offset - 12 } else { offset };
+    }
+    Ok(signature)
+}
+
+/// A fraction such as "3/4", "1/8" or a bare "120"
+fn parse_fraction(value: &str) -> Option<f64> {
+    match value.trim().split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator: f64 = numerator.trim().parse().ok()?;
+            let denominator: f64 = denominator.trim().parse().ok()?;
+            (numerator > 0.0 && denominator > 0.0).then_some(numerator / denominator)
+        }
+        None => value.trim().parse().ok().filter(|value: &f64| *value > 0.0),
+    }
+}
+
+/// A note or rest, timed in seconds
+struct Event {
+    pitch: Option<u8>,
+    start: f64,
+    length: f64,
+}
+
+/// Reads a single-voice ABC tune line by line, keeping the timing state between notes
+struct AbcParser {
+    key: [i8; 7],
+    /// Accidentals written earlier in the bar, by letter and octave
+    bar_accidentals: HashMap<(usize, i32), i8>,
+    /// `L:` in whole notes, or None for the meter's default
+    unit: Option<f64>,
+    meter: Option<(u32, u32)>,
+    /// Quarter notes per minute
+    tempo: f64,
+    events: Vec<Event>,
+    time: f64,
+    /// Event index and time a `:|` repeats back to
+    repeat_start: (usize, f64),
+    tie: bool,
+    /// Length factor the previous `>` or `<` left for the next note
+    broken: Option<f64>,
+    /// Notes left in the current tuplet and the factor they are played at
+    tuplet: Option<(u32, f64)>,
+}
+
+impl AbcParser {
+    fn new() -> Self {
+        Self {
+            key: [0; 7],
+            bar_accidentals: HashMap::new(),
+            unit: None,
+            meter: None,
+            tempo: 120.0,
+            events: Vec::new(),
+            time: 0.0,
+            repeat_start: (0, 0.0),
+            tie: false,
+            broken: None,
+            tuplet: None,
+        }
+    }
+
+    /// `L:` if set, otherwise 1/16 for meters under 3/4 and 1/8 for the rest
+    fn unit(&self) -> f64 {
+        self.unit.unwrap_or(match self.meter {
+            Some((numerator, denominator)) if (numerator as f64 / denominator as f64) < 0.75 => {
+                1.0 / 16.0
+            }
+            _ => 1.0 / 8.0,
+        })
+    }
+
+    fn seconds_per_whole_note(&self) -> f64 {
+        4.0 * 60.0 / self.tempo
+    }
+
+    fn field(&mut self, name: char, value: &str) -> Result<(), String> {
+        match name {
+            'K' => {
+                self.key = key_signature(value)?;
+                self.bar_accidentals.clear();
+            }
+            'M' => {
+                self.meter = match value.trim() {
+                    "C" => Some((4, 4)),
+                    "C|" => Some((2, 2)),
+                    "none" => None,
+                    meter => {
+                        let parsed = meter.split_once('/').and_then(|(numerator, denominator)| {
+                            Some((
+                                numerator.trim().parse().ok()?,
+                                denominator.trim().parse().ok()?,
+                            ))
+                        });
+                        match parsed {
+                            Some((numerator, denominator)) if numerator > 0 && denominator > 0 => {
+                                Some((numerator, denominator))
+                            }
+                            _ => return Err(format!("Unsupported meter 'M:{}'", meter)),
+                        }
+                    }
+                };
+            }
+            'L' => {
+                self.unit = Some(
+                    parse_fraction(value)
+                        .ok_or_else(|| format!("Unsupported note length 'L:{}'", value.trim()))?,
+                );
+            }
+            'Q' => {
+                // "1/4=120" gives the beat; a bare number counts quarter notes
+                let (beat, bpm) = match value.split_once('=') {
+                    Some((beat, bpm)) => (parse_fraction(beat), parse_fraction(bpm)),
+                    None => (Some(0.25), parse_fraction(value)),
+                };
+                match (beat, bpm) {
+                    (Some(beat), Some(bpm)) => self.tempo = bpm * beat * 4.0,
+                    _ => return Err(format!("Unsupported tempo 'Q:{}'", value.trim())),
+                }
+            }
+            'V' => return Err("Multiple voices (V:) are not supported".to_string()),
+            name if INFORMATION_FIELDS.contains(name) => {}
+            name => return Err(format!("Unsupported field '{}:'", name)),
+        }
+        Ok(())
+    }
+
+    /// Length multiplier after a note: "2", "3/2", "/", "//", "/4". Zero, infinite or
+    /// out-of-range lengths are errors naming the length.
+    fn length(chars: &[char], i: &mut usize) -> Result<f64, String> {
+        let begin = *i;
+        let digits = |i: &mut usize| {
+            let start = *i;
+            while *i < chars.len() && chars[*i].is_ascii_digit() {
+                *i += 1;
+            }
+            chars[start..*i]
+                .iter()
+                .collect::<String>()
+                .parse::<f64>()
+                .ok()
+        };
+        let numerator = digits(i).unwrap_or(1.0);
+        let mut denominator = 1.0;
+        while *i < chars.len() && chars[*i] == '/' {
+            *i += 1;
+            denominator *= digits(i).unwrap_or(2.0);
+        }
+        let multiplier = numerator / denominator;
+        let (shortest, longest) = LENGTH_MULTIPLIER_RANGE;
+        if !(shortest..=longest).contains(&multiplier) {
+            return Err(format!(
+                "Unsupported note length '{}': use a multiplier between 1/64 and 64",
+                chars[begin..*i].iter().collect::<String>()
+            ));
+        }
+        Ok(multiplier)
+    }
+
+    fn note(&mut self, pitch: Option<u8>, multiplier: f64) {
+        let mut length = self.unit() * multiplier * self.seconds_per_whole_note();
+        if let Some(factor) = self.broken.take() {
+            length *= factor;
+        }
+        if let Some((remaining, factor)) = self.tuplet {
+            length *= factor;
+            self.tuplet = (remaining > 1).then_some((remaining - 1, factor));
+        }
+
+        let tied = std::mem::take(&mut self.tie);
+        if let Some(last) = self.events.last_mut()
+            && tied
+            && pitch.is_some()
+            && last.pitch == pitch
+        {
+            last.length += length;
+        } else {
+            self.events.push(Event {
+                pitch,
+                start: self.time,
+                length,
+            });
+        }
+        self.time += length;
+    }
+
+    /// Stretch the previous note by `previous` and leave `next` for the following one
+    fn broken_rhythm(&mut self, previous: f64, next: f64) -> Result<(), String> {
+        let last = self
+            .events
+            .last_mut()
+            .ok_or("Broken rhythm '>' or '<' needs a note before it")?;
+        last.length *= previous;
+        self.time = last.start + last.length;
+        self.broken = Some(next);
+        Ok(())
+    }
+
+    fn bar_line(&mut self, symbol: &str) {
+        self.bar_accidentals.clear();
+        if symbol.starts_with(':') {
+            // Play the section again from the last start repeat (or the top)
+            let (first, start) = self.repeat_start;
+            let section = self.time - start;
+            let repeated: Vec<Event> = self.events[first..]
+                .iter()
+                .map(|event| Event {
+                    pitch: event.pitch,
+                    start: event.start + section,
+                    length: event.length,
+                })
+                .collect();
+            self.events.extend(repeated);
+            self.time += section;
+        }
+        if symbol.ends_with(':') || symbol.contains("||") || symbol.contains(']') {
+            self.repeat_start = (self.events.len(), self.time);
+        }
+    }
+
+    fn body_line(&mut self, chars: &[char]) -> Result<(), (usize, String)> {
+        let mut i = 0;
+        while i < chars.len() {
+            let start = i;
+            let token = |end: usize| {
+                chars[start..end.min(chars.len())]
+                    .iter()
+                    .collect::<String>()
+            };
+            match chars[i] {
+                '%' => break,
+                ' ' | '\t' | '`' | '\\' | 'y' | ')' => i += 1,
+                '"' => {
+                    // Chord symbols and annotations don't sound; skip them
+                    match chars[i + 1..].iter().position(|&c| c == '"') {
+                        Some(end) => i += end + 2,
+                        None => return Err((start, "Unclosed chord symbol '\"'".to_string())),
+                    }
+                }
+                '|' | ':' => {
+                    while i < chars.len() && matches!(chars[i], '|' | ':' | ']') {
+                        i += 1;
+                    }
+                    if i < chars.len() && chars[i].is_ascii_digit() {
+                        return Err((
+                            start,
+                            format!("Unsupported repeat ending '{}'", token(i + 1)),
+                        ));
+                    }
+                    self.bar_line(&token(i));
+                }
+                '[' => match chars.get(i + 1) {
+                    Some('|') => {
+                        i += 2;
+                        while i < chars.len() && matches!(chars[i], '|' | ':' | ']') {
+                            i += 1;
+                        }
+                        self.bar_line(&token(i));
+                    }
+                    Some(c) if c.is_ascii_digit() => {
+                        return Err((
+                            start,
+                            format!("Unsupported repeat ending '{}'", token(i + 2)),
+                        ));
+                    }
+                    Some(c) if c.is_ascii_alphabetic() && chars.get(i + 2) == Some(&':') => {
+                        return Err((
+                            start,
+                            format!(
+                                "Unsupported inline field '{}'; put fields on their own line",
+                                token(i + 3)
+                            ),
+                        ));
+                    }
+                    _ => {
+                        let end = chars[i..]
+                            .iter()
+                            .position(|&c| c == ']')
+                            .map_or(chars.len(), |end| i + end + 1);
+                        return Err((
+                            start,
+                            format!(
+                                "Unsupported chord '{}': only a single melody line can be played",
+                                token(end)
+                            ),
+                        ));
+                    }
+                },
+                '(' => {
+                    i += 1;
+                    if i < chars.len() && chars[i].is_ascii_digit() {
+                        let mut numbers = vec![chars[i].to_digit(10).unwrap_or(3)];
+                        i += 1;
+                        while i + 1 < chars.len()
+                            && chars[i] == ':'
+                            && chars[i + 1].is_ascii_digit()
+                        {
+                            numbers.push(chars[i + 1].to_digit(10).unwrap_or(1));
+                            i += 2;
+                        }
+                        let p = numbers[0];
+                        let compound = self
+                            .meter
+                            .is_some_and(|(numerator, _)| numerator % 3 == 0 && numerator > 3);
+                        let q = numbers.get(1).copied().unwrap_or(match p {
+                            2 | 4 | 8 => 3,
+                            3 | 6 => 2,
+                            _ if compound => 3,
+                            _ => 2,
+                        });
+                        let r = numbers.get(2).copied().unwrap_or(p);
+                        if p < 2 || q == 0 || r == 0 {
+                            return Err((start, format!("Unsupported tuplet '{}'", token(i))));
+                        }
+                        self.tuplet = Some((r, q as f64 / p as f64));
+                    }
+                    // A plain '(' opens a slur, which does not change the notes
+                }
+                '>' | '<' => {
+                    let symbol = chars[i];
+                    let mut count = 0;
+                    while i < chars.len() && chars[i] == symbol {
+                        count += 1;
+                        i += 1;
+                    }
+                    // '>' dots the first note and halves the second; each extra mark doubles it
+                    let short = 0.5f64.powi(count);
+                    let (previous, next) = if symbol == '>' {
+                        (2.0 - short, short)
+                    } else {
+                        (short, 2.0 - short)
+                    };
+                    self.broken_rhythm(previous, next).map_err(|e| (start, e))?;
+                }
+                '-' => {
+                    self.tie = true;
+                    i += 1;
+                }
+                'z' | 'x' => {
+                    i += 1;
+                    let multiplier = Self::length(chars, &mut i).map_err(|e| (start, e))?;
+                    self.note(None, multiplier);
+                }
+                '^' | '_' | '=' | 'A'..='G' | 'a'..='g' => {
+                    let mut accidental = None;
+                    while i < chars.len() && matches!(chars[i], '^' | '_' | '=') {
+                        accidental = Some(
+                            accidental.unwrap_or(0)
+                                + match chars[i] {
+                                    '^' => 1,
+                                    '_' => -1,
+                                    _ => 0,
+                                },
+                        );
+                        i += 1;
+                    }
+                    let Some(letter) = chars.get(i).copied().filter(|c| letter_index(*c).is_some())
+                    else {
+                        return Err((
+                            start,
+                            format!(
+                                "Accidental '{}' must be followed by a note A-G",
+                                token(i + 1)
+                            ),
+                        ));
+                    };
+                    let index = letter_index(letter).unwrap_or(0);
+                    i += 1;
+                    let mut octave = if letter.is_ascii_lowercase() { 1 } else { 0 };
+                    while i < chars.len() && matches!(chars[i], '\'' | ',') {
+                        octave += if chars[i] == '\'' { 1 } else { -1 };
+                        i += 1;
+                    }
+                    let offset = match accidental {
+                        Some(offset) => {
+                            self.bar_accidentals.insert((index, octave), offset);
+                            offset
+                        }
+                        None => self
+                            .bar_accidentals
+                            .get(&(index, octave))
+                            .copied()
+                            .unwrap_or(self.key[index]),
+                    };
+                    let pitch = 60 + NATURALS[index] as i32 + octave * 12 + offset as i32;
+                    if !(0..=127).contains(&pitch) {
+                        return Err((
+                            start,
+                            format!("Note '{}' is outside the MIDI range", token(i)),
+                        ));
+                    }
+                    let multiplier = Self::length(chars, &mut i).map_err(|e| (start, e))?;
+                    self.note(Some(pitch as u8), multiplier);
+                }
+                '{' => return Err((start, "Unsupported grace notes '{'".to_string())),
+                '!' | '+' => {
+                    let end = chars[i + 1..]
+                        .iter()
+                        .position(|&c| c == chars[start])
+                        .map_or(chars.len(), |end| i + end + 2);
+                    return Err((start, format!("Unsupported decoration '{}'", token(end))));
+                }
+                '&' => return Err((start, "Unsupported voice overlay '&'".to_string())),
+                other => return Err((start, format!("Unsupported symbol '{}'", other))),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse a single-voice ABC tune into seconds-timed notes. Supports the K: (key and mode),
+/// M: (meter), L: (unit note length) and Q: (tempo) fields; notes with accidentals, octave
+/// marks and lengths; rests, ties, broken rhythms, tuplets, bar lines and simple repeats.
+/// Chord symbols in quotes and slurs are skipped. Anything else, such as chords, grace
+/// notes, decorations or more than one voice, is rejected with its line and column.
+pub fn parse_abc(input: &str) -> Result<SimpleSequence, String> {
+    let mut parser = AbcParser::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let at = |column: usize, message: String| {
+            format!(
+                "{} at line {}, column {}",
+                message,
+                line_index + 1,
+                column + 1
+            )
+        };
+
+        // Fields are a letter and a colon at the start of a line
+        if chars.len() >= 2 && chars[0].is_ascii_alphabetic() && chars[1] == ':' {
+            let value: String = chars[2..].iter().collect();
+            let value = value.split('%').next().unwrap_or_default();
+            parser.field(chars[0], value).map_err(|e| at(0, e))?;
+            continue;
+        }
+        parser
+            .body_line(&chars)
+            .map_err(|(column, message)| at(column, message))?;
+    }
+
+    if !parser.events.iter().any(|event| event.pitch.is_some()) {
+        return Err("The ABC tune has no notes".to_string());
+    }
+
+    let mut sequence = SimpleSequence::new();
+    sequence.tempo = parser.tempo.round().clamp(1.0, 999.0) as u32;
+    if let Some((numerator, _)) = parser.meter {
+        sequence.beats_per_bar = numerator.clamp(1, 16);
+    }
+    sequence.notes = parser
+        .events
+        .iter()
+        .filter_map(|event| {
+            Some(SimpleNote {
+                note: Some(event.pitch?),
+                velocity: Some(ABC_VELOCITY),
+                start_time: Some(event.start),
+                duration: Some(event.length),
+                ..Default::default()
+            })
+        })
+        .collect();
+    Ok(sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (pitch, start, length) of every note
+    fn notes(sequence: &SimpleSequence) -> Vec<(u8, f64, f64)> {
+        sequence
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.note.unwrap(),
+                    note.start_time.unwrap(),
+                    note.duration.unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tune_with_key_accidentals_and_lengths() {
+        // G major at quarter = 120 in eighths: F is sharp unless a natural says otherwise,
+        // and an accidental holds until the bar line
+        let tune =
+            "X:1\nT:Scale\nM:4/4\nL:1/8\nQ:1/4=120\nK:G\nG2 A B c d e =f | f g'/2 z/2 C,4 g2 |]";
+        let sequence = parse_abc(tune).unwrap();
+        assert_eq!(sequence.tempo, 120);
+        assert_eq!(sequence.beats_per_bar, 4);
+        assert_eq!(
+            notes(&sequence),
+            vec![
+                (67, 0.0, 0.5),
+                (69, 0.5, 0.25),
+                (71, 0.75, 0.25),
+                (72, 1.0, 0.25),
+                (74, 1.25, 0.25),
+                (76, 1.5, 0.25),
+                (77, 1.75, 0.25),
+                (78, 2.0, 0.25),
+                (91, 2.25, 0.125),
+                (48, 2.5, 1.0),
+                (79, 3.5, 0.5),
+            ]
+        );
+
+        // Modes and flats: D dorian has no accidentals, B flat minor has five flats
+        assert_eq!(key_signature("Ddor").unwrap(), [0; 7]);
+        assert_eq!(key_signature("Bbm").unwrap(), [0, -1, -1, 0, -1, -1, -1]);
+        assert_eq!(key_signature("F#").unwrap(), [1, 1, 1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_ties_broken_rhythm_tuplets_and_repeats() {
+        let tune = "M:3/4\nL:1/4\nQ:60\nK:C\n|: C>D (3E/F/G/ | c-c2 :|";
+        let sequence = parse_abc(tune).unwrap();
+        let once = [
+            (60, 0.0, 1.5),
+            (62, 1.5, 0.5),
+            (64, 2.0, 1.0 / 3.0),
+            (65, 2.0 + 1.0 / 3.0, 1.0 / 3.0),
+            (67, 2.0 + 2.0 / 3.0, 1.0 / 3.0),
+            (72, 3.0, 3.0),
+        ];
+        let played = notes(&sequence);
+        assert_eq!(played.len(), 12);
+        // The repeat plays the two bars again, 6 seconds later
+        let repeated = once.map(|(pitch, start, length)| (pitch, start + 6.0, length));
+        for (note, expected) in played.iter().zip(once.iter().chain(&repeated)) {
+            assert_eq!(note.0, expected.0);
+            assert!(
+                (note.1 - expected.1).abs() < 1e-9,
+                "{:?} vs {:?}",
+                note,
+                expected
+            );
+            assert!(
+                (note.2 - expected.2).abs() < 1e-9,
+                "{:?} vs {:?}",
+                note,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_constructs_name_their_position() {
+        let error = parse_abc("K:C\nCDE [CEG] F").unwrap_err();
+        assert!(error.contains("Unsupported chord '[CEG]'"), "{}", error);
+        assert!(error.ends_with("line 2, column 5"), "{}", error);
+
+        let error = parse_abc("K:C\nC !trill!D").unwrap_err();
+        assert!(error.contains("decoration '!trill!'"), "{}", error);
+        assert!(parse_abc("V:1\nK:C\nC").unwrap_err().contains("voices"));
+        assert!(parse_abc("K:C\n{g}A").unwrap_err().contains("grace notes"));
+        assert!(parse_abc("K:C\n\"Am\"z4").unwrap_err().contains("no notes"));
+    }
+
+    #[test]
+    fn test_zero_and_unbounded_lengths_are_rejected() {
+        for (tune, length) in [
+            ("K:C\nD C/0", "/0"),
+            ("K:C\nD C0", "0"),
+            ("K:C\nD z0/2", "0/2"),
+            ("K:C\nD C99999999999999999999999", "99999999999999999999999"),
+        ] {
+            let error = parse_abc(tune).unwrap_err();
+            assert!(
+                error.contains(&format!("Unsupported note length '{}'", length)),
+                "{}",
+                error
+            );
+            assert!(error.ends_with("line 2, column 3"), "{}", error);
+        }
+        assert_eq!(notes(&parse_abc("K:C\nC64").unwrap())[0].2, 64.0 * 0.25);
+    }
+}
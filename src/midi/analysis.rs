@@ -0,0 +1,369 @@
+use fundsp::fft::real_fft;
+use serde::Serialize;
+
+/// Mono loss above which a mix is reported as phase-cancelling. Uncorrelated stereo
+/// (wide reverbs, independent doubles) loses about 3dB when summed, which is normal.
+pub const MONO_LOSS_WARNING_DB: f32 = 6.0;
+
+/// Loss reported when the mono sum cancels to silence
+const MAX_MONO_LOSS_DB: f32 = 96.0;
+
+/// How a stereo mix holds up when summed to mono
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonoCompatibility {
+    /// Average per-channel level of the stereo mix in dBFS
+    pub stereo_rms_db: f32,
+    /// Level of the mono sum (L+R)/2 in dBFS
+    pub mono_rms_db: f32,
+    /// How far the mono sum falls below the stereo level (0 dB for a centered mono signal)
+    pub mono_loss_db: f32,
+}
+
+impl MonoCompatibility {
+    /// Warning text when the mono sum loses enough level to indicate phase cancellation
+    pub fn warning(&self) -> Option<String> {
+        (self.mono_loss_db > MONO_LOSS_WARNING_DB).then(|| {
+            format!(
+                "⚠️ Mono compatibility: the mono sum is {:.1} dB quieter than the stereo mix, so parts of it cancel on mono speakers. Reduce stereo widening or ping-pong delay.",
+                self.mono_loss_db
+            )
+        })
+    }
+}
+
+/// Sum interleaved stereo samples to mono and compare the level against the stereo energy
+pub fn check_mono_compatibility(samples: &[f32]) -> MonoCompatibility {
+    let mut stereo_energy = 0.0f64;
+    let mut mono_energy = 0.0f64;
+    let mut frames = 0usize;
+
+    for frame in samples.chunks_exact(2) {
+        let (left, right) = (frame[0] as f64, frame[1] as f64);
+        stereo_energy += (left * left + right * right) / 2.0;
+        let mono = (left + right) / 2.0;
+        mono_energy += mono * mono;
+        frames += 1;
+    }
+
+    let to_db = |energy: f64| -> f32 {
+        if frames == 0 || energy <= 0.0 {
+            -MAX_MONO_LOSS_DB
+        } else {
+            (10.0 * (energy / frames as f64).log10()) as f32
+        }
+    };
+    let stereo_rms_db = to_db(stereo_energy);
+    let mono_rms_db = to_db(mono_energy);
+    let mono_loss_db = if stereo_energy <= 0.0 {
+        0.0
+    } else {
+        (stereo_rms_db - mono_rms_db).clamp(0.0, MAX_MONO_LOSS_DB)
+    };
+
+    MonoCompatibility {
+        stereo_rms_db,
+        mono_rms_db,
+        mono_loss_db,
+    }
+}
+
+/// Samples per spectrum frame (~46ms at 44.1kHz)
+pub const SPECTRUM_FFT_SIZE: usize = 2048;
+/// Upper bound on frames returned for one render, however long it is
+pub const MAX_SPECTRUM_FRAMES: usize = 256;
+/// Upper bound on frequency bands per frame
+pub const MAX_SPECTRUM_BANDS: usize = 512;
+
+/// Downsampled magnitude spectra over time, ready to draw as a spectrogram
+#[derive(Debug, Clone, Serialize)]
+pub struct Spectrogram {
+    /// Seconds between the starts of consecutive frames
+    pub frame_interval: f32,
+    /// Width of each frequency band in Hz; band `i` covers `i * band_hz..(i + 1) * band_hz`
+    pub band_hz: f32,
+    /// Per-frame band magnitudes in dBFS, lowest band first
+    pub frames: Vec<Vec<f32>>,
+}
+
+/// Hann-windowed FFT frames of the mono sum of interleaved stereo samples, spread evenly
+/// over the render. Each frame's bins are reduced to `bands` equal-width bands by their peak.
+pub fn spectrogram(
+    samples: &[f32],
+    sample_rate: u32,
+    max_frames: usize,
+    bands: usize,
+) -> Spectrogram {
+    let max_frames = max_frames.clamp(1, MAX_SPECTRUM_FRAMES);
+    let bands = bands.clamp(1, MAX_SPECTRUM_BANDS.min(SPECTRUM_FFT_SIZE / 2));
+    let mono: Vec<f32> = samples
+        .chunks_exact(2)
+        .map(|frame| (frame[0] + frame[1]) / 2.0)
+        .collect();
+
+    // Spread at most max_frames windows over the signal, never overlapping more than needed
+    let span = mono.len().saturating_sub(SPECTRUM_FFT_SIZE);
+    let frame_count = (span / (SPECTRUM_FFT_SIZE / 2) + 1).min(max_frames);
+    let hop = if frame_count > 1 {
+        span / (frame_count - 1)
+    } else {
+        0
+    };
+
+    let window: Vec<f32> = (0..SPECTRUM_FFT_SIZE)
+        .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / SPECTRUM_FFT_SIZE as f32).cos())
+        .collect();
+    // A full-scale sine through the Hann window peaks at N/4
+    let full_scale = SPECTRUM_FFT_SIZE as f32 / 4.0;
+    let bins_per_band = (SPECTRUM_FFT_SIZE / 2) / bands;
+
+    let mut input = vec![0.0f32; SPECTRUM_FFT_SIZE];
+    let mut spectrum = vec![Default::default(); SPECTRUM_FFT_SIZE / 2 + 1];
+    let frames = (0..frame_count)
+        .map(|frame| {
+            let start = frame * hop;
+            for (i, sample) in input.iter_mut().enumerate() {
+                *sample = mono.get(start + i).copied().unwrap_or(0.0) * window[i];
+            }
+            real_fft(&input, &mut spectrum);
+
+            (0..bands)
+                .map(|band| {
+                    let peak = spectrum[band * bins_per_band..(band + 1) * bins_per_band]
+                        .iter()
+                        .map(|bin| bin.norm())
+                        .fold(0.0f32, f32::max);
+                    // Floor silence at -120 dBFS
+                    20.0 * (peak / full_scale).max(1e-6).log10()
+                })
+                .collect()
+        })
+        .collect();
+
+    Spectrogram {
+        frame_interval: hop as f32 / sample_rate as f32,
+        band_hz: bins_per_band as f32 * sample_rate as f32 / SPECTRUM_FFT_SIZE as f32,
+        frames,
+    }
+}
+
+/// Loudest and quietest accepted `target_lufs`
+pub const TARGET_LUFS_RANGE: std::ops::RangeInclusive<f32> = -50.0..=-6.0;
+/// Highest peak loudness normalization may raise a render to (-1 dBFS)
+const NORMALIZE_PEAK_CEILING: f32 = 0.891;
+/// Gating block length and hop for integrated loudness (400ms blocks, 75% overlap)
+const LOUDNESS_BLOCK_SECONDS: f64 = 0.4;
+const LOUDNESS_HOP_SECONDS: f64 = 0.1;
+/// Blocks quieter than this never count toward integrated loudness
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Blocks this far below the ungated loudness are dropped as pauses
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+pub fn validate_target_lufs(target: Option<f32>) -> Result<(), String> {
+    match target {
+        Some(target) if !TARGET_LUFS_RANGE.contains(&target) => Err(format!(
+            "target_lufs must be between {} and {} LUFS, got {}",
+            TARGET_LUFS_RANGE.start(),
+            TARGET_LUFS_RANGE.end(),
+            target
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Direct-form biquad used for the K-weighting stages
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Normalize coefficients by a0
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Biquad {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// BS.1770 stage 1: +4 dB high shelf modelling the head above ~1.5kHz
+    fn head_shelf(sample_rate: f64) -> Self {
+        let gain = 10f64.powf(4.0 / 40.0);
+        let w0 = std::f64::consts::TAU * 1500.0 / sample_rate;
+        let alpha = w0.sin() / (2.0 * std::f64::consts::FRAC_1_SQRT_2);
+        let (cos, root) = (w0.cos(), 2.0 * gain.sqrt() * alpha);
+        Biquad::new(
+            [
+                gain * ((gain + 1.0) + (gain - 1.0) * cos + root),
+                -2.0 * gain * ((gain - 1.0) + (gain + 1.0) * cos),
+                gain * ((gain + 1.0) + (gain - 1.0) * cos - root),
+            ],
+            [
+                (gain + 1.0) - (gain - 1.0) * cos + root,
+                2.0 * ((gain - 1.0) - (gain + 1.0) * cos),
+                (gain + 1.0) - (gain - 1.0) * cos - root,
+            ],
+        )
+    }
+
+    /// BS.1770 stage 2: high-pass at ~38Hz discounting sub-bass
+    fn low_cut(sample_rate: f64) -> Self {
+        let w0 = std::f64::consts::TAU * 38.0 / sample_rate;
+        let alpha = w0.sin() / (2.0 * 0.5);
+        let cos = w0.cos();
+        Biquad::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// Integrated loudness of interleaved stereo samples in LUFS: K-weighted, gated mean
+/// energy per BS.1770. `None` when the render is too short or silent to measure.
+pub fn integrated_loudness(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let sample_rate_f = sample_rate as f64;
+    let mut filters: Vec<[Biquad; 2]> = (0..2)
+        .map(|_| {
+            [
+                Biquad::head_shelf(sample_rate_f),
+                Biquad::low_cut(sample_rate_f),
+            ]
+        })
+        .collect();
+    // Per-frame K-weighted energy summed over both channels
+    let weighted: Vec<f64> = samples
+        .chunks_exact(2)
+        .map(|frame| {
+            frame
+                .iter()
+                .zip(filters.iter_mut())
+                .map(|(&sample, [shelf, low_cut])| {
+                    let filtered = low_cut.process(shelf.process(sample as f64));
+                    filtered * filtered
+                })
+                .sum()
+        })
+        .collect();
+
+    let block = (LOUDNESS_BLOCK_SECONDS * sample_rate_f) as usize;
+    let hop = (LOUDNESS_HOP_SECONDS * sample_rate_f) as usize;
+    if block == 0 || weighted.len() < block {
+        return None;
+    }
+    let blocks: Vec<f64> = (0..=(weighted.len() - block) / hop)
+        .map(|index| {
+            weighted[index * hop..index * hop + block]
+                .iter()
+                .sum::<f64>()
+                / block as f64
+        })
+        .collect();
+
+    let loudness = |energy: f64| -0.691 + 10.0 * energy.log10();
+    let gated_mean = |threshold: f64| {
+        let kept: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&energy| energy > 0.0 && loudness(energy) > threshold)
+            .collect();
+        (!kept.is_empty()).then(|| kept.iter().sum::<f64>() / kept.len() as f64)
+    };
+
+    let ungated = gated_mean(ABSOLUTE_GATE_LUFS)?;
+    let integrated = gated_mean(loudness(ungated) - RELATIVE_GATE_LU)?;
+    Some(loudness(integrated) as f32)
+}
+
+/// Single gain that brings a render to `target_lufs`, held back so the loudest peak stays
+/// under -1 dBFS. Silent renders are left alone.
+pub fn loudness_normalization_gain(samples: &[f32], sample_rate: u32, target_lufs: f32) -> f32 {
+    let Some(measured) = integrated_loudness(samples, sample_rate) else {
+        return 1.0;
+    };
+    let gain = 10f32.powf((target_lufs - measured) / 20.0);
+    let peak = samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    if peak * gain > NORMALIZE_PEAK_CEILING {
+        tracing::warn!(
+            "Loudness normalization to {} LUFS limited by peaks; render stays at {:.1} LUFS",
+            target_lufs,
+            measured + 20.0 * (NORMALIZE_PEAK_CEILING / peak).log10()
+        );
+        NORMALIZE_PEAK_CEILING / peak
+    } else {
+        gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stereo_sine(right_gain: f32) -> Vec<f32> {
+        (0..44100)
+            .flat_map(|i| {
+                let sample = 0.5 * (i as f32 * 440.0 * std::f32::consts::TAU / 44100.0).sin();
+                [sample, sample * right_gain]
+            })
+            .collect()
+    }
+
+    fn stereo_sine_at(frequency: f32) -> Vec<f32> {
+        (0..44100)
+            .flat_map(|i| {
+                let sample = 0.5 * (i as f32 * frequency * std::f32::consts::TAU / 44100.0).sin();
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_out_of_phase_stereo_warns_and_centered_mono_does_not() {
+        let out_of_phase = check_mono_compatibility(&stereo_sine(-1.0));
+        assert!(out_of_phase.mono_loss_db > 40.0);
+        assert!(out_of_phase.warning().is_some());
+
+        let centered = check_mono_compatibility(&stereo_sine(1.0));
+        assert!(centered.mono_loss_db.abs() < 0.01);
+        assert!(centered.warning().is_none());
+    }
+
+    #[test]
+    fn test_pure_1khz_tone_peaks_in_the_1khz_band_every_frame() {
+        let tone = stereo_sine_at(1000.0);
+        let spectrogram = spectrogram(&tone, 44100, 16, 128);
+        assert_eq!(spectrogram.frames.len(), 16);
+
+        for frame in &spectrogram.frames {
+            let (loudest, level) =
+                frame
+                    .iter()
+                    .enumerate()
+                    .fold((0, f32::MIN), |best, (band, &level)| {
+                        if level > best.1 { (band, level) } else { best }
+                    });
+            let low = loudest as f32 * spectrogram.band_hz;
+            assert!(
+                (low..low + spectrogram.band_hz).contains(&1000.0),
+                "peak band {}-{}Hz",
+                low,
+                low + spectrogram.band_hz
+            );
+            // Half-scale sine reads about -6 dBFS
+            assert!((level + 6.0).abs() < 1.5, "peak level {}", level);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use super::scales::{scale_intervals, scale_notes};
+use super::{SimpleNote, deserialize_null_default};
+use crate::expressive::random_f32;
+use serde::{Deserialize, Serialize};
+
+/// Order in which the arpeggiator walks its notes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArpPattern {
+    #[default]
+    Up,
+    Down,
+    UpDown,
+    Random,
+}
+
+/// Arpeggio over a chord or a scale, expanded into notes when the sequence is resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Arpeggio {
+    /// Chord tones to arpeggiate (MIDI notes); ignored when `scale` is set
+    #[serde(default)]
+    pub notes: Vec<u8>,
+    /// Scale to run through instead of a chord (e.g., "major", "dorian", "minor_pentatonic")
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub scale: Option<String>,
+    /// Root note of the scale (MIDI, default: 60 = C4)
+    #[serde(default = "default_root")]
+    pub root: u8,
+    /// Span of the scale run in octaves (1-4, default: 1)
+    #[serde(default = "default_range")]
+    pub range: u8,
+    /// Walk order: up, down, up_down or random (default: up)
+    #[serde(default)]
+    pub pattern: ArpPattern,
+    /// Bar where the arpeggio starts (1-based, default: 1)
+    #[serde(default = "default_start_bar")]
+    pub start_bar: u32,
+    /// Length of each step in beats (default: 0.5 = eighth notes)
+    #[serde(default = "default_step_beats")]
+    pub step_beats: f64,
+    /// Number of notes to generate (default: one pass through the pattern)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub count: Option<u32>,
+    /// Velocity of every note (default: 80)
+    #[serde(default = "default_velocity")]
+    pub velocity: u8,
+    /// Accent every Nth note, starting with the first, for a pulse on pattern downbeats
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub accent_every: Option<u32>,
+    /// Sounding length as a fraction of the step (0.0-1.0, default: 1.0 = legato)
+    #[serde(default = "default_gate")]
+    pub gate: f32,
+    /// GM instrument for the generated notes
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub instrument: Option<u8>,
+    /// MIDI channel for the generated notes (default: 0)
+    #[serde(default)]
+    pub channel: u8,
+}
+
+fn default_root() -> u8 {
+    60
+}
+
+fn default_range() -> u8 {
+    1
+}
+
+fn default_start_bar() -> u32 {
+    1
+}
+
+fn default_step_beats() -> f64 {
+    0.5
+}
+
+fn default_velocity() -> u8 {
+    80
+}
+
+fn default_gate() -> f32 {
+    1.0
+}
+
+/// Velocity added to accented steps
+const ACCENT_VELOCITY_BOOST: u8 = 20;
+/// Shortest gate, so staccato steps still speak
+const MIN_GATE: f32 = 0.05;
+
+impl Arpeggio {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.scale.is_none() && self.notes.is_empty() {
+            return Err("Arpeggio needs either chord 'notes' or a 'scale'".to_string());
+        }
+        if let Some(scale) = &self.scale {
+            scale_intervals(scale)?;
+        }
+        if self.notes.iter().any(|&note| note > 127) || self.root > 127 {
+            return Err("Arpeggio notes must be MIDI notes 0-127".to_string());
+        }
+        if !(1..=4).contains(&self.range) {
+            return Err(format!(
+                "Arpeggio range must be 1-4 octaves, got {}",
+                self.range
+            ));
+        }
+        if self.start_bar == 0 {
+            return Err("Arpeggio start_bar is 1-based".to_string());
+        }
+        if !(self.step_beats > 0.0 && self.step_beats <= 16.0) {
+            return Err(format!(
+                "Arpeggio step_beats must be greater than 0 and at most 16, got {}",
+                self.step_beats
+            ));
+        }
+        if self.count.is_some_and(|count| count == 0 || count > 1024) {
+            return Err("Arpeggio count must be 1-1024".to_string());
+        }
+        if !(1..=127).contains(&self.velocity) {
+            return Err(format!(
+                "Arpeggio velocity must be 1-127, got {}",
+                self.velocity
+            ));
+        }
+        if self.accent_every == Some(0) {
+            return Err("Arpeggio accent_every must be at least 1".to_string());
+        }
+        if !(MIN_GATE..=1.0).contains(&self.gate) {
+            return Err(format!(
+                "Arpeggio gate must be between {} and 1.0, got {}",
+                MIN_GATE, self.gate
+            ));
+        }
+        if self.channel > 15 {
+            return Err(format!(
+                "Arpeggio channel must be 0-15, got {}",
+                self.channel
+            ));
+        }
+        Ok(())
+    }
+
+    /// The notes the arpeggio walks, lowest first
+    fn note_pool(&self) -> Result<Vec<u8>, String> {
+        match &self.scale {
+            Some(scale) => Ok(scale_notes(self.root, scale_intervals(scale)?, self.range)),
+            None => {
+                let mut notes = self.notes.clone();
+                notes.sort_unstable();
+                notes.dedup();
+                Ok(notes)
+            }
+        }
+    }
+
+    /// Pitches in play order for `count` steps (or one full pass of the pattern)
+    fn pitch_order(&self, pool: &[u8]) -> Vec<u8> {
+        let cycle: Vec<u8> = match self.pattern {
+            ArpPattern::Up | ArpPattern::Random => pool.to_vec(),
+            ArpPattern::Down => pool.iter().rev().copied().collect(),
+            ArpPattern::UpDown => {
+                let descent = pool.iter().rev().skip(1);
+                let descent_len = pool.len().saturating_sub(2);
+                pool.iter()
+                    .copied()
+                    .chain(descent.take(descent_len).copied())
+                    .collect()
+            }
+        };
+        let count = self.count.map_or(cycle.len(), |count| count as usize);
+
+        (0..count)
+            .map(|step| match self.pattern {
+                ArpPattern::Random => {
+                    let index = (random_f32() * pool.len() as f32) as usize;
+                    pool[index.min(pool.len() - 1)]
+                }
+                _ => cycle[step % cycle.len()],
+            })
+            .collect()
+    }
+
+    /// Velocity of the note at `index`, boosted on accented steps
+    fn step_velocity(&self, index: usize) -> u8 {
+        match self.accent_every {
+            Some(every) if index.is_multiple_of(every as usize) => {
+                self.velocity.saturating_add(ACCENT_VELOCITY_BOOST).min(127)
+            }
+            _ => self.velocity,
+        }
+    }
+
+    /// Expand the arpeggio into timed notes
+    pub fn generate(&self, tempo: u32, beats_per_bar: u32) -> Result<Vec<SimpleNote>, String> {
+        self.validate()?;
+        let pool = self.note_pool()?;
+        if pool.is_empty() {
+            return Err("Arpeggio has no notes within the MIDI range".to_string());
+        }
+
+        let seconds_per_beat = 60.0 / tempo as f64;
+        let start_time = (self.start_bar - 1) as f64 * beats_per_bar as f64 * seconds_per_beat;
+        let step = self.step_beats * seconds_per_beat;
+
+        Ok(self
+            .pitch_order(&pool)
+            .into_iter()
+            .enumerate()
+            .map(|(index, pitch)| SimpleNote {
+                note: Some(pitch),
+                velocity: Some(self.step_velocity(index)),
+                start_time: Some(start_time + index as f64 * step),
+                duration: Some(step * self.gate as f64),
+                channel: self.channel,
+                instrument: self.instrument,
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arpeggio(json: serde_json::Value) -> Arpeggio {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_c_major_up_run_over_one_octave() {
+        let notes = arpeggio(serde_json::json!({
+            "scale": "major",
+            "root": 60,
+            "range": 1,
+            "pattern": "up",
+            "start_bar": 2,
+            "step_beats": 0.5
+        }))
+        .generate(120, 4)
+        .unwrap();
+
+        let pitches: Vec<u8> = notes.iter().map(|note| note.note.unwrap()).collect();
+        assert_eq!(pitches, vec![60, 62, 64, 65, 67, 69, 71, 72]);
+
+        // Bar 2 at 120 BPM starts at 2.0s; eighth notes are 0.25s apart
+        for (index, note) in notes.iter().enumerate() {
+            let expected = 2.0 + index as f64 * 0.25;
+            assert!((note.start_time.unwrap() - expected).abs() < 1e-9);
+            assert!((note.duration.unwrap() - 0.25).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_accent_every_four_lifts_downbeat_positions() {
+        let notes = arpeggio(serde_json::json!({
+            "notes": [60, 64, 67],
+            "count": 12,
+            "velocity": 70,
+            "accent_every": 4,
+            "gate": 0.5
+        }))
+        .generate(120, 4)
+        .unwrap();
+
+        for (index, note) in notes.iter().enumerate() {
+            let expected = if index % 4 == 0 { 90 } else { 70 };
+            assert_eq!(note.velocity, Some(expected), "position {}", index);
+            // Half of an eighth note at 120 BPM
+            assert!((note.duration.unwrap() - 0.125).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_unknown_scale_is_rejected() {
+        let error = arpeggio(serde_json::json!({"scale": "bebop_x"}))
+            .generate(120, 4)
+            .unwrap_err();
+        assert!(error.contains("Unknown scale"), "{}", error);
+    }
+}
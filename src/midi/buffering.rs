@@ -0,0 +1,154 @@
+use rodio::Source;
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, TryRecvError, sync_channel};
+use std::thread;
+use std::time::Duration;
+
+/// Source that renders its inner source ahead of the output device in fixed-size blocks
+/// on a worker thread, so a slow machine has headroom before the device runs dry.
+/// Construction waits until `preroll` worth of audio is queued; the worker then stays at
+/// most about one more pre-roll ahead. If the worker still falls behind, frames of
+/// silence are played instead of stalling the device.
+pub struct BufferedSource {
+    blocks: Receiver<Vec<f32>>,
+    queued: VecDeque<Vec<f32>>,
+    current: std::vec::IntoIter<f32>,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+    finished: bool,
+    underruns: usize,
+}
+
+impl BufferedSource {
+    pub fn new<S>(source: S, block_frames: usize, preroll: Duration) -> Self
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        let total_duration = source.total_duration();
+
+        let block_frames = block_frames.max(1);
+        let block_len = block_frames * channels as usize;
+        let preroll_frames = (preroll.as_secs_f64() * sample_rate as f64).ceil() as usize;
+        let preroll_blocks = preroll_frames.div_ceil(block_frames).max(1);
+
+        // The bounded channel keeps the worker from racing through the whole sequence
+        let (sender, blocks) = sync_channel(preroll_blocks);
+        thread::spawn(move || {
+            let mut source = source;
+            loop {
+                let block: Vec<f32> = source.by_ref().take(block_len).collect();
+                if block.is_empty() || sender.send(block).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut buffered = BufferedSource {
+            blocks,
+            queued: VecDeque::new(),
+            current: Vec::new().into_iter(),
+            channels,
+            sample_rate,
+            total_duration,
+            finished: false,
+            underruns: 0,
+        };
+        while buffered.queued.len() < preroll_blocks {
+            match buffered.blocks.recv() {
+                Ok(block) => buffered.queued.push_back(block),
+                // Sequence shorter than the pre-roll: everything is already queued
+                Err(_) => {
+                    buffered.finished = true;
+                    break;
+                }
+            }
+        }
+        tracing::debug!(
+            "Pre-rolled {} frames in {}-frame blocks",
+            buffered.queued_frames(),
+            block_frames
+        );
+        buffered
+    }
+
+    /// Frames rendered and waiting to be played, excluding the block being played
+    pub fn queued_frames(&self) -> usize {
+        self.queued.iter().map(Vec::len).sum::<usize>() / self.channels.max(1) as usize
+    }
+}
+
+impl Iterator for BufferedSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.current.next() {
+                return Some(sample);
+            }
+            if let Some(block) = self.queued.pop_front() {
+                self.current = block.into_iter();
+                continue;
+            }
+            if self.finished {
+                return None;
+            }
+            match self.blocks.try_recv() {
+                Ok(block) => self.current = block.into_iter(),
+                Err(TryRecvError::Empty) => {
+                    if self.underruns == 0 {
+                        tracing::warn!(
+                            "Audio buffer underrun; consider raising audio_buffer_ms in the config"
+                        );
+                    }
+                    self.underruns += 1;
+                    self.current = vec![0.0; self.channels as usize].into_iter();
+                }
+                Err(TryRecvError::Disconnected) => self.finished = true,
+            }
+        }
+    }
+}
+
+impl Source for BufferedSource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn test_preroll_is_queued_before_the_first_block_is_consumed() {
+        let samples: Vec<f32> = (1..=44100).map(|i| i as f32 / 44100.0).collect();
+        let source = SamplesBuffer::new(1, 44100, samples.clone());
+
+        // 50ms at 44.1kHz is 2205 frames, rounded up to whole 256-frame blocks
+        let buffered = BufferedSource::new(source, 256, Duration::from_millis(50));
+        assert_eq!(buffered.queued_frames(), 9 * 256);
+
+        // The pre-roll plays straight through; later blocks may be padded by underrun
+        // silence if the worker falls behind, but never reordered or dropped
+        let played: Vec<f32> = buffered.collect();
+        assert_eq!(played[..9 * 256], samples[..9 * 256]);
+        let audible: Vec<f32> = played.into_iter().filter(|&sample| sample != 0.0).collect();
+        assert_eq!(audible, samples);
+    }
+}
@@ -0,0 +1,529 @@
+use crate::expressive::random_f32;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Audio file formats a render can be written as, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Wav,
+    Flac,
+    Ogg,
+    Opus,
+}
+
+impl ExportFormat {
+    /// Supported file extensions (compressed ones need the `compressed-export` feature)
+    pub const EXTENSIONS: [&'static str; 4] = ["wav", "flac", "ogg", "opus"];
+
+    /// Infer the format from the path's extension, rejecting unsupported or unavailable formats
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+
+        let format = match extension.as_str() {
+            "wav" => ExportFormat::Wav,
+            "flac" => ExportFormat::Flac,
+            "ogg" => ExportFormat::Ogg,
+            "opus" => ExportFormat::Opus,
+            _ => {
+                return Err(format!(
+                    "Unsupported output format '{}' for {:?}. Supported extensions: {}",
+                    extension,
+                    path,
+                    Self::EXTENSIONS.map(|ext| format!(".{}", ext)).join(", ")
+                ));
+            }
+        };
+
+        if format != ExportFormat::Wav && !cfg!(feature = "compressed-export") {
+            return Err(format!(
+                ".{} export requires mcp-muse to be built with the `compressed-export` feature; use .wav instead",
+                extension
+            ));
+        }
+
+        Ok(format)
+    }
+}
+
+/// Directory `render_sequence` writes into: `renders` in the mcp-muse data directory
+pub fn renders_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("mcp-muse")
+        .join("renders")
+}
+
+/// Resolve a requested output path inside `dir`. Relative paths are taken from `dir` and
+/// absolute ones must already lie in it; a path that climbs out with `..`, or leads out
+/// through a symlink inside `dir`, is rejected.
+pub fn output_path_in(dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    let escapes = || {
+        format!(
+            "output_path {:?} must stay inside the renders directory {:?}",
+            requested, dir
+        )
+    };
+    let requested_path = Path::new(requested);
+    let relative = if requested_path.is_absolute() {
+        requested_path.strip_prefix(dir).map_err(|_| escapes())?
+    } else {
+        requested_path
+    };
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(escapes());
+    }
+
+    let path = dir.join(relative);
+    // Whatever part of the path already exists must resolve to somewhere inside `dir`
+    if let Ok(root) = dir.canonicalize()
+        && let Some(existing) = path.ancestors().find(|ancestor| ancestor.exists())
+        && !existing
+            .canonicalize()
+            .is_ok_and(|existing| existing.starts_with(&root))
+    {
+        return Err(escapes());
+    }
+    Ok(path)
+}
+
+/// How an offline render is written to a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderOptions {
+    /// TPDF-dither 16-bit output (default: true)
+    #[serde(default = "default_dither")]
+    pub dither: bool,
+    /// Trim the render to a whole number of bars and fold the reverb and release tails
+    /// back onto the start so the file loops without a click (default: false)
+    #[serde(default)]
+    pub seamless_loop: bool,
+}
+
+fn default_dither() -> bool {
+    true
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            dither: default_dither(),
+            seamless_loop: false,
+        }
+    }
+}
+
+/// Write interleaved f32 samples to `path` in the given format. With `dither`, the 16-bit
+/// formats (WAV, FLAC) get TPDF dither before quantization so quiet tails fade into a
+/// steady noise floor instead of distorting.
+pub fn write_audio(
+    path: &Path,
+    format: ExportFormat,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    dither: bool,
+) -> Result<(), String> {
+    match format {
+        ExportFormat::Wav => write_wav(path, samples, channels, sample_rate, dither),
+        #[cfg(feature = "compressed-export")]
+        ExportFormat::Flac => compressed::write_flac(path, samples, channels, sample_rate, dither),
+        #[cfg(feature = "compressed-export")]
+        ExportFormat::Ogg => compressed::write_vorbis(path, samples, channels, sample_rate),
+        #[cfg(feature = "compressed-export")]
+        ExportFormat::Opus => compressed::write_opus(path, samples, channels, sample_rate),
+        #[cfg(not(feature = "compressed-export"))]
+        _ => Err(format!(
+            "{:?} export requires the `compressed-export` feature",
+            format
+        )),
+    }
+}
+
+/// Convert a float sample to 16-bit PCM
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Convert a float sample to 16-bit PCM with TPDF dither: the difference of two uniform
+/// draws (triangular, up to one step either way) is added before rounding, so the
+/// quantization error stops following the signal. Draws from the master seed when set.
+fn to_i16_dithered(sample: f32) -> i16 {
+    let noise = random_f32() - random_f32();
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32 + noise)
+        .round()
+        .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Quantize a whole buffer to 16-bit PCM
+fn quantize(samples: &[f32], dither: bool) -> Vec<i16> {
+    let convert = if dither { to_i16_dithered } else { to_i16 };
+    samples.iter().map(|&sample| convert(sample)).collect()
+}
+
+fn write_wav(
+    path: &Path,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    dither: bool,
+) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .map_err(|e| format!("Failed to create WAV file {:?}: {}", path, e))?;
+    for sample in quantize(samples, dither) {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("Failed to write WAV samples: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize WAV file {:?}: {}", path, e))
+}
+
+#[cfg(feature = "compressed-export")]
+mod compressed {
+    use super::quantize;
+    use std::fs::{self, File};
+    use std::io::BufWriter;
+    use std::num::{NonZeroU8, NonZeroU32};
+    use std::path::Path;
+
+    /// Opus always encodes at 48kHz
+    const OPUS_SAMPLE_RATE: u32 = 48000;
+    /// 20ms Opus frames
+    const OPUS_FRAME_SIZE: usize = 960;
+    /// Frames fed to the Vorbis encoder per block
+    const VORBIS_BLOCK_FRAMES: usize = 4096;
+
+    pub fn write_flac(
+        path: &Path,
+        samples: &[f32],
+        channels: u16,
+        sample_rate: u32,
+        dither: bool,
+    ) -> Result<(), String> {
+        use flacenc::component::BitRepr;
+        use flacenc::error::Verify;
+
+        let pcm: Vec<i32> = quantize(samples, dither)
+            .into_iter()
+            .map(i32::from)
+            .collect();
+        let config = flacenc::config::Encoder::default()
+            .into_verified()
+            .map_err(|(_, e)| format!("Invalid FLAC encoder config: {:?}", e))?;
+        let source = flacenc::source::MemSource::from_samples(
+            &pcm,
+            channels as usize,
+            16,
+            sample_rate as usize,
+        );
+        let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+            .map_err(|e| format!("FLAC encoding failed: {:?}", e))?;
+
+        let mut sink = flacenc::bitsink::ByteSink::new();
+        stream
+            .write(&mut sink)
+            .map_err(|e| format!("FLAC encoding failed: {:?}", e))?;
+        fs::write(path, sink.as_slice())
+            .map_err(|e| format!("Failed to write FLAC file {:?}: {}", path, e))
+    }
+
+    pub fn write_vorbis(
+        path: &Path,
+        samples: &[f32],
+        channels: u16,
+        sample_rate: u32,
+    ) -> Result<(), String> {
+        let channel_count =
+            NonZeroU8::new(channels as u8).ok_or("Vorbis needs at least one channel")?;
+        let rate = NonZeroU32::new(sample_rate).ok_or("Vorbis needs a non-zero sample rate")?;
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create Ogg file {:?}: {}", path, e))?;
+
+        let mut encoder =
+            vorbis_rs::VorbisEncoderBuilder::new(rate, channel_count, BufWriter::new(file))
+                .and_then(|mut builder| builder.build())
+                .map_err(|e| format!("Failed to start Vorbis encoder: {}", e))?;
+
+        // The encoder takes planar blocks
+        for block in samples.chunks(VORBIS_BLOCK_FRAMES * channels as usize) {
+            let planar: Vec<Vec<f32>> = (0..channels as usize)
+                .map(|channel| {
+                    block
+                        .iter()
+                        .skip(channel)
+                        .step_by(channels as usize)
+                        .copied()
+                        .collect()
+                })
+                .collect();
+            encoder
+                .encode_audio_block(&planar)
+                .map_err(|e| format!("Vorbis encoding failed: {}", e))?;
+        }
+        encoder
+            .finish()
+            .map_err(|e| format!("Failed to finish Ogg file {:?}: {}", path, e))?;
+        Ok(())
+    }
+
+    pub fn write_opus(
+        path: &Path,
+        samples: &[f32],
+        channels: u16,
+        sample_rate: u32,
+    ) -> Result<(), String> {
+        use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+        let opus_channels = match channels {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            _ => {
+                return Err(format!(
+                    "Opus export supports 1 or 2 channels, got {}",
+                    channels
+                ));
+            }
+        };
+        let mut encoder =
+            opus::Encoder::new(OPUS_SAMPLE_RATE, opus_channels, opus::Application::Audio)
+                .map_err(|e| format!("Failed to start Opus encoder: {}", e))?;
+        let pre_skip = encoder
+            .get_lookahead()
+            .map_err(|e| format!("Failed to query Opus lookahead: {}", e))?
+            as u16;
+
+        let channels = channels as usize;
+        let mut resampled = resample_linear(samples, channels, sample_rate, OPUS_SAMPLE_RATE);
+        let frame_len = OPUS_FRAME_SIZE * channels;
+        let total_frames = resampled.len() / channels;
+        resampled.resize(resampled.len().div_ceil(frame_len) * frame_len, 0.0);
+
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create Opus file {:?}: {}", path, e))?;
+        let mut writer = PacketWriter::new(BufWriter::new(file));
+        let serial = 1;
+        let io_error = |e: std::io::Error| format!("Failed to write Opus file {:?}: {}", path, e);
+
+        // Identification and comment headers each sit on their own page
+        let mut head = b"OpusHead".to_vec();
+        head.push(1);
+        head.push(channels as u8);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        writer
+            .write_packet(
+                head.into_boxed_slice(),
+                serial,
+                PacketWriteEndInfo::EndPage,
+                0,
+            )
+            .map_err(io_error)?;
+
+        let vendor = concat!("mcp-muse ", env!("CARGO_PKG_VERSION"));
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor.as_bytes());
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        writer
+            .write_packet(
+                tags.into_boxed_slice(),
+                serial,
+                PacketWriteEndInfo::EndPage,
+                0,
+            )
+            .map_err(io_error)?;
+
+        let frame_count = resampled.len() / frame_len;
+        let mut packet = vec![0u8; 4000];
+        for (index, frame) in resampled.chunks(frame_len).enumerate() {
+            let length = encoder
+                .encode_float(frame, &mut packet)
+                .map_err(|e| format!("Opus encoding failed: {}", e))?;
+            let last = index + 1 == frame_count;
+            // The final granule position trims the zero padding on decode
+            let granule = if last {
+                pre_skip as u64 + total_frames as u64
+            } else {
+                pre_skip as u64 + ((index + 1) * OPUS_FRAME_SIZE) as u64
+            };
+            let end_info = if last {
+                PacketWriteEndInfo::EndStream
+            } else {
+                PacketWriteEndInfo::NormalPacket
+            };
+            writer
+                .write_packet(packet[..length].into(), serial, end_info, granule)
+                .map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    /// Linear-interpolation sample rate conversion of interleaved audio
+    fn resample_linear(samples: &[f32], channels: usize, from: u32, to: u32) -> Vec<f32> {
+        if from == to {
+            return samples.to_vec();
+        }
+        let input_frames = samples.len() / channels;
+        let output_frames = (input_frames as u64 * to as u64 / from as u64) as usize;
+        let step = from as f64 / to as f64;
+        let mut output = Vec::with_capacity(output_frames * channels);
+        for frame in 0..output_frames {
+            let position = frame as f64 * step;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let next = (index + 1).min(input_frames.saturating_sub(1));
+            for channel in 0..channels {
+                let a = samples[index * channels + channel];
+                let b = samples[next * channels + channel];
+                output.push(a + (b - a) * fraction);
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_extension_is_rejected() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("song.WAV")),
+            Ok(ExportFormat::Wav)
+        );
+        let error = ExportFormat::from_path(Path::new("song.mp3")).unwrap_err();
+        assert!(error.contains(".wav, .flac, .ogg, .opus"), "{}", error);
+        assert!(ExportFormat::from_path(Path::new("song")).is_err());
+    }
+
+    #[test]
+    fn test_output_paths_stay_in_the_renders_directory() {
+        let dir = std::env::temp_dir().join(format!("mcp-muse-renders-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            output_path_in(&dir, "loops/./beat.wav"),
+            Ok(dir.join("loops/./beat.wav"))
+        );
+        let inside = dir.join("beat.wav");
+        assert_eq!(
+            output_path_in(&dir, inside.to_str().unwrap()),
+            Ok(inside.clone())
+        );
+        for escaping in ["../beat.wav", "loops/../../beat.wav", "/tmp/beat.wav"] {
+            let error = output_path_in(&dir, escaping).unwrap_err();
+            assert!(error.contains("renders directory"), "{}", error);
+        }
+
+        // A symlink inside the directory cannot lead a render out of it
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::env::temp_dir(), dir.join("out")).unwrap();
+            assert!(output_path_in(&dir, "out/beat.wav").is_err());
+            assert!(output_path_in(&dir, "out/new/beat.wav").is_err());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dithered_fade_out_has_an_even_noise_floor() {
+        // A 440 Hz tone fading from four quantization steps to silence over a second
+        let sample_rate = 44100;
+        let step = 1.0 / i16::MAX as f32;
+        let fade: Vec<f32> = (0..sample_rate)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                4.0 * step * (1.0 - t) * (std::f32::consts::TAU * 440.0 * t).sin()
+            })
+            .collect();
+
+        // Quantization error RMS, in steps, over each tenth of the fade
+        let error_floor = |dither: bool| -> Vec<f32> {
+            let path = std::env::temp_dir().join(format!(
+                "mcp-muse-dither-{}-{}.wav",
+                dither,
+                std::process::id()
+            ));
+            let _seed_scope = crate::expressive::MasterSeedScope::new(Some(3));
+            write_audio(&path, ExportFormat::Wav, &fade, 1, sample_rate, dither).unwrap();
+            let exported: Vec<i16> = hound::WavReader::open(&path)
+                .unwrap()
+                .samples::<i16>()
+                .map(|sample| sample.unwrap())
+                .collect();
+            std::fs::remove_file(&path).unwrap();
+
+            let errors: Vec<f32> = exported
+                .iter()
+                .zip(&fade)
+                .map(|(&pcm, &original)| pcm as f32 - original / step)
+                .collect();
+            errors
+                .chunks(errors.len() / 10)
+                .map(|chunk| (chunk.iter().map(|e| e * e).sum::<f32>() / chunk.len() as f32).sqrt())
+                .collect()
+        };
+        let spread = |floor: &[f32]| {
+            let loudest = floor.iter().cloned().fold(f32::MIN, f32::max);
+            let quietest = floor.iter().cloned().fold(f32::MAX, f32::min);
+            loudest / quietest.max(1e-6)
+        };
+
+        let dithered = error_floor(true);
+        let truncated = error_floor(false);
+        // Dither leaves a steady half-step of noise from the loudest part to the silence
+        assert!(spread(&dithered) < 1.3, "dithered floor {:?}", dithered);
+        // Without it the error tracks the signal and vanishes once the tone drops below a step
+        assert!(spread(&truncated) > 3.0, "undithered floor {:?}", truncated);
+    }
+
+    #[cfg(feature = "compressed-export")]
+    #[test]
+    fn test_flac_render_decodes_to_wav_length() {
+        use crate::midi::{MidiPlayer, SimpleNote, SimpleSequence};
+
+        let sequence = SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(0.5),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(440.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let dir = std::env::temp_dir();
+        let wav_path = dir.join(format!("mcp-muse-export-{}.wav", std::process::id()));
+        let flac_path = dir.join(format!("mcp-muse-export-{}.flac", std::process::id()));
+
+        MidiPlayer::render_to_file(sequence.clone(), &wav_path, &RenderOptions::default()).unwrap();
+        MidiPlayer::render_to_file(sequence, &flac_path, &RenderOptions::default()).unwrap();
+
+        let wav_frames = hound::WavReader::open(&wav_path).unwrap().duration() as usize;
+        let mut flac = claxon::FlacReader::open(&flac_path).unwrap();
+        let flac_channels = flac.streaminfo().channels as usize;
+        let flac_samples = flac.samples().map(|sample| sample.unwrap()).count();
+
+        assert!(wav_frames > 0);
+        assert_eq!(flac_samples / flac_channels, wav_frames);
+
+        std::fs::remove_file(wav_path).unwrap();
+        std::fs::remove_file(flac_path).unwrap();
+    }
+}
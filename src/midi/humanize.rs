@@ -0,0 +1,133 @@
+use super::SimpleNote;
+use crate::expressive::{random_bipolar, random_f32};
+
+/// GM drum channel (0-based)
+const DRUM_CHANNEL: u8 = 9;
+
+/// How a drum voice is humanized at full amount
+struct DrumFeel {
+    /// Maximum timing push/pull in seconds
+    timing: f64,
+    /// Maximum velocity change either way
+    velocity: f32,
+    /// Maximum fraction of velocity pulled off (ghosted strokes)
+    taper: f32,
+}
+
+/// Kick anchors the groove, so it barely moves
+const KICK_FEEL: DrumFeel = DrumFeel {
+    timing: 0.004,
+    velocity: 4.0,
+    taper: 0.0,
+};
+
+/// Snare backbeats stay tight with a little more dynamic range
+const SNARE_FEEL: DrumFeel = DrumFeel {
+    timing: 0.006,
+    velocity: 6.0,
+    taper: 0.0,
+};
+
+/// Hi-hats float around the grid and lose velocity on lighter strokes
+const HIHAT_FEEL: DrumFeel = DrumFeel {
+    timing: 0.015,
+    velocity: 6.0,
+    taper: 0.3,
+};
+
+/// Toms, cymbals and percussion sit in between
+const OTHER_FEEL: DrumFeel = DrumFeel {
+    timing: 0.010,
+    velocity: 8.0,
+    taper: 0.0,
+};
+
+fn drum_feel(note: u8) -> &'static DrumFeel {
+    match note {
+        35 | 36 => &KICK_FEEL,
+        37..=40 => &SNARE_FEEL,
+        42 | 44 | 46 => &HIHAT_FEEL,
+        _ => &OTHER_FEEL,
+    }
+}
+
+pub fn validate_drum_humanize(amount: Option<f32>) -> Result<(), String> {
+    match amount {
+        Some(amount) if !(0.0..=1.0).contains(&amount) => Err(format!(
+            "drum_humanize must be between 0.0 and 1.0, got {}",
+            amount
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl SimpleNote {
+    /// Push/pull a drum hit and vary its velocity by voice: kick and snare stay tight,
+    /// hi-hats drift more and taper in velocity. Draws from the master seed when set.
+    pub fn humanize_drum(&mut self, amount: f32) {
+        if self.channel != DRUM_CHANNEL || self.is_r2d2() || self.is_synthesis() {
+            return;
+        }
+        let (Some(note), Some(start_time)) = (self.note, self.start_time) else {
+            return;
+        };
+        let feel = drum_feel(note);
+        let amount = amount.clamp(0.0, 1.0);
+
+        let offset = random_bipolar() as f64 * feel.timing * amount as f64;
+        self.start_time = Some((start_time + offset).max(0.0));
+
+        let velocity = self.velocity.unwrap_or(80) as f32;
+        let tapered = velocity * (1.0 - feel.taper * amount * random_f32());
+        let varied = tapered + random_bipolar() * feel.velocity * amount;
+        self.velocity = Some(varied.round().clamp(1.0, 127.0) as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressive::MasterSeedScope;
+
+    fn timing_offsets(drum: u8) -> Vec<(f64, u8)> {
+        (0..200)
+            .map(|i| {
+                let start = 1.0 + i as f64 * 0.25;
+                let mut note = SimpleNote {
+                    note: Some(drum),
+                    channel: 9,
+                    velocity: Some(100),
+                    start_time: Some(start),
+                    duration: Some(0.1),
+                    ..Default::default()
+                };
+                note.humanize_drum(1.0);
+                (note.start_time.unwrap() - start, note.velocity.unwrap())
+            })
+            .collect()
+    }
+
+    fn variance(values: &[f64]) -> f64 {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn test_hihat_drifts_more_than_kick_within_bounds() {
+        let _seed = MasterSeedScope::new(Some(1683));
+        let kicks = timing_offsets(36);
+        let hats = timing_offsets(42);
+
+        let kick_offsets: Vec<f64> = kicks.iter().map(|&(offset, _)| offset).collect();
+        let hat_offsets: Vec<f64> = hats.iter().map(|&(offset, _)| offset).collect();
+        assert!(variance(&hat_offsets) > variance(&kick_offsets));
+
+        for &(offset, velocity) in kicks.iter().chain(&hats) {
+            assert!(offset.abs() <= 0.015 + 1e-9, "offset {}", offset);
+            assert!((60..=127).contains(&velocity), "velocity {}", velocity);
+        }
+        // Hats taper below the kick's tight velocity range
+        let min_hat = hats.iter().map(|&(_, velocity)| velocity).min().unwrap();
+        assert!(min_hat < 96);
+    }
+}
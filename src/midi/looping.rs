@@ -0,0 +1,193 @@
+use super::player::{MidiPlayer, RenderedAudio};
+use super::resolve::resolve_notes;
+use super::{SimpleSequence, TempoMap};
+use crate::expressive::{EffectsPresetLibrary, MasterSeedScope, PresetLibrary};
+use std::time::Duration;
+
+/// Length of a seamless loop of `sequence`: the end of its last note, rounded up to a
+/// whole bar along the sequence's tempo changes
+pub fn loop_length(sequence: &SimpleSequence) -> Result<Duration, String> {
+    let notes = {
+        let _seed_scope = MasterSeedScope::new(sequence.master_seed);
+        resolve_notes(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )?
+    };
+    let end = notes
+        .iter()
+        .map(|note| note.start_time.unwrap_or(0.0) + note.duration.unwrap_or(1.0))
+        .fold(0.0, f64::max);
+    let beats_per_bar = sequence.beats_per_bar as f64;
+    let tempo_map = TempoMap::new(
+        sequence.tempo,
+        sequence.beats_per_bar,
+        &sequence.tempo_changes,
+    );
+    // Notes ending a hair past a bar line from float rounding still fit that bar
+    let bars = (tempo_map.beat_at(end) / beats_per_bar - 1e-9)
+        .ceil()
+        .max(1.0);
+    Ok(Duration::from_secs_f64(
+        tempo_map.seconds_at(bars * beats_per_bar),
+    ))
+}
+
+/// Render `sequence` as a seamless loop of `loop_length`. Loudness normalization and the
+/// master limiter run on the folded loop, since the tails folded onto its start add to
+/// what is already there.
+pub fn render_loop(sequence: SimpleSequence) -> Result<RenderedAudio, String> {
+    let (target_lufs, master_limiter) = (sequence.target_lufs, sequence.master_limiter);
+    let length = loop_length(&sequence)?;
+    let mut looped = fold_into_loop(&MidiPlayer::render_mix(sequence)?, length)?;
+    MidiPlayer::master_render(&mut looped, target_lufs, master_limiter);
+    Ok(looped)
+}
+
+/// Cut a render to `length` and mix everything after the cut (reverb and release tails)
+/// back onto the start, the way the tail of each pass overlaps the next when the loop
+/// repeats. The last frame then runs straight into the first with no click or gap.
+pub fn fold_into_loop(audio: &RenderedAudio, length: Duration) -> Result<RenderedAudio, String> {
+    let channels = audio.channels.max(1) as usize;
+    let loop_frames = (length.as_secs_f64() * audio.sample_rate as f64).round() as usize;
+    if loop_frames == 0 {
+        return Err("A seamless loop needs a length of at least one sample".to_string());
+    }
+
+    let mut samples = vec![0.0f32; loop_frames * channels];
+    for (frame, frame_samples) in audio.samples.chunks_exact(channels).enumerate() {
+        let start = (frame % loop_frames) * channels;
+        for (slot, sample) in samples[start..start + channels]
+            .iter_mut()
+            .zip(frame_samples)
+        {
+            *slot += sample;
+        }
+    }
+
+    Ok(RenderedAudio {
+        samples,
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+        duration: Duration::from_secs_f64(loop_frames as f64 / audio.sample_rate as f64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::SimpleNote;
+
+    /// Largest jump between consecutive frames of the left channel
+    fn largest_step(samples: &[f32], channels: usize) -> f32 {
+        let left: Vec<f32> = samples.iter().step_by(channels).copied().collect();
+        left.windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_seamless_loop_joins_its_end_to_its_start() {
+        let sine = |duration: f64| SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(duration),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(440.125),
+                synth_attack: Some(0.0),
+                synth_release: Some(0.5),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        // Loops last whole bars at 120 BPM
+        assert_eq!(loop_length(&sine(2.0)).unwrap(), Duration::from_secs(2));
+        assert_eq!(loop_length(&sine(2.5)).unwrap(), Duration::from_secs(4));
+        // Slowing to 60 BPM across bar 1, a note ending early in bar 2 fills the
+        // four-second bar 2 too
+        let slowing = SimpleSequence {
+            tempo_changes: vec![crate::midi::TempoChange {
+                at: crate::midi::MusicalTime::new(2, 1, 0),
+                tempo: 60,
+            }],
+            ..sine(3.0)
+        };
+        let ramp = 4.0 * std::f64::consts::LN_2;
+        let length = loop_length(&slowing).unwrap().as_secs_f64();
+        assert!((length - (ramp + 4.0)).abs() < 1e-6, "{}", length);
+
+        // A note held half a second over a one-bar loop; the pitch puts the bar line a
+        // quarter cycle into the waveform, at a peak rather than a zero crossing
+        let rendered = MidiPlayer::render_samples(sine(2.5)).unwrap();
+        let channels = rendered.channels as usize;
+        let length = Duration::from_secs(2);
+        let looped = fold_into_loop(&rendered, length).unwrap();
+        assert_eq!(looped.samples.len(), 88200 * channels);
+        assert_eq!(looped.duration, length);
+
+        // Play the loop twice in a row: the seam must be no bigger a step than the waveform
+        // takes anywhere else
+        let seam = |samples: &[f32]| {
+            let last = samples[samples.len() - channels];
+            (samples[0] - last).abs()
+        };
+        let steady = largest_step(&looped.samples, channels);
+        assert!(steady > 0.01);
+        assert!(
+            seam(&looped.samples) <= steady * 1.1,
+            "seam {} vs largest step {}",
+            seam(&looped.samples),
+            steady
+        );
+
+        // Simply cutting the render at the loop point drops the held note mid-waveform
+        let cut = &rendered.samples[..looped.samples.len()];
+        assert!(
+            seam(cut) > steady * 2.0,
+            "cut seam {} vs largest step {}",
+            seam(cut),
+            steady
+        );
+    }
+
+    #[test]
+    fn test_folded_tails_are_limited_with_the_loop() {
+        // Loud unison notes held half a second over a one-bar loop, into the limiter; the
+        // overhang folds back onto the start of the loop
+        let note = SimpleNote {
+            start_time: Some(0.0),
+            duration: Some(2.5),
+            synth_type: Some("sine".to_string()),
+            synth_frequency: Some(440.125),
+            synth_amplitude: Some(1.0),
+            synth_attack: Some(0.0),
+            synth_release: Some(0.5),
+            ..Default::default()
+        };
+        let sequence = SimpleSequence {
+            notes: vec![note; 3],
+            ..Default::default()
+        };
+        let length = Duration::from_secs(2);
+        let peak = |samples: &[f32]| samples.iter().map(|s| s.abs()).fold(0.0, f32::max);
+
+        // Limiting before the fold lets the overlap past full scale
+        let mastered = MidiPlayer::render_samples(sequence.clone()).unwrap();
+        assert!(peak(&mastered.samples) <= 1.0);
+        let folded = fold_into_loop(&mastered, length).unwrap();
+        assert!(peak(&folded.samples) > 1.0, "{}", peak(&folded.samples));
+
+        // As render_loop does it: fold the mix, then master the loop
+        let mut looped =
+            fold_into_loop(&MidiPlayer::render_mix(sequence.clone()).unwrap(), length).unwrap();
+        MidiPlayer::master_render(&mut looped, sequence.target_lufs, sequence.master_limiter);
+        assert!(
+            peak(&looped.samples) <= 1.0,
+            "loop peaks at {}",
+            peak(&looped.samples)
+        );
+        let rendered = render_loop(sequence).unwrap();
+        assert!(peak(&rendered.samples) <= 1.0);
+    }
+}
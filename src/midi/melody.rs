@@ -0,0 +1,227 @@
+use super::SimpleNote;
+use super::scales::{scale_intervals, scale_notes};
+use crate::expressive::{MasterSeedScope, random_bipolar, random_f32};
+use serde::{Deserialize, Serialize};
+
+/// Note lengths a generated melody picks from in beats, longest first
+const NOTE_BEATS: [f64; 4] = [2.0, 1.0, 0.5, 0.25];
+/// Most scale steps a note strays from the contour line either way
+const CONTOUR_JITTER: f32 = 1.5;
+
+/// Overall shape of a generated melody's pitch line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Contour {
+    Rising,
+    Falling,
+    /// Climbs to a peak in the middle and comes back down
+    Arch,
+    /// Wanders by small steps from the middle of the range
+    #[default]
+    Random,
+}
+
+/// A seeded melody in a key: the same settings and seed always give the same notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MelodySpec {
+    /// Root note of the key (MIDI, default: 60 = C4)
+    #[serde(default = "default_root")]
+    pub root: u8,
+    /// Scale to stay in (e.g., "major", "dorian", "minor_pentatonic"; default: major)
+    #[serde(default = "default_scale")]
+    pub scale: String,
+    /// Number of notes to generate (1-256)
+    pub length: u32,
+    /// Rhythmic density from 0.0 (long notes) to 1.0 (sixteenths), default: 0.5
+    #[serde(default = "default_density")]
+    pub density: f32,
+    /// Pitch line: rising, falling, arch or random (default: random)
+    #[serde(default)]
+    pub contour: Contour,
+    /// Span of the melody in octaves above the root (1-4, default: 2)
+    #[serde(default = "default_range")]
+    pub range: u8,
+    /// RNG seed; the same seed reproduces the same melody
+    pub seed: u64,
+    /// Velocity of every note (default: 80)
+    #[serde(default = "default_velocity")]
+    pub velocity: u8,
+}
+
+fn default_root() -> u8 {
+    60
+}
+
+fn default_scale() -> String {
+    "major".to_string()
+}
+
+fn default_density() -> f32 {
+    0.5
+}
+
+fn default_range() -> u8 {
+    2
+}
+
+fn default_velocity() -> u8 {
+    80
+}
+
+impl MelodySpec {
+    pub fn validate(&self) -> Result<(), String> {
+        scale_intervals(&self.scale)?;
+        if self.root > 127 {
+            return Err(format!("Melody root must be 0-127, got {}", self.root));
+        }
+        if !(1..=256).contains(&self.length) {
+            return Err(format!(
+                "Melody length must be 1-256 notes, got {}",
+                self.length
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.density) {
+            return Err(format!(
+                "Melody density must be between 0.0 and 1.0, got {}",
+                self.density
+            ));
+        }
+        if !(1..=4).contains(&self.range) {
+            return Err(format!(
+                "Melody range must be 1-4 octaves, got {}",
+                self.range
+            ));
+        }
+        if !(1..=127).contains(&self.velocity) {
+            return Err(format!(
+                "Melody velocity must be 1-127, got {}",
+                self.velocity
+            ));
+        }
+        Ok(())
+    }
+
+    /// Where the contour sits at `progress` (0.0-1.0 through the melody), from 0.0 at the
+    /// bottom of the range to 1.0 at the top; None for a random walk
+    fn contour_at(&self, progress: f32) -> Option<f32> {
+        match self.contour {
+            Contour::Rising => Some(progress),
+            Contour::Falling => Some(1.0 - progress),
+            Contour::Arch => Some(1.0 - (2.0 * progress - 1.0).abs()),
+            Contour::Random => None,
+        }
+    }
+
+    /// Generate the melody's notes, starting at time 0
+    pub fn generate(&self, tempo: u32) -> Result<Vec<SimpleNote>, String> {
+        self.validate()?;
+        let pool = scale_notes(self.root, scale_intervals(&self.scale)?, self.range);
+        if pool.is_empty() {
+            return Err("Melody has no scale notes within the MIDI range".to_string());
+        }
+        let top = (pool.len() - 1) as f32;
+        let seconds_per_beat = 60.0 / tempo as f64;
+        let _seed_scope = MasterSeedScope::new(Some(self.seed));
+
+        let mut time = 0.0;
+        let mut walk = top / 2.0;
+        let notes = (0..self.length)
+            .map(|index| {
+                let progress = index as f32 / (self.length - 1).max(1) as f32;
+                let position = match self.contour_at(progress) {
+                    Some(level) => level * top + random_bipolar() * CONTOUR_JITTER,
+                    None => {
+                        walk = (walk + random_bipolar() * 2.0).clamp(0.0, top);
+                        walk
+                    }
+                };
+                let pitch = pool[position.round().clamp(0.0, top) as usize];
+
+                // Denser melodies lean towards the shorter lengths
+                let length = self.density * (NOTE_BEATS.len() - 1) as f32 + random_f32() - 0.5;
+                let beats =
+                    NOTE_BEATS[length.round().clamp(0.0, (NOTE_BEATS.len() - 1) as f32) as usize];
+                let duration = beats * seconds_per_beat;
+
+                let note = SimpleNote {
+                    note: Some(pitch),
+                    velocity: Some(self.velocity),
+                    start_time: Some(time),
+                    duration: Some(duration),
+                    ..Default::default()
+                };
+                time += duration;
+                note
+            })
+            .collect();
+        Ok(notes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn melody(contour: Contour, seed: u64) -> Vec<SimpleNote> {
+        MelodySpec {
+            root: 62,
+            scale: "dorian".to_string(),
+            length: 16,
+            density: 0.6,
+            contour,
+            range: 2,
+            seed,
+            velocity: 90,
+        }
+        .generate(120)
+        .unwrap()
+    }
+
+    fn pitches(notes: &[SimpleNote]) -> Vec<u8> {
+        notes.iter().map(|note| note.note.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_seeded_melody_repeats_stays_in_scale_and_follows_contour() {
+        assert_eq!(
+            pitches(&melody(Contour::Random, 7)),
+            pitches(&melody(Contour::Random, 7))
+        );
+        assert_ne!(
+            pitches(&melody(Contour::Random, 7)),
+            pitches(&melody(Contour::Random, 8))
+        );
+
+        let dorian = scale_intervals("dorian").unwrap();
+        for seed in 0..20 {
+            for contour in [Contour::Rising, Contour::Falling, Contour::Arch] {
+                let notes = melody(contour, seed);
+                assert_eq!(notes.len(), 16);
+                for pitch in pitches(&notes) {
+                    assert!(
+                        dorian.contains(&((pitch - 62) % 12)),
+                        "{} is not in D dorian",
+                        pitch
+                    );
+                }
+                // Notes follow one another without gaps
+                for pair in notes.windows(2) {
+                    let end = pair[0].start_time.unwrap() + pair[0].duration.unwrap();
+                    assert!((pair[1].start_time.unwrap() - end).abs() < 1e-9);
+                }
+            }
+
+            let rising = pitches(&melody(Contour::Rising, seed));
+            assert!(rising[15] > rising[0], "seed {}: {:?}", seed, rising);
+            let falling = pitches(&melody(Contour::Falling, seed));
+            assert!(falling[15] < falling[0], "seed {}: {:?}", seed, falling);
+            let arch = pitches(&melody(Contour::Arch, seed));
+            assert!(
+                arch[8] > arch[0] && arch[8] > arch[15],
+                "seed {}: {:?}",
+                seed,
+                arch
+            );
+        }
+    }
+}
@@ -1,9 +1,27 @@
+pub mod abc;
+pub mod analysis;
+pub mod arpeggiator;
+pub mod buffering;
+pub mod export;
+pub mod humanize;
+pub mod looping;
+pub mod melody;
+#[cfg(feature = "audio-analysis")]
+pub mod onsets;
 pub mod parser;
 pub mod player;
 pub mod polyphonic_source;
-
+pub mod project;
+pub mod resolve;
+pub mod roll;
+pub mod scales;
+pub mod slicing;
+pub mod voicing;
+
+pub use arpeggiator::Arpeggio;
 pub use player::*;
 
+use crate::expressive::MasterSeedScope;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
 
@@ -27,12 +45,23 @@ impl MusicalTime {
     /// Convert to absolute seconds given tempo and time signature
     pub fn to_seconds(&self, tempo: u32, beats_per_bar: u32, ticks_per_beat: u32) -> f64 {
         let seconds_per_beat = 60.0 / tempo as f64;
+        self.beats(beats_per_bar, ticks_per_beat) * seconds_per_beat
+    }
 
-        // Calculate total beats from start
-        let total_beats = ((self.bar - 1) * beats_per_bar + (self.beat - 1)) as f64
-            + (self.tick as f64 / ticks_per_beat as f64);
+    /// Convert to absolute seconds following a tempo map
+    pub fn to_seconds_in(
+        &self,
+        tempo_map: &TempoMap,
+        beats_per_bar: u32,
+        ticks_per_beat: u32,
+    ) -> f64 {
+        tempo_map.seconds_at(self.beats(beats_per_bar, ticks_per_beat))
+    }
 
-        total_beats * seconds_per_beat
+    /// Beats from the start of the sequence
+    pub fn beats(&self, beats_per_bar: u32, ticks_per_beat: u32) -> f64 {
+        (self.bar.saturating_sub(1) * beats_per_bar + self.beat.saturating_sub(1)) as f64
+            + (self.tick as f64 / ticks_per_beat as f64)
     }
 
     /// Create from absolute seconds
@@ -51,7 +80,6 @@ impl MusicalTime {
     }
 
     /// Quantize to nearest grid position
-    #[allow(dead_code)]
     pub fn quantize(&self, grid_division: u32, ticks_per_beat: u32, beats_per_bar: u32) -> Self {
         let ticks_per_division = ticks_per_beat / grid_division;
         let quantized_tick =
@@ -138,6 +166,148 @@ impl MusicalDuration {
             },
         }
     }
+
+    /// Convert to seconds following a tempo map, for a note starting `start_beat` beats in
+    pub fn to_seconds_in(&self, tempo_map: &TempoMap, beats_per_bar: u32, start_beat: f64) -> f64 {
+        let beats = match self {
+            MusicalDuration::Bars(bars) => bars * beats_per_bar as f64,
+            MusicalDuration::Beats(beats) => *beats,
+            MusicalDuration::Seconds(secs) => return *secs,
+            MusicalDuration::NoteValue(note) => match note {
+                NoteValue::Whole => 4.0,
+                NoteValue::Half => 2.0,
+                NoteValue::Quarter => 1.0,
+                NoteValue::Eighth => 0.5,
+                NoteValue::Sixteenth => 0.25,
+                NoteValue::Triplet => 2.0 / 3.0,
+            },
+        };
+        tempo_map.seconds_at(start_beat + beats) - tempo_map.seconds_at(start_beat)
+    }
+}
+
+/// Slowest and fastest tempo a tempo change may set, in BPM
+pub const TEMPO_CHANGE_RANGE: std::ops::RangeInclusive<u32> = 20..=300;
+
+/// A tempo the sequence reaches at a point in musical time. BPM ramps linearly from the
+/// previous change (or the sequence tempo at the start) to this one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TempoChange {
+    /// Where the tempo is reached
+    pub at: MusicalTime,
+    /// Tempo in BPM at that point (20-300)
+    pub tempo: u32,
+}
+
+/// Tempo over a sequence as BPM at beat positions, linearly interpolated between them and
+/// held after the last one
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    /// (beat, BPM) points in ascending beat order, starting at beat 0
+    points: Vec<(f64, f64)>,
+}
+
+impl TempoMap {
+    pub fn new(tempo: u32, beats_per_bar: u32, changes: &[TempoChange]) -> Self {
+        let mut points = vec![(0.0, tempo as f64)];
+        for change in changes {
+            let beat = change.at.beats(beats_per_bar, 480);
+            points.push((beat, change.tempo as f64));
+        }
+        // Stable, so changes at the same point keep their order and jump between tempos
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { points }
+    }
+
+    /// (beat, BPM) points in ascending beat order, starting at beat 0
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Seconds from the start of the sequence to `beat`
+    pub fn seconds_at(&self, beat: f64) -> f64 {
+        let mut seconds = 0.0;
+        for pair in self.points.windows(2) {
+            let ((from, from_bpm), (to, to_bpm)) = (pair[0], pair[1]);
+            if beat <= from {
+                return seconds;
+            }
+            seconds += ramp_seconds(from_bpm, to_bpm, to - from, beat.min(to) - from);
+            if beat <= to {
+                return seconds;
+            }
+        }
+        let &(last, bpm) = self.points.last().unwrap_or(&(0.0, 120.0));
+        seconds + (beat - last).max(0.0) * 60.0 / bpm
+    }
+
+    /// Beats from the start of the sequence at `seconds`
+    pub fn beat_at(&self, seconds: f64) -> f64 {
+        let mut elapsed = 0.0;
+        for pair in self.points.windows(2) {
+            let ((from, from_bpm), (to, to_bpm)) = (pair[0], pair[1]);
+            let length = ramp_seconds(from_bpm, to_bpm, to - from, to - from);
+            if seconds < elapsed + length {
+                let into = seconds - elapsed;
+                let slope = (to_bpm - from_bpm) / (to - from);
+                // Invert seconds = 60 / slope * ln(bpm / from_bpm) for the BPM reached
+                return if slope.abs() < 1e-9 {
+                    from + into * from_bpm / 60.0
+                } else {
+                    from + (from_bpm * (into * slope / 60.0).exp() - from_bpm) / slope
+                };
+            }
+            elapsed += length;
+        }
+        let &(last, bpm) = self.points.last().unwrap_or(&(0.0, 120.0));
+        last + (seconds - elapsed) * bpm / 60.0
+    }
+}
+
+/// Seconds taken by the first `beats` of a `length`-beat ramp from `from_bpm` to `to_bpm`
+fn ramp_seconds(from_bpm: f64, to_bpm: f64, length: f64, beats: f64) -> f64 {
+    if length <= 0.0 || beats <= 0.0 {
+        return 0.0;
+    }
+    let slope = (to_bpm - from_bpm) / length;
+    if slope.abs() < 1e-9 {
+        beats * 60.0 / from_bpm
+    } else {
+        60.0 / slope * ((from_bpm + slope * beats) / from_bpm).ln()
+    }
+}
+
+/// Check tempo changes sit at valid musical times in ascending order with usable tempos
+pub fn validate_tempo_changes(changes: &[TempoChange], beats_per_bar: u32) -> Result<(), String> {
+    for (i, change) in changes.iter().enumerate() {
+        let at = &change.at;
+        if at.bar == 0 || at.beat == 0 || at.beat > beats_per_bar || at.tick >= 480 {
+            return Err(format!(
+                "Tempo change {} time {} must have bar >= 1, beat 1-{} and tick 0-479",
+                i + 1,
+                at,
+                beats_per_bar
+            ));
+        }
+        if !TEMPO_CHANGE_RANGE.contains(&change.tempo) {
+            return Err(format!(
+                "Tempo change {} tempo must be {}-{} BPM, got {}",
+                i + 1,
+                TEMPO_CHANGE_RANGE.start(),
+                TEMPO_CHANGE_RANGE.end(),
+                change.tempo
+            ));
+        }
+        if i > 0 && at.beats(beats_per_bar, 480) < changes[i - 1].at.beats(beats_per_bar, 480) {
+            return Err(format!(
+                "Tempo changes must be in ascending order: change {} at {} comes after {}",
+                i + 1,
+                at,
+                changes[i - 1].at
+            ));
+        }
+    }
+    Ok(())
 }
 
 /// Quantization grid options
@@ -160,6 +330,94 @@ pub enum QuantizeGrid {
     Triplet,
 }
 
+impl QuantizeGrid {
+    /// Grid spacing in beats, or None when quantization is off
+    pub fn beats(&self, beats_per_bar: u32) -> Option<f64> {
+        match self {
+            QuantizeGrid::Off => None,
+            QuantizeGrid::Bar => Some(beats_per_bar as f64),
+            QuantizeGrid::Beat => Some(1.0),
+            QuantizeGrid::Eighth => Some(0.5),
+            QuantizeGrid::Sixteenth => Some(0.25),
+            QuantizeGrid::ThirtySecond => Some(0.125),
+            QuantizeGrid::Triplet => Some(1.0 / 3.0),
+        }
+    }
+
+    /// Grid lines per beat for `MusicalTime::quantize`, or None for grids coarser than a
+    /// beat and when quantization is off
+    pub fn divisions_per_beat(&self) -> Option<u32> {
+        match self {
+            QuantizeGrid::Off | QuantizeGrid::Bar => None,
+            QuantizeGrid::Beat => Some(1),
+            QuantizeGrid::Eighth => Some(2),
+            QuantizeGrid::Sixteenth => Some(4),
+            QuantizeGrid::ThirtySecond => Some(8),
+            QuantizeGrid::Triplet => Some(3),
+        }
+    }
+}
+
+/// Which off-beats `swing` delays: every second 8th or every second 16th note
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum SwingSubdivision {
+    #[serde(rename = "8th")]
+    #[default]
+    Eighth,
+    #[serde(rename = "16th")]
+    Sixteenth,
+}
+
+impl SwingSubdivision {
+    /// Subdivision length in beats
+    pub fn beats(&self) -> f64 {
+        match self {
+            SwingSubdivision::Eighth => 0.5,
+            SwingSubdivision::Sixteenth => 0.25,
+        }
+    }
+}
+
+pub fn validate_swing(swing: f32) -> Result<(), String> {
+    if !(0.0..=0.75).contains(&swing) {
+        return Err(format!("swing must be between 0.0 and 0.75, got {}", swing));
+    }
+    Ok(())
+}
+
+/// Move a position in beats onto the swung grid. Each off-beat subdivision is delayed by
+/// `swing` of a subdivision; positions between grid lines stretch or squeeze with it so
+/// notes keep their order, and on-beat subdivisions stay where they are. Swing works on
+/// the straight grid, so notes that `quantize_grid` snaps are snapped first and swung after.
+pub fn swing_beats(beats: f64, swing: f32, subdivision: SwingSubdivision) -> f64 {
+    if swing == 0.0 {
+        return beats;
+    }
+    let step = subdivision.beats();
+    let pair_start = (beats / (2.0 * step)).floor() * 2.0 * step;
+    let offset = beats - pair_start;
+    let swing = swing as f64;
+    let swung = if offset < step {
+        offset * (1.0 + swing)
+    } else {
+        step * (1.0 + swing) + (offset - step) * (1.0 - swing)
+    };
+    pair_start + swung
+}
+
+/// `swing_beats` for a position in seconds, finding its beat along `tempo_map`
+pub fn swing_seconds(
+    seconds: f64,
+    tempo_map: &TempoMap,
+    swing: f32,
+    subdivision: SwingSubdivision,
+) -> f64 {
+    if swing == 0.0 {
+        return seconds;
+    }
+    tempo_map.seconds_at(swing_beats(tempo_map.beat_at(seconds), swing, subdivision))
+}
+
 /// Custom deserializer that converts null to None for optional fields
 fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
 where
@@ -171,7 +429,7 @@ where
 }
 
 /// Universal effect configuration for all audio sources
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EffectConfig {
     /// Effect type and parameters
     #[serde(flatten)]
@@ -182,6 +440,16 @@ pub struct EffectConfig {
     /// Whether this effect is enabled
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// Output only the processed signal, without the dry input (for effect returns on a separate bus)
+    #[serde(default)]
+    pub wet_only: bool,
+}
+
+impl EffectConfig {
+    /// Disabled or zero-intensity effects pass audio through untouched
+    pub fn is_bypassed(&self) -> bool {
+        !self.enabled || self.intensity <= 0.0
+    }
 }
 
 fn default_effect_intensity() -> f32 {
@@ -189,7 +457,7 @@ fn default_effect_intensity() -> f32 {
 }
 
 /// Effect types with their specific parameters
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum EffectType {
     /// High-quality reverb effect
@@ -206,6 +474,12 @@ pub enum EffectType {
         /// Pre-delay in seconds (0.0-0.1, default: 0.02)
         #[serde(default = "default_pre_delay")]
         pre_delay: f32,
+        /// Comb delay LFO modulation to smooth metallic resonances (0.0-1.0, default: 0.15)
+        #[serde(default = "default_reverb_modulation")]
+        modulation: f32,
+        /// Reverb implementation: schroeder (default) or fdn for smoother, denser halls
+        #[serde(default)]
+        algorithm: ReverbAlgorithm,
     },
     /// Delay/echo effect
     Delay {
@@ -237,6 +511,38 @@ pub enum EffectType {
         #[serde(default = "default_stereo_width")]
         stereo_width: f32,
     },
+    /// Flanger: a very short swept delay with feedback, mixed back in for a jet-like
+    /// comb sweep
+    Flanger {
+        /// Sweep rate in Hz (0.05-10.0, default: 0.5)
+        #[serde(default = "default_flanger_rate")]
+        rate: f32,
+        /// Sweep depth around the manual delay (0.0-1.0, default: 0.7)
+        #[serde(default = "default_flanger_depth")]
+        depth: f32,
+        /// Feedback amount (-0.95 to 0.95, default: 0.5; negative for inverted flanging)
+        #[serde(default = "default_half")]
+        feedback: f32,
+        /// Center delay of the sweep, 0.0 = 0.5ms to 1.0 = 8ms (default: 0.5)
+        #[serde(default = "default_half")]
+        manual: f32,
+    },
+    /// Phaser: a cascade of all-pass filters swept by an LFO and mixed with the dry signal,
+    /// for moving notches spread across the spectrum
+    Phaser {
+        /// Sweep rate in Hz (0.05-10.0, default: 0.5)
+        #[serde(default = "default_phaser_rate")]
+        rate: f32,
+        /// How far the notches sweep (0.0-1.0, default: 0.7)
+        #[serde(default = "default_phaser_depth")]
+        depth: f32,
+        /// Feedback amount (0.0-0.95, default: 0.3)
+        #[serde(default = "default_phaser_feedback")]
+        feedback: f32,
+        /// All-pass stages (2-12, default: 4); every two stages add one notch
+        #[serde(default = "default_phaser_stages")]
+        stages: u8,
+    },
     /// Parametric filter
     Filter {
         /// Filter type
@@ -266,6 +572,10 @@ pub enum EffectType {
         /// Release time in seconds (0.01-10.0, default: 0.1)
         #[serde(default = "default_release")]
         release: f32,
+        /// Blend of compressed over dry signal for New York-style parallel compression
+        /// (0.0 = dry, 1.0 = fully compressed, default: 1.0)
+        #[serde(default = "default_one")]
+        parallel: f32,
     },
     /// Distortion/overdrive
     Distortion {
@@ -279,9 +589,154 @@ pub enum EffectType {
         #[serde(default = "default_one")]
         output_level: f32,
     },
+    /// Band-split compressor using Linkwitz-Riley crossovers
+    MultibandCompressor {
+        /// Crossover frequencies in Hz, ascending (20-20000, default: [200, 2000])
+        #[serde(default = "default_crossovers")]
+        crossovers: Vec<f32>,
+        /// Compressor settings per band from low to high (count = crossovers + 1)
+        #[serde(default = "default_compressor_bands")]
+        bands: Vec<CompressorBand>,
+    },
+    /// Transient shaper: boosts or cuts the attack and sustain of each hit independently
+    TransientShaper {
+        /// Attack boost/cut (-1.0 to 1.0, default: 0.0; positive adds click and punch)
+        #[serde(default)]
+        attack_gain: f32,
+        /// Sustain boost/cut (-1.0 to 1.0, default: 0.0; negative tightens the body/boom)
+        #[serde(default)]
+        sustain_gain: f32,
+    },
+    /// Sums everything below the crossover to mono and leaves the highs' stereo image alone,
+    /// keeping the low end centered under stereo chorus or wide pads
+    BassMono {
+        /// Crossover frequency in Hz (40-300, default: 120)
+        #[serde(default = "default_bass_mono_crossover")]
+        crossover: f32,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+impl EffectType {
+    /// One instance of every effect type, with default parameters
+    pub fn all() -> Vec<EffectType> {
+        vec![
+            EffectType::Reverb {
+                room_size: default_half(),
+                dampening: default_dampening(),
+                wet_level: default_wet_level(),
+                pre_delay: default_pre_delay(),
+                modulation: default_reverb_modulation(),
+                algorithm: ReverbAlgorithm::default(),
+            },
+            EffectType::Delay {
+                delay_time: default_delay_time(),
+                feedback: default_feedback(),
+                wet_level: default_wet_level(),
+                sync_tempo: false,
+            },
+            EffectType::Chorus {
+                rate: default_chorus_rate(),
+                depth: default_chorus_depth(),
+                feedback: default_chorus_feedback(),
+                stereo_width: default_stereo_width(),
+            },
+            EffectType::Flanger {
+                rate: default_flanger_rate(),
+                depth: default_flanger_depth(),
+                feedback: default_half(),
+                manual: default_half(),
+            },
+            EffectType::Phaser {
+                rate: default_phaser_rate(),
+                depth: default_phaser_depth(),
+                feedback: default_phaser_feedback(),
+                stages: default_phaser_stages(),
+            },
+            EffectType::Filter {
+                filter_type: FilterType::default(),
+                cutoff: default_filter_cutoff(),
+                resonance: default_resonance(),
+                envelope_amount: 0.0,
+            },
+            EffectType::Compressor {
+                threshold: default_threshold(),
+                ratio: default_ratio(),
+                attack: default_attack(),
+                release: default_release(),
+                parallel: default_one(),
+            },
+            EffectType::Distortion {
+                drive: default_drive(),
+                tone: default_half(),
+                output_level: default_one(),
+            },
+            EffectType::MultibandCompressor {
+                crossovers: default_crossovers(),
+                bands: default_compressor_bands(),
+            },
+            EffectType::TransientShaper {
+                attack_gain: 0.0,
+                sustain_gain: 0.0,
+            },
+            EffectType::BassMono {
+                crossover: default_bass_mono_crossover(),
+            },
+        ]
+    }
+
+    /// The `type` tag clients use for this effect, as serialized
+    pub fn name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.get("type")?.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// Compressor settings for a single band of the multiband compressor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressorBand {
+    /// Threshold in dB (-60.0 to 0.0, default: -12.0)
+    #[serde(default = "default_threshold")]
+    pub threshold: f32,
+    /// Compression ratio (1.0-50.0, default: 4.0)
+    #[serde(default = "default_ratio")]
+    pub ratio: f32,
+    /// Attack time in seconds (0.0001-2.0, default: 0.01)
+    #[serde(default = "default_attack")]
+    pub attack: f32,
+    /// Release time in seconds (0.001-20.0, default: 0.1)
+    #[serde(default = "default_release")]
+    pub release: f32,
+    /// Makeup gain in dB (-24.0 to 24.0, default: 0.0)
+    #[serde(default)]
+    pub makeup_gain_db: f32,
+}
+
+impl Default for CompressorBand {
+    fn default() -> Self {
+        Self {
+            threshold: default_threshold(),
+            ratio: default_ratio(),
+            attack: default_attack(),
+            release: default_release(),
+            makeup_gain_db: 0.0,
+        }
+    }
+}
+
+/// Reverb algorithm behind `EffectType::Reverb`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReverbAlgorithm {
+    /// Parallel combs into series allpasses (classic, slightly metallic)
+    #[default]
+    Schroeder,
+    /// Feedback delay network: delay lines mixed through an orthogonal matrix for denser tails
+    Fdn,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum FilterType {
     #[default]
@@ -310,6 +765,9 @@ fn default_wet_level() -> f32 {
 fn default_pre_delay() -> f32 {
     0.02
 }
+fn default_reverb_modulation() -> f32 {
+    0.15
+}
 fn default_delay_time() -> f32 {
     0.25
 }
@@ -325,6 +783,24 @@ fn default_chorus_depth() -> f32 {
 fn default_chorus_feedback() -> f32 {
     0.2
 }
+fn default_flanger_rate() -> f32 {
+    0.5
+}
+fn default_flanger_depth() -> f32 {
+    0.7
+}
+fn default_phaser_rate() -> f32 {
+    0.5
+}
+fn default_phaser_depth() -> f32 {
+    0.7
+}
+fn default_phaser_feedback() -> f32 {
+    0.3
+}
+fn default_phaser_stages() -> u8 {
+    4
+}
 fn default_stereo_width() -> f32 {
     0.7
 }
@@ -349,11 +825,31 @@ fn default_release() -> f32 {
 fn default_drive() -> f32 {
     2.0
 }
+fn default_crossovers() -> Vec<f32> {
+    vec![200.0, 2000.0]
+}
+fn default_bass_mono_crossover() -> f32 {
+    120.0
+}
+fn default_compressor_bands() -> Vec<CompressorBand> {
+    vec![CompressorBand::default(); 3]
+}
 
 fn default_true() -> bool {
     true
 }
 
+/// Blend between two presets for `preset_morph`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetMorph {
+    /// Preset heard at amount 0.0
+    pub from: String,
+    /// Preset heard at amount 1.0
+    pub to: String,
+    /// How far from `from` toward `to` (0.0-1.0, clamped)
+    pub amount: f32,
+}
+
 /// Simple note representation that's easy to work with
 /// Can represent both MIDI notes and R2D2 expressions
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -364,7 +860,9 @@ pub struct SimpleNote {
     /// Velocity (0-127, where 127 = loudest) - Optional for R2D2 notes
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub velocity: Option<u8>,
-    /// Start time in seconds (deprecated - use musical_time when possible)
+    /// Start time in seconds (deprecated - use musical_time when possible). The first MIDI
+    /// note starts exactly here; a later MIDI note off its 64-frame block grid can sound up
+    /// to 63 frames (about 1.4ms) after this.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub start_time: Option<f64>,
     /// Duration in seconds (deprecated - use musical_duration when possible)
@@ -376,6 +874,25 @@ pub struct SimpleNote {
     /// Musical duration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub musical_duration: Option<MusicalDuration>,
+    /// Duration in beats at the sequence tempo; used when neither duration nor musical_duration is set
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub beats: Option<f64>,
+    /// Seconds to start the note early so a slow attack peaks on start_time (0.0-4.0, optional)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub pre_roll: Option<f64>,
+    /// Turn the note into a roll that speeds up from start_rate to end_rate hits per second
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub roll_accelerate: Option<roll::RollAccelerate>,
+    /// Musical articulation adjusting duration and velocity: staccato, legato, tenuto,
+    /// accent or marcato (optional)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub articulation: Option<String>,
+    /// Re-attack the pitch every rate_beats for the note's duration (mandolin-style picking)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub tremolo_pick: Option<roll::TremoloPick>,
+    /// Move the pitch to the nearest tone of the sequence's chord_track chord at this note's bar
+    #[serde(default)]
+    pub snap_to_chord: bool,
     /// MIDI channel (0-15)
     #[serde(default)]
     pub channel: u8,
@@ -427,6 +944,9 @@ pub struct SimpleNote {
     /// R2D2 context for enhanced expression
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub r2d2_context: Option<String>,
+    /// Custom droid timbre (pitch center, ring modulation, formant); omit for the classic voice
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub r2d2_voice: Option<crate::expressive::R2D2VoiceCharacter>,
 
     // NEW: Synthesis parameters (optional)
     /// Synthesis type: "sine", "square", "sawtooth", "triangle", "noise", "fm", "dx7fm", "granular", "wavetable",
@@ -453,6 +973,9 @@ pub struct SimpleNote {
     /// Release time in seconds (0.0-10.0, optional)
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub synth_release: Option<f32>,
+    /// Shape of the attack, decay and release: "linear" (default), "exponential", "logarithmic"
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_envelope_curve: Option<String>,
 
     // Synthesis filter parameters
     /// Filter type: "lowpass", "highpass", "bandpass" (optional)
@@ -464,6 +987,9 @@ pub struct SimpleNote {
     /// Filter resonance (0.0-1.0, optional)
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub synth_filter_resonance: Option<f32>,
+    /// Seconds the filter takes to open to full resonance at note start (0.0-0.1, default 0.005)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_filter_soft_start: Option<f32>,
 
     // Synthesis effects parameters
     /// Reverb intensity (0.0-1.0, optional)
@@ -480,9 +1006,30 @@ pub struct SimpleNote {
     pub synth_delay_time: Option<f32>,
 
     // Synthesis-specific parameters
+    /// Noise color for the noise type: "white" (default), "pink", "brown"
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_noise_color: Option<String>,
     /// Pulse width for square wave (0.1-0.9, optional)
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub synth_pulse_width: Option<f32>,
+    /// Pulse-width modulation for the square wave: { rate (0.05-20 Hz), depth (0.0-0.4) }
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_pwm: Option<crate::expressive::Pwm>,
+    /// Starting oscillator phase as a fraction of a cycle (0.0-1.0, default: 0.0)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_phase: Option<f32>,
+    /// Vibrato LFO rate in Hz (0.0-20.0, optional; 0 disables)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_vibrato_rate: Option<f32>,
+    /// Vibrato depth in semitones either side of the note (0.0-2.0, optional; 0 disables)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_vibrato_depth: Option<f32>,
+    /// Tremolo LFO rate in Hz (0.0-20.0, optional; 0 disables)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_tremolo_rate: Option<f32>,
+    /// Tremolo depth as the fraction of the level it dips by (0.0-1.0, optional; 0 disables)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_tremolo_depth: Option<f32>,
     /// FM modulator frequency in Hz (0.1-1000.0, optional)
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub synth_modulator_freq: Option<f32>,
@@ -495,6 +1042,21 @@ pub struct SimpleNote {
     /// Texture roughness (0.0-1.0, optional)
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub synth_texture_roughness: Option<f32>,
+    /// Morph position across sine/triangle/sawtooth/square (0.0-1.0, optional)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_morph_position: Option<f32>,
+    /// Morph position reached at the end of the note, sweeping from synth_morph_position (0.0-1.0, optional)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_morph_end: Option<f32>,
+    /// Drone harmonic count (1-16, optional, default: 6)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_drone_harmonics: Option<u8>,
+    /// How fast the drone's harmonic balance drifts in Hz (0.0-1.0, optional, default: 0.05)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_drone_evolution_rate: Option<f32>,
+    /// Detune between each drone harmonic's paired oscillators in cents (0.0-50.0, optional, default: 4.0)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub synth_drone_detune: Option<f32>,
 
     // NEW: Classic Synthesizer Preset parameters (optional)
     /// Preset name to load (e.g., "Minimoog Bass", "TB-303 Acid")
@@ -509,6 +1071,15 @@ pub struct SimpleNote {
     /// If true, select random preset from category
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub preset_random: Option<bool>,
+    /// Jitter the preset's envelope, filter and effect parameters by this amount (0.0-1.0)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub preset_randomize_amount: Option<f32>,
+    /// Seed for `preset_randomize_amount` so a variation can be recalled (default: drawn from the sequence seed)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub preset_randomize_seed: Option<u64>,
+    /// Blend two presets instead of loading one, e.g. 40% of the way from "Minimoog Bass" to "Jupiter Bass"
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub preset_morph: Option<PresetMorph>,
 
     // NEW: Universal Effects Parameters (compatible with all audio sources)
     /// Effects chain to apply to this note
@@ -519,6 +1090,31 @@ pub struct SimpleNote {
     pub effects_preset: Option<String>,
 }
 
+/// Synthesis type names accepted in `synth_type`
+pub const VALID_SYNTH_TYPES: [&str; 21] = [
+    "sine",
+    "square",
+    "sawtooth",
+    "triangle",
+    "noise",
+    "morph",
+    "fm",
+    "dx7fm",
+    "granular",
+    "wavetable",
+    "kick",
+    "snare",
+    "hihat",
+    "cymbal",
+    "swoosh",
+    "zap",
+    "chime",
+    "burst",
+    "pad",
+    "texture",
+    "drone",
+];
+
 fn default_note_type() -> String {
     "midi".to_string()
 }
@@ -530,10 +1126,262 @@ pub struct SimpleSequence {
     /// Tempo in BPM (optional, defaults to 120)
     #[serde(default = "default_tempo")]
     pub tempo: u32,
+    /// Time signature beats per bar, used to convert musical_time/musical_duration (default: 4)
+    #[serde(default = "default_beats_per_bar")]
+    pub beats_per_bar: u32,
+    /// Seed for every random choice in the sequence (random presets, noise), for reproducible renders
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub master_seed: Option<u64>,
+    /// Minimum sounding time in seconds for short notes on sustaining MIDI instruments,
+    /// so they reach their natural release instead of clicking (drums are unaffected)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub min_release: Option<f64>,
+    /// Pan each note by register (lows center, highs toward the right) unless it sets pan/balance
+    #[serde(default)]
+    pub auto_pan_by_pitch: bool,
+    /// Sum all channel 9 drums through one shared glue compressor before mixing
+    #[serde(default)]
+    pub drum_glue: bool,
+    /// Brickwall limit the final mix just under full scale so overlapping notes never
+    /// hard-clip (default: true)
+    #[serde(default = "default_true")]
+    pub master_limiter: bool,
+    /// Instrument changes per MIDI channel over time; each affects later note-ons only
+    #[serde(default)]
+    pub program_changes: Vec<ProgramChange>,
+    /// Drum-aware humanization of channel 9 (0.0-1.0): tight kick/snare, looser tapered hi-hats
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub drum_humanize: Option<f32>,
+    /// Per-channel defaults for instrument, mix and effects; notes' own values win
+    #[serde(default)]
+    pub channels: Vec<ChannelConfig>,
+    /// Integrated loudness to normalize the whole render to, in LUFS (e.g. -16.0)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub target_lufs: Option<f32>,
+    /// Stretch or squeeze all note timing so the sequence lasts exactly this many seconds
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub fit_duration: Option<f64>,
+    /// How much note velocity opens synthesis filters (0.0-1.0, default: 0.0 = off)
+    #[serde(default = "default_velocity_brightness")]
+    pub velocity_brightness: f32,
+    /// Level in dBFS below which the render stops once every note has ended, trimming
+    /// silent reverb and decay tails (-120 to -20, default: -60)
+    #[serde(default = "default_tail_cutoff_db")]
+    pub tail_cutoff_db: f32,
+    /// Clamp notes that transforms move before time zero to 0.0, or reject the sequence
+    #[serde(default)]
+    pub negative_start: resolve::NegativeStartMode,
+    /// Chord changes by bar that `snap_to_chord` notes follow
+    #[serde(default)]
+    pub chord_track: Vec<ChordChange>,
+    /// Delay every off-beat `swing_subdivision` by this fraction of a subdivision (0.0-0.75,
+    /// default 0.0 = straight)
+    #[serde(default)]
+    pub swing: f32,
+    /// Which off-beats swing delays (default: 8th)
+    #[serde(default)]
+    pub swing_subdivision: SwingSubdivision,
+    /// Tempo ramps for accelerando and ritardando: BPM moves linearly between successive
+    /// changes, starting from `tempo`. Applies to musical_time, musical_duration and beats.
+    #[serde(default)]
+    pub tempo_changes: Vec<TempoChange>,
+}
+
+fn default_tempo() -> u32 {
+    120
+}
+
+fn default_velocity_brightness() -> f32 {
+    resolve::DEFAULT_VELOCITY_BRIGHTNESS
+}
+
+fn default_tail_cutoff_db() -> f32 {
+    player::DEFAULT_TAIL_CUTOFF_DB
+}
+
+/// Program (instrument) change on a MIDI channel at an absolute time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramChange {
+    /// Time in seconds from the start of the sequence
+    pub at: f64,
+    /// GM instrument to switch to (0-127)
+    pub instrument: u8,
+    /// MIDI channel to change (0-15, default: 0)
+    #[serde(default)]
+    pub channel: u8,
+}
+
+/// Chord that holds from `bar` until the next change in a chord track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChordChange {
+    /// Bar where the chord starts (1-based)
+    pub bar: u32,
+    /// Chord tones (MIDI notes); only their pitch classes matter for snapping
+    pub notes: Vec<u8>,
+}
+
+/// Defaults applied to every note on a MIDI channel that doesn't set its own value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    /// MIDI channel these defaults apply to (0-15)
+    pub channel: u8,
+    /// GM instrument (0-127); not allowed on channel 9, which is reserved for drums
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub instrument: Option<u8>,
+    /// Channel volume (0-127)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub volume: Option<u8>,
+    /// Stereo pan (0 = left, 64 = center, 127 = right)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub pan: Option<u8>,
+    /// Reverb send (0-127)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub reverb: Option<u8>,
+    /// Chorus send (0-127)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub chorus: Option<u8>,
+    /// Effects chain for notes without their own `effects` or `effects_preset`
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub effects: Option<Vec<EffectConfig>>,
+}
+
+/// Check a sequence time signature has a usable number of beats per bar
+pub fn validate_beats_per_bar(beats_per_bar: u32) -> Result<(), String> {
+    if !(1..=16).contains(&beats_per_bar) {
+        return Err(format!(
+            "beats_per_bar must be between 1 and 16, got {}",
+            beats_per_bar
+        ));
+    }
+    Ok(())
+}
+
+/// Check program changes have valid instruments and channels and are listed in ascending time
+pub fn validate_program_changes(changes: &[ProgramChange]) -> Result<(), String> {
+    for (i, change) in changes.iter().enumerate() {
+        if !change.at.is_finite() || change.at < 0.0 {
+            return Err(format!(
+                "Program change {} time must be 0 or later, got {}",
+                i + 1,
+                change.at
+            ));
+        }
+        if change.instrument > 127 {
+            return Err(format!(
+                "Program change {} instrument must be 0-127, got {}",
+                i + 1,
+                change.instrument
+            ));
+        }
+        if change.channel > 15 {
+            return Err(format!(
+                "Program change {} channel must be 0-15, got {}",
+                i + 1,
+                change.channel
+            ));
+        }
+        if change.channel == 9 {
+            return Err(format!(
+                "Program change {} targets channel 9, which is reserved for drums",
+                i + 1
+            ));
+        }
+        if i > 0 && change.at < changes[i - 1].at {
+            return Err(format!(
+                "Program changes must be in ascending time order: change {} at {}s comes after {}s",
+                i + 1,
+                change.at,
+                changes[i - 1].at
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check chord changes start on 1-based bars in ascending order and name valid chord tones
+pub fn validate_chord_track(track: &[ChordChange]) -> Result<(), String> {
+    for (i, chord) in track.iter().enumerate() {
+        if chord.bar == 0 {
+            return Err(format!("Chord {} bar is 1-based, got 0", i + 1));
+        }
+        if chord.notes.is_empty() {
+            return Err(format!("Chord {} at bar {} has no notes", i + 1, chord.bar));
+        }
+        if chord.notes.iter().any(|&note| note > 127) {
+            return Err(format!(
+                "Chord {} at bar {} notes must be MIDI notes 0-127",
+                i + 1,
+                chord.bar
+            ));
+        }
+        if i > 0 && chord.bar <= track[i - 1].bar {
+            return Err(format!(
+                "Chord track bars must be ascending: chord {} at bar {} comes after bar {}",
+                i + 1,
+                chord.bar,
+                track[i - 1].bar
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check channel configs target distinct valid channels with in-range values
+pub fn validate_channel_configs(configs: &[ChannelConfig]) -> Result<(), String> {
+    for (i, config) in configs.iter().enumerate() {
+        if config.channel > 15 {
+            return Err(format!(
+                "Channel config {} channel must be 0-15, got {}",
+                i + 1,
+                config.channel
+            ));
+        }
+        if configs[..i]
+            .iter()
+            .any(|other| other.channel == config.channel)
+        {
+            return Err(format!(
+                "Channel {} is configured more than once",
+                config.channel
+            ));
+        }
+        if config.instrument.is_some() && config.channel == 9 {
+            return Err(format!(
+                "Channel config {} sets an instrument on channel 9, which is reserved for drums",
+                i + 1
+            ));
+        }
+        for (name, value) in [
+            ("instrument", config.instrument),
+            ("volume", config.volume),
+            ("pan", config.pan),
+            ("reverb", config.reverb),
+            ("chorus", config.chorus),
+        ] {
+            if let Some(value) = value
+                && value > 127
+            {
+                return Err(format!(
+                    "Channel config {} {} must be 0-127, got {}",
+                    i + 1,
+                    name,
+                    value
+                ));
+            }
+        }
+        for (j, effect) in config.effects.iter().flatten().enumerate() {
+            SimpleNote::validate_single_effect(effect).map_err(|e| {
+                format!("Channel config {} effect {} in chain: {}", i + 1, j + 1, e)
+            })?;
+        }
+    }
+    Ok(())
 }
 
-fn default_tempo() -> u32 {
-    120
+impl Default for SimpleSequence {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SimpleSequence {
@@ -542,6 +1390,24 @@ impl SimpleSequence {
         Self {
             notes: Vec::new(),
             tempo: 120,
+            beats_per_bar: 4,
+            master_seed: None,
+            min_release: None,
+            auto_pan_by_pitch: false,
+            drum_glue: false,
+            master_limiter: true,
+            program_changes: Vec::new(),
+            drum_humanize: None,
+            channels: Vec::new(),
+            target_lufs: None,
+            fit_duration: None,
+            velocity_brightness: resolve::DEFAULT_VELOCITY_BRIGHTNESS,
+            tail_cutoff_db: player::DEFAULT_TAIL_CUTOFF_DB,
+            negative_start: resolve::NegativeStartMode::Clamp,
+            chord_track: Vec::new(),
+            swing: 0.0,
+            swing_subdivision: SwingSubdivision::Eighth,
+            tempo_changes: Vec::new(),
         }
     }
 
@@ -576,6 +1442,7 @@ impl SimpleSequence {
             r2d2_complexity: None,
             r2d2_pitch_range: None,
             r2d2_context: None,
+            r2d2_voice: None,
             synth_type: None,
             synth_frequency: None,
             synth_amplitude: None,
@@ -583,22 +1450,45 @@ impl SimpleSequence {
             synth_decay: None,
             synth_sustain: None,
             synth_release: None,
+            synth_envelope_curve: None,
             synth_filter_type: None,
             synth_filter_cutoff: None,
             synth_filter_resonance: None,
+            synth_filter_soft_start: None,
             synth_reverb: None,
             synth_chorus: None,
             synth_delay: None,
             synth_delay_time: None,
             synth_pulse_width: None,
+            synth_noise_color: None,
+            synth_pwm: None,
+            synth_phase: None,
+            synth_vibrato_rate: None,
+            synth_vibrato_depth: None,
+            synth_tremolo_rate: None,
+            synth_tremolo_depth: None,
             synth_modulator_freq: None,
             synth_modulation_index: None,
             synth_grain_size: None,
             synth_texture_roughness: None,
+            synth_morph_position: None,
+            synth_morph_end: None,
+            synth_drone_harmonics: None,
+            synth_drone_evolution_rate: None,
+            synth_drone_detune: None,
+            beats: None,
+            pre_roll: None,
+            roll_accelerate: None,
+            articulation: None,
+            tremolo_pick: None,
+            snap_to_chord: false,
             preset_name: None,
             preset_category: None,
             preset_variation: None,
             preset_random: None,
+            preset_randomize_amount: None,
+            preset_randomize_seed: None,
+            preset_morph: None,
             effects: None,
             effects_preset: None,
         });
@@ -637,6 +1527,7 @@ impl SimpleSequence {
             r2d2_complexity: None,
             r2d2_pitch_range: None,
             r2d2_context: None,
+            r2d2_voice: None,
             synth_type: None,
             synth_frequency: None,
             synth_amplitude: None,
@@ -644,22 +1535,45 @@ impl SimpleSequence {
             synth_decay: None,
             synth_sustain: None,
             synth_release: None,
+            synth_envelope_curve: None,
             synth_filter_type: None,
             synth_filter_cutoff: None,
             synth_filter_resonance: None,
+            synth_filter_soft_start: None,
             synth_reverb: None,
             synth_chorus: None,
             synth_delay: None,
             synth_delay_time: None,
             synth_pulse_width: None,
+            synth_noise_color: None,
+            synth_pwm: None,
+            synth_phase: None,
+            synth_vibrato_rate: None,
+            synth_vibrato_depth: None,
+            synth_tremolo_rate: None,
+            synth_tremolo_depth: None,
             synth_modulator_freq: None,
             synth_modulation_index: None,
             synth_grain_size: None,
             synth_texture_roughness: None,
+            synth_morph_position: None,
+            synth_morph_end: None,
+            synth_drone_harmonics: None,
+            synth_drone_evolution_rate: None,
+            synth_drone_detune: None,
+            beats: None,
+            pre_roll: None,
+            roll_accelerate: None,
+            articulation: None,
+            tremolo_pick: None,
+            snap_to_chord: false,
             preset_name: None,
             preset_category: None,
             preset_variation: None,
             preset_random: None,
+            preset_randomize_amount: None,
+            preset_randomize_seed: None,
+            preset_morph: None,
             effects: None,
             effects_preset: None,
         });
@@ -699,6 +1613,7 @@ impl SimpleSequence {
             r2d2_complexity: None,
             r2d2_pitch_range: None,
             r2d2_context: None,
+            r2d2_voice: None,
             synth_type: None,
             synth_frequency: None,
             synth_amplitude: None,
@@ -706,22 +1621,45 @@ impl SimpleSequence {
             synth_decay: None,
             synth_sustain: None,
             synth_release: None,
+            synth_envelope_curve: None,
             synth_filter_type: None,
             synth_filter_cutoff: None,
             synth_filter_resonance: None,
+            synth_filter_soft_start: None,
             synth_reverb: None,
             synth_chorus: None,
             synth_delay: None,
             synth_delay_time: None,
             synth_pulse_width: None,
+            synth_noise_color: None,
+            synth_pwm: None,
+            synth_phase: None,
+            synth_vibrato_rate: None,
+            synth_vibrato_depth: None,
+            synth_tremolo_rate: None,
+            synth_tremolo_depth: None,
             synth_modulator_freq: None,
             synth_modulation_index: None,
             synth_grain_size: None,
             synth_texture_roughness: None,
+            synth_morph_position: None,
+            synth_morph_end: None,
+            synth_drone_harmonics: None,
+            synth_drone_evolution_rate: None,
+            synth_drone_detune: None,
+            beats: None,
+            pre_roll: None,
+            roll_accelerate: None,
+            articulation: None,
+            tremolo_pick: None,
+            snap_to_chord: false,
             preset_name: None,
             preset_category: None,
             preset_variation: None,
             preset_random: None,
+            preset_randomize_amount: None,
+            preset_randomize_seed: None,
+            preset_morph: None,
             effects: None,
             effects_preset: None,
         });
@@ -783,6 +1721,7 @@ impl SimpleSequence {
             r2d2_complexity: Some(complexity),
             r2d2_pitch_range: pitch_range,
             r2d2_context: context,
+            r2d2_voice: None,
             synth_type: None,
             synth_frequency: None,
             synth_amplitude: None,
@@ -790,22 +1729,45 @@ impl SimpleSequence {
             synth_decay: None,
             synth_sustain: None,
             synth_release: None,
+            synth_envelope_curve: None,
             synth_filter_type: None,
             synth_filter_cutoff: None,
             synth_filter_resonance: None,
+            synth_filter_soft_start: None,
             synth_reverb: None,
             synth_chorus: None,
             synth_delay: None,
             synth_delay_time: None,
             synth_pulse_width: None,
+            synth_noise_color: None,
+            synth_pwm: None,
+            synth_phase: None,
+            synth_vibrato_rate: None,
+            synth_vibrato_depth: None,
+            synth_tremolo_rate: None,
+            synth_tremolo_depth: None,
             synth_modulator_freq: None,
             synth_modulation_index: None,
             synth_grain_size: None,
             synth_texture_roughness: None,
+            synth_morph_position: None,
+            synth_morph_end: None,
+            synth_drone_harmonics: None,
+            synth_drone_evolution_rate: None,
+            synth_drone_detune: None,
+            beats: None,
+            pre_roll: None,
+            roll_accelerate: None,
+            articulation: None,
+            tremolo_pick: None,
+            snap_to_chord: false,
             preset_name: None,
             preset_category: None,
             preset_variation: None,
             preset_random: None,
+            preset_randomize_amount: None,
+            preset_randomize_seed: None,
+            preset_morph: None,
             effects: None,
             effects_preset: None,
         });
@@ -828,12 +1790,26 @@ pub struct SequencePattern {
     /// Pattern length in bars (ensures proper looping/alignment)
     #[serde(default = "default_pattern_bars")]
     pub pattern_bars: f64,
-    /// Time signature (beats per bar)
+    /// Time signature (beats per bar). Reads the pattern's own musical_time and
+    /// musical_duration; where the pattern lands is counted in the sequence's meter.
     #[serde(default = "default_beats_per_bar")]
     pub beats_per_bar: u32,
-    /// Quantization grid for this pattern
+    /// Grid that note starts snap to when the pattern is resolved ("off" keeps them as written)
     #[serde(default)]
     pub quantize_grid: QuantizeGrid,
+    /// Snap each note's end to `quantize_grid` so notes fill to grid boundaries (gate_length wins)
+    #[serde(default)]
+    pub quantize_durations: bool,
+    /// Sounding length of each note as a fraction of its step spacing (0.05-1.0, overrides note durations)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub gate_length: Option<f32>,
+    /// Delay every off-beat `swing_subdivision` within the pattern by this fraction of a
+    /// subdivision (0.0-0.75); a swung pattern ignores the sequence's swing
+    #[serde(default)]
+    pub swing: f32,
+    /// Which off-beats swing delays (default: 8th)
+    #[serde(default)]
+    pub swing_subdivision: SwingSubdivision,
     /// Pattern category for organization (e.g., "drums", "bass", "melody")
     pub category: Option<String>,
     /// Tags for searching/filtering
@@ -841,6 +1817,12 @@ pub struct SequencePattern {
     pub tags: Vec<String>,
 }
 
+/// Furthest a note's start_time and longest its duration may be, in seconds
+pub const MAX_NOTE_SECONDS: f64 = 3600.0;
+
+/// Articulations `SimpleNote::articulation` accepts
+pub const ARTICULATIONS: [&str; 5] = ["staccato", "legato", "tenuto", "accent", "marcato"];
+
 fn default_pattern_bars() -> f64 {
     4.0
 }
@@ -849,15 +1831,17 @@ fn default_beats_per_bar() -> u32 {
     4
 }
 
-/// Reference to a sequence pattern with transformations applied
+/// Reference to a sequence pattern with transformations applied. A reference is placed by
+/// exactly one of `bars`, `start_bar` or `start_time_offset`; with none it starts at bar 1.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SequenceReference {
     /// Name of the pattern to reference
     pub pattern_name: String,
-    /// Start time offset for this pattern instance (seconds - deprecated)
+    /// Start time offset for this pattern instance (seconds - deprecated; not combinable
+    /// with `start_bar` or `bars`)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub start_time_offset: Option<f64>,
-    /// Musical start position (bar number, 1-based)
+    /// Musical start position (bar number, 1-based; not combinable with `bars`)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub start_bar: Option<u32>,
     /// Start on specific beat within the bar (1-based)
@@ -888,6 +1872,111 @@ pub struct SequenceReference {
     /// Align pattern to bar boundaries
     #[serde(default = "default_true")]
     pub align_to_bars: bool,
+    /// Play the pattern backwards in time (retrograde)
+    #[serde(default)]
+    pub retrograde: bool,
+    /// Mirror MIDI pitches around this pivot note (melodic inversion)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub invert_around: Option<u8>,
+    /// Chord roots by bar; each placement is transposed from C to the root active at its bar
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub follow_chords: Option<Vec<ChordRoot>>,
+    /// Key to snap MIDI pitches into after inversion and transposition (drums are exempt)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub scale_lock: Option<ScaleLock>,
+}
+
+/// A key that transformed pattern notes are held to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaleLock {
+    /// Scale root as a MIDI note; only its pitch class is used
+    pub root: u8,
+    /// Scale name from `scales::SCALES` (e.g. "major", "dorian", "minor_pentatonic")
+    pub scale: String,
+}
+
+impl ScaleLock {
+    /// Nearest in-scale pitch to `pitch`
+    fn snap(&self, pitch: u8) -> u8 {
+        scales::scale_intervals(&self.scale).map_or(pitch, |intervals| {
+            scales::snap_to_scale(pitch, self.root % 12, intervals)
+        })
+    }
+}
+
+/// A chord root that takes effect from the given bar until the next entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChordRoot {
+    /// Bar where this chord starts (1-based)
+    pub bar: u32,
+    /// Chord root as a MIDI note; only its pitch class is used
+    pub root: u8,
+}
+
+impl SequenceReference {
+    /// Reject conflicting placements and transform scales outside their documented ranges
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_placement()?;
+        if !(0.1..=2.0).contains(&self.velocity_scale) {
+            return Err(format!(
+                "Pattern reference '{}' has velocity_scale {}; it must be between 0.1 and 2.0",
+                self.pattern_name, self.velocity_scale
+            ));
+        }
+        if !(0.1..=4.0).contains(&self.duration_scale) {
+            return Err(format!(
+                "Pattern reference '{}' has duration_scale {}; it must be between 0.1 and 4.0",
+                self.pattern_name, self.duration_scale
+            ));
+        }
+        if let Some(lock) = &self.scale_lock {
+            if lock.root > 127 {
+                return Err(format!(
+                    "Pattern reference '{}' has scale_lock root {}; it must be 0-127",
+                    self.pattern_name, lock.root
+                ));
+            }
+            scales::scale_intervals(&lock.scale)
+                .map_err(|e| format!("Pattern reference '{}': {}", self.pattern_name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Reject references that pick more than one way of placing the pattern
+    pub fn validate_placement(&self) -> Result<(), String> {
+        let conflict = match (
+            self.start_time_offset.is_some(),
+            self.start_bar.is_some(),
+            self.bars.is_some(),
+        ) {
+            (true, true, _) => Some(("start_time_offset", "start_bar")),
+            (true, _, true) => Some(("start_time_offset", "bars")),
+            (_, true, true) => Some(("start_bar", "bars")),
+            _ => None,
+        };
+        match conflict {
+            Some((first, second)) => Err(format!(
+                "Pattern reference '{}' sets both '{}' and '{}'; place it with only one of \
+                 'bars' (every placement listed), 'start_bar' (with repeat_count) or \
+                 'start_time_offset' (seconds, deprecated)",
+                self.pattern_name, first, second
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Semitones to shift a placement starting at `bar` so a pattern written over C follows the chord
+    fn chord_transpose(&self, bar: u32) -> i16 {
+        self.follow_chords
+            .as_ref()
+            .and_then(|chords| {
+                chords
+                    .iter()
+                    .filter(|chord| chord.bar <= bar)
+                    .max_by_key(|chord| chord.bar)
+            })
+            .map_or(0, |chord| (chord.root % 12) as i16)
+    }
 }
 
 fn default_start_beat() -> u32 {
@@ -907,9 +1996,88 @@ pub struct ExtendedSequence {
     /// Pattern references with transformations
     #[serde(default)]
     pub patterns: Vec<SequenceReference>,
+    /// Chord or scale arpeggios expanded into notes
+    #[serde(default)]
+    pub arpeggios: Vec<Arpeggio>,
     /// Tempo in BPM (optional, defaults to 120)
     #[serde(default = "default_tempo")]
     pub tempo: u32,
+    /// Time signature beats per bar (default: 4), used to convert musical_time/musical_duration
+    /// and to place patterns: start_bar, bars and start_beat count bars of this length
+    #[serde(default = "default_beats_per_bar")]
+    pub beats_per_bar: u32,
+    /// Seed for every random choice in the sequence (random presets, noise), for reproducible renders
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub master_seed: Option<u64>,
+    /// Minimum sounding time in seconds for short notes on sustaining MIDI instruments,
+    /// so they reach their natural release instead of clicking (drums are unaffected)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub min_release: Option<f64>,
+    /// Pan each note by register (lows center, highs toward the right) unless it sets pan/balance
+    #[serde(default)]
+    pub auto_pan_by_pitch: bool,
+    /// Sum all channel 9 drums through one shared glue compressor before mixing
+    #[serde(default)]
+    pub drum_glue: bool,
+    /// Brickwall limit the final mix just under full scale so overlapping notes never
+    /// hard-clip (default: true)
+    #[serde(default = "default_true")]
+    pub master_limiter: bool,
+    /// Instrument changes per MIDI channel over time; each affects later note-ons only
+    #[serde(default)]
+    pub program_changes: Vec<ProgramChange>,
+    /// Drum-aware humanization of channel 9 (0.0-1.0): tight kick/snare, looser tapered hi-hats
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub drum_humanize: Option<f32>,
+    /// Per-channel defaults for instrument, mix and effects; notes' own values win
+    #[serde(default)]
+    pub channels: Vec<ChannelConfig>,
+    /// Integrated loudness to normalize the whole render to, in LUFS (e.g. -16.0)
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub target_lufs: Option<f32>,
+    /// Stretch or squeeze all note timing so the sequence lasts exactly this many seconds
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub fit_duration: Option<f64>,
+    /// How much note velocity opens synthesis filters (0.0-1.0, default: 0.0 = off)
+    #[serde(default = "default_velocity_brightness")]
+    pub velocity_brightness: f32,
+    /// Level in dBFS below which the render stops once every note has ended, trimming
+    /// silent reverb and decay tails (-120 to -20, default: -60)
+    #[serde(default = "default_tail_cutoff_db")]
+    pub tail_cutoff_db: f32,
+    /// Clamp notes that transforms move before time zero to 0.0, or reject the sequence
+    #[serde(default)]
+    pub negative_start: resolve::NegativeStartMode,
+    /// Chord changes by bar that `snap_to_chord` notes follow
+    #[serde(default)]
+    pub chord_track: Vec<ChordChange>,
+    /// Delay every off-beat `swing_subdivision` by this fraction of a subdivision (0.0-0.75,
+    /// default 0.0 = straight)
+    #[serde(default)]
+    pub swing: f32,
+    /// Which off-beats swing delays (default: 8th)
+    #[serde(default)]
+    pub swing_subdivision: SwingSubdivision,
+    /// Tempo ramps for accelerando and ritardando, as on `SimpleSequence`. Also places
+    /// patterns: start_bar, bars and start_beat follow the ramps.
+    #[serde(default)]
+    pub tempo_changes: Vec<TempoChange>,
+}
+
+/// One pattern in a medley: a bare pattern name or a name with per-segment overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConcatSegment {
+    Name(String),
+    Segment {
+        pattern_name: String,
+        /// Transpose this segment by semitones (-12 to +12)
+        #[serde(default)]
+        transpose: i8,
+        /// Override instrument for the MIDI notes in this segment
+        #[serde(default)]
+        instrument_override: Option<u8>,
+    },
 }
 
 impl SequencePattern {
@@ -923,19 +2091,181 @@ impl SequencePattern {
             pattern_bars: 4.0,
             beats_per_bar: 4,
             quantize_grid: QuantizeGrid::Off,
+            quantize_durations: false,
+            gate_length: None,
+            swing: 0.0,
+            swing_subdivision: SwingSubdivision::Eighth,
             category: None,
             tags: Vec::new(),
         }
     }
 
-    /// Apply transformations to create a concrete sequence of notes
+    /// Whether the pattern carries every one of `tags` and sits in `category` (when
+    /// given), ignoring case
+    pub fn matches_filter(&self, tags: &[String], category: Option<&str>) -> bool {
+        let has_tag =
+            |wanted: &String| self.tags.iter().any(|tag| tag.eq_ignore_ascii_case(wanted));
+        let in_category = category.is_none_or(|wanted| {
+            self.category
+                .as_deref()
+                .is_some_and(|category| category.eq_ignore_ascii_case(wanted))
+        });
+        in_category && tags.iter().all(has_tag)
+    }
+
+    /// Validate pattern-level parameters
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(gate_length) = self.gate_length
+            && !(0.05..=1.0).contains(&gate_length)
+        {
+            return Err(format!(
+                "Gate length must be between 0.05 and 1.0, got {}",
+                gate_length
+            ));
+        }
+        if self.quantize_durations && matches!(self.quantize_grid, QuantizeGrid::Off) {
+            return Err("quantize_durations needs a quantize_grid other than 'off'".to_string());
+        }
+        validate_swing(self.swing)?;
+        Ok(())
+    }
+
+    /// Run every note through the same validators as played notes, naming the first bad note
+    pub fn validate_notes(&self) -> Result<(), String> {
+        for (i, note) in self.notes.iter().enumerate() {
+            note.validate_timing()
+                .map_err(|e| format!("Invalid timing parameters in note {}: {}", i + 1, e))?;
+            note.validate_r2d2()
+                .map_err(|e| format!("Invalid R2D2 parameters in note {}: {}", i + 1, e))?;
+            note.validate_synthesis()
+                .map_err(|e| format!("Invalid synthesis parameters in note {}: {}", i + 1, e))?;
+            note.validate_preset()
+                .map_err(|e| format!("Invalid preset parameters in note {}: {}", i + 1, e))?;
+        }
+        Ok(())
+    }
+
+    /// Copy of `note` with its start snapped to the nearest `quantize_grid` line. Musical
+    /// times snap by ticks, seconds at the pattern tempo; with the grid off the copy is exact.
+    fn quantized_note(&self, note: &SimpleNote) -> SimpleNote {
+        let mut note = note.clone();
+        let Some(grid_beats) = self.quantize_grid.beats(self.beats_per_bar) else {
+            return note;
+        };
+        if let Some(musical_time) = &note.musical_time {
+            note.musical_time = Some(match self.quantize_grid.divisions_per_beat() {
+                Some(divisions) => musical_time.quantize(divisions, 480, self.beats_per_bar),
+                None => {
+                    // Bar grid: round to the nearer bar line
+                    let into_bar =
+                        (musical_time.beat - 1) as f64 + musical_time.tick as f64 / 480.0;
+                    let bar = if into_bar * 2.0 >= self.beats_per_bar as f64 {
+                        musical_time.bar + 1
+                    } else {
+                        musical_time.bar
+                    };
+                    MusicalTime::new(bar, 1, 0)
+                }
+            });
+        } else if let Some(start_time) = note.start_time {
+            let grid = grid_beats * 60.0 / self.tempo as f64;
+            note.start_time = Some((start_time / grid).round() * grid);
+        }
+        note
+    }
+
+    /// Duration in seconds that moves the note's end to the nearest grid line,
+    /// never shorter than reaching the first grid line after its start
+    fn quantized_duration(&self, start: f64, duration: f64) -> f64 {
+        let Some(grid_beats) = self.quantize_grid.beats(self.beats_per_bar) else {
+            return duration;
+        };
+        let grid = grid_beats * 60.0 / self.tempo as f64;
+        let nearest_end = ((start + duration) / grid).round() * grid;
+        let first_line_after_start = ((start / grid + 1e-9).floor() + 1.0) * grid;
+        nearest_end.max(first_line_after_start) - start
+    }
+
+    /// Scale every note's start and duration so the last note ends exactly at the end of
+    /// `pattern_bars`. Returns where the notes ended before, in beats, or None when the
+    /// pattern has no length to fill.
+    pub fn normalize_to_bars(&mut self) -> Option<f64> {
+        let seconds_per_beat = 60.0 / self.tempo as f64;
+        let timings: Vec<(f64, f64)> = self
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.get_start_time(self.tempo, self.beats_per_bar),
+                    note.get_duration(self.tempo, self.beats_per_bar),
+                )
+            })
+            .collect();
+        let end = timings
+            .iter()
+            .map(|(start, duration)| start + duration)
+            .fold(0.0, f64::max);
+        if self.pattern_bars <= 0.0 || end <= 0.0 {
+            return None;
+        }
+
+        let scale = self.get_pattern_duration() / end;
+        for (note, (start, duration)) in self.notes.iter_mut().zip(timings) {
+            note.start_time = Some(start * scale);
+            note.duration = Some(duration * scale);
+            note.musical_time = None;
+            note.musical_duration = None;
+            note.beats = None;
+        }
+        Some(end / seconds_per_beat)
+    }
+
+    /// Distance in seconds from each note's start to the next later note start,
+    /// or to the end of the pattern for the last step
+    fn step_spacings(&self) -> Vec<f64> {
+        let mut starts: Vec<f64> = self
+            .notes
+            .iter()
+            .map(|note| {
+                self.quantized_note(note)
+                    .get_start_time(self.tempo, self.beats_per_bar)
+            })
+            .collect();
+        let note_starts = starts.clone();
+        starts.sort_by(|a, b| a.total_cmp(b));
+        starts.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+        let pattern_end = self.get_pattern_duration();
+
+        note_starts
+            .iter()
+            .map(|&start| {
+                let next = starts
+                    .iter()
+                    .copied()
+                    .find(|&s| s > start + 1e-9)
+                    .unwrap_or(pattern_end);
+                (next - start).max(0.0)
+            })
+            .collect()
+    }
+
+    /// Apply transformations to create a concrete sequence of notes.
+    ///
+    /// Placement bars and beats are in the sequence's meter (`sequence_beats_per_bar`).
+    /// Notes timed with musical_time keep their beat count from the start of the pattern,
+    /// read in the pattern's meter, so a 3/4 pattern's bar 2 beat 1 (its fourth beat) lands
+    /// three beats after the placement in any sequence meter. Repeats step by `pattern_bars`
+    /// bars of the sequence, and bars, beats and musical_time notes are placed along the
+    /// sequence's tempo map; seconds-timed notes keep their offsets from the placement.
     pub fn apply_reference(
         &self,
         reference: &SequenceReference,
-        sequence_tempo: u32,
+        tempo_map: &TempoMap,
         sequence_beats_per_bar: u32,
     ) -> Result<Vec<SimpleNote>, String> {
+        reference.validate()?;
         let mut transformed_notes = Vec::new();
+        let step_spacings = self.gate_length.map(|_| self.step_spacings());
 
         // Determine where to place pattern instances
         let placements: Vec<(u32, u32)> = if let Some(bars) = &reference.bars {
@@ -956,7 +2286,7 @@ impl SequencePattern {
             return self.apply_reference_legacy(
                 reference,
                 start_offset,
-                sequence_tempo,
+                tempo_map,
                 sequence_beats_per_bar,
             );
         } else {
@@ -971,19 +2301,27 @@ impl SequencePattern {
 
         // Process each pattern placement
         for (bar, beat) in placements {
-            let bar_start_time =
-                self.calculate_bar_start_time(bar, sequence_tempo, sequence_beats_per_bar);
-            let beat_offset = ((beat - 1) as f64 / sequence_beats_per_bar as f64)
-                * (60.0 / sequence_tempo as f64)
-                * sequence_beats_per_bar as f64;
-            let placement_start_time = bar_start_time + beat_offset;
+            let placement_start_time = tempo_map
+                .seconds_at((bar - 1) as f64 * sequence_beats_per_bar as f64 + (beat - 1) as f64);
+            let transpose = reference.transpose as i16 + reference.chord_transpose(bar);
+            let mut placement_notes = Vec::with_capacity(self.notes.len());
 
-            for note in &self.notes {
-                let mut transformed_note = note.clone();
+            for (index, note) in self.notes.iter().enumerate() {
+                let mut transformed_note = self.quantized_note(note);
 
                 // Convert musical time to seconds if needed - use pattern's own tempo for internal timing
-                let note_start_offset = note.get_start_time(self.tempo, self.beats_per_bar);
-                let note_duration = note.get_duration(self.tempo, self.beats_per_bar);
+                let note_start_offset =
+                    transformed_note.get_start_time(self.tempo, self.beats_per_bar);
+                let mut note_duration = note.get_duration(self.tempo, self.beats_per_bar);
+
+                // Gate length replaces the note's own duration with a fraction of its step
+                if let (Some(gate_length), Some(spacings)) = (self.gate_length, &step_spacings) {
+                    note_duration = spacings[index] * gate_length as f64;
+                    transformed_note.musical_duration = None;
+                } else if self.quantize_durations {
+                    note_duration = self.quantized_duration(note_start_offset, note_duration);
+                    transformed_note.musical_duration = None;
+                }
 
                 // Apply timing transformation
                 if let Some(musical_time) = &transformed_note.musical_time {
@@ -991,7 +2329,12 @@ impl SequencePattern {
                     // Pattern placement bar + note's relative position within pattern
                     let note_relative_beats =
                         (musical_time.bar - 1) * self.beats_per_bar + (musical_time.beat - 1);
-                    let note_relative_beat_fraction = musical_time.tick as f64 / 480.0; // Convert ticks to beat fraction
+                    // Convert ticks to beat fraction; whole beats are never swung
+                    let note_relative_beat_fraction = swing_beats(
+                        musical_time.tick as f64 / 480.0,
+                        self.swing,
+                        self.swing_subdivision,
+                    );
 
                     let absolute_bar = bar + (note_relative_beats / sequence_beats_per_bar);
                     let absolute_beat = beat - 1 + (note_relative_beats % sequence_beats_per_bar);
@@ -1004,18 +2347,25 @@ impl SequencePattern {
                     let absolute_beats = (final_bar - 1) as f64 * sequence_beats_per_bar as f64
                         + (final_beat - 1) as f64
                         + note_relative_beat_fraction;
-                    let seconds_per_beat = 60.0 / sequence_tempo as f64;
-                    transformed_note.start_time = Some(absolute_beats * seconds_per_beat);
+                    transformed_note.start_time = Some(tempo_map.seconds_at(absolute_beats));
 
                     // Clear musical time since we're using seconds
                     transformed_note.musical_time = None;
                 } else {
                     // Use seconds-based timing
-                    transformed_note.start_time = Some(placement_start_time + note_start_offset);
+                    let swung_offset = swing_seconds(
+                        note_start_offset,
+                        &TempoMap::new(self.tempo, self.beats_per_bar, &[]),
+                        self.swing,
+                        self.swing_subdivision,
+                    );
+                    transformed_note.start_time = Some(placement_start_time + swung_offset);
                 }
 
                 // Apply duration scaling
-                if let Some(musical_duration) = &transformed_note.musical_duration {
+                if transformed_note.duration.is_none()
+                    && let Some(musical_duration) = &transformed_note.musical_duration
+                {
                     transformed_note.musical_duration = Some(match musical_duration {
                         MusicalDuration::Bars(bars) => {
                             MusicalDuration::Bars(bars * reference.duration_scale as f64)
@@ -1035,10 +2385,16 @@ impl SequencePattern {
                         Some(note_duration * reference.duration_scale as f64);
                 }
 
+                // Apply melodic inversion before transposition
+                if let (Some(midi_note), Some(pivot)) =
+                    (transformed_note.note, reference.invert_around)
+                {
+                    transformed_note.note = Some(Self::invert_pitch(midi_note, pivot));
+                }
+
                 // Apply transposition to MIDI notes
                 if let Some(midi_note) = transformed_note.note {
-                    let new_note =
-                        (midi_note as i16 + reference.transpose as i16).clamp(0, 127) as u8;
+                    let new_note = (midi_note as i16 + transpose).clamp(0, 127) as u8;
                     transformed_note.note = Some(new_note);
                 }
 
@@ -1059,8 +2415,21 @@ impl SequencePattern {
                     transformed_note.channel = channel;
                 }
 
-                transformed_notes.push(transformed_note);
+                // Hold MIDI pitches to the key; drums keep their kit pieces
+                if let (Some(midi_note), Some(lock)) =
+                    (transformed_note.note, &reference.scale_lock)
+                    && transformed_note.channel != 9
+                {
+                    transformed_note.note = Some(lock.snap(midi_note));
+                }
+
+                placement_notes.push(transformed_note);
+            }
+
+            if reference.retrograde {
+                Self::retrograde(&mut placement_notes, tempo_map, sequence_beats_per_bar);
             }
+            transformed_notes.extend(placement_notes);
         }
 
         Ok(transformed_notes)
@@ -1071,31 +2440,60 @@ impl SequencePattern {
         &self,
         reference: &SequenceReference,
         start_offset: f64,
-        sequence_tempo: u32,
-        _sequence_beats_per_bar: u32,
+        tempo_map: &TempoMap,
+        sequence_beats_per_bar: u32,
     ) -> Result<Vec<SimpleNote>, String> {
         let mut transformed_notes = Vec::new();
+        let step_spacings = self.gate_length.map(|_| self.step_spacings());
+        // Pattern notes are timed in seconds at the pattern's own tempo
+        let pattern_tempo = TempoMap::new(self.tempo, self.beats_per_bar, &[]);
+        let spacing_beats = reference.repeat_spacing_bars * self.beats_per_bar as f64;
 
+        let mut repeat_offset = 0.0;
         for repeat in 0..reference.repeat_count {
-            let repeat_offset = repeat as f64
-                * (self.get_pattern_duration()
-                    + reference.repeat_spacing_bars
-                        * (60.0 / sequence_tempo as f64)
-                        * self.beats_per_bar as f64);
-
-            for note in &self.notes {
-                let mut transformed_note = note.clone();
-
-                let note_start = note.get_start_time(self.tempo, self.beats_per_bar);
-                let note_duration = note.get_duration(self.tempo, self.beats_per_bar);
+            // Each repeat follows the previous pass's notes, then the spacing bars as they
+            // fall along the tempo map
+            if repeat > 0 {
+                let pass_end = start_offset + repeat_offset + self.get_pattern_duration();
+                repeat_offset = tempo_map.seconds_at(tempo_map.beat_at(pass_end) + spacing_beats)
+                    - start_offset;
+            }
+            let repeat_beat = tempo_map.beat_at(start_offset + repeat_offset);
+            let repeat_bar = (repeat_beat / sequence_beats_per_bar as f64).floor() as u32 + 1;
+            let transpose = reference.transpose as i16 + reference.chord_transpose(repeat_bar);
+            let mut repeat_notes = Vec::with_capacity(self.notes.len());
+
+            for (index, note) in self.notes.iter().enumerate() {
+                let mut transformed_note = self.quantized_note(note);
+
+                let note_start = transformed_note.get_start_time(self.tempo, self.beats_per_bar);
+                let mut note_duration = note.get_duration(self.tempo, self.beats_per_bar);
+                if let (Some(gate_length), Some(spacings)) = (self.gate_length, &step_spacings) {
+                    note_duration = spacings[index] * gate_length as f64;
+                    transformed_note.musical_duration = None;
+                } else if self.quantize_durations {
+                    note_duration = self.quantized_duration(note_start, note_duration);
+                    transformed_note.musical_duration = None;
+                }
 
-                transformed_note.start_time = Some(start_offset + repeat_offset + note_start);
+                let swung_start = swing_seconds(
+                    note_start,
+                    &pattern_tempo,
+                    self.swing,
+                    self.swing_subdivision,
+                );
+                transformed_note.start_time = Some(start_offset + repeat_offset + swung_start);
                 transformed_note.duration = Some(note_duration * reference.duration_scale as f64);
 
                 // Apply other transformations...
+                if let (Some(midi_note), Some(pivot)) =
+                    (transformed_note.note, reference.invert_around)
+                {
+                    transformed_note.note = Some(Self::invert_pitch(midi_note, pivot));
+                }
+
                 if let Some(midi_note) = transformed_note.note {
-                    let new_note =
-                        (midi_note as i16 + reference.transpose as i16).clamp(0, 127) as u8;
+                    let new_note = (midi_note as i16 + transpose).clamp(0, 127) as u8;
                     transformed_note.note = Some(new_note);
                 }
 
@@ -1113,17 +2511,78 @@ impl SequencePattern {
                     transformed_note.channel = channel;
                 }
 
-                transformed_notes.push(transformed_note);
+                if let (Some(midi_note), Some(lock)) =
+                    (transformed_note.note, &reference.scale_lock)
+                    && transformed_note.channel != 9
+                {
+                    transformed_note.note = Some(lock.snap(midi_note));
+                }
+
+                repeat_notes.push(transformed_note);
+            }
+
+            if reference.retrograde {
+                Self::retrograde(&mut repeat_notes, &pattern_tempo, self.beats_per_bar);
             }
+            transformed_notes.extend(repeat_notes);
         }
 
         Ok(transformed_notes)
     }
 
-    /// Calculate start time in seconds for a given bar
-    fn calculate_bar_start_time(&self, bar: u32, tempo: u32, beats_per_bar: u32) -> f64 {
-        let seconds_per_beat = 60.0 / tempo as f64;
-        (bar - 1) as f64 * beats_per_bar as f64 * seconds_per_beat
+    /// Mirror note timing within the span of one pattern instance so the last note plays first
+    fn retrograde(notes: &mut [SimpleNote], tempo_map: &TempoMap, beats_per_bar: u32) {
+        let spans: Vec<(f64, f64)> = notes
+            .iter()
+            .map(|note| {
+                let start = note.start_time.unwrap_or_else(|| match &note.musical_time {
+                    Some(musical_time) => musical_time.to_seconds_in(tempo_map, beats_per_bar, 480),
+                    None => 0.0,
+                });
+                let start_beat = tempo_map.beat_at(start);
+                let duration = match (&note.musical_duration, note.duration, note.beats) {
+                    (_, Some(duration), _) => duration,
+                    (Some(musical_duration), None, _) => {
+                        musical_duration.to_seconds_in(tempo_map, beats_per_bar, start_beat)
+                    }
+                    (None, None, Some(beats)) => MusicalDuration::Beats(beats).to_seconds_in(
+                        tempo_map,
+                        beats_per_bar,
+                        start_beat,
+                    ),
+                    (None, None, None) => 1.0,
+                };
+                (start, start + duration)
+            })
+            .collect();
+        let Some(first_start) = spans.iter().map(|&(start, _)| start).reduce(f64::min) else {
+            return;
+        };
+        let last_end = spans
+            .iter()
+            .map(|&(_, end)| end)
+            .fold(first_start, f64::max);
+
+        for (note, (_, end)) in notes.iter_mut().zip(spans) {
+            note.start_time = Some(first_start + last_end - end);
+            note.musical_time = None;
+        }
+        notes.sort_by(|a, b| {
+            a.start_time
+                .unwrap_or(0.0)
+                .total_cmp(&b.start_time.unwrap_or(0.0))
+        });
+    }
+
+    /// Mirror a MIDI pitch around the pivot note
+    fn invert_pitch(note: u8, pivot: u8) -> u8 {
+        (2 * pivot as i16 - note as i16).clamp(0, 127) as u8
+    }
+
+    /// Whole bars this pattern occupies when chained, rounding partial bars up to the bar line
+    fn occupied_bars(&self) -> u32 {
+        let bar_duration = self.beats_per_bar as f64 * 60.0 / self.tempo as f64;
+        ((self.get_pattern_duration() / bar_duration - 1e-9).ceil() as u32).max(1)
     }
 
     /// Calculate the total duration of this pattern in seconds
@@ -1150,13 +2609,131 @@ impl SequencePattern {
 }
 
 impl ExtendedSequence {
-    #[allow(dead_code)]
     pub fn new() -> Self {
         Self {
             notes: Vec::new(),
             patterns: Vec::new(),
+            arpeggios: Vec::new(),
             tempo: 120,
+            beats_per_bar: 4,
+            master_seed: None,
+            min_release: None,
+            auto_pan_by_pitch: false,
+            drum_glue: false,
+            master_limiter: true,
+            program_changes: Vec::new(),
+            drum_humanize: None,
+            channels: Vec::new(),
+            target_lufs: None,
+            fit_duration: None,
+            velocity_brightness: resolve::DEFAULT_VELOCITY_BRIGHTNESS,
+            tail_cutoff_db: player::DEFAULT_TAIL_CUTOFF_DB,
+            negative_start: resolve::NegativeStartMode::Clamp,
+            chord_track: Vec::new(),
+            swing: 0.0,
+            swing_subdivision: SwingSubdivision::Eighth,
+            tempo_changes: Vec::new(),
+        }
+    }
+
+    /// Chain patterns end to end, each starting on the bar where the previous one ends.
+    /// Returns the sequence and its total length in bars.
+    pub fn concat_patterns(
+        pattern_store: &std::collections::HashMap<String, SequencePattern>,
+        segments: &[ConcatSegment],
+        tempo: u32,
+    ) -> Result<(Self, u32), String> {
+        if segments.is_empty() {
+            return Err("At least one pattern name is required".to_string());
+        }
+
+        let mut sequence = Self::new();
+        sequence.tempo = tempo;
+        let mut next_bar = 1;
+
+        for segment in segments {
+            let (pattern_name, transpose, instrument_override) = match segment {
+                ConcatSegment::Name(name) => (name, 0, None),
+                ConcatSegment::Segment {
+                    pattern_name,
+                    transpose,
+                    instrument_override,
+                } => (pattern_name, *transpose, *instrument_override),
+            };
+            if !(-12..=12).contains(&transpose) {
+                return Err(format!(
+                    "Transpose for '{}' must be between -12 and 12, got {}",
+                    pattern_name, transpose
+                ));
+            }
+            if instrument_override.is_some_and(|instrument| instrument > 127) {
+                return Err(format!(
+                    "Instrument override for '{}' must be 0-127",
+                    pattern_name
+                ));
+            }
+            let pattern = pattern_store
+                .get(pattern_name)
+                .ok_or_else(|| format!("Pattern '{}' not found", pattern_name))?;
+
+            sequence.patterns.push(SequenceReference {
+                pattern_name: pattern_name.clone(),
+                start_time_offset: None,
+                start_bar: Some(next_bar),
+                start_beat: default_start_beat(),
+                bars: None,
+                transpose,
+                instrument_override,
+                velocity_scale: 1.0,
+                duration_scale: 1.0,
+                channel_override: None,
+                repeat_count: 1,
+                repeat_spacing_bars: 0.0,
+                align_to_bars: true,
+                retrograde: false,
+                invert_around: None,
+                follow_chords: None,
+                scale_lock: None,
+            });
+            next_bar += pattern.occupied_bars();
         }
+
+        Ok((sequence, next_bar - 1))
+    }
+
+    /// A sequence playing one stored pattern once from bar 1, at the pattern's own tempo
+    /// unless `tempo` is given
+    pub fn solo_pattern(
+        pattern_store: &std::collections::HashMap<String, SequencePattern>,
+        pattern_name: &str,
+        transpose: i8,
+        instrument_override: Option<u8>,
+        tempo: Option<u32>,
+    ) -> Result<Self, String> {
+        let Some(pattern) = pattern_store.get(pattern_name) else {
+            let mut names: Vec<&str> = pattern_store.keys().map(String::as_str).collect();
+            names.sort();
+            return Err(if names.is_empty() {
+                format!(
+                    "Pattern '{}' not found; no patterns are defined yet",
+                    pattern_name
+                )
+            } else {
+                format!(
+                    "Pattern '{}' not found. Defined patterns: {}",
+                    pattern_name,
+                    names.join(", ")
+                )
+            });
+        };
+        let segment = ConcatSegment::Segment {
+            pattern_name: pattern_name.to_string(),
+            transpose,
+            instrument_override,
+        };
+        let (sequence, _) =
+            Self::concat_patterns(pattern_store, &[segment], tempo.unwrap_or(pattern.tempo))?;
+        Ok(sequence)
     }
 
     /// Convert to SimpleSequence by resolving all pattern references
@@ -1165,6 +2742,8 @@ impl ExtendedSequence {
         pattern_store: &std::collections::HashMap<String, SequencePattern>,
     ) -> Result<SimpleSequence, String> {
         let mut all_notes = self.notes.clone();
+        self.swing_notes(&mut all_notes);
+        let tempo_map = TempoMap::new(self.tempo, self.beats_per_bar, &self.tempo_changes);
 
         // Resolve all pattern references
         for pattern_ref in &self.patterns {
@@ -1172,14 +2751,29 @@ impl ExtendedSequence {
                 .get(&pattern_ref.pattern_name)
                 .ok_or_else(|| format!("Pattern '{}' not found", pattern_ref.pattern_name))?;
 
-            let resolved_notes = pattern.apply_reference(pattern_ref, self.tempo, 4)?; // Assuming 4/4 time for now
+            let mut resolved_notes =
+                pattern.apply_reference(pattern_ref, &tempo_map, self.beats_per_bar)?;
+            // A pattern with its own swing keeps it instead of taking the sequence's
+            if pattern.swing == 0.0 {
+                self.swing_notes(&mut resolved_notes);
+            }
             all_notes.extend(resolved_notes);
         }
 
+        // Expand arpeggios; random walks draw from the master seed
+        {
+            let _seed_scope = MasterSeedScope::new(self.master_seed);
+            for arpeggio in &self.arpeggios {
+                let mut arpeggio_notes = arpeggio.generate(self.tempo, self.beats_per_bar)?;
+                self.swing_notes(&mut arpeggio_notes);
+                all_notes.extend(arpeggio_notes);
+            }
+        }
+
         // Sort notes by start time for proper playback order
         all_notes.sort_by(|a, b| {
-            let a_time = a.get_start_time(self.tempo, 4); // Assuming 4/4 time
-            let b_time = b.get_start_time(self.tempo, 4);
+            let a_time = a.get_start_time(self.tempo, self.beats_per_bar);
+            let b_time = b.get_start_time(self.tempo, self.beats_per_bar);
             a_time
                 .partial_cmp(&b_time)
                 .unwrap_or(std::cmp::Ordering::Equal)
@@ -1188,8 +2782,40 @@ impl ExtendedSequence {
         Ok(SimpleSequence {
             notes: all_notes,
             tempo: self.tempo,
+            beats_per_bar: self.beats_per_bar,
+            master_seed: self.master_seed,
+            min_release: self.min_release,
+            auto_pan_by_pitch: self.auto_pan_by_pitch,
+            drum_glue: self.drum_glue,
+            master_limiter: self.master_limiter,
+            program_changes: self.program_changes.clone(),
+            drum_humanize: self.drum_humanize,
+            channels: self.channels.clone(),
+            target_lufs: self.target_lufs,
+            fit_duration: self.fit_duration,
+            velocity_brightness: self.velocity_brightness,
+            tail_cutoff_db: self.tail_cutoff_db,
+            negative_start: self.negative_start,
+            chord_track: self.chord_track.clone(),
+            // Already applied above, pattern by pattern
+            swing: 0.0,
+            swing_subdivision: self.swing_subdivision,
+            tempo_changes: self.tempo_changes.clone(),
         })
     }
+
+    /// Apply the sequence's swing to notes in absolute time, along its tempo changes
+    fn swing_notes(&self, notes: &mut [SimpleNote]) {
+        let tempo_map = TempoMap::new(self.tempo, self.beats_per_bar, &self.tempo_changes);
+        for note in notes {
+            note.apply_swing(
+                &tempo_map,
+                self.beats_per_bar,
+                self.swing,
+                self.swing_subdivision,
+            );
+        }
+    }
 }
 
 impl SimpleNote {
@@ -1202,12 +2828,45 @@ impl SimpleNote {
         }
     }
 
-    /// Get duration in seconds, converting from musical duration if needed
+    /// Get duration in seconds: explicit seconds first, then musical duration, then beats
     pub fn get_duration(&self, tempo: u32, beats_per_bar: u32) -> f64 {
-        if let Some(musical_duration) = &self.musical_duration {
+        if let Some(duration) = self.duration {
+            duration
+        } else if let Some(musical_duration) = &self.musical_duration {
             musical_duration.to_seconds(tempo, beats_per_bar)
         } else {
-            self.duration.unwrap_or(1.0)
+            self.beats.map_or(1.0, |beats| beats * 60.0 / tempo as f64)
+        }
+    }
+
+    /// Delay the note's start if it falls on a swung off-beat, resolving musical_time to
+    /// seconds. Notes with no start, and every note when `swing` is 0.0, are left alone.
+    pub fn apply_swing(
+        &mut self,
+        tempo_map: &TempoMap,
+        beats_per_bar: u32,
+        swing: f32,
+        subdivision: SwingSubdivision,
+    ) {
+        if swing == 0.0 {
+            return;
+        }
+        let start = match (self.start_time, &self.musical_time) {
+            (Some(start_time), _) => start_time,
+            (None, Some(musical_time)) => musical_time.to_seconds_in(tempo_map, beats_per_bar, 480),
+            (None, None) => return,
+        };
+        self.start_time = Some(swing_seconds(start, tempo_map, swing, subdivision));
+        self.musical_time = None;
+    }
+
+    /// Fill in duration from `beats` when the note gives neither seconds nor a musical duration
+    pub fn apply_beats(&mut self, tempo: u32) {
+        if self.duration.is_none()
+            && self.musical_duration.is_none()
+            && let Some(beats) = self.beats
+        {
+            self.duration = Some(beats * 60.0 / tempo as f64);
         }
     }
 
@@ -1221,16 +2880,242 @@ impl SimpleNote {
         self.synth_type.is_some()
     }
 
-    /// Check if this note uses presets
-    pub fn is_preset(&self) -> bool {
-        self.preset_name.is_some()
-            || self.preset_category.is_some()
-            || self.preset_random.unwrap_or(false)
+    /// Check if this is a MIDI note on an instrument that sustains while held
+    /// (organs, strings, brass, winds, synth leads/pads) rather than a percussive one
+    pub fn is_sustaining_instrument(&self) -> bool {
+        if self.is_r2d2() || self.is_synthesis() || self.channel == 9 {
+            return false;
+        }
+        matches!(self.instrument.unwrap_or(0), 16..=23 | 40..=103 | 109..=111)
+    }
+
+    /// Stereo position from the note's own pan, or balance without one (-1.0=left,
+    /// 0.0=center, 1.0=right), for notes the synthesizers render rather than a MIDI channel
+    pub fn stereo_pan(&self) -> Option<f32> {
+        self.pan
+            .or(self.balance)
+            .map(|position| ((position as f32 - 64.0) / 63.0).clamp(-1.0, 1.0))
+    }
+
+    /// Stereo position for auto-pan by pitch (-1.0=left, 0.0=center, 1.0=right).
+    /// C2 sits at the center (slightly left below it) and higher notes move right up to C6;
+    /// None when the note sets its own pan/balance or has no pitch.
+    pub fn pitch_pan(&self) -> Option<f32> {
+        if self.pan.is_some() || self.balance.is_some() {
+            return None;
+        }
+        let pitch = match (self.note, self.synth_frequency) {
+            (_, Some(frequency)) if self.is_synthesis() => {
+                69.0 + 12.0 * (frequency.max(1.0) / 440.0).log2()
+            }
+            (Some(note), _) => note as f32,
+            _ => return None,
+        };
+        Some(((pitch - 36.0) / 60.0).clamp(-0.2, 0.8))
+    }
+
+    /// Extend a short note on a sustaining instrument to at least `min_release` seconds
+    pub fn apply_min_release(&mut self, min_release: f64) {
+        if !self.is_sustaining_instrument() {
+            return;
+        }
+        if let Some(duration) = self.duration
+            && duration < min_release
+        {
+            self.duration = Some(min_release);
+        }
+    }
+
+    /// Fill in instrument, mix and effects this note leaves unset from its channel's config.
+    /// Instrument and mix settings only apply to MIDI notes; effects apply to any note.
+    pub fn apply_channel_config(&mut self, configs: &[ChannelConfig]) {
+        let Some(config) = configs.iter().find(|config| config.channel == self.channel) else {
+            return;
+        };
+
+        if !self.is_r2d2() && !self.is_synthesis() {
+            if self.channel != 9 {
+                self.instrument = self.instrument.or(config.instrument);
+            }
+            self.volume = self.volume.or(config.volume);
+            self.pan = self.pan.or(config.pan);
+            self.reverb = self.reverb.or(config.reverb);
+            self.chorus = self.chorus.or(config.chorus);
+        }
+        if !self.has_effects() {
+            self.effects = config.effects.clone();
+        }
+    }
+
+    /// Snap a `snap_to_chord` note to the nearest tone, in any octave, of the chord active at
+    /// its bar, found along `tempo_map`. Synth notes with an explicit frequency land exactly
+    /// on the chord tone.
+    pub fn apply_chord_track(
+        &mut self,
+        track: &[ChordChange],
+        tempo_map: &TempoMap,
+        beats_per_bar: u32,
+    ) {
+        if !self.snap_to_chord || self.is_r2d2() || self.channel == 9 {
+            return;
+        }
+        let beat = tempo_map.beat_at(self.start_time.unwrap_or(0.0));
+        // Small epsilon so a note exactly on a barline belongs to the new bar
+        let bar = ((beat + 1e-9) / beats_per_bar as f64).floor() as u32 + 1;
+        let Some(chord) = track.iter().rev().find(|chord| chord.bar <= bar) else {
+            return;
+        };
+
+        let nearest_chord_tone = |pitch: f32| {
+            let center = pitch.round() as i32;
+            (center - 6..=center + 6)
+                .filter(|candidate| {
+                    (0..=127).contains(candidate)
+                        && chord
+                            .notes
+                            .iter()
+                            .any(|&tone| tone as i32 % 12 == candidate.rem_euclid(12))
+                })
+                .min_by(|a, b| {
+                    (*a as f32 - pitch)
+                        .abs()
+                        .total_cmp(&(*b as f32 - pitch).abs())
+                })
+        };
+
+        match (self.synth_frequency, self.note) {
+            (Some(frequency), _) if self.is_synthesis() => {
+                let pitch = 69.0 + 12.0 * (frequency.max(1.0) / 440.0).log2();
+                if let Some(tone) = nearest_chord_tone(pitch) {
+                    self.synth_frequency = Some(440.0 * 2f32.powf((tone as f32 - 69.0) / 12.0));
+                }
+            }
+            (_, Some(note)) => {
+                if let Some(tone) = nearest_chord_tone(note as f32) {
+                    self.note = Some(tone as u8);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Use the instrument from the latest program change on this note's channel at or before its start
+    pub fn apply_program_changes(&mut self, changes: &[ProgramChange]) {
+        if self.is_r2d2() || self.is_synthesis() || self.channel == 9 {
+            return;
+        }
+        let start_time = self.start_time.unwrap_or(0.0);
+        if let Some(change) = changes
+            .iter()
+            .filter(|change| change.channel == self.channel && change.at <= start_time)
+            .max_by(|a, b| a.at.total_cmp(&b.at))
+        {
+            self.instrument = Some(change.instrument);
+        }
+    }
+
+    /// Check if this note uses presets
+    pub fn is_preset(&self) -> bool {
+        self.preset_name.is_some()
+            || self.preset_category.is_some()
+            || self.preset_random.unwrap_or(false)
+            || self.preset_morph.is_some()
+    }
+
+    /// Check if this note has effects
+    pub fn has_effects(&self) -> bool {
+        self.effects.is_some() || self.effects_preset.is_some()
+    }
+
+    /// Validate timing parameters shared by every note type
+    pub fn validate_timing(&self) -> Result<(), String> {
+        if let Some(start_time) = self.start_time
+            && !(start_time.is_finite() && start_time.abs() <= MAX_NOTE_SECONDS)
+        {
+            return Err(format!(
+                "start_time must be within {} seconds, got {}",
+                MAX_NOTE_SECONDS, start_time
+            ));
+        }
+        if let Some(duration) = self.duration
+            && !(duration.is_finite() && (0.0..=MAX_NOTE_SECONDS).contains(&duration))
+        {
+            return Err(format!(
+                "duration must be between 0 and {} seconds, got {}",
+                MAX_NOTE_SECONDS, duration
+            ));
+        }
+        if let Some(beats) = self.beats
+            && !(beats.is_finite() && beats > 0.0)
+        {
+            return Err(format!("beats must be greater than 0, got {}", beats));
+        }
+        if let Some(pre_roll) = self.pre_roll
+            && !(0.0..=4.0).contains(&pre_roll)
+        {
+            return Err(format!(
+                "pre_roll must be between 0.0 and 4.0 seconds, got {}",
+                pre_roll
+            ));
+        }
+        if let Some(roll) = &self.roll_accelerate {
+            roll.validate()?;
+        }
+        if let Some(articulation) = &self.articulation
+            && !ARTICULATIONS.contains(&articulation.as_str())
+        {
+            return Err(format!(
+                "Unknown articulation '{}'; use one of: {}",
+                articulation,
+                ARTICULATIONS.join(", ")
+            ));
+        }
+        if let Some(pick) = &self.tremolo_pick {
+            if self.channel == 9 || self.is_r2d2() {
+                return Err(
+                    "tremolo_pick is for melodic notes; use roll_accelerate for drums".to_string(),
+                );
+            }
+            if self.roll_accelerate.is_some() {
+                return Err("A note can't have both tremolo_pick and roll_accelerate".to_string());
+            }
+            pick.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Apply the note's articulation to its duration and velocity: staccato halves the
+    /// duration, legato overlaps slightly into the next note, tenuto holds the full value,
+    /// accent and marcato boost the velocity (marcato also shortens). Unknown names are ignored.
+    pub fn apply_articulation(&mut self) {
+        let (length, boost) = match self.articulation.as_deref() {
+            Some("staccato") => (0.5, 1.0),
+            Some("legato") => (1.1, 1.0),
+            Some("tenuto") => (1.0, 1.0),
+            Some("accent") => (1.0, 1.2),
+            Some("marcato") => (0.75, 1.35),
+            _ => return,
+        };
+        if let Some(duration) = self.duration {
+            self.duration = Some(duration * length);
+        }
+        if boost != 1.0 {
+            let velocity = self.velocity.unwrap_or(80) as f32 * boost;
+            self.velocity = Some(velocity.round().min(127.0) as u8);
+        }
     }
 
-    /// Check if this note has effects
-    pub fn has_effects(&self) -> bool {
-        self.effects.is_some() || self.effects_preset.is_some()
+    /// Start the note `pre_roll` seconds early (never before 0) and extend it by the same
+    /// amount, so the end stays put and the attack finishes on the requested start_time
+    pub fn apply_pre_roll(&mut self) {
+        let (Some(pre_roll), Some(start_time)) = (self.pre_roll, self.start_time) else {
+            return;
+        };
+        let shift = pre_roll.min(start_time);
+        self.start_time = Some(start_time - shift);
+        if let Some(duration) = self.duration {
+            self.duration = Some(duration + shift);
+        }
     }
 
     /// Validate R2D2 parameters if this is an R2D2 note
@@ -1307,6 +3192,10 @@ impl SimpleNote {
             }
         }
 
+        if let Some(voice) = &self.r2d2_voice {
+            voice.validate()?;
+        }
+
         Ok(())
     }
 
@@ -1319,28 +3208,7 @@ impl SimpleNote {
         let synth_type = self.synth_type.as_ref().unwrap();
 
         // Validate synthesis type
-        let valid_types = [
-            "sine",
-            "square",
-            "sawtooth",
-            "triangle",
-            "noise",
-            "fm",
-            "dx7fm",
-            "granular",
-            "wavetable",
-            "kick",
-            "snare",
-            "hihat",
-            "cymbal",
-            "swoosh",
-            "zap",
-            "chime",
-            "burst",
-            "pad",
-            "texture",
-            "drone",
-        ];
+        let valid_types = VALID_SYNTH_TYPES;
 
         if !valid_types.contains(&synth_type.as_str()) {
             return Err(format!(
@@ -1407,6 +3275,16 @@ impl SimpleNote {
             ));
         }
 
+        if let Some(curve) = &self.synth_envelope_curve
+            && crate::expressive::EnvelopeCurve::from_name(curve).is_none()
+        {
+            return Err(format!(
+                "Invalid envelope curve: {}. Must be one of: {}",
+                curve,
+                crate::expressive::EnvelopeCurve::NAMES.join(", ")
+            ));
+        }
+
         // Validate filter parameters
         if let Some(filter_type) = &self.synth_filter_type {
             let valid_filter_types = ["lowpass", "highpass", "bandpass"];
@@ -1437,6 +3315,16 @@ impl SimpleNote {
             ));
         }
 
+        if let Some(soft_start) = self.synth_filter_soft_start
+            && !(0.0..=crate::expressive::MAX_FILTER_SOFT_START).contains(&soft_start)
+        {
+            return Err(format!(
+                "Filter soft start {} is out of range (0.0-{} seconds)",
+                soft_start,
+                crate::expressive::MAX_FILTER_SOFT_START
+            ));
+        }
+
         // Validate effect intensities
         if let Some(reverb) = self.synth_reverb
             && !(0.0..=1.0).contains(&reverb)
@@ -1475,6 +3363,16 @@ impl SimpleNote {
         }
 
         // Validate synthesis-specific parameters
+        if let Some(color) = &self.synth_noise_color
+            && crate::expressive::NoiseColor::from_name(color).is_none()
+        {
+            return Err(format!(
+                "Invalid noise color: {}. Must be one of: {}",
+                color,
+                crate::expressive::NoiseColor::NAMES.join(", ")
+            ));
+        }
+
         if let Some(pulse_width) = self.synth_pulse_width
             && !(0.1..=0.9).contains(&pulse_width)
         {
@@ -1484,6 +3382,51 @@ impl SimpleNote {
             ));
         }
 
+        if let Some(pwm) = &self.synth_pwm {
+            pwm.validate()?;
+        }
+
+        if let Some(phase) = self.synth_phase
+            && !(0.0..=1.0).contains(&phase)
+        {
+            return Err(format!(
+                "Synthesis phase {} is out of range (0.0-1.0 of a cycle)",
+                phase
+            ));
+        }
+
+        for (label, rate) in [
+            ("Vibrato rate", self.synth_vibrato_rate),
+            ("Tremolo rate", self.synth_tremolo_rate),
+        ] {
+            if let Some(rate) = rate
+                && !(0.0..=crate::expressive::MAX_LFO_RATE).contains(&rate)
+            {
+                return Err(format!(
+                    "{} {} is out of range (0.0-{} Hz)",
+                    label,
+                    rate,
+                    crate::expressive::MAX_LFO_RATE
+                ));
+            }
+        }
+
+        if let Some(depth) = self.synth_vibrato_depth
+            && !(0.0..=crate::expressive::MAX_VIBRATO_DEPTH).contains(&depth)
+        {
+            return Err(format!(
+                "Vibrato depth {} is out of range (0.0-{} semitones)",
+                depth,
+                crate::expressive::MAX_VIBRATO_DEPTH
+            ));
+        }
+
+        if let Some(depth) = self.synth_tremolo_depth
+            && !(0.0..=1.0).contains(&depth)
+        {
+            return Err(format!("Tremolo depth {} is out of range (0.0-1.0)", depth));
+        }
+
         if let Some(mod_freq) = self.synth_modulator_freq
             && !(0.1..=1000.0).contains(&mod_freq)
         {
@@ -1520,12 +3463,80 @@ impl SimpleNote {
             ));
         }
 
+        for (label, position) in [
+            ("Morph position", self.synth_morph_position),
+            ("Morph end position", self.synth_morph_end),
+        ] {
+            if let Some(position) = position
+                && !(0.0..=1.0).contains(&position)
+            {
+                return Err(format!("{} {} is out of range (0.0-1.0)", label, position));
+            }
+        }
+
+        if let Some(harmonics) = self.synth_drone_harmonics
+            && !(1..=16).contains(&harmonics)
+        {
+            return Err(format!(
+                "Drone harmonics {} is out of range (1-16)",
+                harmonics
+            ));
+        }
+
+        if let Some(rate) = self.synth_drone_evolution_rate
+            && !(0.0..=1.0).contains(&rate)
+        {
+            return Err(format!(
+                "Drone evolution rate {} is out of range (0.0-1.0 Hz)",
+                rate
+            ));
+        }
+
+        if let Some(detune) = self.synth_drone_detune
+            && !(0.0..=50.0).contains(&detune)
+        {
+            return Err(format!(
+                "Drone detune {} is out of range (0.0-50.0 cents)",
+                detune
+            ));
+        }
+
         Ok(())
     }
 
     /// Validate preset parameters if this note uses presets
     pub fn validate_preset(&self) -> Result<(), String> {
         if !self.is_preset() {
+            if self.preset_randomize_amount.is_some() || self.preset_randomize_seed.is_some() {
+                return Err(
+                    "'preset_randomize_amount' and 'preset_randomize_seed' need a preset to randomize".to_string(),
+                );
+            }
+            return Ok(());
+        }
+
+        if let Some(morph) = &self.preset_morph {
+            if self.preset_name.is_some()
+                || self.preset_category.is_some()
+                || self.preset_random.unwrap_or(false)
+                || self.preset_variation.is_some()
+                || self.preset_randomize_amount.is_some()
+                || self.preset_randomize_seed.is_some()
+            {
+                return Err(
+                    "'preset_morph' picks its own presets - don't combine it with 'preset_name', 'preset_category', 'preset_random', 'preset_variation' or preset randomizing"
+                        .to_string(),
+                );
+            }
+            if morph.from.trim().is_empty() || morph.to.trim().is_empty() {
+                return Err("'preset_morph' needs both 'from' and 'to' preset names".to_string());
+            }
+            if !morph.amount.is_finite() {
+                return Err(format!(
+                    "preset_morph amount must be a number between 0.0 and 1.0, got {}",
+                    morph.amount
+                ));
+            }
             return Ok(());
         }
 
@@ -1544,6 +3555,23 @@ impl SimpleNote {
             // This is fine - category can be used with name for validation
         }
 
+        if let Some(amount) = self.preset_randomize_amount {
+            if !(0.0..=1.0).contains(&amount) {
+                return Err(format!(
+                    "preset_randomize_amount must be between 0.0 and 1.0, got {}",
+                    amount
+                ));
+            }
+            if self.preset_variation.is_some() {
+                return Err(
+                    "Cannot use both 'preset_variation' and 'preset_randomize_amount' - choose one"
+                        .to_string(),
+                );
+            }
+        } else if self.preset_randomize_seed.is_some() {
+            return Err("'preset_randomize_seed' needs 'preset_randomize_amount'".to_string());
+        }
+
         if has_random && !has_category {
             return Err(
                 "When using 'preset_random', you must specify 'preset_category'".to_string(),
@@ -1579,7 +3607,7 @@ impl SimpleNote {
         // Validate effects chain
         if let Some(effects) = &self.effects {
             for (i, effect) in effects.iter().enumerate() {
-                if let Err(e) = self.validate_single_effect(effect) {
+                if let Err(e) = Self::validate_single_effect(effect) {
                     return Err(format!("Effect {} in chain: {}", i + 1, e));
                 }
             }
@@ -1603,7 +3631,7 @@ impl SimpleNote {
     }
 
     /// Validate a single effect configuration
-    fn validate_single_effect(&self, effect: &EffectConfig) -> Result<(), String> {
+    fn validate_single_effect(effect: &EffectConfig) -> Result<(), String> {
         // Validate intensity
         if !(0.0..=1.0).contains(&effect.intensity) {
             return Err(format!(
@@ -1619,6 +3647,8 @@ impl SimpleNote {
                 dampening,
                 wet_level,
                 pre_delay,
+                modulation,
+                algorithm: _,
             } => {
                 if !(0.0..=1.0).contains(room_size) {
                     return Err(format!(
@@ -1644,6 +3674,12 @@ impl SimpleNote {
                         pre_delay
                     ));
                 }
+                if !(0.0..=1.0).contains(modulation) {
+                    return Err(format!(
+                        "Reverb modulation {} is out of range (0.0-1.0)",
+                        modulation
+                    ));
+                }
             }
             EffectType::Delay {
                 delay_time,
@@ -1698,6 +3734,62 @@ impl SimpleNote {
                     ));
                 }
             }
+            EffectType::Flanger {
+                rate,
+                depth,
+                feedback,
+                manual,
+            } => {
+                if !(0.05..=10.0).contains(rate) {
+                    return Err(format!(
+                        "Flanger rate {} is out of range (0.05-10.0 Hz)",
+                        rate
+                    ));
+                }
+                if !(0.0..=1.0).contains(depth) {
+                    return Err(format!("Flanger depth {} is out of range (0.0-1.0)", depth));
+                }
+                if !(-0.95..=0.95).contains(feedback) {
+                    return Err(format!(
+                        "Flanger feedback {} is out of range (-0.95 to 0.95)",
+                        feedback
+                    ));
+                }
+                if !(0.0..=1.0).contains(manual) {
+                    return Err(format!(
+                        "Flanger manual {} is out of range (0.0-1.0)",
+                        manual
+                    ));
+                }
+            }
+            EffectType::Phaser {
+                rate,
+                depth,
+                feedback,
+                stages,
+            } => {
+                if !(0.05..=10.0).contains(rate) {
+                    return Err(format!(
+                        "Phaser rate {} is out of range (0.05-10.0 Hz)",
+                        rate
+                    ));
+                }
+                if !(0.0..=1.0).contains(depth) {
+                    return Err(format!("Phaser depth {} is out of range (0.0-1.0)", depth));
+                }
+                if !(0.0..=0.95).contains(feedback) {
+                    return Err(format!(
+                        "Phaser feedback {} is out of range (0.0-0.95)",
+                        feedback
+                    ));
+                }
+                if !(2..=12).contains(stages) {
+                    return Err(format!(
+                        "Phaser stages {} is out of range (2-12; even counts give whole notches)",
+                        stages
+                    ));
+                }
+            }
             EffectType::Filter {
                 filter_type: _,
                 cutoff,
@@ -1728,6 +3820,7 @@ impl SimpleNote {
                 ratio,
                 attack,
                 release,
+                parallel,
             } => {
                 if !(-60.0..=0.0).contains(threshold) {
                     return Err(format!(
@@ -1753,6 +3846,12 @@ impl SimpleNote {
                         release
                     ));
                 }
+                if !(0.0..=1.0).contains(parallel) {
+                    return Err(format!(
+                        "Compressor parallel {} is out of range (0.0-1.0)",
+                        parallel
+                    ));
+                }
             }
             EffectType::Distortion {
                 drive,
@@ -1778,8 +3877,842 @@ impl SimpleNote {
                     ));
                 }
             }
+            EffectType::MultibandCompressor { crossovers, bands } => {
+                for crossover in crossovers {
+                    if !(20.0..=20000.0).contains(crossover) {
+                        return Err(format!(
+                            "MultibandCompressor crossover {} is out of range (20-20000 Hz)",
+                            crossover
+                        ));
+                    }
+                }
+                if crossovers.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(format!(
+                        "MultibandCompressor crossovers {:?} must be in ascending order",
+                        crossovers
+                    ));
+                }
+                if bands.len() != crossovers.len() + 1 {
+                    return Err(format!(
+                        "MultibandCompressor needs {} bands for {} crossovers, got {}",
+                        crossovers.len() + 1,
+                        crossovers.len(),
+                        bands.len()
+                    ));
+                }
+                for band in bands {
+                    if !(-60.0..=0.0).contains(&band.threshold) {
+                        return Err(format!(
+                            "MultibandCompressor threshold {} is out of range (-60.0 to 0.0 dB)",
+                            band.threshold
+                        ));
+                    }
+                    if !(1.0..=50.0).contains(&band.ratio) {
+                        return Err(format!(
+                            "MultibandCompressor ratio {} is out of range (1.0-50.0)",
+                            band.ratio
+                        ));
+                    }
+                    if !(0.0001..=2.0).contains(&band.attack) {
+                        return Err(format!(
+                            "MultibandCompressor attack {} is out of range (0.0001-2.0 seconds)",
+                            band.attack
+                        ));
+                    }
+                    if !(0.001..=20.0).contains(&band.release) {
+                        return Err(format!(
+                            "MultibandCompressor release {} is out of range (0.001-20.0 seconds)",
+                            band.release
+                        ));
+                    }
+                    if !(-24.0..=24.0).contains(&band.makeup_gain_db) {
+                        return Err(format!(
+                            "MultibandCompressor makeup_gain_db {} is out of range (-24.0 to 24.0 dB)",
+                            band.makeup_gain_db
+                        ));
+                    }
+                }
+            }
+            EffectType::TransientShaper {
+                attack_gain,
+                sustain_gain,
+            } => {
+                if !(-1.0..=1.0).contains(attack_gain) {
+                    return Err(format!(
+                        "TransientShaper attack_gain {} is out of range (-1.0 to 1.0)",
+                        attack_gain
+                    ));
+                }
+                if !(-1.0..=1.0).contains(sustain_gain) {
+                    return Err(format!(
+                        "TransientShaper sustain_gain {} is out of range (-1.0 to 1.0)",
+                        sustain_gain
+                    ));
+                }
+            }
+            EffectType::BassMono { crossover } => {
+                if !(40.0..=300.0).contains(crossover) {
+                    return Err(format!(
+                        "BassMono crossover {} is out of range (40-300 Hz)",
+                        crossover
+                    ));
+                }
+            }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gate_length_shortens_notes_to_fraction_of_step() {
+        // Uneven steps at 120 BPM: 0.25s, 0.75s, 0.5s, then 0.5s to the end of the bar
+        let positions = [(1, 0), (1, 240), (3, 0), (4, 0)];
+        let notes = positions
+            .iter()
+            .map(|&(beat, tick)| SimpleNote {
+                note: Some(60),
+                musical_time: Some(MusicalTime::new(1, beat, tick)),
+                duration: None,
+                musical_duration: Some(MusicalDuration::Beats(1.0)),
+                ..Default::default()
+            })
+            .collect();
+        let mut pattern = SequencePattern::new("gated".to_string(), notes);
+        pattern.pattern_bars = 1.0;
+        pattern.gate_length = Some(0.25);
+        assert!(pattern.validate().is_ok());
+
+        let reference: SequenceReference =
+            serde_json::from_value(serde_json::json!({"pattern_name": "gated", "start_bar": 1}))
+                .unwrap();
+        let result = pattern
+            .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+            .unwrap();
+
+        let expected_steps = [0.25, 0.75, 0.5, 0.5];
+        for (note, step) in result.iter().zip(expected_steps) {
+            assert!(note.musical_duration.is_none());
+            let duration = note.duration.unwrap();
+            assert!(
+                (duration - step * 0.25).abs() < 1e-9,
+                "expected {} got {}",
+                step * 0.25,
+                duration
+            );
+        }
+
+        pattern.gate_length = Some(0.01);
+        assert!(pattern.validate().is_err());
+    }
+
+    fn pattern_reference(json: serde_json::Value) -> SequenceReference {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn rising_line() -> SequencePattern {
+        // C4, D4, E4, F4 on beats 1-4 of a one-bar pattern
+        let notes = [60, 62, 64, 65]
+            .iter()
+            .enumerate()
+            .map(|(i, &pitch)| SimpleNote {
+                note: Some(pitch),
+                musical_time: Some(MusicalTime::new(1, i as u32 + 1, 0)),
+                duration: None,
+                musical_duration: Some(MusicalDuration::Beats(1.0)),
+                ..Default::default()
+            })
+            .collect();
+        let mut pattern = SequencePattern::new("line".to_string(), notes);
+        pattern.pattern_bars = 1.0;
+        pattern
+    }
+
+    #[test]
+    fn test_follow_chords_transposes_each_placement_to_root() {
+        let reference = pattern_reference(serde_json::json!({
+            "pattern_name": "line",
+            "bars": [1, 2, 3, 4],
+            "follow_chords": [
+                {"bar": 1, "root": 48},
+                {"bar": 2, "root": 55},
+                {"bar": 3, "root": 57},
+                {"bar": 4, "root": 53}
+            ]
+        }));
+        let result = rising_line()
+            .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+            .unwrap();
+
+        let first_pitches: Vec<u8> = result
+            .chunks(4)
+            .map(|placement| placement[0].note.unwrap())
+            .collect();
+        assert_eq!(first_pitches, vec![60, 67, 69, 65]);
+    }
+
+    #[test]
+    fn test_quantize_durations_snaps_note_ends_to_beat_grid() {
+        let notes = [(1, 0.9), (3, 1.1)]
+            .iter()
+            .map(|&(beat, beats)| SimpleNote {
+                note: Some(60),
+                musical_time: Some(MusicalTime::new(1, beat, 0)),
+                duration: None,
+                musical_duration: Some(MusicalDuration::Beats(beats)),
+                ..Default::default()
+            })
+            .collect();
+        let mut pattern = SequencePattern::new("ragged".to_string(), notes);
+        pattern.pattern_bars = 1.0;
+        pattern.quantize_grid = QuantizeGrid::Beat;
+        pattern.quantize_durations = true;
+        assert!(pattern.validate().is_ok());
+
+        let reference = pattern_reference(serde_json::json!({"pattern_name": "ragged"}));
+        // One beat at 120 BPM is 0.5s
+        for note in pattern
+            .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+            .unwrap()
+        {
+            assert!((note.duration.unwrap() - 0.5).abs() < 1e-9);
+        }
+
+        // Gate length wins over duration quantization: a quarter of each two-beat step
+        pattern.gate_length = Some(0.25);
+        for note in pattern
+            .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+            .unwrap()
+        {
+            assert!((note.duration.unwrap() - 0.25).abs() < 1e-9);
+        }
+
+        pattern.quantize_grid = QuantizeGrid::Off;
+        assert!(pattern.validate().is_err());
+    }
+
+    #[test]
+    fn test_quantize_grid_snaps_note_starts_when_the_pattern_resolves() {
+        // Ticks 37 and 100 of beat 1, the end of beat 4, and a seconds-timed note just
+        // after beat 2 at 120 BPM
+        let mut notes: Vec<SimpleNote> = [(1, 37), (1, 100), (4, 470)]
+            .iter()
+            .map(|&(beat, tick)| SimpleNote {
+                note: Some(60),
+                musical_time: Some(MusicalTime::new(1, beat, tick)),
+                duration: Some(0.1),
+                ..Default::default()
+            })
+            .collect();
+        notes.push(SimpleNote {
+            note: Some(62),
+            start_time: Some(0.53),
+            duration: Some(0.1),
+            ..Default::default()
+        });
+        let mut pattern = SequencePattern::new("loose".to_string(), notes);
+        pattern.pattern_bars = 1.0;
+        let reference = pattern_reference(serde_json::json!({"pattern_name": "loose"}));
+        let starts = |pattern: &SequencePattern| -> Vec<f64> {
+            pattern
+                .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+                .unwrap()
+                .iter()
+                .map(|note| note.start_time.unwrap())
+                .collect()
+        };
+
+        // Off is a true no-op
+        let tick = 0.5 / 480.0;
+        assert_eq!(
+            starts(&pattern),
+            vec![37.0 * tick, 100.0 * tick, 1.5 + 470.0 * tick, 0.53]
+        );
+
+        pattern.quantize_grid = QuantizeGrid::Sixteenth;
+        assert_eq!(starts(&pattern), vec![0.0, 0.125, 2.0, 0.5]);
+
+        pattern.quantize_grid = QuantizeGrid::Triplet;
+        let triplets = starts(&pattern);
+        assert_eq!(&triplets[..3], &[0.0, 0.5 / 3.0, 2.0]);
+        assert!((triplets[3] - 0.5).abs() < 1e-9);
+
+        // The bar grid rounds to the nearer bar line
+        pattern.quantize_grid = QuantizeGrid::Bar;
+        assert_eq!(starts(&pattern), vec![0.0, 0.0, 2.0, 0.0]);
+
+        // Swing moves the snapped positions, not the written ones
+        pattern.quantize_grid = QuantizeGrid::Sixteenth;
+        pattern.swing = 0.5;
+        pattern.swing_subdivision = SwingSubdivision::Sixteenth;
+        assert_eq!(starts(&pattern), vec![0.0, 0.1875, 2.0, 0.5]);
+    }
+
+    #[test]
+    fn test_swing_delays_off_beat_sixteenths_in_patterns_and_sequences() {
+        // Four sixteenths, one written in ticks and one in seconds at 120 BPM
+        let sixteenths = |in_ticks: bool| -> Vec<SimpleNote> {
+            (0..4)
+                .map(|step| SimpleNote {
+                    note: Some(60),
+                    musical_time: in_ticks.then(|| MusicalTime::new(1, 1, step * 120)),
+                    start_time: (!in_ticks).then_some(step as f64 * 0.125),
+                    duration: Some(0.1),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let beats = |notes: &[SimpleNote]| -> Vec<f64> {
+            notes
+                .iter()
+                .map(|note| note.start_time.unwrap() * 2.0)
+                .collect()
+        };
+        let reference = pattern_reference(serde_json::json!({"pattern_name": "groove"}));
+
+        for in_ticks in [true, false] {
+            let mut pattern = SequencePattern::new("groove".to_string(), sixteenths(in_ticks));
+            pattern.pattern_bars = 1.0;
+            let straight = pattern
+                .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+                .unwrap();
+            assert_eq!(beats(&straight), vec![0.0, 0.25, 0.5, 0.75]);
+
+            pattern.swing = 0.5;
+            pattern.swing_subdivision = SwingSubdivision::Sixteenth;
+            assert!(pattern.validate().is_ok());
+            let swung = pattern
+                .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+                .unwrap();
+            assert_eq!(beats(&swung), vec![0.0, 0.375, 0.5, 0.875]);
+            assert_eq!(swung[1].duration, straight[1].duration);
+
+            // 8th swing leaves the 16ths between 8ths in order, squeezed after the off-beat
+            pattern.swing_subdivision = SwingSubdivision::Eighth;
+            let eighths = pattern
+                .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+                .unwrap();
+            assert_eq!(beats(&eighths), vec![0.0, 0.375, 0.75, 0.875]);
+        }
+
+        // Sequence swing reaches direct notes and unswung patterns, but not swung ones
+        let mut swung_pattern = SequencePattern::new("swung".to_string(), sixteenths(false));
+        swung_pattern.pattern_bars = 1.0;
+        swung_pattern.swing = 0.25;
+        swung_pattern.swing_subdivision = SwingSubdivision::Sixteenth;
+        let store: std::collections::HashMap<String, SequencePattern> = [
+            ("straight".to_string(), {
+                let mut pattern = SequencePattern::new("straight".to_string(), sixteenths(true));
+                pattern.pattern_bars = 1.0;
+                pattern
+            }),
+            ("swung".to_string(), swung_pattern),
+        ]
+        .into_iter()
+        .collect();
+        let mut sequence = ExtendedSequence::new();
+        sequence.notes = sixteenths(false);
+        sequence.patterns = vec![
+            pattern_reference(serde_json::json!({"pattern_name": "straight", "start_bar": 2})),
+            pattern_reference(serde_json::json!({"pattern_name": "swung", "start_bar": 3})),
+        ];
+        let straight_sequence = sequence.resolve_patterns(&store).unwrap();
+        sequence.swing = 0.5;
+        sequence.swing_subdivision = SwingSubdivision::Sixteenth;
+        let resolved = sequence.resolve_patterns(&store).unwrap();
+        assert_eq!(resolved.swing, 0.0);
+        assert_eq!(
+            beats(&resolved.notes),
+            vec![
+                0.0, 0.375, 0.5, 0.875, // direct notes
+                4.0, 4.375, 4.5, 4.875, // bar 2, the sequence's swing
+                8.0, 8.3125, 8.5, 8.8125, // bar 3, the pattern's own swing
+            ]
+        );
+
+        // No swing is bit-for-bit straight timing
+        sequence.swing = 0.0;
+        let unswung = sequence.resolve_patterns(&store).unwrap();
+        assert_eq!(beats(&unswung.notes), beats(&straight_sequence.notes));
+        for position in [0.1, 0.25, 1.0 / 3.0, 7.77] {
+            assert_eq!(
+                swing_beats(position, 0.0, SwingSubdivision::Sixteenth),
+                position
+            );
+        }
+        assert!(validate_swing(0.8).is_err());
+    }
+
+    #[test]
+    fn test_concat_two_bar_patterns_places_second_at_bar_three() {
+        let two_bars = |name: &str, note: u8| {
+            let mut pattern = SequencePattern::new(
+                name.to_string(),
+                vec![SimpleNote {
+                    note: Some(note),
+                    musical_time: Some(MusicalTime::new(1, 1, 0)),
+                    duration: None,
+                    musical_duration: Some(MusicalDuration::Beats(1.0)),
+                    ..Default::default()
+                }],
+            );
+            pattern.pattern_bars = 2.0;
+            (name.to_string(), pattern)
+        };
+        let store: std::collections::HashMap<String, SequencePattern> =
+            [two_bars("intro", 60), two_bars("verse", 64)]
+                .into_iter()
+                .collect();
+        let segments: Vec<ConcatSegment> = serde_json::from_value(serde_json::json!([
+            "intro",
+            {"pattern_name": "verse", "transpose": 2, "instrument_override": 33}
+        ]))
+        .unwrap();
+
+        let (sequence, total_bars) =
+            ExtendedSequence::concat_patterns(&store, &segments, 120).unwrap();
+        assert_eq!(total_bars, 4);
+        let start_bars: Vec<Option<u32>> = sequence.patterns.iter().map(|p| p.start_bar).collect();
+        assert_eq!(start_bars, vec![Some(1), Some(3)]);
+
+        // Bar 3 at 120 BPM in 4/4 starts at 4.0s
+        let resolved = sequence.resolve_patterns(&store).unwrap();
+        let second = &resolved.notes[1];
+        assert!((second.start_time.unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(second.note, Some(66));
+        assert_eq!(second.instrument, Some(33));
+
+        let missing = [ConcatSegment::Name("chorus".to_string())];
+        assert!(ExtendedSequence::concat_patterns(&store, &missing, 120).is_err());
+    }
+
+    #[test]
+    fn test_waltz_pattern_on_bar_two_of_three_four() {
+        // Oom-pah-pah over two bars of 3/4, by musical_time
+        let waltz: Vec<SimpleNote> = (0..6)
+            .map(|step| SimpleNote {
+                note: Some(if step % 3 == 0 { 43 } else { 59 }),
+                start_time: None,
+                duration: Some(0.25),
+                musical_time: Some(MusicalTime::new(step / 3 + 1, step % 3 + 1, 0)),
+                ..Default::default()
+            })
+            .collect();
+        let mut pattern = SequencePattern::new("waltz".to_string(), waltz);
+        pattern.beats_per_bar = 3;
+        pattern.pattern_bars = 2.0;
+        let store: std::collections::HashMap<String, SequencePattern> =
+            [("waltz".to_string(), pattern)].into_iter().collect();
+
+        let mut sequence = ExtendedSequence::new();
+        sequence.beats_per_bar = 3;
+        sequence.patterns = vec![pattern_reference(
+            serde_json::json!({"pattern_name": "waltz", "start_bar": 2}),
+        )];
+        let resolved = sequence.resolve_patterns(&store).unwrap();
+        assert_eq!(resolved.beats_per_bar, 3);
+        // Bar 2 of 3/4 at 120 BPM starts 3 beats (1.5s) in; 4/4 would put it at 2.0s
+        let starts: Vec<f64> = resolved
+            .notes
+            .iter()
+            .map(|note| note.start_time.unwrap())
+            .collect();
+        assert_eq!(starts, vec![1.5, 2.0, 2.5, 3.0, 3.5, 4.0]);
+
+        // A 3/4 pattern keeps its beat count when placed on beat 2 of a 4/4 bar
+        sequence.beats_per_bar = 4;
+        sequence.patterns = vec![pattern_reference(
+            serde_json::json!({"pattern_name": "waltz", "start_bar": 2, "start_beat": 2}),
+        )];
+        let resolved = sequence.resolve_patterns(&store).unwrap();
+        assert_eq!(resolved.notes[0].start_time, Some(2.5));
+        assert_eq!(resolved.notes[3].start_time, Some(4.0));
+    }
+
+    #[test]
+    fn test_solo_pattern_schedules_every_note_transposed() {
+        let store: std::collections::HashMap<String, SequencePattern> =
+            [("line".to_string(), rising_line())].into_iter().collect();
+
+        let sequence = ExtendedSequence::solo_pattern(&store, "line", 5, Some(40), None).unwrap();
+        assert_eq!(sequence.tempo, rising_line().tempo);
+        let resolved = sequence.resolve_patterns(&store).unwrap();
+        let pitches: Vec<u8> = resolved
+            .notes
+            .iter()
+            .map(|note| note.note.unwrap())
+            .collect();
+        assert_eq!(pitches, vec![65, 67, 69, 70]);
+        assert!(
+            resolved
+                .notes
+                .iter()
+                .all(|note| note.instrument == Some(40))
+        );
+
+        let faster = ExtendedSequence::solo_pattern(&store, "line", 0, None, Some(90)).unwrap();
+        assert_eq!(faster.tempo, 90);
+
+        let error = ExtendedSequence::solo_pattern(&store, "lien", 0, None, None).unwrap_err();
+        assert!(error.contains("Defined patterns: line"), "{}", error);
+    }
+
+    #[test]
+    fn test_conflicting_placements_are_rejected_and_single_modes_resolve() {
+        let line = rising_line();
+        for (json, first, second) in [
+            (
+                serde_json::json!({"pattern_name": "line", "start_time_offset": 1.0, "start_bar": 2}),
+                "start_time_offset",
+                "start_bar",
+            ),
+            (
+                serde_json::json!({"pattern_name": "line", "start_time_offset": 1.0, "bars": [2]}),
+                "start_time_offset",
+                "bars",
+            ),
+            (
+                serde_json::json!({"pattern_name": "line", "start_bar": 2, "bars": [1, 3]}),
+                "start_bar",
+                "bars",
+            ),
+        ] {
+            let error = line
+                .apply_reference(&pattern_reference(json), &TempoMap::new(120, 4, &[]), 4)
+                .unwrap_err();
+            assert!(
+                error.contains(&format!("both '{}' and '{}'", first, second)),
+                "{}",
+                error
+            );
+        }
+
+        // Each mode on its own puts the first note at 2.0s (bar 2 at 120 BPM in 4/4)
+        for json in [
+            serde_json::json!({"pattern_name": "line", "start_time_offset": 2.0}),
+            serde_json::json!({"pattern_name": "line", "start_bar": 2}),
+            serde_json::json!({"pattern_name": "line", "bars": [2]}),
+        ] {
+            let notes = line
+                .apply_reference(
+                    &pattern_reference(json.clone()),
+                    &TempoMap::new(120, 4, &[]),
+                    4,
+                )
+                .unwrap();
+            assert_eq!(notes.len(), 4, "{}", json);
+            assert!(
+                (notes[0].start_time.unwrap() - 2.0).abs() < 1e-9,
+                "{}: starts at {:?}",
+                json,
+                notes[0].start_time
+            );
+        }
+    }
+
+    #[test]
+    fn test_retrograde_reverses_rising_line() {
+        let reference = pattern_reference(serde_json::json!({
+            "pattern_name": "line",
+            "start_bar": 2,
+            "retrograde": true
+        }));
+        let result = rising_line()
+            .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+            .unwrap();
+
+        let pitches: Vec<u8> = result.iter().map(|note| note.note.unwrap()).collect();
+        assert_eq!(pitches, vec![65, 64, 62, 60]);
+
+        // Still occupies bar 2 (2.0s-4.0s at 120 BPM), one note per beat
+        let starts: Vec<f64> = result.iter().map(|note| note.start_time.unwrap()).collect();
+        for (start, expected) in starts.iter().zip([2.0, 2.5, 3.0, 3.5]) {
+            assert!((start - expected).abs() < 1e-9, "start {}", start);
+        }
+    }
+
+    #[test]
+    fn test_scale_lock_snaps_transposed_notes_into_key_except_drums() {
+        let pattern = rising_line();
+        let pitches = |reference: serde_json::Value| -> Vec<u8> {
+            pattern
+                .apply_reference(
+                    &pattern_reference(reference),
+                    &TempoMap::new(120, 4, &[]),
+                    4,
+                )
+                .unwrap()
+                .iter()
+                .map(|note| note.note.unwrap())
+                .collect()
+        };
+
+        // Up a semitone, C D E F becomes C# D# F F#; in A minor pentatonic (A C D E G)
+        // the ties between C/D and D/E go down
+        let lock = serde_json::json!({"root": 57, "scale": "minor_pentatonic"});
+        assert_eq!(
+            pitches(serde_json::json!({"pattern_name": "line", "transpose": 1})),
+            vec![61, 63, 65, 66]
+        );
+        assert_eq!(
+            pitches(
+                serde_json::json!({"pattern_name": "line", "transpose": 1, "scale_lock": lock})
+            ),
+            vec![60, 62, 64, 67]
+        );
+        assert_eq!(
+            pitches(serde_json::json!({
+                "pattern_name": "line",
+                "transpose": 1,
+                "channel_override": 9,
+                "scale_lock": lock
+            })),
+            vec![61, 63, 65, 66]
+        );
+
+        let error = pattern
+            .apply_reference(
+                &pattern_reference(serde_json::json!({
+                    "pattern_name": "line",
+                    "scale_lock": {"root": 60, "scale": "lydian_dominant"}
+                })),
+                &TempoMap::new(120, 4, &[]),
+                4,
+            )
+            .unwrap_err();
+        assert!(
+            error.contains("Unknown scale 'lydian_dominant'"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_invert_around_c4_maps_e4_to_a_flat_3() {
+        let reference = pattern_reference(serde_json::json!({
+            "pattern_name": "line",
+            "invert_around": 60
+        }));
+        let result = rising_line()
+            .apply_reference(&reference, &TempoMap::new(120, 4, &[]), 4)
+            .unwrap();
+
+        let pitches: Vec<u8> = result.iter().map(|note| note.note.unwrap()).collect();
+        // E4 (64) -> Ab3 (56); the rising line becomes a falling one
+        assert_eq!(pitches, vec![60, 58, 56, 55]);
+    }
+
+    #[test]
+    fn test_min_release_extends_sustaining_notes_only() {
+        let mut strings = SimpleNote {
+            note: Some(60),
+            instrument: Some(48),
+            duration: Some(0.05),
+            ..Default::default()
+        };
+        let mut hihat = SimpleNote {
+            note: Some(42),
+            channel: 9,
+            duration: Some(0.05),
+            ..Default::default()
+        };
+
+        strings.apply_min_release(0.3);
+        hihat.apply_min_release(0.3);
+
+        assert_eq!(strings.duration, Some(0.3));
+        assert_eq!(hihat.duration, Some(0.05));
+    }
+
+    #[test]
+    fn test_program_change_applies_to_later_notes() {
+        let changes: Vec<ProgramChange> = serde_json::from_value(serde_json::json!([
+            {"at": 2.0, "instrument": 48}
+        ]))
+        .unwrap();
+        validate_program_changes(&changes).unwrap();
+
+        let note_at = |start: f64| SimpleNote {
+            note: Some(60),
+            instrument: Some(0),
+            start_time: Some(start),
+            duration: Some(0.5),
+            ..Default::default()
+        };
+        let mut before = note_at(1.5);
+        let mut after = note_at(2.0);
+        let mut other_channel = SimpleNote {
+            channel: 1,
+            ..note_at(3.0)
+        };
+        for note in [&mut before, &mut after, &mut other_channel] {
+            note.apply_program_changes(&changes);
+        }
+
+        assert_eq!(before.instrument, Some(0));
+        assert_eq!(after.instrument, Some(48));
+        assert_eq!(other_channel.instrument, Some(0));
+    }
+
+    #[test]
+    fn test_channel_config_instrument_fills_only_notes_without_one() {
+        let sequence: SimpleSequence = serde_json::from_value(serde_json::json!({
+            "channels": [{"channel": 1, "instrument": 32, "pan": 30}],
+            "notes": [
+                {"note": 40, "channel": 1, "start_time": 0.0, "duration": 0.5},
+                {"note": 43, "channel": 1, "instrument": 33, "start_time": 0.5, "duration": 0.5},
+                {"note": 60, "channel": 0, "start_time": 0.0, "duration": 0.5}
+            ]
+        }))
+        .unwrap();
+        validate_channel_configs(&sequence.channels).unwrap();
+
+        let notes: Vec<SimpleNote> = sequence
+            .notes
+            .iter()
+            .cloned()
+            .map(|mut note| {
+                note.apply_channel_config(&sequence.channels);
+                note
+            })
+            .collect();
+
+        assert_eq!(notes[0].instrument, Some(32));
+        assert_eq!(notes[0].pan, Some(30));
+        assert_eq!(notes[1].instrument, Some(33));
+        assert_eq!(notes[1].pan, Some(30));
+        assert_eq!(notes[2].instrument, None);
+        assert_eq!(notes[2].pan, None);
+    }
+
+    #[test]
+    fn test_snap_to_chord_follows_the_chord_at_the_notes_bar() {
+        let sequence: SimpleSequence = serde_json::from_value(serde_json::json!({
+            "chord_track": [
+                {"bar": 1, "notes": [60, 64, 67]},
+                {"bar": 2, "notes": [67, 71, 74]}
+            ],
+            "notes": [
+                {"note": 65, "start_time": 2.5, "duration": 0.5, "snap_to_chord": true},
+                {"note": 72, "start_time": 2.0, "duration": 0.5, "snap_to_chord": true},
+                {"note": 65, "start_time": 0.5, "duration": 0.5, "snap_to_chord": true},
+                {"note": 65, "start_time": 2.5, "duration": 0.5},
+                {"synth_type": "sine", "synth_frequency": 350.0, "start_time": 3.0,
+                 "duration": 0.5, "snap_to_chord": true}
+            ]
+        }))
+        .unwrap();
+        validate_chord_track(&sequence.chord_track).unwrap();
+
+        let notes: Vec<SimpleNote> = sequence
+            .notes
+            .iter()
+            .cloned()
+            .map(|mut note| {
+                note.apply_chord_track(&sequence.chord_track, &TempoMap::new(120, 4, &[]), 4);
+                note
+            })
+            .collect();
+
+        // Bar 2 (from 2.0s at 120 BPM) is G major: F4 -> G4, C5 -> B4
+        assert_eq!(notes[0].note, Some(67));
+        assert_eq!(notes[1].note, Some(71));
+        // Bar 1 is C major: F4 -> E4
+        assert_eq!(notes[2].note, Some(64));
+        assert_eq!(notes[3].note, Some(65));
+        // 350Hz sits just above F4 and lands exactly on G4
+        let g4 = 440.0 * 2f32.powf(-2.0 / 12.0);
+        assert!((notes[4].synth_frequency.unwrap() - g4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_beats_set_duration_from_tempo_unless_seconds_are_given() {
+        let mut by_beats: SimpleNote = serde_json::from_value(
+            serde_json::json!({"note": 60, "start_time": 0.0, "beats": 2.0}),
+        )
+        .unwrap();
+        by_beats.validate_timing().unwrap();
+        assert_eq!(by_beats.get_duration(120, 4), 1.0);
+        by_beats.apply_beats(120);
+        assert_eq!(by_beats.duration, Some(1.0));
+
+        let mut explicit: SimpleNote = serde_json::from_value(
+            serde_json::json!({"note": 60, "start_time": 0.0, "duration": 0.3, "beats": 2.0}),
+        )
+        .unwrap();
+        explicit.apply_beats(120);
+        assert_eq!(explicit.duration, Some(0.3));
+        assert_eq!(explicit.get_duration(120, 4), 0.3);
+
+        // Seconds beat a musical duration, which beats beats
+        let ranked: SimpleNote = serde_json::from_value(serde_json::json!({
+            "note": 60, "start_time": 0.0, "duration": 0.3, "musical_duration": "half", "beats": 3.0
+        }))
+        .unwrap();
+        assert_eq!(ranked.get_duration(120, 4), 0.3);
+        let musical = SimpleNote {
+            duration: None,
+            ..ranked
+        };
+        assert_eq!(musical.get_duration(120, 4), 1.0);
+
+        let negative: SimpleNote =
+            serde_json::from_value(serde_json::json!({"note": 60, "beats": -1.0})).unwrap();
+        assert!(negative.validate_timing().is_err());
+
+        for timing in [
+            serde_json::json!({"note": 60, "start_time": 0.0, "duration": 2.5e22}),
+            serde_json::json!({"note": 60, "start_time": 0.0, "duration": -1.0}),
+            serde_json::json!({"note": 60, "start_time": 1e300, "duration": 1.0}),
+        ] {
+            let note: SimpleNote = serde_json::from_value(timing).unwrap();
+            assert!(note.validate_timing().is_err());
+        }
+    }
+
+    #[test]
+    fn test_staccato_halves_duration_and_accent_clamps_velocity() {
+        let articulated = |articulation: &str, velocity: u8| {
+            let mut note = SimpleNote {
+                note: Some(60),
+                velocity: Some(velocity),
+                duration: Some(0.5),
+                articulation: Some(articulation.to_string()),
+                ..Default::default()
+            };
+            note.validate_timing().unwrap();
+            note.apply_articulation();
+            note
+        };
+
+        let staccato = articulated("staccato", 100);
+        assert_eq!(staccato.duration, Some(0.25));
+        assert_eq!(staccato.velocity, Some(100));
+
+        let accent = articulated("accent", 100);
+        assert_eq!(accent.velocity, Some(120));
+        assert_eq!(accent.duration, Some(0.5));
+        assert_eq!(articulated("accent", 120).velocity, Some(127));
+
+        let unknown = SimpleNote {
+            articulation: Some("spiccato".to_string()),
+            ..Default::default()
+        };
+        assert!(unknown.validate_timing().is_err());
+    }
+
+    #[test]
+    fn test_program_changes_must_ascend() {
+        let changes: Vec<ProgramChange> = serde_json::from_value(serde_json::json!([
+            {"at": 4.0, "instrument": 48},
+            {"at": 2.0, "instrument": 0}
+        ]))
+        .unwrap();
+        let error = validate_program_changes(&changes).unwrap_err();
+        assert!(error.contains("ascending"), "{}", error);
+    }
+}
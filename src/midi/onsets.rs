@@ -0,0 +1,196 @@
+use super::{SimpleNote, SimpleSequence};
+use fundsp::fft::real_fft;
+use std::path::Path;
+
+/// GM drum channel (0-based)
+const DRUM_CHANNEL: u8 = 9;
+
+/// Samples per energy frame (~12ms at 44.1kHz)
+const ONSET_HOP: usize = 512;
+/// Samples analysed for each hit's spectral centroid (~46ms at 44.1kHz)
+const CENTROID_FFT_SIZE: usize = 2048;
+/// A frame is an onset when its energy jumps this far above the recent average
+const ONSET_ENERGY_RATIO: f32 = 4.0;
+/// Frames averaged to estimate the background level before a hit
+const ONSET_HISTORY_FRAMES: usize = 8;
+/// Mean-square energy below which a frame is treated as silence (about -50 dBFS)
+const SILENCE_ENERGY: f32 = 1e-5;
+/// Shortest gap between two detected hits, so one hit's decay is not re-triggered
+const MIN_ONSET_GAP: f64 = 0.05;
+
+/// Hits with a centroid below this are kicks
+const KICK_MAX_CENTROID_HZ: f32 = 250.0;
+/// Hits with a centroid below this (and above the kick range) are snares; the rest are hats
+const SNARE_MAX_CENTROID_HZ: f32 = 3000.0;
+
+/// Length given to every imported drum hit
+const HIT_DURATION: f64 = 0.1;
+
+/// Detect drum hits in a WAV recording and map each to a GM drum on channel 10 by its
+/// brightness: low spectral centroid becomes a kick, mid a snare, high a closed hi-hat.
+/// Timing follows the audio; tempo is left at the default, so this is a starting point
+/// for reverse-engineering a groove rather than an exact transcription.
+#[allow(dead_code)]
+pub fn analyze_wav(path: &Path) -> Result<SimpleSequence, String> {
+    let (mono, sample_rate) = read_mono(path)?;
+    let notes = detect_onsets(&mono, sample_rate)
+        .into_iter()
+        .map(|onset| {
+            let hit = &mono[onset..(onset + CENTROID_FFT_SIZE).min(mono.len())];
+            let peak = hit
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            SimpleNote {
+                note: Some(drum_for_centroid(spectral_centroid(hit, sample_rate))),
+                velocity: Some((peak.min(1.0) * 127.0).round().max(1.0) as u8),
+                start_time: Some(onset as f64 / sample_rate as f64),
+                duration: Some(HIT_DURATION),
+                channel: DRUM_CHANNEL,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Ok(SimpleSequence {
+        notes,
+        ..Default::default()
+    })
+}
+
+/// Read any PCM or float WAV and average its channels down to mono
+fn read_mono(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to open WAV file {:?}: {}", path, e))?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read WAV file {:?}: {}", path, e))?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read WAV file {:?}: {}", path, e))?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    let mono = samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Ok((mono, spec.sample_rate))
+}
+
+/// Sample offsets where the short-term energy jumps well above the level just before it
+fn detect_onsets(mono: &[f32], sample_rate: u32) -> Vec<usize> {
+    let energies: Vec<f32> = mono
+        .chunks(ONSET_HOP)
+        .map(|frame| frame.iter().map(|sample| sample * sample).sum::<f32>() / frame.len() as f32)
+        .collect();
+    let min_gap = (MIN_ONSET_GAP * sample_rate as f64) as usize;
+
+    let mut onsets: Vec<usize> = Vec::new();
+    for (frame, &energy) in energies.iter().enumerate() {
+        let history = &energies[frame.saturating_sub(ONSET_HISTORY_FRAMES)..frame];
+        let background = if history.is_empty() {
+            0.0
+        } else {
+            history.iter().sum::<f32>() / history.len() as f32
+        };
+        let position = frame * ONSET_HOP;
+        if energy > SILENCE_ENERGY
+            && energy > background.max(SILENCE_ENERGY) * ONSET_ENERGY_RATIO
+            && onsets.last().is_none_or(|&last| position - last >= min_gap)
+        {
+            onsets.push(position);
+        }
+    }
+    onsets
+}
+
+/// Magnitude-weighted mean frequency of a Hann-windowed hit
+fn spectral_centroid(hit: &[f32], sample_rate: u32) -> f32 {
+    let mut input = vec![0.0f32; CENTROID_FFT_SIZE];
+    for (i, (slot, sample)) in input.iter_mut().zip(hit).enumerate() {
+        let window =
+            0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / CENTROID_FFT_SIZE as f32).cos();
+        *slot = sample * window;
+    }
+    let mut spectrum = vec![Default::default(); CENTROID_FFT_SIZE / 2 + 1];
+    real_fft(&input, &mut spectrum);
+
+    let bin_hz = sample_rate as f32 / CENTROID_FFT_SIZE as f32;
+    let (weighted, total) =
+        spectrum
+            .iter()
+            .enumerate()
+            .fold((0.0f32, 0.0f32), |(weighted, total), (bin, value)| {
+                let magnitude = value.norm();
+                (
+                    weighted + magnitude * bin as f32 * bin_hz,
+                    total + magnitude,
+                )
+            });
+    if total > 0.0 { weighted / total } else { 0.0 }
+}
+
+fn drum_for_centroid(centroid: f32) -> u8 {
+    if centroid < KICK_MAX_CENTROID_HZ {
+        36
+    } else if centroid < SNARE_MAX_CENTROID_HZ {
+        38
+    } else {
+        42
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 5ms decaying burst of `frequency` every quarter second
+    fn write_click_track(path: &Path, clicks: usize, frequency: f32) {
+        let sample_rate = 44100;
+        let spacing = sample_rate as usize / 4;
+        let click_len = sample_rate as usize / 200;
+        let mut samples = vec![0.0f32; spacing * (clicks + 1)];
+        for click in 0..clicks {
+            for i in 0..click_len {
+                let t = i as f32 / sample_rate as f32;
+                let decay = 1.0 - i as f32 / click_len as f32;
+                samples[(click + 1) * spacing + i] =
+                    0.8 * decay * (std::f32::consts::TAU * frequency * t).sin();
+            }
+        }
+        crate::midi::export::write_audio(
+            path,
+            crate::midi::export::ExportFormat::Wav,
+            &samples,
+            1,
+            sample_rate,
+            false,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_click_track_onset_count_matches_clicks() {
+        let path = std::env::temp_dir().join(format!("mcp-muse-clicks-{}.wav", std::process::id()));
+        write_click_track(&path, 8, 6000.0);
+
+        let sequence = analyze_wav(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sequence.notes.len(), 8);
+        for (index, note) in sequence.notes.iter().enumerate() {
+            let expected = (index + 1) as f64 * 0.25;
+            assert!((note.start_time.unwrap() - expected).abs() < 0.02);
+            assert_eq!(note.channel, 9);
+            assert_eq!(note.note, Some(42));
+        }
+    }
+}
@@ -1,5 +1,7 @@
-#[cfg(test)]
-use midly::{MidiMessage, Smf, TrackEventKind};
+use super::{SimpleNote, SimpleSequence};
+use midly::{Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+use std::collections::HashMap;
+use std::path::Path;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -19,18 +21,124 @@ pub struct MidiNote {
     pub sustain: Option<u8>,
 }
 
-#[cfg(test)]
+impl MidiNote {
+    /// A plain note with no controller settings
+    pub fn new(
+        note: u8,
+        velocity: u8,
+        channel: u8,
+        start_time: Duration,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            note,
+            velocity,
+            channel,
+            start_time,
+            duration,
+            instrument: None,
+            reverb: None,
+            chorus: None,
+            volume: None,
+            pan: None,
+            balance: None,
+            expression: None,
+            sustain: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedMidi {
     pub notes: Vec<MidiNote>,
-    pub tempo: u32, // microseconds per quarter note
+    pub tempo: u32, // microseconds per quarter note at the start of the file
     pub ticks_per_quarter: u16,
+    #[allow(dead_code)]
+    pub tempo_map: TempoMap,
+    pub time_signatures: Vec<TimeSignature>,
+}
+
+/// Time signature change at an absolute tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeSignature {
+    pub tick: u32,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+/// Tempo change at an absolute tick, with the time it falls at
+#[derive(Debug, Clone, Copy)]
+struct TempoChange {
+    tick: u32,
+    tempo: u32,
+    start: Duration,
+}
+
+/// Tick-to-time conversion that follows every tempo change in the file
+#[derive(Debug, Clone)]
+pub struct TempoMap {
+    ticks_per_quarter: u16,
+    changes: Vec<TempoChange>,
+}
+
+impl TempoMap {
+    /// Build from `(tick, microseconds per quarter note)` events in any order.
+    /// Until the first event the SMF default of 120 BPM applies.
+    fn new(ticks_per_quarter: u16, mut events: Vec<(u32, u32)>) -> Self {
+        events.sort_by_key(|&(tick, _)| tick);
+
+        let mut changes = vec![TempoChange {
+            tick: 0,
+            tempo: 500_000,
+            start: Duration::ZERO,
+        }];
+        for (tick, tempo) in events {
+            let previous = changes[changes.len() - 1];
+            let start = previous.start
+                + ticks_to_duration(tick - previous.tick, ticks_per_quarter, previous.tempo);
+            if previous.tick == tick {
+                changes.pop();
+            }
+            changes.push(TempoChange { tick, tempo, start });
+        }
+
+        Self {
+            ticks_per_quarter,
+            changes,
+        }
+    }
+
+    /// Time from the start of the file to `tick`
+    pub fn tick_to_duration(&self, tick: u32) -> Duration {
+        let change = self
+            .changes
+            .iter()
+            .rev()
+            .find(|change| change.tick <= tick)
+            .unwrap_or(&self.changes[0]);
+        change.start + ticks_to_duration(tick - change.tick, self.ticks_per_quarter, change.tempo)
+    }
+
+    /// Tempo in effect at the start of the file
+    fn initial_tempo(&self) -> u32 {
+        self.changes[0].tempo
+    }
+}
+
+/// Program (instrument) on `channel` at `tick`, from the file's program change events
+fn program_at(programs: &HashMap<u8, Vec<(u32, u8)>>, channel: u8, tick: u32) -> Option<u8> {
+    programs
+        .get(&channel)?
+        .iter()
+        .rev()
+        .find(|&&(change_tick, _)| change_tick <= tick)
+        .map(|&(_, program)| program)
 }
 
-#[cfg(test)]
 pub fn parse_midi_data(midi_bytes: &[u8]) -> Result<ParsedMidi, String> {
     tracing::info!("Parsing MIDI data ({} bytes)", midi_bytes.len());
 
+    // midly expands running status, so every channel event arrives with its own status
     let smf = Smf::parse(midi_bytes).map_err(|e| format!("Failed to parse MIDI: {}", e))?;
 
     tracing::info!(
@@ -47,9 +155,66 @@ pub fn parse_midi_data(midi_bytes: &[u8]) -> Result<ParsedMidi, String> {
         }
     };
 
+    // Tempo, time signature and program change events can sit in any track (tempo usually
+    // in the first of a format 1 file) but apply to all of them, so map them before timing
+    // any notes
+    let mut tempo_events = Vec::new();
+    let mut time_signatures = Vec::new();
+    let mut programs: HashMap<u8, Vec<(u32, u8)>> = HashMap::new();
+    for track in &smf.tracks {
+        let mut current_time = 0u32;
+        for event in track {
+            current_time += event.delta.as_int();
+            match event.kind {
+                TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) => {
+                    tempo_events.push((current_time, tempo.as_int()));
+                }
+                TrackEventKind::Meta(midly::MetaMessage::TimeSignature(
+                    numerator,
+                    denominator_power,
+                    _,
+                    _,
+                )) => {
+                    time_signatures.push(TimeSignature {
+                        tick: current_time,
+                        numerator,
+                        denominator: 1u8.checked_shl(denominator_power as u32).unwrap_or(4),
+                    });
+                }
+                TrackEventKind::Midi {
+                    channel,
+                    message: MidiMessage::ProgramChange { program },
+                } => {
+                    programs
+                        .entry(channel.as_int())
+                        .or_default()
+                        .push((current_time, program.as_int()));
+                }
+                _ => {}
+            }
+        }
+    }
+    time_signatures.sort_by_key(|signature| signature.tick);
+    for changes in programs.values_mut() {
+        changes.sort_by_key(|&(tick, _)| tick);
+    }
+    let tempo_map = TempoMap::new(ticks_per_quarter, tempo_events);
+
     let mut notes = Vec::new();
-    let mut tempo = 500_000; // Default tempo (120 BPM)
-    let mut note_on_events = std::collections::HashMap::new();
+    // Sounding notes by (channel, key), oldest first so a retriggered key releases in order
+    let mut note_on_events: HashMap<(u8, u8), Vec<(u32, u8)>> = HashMap::new();
+    let mut end_time = 0u32;
+
+    let mut finish_note = |channel: u8, key: u8, start_tick: u32, velocity: u8, end_tick: u32| {
+        let start_time = tempo_map.tick_to_duration(start_tick);
+        let duration = tempo_map.tick_to_duration(end_tick) - start_time;
+        let mut note = MidiNote::new(key, velocity, channel, start_time, duration);
+        // Channel 9 program changes pick drum kits, which the GM kit does not follow
+        if channel != 9 {
+            note.instrument = program_at(&programs, channel, start_tick);
+        }
+        notes.push(note);
+    };
 
     // Process all tracks
     for (track_idx, track) in smf.tracks.iter().enumerate() {
@@ -58,103 +223,54 @@ pub fn parse_midi_data(midi_bytes: &[u8]) -> Result<ParsedMidi, String> {
         for event in track {
             current_time += event.delta.as_int();
 
-            match event.kind {
-                TrackEventKind::Meta(midly::MetaMessage::Tempo(new_tempo)) => {
-                    tempo = new_tempo.as_int();
+            let TrackEventKind::Midi { channel, message } = event.kind else {
+                continue; // Tempo and time signature are already mapped
+            };
+            let channel = channel.as_int();
+            match message {
+                MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                    tracing::debug!(
+                        "Note On: channel={}, key={}, velocity={}, time={}",
+                        channel,
+                        key.as_int(),
+                        vel.as_int(),
+                        current_time
+                    );
+                    note_on_events
+                        .entry((channel, key.as_int()))
+                        .or_default()
+                        .push((current_time, vel.as_int()));
                 }
-                TrackEventKind::Meta(_) => {} // Ignore other meta messages
-                TrackEventKind::Midi { channel, message } => {
-                    match message {
-                        MidiMessage::NoteOn { key, vel } => {
-                            tracing::debug!(
-                                "Note On: channel={}, key={}, velocity={}, time={}",
-                                channel.as_int(),
-                                key.as_int(),
-                                vel.as_int(),
-                                current_time
-                            );
-                            if vel.as_int() > 0 {
-                                // Store note on event
-                                note_on_events.insert(
-                                    (channel.as_int(), key.as_int()),
-                                    (current_time, vel.as_int()),
-                                );
-                            } else {
-                                // Note on with velocity 0 is note off
-                                if let Some((start_time, velocity)) =
-                                    note_on_events.remove(&(channel.as_int(), key.as_int()))
-                                {
-                                    let duration = ticks_to_duration(
-                                        current_time - start_time,
-                                        ticks_per_quarter,
-                                        tempo,
-                                    );
-                                    let start_duration =
-                                        ticks_to_duration(start_time, ticks_per_quarter, tempo);
-
-                                    notes.push(MidiNote {
-                                        note: key.as_int(),
-                                        velocity,
-                                        channel: channel.as_int(),
-                                        start_time: start_duration,
-                                        duration,
-                                        instrument: None,
-                                        reverb: None,
-                                        chorus: None,
-                                        volume: None,
-                                        pan: None,
-                                        balance: None,
-                                        expression: None,
-                                        sustain: None,
-                                    });
-                                }
-                            }
-                        }
-                        MidiMessage::NoteOff { key, vel: _ } => {
-                            tracing::debug!(
-                                "Note Off: channel={}, key={}, time={}",
-                                channel.as_int(),
-                                key.as_int(),
-                                current_time
-                            );
-                            if let Some((start_time, velocity)) =
-                                note_on_events.remove(&(channel.as_int(), key.as_int()))
-                            {
-                                let duration = ticks_to_duration(
-                                    current_time - start_time,
-                                    ticks_per_quarter,
-                                    tempo,
-                                );
-                                let start_duration =
-                                    ticks_to_duration(start_time, ticks_per_quarter, tempo);
-
-                                notes.push(MidiNote {
-                                    note: key.as_int(),
-                                    velocity,
-                                    channel: channel.as_int(),
-                                    start_time: start_duration,
-                                    duration,
-                                    instrument: None,
-                                    reverb: None,
-                                    chorus: None,
-                                    volume: None,
-                                    pan: None,
-                                    balance: None,
-                                    expression: None,
-                                    sustain: None,
-                                });
-                            }
-                        }
-                        _ => {} // Ignore other MIDI messages for now
+                // Note on with velocity 0 is note off
+                MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                    tracing::debug!(
+                        "Note Off: channel={}, key={}, time={}",
+                        channel,
+                        key.as_int(),
+                        current_time
+                    );
+                    if let Some(held) = note_on_events.get_mut(&(channel, key.as_int()))
+                        && !held.is_empty()
+                    {
+                        let (start_tick, velocity) = held.remove(0);
+                        finish_note(channel, key.as_int(), start_tick, velocity, current_time);
                     }
                 }
-                _ => {} // Ignore other event types
+                _ => {} // Ignore other MIDI messages for now
             }
         }
+        end_time = end_time.max(current_time);
+    }
+
+    // Notes never released end with the file
+    for ((channel, key), held) in note_on_events {
+        for (start_tick, velocity) in held {
+            finish_note(channel, key, start_tick, velocity, end_time);
+        }
     }
 
     // Sort notes by start time
-    notes.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+    notes.sort_by_key(|a| (a.start_time, a.channel, a.note));
 
     tracing::info!("MIDI parsing complete: {} notes found", notes.len());
     for (i, note) in notes.iter().take(5).enumerate() {
@@ -170,12 +286,241 @@ pub fn parse_midi_data(midi_bytes: &[u8]) -> Result<ParsedMidi, String> {
 
     Ok(ParsedMidi {
         notes,
-        tempo,
+        tempo: tempo_map.initial_tempo(),
         ticks_per_quarter,
+        tempo_map,
+        time_signatures,
     })
 }
 
-#[cfg(test)]
+/// Read a Standard MIDI File (format 0 or 1) as a sequence of seconds-timed notes, with
+/// each note's instrument taken from its channel's latest program change. The sequence
+/// tempo and time signature are the file's opening ones.
+pub fn load_smf(path: &Path) -> Result<SimpleSequence, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read MIDI file {:?}: {}", path, e))?;
+    let parsed = parse_midi_data(&bytes)?;
+    tracing::info!(
+        "Loaded {:?}: {} notes at {} ticks per quarter",
+        path,
+        parsed.notes.len(),
+        parsed.ticks_per_quarter
+    );
+
+    let mut sequence = SimpleSequence::new();
+    sequence.tempo = (60_000_000.0 / parsed.tempo as f64)
+        .round()
+        .clamp(1.0, 999.0) as u32;
+    if let Some(signature) = parsed.time_signatures.first().filter(|s| s.tick == 0) {
+        sequence.beats_per_bar = (signature.numerator as u32).clamp(1, 16);
+    }
+    sequence.notes = parsed
+        .notes
+        .iter()
+        .map(|note| SimpleNote {
+            note: Some(note.note),
+            velocity: Some(note.velocity),
+            channel: note.channel,
+            instrument: note.instrument,
+            start_time: Some(note.start_time.as_secs_f64()),
+            duration: Some(note.duration.as_secs_f64()),
+            ..Default::default()
+        })
+        .collect();
+    Ok(sequence)
+}
+
+/// Ticks per quarter note in files `write_smf` writes
+const EXPORT_TICKS_PER_QUARTER: u16 = 480;
+/// Length in ticks of the constant-tempo steps a tempo ramp is written as (a 16th note)
+const TEMPO_RAMP_STEP_TICKS: u32 = 120;
+
+/// Tempo events, in microseconds per quarter note, for `tempo_map`. MIDI files hold
+/// tempos constant between events, so each ramp becomes 16th-note steps, each at the
+/// average tempo of its span so every step begins at its time along the map.
+fn tempo_events(tempo_map: &super::TempoMap) -> Vec<(u32, u32)> {
+    let ticks_per_quarter = EXPORT_TICKS_PER_QUARTER as f64;
+    let step = TEMPO_RAMP_STEP_TICKS as f64 / ticks_per_quarter;
+    let tick = |beat: f64| (beat * ticks_per_quarter).round() as u32;
+    let micros_per_quarter = |bpm: f64| (60_000_000.0 / bpm) as u32;
+
+    let points = tempo_map.points();
+    let mut tempos = Vec::new();
+    for pair in points.windows(2) {
+        let ((from, from_bpm), (to, to_bpm)) = (pair[0], pair[1]);
+        if from_bpm == to_bpm || to <= from {
+            tempos.push((tick(from), micros_per_quarter(from_bpm)));
+            continue;
+        }
+        let mut beat = from;
+        while beat < to {
+            let end = (beat + step).min(to);
+            let seconds = tempo_map.seconds_at(end) - tempo_map.seconds_at(beat);
+            let micros = (seconds * 1_000_000.0 / (end - beat)).round() as u32;
+            tempos.push((tick(beat), micros));
+            beat = end;
+        }
+    }
+    if let Some(&(beat, bpm)) = points.last() {
+        tempos.push((tick(beat), micros_per_quarter(bpm)));
+    }
+
+    // A later tempo at the same tick replaces the earlier one; repeats add nothing
+    let mut events: Vec<(u32, u32)> = Vec::with_capacity(tempos.len());
+    for (tick, micros) in tempos {
+        match events.last_mut() {
+            Some(last) if last.0 == tick => last.1 = micros,
+            Some(last) if last.1 == micros => {}
+            _ => events.push((tick, micros)),
+        }
+    }
+    events
+}
+
+/// Turn `(tick, event)` pairs, already in order, into a track of delta-timed events
+fn delta_track<'a>(events: Vec<(u32, TrackEventKind<'a>)>) -> Vec<TrackEvent<'a>> {
+    let mut previous = 0;
+    let mut track: Vec<TrackEvent> = events
+        .into_iter()
+        .map(|(tick, kind)| {
+            let delta = tick - previous;
+            previous = tick;
+            TrackEvent {
+                delta: delta.into(),
+                kind,
+            }
+        })
+        .collect();
+    track.push(TrackEvent {
+        delta: 0.into(),
+        kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+    });
+    track
+}
+
+/// Write notes to a format 1 Standard MIDI File at 480 PPQ: a conductor track with the
+/// tempo map and time signature, then one track per channel holding its program changes
+/// (wherever a note's instrument differs from the last) and note on/off pairs. Notes are
+/// placed in beats along `tempo_map`. Where notes on one key overlap, only the last one
+/// to end sends a note-off, as a note-off ends every sounding instance of its key.
+pub fn write_smf(
+    notes: &[MidiNote],
+    tempo_map: &super::TempoMap,
+    beats_per_bar: u32,
+    path: &Path,
+) -> Result<(), String> {
+    let tick = |time: Duration| {
+        (tempo_map.beat_at(time.as_secs_f64()) * EXPORT_TICKS_PER_QUARTER as f64).round() as u32
+    };
+
+    let mut smf = Smf::new(Header::new(
+        Format::Parallel,
+        Timing::Metrical(EXPORT_TICKS_PER_QUARTER.into()),
+    ));
+    // Quarter-note beats, 24 MIDI clocks per click, 8 32nds per quarter
+    let time_signature = (
+        0,
+        TrackEventKind::Meta(MetaMessage::TimeSignature(beats_per_bar as u8, 2, 24, 8)),
+    );
+    let tempos = tempo_events(tempo_map).into_iter().map(|(tick, micros)| {
+        (
+            tick,
+            TrackEventKind::Meta(MetaMessage::Tempo(micros.into())),
+        )
+    });
+    smf.tracks.push(delta_track(
+        std::iter::once(time_signature).chain(tempos).collect(),
+    ));
+
+    let mut channels: Vec<u8> = notes.iter().map(|note| note.channel).collect();
+    channels.sort_unstable();
+    channels.dedup();
+    for channel in channels {
+        let mut channel_notes: Vec<&MidiNote> = notes
+            .iter()
+            .filter(|note| note.channel == channel)
+            .collect();
+        channel_notes.sort_by_key(|note| note.start_time);
+
+        // Ranked so that at a shared tick a note ends before the program changes and the
+        // next note starts
+        let mut events: Vec<(u32, u8, TrackEventKind)> = Vec::new();
+        let mut program = None;
+        let midi = |message| TrackEventKind::Midi {
+            channel: channel.into(),
+            message,
+        };
+        for note in channel_notes {
+            let start = tick(note.start_time);
+            if let Some(instrument) = note.instrument
+                && channel != 9
+                && program != Some(instrument)
+            {
+                events.push((
+                    start,
+                    1,
+                    midi(MidiMessage::ProgramChange {
+                        program: instrument.into(),
+                    }),
+                ));
+                program = Some(instrument);
+            }
+            events.push((
+                start,
+                2,
+                midi(MidiMessage::NoteOn {
+                    key: note.note.into(),
+                    vel: note.velocity.max(1).into(),
+                }),
+            ));
+            // At least one tick long, or the note-off would sort before its note-on
+            let end = tick(note.start_time + note.duration).max(start + 1);
+            events.push((
+                end,
+                0,
+                midi(MidiMessage::NoteOff {
+                    key: note.note.into(),
+                    vel: 0.into(),
+                }),
+            ));
+        }
+        events.sort_by_key(|&(tick, rank, _)| (tick, rank));
+
+        // Sounding instances per key; a note-off waits for the last of them
+        let mut sounding: HashMap<u8, u32> = HashMap::new();
+        let events = events
+            .into_iter()
+            .filter(|(_, _, kind)| match kind {
+                TrackEventKind::Midi {
+                    message: MidiMessage::NoteOn { key, .. },
+                    ..
+                } => {
+                    *sounding.entry(key.as_int()).or_default() += 1;
+                    true
+                }
+                TrackEventKind::Midi {
+                    message: MidiMessage::NoteOff { key, .. },
+                    ..
+                } => {
+                    let count = sounding.entry(key.as_int()).or_default();
+                    *count = count.saturating_sub(1);
+                    *count == 0
+                }
+                _ => true,
+            })
+            .map(|(tick, _, kind)| (tick, kind))
+            .collect();
+        smf.tracks.push(delta_track(events));
+    }
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create output directory {:?}: {}", dir, e))?;
+    }
+    smf.save(path)
+        .map_err(|e| format!("Failed to write MIDI file {:?}: {}", path, e))
+}
+
 fn ticks_to_duration(ticks: u32, ticks_per_quarter: u16, tempo: u32) -> Duration {
     let microseconds_per_tick = (tempo as f64) / (ticks_per_quarter as f64);
     let total_microseconds = (ticks as f64) * microseconds_per_tick;
@@ -290,4 +635,141 @@ mod tests {
         let parsed = result.unwrap();
         assert_eq!(parsed.notes.len(), 0, "Should have no notes");
     }
+
+    #[test]
+    fn test_mid_track_tempo_change_respaces_later_notes() {
+        let mut bytes = Vec::new();
+
+        // MIDI header
+        bytes.extend_from_slice(&[
+            0x4D, 0x54, 0x68, 0x64, // "MThd"
+            0x00, 0x00, 0x00, 0x06, // Header length (6 bytes)
+            0x00, 0x00, // Format type 0
+            0x00, 0x01, // Number of tracks (1)
+            0x00, 0x60, // Ticks per quarter note (96)
+        ]);
+
+        let track_events = [
+            0x00, 0xFF, 0x58, 0x04, 0x03, 0x02, 0x18, 0x08, // Delta=0, Time signature 3/4
+            // Two quarter notes at the default 120 BPM
+            0x00, 0x90, 0x3C, 0x64, // Delta=0, Note On C4
+            0x60, 0x80, 0x3C, 0x00, // Delta=96, Note Off C4
+            0x00, 0x90, 0x3E, 0x64, // Delta=0, Note On D4
+            0x60, 0x80, 0x3E, 0x00, // Delta=96, Note Off D4
+            0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // Delta=0, Tempo 1,000,000us = 60 BPM
+            // Two quarter notes at 60 BPM
+            0x00, 0x90, 0x40, 0x64, // Delta=0, Note On E4
+            0x60, 0x80, 0x40, 0x00, // Delta=96, Note Off E4
+            0x00, 0x90, 0x41, 0x64, // Delta=0, Note On F4
+            0x60, 0x80, 0x41, 0x00, // Delta=96, Note Off F4
+            0x00, 0xFF, 0x2F, 0x00, // Delta=0, End of track
+        ];
+
+        bytes.extend_from_slice(&[0x4D, 0x54, 0x72, 0x6B]); // "MTrk"
+        bytes.extend_from_slice(&(track_events.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&track_events);
+
+        let parsed = parse_midi_data(&bytes).unwrap();
+        assert_eq!(parsed.tempo, 500_000);
+        assert_eq!(
+            parsed.time_signatures,
+            vec![TimeSignature {
+                tick: 0,
+                numerator: 3,
+                denominator: 4
+            }]
+        );
+
+        // Four quarters: two at 0.5s, then two at 1s
+        assert_eq!(
+            parsed.tempo_map.tick_to_duration(384),
+            Duration::from_secs(3)
+        );
+
+        let timing: Vec<(Duration, Duration)> = parsed
+            .notes
+            .iter()
+            .map(|note| (note.start_time, note.duration))
+            .collect();
+        assert_eq!(
+            timing,
+            vec![
+                (Duration::ZERO, Duration::from_millis(500)),
+                (Duration::from_millis(500), Duration::from_millis(500)),
+                (Duration::from_millis(1000), Duration::from_millis(1000)),
+                (Duration::from_millis(2000), Duration::from_millis(1000)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_smf_reads_a_format_1_file_into_seconds_timed_notes() {
+        let track = |events: &[u8]| {
+            let mut chunk = vec![0x4D, 0x54, 0x72, 0x6B]; // "MTrk"
+            chunk.extend_from_slice(&(events.len() as u32).to_be_bytes());
+            chunk.extend_from_slice(events);
+            chunk
+        };
+        let mut bytes = vec![
+            0x4D, 0x54, 0x68, 0x64, // "MThd"
+            0x00, 0x00, 0x00, 0x06, // Header length (6 bytes)
+            0x00, 0x01, // Format type 1
+            0x00, 0x02, // Number of tracks (2)
+            0x00, 0x60, // Ticks per quarter note (96)
+        ];
+        // Conductor track: 120 BPM, then 60 BPM from tick 384
+        bytes.extend(track(&[
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // Delta=0, Tempo 500,000us
+            0x83, 0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // Delta=384, Tempo 1,000,000us
+            0x00, 0xFF, 0x2F, 0x00, // End of track
+        ]));
+        bytes.extend(track(&[
+            0x00, 0xC0, 0x28, // Delta=0, Program Change Ch0 -> 40 (violin)
+            0x00, 0x90, 0x3C, 0x64, // Delta=0, Note On C4
+            0x60, 0x3E, 0x50, // Delta=96, running status Note On D4 vel 80
+            0x60, 0x3C, 0x00, // Delta=96, running status C4 vel 0 (off)
+            0x00, 0x3E, 0x00, // Delta=0, running status D4 vel 0 (off)
+            0x00, 0xC9, 0x05, // Delta=0, Program Change Ch9 (drum kit)
+            0x00, 0x99, 0x24, 0x64, // Delta=0, Note On kick Ch9
+            0x60, 0x89, 0x24, 0x00, // Delta=96, Note Off kick
+            0x60, 0x90, 0x40, 0x64, // Delta=96, Note On E4 at the 60 BPM change
+            0x00, 0x43, 0x64, // Delta=0, running status Note On G4, never released
+            0x60, 0x40, 0x00, // Delta=96, running status E4 vel 0 (off)
+            0x00, 0xFF, 0x2F, 0x00, // End of track
+        ]));
+
+        let path = std::env::temp_dir().join("mcp-muse-load-smf-test.mid");
+        std::fs::write(&path, &bytes).unwrap();
+        let sequence = load_smf(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sequence.tempo, 120);
+        let notes: Vec<(u8, u8, u8, Option<u8>, f64, f64)> = sequence
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.note.unwrap(),
+                    note.channel,
+                    note.velocity.unwrap(),
+                    note.instrument,
+                    note.start_time.unwrap(),
+                    note.duration.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (60, 0, 100, Some(40), 0.0, 1.0),
+                (62, 0, 80, Some(40), 0.5, 0.5),
+                (36, 9, 100, None, 1.0, 0.5),
+                (64, 0, 100, Some(40), 2.0, 1.0),
+                (67, 0, 100, Some(40), 2.0, 1.0),
+            ]
+        );
+
+        let error = load_smf(Path::new("/nonexistent/song.mid")).unwrap_err();
+        assert!(error.contains("Failed to read MIDI file"), "{}", error);
+    }
 }
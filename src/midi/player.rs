@@ -1,22 +1,191 @@
 use crate::expressive::{
-    EffectsPresetLibrary, ExpressiveSynth, FunDSPEffectsProcessor, PresetLibrary, R2D2Emotion,
-    R2D2Expression, R2D2Voice,
+    BassMono, EffectsPresetLibrary, ExpressiveSynth, FunDSPEffectsProcessor, MasterSeedScope,
+    PresetLibrary, R2D2Emotion, R2D2Expression, R2D2Voice, apply_note_off_fade, note_off_stagger,
 };
-use crate::midi::SimpleSequence;
-use crate::midi::parser::MidiNote;
+use crate::midi::analysis;
+use crate::midi::buffering::BufferedSource;
+use crate::midi::export::{self, ExportFormat, RenderOptions};
+use crate::midi::looping;
+use crate::midi::parser::{self, MidiNote};
+use crate::midi::resolve::{apply_preset_to_note, resolve_notes};
+use crate::midi::{SimpleSequence, TempoMap};
+use crate::setup::config::{DEFAULT_STOP_FADE_MS, MAX_MASTER_GAIN, SetupConfig};
 use oxisynth::{MidiEvent, SoundFont, Synth};
 use rodio::{OutputStream, Sink, Source};
+use serde::Serialize;
 use std::time::Duration;
 
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+/// Bumped by `MidiPlayer::panic()`; sources created under an older generation silence themselves
+static PANIC_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Bumped by `MidiPlayer::stop()`; sources created under an older generation fade out and stop
+static STOP_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Fade-out length for the latest stop request, in microseconds
+static STOP_FADE_MICROS: AtomicU64 = AtomicU64::new(DEFAULT_STOP_FADE_MS * 1000);
+
+/// Master gain as f32 bits, read per sample so volume changes reach audio already playing
+static MASTER_GAIN_BITS: AtomicU32 = AtomicU32::new(1.0f32.to_bits());
+
+/// Device playbacks still producing audio, across every player
+static ACTIVE_PLAYBACKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Voices sounding across device playbacks: synthesis and R2D2 events plus held MIDI notes
+static ACTIVE_VOICES: AtomicUsize = AtomicUsize::new(0);
+
+/// Frames between updates of a playing source's voice count
+const VOICE_COUNT_INTERVAL: usize = 1024;
+
+/// GM drum channel (0-based)
+const DRUM_CHANNEL: u8 = 9;
+
+/// Peak level the master limiter holds the mix under (-0.3 dBFS)
+const LIMITER_CEILING: f32 = 0.966;
+/// Time constant of the master limiter's recovery after a peak, in milliseconds
+const LIMITER_RELEASE_MS: f32 = 80.0;
+
+/// Level of MIDI drums on their own buses, matching the regular mix. While drums sound,
+/// the shared synthesizer's frame enters the mixer on channel 9 boosted 3x and again
+/// unboosted on channel 0. Both are centered at volume 1.0, where the equal-power pan
+/// gain (cos 45° · √2) is exactly 1, so the drums reach the mix at 3 + 1 = 4x.
+const MIDI_DRUM_BUS_GAIN: f32 = 4.0;
+
+/// Glue compressor for the drum bus: moderate ratio so the kit tightens up without pumping
+const DRUM_GLUE_COMPRESSOR: crate::midi::EffectConfig = crate::midi::EffectConfig {
+    effect: crate::midi::EffectType::Compressor {
+        threshold: -18.0,
+        ratio: 3.0,
+        attack: 0.001,
+        release: 0.15,
+        parallel: 1.0,
+    },
+    intensity: 1.0,
+    enabled: true,
+    wet_only: false,
+};
+
+/// How far the drum bus compressor looks ahead, so hits are caught from their first sample
+const DRUM_GLUE_LOOKAHEAD: f32 = 0.01;
+
+/// Trailing level, in dBFS, below which the render stops once every note has ended
+pub const DEFAULT_TAIL_CUTOFF_DB: f32 = -60.0;
+/// How long the mix must stay below the tail cutoff before the render stops, so a gap
+/// before an echo does not end it
+const TAIL_CUTOFF_WINDOW: f64 = 0.5;
+
+pub fn validate_tail_cutoff_db(cutoff_db: f32) -> Result<(), String> {
+    if !(-120.0..=-20.0).contains(&cutoff_db) {
+        return Err(format!(
+            "tail_cutoff_db must be between -120 and -20 dBFS, got {}",
+            cutoff_db
+        ));
+    }
+    Ok(())
+}
+
+/// Frames OxiSynth renders at a time; it starts voices only on these block boundaries
+const OXISYNTH_BLOCK: u32 = 64;
+
+/// Sample an event starting at `seconds` begins on. MIDI, synthesized and R2D2 events all
+/// use this so sounds meant to hit together land on the same sample. OxiSynth's blocks are
+/// lined up with the first MIDI note; a later MIDI note off that block grid starts on the
+/// next block, up to 63 frames (about 1.4ms) after this sample.
+pub fn sample_index(seconds: f64, sample_rate: u32) -> u32 {
+    (seconds * sample_rate as f64).round() as u32
+}
+
+/// Equal-power pan law: (left, right) gains for a pan position (-1.0=left, 0.0=center, 1.0=right)
+fn equal_power_pan(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+/// Audio rendered offline from a sequence
+pub struct RenderedAudio {
+    /// Interleaved samples
+    pub samples: Vec<f32>,
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// Total duration including effect tails
+    pub duration: Duration,
+}
+
+/// How many notes `MidiPlayer::export_smf` wrote, and how many it left out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmfExport {
+    pub exported: usize,
+    pub skipped: usize,
+}
+
+/// Stop and panic requests aimed at the sources one player started, leaving every other
+/// playback running. `MidiPlayer::stop()` and `MidiPlayer::panic()` still reach all of them.
+#[derive(Clone, Default)]
+pub struct PlaybackControl {
+    flags: Arc<PlaybackFlags>,
+}
+
+#[derive(Default)]
+struct PlaybackFlags {
+    panicked: AtomicBool,
+    stopped: AtomicBool,
+    stop_fade_micros: AtomicU64,
+}
+
+impl PlaybackControl {
+    /// Silence this playback at once, as `MidiPlayer::panic()` does for every playback
+    pub fn panic(&self) {
+        self.flags.panicked.store(true, Ordering::SeqCst);
+    }
+
+    /// Fade this playback out, as `MidiPlayer::stop()` does for every playback. Returns
+    /// the fade length.
+    pub fn stop(&self) -> Duration {
+        let fade = configured_stop_fade();
+        self.flags
+            .stop_fade_micros
+            .store(fade.as_micros() as u64, Ordering::SeqCst);
+        self.flags.stopped.store(true, Ordering::SeqCst);
+        fade
+    }
+
+    fn panic_requested(&self) -> bool {
+        self.flags.panicked.load(Ordering::SeqCst)
+    }
+
+    /// Fade-out length once a stop reached a source created at `stop_generation`, either
+    /// through this handle or through `MidiPlayer::stop()`
+    fn stop_fade(&self, stop_generation: u64) -> Option<Duration> {
+        if MidiPlayer::stop_generation() != stop_generation {
+            Some(MidiPlayer::stop_fade())
+        } else if self.flags.stopped.load(Ordering::SeqCst) {
+            Some(Duration::from_micros(
+                self.flags.stop_fade_micros.load(Ordering::SeqCst),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Fade-out length for stops, from `stop_fade_ms` in the config
+fn configured_stop_fade() -> Duration {
+    SetupConfig::load()
+        .map(|config| config.stop_fade())
+        .unwrap_or(Duration::from_millis(DEFAULT_STOP_FADE_MS))
+}
 
 pub struct MidiPlayer {
     _stream: OutputStream,
     sink: Sink,
     preset_library: PresetLibrary,
     effects_library: EffectsPresetLibrary,
+    control: PlaybackControl,
 }
 
 impl MidiPlayer {
@@ -31,9 +200,86 @@ impl MidiPlayer {
             sink,
             preset_library: PresetLibrary::new(),
             effects_library: EffectsPresetLibrary::new(),
+            control: PlaybackControl::default(),
         })
     }
 
+    /// Handle reaching only the sources this player starts, e.g. to silence them when a
+    /// play request fails without cutting off other playback
+    pub fn control(&self) -> PlaybackControl {
+        self.control.clone()
+    }
+
+    /// Silence all playback: every active source sends All-Notes-Off and All-Sound-Off on
+    /// every MIDI channel, drops its pending and sounding synthesis voices, and stops.
+    pub fn panic() {
+        let generation = PANIC_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::warn!(
+            "🛑 Panic: silencing all playback (generation {})",
+            generation
+        );
+    }
+
+    /// Current panic generation, captured by audio sources when they are created
+    pub(crate) fn panic_generation() -> u64 {
+        PANIC_GENERATION.load(Ordering::SeqCst)
+    }
+
+    /// Stop all playback with a short fade-out (`stop_fade_ms` in the config) instead of
+    /// cutting mid-buffer; once faded, every source is silenced as with `panic()`.
+    /// Returns the fade length.
+    pub fn stop() -> Duration {
+        let fade = configured_stop_fade();
+        STOP_FADE_MICROS.store(fade.as_micros() as u64, Ordering::SeqCst);
+        let generation = STOP_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::info!(
+            "⏹️ Stop: fading out all playback over {:.1}ms (generation {})",
+            fade.as_secs_f64() * 1000.0,
+            generation
+        );
+        fade
+    }
+
+    /// Current stop generation, captured by audio sources when they are created
+    pub(crate) fn stop_generation() -> u64 {
+        STOP_GENERATION.load(Ordering::SeqCst)
+    }
+
+    /// Fade-out length requested by the latest stop
+    fn stop_fade() -> Duration {
+        Duration::from_micros(STOP_FADE_MICROS.load(Ordering::SeqCst))
+    }
+
+    /// Set the listening volume of everything played on the device, including sounds
+    /// already playing (0.0-2.0). Offline renders are unaffected.
+    pub fn set_master_gain(gain: f32) {
+        let gain = gain.clamp(0.0, MAX_MASTER_GAIN);
+        MASTER_GAIN_BITS.store(gain.to_bits(), Ordering::SeqCst);
+        tracing::info!("🔊 Master gain set to {:.2}", gain);
+    }
+
+    /// Current master gain
+    pub fn master_gain() -> f32 {
+        f32::from_bits(MASTER_GAIN_BITS.load(Ordering::SeqCst))
+    }
+
+    /// Whether a SoundFont can be found for MIDI playback (it is loaded on every play)
+    pub fn soundfont_available() -> bool {
+        find_soundfont().is_ok()
+    }
+
+    /// What the audio engine is doing right now, across every player
+    pub fn engine_status() -> EngineStatus {
+        let playbacks = ACTIVE_PLAYBACKS.load(Ordering::SeqCst);
+        EngineStatus {
+            playing: playbacks > 0,
+            playbacks,
+            voices: ACTIVE_VOICES.load(Ordering::SeqCst),
+            master_gain: Self::master_gain(),
+            soundfont_available: Self::soundfont_available(),
+        }
+    }
+
     /// Calculate additional tail time needed for effects like reverb, chorus, sustain, and natural decay
     fn calculate_tail_time(notes: &[MidiNote]) -> Duration {
         let mut max_tail_seconds: f64 = 2.0; // Base tail time for natural instrument decay
@@ -102,257 +348,383 @@ impl MidiPlayer {
         Duration::from_secs_f64(max_tail_seconds)
     }
 
-    /// Apply preset configuration to a SimpleNote
-    fn apply_preset_to_note(&self, note: &mut crate::midi::SimpleNote) -> Result<(), String> {
-        // Skip if no preset parameters are specified
-        if note.preset_name.is_none()
-            && note.preset_category.is_none()
-            && !note.preset_random.unwrap_or(false)
-        {
-            return Ok(());
+    /// Order notes by start time and keep `instrument` only where it differs from the channel's
+    /// previous program, so the synth gets one program change per actual switch.
+    /// Drums are left alone. Returns how many program changes remain.
+    fn merge_redundant_program_changes(notes: &mut [MidiNote]) -> usize {
+        notes.sort_by_key(|note| note.start_time);
+        let mut programs: std::collections::HashMap<u8, u8> = std::collections::HashMap::new();
+        let mut changes = 0;
+        for note in notes.iter_mut().filter(|note| note.channel != 9) {
+            if let Some(instrument) = note.instrument {
+                if programs.insert(note.channel, instrument) == Some(instrument) {
+                    note.instrument = None;
+                } else {
+                    changes += 1;
+                }
+            }
         }
+        changes
+    }
 
-        // Load preset based on parameters
-        let preset = if let Some(preset_name) = &note.preset_name {
-            // Load specific preset by name
-            self.preset_library
-                .load_preset(preset_name)
-                .ok_or_else(|| format!("Preset '{}' not found", preset_name))?
-        } else if let Some(category_str) = &note.preset_category {
-            // Load random preset from category
-            let category = match category_str.as_str() {
-                "bass" => crate::expressive::PresetCategory::Bass,
-                "pad" => crate::expressive::PresetCategory::Pad,
-                "lead" => crate::expressive::PresetCategory::Lead,
-                "keys" => crate::expressive::PresetCategory::Keys,
-                "organ" => crate::expressive::PresetCategory::Organ,
-                "arp" => crate::expressive::PresetCategory::Arp,
-                "drums" => crate::expressive::PresetCategory::Drums,
-                "effects" => crate::expressive::PresetCategory::Effects,
-                _ => return Err(format!("Unknown preset category: {}", category_str)),
-            };
+    /// Total audible duration of the scheduled content: the last note end plus effect and decay tails
+    fn audible_duration(
+        midi_notes: &[MidiNote],
+        r2d2_events: &[R2D2Event],
+        synthesis_events: &[SynthEvent],
+    ) -> Duration {
+        Self::note_end_time(midi_notes, r2d2_events, synthesis_events)
+            + Self::calculate_tail_time(midi_notes)
+    }
 
-            self.preset_library
-                .get_random_preset(Some(category))
-                .ok_or_else(|| format!("No presets found in category '{}'", category_str))?
-        } else if note.preset_random.unwrap_or(false) {
-            // Load completely random preset
-            self.preset_library
-                .get_random_preset(None)
-                .ok_or("No presets available for random selection")?
+    /// When the last scheduled note ends, before any tail
+    fn note_end_time(
+        midi_notes: &[MidiNote],
+        r2d2_events: &[R2D2Event],
+        synthesis_events: &[SynthEvent],
+    ) -> Duration {
+        let midi_end_time = if !midi_notes.is_empty() {
+            midi_notes
+                .iter()
+                .map(|note| note.start_time + note.duration)
+                .max()
+                .unwrap_or(Duration::from_secs(1))
         } else {
-            return Ok(()); // No valid preset selection
+            Duration::from_secs(0)
         };
 
-        // Apply preset variation if specified
-        let synth_params = if let Some(variation_name) = &note.preset_variation {
-            self.preset_library
-                .apply_variation(&preset.name, variation_name)
-                .unwrap_or_else(|| preset.synth_params.clone())
+        let r2d2_end_time = if !r2d2_events.is_empty() {
+            r2d2_events
+                .iter()
+                .map(|event| {
+                    Duration::from_secs_f64(event.start_time + event.expression.duration as f64)
+                })
+                .max()
+                .unwrap_or(Duration::from_secs(1))
         } else {
-            preset.synth_params.clone()
+            Duration::from_secs(0)
         };
 
-        // Apply preset parameters to the note (convert from SynthParams to SimpleNote fields)
-        note.synth_type = Some(
-            match &synth_params.synth_type {
-                crate::expressive::SynthType::Sine => "sine",
-                crate::expressive::SynthType::Square { .. } => "square",
-                crate::expressive::SynthType::Sawtooth => "sawtooth",
-                crate::expressive::SynthType::Triangle => "triangle",
-                crate::expressive::SynthType::Noise { .. } => "noise",
-                crate::expressive::SynthType::FM { .. } => "fm",
-                crate::expressive::SynthType::DX7FM { .. } => "dx7fm",
-                crate::expressive::SynthType::Granular { .. } => "granular",
-                crate::expressive::SynthType::Wavetable { .. } => "wavetable",
-                crate::expressive::SynthType::Kick { .. } => "kick",
-                crate::expressive::SynthType::Snare { .. } => "snare",
-                crate::expressive::SynthType::HiHat { .. } => "hihat",
-                crate::expressive::SynthType::Cymbal { .. } => "cymbal",
-                crate::expressive::SynthType::Swoosh { .. } => "swoosh",
-                crate::expressive::SynthType::Zap { .. } => "zap",
-                crate::expressive::SynthType::Chime { .. } => "chime",
-                crate::expressive::SynthType::Burst { .. } => "burst",
-                crate::expressive::SynthType::Pad { .. } => "pad",
-                crate::expressive::SynthType::Texture { .. } => "texture",
-                crate::expressive::SynthType::Drone { .. } => "drone",
-            }
-            .to_string(),
-        );
+        let synthesis_end_time = if !synthesis_events.is_empty() {
+            synthesis_events
+                .iter()
+                .map(|event| {
+                    Duration::from_secs_f64(event.start_time + event.note.duration.unwrap_or(1.0))
+                })
+                .max()
+                .unwrap_or(Duration::from_secs(1))
+        } else {
+            Duration::from_secs(0)
+        };
 
-        // Apply envelope parameters
-        note.synth_attack = Some(synth_params.envelope.attack);
-        note.synth_decay = Some(synth_params.envelope.decay);
-        note.synth_sustain = Some(synth_params.envelope.sustain);
-        note.synth_release = Some(synth_params.envelope.release);
-
-        // Apply amplitude
-        note.synth_amplitude = Some(synth_params.amplitude);
-
-        // Apply filter parameters if present
-        if let Some(filter) = &synth_params.filter {
-            note.synth_filter_type = Some(
-                match filter.filter_type {
-                    crate::expressive::FilterType::LowPass => "lowpass",
-                    crate::expressive::FilterType::HighPass => "highpass",
-                    crate::expressive::FilterType::BandPass => "bandpass",
-                }
-                .to_string(),
-            );
-            note.synth_filter_cutoff = Some(filter.cutoff);
-            note.synth_filter_resonance = Some(filter.resonance);
-        }
+        midi_end_time.max(r2d2_end_time).max(synthesis_end_time)
+    }
 
-        // Apply effects
-        for effect in &synth_params.effects {
-            match &effect.effect_type {
-                crate::expressive::EffectType::Reverb => {
-                    note.synth_reverb = Some(effect.intensity);
-                }
-                crate::expressive::EffectType::Chorus => {
-                    note.synth_chorus = Some(effect.intensity);
-                }
-                crate::expressive::EffectType::Delay { delay_time } => {
-                    note.synth_delay = Some(effect.intensity);
-                    note.synth_delay_time = Some(*delay_time);
-                }
-            }
+    /// One-note sequence auditioning a preset: `note` held for `duration` seconds with the
+    /// preset already applied. Unknown names fail with the closest matching preset names.
+    pub fn audition_sequence(
+        preset_library: &PresetLibrary,
+        effects_library: &EffectsPresetLibrary,
+        preset_name: &str,
+        note: u8,
+        duration: f64,
+    ) -> Result<SimpleSequence, String> {
+        if preset_library.load_preset(preset_name).is_none() {
+            let suggestions = preset_library.similar_preset_names(preset_name);
+            return Err(if suggestions.is_empty() {
+                format!("Preset '{}' not found", preset_name)
+            } else {
+                format!(
+                    "Preset '{}' not found. Did you mean: {}?",
+                    preset_name,
+                    suggestions.join(", ")
+                )
+            });
         }
 
-        // Apply synthesis-specific parameters based on synth type
-        match &synth_params.synth_type {
-            crate::expressive::SynthType::Square { pulse_width } => {
-                note.synth_pulse_width = Some(*pulse_width);
-            }
-            crate::expressive::SynthType::FM {
-                modulator_freq,
-                modulation_index,
-            } => {
-                note.synth_modulator_freq = Some(*modulator_freq);
-                note.synth_modulation_index = Some(*modulation_index);
-            }
-            crate::expressive::SynthType::Granular { grain_size, .. } => {
-                note.synth_grain_size = Some(*grain_size);
-            }
-            crate::expressive::SynthType::Texture { roughness, .. } => {
-                note.synth_texture_roughness = Some(*roughness);
-            }
-            _ => {} // Other synth types don't have specific parameters to set
+        let mut audition = crate::midi::SimpleNote {
+            note: Some(note),
+            velocity: Some(100),
+            start_time: Some(0.0),
+            duration: Some(duration),
+            preset_name: Some(preset_name.to_string()),
+            ..Default::default()
+        };
+        apply_preset_to_note(preset_library, effects_library, &mut audition)?;
+
+        let mut sequence = SimpleSequence::new();
+        sequence.notes.push(audition);
+        Ok(sequence)
+    }
+
+    /// Start playing a sequence of MIDI, R2D2 and synthesis notes and return its total
+    /// audible duration (content plus effect tails)
+    pub fn play_enhanced_mixed(&self, sequence: SimpleSequence) -> Result<Duration, String> {
+        tracing::info!(
+            "Playing enhanced mixed sequence with {} notes (pre-computed approach)",
+            sequence.notes.len()
+        );
+
+        if sequence.notes.is_empty() {
+            tracing::warn!("No notes to play - sequence is empty");
+            return Ok(Duration::ZERO);
         }
 
-        // Apply signature effects from preset
-        if note.effects.is_none() && !preset.signature_effects.is_empty() {
-            note.effects = Some(preset.signature_effects.clone());
+        // Normalizing needs the whole render up front, so play that render
+        let master_limiter = sequence.master_limiter;
+        if sequence.target_lufs.is_some() {
+            return self.play_rendered(Self::render_for_playback(sequence)?, master_limiter);
         }
 
-        tracing::info!("Applied preset '{}' to note", preset.name);
+        let (mut enhanced_source, total_time) =
+            Self::build_enhanced_source(&self.preset_library, &self.effects_library, sequence)?;
+        enhanced_source.activity = Some(PlaybackActivity::start());
+        enhanced_source.control = self.control.clone();
 
-        // Apply effects preset if specified
-        if let Some(effects_preset_name) = &note.effects_preset {
-            if let Some(effects) = self.effects_library.get_preset(effects_preset_name) {
-                // Merge with existing effects or replace
-                if let Some(existing_effects) = &mut note.effects {
-                    existing_effects.extend(effects.clone());
-                } else {
-                    note.effects = Some(effects.clone());
-                }
-                tracing::info!("Applied effects preset '{}' to note", effects_preset_name);
-            } else {
-                tracing::warn!("Effects preset '{}' not found", effects_preset_name);
-            }
+        tracing::info!("Created enhanced hybrid audio source, starting playback");
+
+        // Check sink status before playing
+        tracing::info!(
+            "Sink status - is_paused: {}, empty: {}",
+            self.sink.is_paused(),
+            self.sink.empty()
+        );
+
+        let config = SetupConfig::load().unwrap_or_default();
+        Self::set_master_gain(config.master_gain());
+        let preroll = config.audio_buffer();
+        if preroll.is_zero() {
+            self.sink
+                .append(MasterGain::new(enhanced_source, master_limiter));
+        } else {
+            // Master gain sits after the buffer so volume changes are heard immediately
+            self.sink.append(MasterGain::new(
+                BufferedSource::new(enhanced_source, config.audio_block_frames(), preroll),
+                master_limiter,
+            ));
         }
+        self.sink.play();
 
-        Ok(())
-    }
+        // Set volume to ensure it's audible
+        self.sink.set_volume(1.0);
 
-    /// Play an enhanced mixed sequence supporting MIDI, R2D2, and synthesis notes (pre-computed approach)
-    pub fn play_enhanced_mixed(&self, sequence: SimpleSequence) -> Result<(), String> {
         tracing::info!(
-            "Playing enhanced mixed sequence with {} notes (pre-computed approach)",
-            sequence.notes.len()
+            "Playback started (non-blocking) - volume: {}, duration: {:.2}s",
+            self.sink.volume(),
+            total_time.as_secs_f64()
         );
 
-        if sequence.notes.is_empty() {
-            tracing::warn!("No notes to play - sequence is empty");
-            return Ok(());
+        Ok(total_time)
+    }
+
+    /// Block until everything handed to the sink has played out, effect tails included.
+    /// Returns early once a panic stops the source.
+    pub fn wait_until_finished(&self) {
+        self.sink.sleep_until_end();
+    }
+
+    /// Start playing already rendered audio (e.g. rearranged slices) and return its duration.
+    /// Stop and panic reach it like any other playback, and `master_limiter` limits it
+    /// after the master gain as for sequences, so the audio should come unlimited from
+    /// `render_for_playback`.
+    pub fn play_rendered(
+        &self,
+        audio: RenderedAudio,
+        master_limiter: bool,
+    ) -> Result<Duration, String> {
+        if audio.samples.is_empty() {
+            return Err("No audio to play".to_string());
         }
+        let duration = audio.duration;
+        Self::set_master_gain(SetupConfig::load().unwrap_or_default().master_gain());
+        let mut source = RenderedSource::new(audio);
+        source.activity = Some(PlaybackActivity::start());
+        source.control = self.control.clone();
+        self.sink.append(MasterGain::new(source, master_limiter));
+        self.sink.play();
+        Ok(duration)
+    }
 
-        // Process each note and apply presets if specified
-        let mut processed_notes = Vec::new();
-        for mut note in sequence.notes {
-            // Apply preset configuration if present
-            if let Err(e) = self.apply_preset_to_note(&mut note) {
-                tracing::warn!("Failed to apply preset to note: {}", e);
-                // Continue with the note without preset - don't fail completely
-            }
+    /// Render a sequence offline to an audio file whose format follows the extension
+    /// (.wav, or .flac/.ogg/.opus with the `compressed-export` feature). Returns the
+    /// rendered duration, which for a seamless loop is the exact loop length. Needs no
+    /// audio device; missing directories are created.
+    pub fn render_to_file(
+        sequence: SimpleSequence,
+        path: &Path,
+        options: &RenderOptions,
+    ) -> Result<Duration, String> {
+        let format = ExportFormat::from_path(path)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create output directory {:?}: {}", dir, e))?;
+        }
+        let rendered = if options.seamless_loop {
+            looping::render_loop(sequence)?
+        } else {
+            Self::render_samples(sequence)?
+        };
 
-            // Validate effects if present
-            if let Err(e) = note.validate_effects() {
-                tracing::warn!("Invalid effects on note: {}", e);
-                // Continue with the note without effects - don't fail completely
-                note.effects = None;
-                note.effects_preset = None;
-            }
+        export::write_audio(
+            path,
+            format,
+            &rendered.samples,
+            rendered.channels,
+            rendered.sample_rate,
+            options.dither,
+        )?;
+        tracing::info!(
+            "Rendered {:.2}s of audio to {:?} as {:?}",
+            rendered.duration.as_secs_f64(),
+            path,
+            format
+        );
+        Ok(rendered.duration)
+    }
 
-            // Convert musical_time to start_time if present
-            if note.start_time.is_none()
-                && let Some(musical_time) = &note.musical_time
-            {
-                // Use the MusicalTime::to_seconds method
-                // Assuming 4/4 time signature (4 beats per bar) and 480 ticks per beat
-                let tempo = sequence.tempo;
-                note.start_time = Some(musical_time.to_seconds(tempo, 4, 480));
+    /// Render a sequence offline, without an audio device, to interleaved samples
+    pub fn render_samples(sequence: SimpleSequence) -> Result<RenderedAudio, String> {
+        let (target_lufs, master_limiter) = (sequence.target_lufs, sequence.master_limiter);
+        let mut rendered = Self::render_mix(sequence)?;
+        Self::master_render(&mut rendered, target_lufs, master_limiter);
+        Ok(rendered)
+    }
 
-                tracing::debug!(
-                    "Converted musical_time {{bar:{}, beat:{}, tick:{}}} to start_time={:.3}s at tempo={}",
-                    musical_time.bar,
-                    musical_time.beat,
-                    musical_time.tick,
-                    note.start_time.unwrap(),
-                    tempo
-                );
-            }
+    /// Render a sequence offline for `play_rendered`: normalized like `render_samples` but
+    /// not yet limited, as playback limits it once, after the master gain
+    pub fn render_for_playback(sequence: SimpleSequence) -> Result<RenderedAudio, String> {
+        let target_lufs = sequence.target_lufs;
+        let mut rendered = Self::render_mix(sequence)?;
+        Self::master_render(&mut rendered, target_lufs, false);
+        Ok(rendered)
+    }
 
-            // Convert musical_duration to duration if present
-            if note.duration.is_none()
-                && let Some(ref musical_duration) = note.musical_duration
-            {
-                let tempo = sequence.tempo;
-                let seconds_per_beat = 60.0 / tempo as f64;
-
-                // Convert musical duration to seconds
-                let duration_secs = match musical_duration {
-                    crate::midi::MusicalDuration::Bars(bars) => bars * 4.0 * seconds_per_beat, // 4 beats per bar in 4/4 time
-                    crate::midi::MusicalDuration::Beats(beats) => beats * seconds_per_beat,
-                    crate::midi::MusicalDuration::Seconds(secs) => *secs, // Already in seconds
-                    crate::midi::MusicalDuration::NoteValue(value) => {
-                        let duration_in_beats = match value {
-                            crate::midi::NoteValue::Whole => 4.0,
-                            crate::midi::NoteValue::Half => 2.0,
-                            crate::midi::NoteValue::Quarter => 1.0,
-                            crate::midi::NoteValue::Eighth => 0.5,
-                            crate::midi::NoteValue::Sixteenth => 0.25,
-                            crate::midi::NoteValue::Triplet => 2.0 / 3.0, // Triplet quarter note
-                        };
-                        duration_in_beats * seconds_per_beat
-                    }
-                };
+    /// Render a sequence's mix offline, before loudness normalization and the limiter
+    pub(crate) fn render_mix(sequence: SimpleSequence) -> Result<RenderedAudio, String> {
+        if sequence.notes.is_empty() {
+            return Err("Cannot render an empty sequence".to_string());
+        }
 
-                note.duration = Some(duration_secs);
+        let (source, duration) = Self::build_enhanced_source(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )?;
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        let samples: Vec<f32> = source.collect();
+        // Shorter than the estimate when the tail dies away early
+        let frames = samples.len() / channels as usize;
+        Ok(RenderedAudio {
+            samples,
+            channels,
+            sample_rate,
+            duration: duration.min(Duration::from_secs_f64(frames as f64 / sample_rate as f64)),
+        })
+    }
 
-                tracing::debug!(
-                    "Converted musical_duration {:?} to duration={:.3}s at tempo={}",
-                    musical_duration,
-                    note.duration.unwrap(),
-                    tempo
+    /// Normalize a render to `target_lufs`, if set, then run the master limiter over it
+    pub(crate) fn master_render(
+        rendered: &mut RenderedAudio,
+        target_lufs: Option<f32>,
+        master_limiter: bool,
+    ) {
+        let (channels, sample_rate) = (rendered.channels, rendered.sample_rate);
+        // Normalization measures this render itself; the limiter follows its gain
+        let gain = match target_lufs {
+            Some(target_lufs) => {
+                let gain = analysis::loudness_normalization_gain(
+                    &rendered.samples,
+                    sample_rate,
+                    target_lufs,
+                );
+                tracing::info!(
+                    "Normalizing to {} LUFS with {:+.1} dB of gain",
+                    target_lufs,
+                    20.0 * gain.log10()
                 );
+                gain
+            }
+            None => 1.0,
+        };
+        let mut limiter = master_limiter.then(|| MasterLimiter::new(sample_rate as f32));
+        for frame in rendered.samples.chunks_exact_mut(channels.max(1) as usize) {
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
+            if let Some(limiter) = &mut limiter {
+                limiter.process(frame);
             }
-
-            processed_notes.push(note);
         }
+    }
+
+    /// Write the sequence's MIDI notes to a format 1 Standard MIDI File (480 PPQ) with the
+    /// sequence's tempo and tempo changes, for editing in a DAW. Timing, channel defaults and program changes
+    /// are resolved exactly as for playback. R2D2, synthesis and preset notes have no
+    /// General MIDI equivalent and are skipped; both counts are of resolved notes, so a
+    /// roll counts once per hit. Missing directories are created.
+    pub fn export_smf(sequence: &SimpleSequence, path: &Path) -> Result<SmfExport, String> {
+        let notes = {
+            let _seed_scope = MasterSeedScope::new(sequence.master_seed);
+            resolve_notes(
+                &PresetLibrary::new(),
+                &EffectsPresetLibrary::new(),
+                sequence,
+            )?
+        };
+        let midi_notes: Vec<MidiNote> = notes
+            .iter()
+            .filter(|note| note.note_type != "r2d2" && !note.is_synthesis())
+            .filter_map(|note| {
+                let mut midi_note = MidiNote::new(
+                    note.note?,
+                    note.velocity.unwrap_or(80),
+                    note.channel,
+                    Duration::from_secs_f64(note.start_time.unwrap_or(0.0)),
+                    Duration::from_secs_f64(note.duration.unwrap_or(1.0)),
+                );
+                midi_note.instrument = note.instrument;
+                Some(midi_note)
+            })
+            .collect();
 
-        // For the initial implementation, apply all effects globally to avoid MIDI channel separation complexity
-        let mut all_effects = Vec::new();
+        let tempo_map = TempoMap::new(
+            sequence.tempo,
+            sequence.beats_per_bar,
+            &sequence.tempo_changes,
+        );
+        parser::write_smf(&midi_notes, &tempo_map, sequence.beats_per_bar, path)?;
+        let export = SmfExport {
+            exported: midi_notes.len(),
+            skipped: notes.len() - midi_notes.len(),
+        };
+        tracing::info!(
+            "Exported {} MIDI notes to {:?}, skipped {} others",
+            export.exported,
+            path,
+            export.skipped
+        );
+        Ok(export)
+    }
+
+    /// Resolve presets and timing for a sequence and pre-compute its audio source.
+    /// Returns the source together with its total duration (including effect tails).
+    fn build_enhanced_source(
+        preset_library: &PresetLibrary,
+        effects_library: &EffectsPresetLibrary,
+        sequence: SimpleSequence,
+    ) -> Result<(EnhancedHybridAudioSource, Duration), String> {
+        // All random choices (presets, noise) draw from the master seed while building
+        let _seed_scope = MasterSeedScope::new(sequence.master_seed);
+
+        let processed_notes = resolve_notes(preset_library, effects_library, &sequence)?;
+
+        // MIDI effects are chained per channel, with the settings of each note that carries
+        // them taking over from its start; R2D2 and synthesis each share one fixed chain
+        let mut channel_settings: std::collections::HashMap<
+            u8,
+            Vec<(f64, Vec<crate::midi::EffectConfig>)>,
+        > = std::collections::HashMap::new();
         let mut r2d2_effects = Vec::new();
         let mut synthesis_effects = Vec::new();
 
@@ -364,23 +736,19 @@ impl MidiPlayer {
                 } else if note.is_synthesis() {
                     // Synthesis effects
                     synthesis_effects.extend(effects.clone());
-                } else {
-                    // MIDI effects - for now, collect all MIDI effects together
-                    all_effects.extend(effects.clone());
+                } else if !effects.is_empty() {
+                    // MIDI effects go on the note's own channel
+                    channel_settings
+                        .entry(note.channel)
+                        .or_default()
+                        .push((note.start_time.unwrap_or(0.0), effects.clone()));
                 }
             }
         }
 
-        // Put all MIDI effects on channel 0 for simplicity
-        let mut channel_effects: std::collections::HashMap<u8, Vec<crate::midi::EffectConfig>> =
-            std::collections::HashMap::new();
-        if !all_effects.is_empty() {
-            channel_effects.insert(0, all_effects);
-        }
-
         tracing::info!(
             "Collected effects for {} MIDI channels, R2D2 effects: {}, synthesis effects: {}",
-            channel_effects.len(),
+            channel_settings.len(),
             r2d2_effects.len(),
             synthesis_effects.len()
         );
@@ -429,6 +797,7 @@ impl MidiPlayer {
                         (200.0, 800.0)
                     },
                     context: note.r2d2_context,
+                    voice: note.r2d2_voice,
                 };
 
                 r2d2_events.push(R2D2Event {
@@ -441,9 +810,18 @@ impl MidiPlayer {
                     return Err(format!("Invalid synthesis note: {}", e));
                 }
 
+                // The note's own pan wins; otherwise place it by register when auto-pan
+                // is enabled
+                let pan = match note.stereo_pan() {
+                    Some(pan) => pan,
+                    None if sequence.auto_pan_by_pitch => note.pitch_pan().unwrap_or(0.0),
+                    None => 0.0,
+                };
+
                 // Convert SimpleNote to SynthEvent
                 synthesis_events.push(SynthEvent {
                     start_time: note.start_time.unwrap_or(0.0),
+                    pan,
                     note,
                 });
             } else {
@@ -453,6 +831,14 @@ impl MidiPlayer {
                     let start_time_secs = note.start_time.unwrap_or(0.0);
                     let duration_secs = note.duration.unwrap_or(1.0);
 
+                    // Auto-pan MIDI notes through the channel pan controller (0-127)
+                    let pan = match note.pitch_pan() {
+                        Some(pan) if sequence.auto_pan_by_pitch => {
+                            Some((64.0 + pan * 63.0).round().clamp(0.0, 127.0) as u8)
+                        }
+                        _ => note.pan,
+                    };
+
                     tracing::info!(
                         "🎵 MIDI Note scheduled: note={}, velocity={}, start_time={:.3}s, duration={:.3}s, instrument={:?}",
                         note_val,
@@ -472,7 +858,7 @@ impl MidiPlayer {
                         reverb: note.reverb,
                         chorus: note.chorus,
                         volume: note.volume,
-                        pan: note.pan,
+                        pan,
                         balance: note.balance,
                         expression: note.expression,
                         sustain: note.sustain,
@@ -481,44 +867,11 @@ impl MidiPlayer {
             }
         }
 
-        // Calculate total playback time
-        let midi_end_time = if !midi_notes.is_empty() {
-            midi_notes
-                .iter()
-                .map(|note| note.start_time + note.duration)
-                .max()
-                .unwrap_or(Duration::from_secs(1))
-        } else {
-            Duration::from_secs(0)
-        };
-
-        let r2d2_end_time = if !r2d2_events.is_empty() {
-            r2d2_events
-                .iter()
-                .map(|event| {
-                    Duration::from_secs_f64(event.start_time + event.expression.duration as f64)
-                })
-                .max()
-                .unwrap_or(Duration::from_secs(1))
-        } else {
-            Duration::from_secs(0)
-        };
-
-        let synthesis_end_time = if !synthesis_events.is_empty() {
-            synthesis_events
-                .iter()
-                .map(|event| {
-                    Duration::from_secs_f64(event.start_time + event.note.duration.unwrap_or(1.0))
-                })
-                .max()
-                .unwrap_or(Duration::from_secs(1))
-        } else {
-            Duration::from_secs(0)
-        };
-
-        let note_end_time = midi_end_time.max(r2d2_end_time).max(synthesis_end_time);
-        let tail_time = Self::calculate_tail_time(&midi_notes);
-        let total_time = note_end_time + tail_time;
+        let total_time = Self::audible_duration(&midi_notes, &r2d2_events, &synthesis_events);
+        let notes_end = Self::note_end_time(&midi_notes, &r2d2_events, &synthesis_events);
+        // After the tail estimate, which looks at every note's instrument
+        let program_changes = Self::merge_redundant_program_changes(&mut midi_notes);
+        tracing::debug!("{} program changes after merging repeats", program_changes);
 
         tracing::info!(
             "Enhanced mixed sequence: {} MIDI notes, {} R2D2 events, {} synthesis events, total time: {:.2}s",
@@ -528,40 +881,266 @@ impl MidiPlayer {
             total_time.as_secs_f64()
         );
 
+        // MIDI drums on the glue bus get their own synthesizer
+        let drum_notes = if sequence.drum_glue {
+            let (drums, others) = midi_notes
+                .into_iter()
+                .partition(|note| note.channel == DRUM_CHANNEL);
+            midi_notes = others;
+            drums
+        } else {
+            Vec::new()
+        };
+        // Every synthesizer of the render plays one SoundFont, loaded only when MIDI notes
+        // need it: the channel and drum buses borrow it in turn, then the live one keeps it
+        let mut soundfont = if midi_notes.is_empty() && drum_notes.is_empty() {
+            None
+        } else {
+            Some(load_soundfont()?)
+        };
         // Create enhanced hybrid audio source with per-channel effects
-        let enhanced_source = EnhancedHybridAudioSource::new(
+        let mut enhanced_source = EnhancedHybridAudioSource::new(
             midi_notes,
             r2d2_events,
             synthesis_events,
             total_time,
-            channel_effects,
+            channel_settings,
             r2d2_effects,
             synthesis_effects,
+            &mut soundfont,
         )
         .map_err(|e| format!("Failed to create enhanced hybrid audio source: {}", e))?;
+        if sequence.drum_glue {
+            enhanced_source.glue_drums(drum_notes, &mut soundfont)?;
+        }
+        if let Some(soundfont) = soundfont {
+            enhanced_source.attach_soundfont(soundfont);
+        }
+        enhanced_source.set_tail_cutoff(notes_end, sequence.tail_cutoff_db);
 
-        tracing::info!("Created enhanced hybrid audio source, starting playback");
+        Ok((enhanced_source, total_time))
+    }
+}
 
-        // Check sink status before playing
-        tracing::info!(
-            "Sink status - is_paused: {}, empty: {}",
-            self.sink.is_paused(),
-            self.sink.empty()
-        );
+/// Snapshot of the audio engine for debugging stuck or silent playback
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineStatus {
+    /// Whether any playback is still producing audio
+    pub playing: bool,
+    /// Playbacks still producing audio
+    pub playbacks: usize,
+    /// Voices sounding across those playbacks (synthesis, R2D2 and held MIDI notes)
+    pub voices: usize,
+    pub master_gain: f32,
+    /// Whether a SoundFont is found for MIDI notes (it is loaded on every play)
+    pub soundfont_available: bool,
+}
 
-        self.sink.append(enhanced_source);
-        self.sink.play();
+/// Counts a device playback in the engine status while alive. Sources drop it as soon as
+/// they finish, so offline renders, which never get one, are not reported.
+struct PlaybackActivity {
+    voices: usize,
+}
 
-        // Set volume to ensure it's audible
-        self.sink.set_volume(1.0);
+impl PlaybackActivity {
+    fn start() -> Self {
+        ACTIVE_PLAYBACKS.fetch_add(1, Ordering::SeqCst);
+        PlaybackActivity { voices: 0 }
+    }
 
-        tracing::info!(
-            "Playback started (non-blocking) - volume: {}, duration: {:.2}s",
-            self.sink.volume(),
-            total_time.as_secs_f64()
-        );
+    fn set_voices(&mut self, voices: usize) {
+        if voices > self.voices {
+            ACTIVE_VOICES.fetch_add(voices - self.voices, Ordering::SeqCst);
+        } else {
+            ACTIVE_VOICES.fetch_sub(self.voices - voices, Ordering::SeqCst);
+        }
+        self.voices = voices;
+    }
+}
 
-        Ok(())
+impl Drop for PlaybackActivity {
+    fn drop(&mut self) {
+        ACTIVE_VOICES.fetch_sub(self.voices, Ordering::SeqCst);
+        ACTIVE_PLAYBACKS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Plays pre-rendered audio until it ends, fades out after a stop, or a panic is requested
+struct RenderedSource {
+    samples: std::vec::IntoIter<f32>,
+    channels: u16,
+    sample_rate: u32,
+    duration: Duration,
+    panic_generation: u64,
+    stop_generation: u64,
+    /// Fade-out in progress after a stop: (samples remaining, total samples)
+    fade_out: Option<(usize, usize)>,
+    control: PlaybackControl,
+    activity: Option<PlaybackActivity>,
+}
+
+impl RenderedSource {
+    fn new(audio: RenderedAudio) -> Self {
+        RenderedSource {
+            samples: audio.samples.into_iter(),
+            channels: audio.channels,
+            sample_rate: audio.sample_rate,
+            duration: audio.duration,
+            panic_generation: MidiPlayer::panic_generation(),
+            stop_generation: MidiPlayer::stop_generation(),
+            fade_out: None,
+            control: PlaybackControl::default(),
+            activity: None,
+        }
+    }
+}
+
+impl Iterator for RenderedSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fade_out.is_none()
+            && let Some(fade) = self.control.stop_fade(self.stop_generation)
+        {
+            let frames = (fade.as_secs_f64() * self.sample_rate as f64) as usize;
+            let samples = frames * self.channels as usize;
+            self.fade_out = Some((samples, samples));
+        }
+
+        let sample = if MidiPlayer::panic_generation() != self.panic_generation
+            || self.control.panic_requested()
+        {
+            None
+        } else {
+            match &mut self.fade_out {
+                Some((0, _)) => None,
+                // Ramp linearly to zero over the stop fade
+                Some((remaining, total)) => {
+                    *remaining -= 1;
+                    let gain = *remaining as f32 / *total as f32;
+                    self.samples.next().map(|sample| sample * gain)
+                }
+                None => self.samples.next(),
+            }
+        };
+        if sample.is_none() {
+            self.activity = None;
+        }
+        sample
+    }
+}
+
+impl Source for RenderedSource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+}
+
+/// Brickwall limiter on the final mix. The gain drops at once to hold any peak at the
+/// ceiling, all channels together, and recovers exponentially afterwards.
+struct MasterLimiter {
+    gain: f32,
+    release: f32,
+}
+
+impl MasterLimiter {
+    fn new(sample_rate: f32) -> Self {
+        MasterLimiter {
+            gain: 1.0,
+            release: (-1000.0 / (LIMITER_RELEASE_MS * sample_rate)).exp(),
+        }
+    }
+
+    /// Limit one interleaved frame in place
+    fn process(&mut self, frame: &mut [f32]) {
+        self.gain = 1.0 - (1.0 - self.gain) * self.release;
+        let peak = frame
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        if peak * self.gain > LIMITER_CEILING {
+            self.gain = LIMITER_CEILING / peak;
+        }
+        for sample in frame {
+            *sample *= self.gain;
+        }
+    }
+}
+
+/// Scales a source by the live master gain (`MidiPlayer::set_master_gain`), then runs
+/// the master limiter, if any, so that no gain setting can push playback into clipping.
+/// This is the last stage before the sink.
+struct MasterGain<S> {
+    source: S,
+    limiter: Option<MasterLimiter>,
+    // Current frame, already scaled and limited, and the next sample in it to emit
+    frame: Vec<f32>,
+    position: usize,
+}
+
+impl<S: Source<Item = f32>> MasterGain<S> {
+    fn new(source: S, master_limiter: bool) -> Self {
+        let limiter = master_limiter.then(|| MasterLimiter::new(source.sample_rate() as f32));
+        MasterGain {
+            source,
+            limiter,
+            frame: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for MasterGain<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.frame.len() {
+            let gain = MidiPlayer::master_gain();
+            let channels = self.source.channels() as usize;
+            self.frame.clear();
+            self.frame.extend(
+                self.source
+                    .by_ref()
+                    .take(channels)
+                    .map(|sample| sample * gain),
+            );
+            if let Some(limiter) = &mut self.limiter {
+                limiter.process(&mut self.frame);
+            }
+            self.position = 0;
+        }
+        let sample = self.frame.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl<S: Source<Item = f32>> Source for MasterGain<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
     }
 }
 
@@ -635,23 +1214,65 @@ pub struct OxiSynthSource {
     channel_sustain: std::collections::HashMap<u8, u8>,     // channel -> current sustain
 }
 
-impl OxiSynthSource {
-    pub fn new(notes: Vec<MidiNote>, total_duration: Duration) -> Result<Self, String> {
-        let sample_rate = 44100;
+/// Find and parse the SoundFont. It is large, so a render loads it once and passes it
+/// from synthesizer to synthesizer (`Synth::remove_font` hands it back).
+fn load_soundfont() -> Result<SoundFont, String> {
+    let soundfont_path = find_soundfont()?;
+    let mut soundfont_file = fs::File::open(&soundfont_path)
+        .map_err(|e| format!("Failed to open SoundFont file: {}", e))?;
 
-        // Find and load the SoundFont
-        let soundfont_path = find_soundfont()?;
-        let mut soundfont_file = fs::File::open(&soundfont_path)
-            .map_err(|e| format!("Failed to open SoundFont file: {}", e))?;
+    let soundfont = SoundFont::load(&mut soundfont_file)
+        .map_err(|e| format!("Failed to parse SoundFont: {}", e))?;
 
-        let soundfont = SoundFont::load(&mut soundfont_file)
-            .map_err(|e| format!("Failed to parse SoundFont: {}", e))?;
+    tracing::info!("Loaded SoundFont from: {:?}", soundfont_path);
+    Ok(soundfont)
+}
 
-        // Create synthesizer
+impl OxiSynthSource {
+    /// Render `notes` on a synthesizer of their own, adding every frame scaled by `gain`
+    /// into `left` and `right`. The synthesizer borrows `soundfont` and hands it back once
+    /// the notes are rendered.
+    fn render_into(
+        soundfont: &mut Option<SoundFont>,
+        notes: Vec<MidiNote>,
+        total_duration: Duration,
+        gain: f32,
+        left: &mut [f32],
+        right: &mut [f32],
+    ) -> Result<(), String> {
+        let font = soundfont.take().ok_or("SoundFont is not loaded")?;
         let mut synth = Synth::default();
-        synth.add_font(soundfont, true);
+        let font_id = synth.add_font(font, true);
+        let mut source = Self::from_synth(synth, notes, total_duration);
+        for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+            if source.is_finished() {
+                break;
+            }
+            let (frame_left, frame_right) = source.next_frame();
+            *left += frame_left * gain;
+            *right += frame_right * gain;
+        }
+        *soundfont = source.synth.remove_font(font_id, false);
+        Ok(())
+    }
 
-        tracing::info!("Loaded SoundFont from: {:?}", soundfont_path);
+    /// Create a source around an already configured synthesizer
+    fn from_synth(mut synth: Synth, notes: Vec<MidiNote>, total_duration: Duration) -> Self {
+        let sample_rate = 44100;
+
+        // OxiSynth starts voices on its 64-frame blocks. Rendering the partial block ahead
+        // of the first note (silent, and dropped) puts a block boundary on that note's
+        // sample, so it sounds together with synthesized notes that start with it.
+        let first_start = notes
+            .iter()
+            .map(|note| sample_index(note.start_time.as_secs_f64(), sample_rate))
+            .min();
+        let lead = first_start.map_or(0, |start| {
+            (OXISYNTH_BLOCK - start % OXISYNTH_BLOCK) % OXISYNTH_BLOCK
+        });
+        for _ in 0..lead {
+            synth.read_next();
+        }
 
         // Use the provided total duration which includes tail time
         let final_duration = total_duration.max(Duration::from_secs(1));
@@ -664,7 +1285,7 @@ impl OxiSynthSource {
             final_duration
         );
 
-        Ok(Self {
+        Self {
             synth,
             notes,
             sample_rate,
@@ -684,259 +1305,309 @@ impl OxiSynthSource {
             channel_balance: std::collections::HashMap::new(),
             channel_expression: std::collections::HashMap::new(),
             channel_sustain: std::collections::HashMap::new(),
-        })
+        }
     }
 
-    fn process_audio_chunk(&mut self) {
-        // Handle note on/off events for this chunk
-        for note in &self.notes {
-            let note_start_sample =
-                (note.start_time.as_secs_f32() * self.sample_rate as f32) as u32;
-            let note_end_sample =
-                ((note.start_time + note.duration).as_secs_f32() * self.sample_rate as f32) as u32;
-
-            let current_sample_u32 = self.current_sample as u32;
-            let chunk_end = current_sample_u32 + self.buffer_size as u32;
-
-            // Check if we should start this note in this chunk
-            if note_start_sample >= current_sample_u32 && note_start_sample < chunk_end {
-                let key = (note_start_sample, note.note);
-                if !self.playing_notes.contains_key(&key) {
-                    // Handle drums (channel 9) specially
-                    if note.channel == 9 {
-                        // For drums, force bank select 128 (percussion) if not already set
-                        let current_bank = self.channel_instruments.get(&note.channel).copied();
-                        if current_bank != Some(128) {
-                            // Bank Select MSB (Controller 0) = 128 for drums (percussion bank)
-                            let bank_select_msb = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 0,    // Bank Select MSB
-                                value: 128, // Percussion Bank
-                            };
-                            let _ = self.synth.send_event(bank_select_msb);
-
-                            // Bank Select LSB (Controller 32) = 0 for standard percussion
-                            let bank_select_lsb = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 32, // Bank Select LSB
-                                value: 0, // Standard Bank LSB
-                            };
-                            let _ = self.synth.send_event(bank_select_lsb);
-
-                            // Program Change to Standard Kit (program 0 in percussion bank)
-                            let program_change = MidiEvent::ProgramChange {
-                                channel: note.channel,
-                                program_id: 0, // Standard Kit in percussion bank
-                            };
-                            let _ = self.synth.send_event(program_change);
-                            self.channel_instruments.insert(note.channel, 128);
-                            tracing::info!(
-                                "🥁 Drum Setup: channel 9 -> percussion bank 128:0, standard kit (Bank MSB=128, LSB=0, Program=0)"
-                            );
-                        }
-                    } else {
-                        // Check if we need to send a program change for this channel
-                        if let Some(instrument) = note.instrument {
-                            let current_instrument =
-                                self.channel_instruments.get(&note.channel).copied();
-                            if current_instrument != Some(instrument) {
-                                let program_change = MidiEvent::ProgramChange {
-                                    channel: note.channel,
-                                    program_id: instrument,
-                                };
-                                let _ = self.synth.send_event(program_change);
-                                self.channel_instruments.insert(note.channel, instrument);
-                                tracing::debug!(
-                                    "Program Change: channel {} -> instrument {}",
-                                    note.channel,
-                                    instrument
-                                );
-                            }
-                        }
-                    }
-
-                    // Check if we need to send reverb control change for this channel
-                    if let Some(reverb) = note.reverb {
-                        let current_reverb = self.channel_reverb.get(&note.channel).copied();
-                        if current_reverb != Some(reverb) {
-                            let reverb_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 91, // Effects1Depth (Reverb)
-                                value: reverb,
-                            };
-                            let _ = self.synth.send_event(reverb_cc);
-                            self.channel_reverb.insert(note.channel, reverb);
-                            tracing::debug!(
-                                "Reverb CC: channel {} -> depth {}",
-                                note.channel,
-                                reverb
-                            );
-                        }
-                    }
-
-                    // Check if we need to send chorus control change for this channel
-                    if let Some(chorus) = note.chorus {
-                        let current_chorus = self.channel_chorus.get(&note.channel).copied();
-                        if current_chorus != Some(chorus) {
-                            let chorus_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 93, // Effects3Depth (Chorus)
-                                value: chorus,
-                            };
-                            let _ = self.synth.send_event(chorus_cc);
-                            self.channel_chorus.insert(note.channel, chorus);
-                            tracing::debug!(
-                                "Chorus CC: channel {} -> depth {}",
-                                note.channel,
-                                chorus
-                            );
-                        }
-                    }
+    /// Send All-Notes-Off and All-Sound-Off on every channel and drop all scheduled notes
+    pub fn panic(&mut self) {
+        for channel in 0..16 {
+            let _ = self.synth.send_event(MidiEvent::AllNotesOff { channel });
+            let _ = self.synth.send_event(MidiEvent::AllSoundOff { channel });
+        }
+        self.notes.clear();
+        self.playing_notes.clear();
+        self.left_buffer.fill(0.0);
+        self.right_buffer.fill(0.0);
+        self.buffer_pos = self.buffer_size;
+        tracing::info!("OxiSynth panic: all notes and sound off on every channel");
+    }
 
-                    // Check if we need to send volume control change for this channel
-                    if let Some(volume) = note.volume {
-                        let current_volume = self.channel_volume.get(&note.channel).copied();
-                        if current_volume != Some(volume) {
-                            let volume_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 7, // Channel Volume
-                                value: volume,
-                            };
-                            let _ = self.synth.send_event(volume_cc);
-                            self.channel_volume.insert(note.channel, volume);
-                            tracing::debug!(
-                                "Volume CC: channel {} -> volume {}",
-                                note.channel,
-                                volume
-                            );
-                        }
-                    }
+    /// Number of notes currently held on
+    pub fn active_note_count(&self) -> usize {
+        self.playing_notes.len()
+    }
 
-                    // Check if we need to send pan control change for this channel
-                    if let Some(pan) = note.pan {
-                        let current_pan = self.channel_pan.get(&note.channel).copied();
-                        if current_pan != Some(pan) {
-                            let pan_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 10, // Pan
-                                value: pan,
-                            };
-                            let _ = self.synth.send_event(pan_cc);
-                            self.channel_pan.insert(note.channel, pan);
-                            tracing::debug!("Pan CC: channel {} -> pan {}", note.channel, pan);
-                        }
-                    }
+    /// First and last sample of a note, on the same sample grid as synthesized and R2D2 events
+    fn note_span(&self, note: &MidiNote) -> (u32, u32) {
+        (
+            sample_index(note.start_time.as_secs_f64(), self.sample_rate),
+            sample_index(
+                (note.start_time + note.duration).as_secs_f64(),
+                self.sample_rate,
+            ),
+        )
+    }
 
-                    // Check if we need to send balance control change for this channel
-                    if let Some(balance) = note.balance {
-                        let current_balance = self.channel_balance.get(&note.channel).copied();
-                        if current_balance != Some(balance) {
-                            let balance_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 8, // Balance
-                                value: balance,
-                            };
-                            let _ = self.synth.send_event(balance_cc);
-                            self.channel_balance.insert(note.channel, balance);
-                            tracing::debug!(
-                                "Balance CC: channel {} -> balance {}",
-                                note.channel,
-                                balance
-                            );
-                        }
-                    }
+    /// Send the controller changes a note needs, then its note-on
+    fn note_on(&mut self, index: usize) {
+        let note = &self.notes[index];
+        let (note_start_sample, _) = self.note_span(note);
+        let key = (note_start_sample, note.note);
+        if self.playing_notes.contains_key(&key) {
+            return;
+        }
 
-                    // Check if we need to send expression control change for this channel
-                    if let Some(expression) = note.expression {
-                        let current_expression =
-                            self.channel_expression.get(&note.channel).copied();
-                        if current_expression != Some(expression) {
-                            let expression_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 11, // Expression Controller
-                                value: expression,
-                            };
-                            let _ = self.synth.send_event(expression_cc);
-                            self.channel_expression.insert(note.channel, expression);
-                            tracing::debug!(
-                                "Expression CC: channel {} -> expression {}",
-                                note.channel,
-                                expression
-                            );
-                        }
-                    }
+        // Handle drums (channel 9) specially
+        if note.channel == 9 {
+            // For drums, force bank select 128 (percussion) if not already set
+            let current_bank = self.channel_instruments.get(&note.channel).copied();
+            if current_bank != Some(128) {
+                // Bank Select MSB (Controller 0) = 128 for drums (percussion bank)
+                let bank_select_msb = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 0,    // Bank Select MSB
+                    value: 128, // Percussion Bank
+                };
+                let _ = self.synth.send_event(bank_select_msb);
 
-                    // Check if we need to send sustain control change for this channel
-                    if let Some(sustain) = note.sustain {
-                        let current_sustain = self.channel_sustain.get(&note.channel).copied();
-                        if current_sustain != Some(sustain) {
-                            let sustain_cc = MidiEvent::ControlChange {
-                                channel: note.channel,
-                                ctrl: 64, // Damper Pedal (Sustain)
-                                value: sustain,
-                            };
-                            let _ = self.synth.send_event(sustain_cc);
-                            self.channel_sustain.insert(note.channel, sustain);
-                            tracing::debug!(
-                                "Sustain CC: channel {} -> sustain {}",
-                                note.channel,
-                                sustain
-                            );
-                        }
-                    }
+                // Bank Select LSB (Controller 32) = 0 for standard percussion
+                let bank_select_lsb = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 32, // Bank Select LSB
+                    value: 0, // Standard Bank LSB
+                };
+                let _ = self.synth.send_event(bank_select_lsb);
 
-                    let midi_event = MidiEvent::NoteOn {
-                        channel: note.channel,
-                        key: note.note,
-                        vel: note.velocity,
-                    };
-                    let _ = self.synth.send_event(midi_event);
-                    self.playing_notes.insert(key, note.duration);
-                    if note.channel == 9 {
-                        tracing::info!(
-                            "🥁 DRUM Note ON: {} (velocity={}) channel {} at sample {}",
-                            note.note,
-                            note.velocity,
-                            note.channel,
-                            note_start_sample
-                        );
-                    } else {
-                        tracing::debug!(
-                            "Note ON: {} channel {} at sample {}",
-                            note.note,
-                            note.channel,
-                            note_start_sample
-                        );
-                    }
-                }
+                // Program Change to Standard Kit (program 0 in percussion bank)
+                let program_change = MidiEvent::ProgramChange {
+                    channel: note.channel,
+                    program_id: 0, // Standard Kit in percussion bank
+                };
+                let _ = self.synth.send_event(program_change);
+                self.channel_instruments.insert(note.channel, 128);
+                tracing::info!(
+                    "🥁 Drum Setup: channel 9 -> percussion bank 128:0, standard kit (Bank MSB=128, LSB=0, Program=0)"
+                );
             }
-
-            // Check if we should end this note in this chunk
-            if note_end_sample >= current_sample_u32 && note_end_sample < chunk_end {
-                let key = (note_start_sample, note.note);
-                if self.playing_notes.remove(&key).is_some() {
-                    let midi_event = MidiEvent::NoteOff {
+        } else {
+            // Check if we need to send a program change for this channel
+            if let Some(instrument) = note.instrument {
+                let current_instrument = self.channel_instruments.get(&note.channel).copied();
+                if current_instrument != Some(instrument) {
+                    let program_change = MidiEvent::ProgramChange {
                         channel: note.channel,
-                        key: note.note,
+                        program_id: instrument,
                     };
-                    let _ = self.synth.send_event(midi_event);
+                    let _ = self.synth.send_event(program_change);
+                    self.channel_instruments.insert(note.channel, instrument);
                     tracing::debug!(
-                        "Note OFF: {} channel {} at sample {}",
-                        note.note,
+                        "Program Change: channel {} -> instrument {}",
                         note.channel,
-                        note_end_sample
+                        instrument
                     );
                 }
             }
         }
 
+        // Check if we need to send reverb control change for this channel
+        if let Some(reverb) = note.reverb {
+            let current_reverb = self.channel_reverb.get(&note.channel).copied();
+            if current_reverb != Some(reverb) {
+                let reverb_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 91, // Effects1Depth (Reverb)
+                    value: reverb,
+                };
+                let _ = self.synth.send_event(reverb_cc);
+                self.channel_reverb.insert(note.channel, reverb);
+                tracing::debug!("Reverb CC: channel {} -> depth {}", note.channel, reverb);
+            }
+        }
+
+        // Check if we need to send chorus control change for this channel
+        if let Some(chorus) = note.chorus {
+            let current_chorus = self.channel_chorus.get(&note.channel).copied();
+            if current_chorus != Some(chorus) {
+                let chorus_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 93, // Effects3Depth (Chorus)
+                    value: chorus,
+                };
+                let _ = self.synth.send_event(chorus_cc);
+                self.channel_chorus.insert(note.channel, chorus);
+                tracing::debug!("Chorus CC: channel {} -> depth {}", note.channel, chorus);
+            }
+        }
+
+        // Check if we need to send volume control change for this channel
+        if let Some(volume) = note.volume {
+            let current_volume = self.channel_volume.get(&note.channel).copied();
+            if current_volume != Some(volume) {
+                let volume_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 7, // Channel Volume
+                    value: volume,
+                };
+                let _ = self.synth.send_event(volume_cc);
+                self.channel_volume.insert(note.channel, volume);
+                tracing::debug!("Volume CC: channel {} -> volume {}", note.channel, volume);
+            }
+        }
+
+        // Check if we need to send pan control change for this channel
+        if let Some(pan) = note.pan {
+            let current_pan = self.channel_pan.get(&note.channel).copied();
+            if current_pan != Some(pan) {
+                let pan_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 10, // Pan
+                    value: pan,
+                };
+                let _ = self.synth.send_event(pan_cc);
+                self.channel_pan.insert(note.channel, pan);
+                tracing::debug!("Pan CC: channel {} -> pan {}", note.channel, pan);
+            }
+        }
+
+        // Check if we need to send balance control change for this channel
+        if let Some(balance) = note.balance {
+            let current_balance = self.channel_balance.get(&note.channel).copied();
+            if current_balance != Some(balance) {
+                let balance_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 8, // Balance
+                    value: balance,
+                };
+                let _ = self.synth.send_event(balance_cc);
+                self.channel_balance.insert(note.channel, balance);
+                tracing::debug!(
+                    "Balance CC: channel {} -> balance {}",
+                    note.channel,
+                    balance
+                );
+            }
+        }
+
+        // Check if we need to send expression control change for this channel
+        if let Some(expression) = note.expression {
+            let current_expression = self.channel_expression.get(&note.channel).copied();
+            if current_expression != Some(expression) {
+                let expression_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 11, // Expression Controller
+                    value: expression,
+                };
+                let _ = self.synth.send_event(expression_cc);
+                self.channel_expression.insert(note.channel, expression);
+                tracing::debug!(
+                    "Expression CC: channel {} -> expression {}",
+                    note.channel,
+                    expression
+                );
+            }
+        }
+
+        // Check if we need to send sustain control change for this channel
+        if let Some(sustain) = note.sustain {
+            let current_sustain = self.channel_sustain.get(&note.channel).copied();
+            if current_sustain != Some(sustain) {
+                let sustain_cc = MidiEvent::ControlChange {
+                    channel: note.channel,
+                    ctrl: 64, // Damper Pedal (Sustain)
+                    value: sustain,
+                };
+                let _ = self.synth.send_event(sustain_cc);
+                self.channel_sustain.insert(note.channel, sustain);
+                tracing::debug!(
+                    "Sustain CC: channel {} -> sustain {}",
+                    note.channel,
+                    sustain
+                );
+            }
+        }
+
+        let midi_event = MidiEvent::NoteOn {
+            channel: note.channel,
+            key: note.note,
+            vel: note.velocity,
+        };
+        let _ = self.synth.send_event(midi_event);
+        self.playing_notes.insert(key, note.duration);
+        if note.channel == 9 {
+            tracing::info!(
+                "🥁 DRUM Note ON: {} (velocity={}) channel {} at sample {}",
+                note.note,
+                note.velocity,
+                note.channel,
+                note_start_sample
+            );
+        } else {
+            tracing::debug!(
+                "Note ON: {} channel {} at sample {}",
+                note.note,
+                note.channel,
+                note_start_sample
+            );
+        }
+    }
+
+    fn note_off(&mut self, index: usize) {
+        let note = &self.notes[index];
+        let (note_start_sample, note_end_sample) = self.note_span(note);
+        let key = (note_start_sample, note.note);
+        if self.playing_notes.remove(&key).is_some() {
+            let midi_event = MidiEvent::NoteOff {
+                channel: note.channel,
+                key: note.note,
+            };
+            let _ = self.synth.send_event(midi_event);
+            tracing::debug!(
+                "Note OFF: {} channel {} at sample {}",
+                note.note,
+                note.channel,
+                note_end_sample
+            );
+        }
+    }
+
+    /// Note on/off events (sample, is note-on, note index) in the chunk starting at
+    /// `chunk_start`, in order. Note-offs come first on a shared sample so a repeated note
+    /// is released before it is struck again.
+    fn chunk_events(&self, chunk_start: u32) -> Vec<(u32, bool, usize)> {
+        let chunk = chunk_start..chunk_start + self.buffer_size as u32;
+        let mut events = Vec::new();
+        for (index, note) in self.notes.iter().enumerate() {
+            let (start, end) = self.note_span(note);
+            if chunk.contains(&start) {
+                events.push((start, true, index));
+            }
+            if chunk.contains(&end) {
+                events.push((end, false, index));
+            }
+        }
+        events.sort_unstable();
+        events
+    }
+
+    fn process_audio_chunk(&mut self) {
+        let chunk_start = self.current_sample as u32;
+        let events = self.chunk_events(chunk_start);
+
         // Clear buffers
         self.left_buffer.fill(0.0);
         self.right_buffer.fill(0.0);
 
-        // Render audio - OxiSynth expects stereo output
-        self.synth
-            .write((&mut self.left_buffer[..], &mut self.right_buffer[..]));
+        // Render audio up to each event so it reaches the synthesizer on its own sample
+        // rather than at the start of the chunk; OxiSynth still starts voices on its next
+        // 64-frame block. OxiSynth expects stereo output.
+        let mut rendered = 0;
+        for (sample, is_on, index) in events {
+            let offset = (sample - chunk_start) as usize;
+            self.synth.write((
+                &mut self.left_buffer[rendered..offset],
+                &mut self.right_buffer[rendered..offset],
+            ));
+            rendered = offset;
+            if is_on {
+                self.note_on(index);
+            } else {
+                self.note_off(index);
+            }
+        }
+        self.synth.write((
+            &mut self.left_buffer[rendered..],
+            &mut self.right_buffer[rendered..],
+        ));
 
         // Log some debug info about the audio levels
         let max_left = self.left_buffer.iter().map(|x| x.abs()).fold(0.0, f32::max);
@@ -961,6 +1632,38 @@ impl OxiSynthSource {
     }
 }
 
+impl OxiSynthSource {
+    /// Render the next amplified stereo frame (left, right)
+    fn next_frame(&mut self) -> (f32, f32) {
+        // If we've consumed the current buffer, process the next chunk
+        if self.buffer_pos >= self.buffer_size {
+            self.process_audio_chunk();
+        }
+
+        let frame = if self.buffer_pos < self.left_buffer.len() {
+            // Amplify the synth output for drums and quiet SoundFont instruments
+            (
+                self.left_buffer[self.buffer_pos] * 20.0,
+                self.right_buffer[self.buffer_pos] * 20.0,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        self.buffer_pos += 1;
+        self.current_sample += 1;
+
+        frame
+    }
+
+    /// Whether playback has run past the end of the sequence
+    fn is_finished(&self) -> bool {
+        let current_time =
+            Duration::from_secs_f32(self.current_sample as f32 / self.sample_rate as f32);
+        current_time > self.total_duration
+    }
+}
+
 impl Iterator for OxiSynthSource {
     type Item = f32;
 
@@ -977,26 +1680,10 @@ impl Iterator for OxiSynthSource {
             return None;
         }
 
-        // If we've consumed the current buffer, process the next chunk
-        if self.buffer_pos >= self.buffer_size {
-            self.process_audio_chunk();
-        }
-
-        // Get the next sample (mix left and right channels with better balance)
-        let sample = if self.buffer_pos < self.left_buffer.len() {
-            // Amplify and properly mix left and right channels for drums
-            let left = self.left_buffer[self.buffer_pos] * 20.0;
-            let right = self.right_buffer[self.buffer_pos] * 20.0;
-            // Better stereo-to-mono conversion maintaining drum punch
-            (left + right) * 0.7 // Slight reduction to prevent clipping
-        } else {
-            0.0
-        };
-
-        self.buffer_pos += 1;
-        self.current_sample += 1;
+        let (left, right) = self.next_frame();
 
-        Some(sample)
+        // Better stereo-to-mono conversion maintaining drum punch
+        Some((left + right) * 0.7) // Slight reduction to prevent clipping
     }
 }
 
@@ -1036,6 +1723,8 @@ struct R2D2PrecomputedEvent {
 #[derive(Debug, Clone)]
 struct SynthEvent {
     start_time: f64,
+    /// Stereo position (-1.0=left, 0.0=center, 1.0=right)
+    pan: f32,
     note: crate::midi::SimpleNote,
 }
 
@@ -1044,6 +1733,10 @@ struct SynthEvent {
 struct SynthPrecomputedEvent {
     start_sample: u32,
     samples: Vec<f32>,
+    /// Equal-power (left, right) gains for this event
+    gains: (f32, f32),
+    /// MIDI channel of the note, so drums can be routed to the drum bus
+    channel: u8,
 }
 
 /// Per-channel effects chain for independent audio processing
@@ -1060,6 +1753,9 @@ struct ChannelEffectsChain {
     solo: bool,
     /// Effects processor for this channel
     effects_processor: Option<FunDSPEffectsProcessor>,
+    /// Stereo mono-maker from a `BassMono` effect, run on whole frames before the effects
+    bass_mono: Option<BassMono>,
+    sample_rate: f64,
 }
 
 impl ChannelEffectsChain {
@@ -1071,13 +1767,31 @@ impl ChannelEffectsChain {
             mute: false,
             solo: false,
             effects_processor: Some(FunDSPEffectsProcessor::new(sample_rate)),
+            bass_mono: None,
+            sample_rate,
         }
     }
 
     fn set_effects(&mut self, effects: Vec<crate::midi::EffectConfig>) {
+        self.bass_mono = effects.iter().find_map(|effect| match effect.effect {
+            crate::midi::EffectType::BassMono { crossover } if !effect.is_bypassed() => {
+                Some(BassMono::new(crossover, self.sample_rate as f32))
+            }
+            _ => None,
+        });
         self.effects = effects;
     }
 
+    /// Process a stereo frame: the bass mono-maker needs both sides at once, the rest of
+    /// the chain runs on each side
+    fn process_frame(&mut self, (left, right): (f32, f32)) -> (f32, f32) {
+        let (left, right) = match &mut self.bass_mono {
+            Some(bass_mono) => bass_mono.process(left, right),
+            None => (left, right),
+        };
+        (self.process_sample(left), self.process_sample(right))
+    }
+
     fn process_sample(&mut self, input_sample: f32) -> f32 {
         if self.mute {
             return 0.0;
@@ -1194,12 +1908,6 @@ impl ChannelProcessor {
         }
     }
 
-    fn set_channel_effects(&mut self, channel: u8, effects: Vec<crate::midi::EffectConfig>) {
-        if (channel as usize) < self.midi_channels.len() {
-            self.midi_channels[channel as usize].set_effects(effects);
-        }
-    }
-
     fn set_r2d2_effects(&mut self, effects: Vec<crate::midi::EffectConfig>) {
         self.r2d2_channel.set_effects(effects);
     }
@@ -1214,34 +1922,41 @@ impl ChannelProcessor {
             || self.synthesis_channel.solo;
     }
 
+    /// Mix one stereo frame: MIDI channels arrive as stereo frames, R2D2 is a mono source
+    /// placed with the channel pan, and synthesis arrives already panned per note.
     fn process_and_mix(
         &mut self,
-        midi_samples: &[f32],
+        midi_frames: &[(f32, f32)],
         r2d2_sample: f32,
-        synthesis_sample: f32,
-    ) -> f32 {
+        synthesis_frame: (f32, f32),
+    ) -> (f32, f32) {
         // If bypass mode is enabled, do simple mixing without effects
         if self.bypass_mode {
-            let midi_sum: f32 = midi_samples.iter().sum();
-            let result = midi_sum + r2d2_sample + synthesis_sample;
-            if result.abs() > 0.001 {
+            let (r2d2_left, r2d2_right) = equal_power_pan(0.0);
+            let result = midi_frames.iter().fold(
+                (
+                    r2d2_sample * r2d2_left + synthesis_frame.0,
+                    r2d2_sample * r2d2_right + synthesis_frame.1,
+                ),
+                |(left, right), frame| (left + frame.0, right + frame.1),
+            );
+            if result.0.abs() > 0.001 || result.1.abs() > 0.001 {
                 tracing::debug!(
-                    "Bypass mode: midi_sum={:.4}, r2d2={:.4}, synth={:.4}, total={:.4}",
-                    midi_sum,
+                    "Bypass mode: r2d2={:.4}, total=({:.4}, {:.4})",
                     r2d2_sample,
-                    synthesis_sample,
-                    result
+                    result.0,
+                    result.1
                 );
             }
             return result;
         }
 
-        let mut mixed_sample = 0.0;
+        let mut mixed = (0.0, 0.0);
 
-        // Process MIDI channels
+        // Process MIDI channels (stereo; channel pan acts as a balance control)
         for (channel_idx, channel) in self.midi_channels.iter_mut().enumerate() {
-            if channel_idx < midi_samples.len() {
-                let input_sample = midi_samples[channel_idx];
+            if channel_idx < midi_frames.len() {
+                let (input_left, input_right) = midi_frames[channel_idx];
                 let should_play = if self.has_solo {
                     channel.solo
                 } else {
@@ -1249,10 +1964,10 @@ impl ChannelProcessor {
                 };
 
                 if should_play && channel.is_active() {
-                    let processed = channel.process_sample(input_sample);
-                    // Apply stereo panning (calculate inline to avoid borrow checker issues)
-                    let pan_gain = 1.0 - (channel.pan.abs() * 0.3);
-                    mixed_sample += processed * pan_gain;
+                    let (left_gain, right_gain) = equal_power_pan(channel.pan);
+                    let (left, right) = channel.process_frame((input_left, input_right));
+                    mixed.0 += left * left_gain * std::f32::consts::SQRT_2;
+                    mixed.1 += right * right_gain * std::f32::consts::SQRT_2;
                 }
             }
         }
@@ -1265,8 +1980,9 @@ impl ChannelProcessor {
         };
         if should_play_r2d2 && self.r2d2_channel.is_active() {
             let processed = self.r2d2_channel.process_sample(r2d2_sample);
-            let pan_gain = 1.0 - (self.r2d2_channel.pan.abs() * 0.3);
-            mixed_sample += processed * pan_gain;
+            let (left_gain, right_gain) = equal_power_pan(self.r2d2_channel.pan);
+            mixed.0 += processed * left_gain;
+            mixed.1 += processed * right_gain;
         }
 
         // Process synthesis channel
@@ -1276,27 +1992,99 @@ impl ChannelProcessor {
             !self.synthesis_channel.mute
         };
         if should_play_synth && self.synthesis_channel.is_active() {
-            let processed = self.synthesis_channel.process_sample(synthesis_sample);
-            let pan_gain = 1.0 - (self.synthesis_channel.pan.abs() * 0.3);
-            mixed_sample += processed * pan_gain;
+            let (left, right) = self.synthesis_channel.process_frame(synthesis_frame);
+            mixed.0 += left;
+            mixed.1 += right;
         }
 
-        // Apply master effects
+        // Apply master effects to each side
         if !self.master_effects.is_empty()
             && let Some(ref master_processor) = self.master_effects_processor
         {
-            match master_processor.process_effects(&[mixed_sample], &self.master_effects) {
-                Ok(processed) => {
-                    mixed_sample = processed.first().copied().unwrap_or(mixed_sample);
+            for side in [&mut mixed.0, &mut mixed.1] {
+                match master_processor.process_effects(&[*side], &self.master_effects) {
+                    Ok(processed) => {
+                        *side = processed.first().copied().unwrap_or(*side);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Master effects processing failed: {}", e);
+                    }
                 }
-                Err(e) => {
-                    tracing::warn!("Master effects processing failed: {}", e);
+            }
+        }
+
+        mixed
+    }
+}
+
+/// Settings of an effects chain over time as `(sample offset, chain)`, in ascending order
+type EffectChanges = Vec<(usize, Vec<crate::midi::EffectConfig>)>;
+
+/// Changes of a MIDI channel's effects chain from the `(start seconds, effects)` of the
+/// notes carrying them. Notes starting on the same sample share one chain, and a note
+/// repeating the current settings changes nothing.
+fn channel_effect_changes(
+    mut settings: Vec<(f64, Vec<crate::midi::EffectConfig>)>,
+    sample_rate: u32,
+) -> EffectChanges {
+    settings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut changes = EffectChanges::new();
+    for (start, effects) in settings {
+        let start = sample_index(start, sample_rate) as usize;
+        match changes.last_mut() {
+            Some((last_start, chain)) if *last_start == start => {
+                for effect in effects {
+                    if !chain.contains(&effect) {
+                        chain.push(effect);
+                    }
                 }
             }
+            _ => changes.push((start, effects)),
         }
+    }
+    changes.dedup_by(|next, previous| next.1 == previous.1);
+    changes
+}
 
-        mixed_sample
+/// Run each MIDI channel's stereo audio (left, right, effect changes) through its own
+/// chain and sum the results. The channel is dry until its first note carrying effects,
+/// and the chain crossfades between settings as later notes change them. A `BassMono` effect narrows the lows of whole frames first, as on a
+/// channel strip.
+fn mix_channel_buses(
+    sample_rate: u32,
+    channels: &[(Vec<f32>, Vec<f32>, EffectChanges)],
+    frames: usize,
+) -> Result<Vec<(f32, f32)>, String> {
+    let processor = FunDSPEffectsProcessor::new(sample_rate as f64);
+    let mut bus = vec![(0.0f32, 0.0f32); frames];
+    for (left, right, changes) in channels {
+        let (mut left, mut right) = (left.clone(), right.clone());
+        let effects = changes.iter().flat_map(|(_, chain)| chain);
+        let crossover = effects.clone().find_map(|effect| match effect.effect {
+            crate::midi::EffectType::BassMono { crossover } if !effect.is_bypassed() => {
+                Some(crossover)
+            }
+            _ => None,
+        });
+        if let Some(crossover) = crossover {
+            let mut bass_mono = BassMono::new(crossover, sample_rate as f32);
+            for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                (*l, *r) = bass_mono.process(*l, *r);
+            }
+        }
+
+        let process = |side: &[f32]| {
+            processor
+                .process_effects_with_changes(side, changes)
+                .map_err(|e| format!("Channel effects processing failed: {}", e))
+        };
+        let (left, right) = (process(&left)?, process(&right)?);
+        for (frame, sample) in bus.iter_mut().zip(left.into_iter().zip(right)) {
+            frame.0 += sample.0;
+            frame.1 += sample.1;
+        }
     }
+    Ok(bus)
 }
 
 /// Enhanced hybrid audio source that mixes MIDI, R2D2, and synthesis with per-channel effects
@@ -1316,27 +2104,67 @@ struct EnhancedHybridAudioSource {
 
     // Per-channel effects processing
     channel_processor: ChannelProcessor,
+
+    // Panic generation at creation; a newer generation silences this source
+    panic_generation: u64,
+
+    // Stop generation at creation; a newer generation starts the fade-out
+    stop_generation: u64,
+
+    // Panic requests for this playback alone
+    control: PlaybackControl,
+
+    // Fade-out in progress after a stop: (frames remaining, total frames)
+    fade_out: Option<(usize, usize)>,
+
+    // Right sample of the current frame, emitted after the left one
+    pending_right: Option<f32>,
+
+    // Pre-rendered, compressed channel 9 frames when drum glue is on
+    drum_bus: Vec<(f32, f32)>,
+
+    // Pre-rendered frames of the MIDI channels with their own effects, each already
+    // through its chain
+    channel_bus: Vec<(f32, f32)>,
+
+    // Engine status registration while playing on the device
+    activity: Option<PlaybackActivity>,
+
+    // First frame after every note has ended, the level counted as silence from there on,
+    // and how many silent frames have run since the mix was last louder
+    tail_start: usize,
+    tail_floor: f32,
+    quiet_frames: usize,
 }
 
 impl EnhancedHybridAudioSource {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         midi_notes: Vec<MidiNote>,
         r2d2_events: Vec<R2D2Event>,
         synthesis_events: Vec<SynthEvent>,
         total_duration: Duration,
-        channel_effects: std::collections::HashMap<u8, Vec<crate::midi::EffectConfig>>,
+        channel_effects: std::collections::HashMap<u8, Vec<(f64, Vec<crate::midi::EffectConfig>)>>,
         r2d2_effects: Vec<crate::midi::EffectConfig>,
         synthesis_effects: Vec<crate::midi::EffectConfig>,
+        soundfont: &mut Option<SoundFont>,
     ) -> Result<Self, String> {
         let sample_rate = 44100;
         let buffer_size = 512; // Smaller buffer for lower latency
 
-        // Create MIDI synthesizer source if there are MIDI notes
+        // Channels with their own effects are rendered apart from the shared synthesizer
+        let (routed_notes, midi_notes): (Vec<_>, Vec<_>) = midi_notes
+            .into_iter()
+            .partition(|note| channel_effects.contains_key(&note.channel));
+
+        // Create MIDI synthesizer source if there are MIDI notes; it gets the SoundFont
+        // once the buses rendered ahead of playback are done with it
         let oxisynth_source = if !midi_notes.is_empty() {
-            Some(
-                OxiSynthSource::new(midi_notes, total_duration)
-                    .map_err(|e| format!("Failed to create OxiSynth source: {}", e))?,
-            )
+            Some(OxiSynthSource::from_synth(
+                Synth::default(),
+                midi_notes,
+                total_duration,
+            ))
         } else {
             None
         };
@@ -1345,13 +2173,12 @@ impl EnhancedHybridAudioSource {
         let mut precomputed_r2d2_events = Vec::new();
 
         if !r2d2_events.is_empty() {
-            let expressive_synth = ExpressiveSynth::new()
-                .map_err(|e| format!("Failed to create ExpressiveSynth: {}", e))?;
+            let expressive_synth = ExpressiveSynth::offline();
 
             let r2d2_voice = R2D2Voice::new();
 
             for event in r2d2_events {
-                let start_sample = (event.start_time * sample_rate as f64) as u32;
+                let start_sample = sample_index(event.start_time, sample_rate);
 
                 let synth_params = r2d2_voice
                     .generate_expression_params(&event.expression)
@@ -1362,6 +2189,7 @@ impl EnhancedHybridAudioSource {
                     event.expression.intensity,
                     synth_params.duration,
                     &synth_params.pitch_contour,
+                    event.expression.voice.as_ref(),
                 );
 
                 precomputed_r2d2_events.push(R2D2PrecomputedEvent {
@@ -1375,23 +2203,33 @@ impl EnhancedHybridAudioSource {
         let mut precomputed_synthesis_events = Vec::new();
 
         if !synthesis_events.is_empty() {
-            let expressive_synth = ExpressiveSynth::new()
-                .map_err(|e| format!("Failed to create ExpressiveSynth for synthesis: {}", e))?;
+            let expressive_synth = ExpressiveSynth::offline();
+            // Note-offs already scheduled per (channel, sample), to stagger a stabbed chord
+            let mut note_offs: std::collections::HashMap<(u8, u32), usize> =
+                std::collections::HashMap::new();
 
             for event in synthesis_events {
-                let start_sample = (event.start_time * sample_rate as f64) as u32;
+                let start_sample = sample_index(event.start_time, sample_rate);
 
                 // Convert SimpleNote to SynthParams
-                let synth_params = Self::convert_simple_note_to_synth_params(&event.note)?;
-
-                // Generate synthesis samples
-                let samples = expressive_synth
+                let mut synth_params = Self::convert_simple_note_to_synth_params(&event.note)?;
+                let note_off =
+                    start_sample + sample_index(synth_params.duration as f64, sample_rate);
+                let simultaneous = note_offs.entry((event.note.channel, note_off)).or_default();
+                synth_params.duration += note_off_stagger(*simultaneous);
+                *simultaneous += 1;
+
+                // Generate synthesis samples, faded out at the note-off
+                let mut samples = expressive_synth
                     .generate_synthesized_samples(&synth_params)
                     .map_err(|e| format!("Failed to generate synthesis samples: {}", e))?;
+                apply_note_off_fade(&mut samples, sample_rate as f32);
 
                 precomputed_synthesis_events.push(SynthPrecomputedEvent {
                     start_sample,
                     samples,
+                    gains: equal_power_pan(event.pan),
+                    channel: event.note.channel,
                 });
             }
         }
@@ -1402,11 +2240,6 @@ impl EnhancedHybridAudioSource {
         // Per-channel effects processing enabled
         channel_processor.bypass_mode = false;
 
-        // Set up channel effects
-        for (channel, effects) in channel_effects {
-            channel_processor.set_channel_effects(channel, effects);
-        }
-
         // Set up R2D2 and synthesis effects
         channel_processor.set_r2d2_effects(r2d2_effects);
         channel_processor.set_synthesis_effects(synthesis_effects);
@@ -1414,7 +2247,7 @@ impl EnhancedHybridAudioSource {
         // Update solo state
         channel_processor.update_solo_state();
 
-        Ok(EnhancedHybridAudioSource {
+        let mut source = EnhancedHybridAudioSource {
             oxisynth_source,
             r2d2_events: precomputed_r2d2_events,
             synthesis_events: precomputed_synthesis_events,
@@ -1422,7 +2255,138 @@ impl EnhancedHybridAudioSource {
             current_sample: 0,
             total_duration,
             channel_processor,
-        })
+            panic_generation: MidiPlayer::panic_generation(),
+            stop_generation: MidiPlayer::stop_generation(),
+            control: PlaybackControl::default(),
+            fade_out: None,
+            pending_right: None,
+            drum_bus: Vec::new(),
+            channel_bus: Vec::new(),
+            activity: None,
+            tail_start: usize::MAX,
+            tail_floor: 0.0,
+            quiet_frames: 0,
+        };
+        source.route_channel_effects(routed_notes, &channel_effects, soundfont)?;
+        Ok(source)
+    }
+
+    /// Give the live MIDI synthesizer the render's SoundFont, after every pre-rendered bus
+    fn attach_soundfont(&mut self, soundfont: SoundFont) {
+        if let Some(oxisynth) = &mut self.oxisynth_source {
+            oxisynth.synth.add_font(soundfont, true);
+        }
+    }
+
+    /// Render each MIDI channel that has effects on its own synthesizer and run the whole
+    /// channel through its chain before it joins the mix, so reverb on one channel never
+    /// reaches the notes of another. `channel_effects` holds each channel's
+    /// `(start seconds, effects)` settings.
+    fn route_channel_effects(
+        &mut self,
+        notes: Vec<MidiNote>,
+        channel_effects: &std::collections::HashMap<u8, Vec<(f64, Vec<crate::midi::EffectConfig>)>>,
+        soundfont: &mut Option<SoundFont>,
+    ) -> Result<(), String> {
+        if notes.is_empty() {
+            return Ok(());
+        }
+        let frames = (self.total_duration.as_secs_f64() * self.sample_rate as f64) as usize + 1;
+
+        let mut by_channel: std::collections::BTreeMap<u8, Vec<MidiNote>> =
+            std::collections::BTreeMap::new();
+        for note in notes {
+            by_channel.entry(note.channel).or_default().push(note);
+        }
+
+        let mut channels = Vec::with_capacity(by_channel.len());
+        for (channel, notes) in by_channel {
+            // Drums get the same boost as on the shared synthesizer
+            let gain = if channel == DRUM_CHANNEL {
+                MIDI_DRUM_BUS_GAIN
+            } else {
+                1.0
+            };
+            let mut left = vec![0.0f32; frames];
+            let mut right = vec![0.0f32; frames];
+            OxiSynthSource::render_into(
+                soundfont,
+                notes,
+                self.total_duration,
+                gain,
+                &mut left,
+                &mut right,
+            )
+            .map_err(|e| format!("Failed to render channel {}: {}", channel, e))?;
+            let changes =
+                channel_effect_changes(channel_effects[&channel].clone(), self.sample_rate);
+            channels.push((left, right, changes));
+        }
+
+        self.channel_bus = mix_channel_buses(self.sample_rate, &channels, frames)?;
+        Ok(())
+    }
+
+    /// End the render once the mix has stayed below `cutoff_db` dBFS for
+    /// TAIL_CUTOFF_WINDOW after `notes_end`, instead of playing out the whole tail estimate
+    fn set_tail_cutoff(&mut self, notes_end: Duration, cutoff_db: f32) {
+        self.tail_start = sample_index(notes_end.as_secs_f64(), self.sample_rate) as usize;
+        self.tail_floor = 10f32.powf(cutoff_db / 20.0);
+    }
+
+    /// Render every channel 9 drum onto one stereo bus and compress it as a single
+    /// instrument. `drum_notes` are the MIDI drums, left out of the main synthesizer;
+    /// synthesized drums are taken from this source. The bus joins the mix after the
+    /// per-channel effects.
+    fn glue_drums(
+        &mut self,
+        drum_notes: Vec<MidiNote>,
+        soundfont: &mut Option<SoundFont>,
+    ) -> Result<(), String> {
+        let frames = (self.total_duration.as_secs_f64() * self.sample_rate as f64) as usize + 1;
+        let mut left = vec![0.0f32; frames];
+        let mut right = vec![0.0f32; frames];
+
+        let (drums, others): (Vec<_>, Vec<_>) = std::mem::take(&mut self.synthesis_events)
+            .into_iter()
+            .partition(|event| event.channel == DRUM_CHANNEL);
+        self.synthesis_events = others;
+        for event in drums {
+            let start = event.start_sample as usize;
+            for (frame, sample) in (start..frames).zip(&event.samples) {
+                left[frame] += sample * event.gains.0;
+                right[frame] += sample * event.gains.1;
+            }
+        }
+
+        if !drum_notes.is_empty() {
+            OxiSynthSource::render_into(
+                soundfont,
+                drum_notes,
+                self.total_duration,
+                MIDI_DRUM_BUS_GAIN,
+                &mut left,
+                &mut right,
+            )
+            .map_err(|e| format!("Failed to render drum bus: {}", e))?;
+        }
+
+        let (left, right) = FunDSPEffectsProcessor::new(self.sample_rate as f64)
+            .compress_stereo_bus(&left, &right, &DRUM_GLUE_COMPRESSOR, DRUM_GLUE_LOOKAHEAD)
+            .map_err(|e| format!("Drum bus compression failed: {}", e))?;
+        self.drum_bus = left.into_iter().zip(right).collect();
+        Ok(())
+    }
+
+    /// Silence MIDI channels and drop all pre-computed R2D2 and synthesis audio
+    fn panic(&mut self) {
+        if let Some(ref mut oxisynth) = self.oxisynth_source {
+            oxisynth.panic();
+        }
+        self.r2d2_events.clear();
+        self.synthesis_events.clear();
+        self.drum_bus.clear();
+        self.channel_bus.clear();
     }
 
     /// Convert SimpleNote to SynthParams for the ExpressiveSynth
@@ -1430,8 +2394,8 @@ impl EnhancedHybridAudioSource {
         note: &crate::midi::SimpleNote,
     ) -> Result<crate::expressive::SynthParams, String> {
         use crate::expressive::{
-            EffectParams, EffectType, EnvelopeParams, FilterParams, FilterType, NoiseColor,
-            SynthParams, SynthType,
+            EffectParams, EffectType, EnvelopeCurve, EnvelopeParams, FilterParams, FilterType, Lfo,
+            NoiseColor, SynthParams, SynthType,
         };
 
         let synth_type_str = note
@@ -1444,11 +2408,20 @@ impl EnhancedHybridAudioSource {
             "sine" => SynthType::Sine,
             "square" => SynthType::Square {
                 pulse_width: note.synth_pulse_width.unwrap_or(0.5),
+                pwm: note.synth_pwm,
             },
             "sawtooth" => SynthType::Sawtooth,
             "triangle" => SynthType::Triangle,
             "noise" => SynthType::Noise {
-                color: NoiseColor::White,
+                color: note
+                    .synth_noise_color
+                    .as_deref()
+                    .and_then(NoiseColor::from_name)
+                    .unwrap_or_default(),
+            },
+            "morph" => SynthType::Morph {
+                position: note.synth_morph_position.unwrap_or(0.0),
+                end_position: note.synth_morph_end,
             },
             "fm" => SynthType::FM {
                 modulator_freq: note.synth_modulator_freq.unwrap_or(440.0),
@@ -1471,6 +2444,11 @@ impl EnhancedHybridAudioSource {
                                 decay: note.synth_decay.unwrap_or(0.1),
                                 sustain: note.synth_sustain.unwrap_or(0.7),
                                 release: note.synth_release.unwrap_or(0.3),
+                                curve: note
+                                    .synth_envelope_curve
+                                    .as_deref()
+                                    .and_then(EnvelopeCurve::from_name)
+                                    .unwrap_or_default(),
                             },
                         },
                         DX7Operator {
@@ -1482,6 +2460,7 @@ impl EnhancedHybridAudioSource {
                                 decay: 0.1,
                                 sustain: 0.3,
                                 release: 0.2,
+                                curve: EnvelopeCurve::Linear,
                             },
                         },
                         // Unused operators
@@ -1561,6 +2540,9 @@ impl EnhancedHybridAudioSource {
                 fundamental: note.synth_frequency.unwrap_or(110.0),
                 overtone_spread: 0.5,
                 modulation: 0.3,
+                harmonics: note.synth_drone_harmonics.unwrap_or(6),
+                evolution_rate: note.synth_drone_evolution_rate.unwrap_or(0.05),
+                detune: note.synth_drone_detune.unwrap_or(4.0),
             },
             _ => return Err(format!("Unknown synthesis type: {}", synth_type_str)),
         };
@@ -1592,6 +2574,11 @@ impl EnhancedHybridAudioSource {
             decay: note.synth_decay.unwrap_or(0.1),
             sustain: note.synth_sustain.unwrap_or(0.7),
             release: note.synth_release.unwrap_or(0.3),
+            curve: note
+                .synth_envelope_curve
+                .as_deref()
+                .and_then(EnvelopeCurve::from_name)
+                .unwrap_or_default(),
         };
 
         // Create filter if specified
@@ -1607,6 +2594,9 @@ impl EnhancedHybridAudioSource {
                 cutoff: note.synth_filter_cutoff.unwrap_or(1000.0),
                 resonance: note.synth_filter_resonance.unwrap_or(0.1),
                 filter_type,
+                soft_start: note
+                    .synth_filter_soft_start
+                    .unwrap_or(crate::expressive::DEFAULT_FILTER_SOFT_START),
             })
         } else {
             None
@@ -1646,7 +2636,7 @@ impl EnhancedHybridAudioSource {
         // Process universal effects from the new effects system
         if let Some(universal_effects) = &note.effects {
             for effect_config in universal_effects {
-                if effect_config.enabled {
+                if !effect_config.is_bypassed() {
                     // Convert EffectConfig to EffectParams for audio processing
                     match &effect_config.effect {
                         crate::midi::EffectType::Reverb {
@@ -1654,6 +2644,8 @@ impl EnhancedHybridAudioSource {
                             dampening: _,
                             wet_level: _,
                             pre_delay: _,
+                            modulation: _,
+                            algorithm: _,
                         } => {
                             effects.push(EffectParams {
                                 effect_type: EffectType::Reverb,
@@ -1700,9 +2692,12 @@ impl EnhancedHybridAudioSource {
             frequency,
             amplitude: note.synth_amplitude.unwrap_or(0.7),
             duration: note.duration.unwrap_or(1.0) as f32,
+            phase: note.synth_phase.unwrap_or(0.0),
             envelope,
             filter,
             effects,
+            vibrato: Lfo::new(note.synth_vibrato_rate, note.synth_vibrato_depth),
+            tremolo: Lfo::new(note.synth_tremolo_rate, note.synth_tremolo_depth),
         })
     }
 
@@ -1720,18 +2715,20 @@ impl EnhancedHybridAudioSource {
         sample
     }
 
-    /// Get synthesis sample at the given sample index
-    fn get_synthesis_sample(&self, sample_index: usize) -> f32 {
-        let mut sample = 0.0;
+    /// Get the panned synthesis frame (left, right) at the given sample index
+    fn get_synthesis_frame(&self, sample_index: usize) -> (f32, f32) {
+        let mut frame = (0.0, 0.0);
 
         for event in &self.synthesis_events {
             let event_sample_index = sample_index as i32 - event.start_sample as i32;
             if event_sample_index >= 0 && (event_sample_index as usize) < event.samples.len() {
-                sample += event.samples[event_sample_index as usize];
+                let sample = event.samples[event_sample_index as usize];
+                frame.0 += sample * event.gains.0;
+                frame.1 += sample * event.gains.1;
             }
         }
 
-        sample
+        frame
     }
 
     /// Check if drums are currently playing (for channel routing)
@@ -1749,12 +2746,46 @@ impl EnhancedHybridAudioSource {
             false
         }
     }
-}
 
-impl Iterator for EnhancedHybridAudioSource {
-    type Item = f32;
+    /// Synthesis and R2D2 events sounding at the current sample, plus held MIDI notes
+    fn sounding_voices(&self) -> usize {
+        let sounding = |start_sample: u32, len: usize| {
+            (start_sample as usize..start_sample as usize + len).contains(&self.current_sample)
+        };
+        let synthesis = self
+            .synthesis_events
+            .iter()
+            .filter(|event| sounding(event.start_sample, event.samples.len()))
+            .count();
+        let r2d2 = self
+            .r2d2_events
+            .iter()
+            .filter(|event| sounding(event.start_sample, event.samples.len()))
+            .count();
+        let midi = self
+            .oxisynth_source
+            .as_ref()
+            .map_or(0, OxiSynthSource::active_note_count);
+        synthesis + r2d2 + midi
+    }
+
+    /// Next interleaved sample of the mix, or None once finished, panicked or faded out
+    fn next_sample(&mut self) -> Option<f32> {
+        // Stop immediately if a panic was requested after this source was created
+        if MidiPlayer::panic_generation() != self.panic_generation || self.control.panic_requested()
+        {
+            self.panic();
+            return None;
+        }
+
+        // Start fading out the first time a stop is seen
+        if self.fade_out.is_none()
+            && let Some(fade) = self.control.stop_fade(self.stop_generation)
+        {
+            let frames = (fade.as_secs_f64() * self.sample_rate as f64) as usize;
+            self.fade_out = Some((frames, frames));
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
         // Check if we've reached the end of the sequence
         let current_time =
             Duration::from_secs_f32(self.current_sample as f32 / self.sample_rate as f32);
@@ -1763,25 +2794,46 @@ impl Iterator for EnhancedHybridAudioSource {
             return None;
         }
 
+        // Emit the right half of the frame produced by the previous call
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+
+        // The stop fade has reached silence
+        if let Some((0, _)) = self.fade_out {
+            self.panic();
+            return None;
+        }
+
+        // The tail has died away
+        if self.quiet_frames as f64 >= TAIL_CUTOFF_WINDOW * self.sample_rate as f64 {
+            return None;
+        }
+
         // Get R2D2 sample
         let r2d2_sample = self.get_r2d2_sample(self.current_sample);
 
-        // Get synthesis sample
-        let synthesis_sample = self.get_synthesis_sample(self.current_sample);
+        // Get panned synthesis frame
+        let synthesis_frame = self.get_synthesis_frame(self.current_sample);
 
-        // Get MIDI samples with proper per-channel separation
-        let mut midi_channels = vec![0.0; 16]; // 16 MIDI channels
+        // Get MIDI frames with proper per-channel separation
+        let mut midi_channels = [(0.0, 0.0); 16]; // 16 MIDI channels
 
+        let drums_playing = self.has_drums_playing();
         if let Some(ref mut oxisynth) = self.oxisynth_source {
-            // Get the mixed sample from OxiSynth
-            let midi_sample = oxisynth.next().unwrap_or(0.0);
+            // Get the mixed stereo frame from OxiSynth
+            let midi_frame = if oxisynth.is_finished() {
+                (0.0, 0.0)
+            } else {
+                oxisynth.next_frame()
+            };
 
             // Enhanced channel routing with special drum handling
-            if self.has_drums_playing() {
+            if drums_playing {
                 // Drums are playing - give them special routing and volume boost
-                midi_channels[9] = midi_sample * 3.0; // Significant drum volume boost
+                midi_channels[9] = (midi_frame.0 * 3.0, midi_frame.1 * 3.0); // Significant drum volume boost
                 // Also put on channel 0 for compatibility, but at normal volume
-                midi_channels[0] = midi_sample;
+                midi_channels[0] = midi_frame;
 
                 // Debug log when drums are detected
                 if self.current_sample.is_multiple_of(22050) {
@@ -1789,17 +2841,63 @@ impl Iterator for EnhancedHybridAudioSource {
                     tracing::info!("🥁 Drums detected playing on channel 9, boosted volume");
                 }
             } else {
-                midi_channels[0] = midi_sample;
+                midi_channels[0] = midi_frame;
             }
         }
 
         // Use channel processor to mix and apply effects
-        let final_sample =
+        let (mut left, mut right) =
             self.channel_processor
-                .process_and_mix(&midi_channels, r2d2_sample, synthesis_sample);
+                .process_and_mix(&midi_channels, r2d2_sample, synthesis_frame);
+
+        if let Some((drum_left, drum_right)) = self.drum_bus.get(self.current_sample) {
+            left += drum_left;
+            right += drum_right;
+        }
+        if let Some((channel_left, channel_right)) = self.channel_bus.get(self.current_sample) {
+            left += channel_left;
+            right += channel_right;
+        }
+
+        if self.current_sample >= self.tail_start {
+            if left.abs().max(right.abs()) < self.tail_floor {
+                self.quiet_frames += 1;
+            } else {
+                self.quiet_frames = 0;
+            }
+        }
+
+        // Ramp linearly to zero over the stop fade
+        if let Some((remaining, total)) = &mut self.fade_out {
+            *remaining -= 1;
+            let gain = *remaining as f32 / *total as f32;
+            left *= gain;
+            right *= gain;
+        }
 
         self.current_sample += 1;
-        Some(final_sample)
+        self.pending_right = Some(right);
+        Some(left)
+    }
+}
+
+impl Iterator for EnhancedHybridAudioSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.next_sample();
+        if sample.is_none() {
+            self.activity = None;
+        } else if self.activity.is_some()
+            && self.pending_right.is_some()
+            && (self.current_sample - 1).is_multiple_of(VOICE_COUNT_INTERVAL)
+        {
+            let voices = self.sounding_voices();
+            if let Some(activity) = &mut self.activity {
+                activity.set_voices(voices);
+            }
+        }
+        sample
     }
 }
 
@@ -1809,7 +2907,7 @@ impl Source for EnhancedHybridAudioSource {
     }
 
     fn channels(&self) -> u16 {
-        1 // Mono output
+        2 // Interleaved stereo output
     }
 
     fn sample_rate(&self) -> u32 {
@@ -1824,6 +2922,624 @@ impl Source for EnhancedHybridAudioSource {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::midi::SimpleNote;
+
+    #[test]
+    fn test_reverbed_short_note_reports_tail_in_audible_duration() {
+        let mut note = test_note(60, 0.0, 0.25);
+        note.reverb = Some(127);
+
+        let dry = MidiPlayer::audible_duration(&[test_note(60, 0.0, 0.25)], &[], &[]);
+        let wet = MidiPlayer::audible_duration(&[note], &[], &[]);
+
+        assert!(wet.as_secs_f64() > 0.25 + 3.0, "wet duration {:?}", wet);
+        assert!(wet > dry);
+    }
+
+    #[test]
+    fn test_export_smf_round_trips_midi_notes_and_skips_the_rest() {
+        let sequence: SimpleSequence = serde_json::from_value(serde_json::json!({
+            "tempo": 90,
+            "notes": [
+                {"note": 60, "velocity": 100, "start_time": 0.0, "duration": 0.5, "instrument": 0},
+                {"note": 64, "velocity": 90, "start_time": 0.5, "duration": 0.5, "instrument": 0},
+                {"note": 67, "velocity": 80, "start_time": 1.0, "duration": 1.0, "instrument": 40},
+                {"note": 36, "velocity": 110, "start_time": 0.0, "duration": 0.25, "channel": 9},
+                {"start_time": 0.0, "duration": 0.5, "synth_type": "sine", "synth_frequency": 440.0},
+                {"note_type": "r2d2", "r2d2_emotion": "Happy", "start_time": 1.0, "duration": 0.5}
+            ]
+        }))
+        .unwrap();
+
+        let path = std::env::temp_dir().join("mcp-muse-export-smf-test/song.mid");
+        let export = MidiPlayer::export_smf(&sequence, &path).unwrap();
+        assert_eq!(
+            export,
+            SmfExport {
+                exported: 4,
+                skipped: 2
+            }
+        );
+
+        let parsed = parser::parse_midi_data(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(parsed.ticks_per_quarter, 480);
+        assert_eq!(parsed.tempo, 60_000_000 / 90);
+        let notes: Vec<(u8, u8, u8, Option<u8>, f64, f64)> = parsed
+            .notes
+            .iter()
+            .map(|note| {
+                (
+                    note.note,
+                    note.channel,
+                    note.velocity,
+                    note.instrument,
+                    note.start_time.as_secs_f64(),
+                    note.duration.as_secs_f64(),
+                )
+            })
+            .collect();
+        let expected = [
+            (60, 0, 100, Some(0), 0.0, 0.5),
+            (36, 9, 110, None, 0.0, 0.25),
+            (64, 0, 90, Some(0), 0.5, 0.5),
+            (67, 0, 80, Some(40), 1.0, 1.0),
+        ];
+        assert_eq!(notes.len(), expected.len());
+        for (note, expected) in notes.iter().zip(expected) {
+            assert_eq!(
+                (note.0, note.1, note.2, note.3),
+                (expected.0, expected.1, expected.2, expected.3)
+            );
+            assert!((note.4 - expected.4).abs() < 1e-3, "{:?}", note);
+            assert!((note.5 - expected.5).abs() < 1e-3, "{:?}", note);
+        }
+    }
+
+    #[test]
+    fn test_export_smf_follows_tempo_changes_and_overlapping_notes() {
+        // 120 BPM ramps down to 60 across bar 1; a long C4 is struck again while held
+        let sequence: SimpleSequence = serde_json::from_value(serde_json::json!({
+            "tempo": 120,
+            "tempo_changes": [{"at": {"bar": 2, "beat": 1, "tick": 0}, "tempo": 60}],
+            "notes": [
+                {"note": 60, "start_time": 0.0, "duration": 4.0, "instrument": 0},
+                {"note": 60, "start_time": 1.0, "duration": 1.0, "instrument": 0},
+                {"note": 64, "musical_time": {"bar": 3, "beat": 1, "tick": 0},
+                 "duration": 1.0, "instrument": 0}
+            ]
+        }))
+        .unwrap();
+        let tempo_map = TempoMap::new(120, 4, &sequence.tempo_changes);
+
+        let path = std::env::temp_dir().join("mcp-muse-export-smf-tempo-test/song.mid");
+        MidiPlayer::export_smf(&sequence, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        let smf = midly::Smf::parse(&bytes).unwrap();
+
+        // Tempo steps down through the ramp and holds 60 BPM from bar 2
+        let mut tick = 0;
+        let mut tempos = Vec::new();
+        for event in &smf.tracks[0] {
+            tick += event.delta.as_int();
+            if let midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(micros)) = event.kind {
+                tempos.push((tick, micros.as_int()));
+            }
+        }
+        assert_eq!(tempos[0].0, 0);
+        assert!((500_000..520_000).contains(&tempos[0].1), "{:?}", tempos[0]);
+        assert_eq!(tempos.last(), Some(&(1920, 1_000_000)));
+        assert!(tempos.windows(2).all(|pair| pair[0].1 < pair[1].1));
+
+        // Notes read back at their times along the tempo map
+        let parsed = parser::parse_midi_data(&bytes).unwrap();
+        let bar_three = tempo_map.seconds_at(8.0);
+        let e4 = parsed.notes.iter().find(|note| note.note == 64).unwrap();
+        assert!(
+            (e4.start_time.as_secs_f64() - bar_three).abs() < 1e-3,
+            "{:?} vs {}",
+            e4.start_time,
+            bar_three
+        );
+        assert!((e4.duration.as_secs_f64() - 1.0).abs() < 1e-3);
+
+        // The restruck C4 sends no note-off of its own; the held note's one ends both
+        let mut tick = 0;
+        let mut c4_offs = Vec::new();
+        for event in &smf.tracks[1] {
+            tick += event.delta.as_int();
+            match event.kind {
+                midly::TrackEventKind::Midi {
+                    message: midly::MidiMessage::NoteOff { key, .. },
+                    ..
+                } if key == 60 => c4_offs.push(tick),
+                _ => {}
+            }
+        }
+        let end = (tempo_map.beat_at(4.0) * 480.0).round() as u32;
+        assert_eq!(c4_offs, vec![end]);
+    }
+
+    #[test]
+    fn test_audible_duration_counts_synthesis_and_r2d2_notes() {
+        let synth = SynthEvent {
+            start_time: 1.0,
+            pan: 0.0,
+            note: SimpleNote {
+                duration: Some(3.0),
+                synth_type: Some("sine".to_string()),
+                ..Default::default()
+            },
+        };
+        let r2d2 = R2D2Event {
+            start_time: 5.0,
+            expression: R2D2Expression {
+                emotion: R2D2Emotion::Happy,
+                intensity: 0.5,
+                duration: 1.5,
+                phrase_complexity: 2,
+                pitch_range: (300.0, 800.0),
+                context: None,
+                voice: None,
+            },
+        };
+
+        // No MIDI notes, so only the base decay tail follows the last note's end
+        let synth_only = MidiPlayer::audible_duration(&[], &[], std::slice::from_ref(&synth));
+        assert!(
+            (synth_only.as_secs_f64() - 6.0).abs() < 1e-9,
+            "{:?}",
+            synth_only
+        );
+        let both = MidiPlayer::audible_duration(&[], &[r2d2], &[synth]);
+        assert!((both.as_secs_f64() - 8.5).abs() < 1e-9, "{:?}", both);
+    }
+
+    #[test]
+    fn test_reverb_tail_render_stops_once_it_decays_below_the_cutoff() {
+        let note: SimpleNote = serde_json::from_value(serde_json::json!({
+            "start_time": 0.0,
+            "duration": 0.25,
+            "synth_type": "sine",
+            "synth_frequency": 440.0,
+            "effects": [{"type": "reverb", "room_size": 0.5}]
+        }))
+        .unwrap();
+        let render = |tail_cutoff_db: f32| {
+            MidiPlayer::render_samples(SimpleSequence {
+                notes: vec![note.clone()],
+                tail_cutoff_db,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let trimmed = render(DEFAULT_TAIL_CUTOFF_DB);
+        let full = render(-120.0);
+        let channels = trimmed.channels as usize;
+
+        // The reverb reserves a fixed tail after the note; the render instead stops one
+        // cutoff window after the note once nothing is left above -60 dBFS
+        let floor = 10f32.powf(DEFAULT_TAIL_CUTOFF_DB / 20.0);
+        let last_audible = trimmed
+            .samples
+            .iter()
+            .rposition(|sample| sample.abs() >= floor)
+            .unwrap()
+            / channels;
+        let frames = trimmed.samples.len() / channels;
+        let window = (TAIL_CUTOFF_WINDOW * 44100.0) as usize;
+        let tail_start = last_audible.max(44100 / 4);
+        assert!(
+            frames - tail_start <= window + 1,
+            "{} frames of silence kept",
+            frames - tail_start
+        );
+        // The fixed tail alone would run to at least 2.25 seconds
+        assert!(
+            trimmed.duration < Duration::from_secs(1),
+            "trimmed to {:?}",
+            trimmed.duration
+        );
+        // Trimming only drops the end
+        assert_eq!(trimmed.samples[..], full.samples[..trimmed.samples.len()]);
+    }
+
+    #[test]
+    fn test_drum_bus_gain_matches_the_regular_mix() {
+        // What the shared synthesizer's frame becomes in the mixer while drums sound
+        let frame = (0.2, -0.1);
+        let mut midi_channels = [(0.0, 0.0); 16];
+        midi_channels[DRUM_CHANNEL as usize] = (frame.0 * 3.0, frame.1 * 3.0);
+        midi_channels[0] = frame;
+        let mut processor = ChannelProcessor::new(512, 44100.0);
+        let (left, right) = processor.process_and_mix(&midi_channels, 0.0, (0.0, 0.0));
+
+        assert!(
+            (left - frame.0 * MIDI_DRUM_BUS_GAIN).abs() < 1e-6,
+            "{}",
+            left
+        );
+        assert!(
+            (right - frame.1 * MIDI_DRUM_BUS_GAIN).abs() < 1e-6,
+            "{}",
+            right
+        );
+    }
+
+    #[test]
+    fn test_bypassed_effects_are_left_off_synth_notes() {
+        let effect = |value: serde_json::Value| -> crate::midi::EffectConfig {
+            serde_json::from_value(value).unwrap()
+        };
+        let note = SimpleNote {
+            synth_type: Some("sine".to_string()),
+            effects: Some(vec![
+                effect(
+                    serde_json::json!({"type": "chorus", "rate": 1.0, "depth": 0.5, "delay": 20.0, "intensity": 0.0}),
+                ),
+                effect(
+                    serde_json::json!({"type": "delay", "delay_time": 0.2, "feedback": 0.5, "wet_level": 0.8, "enabled": false}),
+                ),
+                effect(
+                    serde_json::json!({"type": "delay", "delay_time": 0.2, "feedback": 0.5, "wet_level": 0.8, "intensity": 0.6}),
+                ),
+            ]),
+            ..Default::default()
+        };
+        let params = EnhancedHybridAudioSource::convert_simple_note_to_synth_params(&note).unwrap();
+        assert_eq!(params.effects.len(), 1);
+        assert_eq!(params.effects[0].intensity, 0.6);
+    }
+
+    #[test]
+    fn test_channel_effect_changes_follow_the_notes_carrying_them() {
+        let filter = |cutoff: f32| -> crate::midi::EffectConfig {
+            serde_json::from_value(serde_json::json!({
+                "type": "filter",
+                "filter_type": "low_pass",
+                "cutoff": cutoff,
+                "intensity": 1.0
+            }))
+            .unwrap()
+        };
+        // Out of order, a chord sharing one setting and a note repeating the current one
+        let changes = channel_effect_changes(
+            vec![
+                (1.0, vec![filter(2000.0)]),
+                (0.0, vec![filter(500.0)]),
+                (0.5, vec![filter(500.0)]),
+                (1.5, vec![filter(800.0)]),
+                (1.5, vec![filter(800.0)]),
+                (1.5, vec![filter(1200.0)]),
+            ],
+            44100,
+        );
+        assert_eq!(
+            changes,
+            vec![
+                (0, vec![filter(500.0)]),
+                (44100, vec![filter(2000.0)]),
+                (66150, vec![filter(800.0), filter(1200.0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_midi_channel_effects_do_not_reach_other_channels() {
+        let effect = |value: serde_json::Value| -> crate::midi::EffectConfig {
+            serde_json::from_value(value).unwrap()
+        };
+        let frames = 44100;
+        let tone = |frequency: f32, start: usize, end: usize| -> Vec<f32> {
+            (0..frames)
+                .map(|i| {
+                    if (start..end).contains(&i) {
+                        0.4 * (2.0 * std::f32::consts::PI * frequency * i as f32 / 44100.0).sin()
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        };
+        // Strings with an echo on channel 0, an overdriven bass later on channel 1
+        let strings = tone(440.0, 0, 4410);
+        let bass = tone(110.0, 22050, 30870);
+        let echo = effect(serde_json::json!({
+            "type": "delay",
+            "delay_time": 0.2,
+            "feedback": 0.5,
+            "wet_level": 0.8,
+            "intensity": 1.0
+        }));
+        let drive = effect(serde_json::json!({
+            "type": "distortion",
+            "drive": 8.0,
+            "intensity": 1.0
+        }));
+
+        let alone = |signal: &Vec<f32>, effects: Vec<crate::midi::EffectConfig>| {
+            mix_channel_buses(
+                44100,
+                &[(signal.clone(), signal.clone(), vec![(0, effects)])],
+                frames,
+            )
+            .unwrap()
+        };
+        let routed = mix_channel_buses(
+            44100,
+            &[
+                (
+                    strings.clone(),
+                    strings.clone(),
+                    vec![(0, vec![echo.clone()])],
+                ),
+                (bass.clone(), bass.clone(), vec![(0, vec![drive.clone()])]),
+            ],
+            frames,
+        )
+        .unwrap();
+        let expected: Vec<(f32, f32)> = alone(&strings, vec![echo.clone()])
+            .into_iter()
+            .zip(alone(&bass, vec![drive.clone()]))
+            .map(|(a, b)| (a.0 + b.0, a.1 + b.1))
+            .collect();
+        for (frame, (got, want)) in routed.iter().zip(&expected).enumerate() {
+            assert!(
+                (got.0 - want.0).abs() < 1e-6 && (got.1 - want.1).abs() < 1e-6,
+                "frame {}: {:?} vs {:?}",
+                frame,
+                got,
+                want
+            );
+        }
+
+        // Lumping both chains onto one channel echoes the bass and distorts the strings
+        let summed: Vec<f32> = strings.iter().zip(&bass).map(|(a, b)| a + b).collect();
+        let lumped = alone(&summed, vec![echo, drive]);
+        let difference = routed
+            .iter()
+            .zip(&lumped)
+            .map(|(a, b)| (a.0 - b.0).abs())
+            .fold(0.0f32, f32::max);
+        assert!(
+            difference > 0.1,
+            "routing made no difference: {}",
+            difference
+        );
+    }
+
+    #[test]
+    fn test_midi_synth_and_r2d2_notes_start_on_the_same_sample() {
+        let synth = SimpleNote {
+            duration: Some(0.3),
+            synth_type: Some("square".to_string()),
+            synth_frequency: Some(220.0),
+            ..Default::default()
+        };
+        let r2d2 = SimpleNote {
+            duration: Some(0.3),
+            note_type: "r2d2".to_string(),
+            r2d2_emotion: Some("Happy".to_string()),
+            r2d2_intensity: Some(0.8),
+            r2d2_complexity: Some(2),
+            ..Default::default()
+        };
+        // 0.7s is 30869.999... samples, which truncating would start a sample early
+        for start in [1.0, 0.7] {
+            let expected = (start * 44100.0f64).round() as u32;
+
+            let midi = OxiSynthSource::from_synth(
+                Synth::default(),
+                vec![test_note(60, start, 0.5)],
+                Duration::from_secs(2),
+            );
+            assert_eq!(
+                midi.chunk_events(expected / 1024 * 1024),
+                vec![(expected, true, 0)]
+            );
+
+            // Silent up to the start sample, then exactly the sound rendered from time 0
+            for note in [&synth, &r2d2] {
+                let render = |start_time: f64| {
+                    MidiPlayer::render_samples(SimpleSequence {
+                        notes: vec![SimpleNote {
+                            start_time: Some(start_time),
+                            ..note.clone()
+                        }],
+                        master_seed: Some(5),
+                        ..Default::default()
+                    })
+                    .unwrap()
+                };
+                let from_zero = render(0.0);
+                let delayed = render(start);
+                let offset = expected as usize * delayed.channels as usize;
+                assert!(delayed.samples[..offset].iter().all(|&s| s == 0.0));
+                let onset = 2000;
+                for (a, b) in delayed.samples[offset..offset + onset]
+                    .iter()
+                    .zip(&from_zero.samples[..onset])
+                {
+                    assert!((a - b).abs() < 1e-6, "{}: {} vs {}", note.note_type, a, b);
+                }
+                assert!(from_zero.samples[..onset].iter().any(|s| s.abs() > 0.01));
+            }
+        }
+    }
+
+    /// Minimal SoundFont whose one preset (bank 0, program 0) plays a constant level,
+    /// so a MIDI note is audible from its very first frame
+    fn test_soundfont() -> SoundFont {
+        fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+            let mut chunk = id.to_vec();
+            chunk.extend((data.len() as u32).to_le_bytes());
+            chunk.extend(data);
+            if data.len() % 2 == 1 {
+                chunk.push(0);
+            }
+            chunk
+        }
+        fn list(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+            chunk(b"LIST", &[kind.to_vec(), chunks.concat()].concat())
+        }
+        fn name(name: &str) -> Vec<u8> {
+            let mut bytes = name.as_bytes().to_vec();
+            bytes.resize(20, 0);
+            bytes
+        }
+        fn pairs(values: &[(u16, u16)]) -> Vec<u8> {
+            values
+                .iter()
+                .flat_map(|(a, b)| [a.to_le_bytes(), b.to_le_bytes()].concat())
+                .collect()
+        }
+        fn sample_header(label: &str, end: u32, sample_type: u16) -> Vec<u8> {
+            let mut header = name(label);
+            for value in [0, end, 0, end, if end > 0 { 44100 } else { 0 }] {
+                header.extend(value.to_le_bytes());
+            }
+            header.extend([60, 0, 0, 0]);
+            header.extend(sample_type.to_le_bytes());
+            header
+        }
+
+        let frames = 44100;
+        let mut samples = vec![0u8; (frames + 46) * 2];
+        for frame in samples.chunks_mut(2).take(frames) {
+            frame.copy_from_slice(&16000i16.to_le_bytes());
+        }
+        let preset_headers = [
+            [name("Tone"), vec![0; 6], vec![0; 12]].concat(),
+            [name("EOP"), vec![0, 0, 0, 0, 1, 0], vec![0; 12]].concat(),
+        ];
+        let instruments = [
+            [name("Tone"), vec![0, 0]].concat(),
+            [name("EOI"), vec![1, 0]].concat(),
+        ];
+        let body = [
+            b"sfbk".to_vec(),
+            list(b"INFO", &[chunk(b"ifil", &pairs(&[(2, 1)]))]),
+            list(b"sdta", &[chunk(b"smpl", &samples)]),
+            list(
+                b"pdta",
+                &[
+                    chunk(b"phdr", &preset_headers.concat()),
+                    chunk(b"pbag", &pairs(&[(0, 0), (1, 0)])),
+                    chunk(b"pmod", &[0; 10]),
+                    // Generator 41 picks the instrument, 53 the sample
+                    chunk(b"pgen", &pairs(&[(41, 0), (0, 0)])),
+                    chunk(b"inst", &instruments.concat()),
+                    chunk(b"ibag", &pairs(&[(0, 0), (1, 0)])),
+                    chunk(b"imod", &[0; 10]),
+                    chunk(b"igen", &pairs(&[(53, 0), (0, 0)])),
+                    chunk(
+                        b"shdr",
+                        &[
+                            sample_header("Level", frames as u32, 1),
+                            sample_header("EOS", 0, 0),
+                        ]
+                        .concat(),
+                    ),
+                ],
+            ),
+        ]
+        .concat();
+        SoundFont::load(&mut std::io::Cursor::new(chunk(b"RIFF", &body))).unwrap()
+    }
+
+    #[test]
+    fn test_midi_onset_lands_on_the_synth_onset() {
+        // A synthesized note panned hard left and a MIDI note panned right, both at 1.0s;
+        // the right channel of the mix carries only the MIDI note
+        let start = 1.0;
+        let mut midi = test_note(60, start, 0.5);
+        midi.pan = Some(127);
+        let synth = SynthEvent {
+            start_time: start,
+            pan: -1.0,
+            note: SimpleNote {
+                start_time: Some(start),
+                duration: Some(0.5),
+                synth_type: Some("square".to_string()),
+                synth_frequency: Some(220.0),
+                ..Default::default()
+            },
+        };
+        let mut source = EnhancedHybridAudioSource::new(
+            vec![midi],
+            Vec::new(),
+            vec![synth],
+            Duration::from_secs(2),
+            std::collections::HashMap::new(),
+            Vec::new(),
+            Vec::new(),
+            &mut None,
+        )
+        .unwrap();
+        source.attach_soundfont(test_soundfont());
+        let mix: Vec<f32> = source.take(2 * 2 * 44100).collect();
+
+        let onset = |channel: usize| {
+            mix.iter()
+                .skip(channel)
+                .step_by(2)
+                .position(|s| s.abs() > 1e-4)
+                .unwrap()
+        };
+        let (synth_onset, midi_onset) = (onset(0), onset(1));
+        let expected = sample_index(start, 44100) as usize;
+        assert!(
+            (expected..=expected + 1).contains(&synth_onset),
+            "synthesized note began on sample {} rather than {}",
+            synth_onset,
+            expected
+        );
+        // 1.0s is 4 frames into an OxiSynth block, which the leading partial block absorbs
+        assert_ne!(expected % 64, 0);
+        assert_eq!(
+            midi_onset, synth_onset,
+            "MIDI note began on sample {}, synthesized note on {}",
+            midi_onset, synth_onset
+        );
+    }
+
+    #[test]
+    fn test_stabbed_synth_chord_release_has_no_discontinuity() {
+        // Six-note chord with no release, every note ending on the same sample
+        let chord: Vec<SimpleNote> = [261.6, 329.6, 392.0, 523.3, 659.3, 784.0]
+            .into_iter()
+            .map(|frequency| SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(0.1),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(frequency),
+                synth_amplitude: Some(0.15),
+                synth_attack: Some(0.0),
+                synth_decay: Some(0.0),
+                synth_sustain: Some(1.0),
+                synth_release: Some(0.0),
+                ..Default::default()
+            })
+            .collect();
+        let audio = MidiPlayer::render_samples(SimpleSequence {
+            notes: chord,
+            ..Default::default()
+        })
+        .unwrap();
+        let left: Vec<f32> = audio.samples.iter().step_by(2).copied().collect();
+
+        let release = (0.09 * 44100.0) as usize..(0.11 * 44100.0) as usize;
+        assert!(
+            left[release.clone()].iter().any(|s| s.abs() > 0.1),
+            "chord should be sounding up to the release"
+        );
+        let click = left[release]
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0f32, f32::max);
+        assert!(click < 0.05, "click of {}", click);
+    }
 
     #[test]
     fn test_midi_player_creation() {
@@ -1832,4 +3548,817 @@ mod tests {
             // Success
         }
     }
+
+    fn test_note(note: u8, start: f64, duration: f64) -> MidiNote {
+        MidiNote {
+            note,
+            velocity: 100,
+            channel: 0,
+            start_time: Duration::from_secs_f64(start),
+            duration: Duration::from_secs_f64(duration),
+            instrument: None,
+            reverb: None,
+            chorus: None,
+            volume: None,
+            pan: None,
+            balance: None,
+            expression: None,
+            sustain: None,
+        }
+    }
+
+    #[test]
+    fn test_panic_silences_oxisynth_source() {
+        let notes = vec![test_note(60, 0.0, 2.0), test_note(64, 0.0, 2.0)];
+        let mut source =
+            OxiSynthSource::from_synth(Synth::default(), notes, Duration::from_secs(3));
+
+        // Trigger the notes
+        for _ in 0..2048 {
+            source.next();
+        }
+        assert_eq!(source.active_note_count(), 2);
+
+        source.panic();
+        assert_eq!(source.active_note_count(), 0);
+
+        // Nothing is re-triggered and every channel renders silence (below -80dB) afterwards
+        let after: Vec<f32> = (0..4096).filter_map(|_| source.next()).collect();
+        assert_eq!(source.active_note_count(), 0);
+        let peak = after.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(
+            peak < 1e-4,
+            "synth still sounding after panic: peak {}",
+            peak
+        );
+    }
+
+    /// Time in seconds of the loudest 10ms window of a rendered sequence
+    fn loudest_window_time(sequence: SimpleSequence) -> f64 {
+        let rendered = MidiPlayer::render_samples(sequence).unwrap();
+        let window = 441 * 2;
+        let (index, _) = rendered
+            .samples
+            .chunks(window)
+            .map(|chunk| chunk.iter().map(|s| s * s).sum::<f32>())
+            .enumerate()
+            .fold((0, 0.0f32), |best, (i, energy)| {
+                if energy > best.1 { (i, energy) } else { best }
+            });
+        (index as f64 + 0.5) * 0.01
+    }
+
+    #[test]
+    fn test_pre_roll_lands_slow_attack_peak_on_start_time() {
+        let pad = |pre_roll| SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(1.0),
+                duration: Some(1.5),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(220.0),
+                synth_attack: Some(0.5),
+                synth_decay: Some(0.3),
+                synth_sustain: Some(0.4),
+                synth_release: Some(0.2),
+                pre_roll,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let without = loudest_window_time(pad(None));
+        let with = loudest_window_time(pad(Some(0.5)));
+        assert!(
+            (without - 1.5).abs() < 0.03,
+            "peak without pre_roll at {}",
+            without
+        );
+        assert!((with - 1.0).abs() < 0.03, "peak with pre_roll at {}", with);
+    }
+
+    #[test]
+    fn test_musical_only_timing_uses_sequence_time_signature() {
+        // No seconds fields: bar 2 of 3/4 at 120 BPM starts at 1.5s, a half note lasts 1.0s
+        let sequence: SimpleSequence = serde_json::from_value(serde_json::json!({
+            "tempo": 120,
+            "beats_per_bar": 3,
+            "notes": [{
+                "synth_type": "sine",
+                "synth_frequency": 440.0,
+                "musical_time": {"bar": 2, "beat": 1, "tick": 0},
+                "musical_duration": "half"
+            }]
+        }))
+        .unwrap();
+        assert!(sequence.notes[0].start_time.is_none() && sequence.notes[0].duration.is_none());
+
+        let rendered = MidiPlayer::render_samples(sequence).unwrap();
+        let onset = rendered
+            .samples
+            .iter()
+            .position(|s| s.abs() > 0.01)
+            .expect("note never sounds") as f64
+            / rendered.channels as f64
+            / rendered.sample_rate as f64;
+        assert!((onset - 1.5).abs() < 0.02, "note starts at {}s", onset);
+        assert!(rendered.duration.as_secs_f64() >= 2.5);
+    }
+
+    #[test]
+    fn test_stop_fades_out_instead_of_truncating() {
+        let sequence = SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(2.0),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(220.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (mut source, _) = MidiPlayer::build_enhanced_source(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )
+        .unwrap();
+        let before: Vec<f32> = source.by_ref().take(44100).collect();
+        let peak = before.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.01);
+
+        // Simulate a stop issued after creation without bumping the global generation
+        source.stop_generation = source.stop_generation.wrapping_sub(1);
+        let tail: Vec<f32> = source.collect();
+
+        // The default 10ms fade is 441 stereo frames, well short of the 1.5s left in the note
+        assert_eq!(tail.len(), 441 * 2);
+        let window_peak = |window: &[f32]| window.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(window_peak(&tail[..220]) > 0.5 * peak);
+        assert!(window_peak(&tail[tail.len() - 20..]) < 0.05 * peak);
+        assert_eq!(tail[tail.len() - 2..], [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_stop_fades_out_rendered_playback() {
+        let mut source = RenderedSource::new(RenderedAudio {
+            samples: vec![0.5; 44100 * 2],
+            channels: 2,
+            sample_rate: 44100,
+            duration: Duration::from_secs(1),
+        });
+        assert_eq!(source.by_ref().take(1000).count(), 1000);
+
+        // Simulate a stop issued after creation without bumping the global generation
+        source.stop_generation = source.stop_generation.wrapping_sub(1);
+        let tail: Vec<f32> = source.collect();
+        assert_eq!(tail.len(), 441 * 2);
+        assert!(tail[0] > 0.49);
+        assert!(tail.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(tail[tail.len() - 1], 0.0);
+    }
+
+    #[test]
+    fn test_playback_stop_fades_only_its_own_source() {
+        let audio = || RenderedAudio {
+            samples: vec![0.5; 44100 * 2],
+            channels: 2,
+            sample_rate: 44100,
+            duration: Duration::from_secs(1),
+        };
+        let mut stopped = RenderedSource::new(audio());
+        let mut other = RenderedSource::new(audio());
+        let control = PlaybackControl::default();
+        stopped.control = control.clone();
+
+        let fade = control.stop();
+        let frames = (fade.as_secs_f64() * 44100.0) as usize;
+        let tail: Vec<f32> = stopped.collect();
+        assert_eq!(tail.len(), frames * 2);
+        assert!(tail.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(other.by_ref().count(), 44100 * 2);
+    }
+
+    #[test]
+    fn test_panic_stops_enhanced_source() {
+        let sequence = SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(1.0),
+                synth_type: Some("sine".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (mut source, _) = MidiPlayer::build_enhanced_source(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )
+        .unwrap();
+        assert!(source.next().is_some());
+
+        // Simulate a panic issued after creation without bumping the global generation,
+        // which would also stop sources built by concurrently running tests
+        source.panic_generation = source.panic_generation.wrapping_sub(1);
+        assert!(source.next().is_none());
+        assert!(source.synthesis_events.is_empty());
+    }
+
+    #[test]
+    fn test_playback_panic_silences_only_its_own_source() {
+        let source = || {
+            let sequence = SimpleSequence {
+                notes: vec![SimpleNote {
+                    start_time: Some(0.0),
+                    duration: Some(1.0),
+                    synth_type: Some("sine".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            MidiPlayer::build_enhanced_source(
+                &PresetLibrary::new(),
+                &EffectsPresetLibrary::new(),
+                sequence,
+            )
+            .unwrap()
+            .0
+        };
+        let (mut failed, mut other) = (source(), source());
+        let control = PlaybackControl::default();
+        failed.control = control.clone();
+        assert!(failed.next().is_some() && other.next().is_some());
+
+        control.panic();
+        assert!(failed.next().is_none());
+        assert!(failed.synthesis_events.is_empty());
+        assert!(other.next().is_some());
+        assert!(!other.synthesis_events.is_empty());
+    }
+
+    #[test]
+    fn test_engine_status_follows_a_playing_source_until_stopped() {
+        // Only device playbacks register, and no test plays on a device
+        assert!(!MidiPlayer::engine_status().playing);
+
+        let sequence = SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(1.0),
+                synth_type: Some("sine".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let (mut source, _) = MidiPlayer::build_enhanced_source(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )
+        .unwrap();
+        source.activity = Some(PlaybackActivity::start());
+        for _ in 0..64 {
+            source.next();
+        }
+        let status = MidiPlayer::engine_status();
+        assert!(status.playing);
+        assert_eq!(status.playbacks, 1);
+        assert!(status.voices > 0, "{:?}", status);
+
+        // Simulate a stop issued after creation without bumping the global generation,
+        // which would also fade out sources built by concurrently running tests
+        source.stop_generation = source.stop_generation.wrapping_sub(1);
+        while source.next().is_some() {}
+        let status = MidiPlayer::engine_status();
+        assert!(!status.playing, "{:?}", status);
+        assert_eq!(status.voices, 0);
+    }
+
+    fn render_bits(sequence: SimpleSequence) -> Vec<u32> {
+        let (source, _) = MidiPlayer::build_enhanced_source(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )
+        .unwrap();
+        source.take(22050).map(f32::to_bits).collect()
+    }
+
+    #[test]
+    fn test_master_seed_renders_are_bit_identical() {
+        let sequence = |seed| SimpleSequence {
+            notes: vec![
+                SimpleNote {
+                    note: Some(60),
+                    start_time: Some(0.0),
+                    duration: Some(0.4),
+                    preset_random: Some(true),
+                    ..Default::default()
+                },
+                SimpleNote {
+                    start_time: Some(0.1),
+                    duration: Some(0.3),
+                    synth_type: Some("noise".to_string()),
+                    ..Default::default()
+                },
+            ],
+            master_seed: Some(seed),
+            ..Default::default()
+        };
+
+        assert_eq!(render_bits(sequence(42)), render_bits(sequence(42)));
+        assert_ne!(render_bits(sequence(42)), render_bits(sequence(7)));
+    }
+
+    /// Left/right energy imbalance of a rendered sequence: 0.0 = centered, 1.0 = one side only
+    fn stereo_imbalance(sequence: SimpleSequence) -> f32 {
+        let (source, _) = MidiPlayer::build_enhanced_source(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            sequence,
+        )
+        .unwrap();
+        let samples: Vec<f32> = source.take(2 * 11025).collect();
+        let (left, right) = samples.chunks(2).fold((0.0, 0.0), |(l, r), frame| {
+            (l + frame[0] * frame[0], r + frame[1] * frame[1])
+        });
+        (left - right).abs() / (left + right)
+    }
+
+    #[test]
+    fn test_auto_pan_by_pitch_centers_lows_and_spreads_highs() {
+        let sequence = |pitch: u8| SimpleSequence {
+            notes: vec![SimpleNote {
+                note: Some(pitch),
+                start_time: Some(0.0),
+                duration: Some(0.25),
+                synth_type: Some("sine".to_string()),
+                ..Default::default()
+            }],
+            auto_pan_by_pitch: true,
+            ..Default::default()
+        };
+
+        let c2 = stereo_imbalance(sequence(36));
+        let c6 = stereo_imbalance(sequence(84));
+        assert!(c2 < 0.05, "C2 imbalance {}", c2);
+        assert!(c6 > c2 + 0.5, "C6 imbalance {} vs C2 {}", c6, c2);
+
+        // Explicit pan opts a note out of auto-pan
+        let mut explicit = sequence(84);
+        explicit.notes[0].pan = Some(64);
+        assert!(stereo_imbalance(explicit) < 0.05);
+    }
+
+    #[test]
+    fn test_synth_and_preset_notes_follow_their_pan() {
+        let note = |pan: u8, preset_name: Option<&str>| SimpleNote {
+            note: Some(48),
+            start_time: Some(0.0),
+            duration: Some(0.25),
+            synth_type: preset_name.is_none().then(|| "sine".to_string()),
+            preset_name: preset_name.map(str::to_string),
+            pan: Some(pan),
+            ..Default::default()
+        };
+        // Left and right energy over the first quarter second
+        let energy = |note: SimpleNote| {
+            let rendered = MidiPlayer::render_samples(SimpleSequence {
+                notes: vec![note],
+                ..Default::default()
+            })
+            .unwrap();
+            rendered
+                .samples
+                .chunks(2)
+                .take(11025)
+                .fold((0.0f32, 0.0f32), |(l, r), frame| {
+                    (l + frame[0] * frame[0], r + frame[1] * frame[1])
+                })
+        };
+
+        for preset_name in [None, Some("Minimoog Bass")] {
+            let (left, right) = energy(note(0, preset_name));
+            assert!(
+                right < left * 1e-3,
+                "{:?} at pan 0: {} {}",
+                preset_name,
+                left,
+                right
+            );
+            let (left, right) = energy(note(127, preset_name));
+            assert!(
+                left < right * 1e-3,
+                "{:?} at pan 127: {} {}",
+                preset_name,
+                left,
+                right
+            );
+            let (left, right) = energy(note(64, preset_name));
+            assert!(
+                (left - right).abs() < (left + right) * 0.05,
+                "{:?} centered",
+                preset_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_harder_synth_notes_sound_brighter() {
+        let centroid = |velocity: u8, velocity_brightness: f32| {
+            let rendered = MidiPlayer::render_samples(SimpleSequence {
+                notes: vec![SimpleNote {
+                    velocity: Some(velocity),
+                    start_time: Some(0.0),
+                    duration: Some(0.5),
+                    synth_type: Some("sawtooth".to_string()),
+                    synth_frequency: Some(220.0),
+                    synth_filter_cutoff: Some(1500.0),
+                    ..Default::default()
+                }],
+                velocity_brightness,
+                ..Default::default()
+            })
+            .unwrap();
+            // Magnitude-weighted mean frequency of 2048 Hann-windowed samples of the sustain
+            let input: Vec<f32> = rendered
+                .samples
+                .iter()
+                .step_by(rendered.channels as usize)
+                .skip(rendered.sample_rate as usize / 10)
+                .take(2048)
+                .enumerate()
+                .map(|(i, sample)| {
+                    sample * (0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / 2048.0).cos())
+                })
+                .collect();
+            let mut spectrum = vec![Default::default(); 1025];
+            fundsp::fft::real_fft(&input, &mut spectrum);
+            let bin_hz = rendered.sample_rate as f32 / 2048.0;
+            let (weighted, total) = spectrum.iter().map(|bin| bin.norm()).enumerate().fold(
+                (0.0f32, 0.0f32),
+                |(weighted, total), (bin, magnitude)| {
+                    (
+                        weighted + magnitude * bin as f32 * bin_hz,
+                        total + magnitude,
+                    )
+                },
+            );
+            weighted / total
+        };
+
+        let soft = centroid(40, 0.5);
+        let hard = centroid(120, 0.5);
+        assert!(
+            hard > soft * 1.3,
+            "centroid {} at 120 vs {} at 40",
+            hard,
+            soft
+        );
+
+        // Without the mapping, the default, velocity leaves the timbre alone
+        let soft = centroid(40, SimpleSequence::default().velocity_brightness);
+        let hard = centroid(120, SimpleSequence::default().velocity_brightness);
+        assert!((hard - soft).abs() < soft * 0.02, "{} vs {}", hard, soft);
+    }
+
+    #[test]
+    fn test_audition_schedules_one_note_with_the_preset_applied() {
+        let presets = PresetLibrary::new();
+        let effects = EffectsPresetLibrary::new();
+
+        let sequence =
+            MidiPlayer::audition_sequence(&presets, &effects, "JP-8 Strings", 60, 1.5).unwrap();
+        assert_eq!(sequence.notes.len(), 1);
+        let note = &sequence.notes[0];
+        assert_eq!(note.note, Some(60));
+        assert_eq!(note.preset_name.as_deref(), Some("JP-8 Strings"));
+        assert!(note.is_synthesis());
+        let rendered = MidiPlayer::render_samples(sequence).unwrap();
+        assert!(rendered.samples.iter().any(|sample| sample.abs() > 0.01));
+
+        let error =
+            MidiPlayer::audition_sequence(&presets, &effects, "jp8 strings", 60, 1.5).unwrap_err();
+        assert!(error.contains("Did you mean: JP-8 Strings"), "{}", error);
+    }
+
+    #[test]
+    fn test_quiet_and_loud_sequences_normalize_to_the_target_lufs() {
+        let sequence = |amplitude: f32, target_lufs: Option<f32>| SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(2.0),
+                synth_type: Some("sawtooth".to_string()),
+                synth_frequency: Some(220.0),
+                synth_amplitude: Some(amplitude),
+                ..Default::default()
+            }],
+            target_lufs,
+            ..Default::default()
+        };
+        let loudness = |sequence: SimpleSequence| {
+            let rendered = MidiPlayer::render_samples(sequence).unwrap();
+            analysis::integrated_loudness(&rendered.samples, rendered.sample_rate).unwrap()
+        };
+
+        let quiet = loudness(sequence(0.05, None));
+        let loud = loudness(sequence(0.8, None));
+        assert!(loud - quiet > 20.0, "quiet {} loud {}", quiet, loud);
+
+        for amplitude in [0.05, 0.8] {
+            let normalized = loudness(sequence(amplitude, Some(-16.0)));
+            assert!(
+                (normalized + 16.0).abs() < 1.0,
+                "amplitude {} normalized to {} LUFS",
+                amplitude,
+                normalized
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalization_scales_the_render_it_measured() {
+        let sequence = |target_lufs: Option<f32>| SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(1.0),
+                synth_type: Some("sawtooth".to_string()),
+                synth_frequency: Some(220.0),
+                synth_amplitude: Some(0.05),
+                ..Default::default()
+            }],
+            target_lufs,
+            master_limiter: false,
+            ..Default::default()
+        };
+        let plain = MidiPlayer::render_samples(sequence(None)).unwrap();
+        let normalized = MidiPlayer::render_samples(sequence(Some(-16.0))).unwrap();
+        assert_eq!(plain.samples.len(), normalized.samples.len());
+
+        let gain = analysis::loudness_normalization_gain(&plain.samples, 44100, -16.0);
+        assert!(gain > 1.0);
+        for (plain, normalized) in plain.samples.iter().zip(&normalized.samples) {
+            assert!((plain * gain - normalized).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_master_limiter_keeps_a_hot_eight_voice_chord_under_full_scale() {
+        let sequence = |master_limiter: bool| SimpleSequence {
+            notes: [110.0, 138.6, 164.8, 220.0, 277.2, 329.6, 440.0, 554.4]
+                .map(|frequency| SimpleNote {
+                    start_time: Some(0.0),
+                    duration: Some(1.0),
+                    synth_type: Some("sawtooth".to_string()),
+                    synth_frequency: Some(frequency),
+                    synth_amplitude: Some(0.9),
+                    ..Default::default()
+                })
+                .to_vec(),
+            master_limiter,
+            ..Default::default()
+        };
+        let peak = |sequence: SimpleSequence| {
+            let rendered = MidiPlayer::render_samples(sequence).unwrap();
+            rendered
+                .samples
+                .iter()
+                .fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+
+        let unlimited = peak(sequence(false));
+        assert!(unlimited > 1.0, "chord only peaks at {}", unlimited);
+        let limited = peak(sequence(true));
+        assert!(limited <= 1.0, "limited chord peaks at {}", limited);
+        // Held at the ceiling, not squashed far below it
+        assert!(
+            limited > LIMITER_CEILING * 0.9,
+            "limited chord peaks at {}",
+            limited
+        );
+    }
+
+    #[test]
+    fn test_repeated_instrument_sends_one_program_change() {
+        let mut notes: Vec<MidiNote> = (0..10)
+            .rev()
+            .map(|i| MidiNote {
+                note: 60 + i as u8,
+                velocity: 90,
+                channel: 2,
+                start_time: Duration::from_millis(i * 250),
+                duration: Duration::from_millis(200),
+                instrument: Some(40),
+                reverb: None,
+                chorus: None,
+                volume: None,
+                pan: None,
+                balance: None,
+                expression: None,
+                sustain: None,
+            })
+            .collect();
+
+        assert_eq!(MidiPlayer::merge_redundant_program_changes(&mut notes), 1);
+        let carriers: Vec<&MidiNote> = notes.iter().filter(|n| n.instrument.is_some()).collect();
+        assert_eq!(carriers.len(), 1);
+        assert_eq!(carriers[0].start_time, Duration::ZERO);
+
+        // A real switch still gets its own change
+        notes[5].instrument = Some(41);
+        notes[6].instrument = Some(40);
+        assert_eq!(MidiPlayer::merge_redundant_program_changes(&mut notes), 3);
+    }
+
+    // Tests that set the process-wide master gain take turns
+    static MASTER_GAIN_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn test_master_gain_of_half_halves_the_output() {
+        let _lock = MASTER_GAIN_LOCK.lock().unwrap();
+        let rendered = MidiPlayer::render_samples(SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(0.5),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(440.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        let play = |gain: f32| -> Vec<f32> {
+            MidiPlayer::set_master_gain(gain);
+            MasterGain::new(
+                rodio::buffer::SamplesBuffer::new(
+                    rendered.channels,
+                    rendered.sample_rate,
+                    rendered.samples.clone(),
+                ),
+                false,
+            )
+            .collect()
+        };
+
+        let full = play(1.0);
+        let half = play(0.5);
+        MidiPlayer::set_master_gain(1.0);
+
+        assert_eq!(full, rendered.samples);
+        assert!(full.iter().any(|sample| sample.abs() > 0.1));
+        for (full, half) in full.iter().zip(&half) {
+            assert_eq!(*half, full * 0.5);
+        }
+    }
+
+    #[test]
+    fn test_rendered_playback_is_limited_once() {
+        let _lock = MASTER_GAIN_LOCK.lock().unwrap();
+        // Unison notes loud enough that the limiter has to catch the peaks
+        let note = SimpleNote {
+            start_time: Some(0.0),
+            duration: Some(0.5),
+            synth_type: Some("sine".to_string()),
+            synth_frequency: Some(440.0),
+            synth_amplitude: Some(1.0),
+            ..Default::default()
+        };
+        let sequence = SimpleSequence {
+            notes: vec![note; 3],
+            ..Default::default()
+        };
+        let rendered = MidiPlayer::render_for_playback(sequence.clone()).unwrap();
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak(&rendered.samples) > 1.0, "{}", peak(&rendered.samples));
+
+        MidiPlayer::set_master_gain(1.0);
+        let played: Vec<f32> =
+            MasterGain::new(RenderedSource::new(rendered), sequence.master_limiter).collect();
+        assert!(peak(&played) <= LIMITER_CEILING + 1e-6, "{}", peak(&played));
+        // One pass of the limiter, exactly as an offline render gets
+        assert_eq!(
+            played,
+            MidiPlayer::render_samples(sequence).unwrap().samples
+        );
+    }
+
+    #[test]
+    fn test_master_limiter_follows_a_master_gain_above_unity() {
+        let _lock = MASTER_GAIN_LOCK.lock().unwrap();
+        let rendered = MidiPlayer::render_samples(SimpleSequence {
+            notes: vec![SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(0.5),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(440.0),
+                synth_amplitude: Some(0.8),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        let peak = |master_limiter: bool| -> f32 {
+            MidiPlayer::set_master_gain(2.0);
+            let played: Vec<f32> = MasterGain::new(
+                RenderedSource::new(RenderedAudio {
+                    samples: rendered.samples.clone(),
+                    channels: rendered.channels,
+                    sample_rate: rendered.sample_rate,
+                    duration: rendered.duration,
+                }),
+                master_limiter,
+            )
+            .collect();
+            MidiPlayer::set_master_gain(1.0);
+            assert_eq!(played.len(), rendered.samples.len());
+            played.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+
+        let unlimited = peak(false);
+        assert!(
+            unlimited > 1.0,
+            "doubled render only peaks at {}",
+            unlimited
+        );
+        let limited = peak(true);
+        assert!(
+            limited <= LIMITER_CEILING,
+            "limited playback peaks at {}",
+            limited
+        );
+        assert!(
+            limited > LIMITER_CEILING * 0.9,
+            "limited playback peaks at {}",
+            limited
+        );
+    }
+
+    fn glue_test_sequence(drums: bool, melody: bool, drum_glue: bool) -> SimpleSequence {
+        let mut notes = Vec::new();
+        if drums {
+            for step in 0..8 {
+                let synth_type = match step % 4 {
+                    0 => "kick",
+                    2 => "snare",
+                    _ => "hihat",
+                };
+                notes.push(SimpleNote {
+                    start_time: Some(step as f64 * 0.25),
+                    duration: Some(0.2),
+                    channel: 9,
+                    synth_type: Some(synth_type.to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+        if melody {
+            notes.push(SimpleNote {
+                start_time: Some(0.0),
+                duration: Some(2.0),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(330.0),
+                ..Default::default()
+            });
+        }
+        SimpleSequence {
+            notes,
+            drum_glue,
+            master_seed: Some(1711),
+            ..Default::default()
+        }
+    }
+
+    fn crest_factor(samples: &[f32]) -> f32 {
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        peak / rms
+    }
+
+    #[test]
+    fn test_drum_glue_compresses_drums_and_leaves_other_channels_alone() {
+        let render = |drums, melody, glue| {
+            MidiPlayer::render_samples(glue_test_sequence(drums, melody, glue))
+                .unwrap()
+                .samples
+        };
+
+        let dry_drums = render(true, false, false);
+        let glued_drums = render(true, false, true);
+        assert!(
+            crest_factor(&glued_drums) < crest_factor(&dry_drums),
+            "glued {} vs dry {}",
+            crest_factor(&glued_drums),
+            crest_factor(&dry_drums)
+        );
+
+        // The melody in a glued mix is exactly the melody on its own
+        let glued_mix = render(true, true, true);
+        let melody = render(false, true, false);
+        assert_eq!(melody, render(false, true, true));
+        for (index, ((mix, drums), melody)) in
+            glued_mix.iter().zip(&glued_drums).zip(&melody).enumerate()
+        {
+            assert!((mix - drums - melody).abs() < 1e-5, "sample {}", index);
+        }
+    }
 }
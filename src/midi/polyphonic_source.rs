@@ -58,6 +58,9 @@ pub struct RealtimePolyphonicAudioSource {
 
     /// Delta time per sample
     dt: f32,
+
+    /// Panic generation at creation; a newer generation silences this source
+    panic_generation: u64,
 }
 
 #[allow(dead_code)]
@@ -72,11 +75,7 @@ impl RealtimePolyphonicAudioSource {
                 // Convert SimpleNote to SynthParams
                 if let Ok(synth_params) = Self::convert_simple_note_to_synth_params(&event.note) {
                     // Determine priority based on note characteristics
-                    let priority = if event.note.preset_name.is_some() {
-                        100
-                    } else {
-                        50
-                    };
+                    let priority = if event.note.is_preset() { 100 } else { 50 };
 
                     // Extract note and channel info
                     let note = event.note.note;
@@ -90,13 +89,20 @@ impl RealtimePolyphonicAudioSource {
                         channel,
                         priority,
                     ) {
-                        Ok(voice_id) => {
-                            event.voice_id = Some(voice_id);
+                        Ok(allocation) => {
+                            event.voice_id = Some(allocation.voice_id);
                             tracing::debug!(
                                 "Allocated voice {} for synthesis event at {:.3}s",
-                                voice_id,
+                                allocation.voice_id,
                                 event.start_time
                             );
+                            if let Some(stolen) = allocation.stolen {
+                                tracing::debug!(
+                                    "Voice limit reached: stole voice {} for voice {}",
+                                    stolen,
+                                    allocation.voice_id
+                                );
+                            }
                         }
                         Err(e) => {
                             tracing::warn!("Failed to allocate voice for synthesis event: {}", e);
@@ -143,8 +149,8 @@ impl RealtimePolyphonicAudioSource {
     /// Convert SimpleNote to SynthParams (moved from player.rs)
     fn convert_simple_note_to_synth_params(note: &SimpleNote) -> Result<SynthParams> {
         use crate::expressive::{
-            EffectParams, EffectType, EnvelopeParams, FilterParams, FilterType, NoiseColor,
-            SynthParams, SynthType,
+            EffectParams, EffectType, EnvelopeCurve, EnvelopeParams, FilterParams, FilterType, Lfo,
+            NoiseColor, SynthParams, SynthType,
         };
 
         let synth_type_str = note
@@ -157,11 +163,20 @@ impl RealtimePolyphonicAudioSource {
             "sine" => SynthType::Sine,
             "square" => SynthType::Square {
                 pulse_width: note.synth_pulse_width.unwrap_or(0.5),
+                pwm: note.synth_pwm,
             },
             "sawtooth" => SynthType::Sawtooth,
             "triangle" => SynthType::Triangle,
             "noise" => SynthType::Noise {
-                color: NoiseColor::White,
+                color: note
+                    .synth_noise_color
+                    .as_deref()
+                    .and_then(NoiseColor::from_name)
+                    .unwrap_or_default(),
+            },
+            "morph" => SynthType::Morph {
+                position: note.synth_morph_position.unwrap_or(0.0),
+                end_position: note.synth_morph_end,
             },
             "fm" => SynthType::FM {
                 modulator_freq: note.synth_modulator_freq.unwrap_or(440.0),
@@ -248,6 +263,11 @@ impl RealtimePolyphonicAudioSource {
             decay: note.synth_decay.unwrap_or(0.1),
             sustain: note.synth_sustain.unwrap_or(0.7),
             release: note.synth_release.unwrap_or(0.3),
+            curve: note
+                .synth_envelope_curve
+                .as_deref()
+                .and_then(EnvelopeCurve::from_name)
+                .unwrap_or_default(),
         };
 
         // Create filter if specified
@@ -263,6 +283,9 @@ impl RealtimePolyphonicAudioSource {
                 cutoff: note.synth_filter_cutoff.unwrap_or(1000.0),
                 resonance: note.synth_filter_resonance.unwrap_or(0.1),
                 filter_type,
+                soft_start: note
+                    .synth_filter_soft_start
+                    .unwrap_or(crate::expressive::DEFAULT_FILTER_SOFT_START),
             })
         } else {
             None
@@ -302,7 +325,7 @@ impl RealtimePolyphonicAudioSource {
         // Process universal effects from the new effects system
         if let Some(universal_effects) = &note.effects {
             for effect_config in universal_effects {
-                if effect_config.enabled {
+                if !effect_config.is_bypassed() {
                     // Convert EffectConfig to EffectParams for audio processing
                     match &effect_config.effect {
                         crate::midi::EffectType::Reverb {
@@ -310,6 +333,8 @@ impl RealtimePolyphonicAudioSource {
                             dampening: _,
                             wet_level: _,
                             pre_delay: _,
+                            modulation: _,
+                            algorithm: _,
                         } => {
                             effects.push(EffectParams {
                                 effect_type: EffectType::Reverb,
@@ -356,9 +381,12 @@ impl RealtimePolyphonicAudioSource {
             frequency,
             amplitude: note.synth_amplitude.unwrap_or(0.7),
             duration: note.duration.unwrap_or(1.0) as f32,
+            phase: note.synth_phase.unwrap_or(0.0),
             envelope,
             filter,
             effects,
+            vibrato: Lfo::new(note.synth_vibrato_rate, note.synth_vibrato_depth),
+            tremolo: Lfo::new(note.synth_tremolo_rate, note.synth_tremolo_depth),
         })
     }
 }
@@ -367,6 +395,15 @@ impl Iterator for RealtimePolyphonicAudioSource {
     type Item = f32;
 
     fn next(&mut self) -> Option<Self::Item> {
+        // Stop immediately if a panic was requested after this source was created
+        if super::MidiPlayer::panic_generation() != self.panic_generation {
+            self.voice_manager.all_notes_off();
+            if let Some(ref mut oxisynth) = self.oxisynth_source {
+                oxisynth.panic();
+            }
+            return None;
+        }
+
         let current_time =
             Duration::from_secs_f32(self.current_sample as f32 / self.sample_rate as f32);
 
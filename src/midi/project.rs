@@ -0,0 +1,79 @@
+use super::{ExtendedSequence, SequencePattern};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Project file format version written by `save`; newer files are refused on load
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A saved session: every defined pattern plus optional context for picking up the work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Working tempo of the session, if one was set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tempo: Option<u32>,
+    pub patterns: Vec<SequencePattern>,
+    /// Sequences played most recently, oldest first
+    #[serde(default)]
+    pub recent_sequences: Vec<ExtendedSequence>,
+}
+
+fn default_version() -> u32 {
+    PROJECT_VERSION
+}
+
+impl Project {
+    /// Check the version, pattern names and every pattern's notes
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version > PROJECT_VERSION {
+            return Err(format!(
+                "Project version {} is newer than this mcp-muse supports ({})",
+                self.version, PROJECT_VERSION
+            ));
+        }
+        let mut names = HashSet::new();
+        for pattern in &self.patterns {
+            if !names.insert(pattern.name.as_str()) {
+                return Err(format!("Pattern '{}' appears more than once", pattern.name));
+            }
+            if pattern.notes.is_empty() {
+                return Err(format!("Pattern '{}' has no notes", pattern.name));
+            }
+            pattern
+                .validate()
+                .and_then(|()| pattern.validate_notes())
+                .map_err(|e| format!("Pattern '{}': {}", pattern.name, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create project directory {:?}: {}", dir, e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize project: {}", e))?;
+        fs::write(path, content)
+            .map_err(|e| format!("Failed to write project to {:?}: {}", path, e))?;
+        tracing::info!(
+            "Saved project with {} patterns to {:?}",
+            self.patterns.len(),
+            path
+        );
+        Ok(())
+    }
+
+    /// Read and validate a project file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read project {:?}: {}", path, e))?;
+        let project: Project = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project {:?}: {}", path, e))?;
+        project.validate()?;
+        Ok(project)
+    }
+}
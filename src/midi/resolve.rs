@@ -0,0 +1,1101 @@
+use super::{
+    SimpleNote, SimpleSequence, TempoMap, swing_seconds, validate_chord_track, validate_swing,
+    validate_tempo_changes,
+};
+use crate::expressive::{EffectsPresetLibrary, MasterSeedScope, PresetLibrary};
+use crate::midi::analysis::validate_target_lufs;
+use crate::midi::humanize::validate_drum_humanize;
+use crate::midi::player::validate_tail_cutoff_db;
+use serde::{Deserialize, Serialize};
+
+/// Longest sequence length `fit_duration` can ask for, in seconds
+const MAX_FIT_DURATION: f64 = 600.0;
+
+/// Longest `min_release` a sequence can ask for, in seconds
+const MAX_MIN_RELEASE: f64 = 2.0;
+
+/// How strongly velocity brightens synthesis notes unless a sequence asks for it: not at
+/// all, so notes keep the filters they were given
+pub const DEFAULT_VELOCITY_BRIGHTNESS: f32 = 0.0;
+/// Velocity at which the brightness mapping leaves the cutoff where it is
+const NEUTRAL_BRIGHTNESS_VELOCITY: f32 = 80.0;
+/// Cutoff shift in octaves at full velocity_brightness, between neutral and velocity 127
+const MAX_BRIGHTNESS_OCTAVES: f32 = 1.5;
+/// Lowpass cutoff given to unfiltered synthesis notes at neutral velocity, high enough to
+/// leave them nearly untouched
+const OPEN_BRIGHTNESS_CUTOFF: f32 = 12000.0;
+
+/// What to do with notes that pattern offsets or other transforms place before time zero
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeStartMode {
+    /// Move them to 0.0 and log which notes moved
+    #[default]
+    Clamp,
+    /// Fail the sequence, naming the notes
+    Reject,
+}
+
+/// One sequence note after resolution
+struct ResolvedNote {
+    /// The note, or its hits when it is a roll
+    notes: Vec<SimpleNote>,
+    /// Problems playback works around and a dry run reports
+    problems: Vec<String>,
+    /// Whether its timing resolved to before time zero (it has been clamped to 0.0)
+    before_zero: bool,
+}
+
+/// One resolved note of a dry run: what would sound, where and for how long
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEvent {
+    /// "midi", "synthesis" or "r2d2"
+    pub kind: &'static str,
+    pub start_time: f64,
+    pub duration: f64,
+    pub channel: u8,
+    /// MIDI note number, when the note has one
+    pub note: Option<u8>,
+}
+
+/// Resolve and validate a sequence without an audio device or SoundFont: presets, effects,
+/// musical timing and sequence-level processing are applied exactly as for playback, but
+/// anything playback would skip or drop with a warning is an error here. Patterns are
+/// resolved beforehand with `ExtendedSequence::resolve_patterns`.
+pub fn dry_run(sequence: &SimpleSequence) -> Result<Vec<TimelineEvent>, String> {
+    validate_drum_humanize(sequence.drum_humanize)?;
+    validate_chord_track(&sequence.chord_track)?;
+    validate_target_lufs(sequence.target_lufs)?;
+    validate_fit_duration(sequence.fit_duration)?;
+    validate_velocity_brightness(sequence.velocity_brightness)?;
+    validate_min_release(sequence.min_release)?;
+    validate_tail_cutoff_db(sequence.tail_cutoff_db)?;
+    validate_swing(sequence.swing)?;
+    validate_tempo_changes(&sequence.tempo_changes, sequence.beats_per_bar)?;
+
+    let _seed_scope = MasterSeedScope::new(sequence.master_seed);
+    let preset_library = PresetLibrary::new();
+    let effects_library = EffectsPresetLibrary::new();
+
+    let mut kinds = Vec::with_capacity(sequence.notes.len());
+    let mut resolved = Vec::with_capacity(sequence.notes.len());
+    let mut before_zero = Vec::new();
+    for (index, note) in sequence.notes.iter().enumerate() {
+        let fail = |e: String| format!("Note {}: {}", index + 1, e);
+        note.validate_timing().map_err(fail)?;
+        let note = resolve_note(&preset_library, &effects_library, sequence, note.clone());
+        if let Some(problem) = note.problems.into_iter().next() {
+            return Err(fail(problem));
+        }
+        if note.before_zero {
+            before_zero.push(index);
+        }
+
+        for note in note.notes {
+            let kind = if note.is_r2d2() {
+                note.validate_r2d2().map_err(fail)?;
+                "r2d2"
+            } else if note.is_synthesis() {
+                note.validate_synthesis().map_err(fail)?;
+                "synthesis"
+            } else if note.note.is_some() {
+                "midi"
+            } else {
+                return Err(fail("MIDI note has no note number".to_string()));
+            };
+            kinds.push(kind);
+            resolved.push(note);
+        }
+    }
+    report_before_zero(sequence.negative_start, &before_zero)?;
+
+    if let Some(target) = sequence.fit_duration {
+        fit_to_duration(&mut resolved, target);
+    }
+    let mut timeline: Vec<TimelineEvent> = kinds
+        .into_iter()
+        .zip(resolved)
+        .map(|(kind, note)| TimelineEvent {
+            kind,
+            start_time: note.start_time.unwrap_or(0.0),
+            duration: note.duration.unwrap_or(1.0),
+            channel: note.channel,
+            note: note.note,
+        })
+        .collect();
+    timeline.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    Ok(timeline)
+}
+
+/// Resolve every note of a sequence for playback. Problems are logged and the note is
+/// kept without the part that failed, so one bad preset does not silence a sequence.
+/// Notes that land before time zero fail the sequence only with `NegativeStartMode::Reject`.
+pub fn resolve_notes(
+    preset_library: &PresetLibrary,
+    effects_library: &EffectsPresetLibrary,
+    sequence: &SimpleSequence,
+) -> Result<Vec<SimpleNote>, String> {
+    if let Err(e) = validate_min_release(sequence.min_release) {
+        tracing::warn!("Ignoring min_release: {}", e);
+    }
+    let mut notes = Vec::with_capacity(sequence.notes.len());
+    let mut before_zero = Vec::new();
+    for (index, note) in sequence.notes.iter().enumerate() {
+        let note = resolve_note(preset_library, effects_library, sequence, note.clone());
+        for problem in note.problems {
+            tracing::warn!("{}", problem);
+        }
+        if note.before_zero {
+            before_zero.push(index);
+        }
+        notes.extend(note.notes);
+    }
+    report_before_zero(sequence.negative_start, &before_zero)?;
+
+    // Out-of-range targets are dropped like other invalid sequence settings
+    match validate_fit_duration(sequence.fit_duration) {
+        Ok(()) => {
+            if let Some(target) = sequence.fit_duration {
+                fit_to_duration(&mut notes, target);
+            }
+        }
+        Err(e) => tracing::warn!("Ignoring fit_duration: {}", e),
+    }
+    Ok(notes)
+}
+
+/// Log or reject the notes (by index in the sequence) that resolved to before time zero.
+/// Messages number notes from 1, as request validation does.
+fn report_before_zero(mode: NegativeStartMode, indices: &[usize]) -> Result<(), String> {
+    match before_zero_problem(mode, indices) {
+        Some(Ok(warning)) => {
+            tracing::warn!("{}", warning);
+            Ok(())
+        }
+        Some(Err(error)) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// The warning (clamped) or error (rejected) for notes that resolved to before time zero
+fn before_zero_problem(
+    mode: NegativeStartMode,
+    indices: &[usize],
+) -> Option<Result<String, String>> {
+    if indices.is_empty() {
+        return None;
+    }
+    let listed = indices
+        .iter()
+        .map(|index| (index + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(match mode {
+        NegativeStartMode::Clamp => Ok(format!(
+            "Notes {} resolved to before time zero and were moved to 0.0",
+            listed
+        )),
+        NegativeStartMode::Reject => Err(format!(
+            "Notes {} start before time zero after pattern offsets and timing transforms",
+            listed
+        )),
+    })
+}
+
+/// What playback would make of a sequence, found without playing it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SequenceCheck {
+    /// Messages playback would fail with
+    pub errors: Vec<String>,
+    /// Problems playback would log and work around
+    pub warnings: Vec<String>,
+    /// Notes playback would schedule, counting each roll hit
+    pub resolved_notes: usize,
+}
+
+/// Resolve a sequence the way playback does and sort what goes wrong into the errors it
+/// would fail with and the problems it would only log, unlike `dry_run` which fails on both.
+/// Patterns are resolved beforehand with `ExtendedSequence::resolve_patterns`.
+pub fn check_playback(sequence: &SimpleSequence) -> SequenceCheck {
+    let _seed_scope = MasterSeedScope::new(sequence.master_seed);
+    let preset_library = PresetLibrary::new();
+    let effects_library = EffectsPresetLibrary::new();
+
+    let mut check = SequenceCheck::default();
+    let mut before_zero = Vec::new();
+    for (index, note) in sequence.notes.iter().enumerate() {
+        let note = resolve_note(&preset_library, &effects_library, sequence, note.clone());
+        check.warnings.extend(
+            note.problems
+                .into_iter()
+                .map(|problem| format!("Note {}: {}", index + 1, problem)),
+        );
+        if note.before_zero {
+            before_zero.push(index);
+        }
+
+        for note in note.notes {
+            if note.is_r2d2() {
+                if let Err(e) = note.validate_r2d2() {
+                    check.errors.push(format!("Invalid R2D2 note: {}", e));
+                }
+            } else if note.is_synthesis() {
+                if let Err(e) = note.validate_synthesis() {
+                    check.errors.push(format!("Invalid synthesis note: {}", e));
+                }
+            } else if note.note.is_none() {
+                check.warnings.push(format!(
+                    "Note {}: MIDI note has no note number and is skipped",
+                    index + 1
+                ));
+                continue;
+            }
+            check.resolved_notes += 1;
+        }
+    }
+    match before_zero_problem(sequence.negative_start, &before_zero) {
+        Some(Ok(warning)) => check.warnings.push(warning),
+        // Playback rejects these before it looks at any single note
+        Some(Err(error)) => check.errors.insert(0, error),
+        None => {}
+    }
+    check
+}
+
+pub fn validate_fit_duration(target: Option<f64>) -> Result<(), String> {
+    match target {
+        Some(target) if !(target.is_finite() && target > 0.0 && target <= MAX_FIT_DURATION) => {
+            Err(format!(
+                "fit_duration must be greater than 0 and at most {} seconds, got {}",
+                MAX_FIT_DURATION, target
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_min_release(min_release: Option<f64>) -> Result<(), String> {
+    match min_release {
+        Some(seconds) if !(0.0..=MAX_MIN_RELEASE).contains(&seconds) => Err(format!(
+            "min_release must be between 0 and {} seconds, got {}",
+            MAX_MIN_RELEASE, seconds
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_velocity_brightness(amount: f32) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&amount) {
+        return Err(format!(
+            "velocity_brightness must be between 0.0 and 1.0, got {}",
+            amount
+        ));
+    }
+    Ok(())
+}
+
+/// Move a synthesis note's filter cutoff with its velocity, so harder notes sound brighter
+/// like real instruments. Up to 1.5 octaves either way of velocity 80 at `amount` 1.0;
+/// unfiltered notes get a lowpass that starts nearly open. Nothing changes at `amount` 0.0
+/// (the default), and highpass filters and notes without a velocity are left alone.
+fn apply_velocity_brightness(note: &mut SimpleNote, amount: f32) {
+    let Some(velocity) = note.velocity else {
+        return;
+    };
+    if amount <= 0.0
+        || !note.is_synthesis()
+        || note.synth_filter_type.as_deref() == Some("highpass")
+    {
+        return;
+    }
+    let octaves = amount * MAX_BRIGHTNESS_OCTAVES * (velocity as f32 - NEUTRAL_BRIGHTNESS_VELOCITY)
+        / (127.0 - NEUTRAL_BRIGHTNESS_VELOCITY);
+    let cutoff = match (note.synth_filter_type.is_some(), note.synth_filter_cutoff) {
+        (_, Some(cutoff)) => cutoff,
+        // A filter type without a cutoff plays at the synth's 1 kHz default
+        (true, None) => 1000.0,
+        (false, None) => OPEN_BRIGHTNESS_CUTOFF,
+    };
+    note.synth_filter_cutoff = Some((cutoff * octaves.exp2()).clamp(20.0, 20000.0));
+}
+
+/// Scale every start time and duration by one factor so the last note ends at `target`
+/// seconds. Pitches and envelope shapes are untouched; an empty or zero-length sequence
+/// is left as is.
+fn fit_to_duration(notes: &mut [SimpleNote], target: f64) {
+    let length = notes
+        .iter()
+        .map(|note| note.start_time.unwrap_or(0.0) + note.duration.unwrap_or(1.0))
+        .fold(0.0, f64::max);
+    if length <= 0.0 {
+        return;
+    }
+    let scale = target / length;
+    for note in notes.iter_mut() {
+        note.start_time = Some(note.start_time.unwrap_or(0.0) * scale);
+        note.duration = Some(note.duration.unwrap_or(1.0) * scale);
+    }
+}
+
+/// Beats from the start of the sequence to where `note` starts, before swing
+fn note_start_beat(note: &SimpleNote, tempo_map: &TempoMap) -> f64 {
+    tempo_map.beat_at(note.start_time.unwrap_or(0.0))
+}
+
+/// Apply presets, validate effects and resolve musical timing and sequence-level
+/// processing for one note, returning it (or its roll hits) with any problems found along the way
+fn resolve_note(
+    preset_library: &PresetLibrary,
+    effects_library: &EffectsPresetLibrary,
+    sequence: &SimpleSequence,
+    mut note: SimpleNote,
+) -> ResolvedNote {
+    let mut problems = Vec::new();
+
+    // Continue with the note without preset - don't fail completely
+    if let Err(e) = apply_preset_to_note(preset_library, effects_library, &mut note) {
+        problems.push(format!("Failed to apply preset to note: {}", e));
+    }
+
+    // Continue with an invalid note's effects dropped - don't fail completely
+    if let Err(e) = note.validate_effects() {
+        problems.push(format!("Invalid effects: {}", e));
+        note.effects = None;
+        note.effects_preset = None;
+    }
+
+    let timeline = TempoMap::new(
+        sequence.tempo,
+        sequence.beats_per_bar,
+        &sequence.tempo_changes,
+    );
+    // Only sequences with tempo changes leave the constant-tempo conversions below
+    let tempo_map = (!sequence.tempo_changes.is_empty()).then_some(&timeline);
+
+    // Convert musical_time to start_time if present
+    if note.start_time.is_none()
+        && let Some(musical_time) = &note.musical_time
+    {
+        // 480 ticks per beat, in the sequence's time signature
+        let tempo = sequence.tempo;
+        note.start_time = Some(match &tempo_map {
+            Some(tempo_map) => musical_time.to_seconds_in(tempo_map, sequence.beats_per_bar, 480),
+            None => musical_time.to_seconds(tempo, sequence.beats_per_bar, 480),
+        });
+
+        tracing::debug!(
+            "Converted musical_time {{bar:{}, beat:{}, tick:{}}} to start_time={:.3}s at tempo={}",
+            musical_time.bar,
+            musical_time.beat,
+            musical_time.tick,
+            note.start_time.unwrap(),
+            tempo
+        );
+    }
+
+    // Convert musical_duration to duration if present
+    if note.duration.is_none()
+        && let Some(ref musical_duration) = note.musical_duration
+    {
+        let tempo = sequence.tempo;
+
+        // Convert musical duration to seconds
+        let duration_secs = match &tempo_map {
+            Some(tempo_map) => {
+                let start_beat = note_start_beat(&note, tempo_map);
+                musical_duration.to_seconds_in(tempo_map, sequence.beats_per_bar, start_beat)
+            }
+            None => musical_duration.to_seconds(tempo, sequence.beats_per_bar),
+        };
+
+        note.duration = Some(duration_secs);
+
+        tracing::debug!(
+            "Converted musical_duration {:?} to duration={:.3}s at tempo={}",
+            musical_duration,
+            note.duration.unwrap(),
+            tempo
+        );
+    }
+
+    // Beats-only durations follow the tempo map from where the note starts
+    if let Some(tempo_map) = &tempo_map
+        && note.duration.is_none()
+        && note.musical_duration.is_none()
+        && let Some(beats) = note.beats
+    {
+        let start_beat = note_start_beat(&note, tempo_map);
+        note.duration =
+            Some(tempo_map.seconds_at(start_beat + beats) - tempo_map.seconds_at(start_beat));
+    }
+
+    note.start_time = note.start_time.map(|start_time| {
+        swing_seconds(
+            start_time,
+            &timeline,
+            sequence.swing,
+            sequence.swing_subdivision,
+        )
+    });
+    note.apply_beats(sequence.tempo);
+    note.apply_articulation();
+
+    // Checked before pre_roll and humanize, which keep their own small shifts above zero
+    let before_zero = note.start_time.is_some_and(|start_time| start_time < 0.0);
+    if before_zero {
+        note.start_time = Some(0.0);
+    }
+
+    // Play the note as a single hit rather than an out-of-range roll
+    if let Some(roll) = &note.roll_accelerate
+        && let Err(e) = roll.validate()
+    {
+        problems.push(format!("Invalid roll: {}", e));
+        note.roll_accelerate = None;
+    }
+    if let Some(pick) = &note.tremolo_pick
+        && let Err(e) = pick.validate()
+    {
+        problems.push(format!("Invalid tremolo_pick: {}", e));
+        note.tremolo_pick = None;
+    }
+
+    let notes = note
+        .expand_roll()
+        .into_iter()
+        .flat_map(|note| note.expand_tremolo_pick(&timeline))
+        .map(|mut note| {
+            note.apply_chord_track(&sequence.chord_track, &timeline, sequence.beats_per_bar);
+            note.apply_channel_config(&sequence.channels);
+            note.apply_program_changes(&sequence.program_changes);
+            note.apply_pre_roll();
+            apply_velocity_brightness(&mut note, sequence.velocity_brightness);
+
+            if let Some(amount) = sequence.drum_humanize {
+                note.humanize_drum(amount);
+            }
+
+            // Out-of-range values are dropped with a warning in resolve_notes
+            if let Some(min_release) = sequence.min_release
+                && validate_min_release(sequence.min_release).is_ok()
+            {
+                note.apply_min_release(min_release);
+            }
+            note
+        })
+        .collect();
+
+    ResolvedNote {
+        notes,
+        problems,
+        before_zero,
+    }
+}
+
+/// Apply preset configuration to a SimpleNote
+pub fn apply_preset_to_note(
+    preset_library: &PresetLibrary,
+    effects_library: &EffectsPresetLibrary,
+    note: &mut crate::midi::SimpleNote,
+) -> Result<(), String> {
+    // Skip if no preset parameters are specified
+    if !note.is_preset() {
+        return Ok(());
+    }
+
+    // Load preset based on parameters; a morph takes the rest of its preset from the side
+    // it is nearer
+    let preset = if let Some(morph) = &note.preset_morph {
+        let nearer = if morph.amount < 0.5 {
+            &morph.from
+        } else {
+            &morph.to
+        };
+        preset_library
+            .load_preset(nearer)
+            .ok_or_else(|| format!("Preset '{}' not found", nearer))?
+    } else if let Some(preset_name) = &note.preset_name {
+        // Load specific preset by name
+        preset_library
+            .load_preset(preset_name)
+            .ok_or_else(|| format!("Preset '{}' not found", preset_name))?
+    } else if let Some(category_str) = &note.preset_category {
+        // Load random preset from category
+        let category = crate::expressive::PresetCategory::from_name(category_str)?;
+
+        preset_library
+            .get_random_preset(Some(category))
+            .ok_or_else(|| format!("No presets found in category '{}'", category_str))?
+    } else if note.preset_random.unwrap_or(false) {
+        // Load completely random preset
+        preset_library
+            .get_random_preset(None)
+            .ok_or("No presets available for random selection")?
+    } else {
+        return Ok(()); // No valid preset selection
+    };
+
+    // Apply preset variation if specified
+    let synth_params = if let Some(morph) = &note.preset_morph {
+        preset_library
+            .morph(&morph.from, &morph.to, morph.amount)
+            .ok_or_else(|| {
+                format!(
+                    "Preset morph needs two known presets, got '{}' and '{}'",
+                    morph.from, morph.to
+                )
+            })?
+    } else if let Some(variation_name) = &note.preset_variation {
+        preset_library
+            .apply_variation(&preset.name, variation_name)
+            .unwrap_or_else(|| preset.synth_params.clone())
+    } else if let Some(amount) = note.preset_randomize_amount {
+        let seed = note
+            .preset_randomize_seed
+            .unwrap_or_else(|| crate::expressive::with_rng(|rng| rng.next_u64()));
+        preset_library
+            .randomize(&preset.name, amount, seed)
+            .unwrap_or_else(|| preset.synth_params.clone())
+    } else {
+        preset.synth_params.clone()
+    };
+
+    // Apply preset parameters to the note (convert from SynthParams to SimpleNote fields)
+    note.synth_type = Some(
+        match &synth_params.synth_type {
+            crate::expressive::SynthType::Sine => "sine",
+            crate::expressive::SynthType::Square { .. } => "square",
+            crate::expressive::SynthType::Sawtooth => "sawtooth",
+            crate::expressive::SynthType::Triangle => "triangle",
+            crate::expressive::SynthType::Noise { .. } => "noise",
+            crate::expressive::SynthType::Morph { .. } => "morph",
+            crate::expressive::SynthType::FM { .. } => "fm",
+            crate::expressive::SynthType::DX7FM { .. } => "dx7fm",
+            crate::expressive::SynthType::Granular { .. } => "granular",
+            crate::expressive::SynthType::Wavetable { .. } => "wavetable",
+            crate::expressive::SynthType::Kick { .. } => "kick",
+            crate::expressive::SynthType::Snare { .. } => "snare",
+            crate::expressive::SynthType::HiHat { .. } => "hihat",
+            crate::expressive::SynthType::Cymbal { .. } => "cymbal",
+            crate::expressive::SynthType::Swoosh { .. } => "swoosh",
+            crate::expressive::SynthType::Zap { .. } => "zap",
+            crate::expressive::SynthType::Chime { .. } => "chime",
+            crate::expressive::SynthType::Burst { .. } => "burst",
+            crate::expressive::SynthType::Pad { .. } => "pad",
+            crate::expressive::SynthType::Texture { .. } => "texture",
+            crate::expressive::SynthType::Drone { .. } => "drone",
+        }
+        .to_string(),
+    );
+
+    // Apply envelope parameters
+    note.synth_attack = Some(synth_params.envelope.attack);
+    note.synth_decay = Some(synth_params.envelope.decay);
+    note.synth_sustain = Some(synth_params.envelope.sustain);
+    note.synth_release = Some(synth_params.envelope.release);
+    note.synth_envelope_curve = Some(synth_params.envelope.curve.name().to_string());
+
+    // Apply amplitude
+    note.synth_amplitude = Some(synth_params.amplitude);
+
+    // Apply filter parameters if present
+    if let Some(filter) = &synth_params.filter {
+        note.synth_filter_type = Some(
+            match filter.filter_type {
+                crate::expressive::FilterType::LowPass => "lowpass",
+                crate::expressive::FilterType::HighPass => "highpass",
+                crate::expressive::FilterType::BandPass => "bandpass",
+            }
+            .to_string(),
+        );
+        note.synth_filter_cutoff = Some(filter.cutoff);
+        note.synth_filter_resonance = Some(filter.resonance);
+    }
+
+    // Apply effects
+    for effect in &synth_params.effects {
+        match &effect.effect_type {
+            crate::expressive::EffectType::Reverb => {
+                note.synth_reverb = Some(effect.intensity);
+            }
+            crate::expressive::EffectType::Chorus => {
+                note.synth_chorus = Some(effect.intensity);
+            }
+            crate::expressive::EffectType::Delay { delay_time } => {
+                note.synth_delay = Some(effect.intensity);
+                note.synth_delay_time = Some(*delay_time);
+            }
+        }
+    }
+
+    // Preset modulation, unless the note sets its own
+    if let Some(vibrato) = synth_params.vibrato
+        && note.synth_vibrato_rate.is_none()
+        && note.synth_vibrato_depth.is_none()
+    {
+        note.synth_vibrato_rate = Some(vibrato.rate);
+        note.synth_vibrato_depth = Some(vibrato.depth);
+    }
+    if let Some(tremolo) = synth_params.tremolo
+        && note.synth_tremolo_rate.is_none()
+        && note.synth_tremolo_depth.is_none()
+    {
+        note.synth_tremolo_rate = Some(tremolo.rate);
+        note.synth_tremolo_depth = Some(tremolo.depth);
+    }
+
+    // Apply synthesis-specific parameters based on synth type
+    match &synth_params.synth_type {
+        crate::expressive::SynthType::Noise { color } => {
+            note.synth_noise_color = Some(color.name().to_string());
+        }
+        crate::expressive::SynthType::Square { pulse_width, pwm } => {
+            note.synth_pulse_width = Some(*pulse_width);
+            note.synth_pwm = *pwm;
+        }
+        crate::expressive::SynthType::FM {
+            modulator_freq,
+            modulation_index,
+        } => {
+            note.synth_modulator_freq = Some(*modulator_freq);
+            note.synth_modulation_index = Some(*modulation_index);
+        }
+        crate::expressive::SynthType::Granular { grain_size, .. } => {
+            note.synth_grain_size = Some(*grain_size);
+        }
+        crate::expressive::SynthType::Texture { roughness, .. } => {
+            note.synth_texture_roughness = Some(*roughness);
+        }
+        crate::expressive::SynthType::Morph {
+            position,
+            end_position,
+        } => {
+            note.synth_morph_position = Some(*position);
+            note.synth_morph_end = *end_position;
+        }
+        crate::expressive::SynthType::Drone {
+            harmonics,
+            evolution_rate,
+            detune,
+            ..
+        } => {
+            note.synth_drone_harmonics = Some(*harmonics);
+            note.synth_drone_evolution_rate = Some(*evolution_rate);
+            note.synth_drone_detune = Some(*detune);
+        }
+        _ => {} // Other synth types don't have specific parameters to set
+    }
+
+    // Apply signature effects from preset
+    if note.effects.is_none() && !preset.signature_effects.is_empty() {
+        note.effects = Some(preset.signature_effects.clone());
+    }
+
+    tracing::info!("Applied preset '{}' to note", preset.name);
+
+    // Apply effects preset if specified
+    if let Some(effects_preset_name) = &note.effects_preset {
+        if let Some(effects) = effects_library.get_preset(effects_preset_name) {
+            // Merge with existing effects or replace
+            if let Some(existing_effects) = &mut note.effects {
+                existing_effects.extend(effects.clone());
+            } else {
+                note.effects = Some(effects.clone());
+            }
+            tracing::info!("Applied effects preset '{}' to note", effects_preset_name);
+        } else {
+            tracing::warn!("Effects preset '{}' not found", effects_preset_name);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixed_sequence_resolves_to_a_timeline_without_audio() {
+        let mut sequence: SimpleSequence =
+            serde_json::from_str(include_str!("../../examples/mixed_victory_fanfare.json"))
+                .unwrap();
+        sequence.notes.push(SimpleNote {
+            preset_name: Some("Minimoog Bass".to_string()),
+            note: Some(36),
+            start_time: None,
+            musical_time: Some(crate::midi::MusicalTime {
+                bar: 2,
+                beat: 1,
+                tick: 0,
+            }),
+            beats: Some(2.0),
+            ..Default::default()
+        });
+
+        let timeline = dry_run(&sequence).unwrap();
+        assert_eq!(timeline.len(), 7);
+        assert!(timeline.iter().any(|event| event.kind == "midi"));
+        assert!(timeline.iter().any(|event| event.kind == "r2d2"));
+        // The preset turns the bass into a synthesis note at bar 2 (2.0s at 120 BPM)
+        let bass = timeline
+            .iter()
+            .find(|event| event.kind == "synthesis")
+            .unwrap();
+        assert!((bass.start_time - 2.0).abs() < 1e-9);
+        assert!((bass.duration - 1.0).abs() < 1e-9);
+        assert!(
+            timeline
+                .windows(2)
+                .all(|pair| pair[0].start_time <= pair[1].start_time)
+        );
+    }
+
+    #[test]
+    fn test_dry_run_reports_what_playback_would_skip() {
+        let sequence = SimpleSequence {
+            notes: vec![
+                SimpleNote {
+                    note: Some(60),
+                    start_time: Some(0.0),
+                    duration: Some(1.0),
+                    ..Default::default()
+                },
+                SimpleNote {
+                    preset_name: Some("No Such Preset".to_string()),
+                    note: Some(60),
+                    start_time: Some(1.0),
+                    duration: Some(1.0),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let error = dry_run(&sequence).unwrap_err();
+        assert!(error.starts_with("Note 2:"), "{}", error);
+        assert!(error.contains("No Such Preset"), "{}", error);
+    }
+
+    #[test]
+    fn test_fit_duration_doubles_a_five_second_sequence() {
+        let notes: Vec<SimpleNote> = [(60, 0.0, 1.0), (64, 1.5, 2.0), (67, 4.0, 1.0)]
+            .iter()
+            .map(|&(pitch, start_time, duration)| SimpleNote {
+                note: Some(pitch),
+                start_time: Some(start_time),
+                duration: Some(duration),
+                ..Default::default()
+            })
+            .collect();
+        let sequence = |fit_duration| SimpleSequence {
+            notes: notes.clone(),
+            fit_duration,
+            ..Default::default()
+        };
+        let preset_library = PresetLibrary::new();
+        let effects_library = EffectsPresetLibrary::new();
+        let resolve =
+            |sequence| resolve_notes(&preset_library, &effects_library, &sequence).unwrap();
+
+        let original = resolve(sequence(None));
+        let fitted = resolve(sequence(Some(10.0)));
+        assert_eq!(fitted.len(), original.len());
+        for (before, after) in original.iter().zip(&fitted) {
+            assert_eq!(after.note, before.note);
+            assert_eq!(after.start_time, before.start_time.map(|t| t * 2.0));
+            assert_eq!(after.duration, before.duration.map(|d| d * 2.0));
+        }
+
+        let timeline = dry_run(&sequence(Some(10.0))).unwrap();
+        let end = timeline
+            .iter()
+            .map(|e| e.start_time + e.duration)
+            .fold(0.0, f64::max);
+        assert!((end - 10.0).abs() < 1e-9, "ends at {}", end);
+        assert!(dry_run(&sequence(Some(0.0))).is_err());
+    }
+
+    #[test]
+    fn test_min_release_out_of_range_is_rejected_or_ignored() {
+        let sequence = |min_release| SimpleSequence {
+            notes: vec![SimpleNote {
+                note: Some(60),
+                instrument: Some(48),
+                start_time: Some(0.0),
+                duration: Some(0.05),
+                ..Default::default()
+            }],
+            min_release,
+            ..Default::default()
+        };
+        let timeline = dry_run(&sequence(Some(0.3))).unwrap();
+        assert_eq!(timeline[0].duration, 0.3);
+
+        let preset_library = PresetLibrary::new();
+        let effects_library = EffectsPresetLibrary::new();
+        for min_release in [-0.1, f64::NAN, f64::INFINITY, 2.5] {
+            let error = dry_run(&sequence(Some(min_release))).unwrap_err();
+            assert!(error.contains("min_release"), "{}", error);
+            // Playback drops the setting rather than stretching notes by it
+            let notes = resolve_notes(
+                &preset_library,
+                &effects_library,
+                &sequence(Some(min_release)),
+            )
+            .unwrap();
+            assert_eq!(notes[0].duration, Some(0.05));
+        }
+    }
+
+    #[test]
+    fn test_pattern_offset_before_zero_is_clamped_or_rejected() {
+        use crate::midi::{ExtendedSequence, SequencePattern, SequenceReference};
+
+        let hits: Vec<SimpleNote> = (0..4)
+            .map(|step| SimpleNote {
+                note: Some(42),
+                channel: 9,
+                start_time: Some(step as f64 * 0.25),
+                duration: Some(0.1),
+                ..Default::default()
+            })
+            .collect();
+        let pattern: SequencePattern = serde_json::from_value(serde_json::json!({
+            "name": "hats",
+            "notes": hits,
+        }))
+        .unwrap();
+        let reference: SequenceReference = serde_json::from_value(serde_json::json!({
+            "pattern_name": "hats",
+            "start_time_offset": -0.3,
+        }))
+        .unwrap();
+        let extended: ExtendedSequence = serde_json::from_value(serde_json::json!({
+            "patterns": [reference],
+            "drum_humanize": 1.0,
+        }))
+        .unwrap();
+        let store = std::collections::HashMap::from([("hats".to_string(), pattern)]);
+        let mut sequence = extended.resolve_patterns(&store).unwrap();
+
+        let preset_library = PresetLibrary::new();
+        let effects_library = EffectsPresetLibrary::new();
+        let clamped = resolve_notes(&preset_library, &effects_library, &sequence).unwrap();
+        assert!(clamped.iter().all(|note| note.start_time.unwrap() >= 0.0));
+        // Hits at -0.3s and -0.05s are moved to zero before humanize nudges them
+        let near_zero = clamped
+            .iter()
+            .filter(|note| note.start_time.unwrap() < 0.02);
+        assert_eq!(near_zero.count(), 2);
+
+        sequence.negative_start = NegativeStartMode::Reject;
+        let error = resolve_notes(&preset_library, &effects_library, &sequence).unwrap_err();
+        assert!(error.starts_with("Notes 1, 2 start before"), "{}", error);
+        assert_eq!(dry_run(&sequence).unwrap_err(), error);
+    }
+
+    #[test]
+    fn test_slowdown_at_bar_two_moves_a_bar_four_note_later() {
+        use crate::midi::{MusicalDuration, MusicalTime, TempoChange, TempoMap};
+
+        let sequence = |tempo_changes| SimpleSequence {
+            notes: vec![SimpleNote {
+                note: Some(60),
+                start_time: None,
+                duration: None,
+                musical_time: Some(MusicalTime::new(4, 1, 0)),
+                musical_duration: Some(MusicalDuration::Beats(1.0)),
+                ..Default::default()
+            }],
+            tempo: 120,
+            tempo_changes,
+            ..Default::default()
+        };
+        let preset_library = PresetLibrary::new();
+        let effects_library = EffectsPresetLibrary::new();
+        let resolve = |sequence| {
+            resolve_notes(&preset_library, &effects_library, &sequence)
+                .unwrap()
+                .remove(0)
+        };
+
+        let steady = resolve(sequence(Vec::new()));
+        assert_eq!(steady.start_time, Some(6.0));
+        assert_eq!(steady.duration, Some(0.5));
+
+        // 120 BPM ramps down to 60 across bar 1, then bar 2 and 3 take a second per beat
+        let slowdown = vec![TempoChange {
+            at: MusicalTime::new(2, 1, 0),
+            tempo: 60,
+        }];
+        let slowed = resolve(sequence(slowdown.clone()));
+        let ramp = 4.0 * std::f64::consts::LN_2;
+        assert!((slowed.start_time.unwrap() - (ramp + 8.0)).abs() < 1e-9);
+        assert!((slowed.duration.unwrap() - 1.0).abs() < 1e-9);
+
+        let tempo_map = TempoMap::new(120, 4, &slowdown);
+        for beat in [0.5, 3.0, 6.0] {
+            let seconds = tempo_map.seconds_at(beat);
+            assert!(
+                (tempo_map.beat_at(seconds) - beat).abs() < 1e-9,
+                "beat {}",
+                beat
+            );
+        }
+
+        let backwards = vec![
+            TempoChange {
+                at: MusicalTime::new(3, 1, 0),
+                tempo: 90,
+            },
+            slowdown[0].clone(),
+        ];
+        let error = dry_run(&sequence(backwards)).unwrap_err();
+        assert!(error.contains("ascending order"), "{}", error);
+    }
+
+    #[test]
+    fn test_bar_three_note_after_a_slowdown_keeps_to_bar_three() {
+        use crate::midi::roll::TremoloPick;
+        use crate::midi::{
+            ChordChange, MusicalDuration, MusicalTime, SwingSubdivision, TempoChange, TempoMap,
+            swing_beats,
+        };
+
+        // 120 BPM ramps down to 60 across bar 1, so bar 3 starts near 6.77s, where a
+        // steady 120 BPM would already be in bar 4
+        let slowdown = vec![TempoChange {
+            at: MusicalTime::new(2, 1, 0),
+            tempo: 60,
+        }];
+        let tempo_map = TempoMap::new(120, 4, &slowdown);
+        let at = |beat: u32, tick: u32, beats: f64| SimpleNote {
+            note: Some(66),
+            start_time: None,
+            duration: None,
+            musical_time: Some(MusicalTime::new(3, beat, tick)),
+            musical_duration: Some(MusicalDuration::Beats(beats)),
+            ..Default::default()
+        };
+        let sequence = SimpleSequence {
+            notes: vec![
+                SimpleNote {
+                    snap_to_chord: true,
+                    ..at(1, 0, 1.0)
+                },
+                SimpleNote {
+                    tremolo_pick: Some(TremoloPick {
+                        rate_beats: 1.0,
+                        velocity_variation: 0.0,
+                    }),
+                    ..at(2, 0, 2.0)
+                },
+                at(4, 240, 0.5),
+            ],
+            tempo: 120,
+            tempo_changes: slowdown,
+            chord_track: vec![
+                ChordChange {
+                    bar: 3,
+                    notes: vec![60, 64, 67],
+                },
+                ChordChange {
+                    bar: 4,
+                    notes: vec![62, 65, 69],
+                },
+            ],
+            swing: 0.5,
+            ..Default::default()
+        };
+        let notes = resolve_notes(
+            &PresetLibrary::new(),
+            &EffectsPresetLibrary::new(),
+            &sequence,
+        )
+        .unwrap();
+        assert_eq!(notes.len(), 4);
+        let beat = |note: &SimpleNote| tempo_map.beat_at(note.start_time.unwrap());
+
+        // Snaps to bar 3's C major, not bar 4's D minor
+        assert!((beat(&notes[0]) - 8.0).abs() < 1e-9);
+        assert_eq!(notes[0].note, Some(67));
+
+        // Two beats at 60 BPM pick two one-second strokes
+        for (stroke, note) in notes[1..3].iter().enumerate() {
+            assert!((beat(note) - (9.0 + stroke as f64)).abs() < 1e-9);
+            assert!((note.duration.unwrap() - 1.0).abs() < 1e-9);
+        }
+
+        // The last off-beat eighth of bar 3 swings on bar 3's beat grid
+        let swung = swing_beats(11.5, 0.5, SwingSubdivision::Eighth);
+        assert!(swung > 11.5);
+        assert!((beat(&notes[3]) - swung).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_velocity_brightness_only_filters_notes_when_asked() {
+        let sequence = |velocity_brightness| SimpleSequence {
+            notes: vec![SimpleNote {
+                velocity: Some(120),
+                start_time: Some(0.0),
+                duration: Some(0.5),
+                synth_type: Some("sawtooth".to_string()),
+                synth_frequency: Some(220.0),
+                ..Default::default()
+            }],
+            velocity_brightness,
+            ..Default::default()
+        };
+        let resolve = |sequence| {
+            resolve_notes(
+                &PresetLibrary::new(),
+                &EffectsPresetLibrary::new(),
+                &sequence,
+            )
+            .unwrap()
+            .remove(0)
+        };
+
+        let untouched = resolve(sequence(SimpleSequence::default().velocity_brightness));
+        assert_eq!(untouched.synth_filter_cutoff, None);
+        let brightened = resolve(sequence(0.5)).synth_filter_cutoff.unwrap();
+        assert!(brightened > OPEN_BRIGHTNESS_CUTOFF, "{}", brightened);
+    }
+
+    #[test]
+    fn test_preset_morph_note_takes_the_blended_envelope() {
+        let preset_library = PresetLibrary::new();
+        let effects_library = EffectsPresetLibrary::new();
+        let morph = crate::midi::PresetMorph {
+            from: "Minimoog Bass".to_string(),
+            to: "Jupiter Bass".to_string(),
+            amount: 0.6,
+        };
+        let mut note = SimpleNote {
+            note: Some(36),
+            preset_morph: Some(morph.clone()),
+            ..Default::default()
+        };
+        note.validate_preset().unwrap();
+        apply_preset_to_note(&preset_library, &effects_library, &mut note).unwrap();
+
+        let blend = preset_library
+            .morph(&morph.from, &morph.to, morph.amount)
+            .unwrap();
+        assert_eq!(note.synth_type.as_deref(), Some("sawtooth"));
+        assert_eq!(note.synth_attack, Some(blend.envelope.attack));
+        assert_eq!(note.synth_release, Some(blend.envelope.release));
+        assert_eq!(
+            note.synth_filter_cutoff,
+            blend.filter.as_ref().map(|filter| filter.cutoff)
+        );
+
+        let both = SimpleNote {
+            preset_name: Some("TB-303 Acid".to_string()),
+            preset_morph: Some(morph),
+            ..Default::default()
+        };
+        let error = both.validate_preset().unwrap_err();
+        assert!(error.contains("preset_morph"), "{}", error);
+    }
+}
@@ -0,0 +1,284 @@
+use super::{SimpleNote, TempoMap};
+use crate::expressive::random_bipolar;
+use serde::{Deserialize, Serialize};
+
+/// Fastest roll rate in hits per second
+const MAX_ROLL_RATE: f64 = 64.0;
+/// Longest roll in seconds
+const MAX_ROLL_DURATION: f64 = 8.0;
+/// Shortest gap between tremolo-picked strokes in beats (128th notes)
+const MIN_TREMOLO_PICK_BEATS: f64 = 1.0 / 32.0;
+
+/// How the rate of an accelerating roll moves from start_rate to end_rate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollShape {
+    /// Rate rises by the same number of hits per second at every step
+    #[default]
+    Linear,
+    /// Rate rises by the same ratio at every step, holding back until late in the roll
+    Exponential,
+}
+
+/// Drum roll that speeds up: one note becomes repeated hits whose spacing shrinks
+/// from 1/start_rate to 1/end_rate, starting at the note's start time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollAccelerate {
+    /// Hits per second at the start of the roll
+    pub start_rate: f64,
+    /// Hits per second at the end of the roll; must be faster than start_rate
+    pub end_rate: f64,
+    /// Length of the roll in seconds
+    pub duration: f64,
+    /// Rate curve: linear or exponential (default: linear)
+    #[serde(default)]
+    pub shape: RollShape,
+}
+
+impl RollAccelerate {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, rate) in [("start_rate", self.start_rate), ("end_rate", self.end_rate)] {
+            if !(rate.is_finite() && rate > 0.0 && rate <= MAX_ROLL_RATE) {
+                return Err(format!(
+                    "roll {} must be greater than 0 and at most {} hits/s, got {}",
+                    name, MAX_ROLL_RATE, rate
+                ));
+            }
+        }
+        if self.end_rate <= self.start_rate {
+            return Err(format!(
+                "roll end_rate ({}) must be greater than start_rate ({})",
+                self.end_rate, self.start_rate
+            ));
+        }
+        let duration = self.duration;
+        if !(duration.is_finite() && duration > 0.0 && duration <= MAX_ROLL_DURATION) {
+            return Err(format!(
+                "roll duration must be greater than 0 and at most {} seconds, got {}",
+                MAX_ROLL_DURATION, duration
+            ));
+        }
+        Ok(())
+    }
+
+    fn rate_at(&self, progress: f64) -> f64 {
+        match self.shape {
+            RollShape::Linear => self.start_rate + (self.end_rate - self.start_rate) * progress,
+            RollShape::Exponential => {
+                self.start_rate * (self.end_rate / self.start_rate).powf(progress)
+            }
+        }
+    }
+
+    /// `count` intervals stepping along the rate curve from 1/start_rate to 1/end_rate
+    fn ramp(&self, count: usize) -> impl Iterator<Item = f64> + '_ {
+        (0..count).map(move |step| 1.0 / self.rate_at(step as f64 / (count - 1) as f64))
+    }
+
+    /// Gaps between consecutive hits. The first and last gaps are exactly 1/start_rate
+    /// and 1/end_rate; the number of gaps is whichever makes the roll closest to its duration.
+    pub fn intervals(&self) -> Vec<f64> {
+        // No gap is shorter than 1/end_rate, so more gaps than this always overshoot
+        let most = ((self.duration * self.end_rate).ceil() as usize).max(2);
+        let overshoot = |count: usize| (self.ramp(count).sum::<f64>() - self.duration).abs();
+        let count = (2..=most)
+            .min_by(|&a, &b| overshoot(a).total_cmp(&overshoot(b)))
+            .unwrap_or(2);
+        self.ramp(count).collect()
+    }
+}
+
+/// Tremolo picking (mandolin, balalaika): the note is re-attacked at a steady rate for its
+/// whole duration, each stroke at a slightly different velocity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TremoloPick {
+    /// Gap between strokes in beats at the sequence tempo (0.5 = eighth notes)
+    pub rate_beats: f64,
+    /// Largest velocity change either way as a fraction of the note's velocity
+    /// (0.0-1.0, default: 0.1)
+    #[serde(default = "default_velocity_variation")]
+    pub velocity_variation: f32,
+}
+
+fn default_velocity_variation() -> f32 {
+    0.1
+}
+
+impl TremoloPick {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.rate_beats.is_finite() && self.rate_beats >= MIN_TREMOLO_PICK_BEATS) {
+            return Err(format!(
+                "tremolo_pick rate_beats must be at least {} beats, got {}",
+                MIN_TREMOLO_PICK_BEATS, self.rate_beats
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.velocity_variation) {
+            return Err(format!(
+                "tremolo_pick velocity_variation must be between 0.0 and 1.0, got {}",
+                self.velocity_variation
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl SimpleNote {
+    /// Expand a note with `tremolo_pick` into its strokes, spaced in beats along
+    /// `tempo_map` (the note itself otherwise). Strokes fill the note's duration, the last
+    /// one taking what is left. Velocities draw from the master seed when set.
+    pub fn expand_tremolo_pick(self, tempo_map: &TempoMap) -> Vec<SimpleNote> {
+        let Some(pick) = self.tremolo_pick.clone() else {
+            return vec![self];
+        };
+        let start_time = self.start_time.unwrap_or(0.0);
+        let end_time = start_time + self.duration.unwrap_or(1.0);
+        let start_beat = tempo_map.beat_at(start_time);
+        let beats = tempo_map.beat_at(end_time) - start_beat;
+        let strokes = ((beats / pick.rate_beats).round() as usize).max(1);
+        let velocity = self.velocity.unwrap_or(80) as f32;
+        let onset = |stroke: usize| {
+            if stroke == 0 {
+                start_time
+            } else {
+                tempo_map.seconds_at(start_beat + stroke as f64 * pick.rate_beats)
+            }
+        };
+
+        (0..strokes)
+            .map(|stroke| {
+                let stroke_start = onset(stroke);
+                let stroke_end = if stroke + 1 == strokes {
+                    end_time
+                } else {
+                    onset(stroke + 1)
+                };
+                let varied = velocity * (1.0 + random_bipolar() * pick.velocity_variation);
+                SimpleNote {
+                    start_time: Some(stroke_start),
+                    duration: Some(stroke_end - stroke_start),
+                    velocity: Some(varied.round().clamp(1.0, 127.0) as u8),
+                    tremolo_pick: None,
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Expand a note with `roll_accelerate` into its hits (the note itself otherwise). Each
+    /// hit keeps the note's sound and lasts until the next one.
+    pub fn expand_roll(self) -> Vec<SimpleNote> {
+        let Some(roll) = self.roll_accelerate.clone() else {
+            return vec![self];
+        };
+        let intervals = roll.intervals();
+        let mut time = self.start_time.unwrap_or(0.0);
+        intervals
+            .iter()
+            .chain(intervals.last())
+            .map(|&interval| {
+                let hit = SimpleNote {
+                    start_time: Some(time),
+                    duration: Some(interval),
+                    roll_accelerate: None,
+                    ..self.clone()
+                };
+                time += interval;
+                hit
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roll_hits_get_closer_together_and_end_at_end_rate() {
+        for shape in [RollShape::Linear, RollShape::Exponential] {
+            let snare = SimpleNote {
+                note: Some(38),
+                channel: 9,
+                start_time: Some(1.0),
+                roll_accelerate: Some(RollAccelerate {
+                    start_rate: 4.0,
+                    end_rate: 16.0,
+                    duration: 2.0,
+                    shape,
+                }),
+                ..Default::default()
+            };
+            let hits = snare.expand_roll();
+            assert!(hits.len() > 8, "{:?}: only {} hits", shape, hits.len());
+            assert_eq!(hits[0].start_time, Some(1.0));
+            assert!(hits.iter().all(|hit| hit.roll_accelerate.is_none()));
+
+            let onsets: Vec<f64> = hits.iter().map(|hit| hit.start_time.unwrap()).collect();
+            let gaps: Vec<f64> = onsets.windows(2).map(|pair| pair[1] - pair[0]).collect();
+            assert!(
+                gaps.windows(2).all(|pair| pair[1] < pair[0]),
+                "{:?}: gaps should shrink, got {:?}",
+                shape,
+                gaps
+            );
+            assert!(
+                (gaps[0] - 0.25).abs() < 1e-9,
+                "{:?}: first gap {}",
+                shape,
+                gaps[0]
+            );
+            let last = gaps[gaps.len() - 1];
+            assert!(
+                (last - 1.0 / 16.0).abs() < 1e-9,
+                "{:?}: last gap {}",
+                shape,
+                last
+            );
+            let span = onsets[onsets.len() - 1] - onsets[0];
+            assert!((span - 2.0).abs() < 0.1, "{:?}: roll spans {}", shape, span);
+        }
+    }
+
+    #[test]
+    fn test_eighth_note_tremolo_pick_retriggers_a_whole_note_eight_times() {
+        // A whole note is two seconds at 120 BPM
+        let whole_note = SimpleNote {
+            note: Some(67),
+            velocity: Some(90),
+            start_time: Some(0.5),
+            duration: Some(2.0),
+            tremolo_pick: Some(TremoloPick {
+                rate_beats: 0.5,
+                velocity_variation: 0.1,
+            }),
+            ..Default::default()
+        };
+        let strokes = whole_note.expand_tremolo_pick(&TempoMap::new(120, 4, &[]));
+
+        assert_eq!(strokes.len(), 8);
+        for (index, stroke) in strokes.iter().enumerate() {
+            let onset = 0.5 + index as f64 * 0.25;
+            assert!((stroke.start_time.unwrap() - onset).abs() < 1e-9);
+            assert!((stroke.duration.unwrap() - 0.25).abs() < 1e-9);
+            assert_eq!(stroke.note, Some(67));
+            assert!(stroke.tremolo_pick.is_none());
+            assert!(
+                (81..=99).contains(&stroke.velocity.unwrap()),
+                "velocity {:?}",
+                stroke.velocity
+            );
+        }
+    }
+
+    #[test]
+    fn test_decelerating_roll_is_rejected() {
+        let roll = RollAccelerate {
+            start_rate: 16.0,
+            end_rate: 4.0,
+            duration: 1.0,
+            shape: RollShape::Linear,
+        };
+        let error = roll.validate().unwrap_err();
+        assert!(error.contains("end_rate"), "{}", error);
+    }
+}
@@ -0,0 +1,59 @@
+/// Scale names and their semitone intervals above the root
+pub const SCALES: [(&str, &[u8]); 13] = [
+    ("major", &[0, 2, 4, 5, 7, 9, 11]),
+    ("minor", &[0, 2, 3, 5, 7, 8, 10]),
+    ("harmonic_minor", &[0, 2, 3, 5, 7, 8, 11]),
+    ("melodic_minor", &[0, 2, 3, 5, 7, 9, 11]),
+    ("dorian", &[0, 2, 3, 5, 7, 9, 10]),
+    ("phrygian", &[0, 1, 3, 5, 7, 8, 10]),
+    ("lydian", &[0, 2, 4, 6, 7, 9, 11]),
+    ("mixolydian", &[0, 2, 4, 5, 7, 9, 10]),
+    ("locrian", &[0, 1, 3, 5, 6, 8, 10]),
+    ("major_pentatonic", &[0, 2, 4, 7, 9]),
+    ("minor_pentatonic", &[0, 3, 5, 7, 10]),
+    ("blues", &[0, 3, 5, 6, 7, 10]),
+    ("chromatic", &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+];
+
+/// Look up a scale's intervals by name (case-insensitive)
+pub fn scale_intervals(name: &str) -> Result<&'static [u8], String> {
+    let name = name.to_lowercase();
+    SCALES
+        .iter()
+        .find(|(scale, _)| *scale == name)
+        .map(|(_, intervals)| *intervals)
+        .ok_or_else(|| {
+            format!(
+                "Unknown scale '{}'. Valid scales: {}",
+                name,
+                SCALES.map(|(scale, _)| scale).join(", ")
+            )
+        })
+}
+
+/// Nearest MIDI note to `pitch` in the scale on `root`, in any octave; ties resolve downward
+pub fn snap_to_scale(pitch: u8, root: u8, intervals: &[u8]) -> u8 {
+    (0..=6i16)
+        .flat_map(|distance| [pitch as i16 - distance, pitch as i16 + distance])
+        .find(|&candidate| {
+            (0..=127).contains(&candidate)
+                && intervals.contains(&((candidate - root as i16).rem_euclid(12) as u8))
+        })
+        .map_or(pitch, |candidate| candidate as u8)
+}
+
+/// Every MIDI note of the scale from `root` up to `octaves` octaves above it, inclusive of the top root
+pub fn scale_notes(root: u8, intervals: &[u8], octaves: u8) -> Vec<u8> {
+    let mut notes: Vec<u8> = (0..octaves as u16)
+        .flat_map(|octave| {
+            intervals
+                .iter()
+                .map(move |&interval| root as u16 + octave * 12 + interval as u16)
+        })
+        .chain(std::iter::once(root as u16 + octaves as u16 * 12))
+        .filter(|&note| note <= 127)
+        .map(|note| note as u8)
+        .collect();
+    notes.dedup();
+    notes
+}
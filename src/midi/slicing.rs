@@ -0,0 +1,157 @@
+use super::player::RenderedAudio;
+use std::time::Duration;
+
+/// Most slices a render can be cut into
+pub const MAX_SLICES: u32 = 64;
+/// Longest slice order accepted, enough to repeat every slice several times
+pub const MAX_SLICE_ORDER: usize = 256;
+
+/// Fade at both edges of every slice so cuts mid-waveform do not click
+const SLICE_FADE_MS: f64 = 2.0;
+
+pub fn validate_slice_order(slices: u32, order: &[u32]) -> Result<(), String> {
+    if !(1..=MAX_SLICES).contains(&slices) {
+        return Err(format!(
+            "slices must be between 1 and {}, got {}",
+            MAX_SLICES, slices
+        ));
+    }
+    if order.is_empty() {
+        return Err("order must list at least one slice".to_string());
+    }
+    if order.len() > MAX_SLICE_ORDER {
+        return Err(format!(
+            "order can list at most {} slices, got {}",
+            MAX_SLICE_ORDER,
+            order.len()
+        ));
+    }
+    if let Some((position, index)) = order
+        .iter()
+        .enumerate()
+        .find(|&(_, &index)| index >= slices)
+    {
+        return Err(format!(
+            "order[{}] is slice {}, but slices are numbered 0-{}",
+            position,
+            index,
+            slices - 1
+        ));
+    }
+    Ok(())
+}
+
+/// Cut a render into `slices` equal slices and join them in `order` (0-based indices that
+/// may repeat or skip slices), breakbeat style. Frames left over after the last whole
+/// slice are dropped, and each slice fades in and out over a couple of milliseconds.
+pub fn rearrange(
+    audio: &RenderedAudio,
+    slices: u32,
+    order: &[u32],
+) -> Result<RenderedAudio, String> {
+    validate_slice_order(slices, order)?;
+    let channels = audio.channels.max(1) as usize;
+    let slice_frames = audio.samples.len() / channels / slices as usize;
+    if slice_frames == 0 {
+        return Err(format!(
+            "The render is too short to cut into {} slices",
+            slices
+        ));
+    }
+
+    let fade_frames = ((SLICE_FADE_MS / 1000.0 * audio.sample_rate as f64) as usize)
+        .clamp(1, slice_frames.div_ceil(2));
+    let edge_gain = |frame: usize| {
+        let from_edge = frame.min(slice_frames - 1 - frame);
+        (from_edge as f32 / fade_frames as f32).min(1.0)
+    };
+
+    let slice_len = slice_frames * channels;
+    let mut samples = Vec::with_capacity(slice_len * order.len());
+    for &index in order {
+        let start = index as usize * slice_len;
+        let slice = &audio.samples[start..start + slice_len];
+        for (frame, frame_samples) in slice.chunks_exact(channels).enumerate() {
+            let gain = edge_gain(frame);
+            samples.extend(frame_samples.iter().map(|sample| sample * gain));
+        }
+    }
+
+    Ok(RenderedAudio {
+        duration: Duration::from_secs_f64(
+            (slice_frames * order.len()) as f64 / audio.sample_rate as f64,
+        ),
+        samples,
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::player::MidiPlayer;
+    use crate::midi::{SimpleNote, SimpleSequence};
+
+    /// A one-bar loop with a different pitch in each quarter
+    fn four_note_loop() -> RenderedAudio {
+        let notes = [220.0, 330.0, 440.0, 660.0]
+            .iter()
+            .enumerate()
+            .map(|(index, &frequency)| SimpleNote {
+                start_time: Some(index as f64 * 0.5),
+                duration: Some(0.5),
+                synth_type: Some("sine".to_string()),
+                synth_frequency: Some(frequency),
+                synth_attack: Some(0.0),
+                synth_release: Some(0.0),
+                ..Default::default()
+            })
+            .collect();
+        let rendered = MidiPlayer::render_samples(SimpleSequence {
+            notes,
+            ..Default::default()
+        })
+        .unwrap();
+        // Trim any tail so each quarter of the render is one note
+        let frames = rendered.sample_rate as usize * 2;
+        RenderedAudio {
+            samples: rendered.samples[..frames * rendered.channels as usize].to_vec(),
+            duration: Duration::from_secs(2),
+            ..rendered
+        }
+    }
+
+    #[test]
+    fn test_reversed_order_puts_each_slice_in_the_mirrored_position() {
+        let original = four_note_loop();
+        let forward = rearrange(&original, 4, &[0, 1, 2, 3]).unwrap();
+        let reversed = rearrange(&original, 4, &[3, 2, 1, 0]).unwrap();
+        assert_eq!(reversed.samples.len(), forward.samples.len());
+
+        let slice_len = forward.samples.len() / 4;
+        assert_ne!(
+            forward.samples[..slice_len],
+            forward.samples[3 * slice_len..],
+            "slices should hold different notes"
+        );
+        for position in 0..4 {
+            let mirrored = 3 - position;
+            assert_eq!(
+                reversed.samples[position * slice_len..(position + 1) * slice_len],
+                forward.samples[mirrored * slice_len..(mirrored + 1) * slice_len],
+                "position {}",
+                position
+            );
+        }
+        assert!(forward.samples.iter().any(|sample| sample.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_out_of_range_slice_is_rejected() {
+        let error = validate_slice_order(4, &[0, 1, 4]).unwrap_err();
+        assert!(error.contains("order[2]"), "{}", error);
+        assert!(validate_slice_order(4, &[]).is_err());
+        assert!(validate_slice_order(0, &[0]).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+use super::{SimpleNote, deserialize_null_default};
+use serde::{Deserialize, Serialize};
+
+/// Semitones above the key for each roman numeral degree (I-VII)
+const MAJOR_DEGREES: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+const NUMERALS: [&str; 7] = ["vii", "vi", "iv", "v", "iii", "ii", "i"];
+/// Chord qualities by suffix, with their intervals above the chord root
+const QUALITIES: [(&str, &[u8]); 11] = [
+    ("", &[0, 4, 7]),
+    ("m", &[0, 3, 7]),
+    ("dim", &[0, 3, 6]),
+    ("aug", &[0, 4, 8]),
+    ("sus2", &[0, 2, 7]),
+    ("sus4", &[0, 5, 7]),
+    ("7", &[0, 4, 7, 10]),
+    ("maj7", &[0, 4, 7, 11]),
+    ("m7", &[0, 3, 7, 10]),
+    ("m7b5", &[0, 3, 6, 10]),
+    ("dim7", &[0, 3, 6, 9]),
+];
+/// Longest progression accepted
+const MAX_CHORDS: usize = 64;
+/// Voicings stay within this many semitones of the key so a long progression cannot drift
+/// out of its register
+const MAX_DRIFT: i16 = 12;
+
+/// A chord progression played as block chords, each voiced to move as little as possible
+/// from the one before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoicedProgression {
+    /// Key as a MIDI note (default: 60 = C4); roman numerals count from it and the first
+    /// chord is voiced in its octave
+    #[serde(default = "default_key")]
+    pub key: u8,
+    /// Chord symbols ("C", "Am", "G7", "F#dim") or roman numerals in the key ("I", "vi", "V7", "bVII")
+    pub chords: Vec<String>,
+    /// Length of each chord in beats, 0.25-16 (default: 4)
+    #[serde(default = "default_beats_per_chord")]
+    pub beats_per_chord: f64,
+    /// Velocity of every note (default: 80)
+    #[serde(default = "default_velocity")]
+    pub velocity: u8,
+    /// GM instrument for the chords
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub instrument: Option<u8>,
+    /// MIDI channel for the chords (default: 0)
+    #[serde(default)]
+    pub channel: u8,
+}
+
+fn default_key() -> u8 {
+    60
+}
+
+fn default_beats_per_chord() -> f64 {
+    4.0
+}
+
+fn default_velocity() -> u8 {
+    80
+}
+
+/// Pitch classes of a chord symbol or roman numeral, chord root first
+pub fn parse_chord(symbol: &str, key: u8) -> Result<Vec<u8>, String> {
+    let unknown = || {
+        format!(
+            "Unknown chord '{}'. Use a chord name (C, Am, G7, F#dim) or a roman numeral (I, vi, V7, bVII) with a suffix from: {}",
+            symbol,
+            QUALITIES
+                .map(|(suffix, _)| if suffix.is_empty() { "major" } else { suffix })
+                .join(", ")
+        )
+    };
+
+    let (root, minor, suffix) = match symbol.chars().next() {
+        Some(letter @ 'A'..='G') => {
+            let natural = [9, 11, 0, 2, 4, 5, 7][(letter as u8 - b'A') as usize];
+            let rest = &symbol[1..];
+            let (root, rest) = match rest.chars().next() {
+                Some('#') => (natural + 1, &rest[1..]),
+                Some('b') => (natural + 11, &rest[1..]),
+                _ => (natural, rest),
+            };
+            (root % 12, false, rest)
+        }
+        _ => {
+            let (shift, rest) = match symbol.chars().next() {
+                Some('#') => (1, &symbol[1..]),
+                Some('b') => (11, &symbol[1..]),
+                _ => (0, symbol),
+            };
+            // Longest numerals first so "vi" is not read as "v"; only an ASCII match is
+            // sliced off, so other scripts fall through to the unknown-chord error
+            let numeral = NUMERALS
+                .iter()
+                .find(|numeral| {
+                    rest.get(..numeral.len())
+                        .is_some_and(|head| head.eq_ignore_ascii_case(numeral))
+                })
+                .ok_or_else(unknown)?;
+            let degree = match *numeral {
+                "i" => 0,
+                "ii" => 1,
+                "iii" => 2,
+                "iv" => 3,
+                "v" => 4,
+                "vi" => 5,
+                _ => 6,
+            };
+            let minor = rest.starts_with(|c: char| c.is_lowercase());
+            let root = (key % 12 + MAJOR_DEGREES[degree] + shift) % 12;
+            (root, minor, &rest[numeral.len()..])
+        }
+    };
+
+    // Lowercase numerals are minor unless the suffix says otherwise ("vii" + "dim")
+    let suffix = match (minor, suffix) {
+        (true, "") => "m",
+        (true, "7") => "m7",
+        (_, suffix) => suffix,
+    };
+    let intervals = QUALITIES
+        .iter()
+        .find(|(name, _)| *name == suffix)
+        .map(|(_, intervals)| *intervals)
+        .ok_or_else(unknown)?;
+    Ok(intervals
+        .iter()
+        .map(|interval| (root + interval) % 12)
+        .collect())
+}
+
+/// Close-position voicing of `chord` (pitch classes) starting from the `inversion`th tone
+/// with its lowest note on or above `floor`
+fn close_voicing(chord: &[u8], inversion: usize, floor: i16) -> Vec<i16> {
+    let mut voicing = Vec::with_capacity(chord.len());
+    let mut next = floor;
+    for offset in 0..chord.len() {
+        let class = chord[(inversion + offset) % chord.len()] as i16;
+        let pitch = next + (class - next).rem_euclid(12);
+        voicing.push(pitch);
+        next = pitch + 1;
+    }
+    voicing
+}
+
+/// Total semitones the voices move from `from` to `to`. Equal-sized chords pair voices
+/// bottom to top; otherwise every note is charged its distance to the nearest note of the
+/// other chord.
+fn movement(from: &[i16], to: &[i16]) -> i16 {
+    if from.len() == to.len() {
+        return from.iter().zip(to).map(|(a, b)| (a - b).abs()).sum();
+    }
+    let nearest = |pitch: i16, chord: &[i16]| {
+        chord
+            .iter()
+            .map(|other| (pitch - other).abs())
+            .min()
+            .unwrap_or(0)
+    };
+    from.iter().map(|&pitch| nearest(pitch, to)).sum::<i16>()
+        + to.iter().map(|&pitch| nearest(pitch, from)).sum::<i16>()
+}
+
+/// The inversion and octave of `chord` that moves least from `previous`, keeping near `key`
+fn lead_voices(previous: &[i16], chord: &[u8], key: i16) -> Vec<i16> {
+    let lowest = previous.iter().copied().min().unwrap_or(key);
+    (0..chord.len())
+        .flat_map(|inversion| {
+            (-12..=12).map(move |shift| close_voicing(chord, inversion, lowest + shift))
+        })
+        .filter(|voicing| {
+            voicing
+                .iter()
+                .all(|&pitch| (0..=127).contains(&pitch) && (pitch - key).abs() <= MAX_DRIFT + 12)
+                && (voicing[0] - key).abs() <= MAX_DRIFT
+        })
+        .min_by_key(|voicing| (movement(previous, voicing), (voicing[0] - key).abs()))
+        .unwrap_or_else(|| close_voicing(chord, 0, key))
+}
+
+impl VoicedProgression {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.chords.is_empty() || self.chords.len() > MAX_CHORDS {
+            return Err(format!(
+                "Progression needs 1-{} chords, got {}",
+                MAX_CHORDS,
+                self.chords.len()
+            ));
+        }
+        if !(24..=96).contains(&self.key) {
+            return Err(format!(
+                "Progression key must be MIDI note 24-96, got {}",
+                self.key
+            ));
+        }
+        if !(0.25..=16.0).contains(&self.beats_per_chord) {
+            return Err(format!(
+                "Progression beats_per_chord must be between 0.25 and 16, got {}",
+                self.beats_per_chord
+            ));
+        }
+        if !(1..=127).contains(&self.velocity) {
+            return Err(format!(
+                "Progression velocity must be 1-127, got {}",
+                self.velocity
+            ));
+        }
+        if self.instrument.is_some_and(|instrument| instrument > 127) {
+            return Err("Progression instrument must be 0-127".to_string());
+        }
+        if self.channel > 15 {
+            return Err(format!(
+                "Progression channel must be 0-15, got {}",
+                self.channel
+            ));
+        }
+        Ok(())
+    }
+
+    /// MIDI notes of each chord, lowest first. The first chord is in root position in the
+    /// key's octave; every later one takes the inversion that moves the voices least.
+    pub fn voicings(&self) -> Result<Vec<Vec<u8>>, String> {
+        self.validate()?;
+        let key = self.key as i16;
+        let mut previous: Option<Vec<i16>> = None;
+        self.chords
+            .iter()
+            .map(|symbol| {
+                let chord = parse_chord(symbol, self.key)?;
+                let voicing = match &previous {
+                    Some(previous) => lead_voices(previous, &chord, key),
+                    None => close_voicing(&chord, 0, key),
+                };
+                previous = Some(voicing.clone());
+                Ok(voicing.iter().map(|&pitch| pitch as u8).collect())
+            })
+            .collect()
+    }
+
+    /// Block chords at `tempo`, starting at time 0
+    pub fn generate(&self, tempo: u32) -> Result<Vec<SimpleNote>, String> {
+        let length = self.beats_per_chord * 60.0 / tempo as f64;
+        Ok(self
+            .voicings()?
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, voicing)| {
+                voicing.into_iter().map(move |pitch| SimpleNote {
+                    note: Some(pitch),
+                    velocity: Some(self.velocity),
+                    start_time: Some(index as f64 * length),
+                    duration: Some(length),
+                    instrument: self.instrument,
+                    channel: self.channel,
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progression(chords: &[&str]) -> VoicedProgression {
+        VoicedProgression {
+            key: 60,
+            chords: chords.iter().map(|chord| chord.to_string()).collect(),
+            beats_per_chord: 4.0,
+            velocity: 80,
+            instrument: None,
+            channel: 0,
+        }
+    }
+
+    #[test]
+    fn test_pop_progression_keeps_common_tones_and_moves_by_steps() {
+        let voicings = progression(&["C", "G", "Am", "F"]).voicings().unwrap();
+        assert_eq!(
+            voicings,
+            vec![
+                vec![60, 64, 67],
+                // G: B D G, holding the G
+                vec![59, 62, 67],
+                // Am: C E A, each voice up a step
+                vec![60, 64, 69],
+                // F: C F A, holding the C and the A
+                vec![60, 65, 69],
+            ]
+        );
+        for pair in voicings.windows(2) {
+            for (from, to) in pair[0].iter().zip(&pair[1]) {
+                assert!(
+                    from.abs_diff(*to) <= 2,
+                    "{:?} -> {:?} leaps",
+                    pair[0],
+                    pair[1]
+                );
+            }
+        }
+        // Root position every time would leap a fifth or more between chords
+        assert_ne!(voicings[1][0] % 12, 7);
+
+        // The same progression as numerals in the key
+        assert_eq!(
+            progression(&["I", "V", "vi", "IV"]).voicings().unwrap(),
+            voicings
+        );
+
+        // The schema's 0.25-beat minimum holds here too
+        let mut short = progression(&["C"]);
+        short.beats_per_chord = 0.25;
+        assert!(short.validate().is_ok());
+        short.beats_per_chord = 0.1;
+        let error = short.validate().unwrap_err();
+        assert!(error.contains("between 0.25 and 16"), "{}", error);
+    }
+
+    #[test]
+    fn test_chord_symbols_and_numerals_parse() {
+        assert_eq!(parse_chord("F#dim", 60).unwrap(), vec![6, 9, 0]);
+        assert_eq!(parse_chord("Bbmaj7", 60).unwrap(), vec![10, 2, 5, 9]);
+        assert_eq!(parse_chord("ii7", 62).unwrap(), vec![4, 7, 11, 2]);
+        assert_eq!(parse_chord("bVII", 60).unwrap(), vec![10, 2, 5]);
+        assert_eq!(parse_chord("viidim", 60).unwrap(), vec![11, 2, 5]);
+        let error = parse_chord("Cmaj13", 60).unwrap_err();
+        assert!(error.contains("Unknown chord 'Cmaj13'"), "{}", error);
+        for symbol in ["İ", "vİ", "bİV", "ⅰ"] {
+            let error = parse_chord(symbol, 60).unwrap_err();
+            assert!(error.contains("Unknown chord"), "{}", error);
+        }
+    }
+}
@@ -1,14 +1,116 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
-use crate::midi::{ExtendedSequence, MidiPlayer, SequencePattern, SimpleSequence};
-use std::collections::HashMap;
+use crate::expressive::{
+    ClassicSynthPreset, EffectsPresetLibrary, PresetCategory, PresetLibrary, SynthParams, SynthType,
+};
+use crate::midi::abc::parse_abc;
+use crate::midi::analysis::{check_mono_compatibility, spectrogram, validate_target_lufs};
+use crate::midi::export::{ExportFormat, RenderOptions, output_path_in, renders_dir};
+use crate::midi::humanize::validate_drum_humanize;
+use crate::midi::melody::MelodySpec;
+use crate::midi::parser::load_smf;
+use crate::midi::project::{PROJECT_VERSION, Project};
+use crate::midi::resolve::{
+    SequenceCheck, check_playback, dry_run, validate_fit_duration, validate_min_release,
+    validate_velocity_brightness,
+};
+use crate::midi::slicing::{rearrange, validate_slice_order};
+use crate::midi::voicing::VoicedProgression;
+use crate::midi::{
+    ChannelConfig, ConcatSegment, EffectConfig, EffectType, ExtendedSequence, MidiPlayer,
+    PlaybackControl, SequencePattern, SimpleNote, SimpleSequence, validate_beats_per_bar,
+    validate_channel_configs, validate_chord_track, validate_program_changes, validate_swing,
+    validate_tail_cutoff_db, validate_tempo_changes,
+};
+use crate::setup::config::{MAX_MASTER_GAIN, SetupConfig};
+use std::collections::{HashMap, VecDeque};
 use std::io::{self, BufRead, Write};
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::Duration;
 
 // Global pattern storage for the MCP server session
 lazy_static::lazy_static! {
     static ref PATTERN_STORE: Arc<Mutex<HashMap<String, SequencePattern>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref RECENT_SEQUENCES: Arc<Mutex<VecDeque<ExtendedSequence>>> = Arc::new(Mutex::new(VecDeque::new()));
+    /// Detached playbacks still sounding, by playback id, with the handle that stops them
+    static ref PLAYBACKS: Arc<Mutex<HashMap<u64, PlaybackControl>>> = Arc::new(Mutex::new(HashMap::new()));
+}
+
+static NEXT_PLAYBACK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How many played sequences are kept for save_project
+const MAX_RECENT_SEQUENCES: usize = 5;
+
+/// Play a sequence on its own thread and return its playback id and audible duration as
+/// soon as it starts. The thread owns the player, and with it the output stream, until
+/// the sink has played everything including effect tails; only then is the playback
+/// dropped from the registry.
+fn start_detached_playback(sequence: SimpleSequence) -> Result<(u64, Duration), String> {
+    let playback_id = NEXT_PLAYBACK_ID.fetch_add(1, Ordering::SeqCst);
+    let (started_tx, started_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let player = match MidiPlayer::new() {
+            Ok(player) => player,
+            Err(e) => {
+                let _ = started_tx.send(Err(format!("Failed to create MIDI player: {}", e)));
+                return;
+            }
+        };
+        match player.play_enhanced_mixed(sequence) {
+            Ok(total_time) => {
+                register_playback(playback_id, player.control());
+                let _ = started_tx.send(Ok(total_time));
+                player.wait_until_finished();
+                finish_playback(playback_id);
+                tracing::info!("Detached playback {} finished", playback_id);
+            }
+            Err(e) => {
+                player.control().panic();
+                let _ = started_tx.send(Err(format!("Failed to play sequence: {}", e)));
+            }
+        }
+    });
+    let total_time = started_rx
+        .recv()
+        .map_err(|_| "Playback thread exited before starting".to_string())??;
+    Ok((playback_id, total_time))
+}
+
+fn register_playback(playback_id: u64, control: PlaybackControl) {
+    if let Ok(mut playbacks) = PLAYBACKS.lock() {
+        playbacks.insert(playback_id, control);
+    }
+}
+
+fn finish_playback(playback_id: u64) {
+    if let Ok(mut playbacks) = PLAYBACKS.lock() {
+        playbacks.remove(&playback_id);
+    }
+}
+
+/// Fade out one detached playback, leaving everything else playing. Returns the fade
+/// length, or an error when the playback has already finished or never existed.
+fn stop_detached_playback(playback_id: u64) -> Result<Duration, String> {
+    let playbacks = PLAYBACKS
+        .lock()
+        .map_err(|_| "Playback registry is unavailable".to_string())?;
+    playbacks
+        .get(&playback_id)
+        .map(PlaybackControl::stop)
+        .ok_or_else(|| format!("No playback {} is playing", playback_id))
+}
+
+/// Keep a played sequence for the session's project file, dropping the oldest past the limit
+fn remember_sequence(sequence: ExtendedSequence) {
+    if let Ok(mut recent) = RECENT_SEQUENCES.lock() {
+        if recent.len() == MAX_RECENT_SEQUENCES {
+            recent.pop_front();
+        }
+        recent.push_back(sequence);
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,9 +168,52 @@ struct InitializeParams {
 #[derive(Debug, Deserialize)]
 struct ToolCallParams {
     name: String,
+    /// Null when the client omits it; each tool reports what it needs
+    #[serde(default)]
     arguments: Value,
 }
 
+/// Guidance for tools that render a plain note list
+const NOTES_GUIDANCE: &str = "provide a non-empty 'notes' array, e.g. {\"notes\": [{\"note\": 60, \"velocity\": 100, \"start_time\": 0, \"duration\": 0.5}]}. Call get_capabilities for the note fields, instruments and synth types";
+
+/// Guidance for define_sequence_pattern
+const PATTERN_GUIDANCE: &str = "provide a 'name' and a non-empty 'notes' array, e.g. {\"name\": \"my_riff\", \"notes\": [{\"note\": 60, \"start_time\": 0, \"duration\": 0.5}]}. Call get_capabilities for the note fields";
+
+/// Guidance for play_sequence, which also accepts patterns and arpeggios
+const SEQUENCE_GUIDANCE: &str = "provide a non-empty 'notes', 'patterns' or 'arpeggios' array, e.g. {\"notes\": [{\"note\": 60, \"start_time\": 0, \"duration\": 0.5}]} or {\"patterns\": [{\"pattern_name\": \"my_riff\"}]}. Call list_patterns for defined patterns";
+
+/// Catch null, non-object or contentless arguments before parsing, so callers get guidance
+/// instead of a serde error. Passes when any `content_keys` entry is present and not an empty array.
+fn check_tool_arguments(
+    tool: &str,
+    arguments: &Value,
+    content_keys: &[&str],
+    guidance: &str,
+) -> Result<(), String> {
+    let Some(object) = arguments.as_object() else {
+        return Err(if arguments.is_null() {
+            format!("{} was called without arguments: {}", tool, guidance)
+        } else {
+            format!("{} arguments must be a JSON object: {}", tool, guidance)
+        });
+    };
+    let has_content = content_keys.iter().any(|key| match object.get(*key) {
+        None | Some(Value::Null) => false,
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(_) => true,
+    });
+    if has_content {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} was called with nothing in {}: {}",
+            tool,
+            content_keys.join("/"),
+            guidance
+        ))
+    }
+}
+
 fn handle_initialize(_params: Option<Value>, id: Option<Value>) -> JsonRpcResponse {
     tracing::info!("Handling initialize request");
 
@@ -105,7 +250,7 @@ fn handle_initialize(_params: Option<Value>, id: Option<Value>) -> JsonRpcRespon
 fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
     tracing::info!("Handling tools/list request");
 
-    let tools = json!([
+    let mut tools = json!([
         {
             "name": "define_sequence_pattern",
             "description": "Create reusable musical patterns (drum beats, bass lines, chord progressions, melodies) that can be referenced with play_sequence. Patterns can be transposed, use different instruments, and repeat with perfect bar-based timing.
@@ -132,18 +277,18 @@ Example: Define a 4-bar house beat once, then play it with variations throughout
                                 "velocity": {"type": "integer", "minimum": 0, "maximum": 127},
                                 "start_time": {"type": "number", "description": "⚠️ DEPRECATED: Use musical_time for better sync"},
                                 "duration": {"type": "number", "description": "⚠️ DEPRECATED: Use musical_duration for better sync"},
+                                "beats": {"type": "number", "exclusiveMinimum": 0, "description": "Duration in beats at the pattern tempo (2.0 = two beats), used when duration and musical_duration are absent"},
                                 "musical_time": {
                                     "type": "object",
                                     "description": "🎼 Musical timing (bar.beat.tick) - RECOMMENDED for perfect sync!",
                                     "properties": {
                                         "bar": {"type": "integer", "minimum": 1, "description": "Bar number (1-based)"},
-                                        "beat": {"type": "integer", "minimum": 1, "maximum": 4, "description": "Beat within bar (1-4)"},
+                                        "beat": {"type": "integer", "minimum": 1, "description": "Beat within bar (1 to beats_per_bar)"},
                                         "tick": {"type": "integer", "minimum": 0, "maximum": 479, "description": "Tick within beat (0-479)"}
                                     },
                                     "required": ["bar", "beat", "tick"]
                                 },
                                 "musical_duration": {
-                                    "type": "object",
                                     "description": "🎵 Musical duration - RECOMMENDED for perfect sync!",
                                     "oneOf": [
                                         {"type": "number", "description": "Duration in bars (e.g., 1.5 for one and a half bars)"},
@@ -162,7 +307,8 @@ Example: Define a 4-bar house beat once, then play it with variations throughout
                             },
                             "anyOf": [
                                 {"required": ["start_time", "duration"]},
-                                {"required": ["musical_time", "musical_duration"]}
+                                {"required": ["musical_time", "musical_duration"]},
+                                {"required": ["start_time", "beats"]}
                             ]
                         }
                     },
@@ -189,10 +335,34 @@ Example: Define a 4-bar house beat once, then play it with variations throughout
                     },
                     "quantize_grid": {
                         "type": "string",
-                        "description": "📐 Snap timing to musical grid for perfect alignment",
+                        "description": "📐 Snap every note start to this grid when the pattern is played, so loose timing lands exactly on the beat ('off' keeps notes as written; swing is applied after snapping)",
                         "enum": ["off", "bar", "beat", "8th", "16th", "32nd", "triplet"],
                         "default": "off"
                     },
+                    "quantize_durations": {
+                        "type": "boolean",
+                        "description": "📏 Also snap each note's end to quantize_grid so notes fill to grid boundaries without ragged overlaps or gaps (gate_length wins if set)",
+                        "default": false
+                    },
+                    "gate_length": {
+                        "type": "number",
+                        "description": "✂️ Note length as a fraction of the step spacing (0.25 = tight/staccato, 1.0 = legato). Overrides note durations",
+                        "minimum": 0.05,
+                        "maximum": 1.0
+                    },
+                    "swing": {
+                        "type": "number",
+                        "description": "🕺 Swing this pattern: delay every off-beat 8th or 16th by this fraction of the subdivision (0.0-0.75). Swing is applied after quantize_grid, to the straight grid positions; durations stay as written. A swung pattern ignores the sequence's swing",
+                        "minimum": 0.0,
+                        "maximum": 0.75,
+                        "default": 0.0
+                    },
+                    "swing_subdivision": {
+                        "type": "string",
+                        "enum": ["8th", "16th"],
+                        "description": "🎷 Which off-beats swing delays (default: 8th)",
+                        "default": "8th"
+                    },
                     "category": {
                         "type": "string",
                         "description": "🏗️ Pattern category for organization (e.g., 'drums', 'bass', 'melody', 'chords')"
@@ -201,6 +371,16 @@ Example: Define a 4-bar house beat once, then play it with variations throughout
                         "type": "array",
                         "description": "🏷️ Tags for searching/filtering patterns",
                         "items": {"type": "string"}
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "♻️ Replace an existing pattern with the same name (default: false, which rejects duplicate names)",
+                        "default": false
+                    },
+                    "normalize": {
+                        "type": "boolean",
+                        "description": "📏 Scale note timings so the pattern exactly fills pattern_bars, fixing loops whose notes run over or stop short (default: false)",
+                        "default": false
                     }
                 },
                 "required": ["name", "notes"]
@@ -226,11 +406,18 @@ Example: {\"patterns\": [{\"pattern_name\": \"drums\", \"start_bar\": 1, \"repea
                                 "velocity": {"type": "integer", "minimum": 0, "maximum": 127},
                                 "start_time": {"type": "number"},
                                 "duration": {"type": "number"},
+                                "beats": {"type": "number", "exclusiveMinimum": 0, "description": "Duration in beats at the sequence tempo, used when duration and musical_duration are absent"},
+                                "musical_time": {"type": "object", "description": "Bar/beat/tick position, instead of start_time"},
+                                "musical_duration": {"description": "Length in bars (number) or a note value ('quarter', 'eighth', ...), instead of duration"},
                                 "channel": {"type": "integer", "minimum": 0, "maximum": 15, "default": 0},
                                 "instrument": {"type": "integer", "minimum": 0, "maximum": 127},
                                 "note_type": {"type": "string", "enum": ["midi", "r2d2"], "default": "midi"}
                             },
-                            "required": ["start_time", "duration"]
+                            "anyOf": [
+                                {"required": ["start_time", "duration"]},
+                                {"required": ["musical_time", "musical_duration"]},
+                                {"required": ["start_time", "beats"]}
+                            ]
                         }
                     },
                     "patterns": {
@@ -245,12 +432,11 @@ Example: {\"patterns\": [{\"pattern_name\": \"drums\", \"start_bar\": 1, \"repea
                                 },
                                 "start_time_offset": {
                                     "type": "number",
-                                    "description": "⏰ DEPRECATED: Use start_bar for perfect sync!",
-                                    "default": 0
+                                    "description": "⏰ DEPRECATED: Use start_bar for perfect sync! Start offset in seconds; cannot be combined with start_bar or bars"
                                 },
                                 "start_bar": {
                                     "type": "integer",
-                                    "description": "🎼 RECOMMENDED: Start at specific bar number (1-based) - ensures perfect alignment!",
+                                    "description": "🎼 RECOMMENDED: Start at specific bar number (1-based) - ensures perfect alignment! Cannot be combined with bars or start_time_offset",
                                     "minimum": 1,
                                     "maximum": 256
                                 },
@@ -263,7 +449,7 @@ Example: {\"patterns\": [{\"pattern_name\": \"drums\", \"start_bar\": 1, \"repea
                                 },
                                 "bars": {
                                     "type": "array",
-                                    "description": "🎯 SMART ARRANGEMENT: Play pattern on specific bars only (e.g., [1, 5, 9, 13])",
+                                    "description": "🎯 SMART ARRANGEMENT: Play pattern on specific bars only (e.g., [1, 5, 9, 13]). Replaces start_bar and repeat_count, so set only one of bars, start_bar or start_time_offset",
                                     "items": {"type": "integer", "minimum": 1, "maximum": 256}
                                 },
                                 "transpose": {
@@ -317,17 +503,223 @@ Example: {\"patterns\": [{\"pattern_name\": \"drums\", \"start_bar\": 1, \"repea
                                     "type": "boolean",
                                     "description": "📐 Align pattern to bar boundaries for perfect sync",
                                     "default": true
+                                },
+                                "retrograde": {
+                                    "type": "boolean",
+                                    "description": "⏪ Play the pattern backwards in time - a rising line becomes a falling one",
+                                    "default": false
+                                },
+                                "invert_around": {
+                                    "type": "integer",
+                                    "description": "🪞 Mirror pitches around this MIDI note (e.g., 60 = C4: E4 becomes A♭3). Applied before transpose",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "follow_chords": {
+                                    "type": "array",
+                                    "description": "🎸 Auto-harmonize: transpose each placement from C to the chord root active at its bar (e.g., [{\"bar\": 1, \"root\": 48}, {\"bar\": 2, \"root\": 55}] plays C then G). Added to transpose",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {
+                                            "bar": {"type": "integer", "minimum": 1, "description": "Bar where the chord starts (1-based)"},
+                                            "root": {"type": "integer", "minimum": 0, "maximum": 127, "description": "Chord root as a MIDI note (only the pitch class is used)"}
+                                        },
+                                        "required": ["bar", "root"]
+                                    }
+                                },
+                                "scale_lock": {
+                                    "type": "object",
+                                    "description": "🔒 Keep transposed notes in key: after inversion, transpose and follow_chords, snap each MIDI note to the nearest pitch of this scale (ties go down). Channel 9 drums are left alone. E.g. {\"root\": 57, \"scale\": \"minor_pentatonic\"} for A minor pentatonic",
+                                    "properties": {
+                                        "root": {"type": "integer", "minimum": 0, "maximum": 127, "description": "Scale root as a MIDI note (only the pitch class is used)"},
+                                        "scale": {"type": "string", "enum": ["major", "minor", "harmonic_minor", "melodic_minor", "dorian", "phrygian", "lydian", "mixolydian", "locrian", "major_pentatonic", "minor_pentatonic", "blues", "chromatic"], "description": "Scale to snap into"}
+                                    },
+                                    "required": ["root", "scale"]
                                 }
                             },
                             "required": ["pattern_name"]
                         }
                     },
+                    "arpeggios": {
+                        "type": "array",
+                        "description": "🎹 Arpeggiator: walk chord tones or a whole scale (scale runs) across a range, expanded into notes",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "notes": {"type": "array", "items": {"type": "integer", "minimum": 0, "maximum": 127}, "description": "Chord tones to arpeggiate (ignored when scale is set)"},
+                                "scale": {"type": "string", "enum": ["major", "minor", "harmonic_minor", "melodic_minor", "dorian", "phrygian", "lydian", "mixolydian", "locrian", "major_pentatonic", "minor_pentatonic", "blues", "chromatic"], "description": "Scale to run through instead of a chord"},
+                                "root": {"type": "integer", "minimum": 0, "maximum": 127, "default": 60, "description": "Root note of the scale"},
+                                "range": {"type": "integer", "minimum": 1, "maximum": 4, "default": 1, "description": "Span of the scale run in octaves"},
+                                "pattern": {"type": "string", "enum": ["up", "down", "up_down", "random"], "default": "up"},
+                                "start_bar": {"type": "integer", "minimum": 1, "default": 1},
+                                "step_beats": {"type": "number", "exclusiveMinimum": 0, "maximum": 16, "default": 0.5, "description": "Step length in beats (0.5 = eighth notes, 0.25 = sixteenths)"},
+                                "count": {"type": "integer", "minimum": 1, "maximum": 1024, "description": "Notes to generate (default: one pass)"},
+                                "velocity": {"type": "integer", "minimum": 1, "maximum": 127, "default": 80},
+                                "accent_every": {"type": "integer", "minimum": 1, "description": "Accent every Nth note starting with the first (e.g. 4 for a pulse on each group of four) with a velocity boost"},
+                                "gate": {"type": "number", "minimum": 0.05, "maximum": 1.0, "default": 1.0, "description": "Note length as a fraction of the step (1.0 = legato, 0.5 = detached, 0.2 = staccato)"},
+                                "instrument": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "channel": {"type": "integer", "minimum": 0, "maximum": 15, "default": 0}
+                            }
+                        }
+                    },
                     "tempo": {
                         "type": "integer",
                         "description": "🎵 Tempo in BPM for the entire sequence",
                         "minimum": 60,
                         "maximum": 200,
                         "default": 120
+                    },
+                    "beats_per_bar": {
+                        "type": "integer",
+                        "description": "🎶 Beats per bar for musical_time/musical_duration and bar placement (default: 4)",
+                        "minimum": 1,
+                        "maximum": 16,
+                        "default": 4
+                    },
+                    "master_seed": {
+                        "type": "integer",
+                        "description": "🎲 Seed for all randomness in the sequence (random presets, noise). Same seed = identical render",
+                        "minimum": 0
+                    },
+                    "min_release": {
+                        "type": "number",
+                        "description": "🎻 Minimum length in seconds for short notes on sustaining instruments (strings, pads, organs) so they release naturally instead of clicking. Drums are unaffected",
+                        "minimum": 0.0,
+                        "maximum": 2.0
+                    },
+                    "auto_pan_by_pitch": {
+                        "type": "boolean",
+                        "description": "🎭 Orchestral stage placement: low notes centered, higher notes spread toward the right. Notes with explicit pan/balance keep their own position",
+                        "default": false
+                    },
+                    "drum_glue": {
+                        "type": "boolean",
+                        "description": "🥁 Drum bus: run every channel 9 drum (MIDI and synthesized) through one shared glue compressor so the kit sounds like a single instrument. Other channels are untouched",
+                        "default": false
+                    },
+                    "master_limiter": {
+                        "type": "boolean",
+                        "description": "🧱 Brickwall limiter on the final mix, after the master volume, so dense chords, stacked layers and a boosted volume never hard-clip. Turn off only for raw, unprocessed output (default: true)",
+                        "default": true
+                    },
+                    "drum_humanize": {
+                        "type": "number",
+                        "description": "🥁 Drum-aware humanize for channel 9 (0.0-1.0): kick and snare stay tight, hi-hats drift more with softer strokes. Reproducible with master_seed",
+                        "minimum": 0.0,
+                        "maximum": 1.0
+                    },
+                    "target_lufs": {
+                        "type": "number",
+                        "description": "🔊 Loudness normalization: measure the render's integrated loudness and apply one gain to hit this target (-50 to -6 LUFS; -16 suits most snippets) so clips play back at consistent volume. Peaks are kept under -1 dBFS",
+                        "minimum": -50.0,
+                        "maximum": -6.0
+                    },
+                    "fit_duration": {
+                        "type": "number",
+                        "description": "⏱️ Fit to a time slot: scale every start time and duration so the sequence ends exactly at this many seconds (e.g. 8.0 to fill an 8-second gap). Pitch is unchanged",
+                        "exclusiveMinimum": 0,
+                        "maximum": 600.0
+                    },
+                    "velocity_brightness": {
+                        "type": "number",
+                        "description": "✨ How much velocity brightens synthesis notes (0.0-1.0, default 0 = off): harder notes open the filter cutoff, softer ones close it, like a real instrument. Off, velocity leaves the timbre alone",
+                        "minimum": 0.0,
+                        "maximum": 1.0,
+                        "default": 0.0
+                    },
+                    "tail_cutoff_db": {
+                        "type": "number",
+                        "description": "🔇 Trim silent tails: once every note has ended, stop rendering when the output stays below this level in dBFS (-120 to -20, default -60). Lower keeps more of long reverb tails",
+                        "minimum": -120.0,
+                        "maximum": -20.0,
+                        "default": -60.0
+                    },
+                    "swing": {
+                        "type": "number",
+                        "description": "🕺 Swing feel: delay every off-beat 8th or 16th (see swing_subdivision) by this fraction of the subdivision (0.0 = straight, 0.33 ≈ triplet shuffle, max 0.75). Put notes on the straight grid; swing moves them",
+                        "minimum": 0.0,
+                        "maximum": 0.75,
+                        "default": 0.0
+                    },
+                    "swing_subdivision": {
+                        "type": "string",
+                        "enum": ["8th", "16th"],
+                        "description": "🎷 Which off-beats swing delays: 8th for jazz/shuffle, 16th for funk/hip-hop grooves (default: 8th)",
+                        "default": "8th"
+                    },
+                    "tempo_changes": {
+                        "type": "array",
+                        "description": "🎢 Tempo ramps for accelerando and ritardando: the tempo moves linearly from the sequence tempo (or the previous change) to each change's tempo, reached at its bar/beat (ascending). Two changes at the same point jump instantly. Patterns placed by start_bar, bars or start_beat follow the ramps, as do notes placed with musical_time, musical_duration or beats",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "at": {
+                                    "type": "object",
+                                    "properties": {
+                                        "bar": {"type": "integer", "minimum": 1},
+                                        "beat": {"type": "integer", "minimum": 1},
+                                        "tick": {"type": "integer", "minimum": 0, "maximum": 479}
+                                    },
+                                    "required": ["bar", "beat", "tick"],
+                                    "description": "Where the tempo is reached"
+                                },
+                                "tempo": {"type": "integer", "minimum": 20, "maximum": 300, "description": "Tempo in BPM at that point"}
+                            },
+                            "required": ["at", "tempo"]
+                        }
+                    },
+                    "async": {
+                        "type": "boolean",
+                        "description": "🚀 Fire and forget: play on a background thread and return a playback_id straight away, e.g. for a celebration sound while the conversation carries on. The audio, reverb tails included, keeps playing after the response (default: false)",
+                        "default": false
+                    },
+                    "negative_start": {
+                        "type": "string",
+                        "enum": ["clamp", "reject"],
+                        "description": "⏪ Notes that pattern offsets (e.g. a negative start_time_offset) push before time zero: clamp moves them to 0.0 (default), reject fails with the note numbers (counting from 1)",
+                        "default": "clamp"
+                    },
+                    "chord_track": {
+                        "type": "array",
+                        "description": "🎼 Chord track: the harmony by bar (ascending). Notes with snap_to_chord move to the nearest tone of the chord active at their bar, so generated leads stay on the changes",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "bar": {"type": "integer", "minimum": 1, "description": "Bar where the chord starts (1-based)"},
+                                "notes": {"type": "array", "items": {"type": "integer", "minimum": 0, "maximum": 127}, "minItems": 1, "description": "Chord tones, e.g. [67, 71, 74] for G major; any octave works"}
+                            },
+                            "required": ["bar", "notes"]
+                        }
+                    },
+                    "program_changes": {
+                        "type": "array",
+                        "description": "🔄 Program automation: switch a channel's instrument at given times (ascending) for evolving textures. Affects notes starting after the change, not notes already sounding",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "at": {"type": "number", "minimum": 0, "description": "Time in seconds from the start of the sequence"},
+                                "instrument": {"type": "integer", "minimum": 0, "maximum": 127, "description": "GM instrument to switch to"},
+                                "channel": {"type": "integer", "minimum": 0, "maximum": 15, "default": 0, "description": "MIDI channel to change (not 9, drums)"}
+                            },
+                            "required": ["at", "instrument"]
+                        }
+                    },
+                    "channels": {
+                        "type": "array",
+                        "description": "🎚️ Channel defaults: set instrument, volume, pan, reverb, chorus and effects once per channel instead of on every note. Notes that set their own value keep it",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "channel": {"type": "integer", "minimum": 0, "maximum": 15, "description": "MIDI channel these defaults apply to"},
+                                "instrument": {"type": "integer", "minimum": 0, "maximum": 127, "description": "GM instrument (not on channel 9, drums)"},
+                                "volume": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "pan": {"type": "integer", "minimum": 0, "maximum": 127, "description": "0 = left, 64 = center, 127 = right"},
+                                "reverb": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "chorus": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "effects": {"type": "array", "items": {"type": "object"}, "description": "Effects chain (same format as note effects) for notes without their own effects"}
+                            },
+                            "required": ["channel"]
+                        }
                     }
                 },
                 "anyOf": [
@@ -346,248 +738,1051 @@ Example: {\"patterns\": [{\"pattern_name\": \"drums\", \"start_bar\": 1, \"repea
             }
         },
         {
-            "name": "play_notes",
-            "description": "Play quick sounds, effects, and simple melodies. Supports MIDI (128 instruments), R2D2 expressions (9 emotions), and synthesis (19 types). For complex compositions with 3+ notes, use define_sequence_pattern + play_sequence instead.
-
-Examples:
-- Success chime: [{\"note\": 72, \"instrument\": 9, \"duration\": 0.5}]
-- R2D2 happy: [{\"note_type\": \"r2d2\", \"r2d2_emotion\": \"Happy\", \"r2d2_intensity\": 0.8, \"r2d2_complexity\": 2, \"duration\": 1.0}]
-- Kick drum: [{\"synth_type\": \"kick\", \"synth_frequency\": 60, \"duration\": 0.5}]",
+            "name": "list_presets",
+            "description": "🎹 List the classic synthesizer presets available for preset_name, grouped by category with a short description of each. Check a name here before using it on a note.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "category": {
+                        "type": "string",
+                        "description": "Only list presets in this category",
+                        "enum": ["bass", "pad", "lead", "keys", "organ", "arp", "drums", "effects"]
+                    }
+                }
+            }
+        },
+        {
+            "name": "play_pattern",
+            "description": "🎧 Solo preview: play one stored pattern once, without building a play_sequence request. Optionally transpose it, swap its MIDI instrument or change the tempo.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Name of a pattern defined with define_sequence_pattern"
+                    },
+                    "transpose": {
+                        "type": "integer",
+                        "description": "Transpose by semitones (-12 to +12, default 0)",
+                        "minimum": -12,
+                        "maximum": 12
+                    },
+                    "instrument_override": {
+                        "type": "integer",
+                        "description": "GM instrument for the pattern's MIDI notes (0-127)",
+                        "minimum": 0,
+                        "maximum": 127
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (60-200, default: the pattern's own tempo)",
+                        "minimum": 60,
+                        "maximum": 200
+                    }
+                },
+                "required": ["name"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "find_patterns",
+            "description": "🔎 Find defined sequence patterns by tag and/or category. Returns only patterns carrying ALL of the given tags and, if category is set, in that category (case-insensitive).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tags": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Tags every returned pattern must have (e.g., [\"funky\", \"groove\"])"
+                    },
+                    "category": {
+                        "type": "string",
+                        "description": "Category returned patterns must be in (e.g., \"drums\", \"bass\")"
+                    }
+                },
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "panic",
+            "description": "🛑 Emergency stop: sends All-Notes-Off and All-Sound-Off on every MIDI channel, clears all synthesis voices, and stops everything currently playing. Use when notes are stuck or playback needs to be silenced immediately.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "stop_playback",
+            "description": "⏹️ Stop everything currently playing with a short fade-out (`stop_fade_ms` in the config, default 10ms) instead of an abrupt cut. Once faded, every source sends All-Notes-Off and All-Sound-Off as with panic. Pass a playback_id from an async play to stop only that playback. Use panic instead when sound must stop this instant.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "playback_id": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "🚀 Stop only this background playback (the playback_id returned by an async play); omit to stop everything"
+                    }
+                },
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "set_master_volume",
+            "description": "🔊 Set the master listening volume for everything mcp-muse plays, including sounds already playing (0.0-2.0, default 1.0). Saved to the config so it persists across sessions. Separate from per-note velocity and target_lufs normalization.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "volume": {
+                        "type": "number",
+                        "description": "🎚️ Master gain: 0.0 = silent, 0.5 = about -6 dB, 1.0 = unchanged, 2.0 = about +6 dB",
+                        "minimum": 0.0,
+                        "maximum": 2.0
+                    }
+                },
+                "required": ["volume"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "save_effects_preset",
+            "description": "💾 Save your own effects chain under a name so any note can use it via `effects_preset`. Saved presets persist across sessions and take priority over built-in presets with the same name.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "🏷️ Preset name to use with `effects_preset` (e.g., 'my_lofi_chain')"
+                    },
+                    "effects": {
+                        "type": "array",
+                        "description": "🎛️ Effects chain in the same format as a note's `effects` (e.g., [{\"type\": \"reverb\", \"room_size\": 0.7}, {\"type\": \"compressor\"}])",
+                        "items": {"type": "object"}
+                    }
+                },
+                "required": ["name", "effects"]
+            }
+        },
+        {
+            "name": "define_preset",
+            "description": "🎛️ Define your own synth preset for this session so any note can use it via `preset_name`. A user preset takes precedence over a built-in preset with the same name (ignoring case), and defining a name again replaces the earlier definition. User presets are not saved between sessions.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "🏷️ Preset name to use with `preset_name` (e.g., 'My Wobble Bass')"
+                    },
+                    "category": {
+                        "type": "string",
+                        "enum": ["bass", "pad", "lead", "keys", "organ", "arp", "drums", "effects"],
+                        "description": "📂 Category the preset is listed under and picked from by `preset_category`"
+                    },
+                    "synth_params": {
+                        "type": "object",
+                        "description": "🎹 Synthesis parameters in the built-in preset format: `synth_type` (e.g., \"Sawtooth\" or {\"Square\": {\"pulse_width\": 0.3}}), `amplitude` (0.0-1.0), `envelope` {attack, decay, sustain, release}, optional `filter` {cutoff, resonance, filter_type: LowPass|HighPass|BandPass} and `effects` [{effect_type: Reverb|Chorus|{\"Delay\": {\"delay_time\": 0.3}}, intensity}]",
+                        "properties": {
+                            "synth_type": {},
+                            "amplitude": {"type": "number", "minimum": 0.0, "maximum": 1.0},
+                            "envelope": {"type": "object"},
+                            "filter": {"type": "object"},
+                            "effects": {"type": "array", "items": {"type": "object"}}
+                        },
+                        "required": ["synth_type", "amplitude", "envelope"]
+                    },
+                    "description": {
+                        "type": "string",
+                        "description": "📝 What the preset sounds like"
+                    },
+                    "tags": {
+                        "type": "array",
+                        "description": "🔖 Tags for finding the preset",
+                        "items": {"type": "string"}
+                    }
+                },
+                "required": ["name", "category", "synth_params"]
+            }
+        },
+        {
+            "name": "check_mono_compatibility",
+            "description": "🔈 Diagnose a mix before sharing it: renders the sequence offline, sums it to mono, and reports how many dB quieter the mono version is. Warns when heavy stereo widening or ping-pong delay cancels on mono speakers (phones, club PAs). Nothing is played.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "notes": {
                         "type": "array",
-                        "description": "Array of notes to play",
-                        "items": {
-                            "type": "object",
-                            "properties": {
-                                "note": {
-                                    "type": "integer",
-                                    "description": "🎵 MIDI note number: 60=C4(middle C), 64=E4, 67=G4. Range: C0(12) to G9(127). Use chromatic scales: C=0,2,4,5,7,9,11 pattern",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "velocity": {
-                                    "type": "integer",
-                                    "description": "🔊 Note attack velocity (intensity): 40=soft, 80=medium, 110=forte, 127=maximum. Affects both volume and timbre",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "start_time": {
-                                    "type": "number",
-                                    "description": "⏰ Start time in seconds. Use 0.0 for simultaneous notes (chords), incremental timing for melodies. DEPRECATED: Consider using musical_time for better sync."
-                                },
-                                "duration": {
-                                    "type": "number",
-                                    "description": "⏳ Note duration in seconds. Try: 0.25=16th, 0.5=8th, 1.0=quarter, 2.0=half, 4.0=whole note. DEPRECATED: Consider using musical_duration for better sync."
-                                },
-                                "musical_time": {
+                        "description": "🎵 Notes in the same format as play_notes",
+                        "items": {"type": "object"}
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    }
+                },
+                "required": ["notes"]
+            }
+        },
+        {
+            "name": "spectrum_sequence",
+            "description": "📊 Spectrogram data for UIs: renders the sequence offline and returns magnitude-spectrum frames (FFT of fixed windows spread over the render, downsampled to frequency bands, in dBFS). Nothing is played.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "notes": {
+                        "type": "array",
+                        "description": "🎵 Notes in the same format as play_notes",
+                        "items": {"type": "object"}
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    },
+                    "frames": {
+                        "type": "integer",
+                        "description": "Maximum number of spectrum frames over the render (default: 64)",
+                        "minimum": 1,
+                        "maximum": 256
+                    },
+                    "bands": {
+                        "type": "integer",
+                        "description": "Frequency bands per frame, equally spaced from 0Hz to Nyquist (default: 64)",
+                        "minimum": 1,
+                        "maximum": 512
+                    }
+                },
+                "required": ["notes"]
+            }
+        },
+        {
+            "name": "render_sequence",
+            "description": "💿 Render to an audio file instead of the speakers: runs the same synthesis and effects as play_notes offline and writes the result, so it works on headless machines with no audio device. WAV is 44.1kHz stereo 16-bit.
+
+Example: {\"notes\": [...], \"output_path\": \"fanfare.wav\"}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "notes": {
+                        "type": "array",
+                        "description": "🎵 Notes in the same format as play_notes; the other play_notes sequence fields apply too",
+                        "items": {"type": "object"}
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "📁 File to write, relative to the renders folder in the mcp-muse data directory; paths that lead outside it are rejected. The extension picks the format (.wav, plus .flac/.ogg/.opus when built with compressed export). Missing directories are created"
+                    },
+                    "dither": {
+                        "type": "boolean",
+                        "description": "TPDF-dither the 16-bit output (default: true)",
+                        "default": true
+                    },
+                    "seamless_loop": {
+                        "type": "boolean",
+                        "description": "🔁 Trim to whole bars and fold the reverb and release tails back onto the start so the file loops without a click (default: false)",
+                        "default": false
+                    }
+                },
+                "required": ["notes", "output_path"]
+            }
+        },
+        {
+            "name": "export_midi_file",
+            "description": "🎹 Save a sequence as a Standard MIDI File (.mid) to keep editing it in a DAW: tempo, time signature, program changes and every MIDI note, one track per channel. R2D2, synthesis and preset notes have no General MIDI equivalent and are left out; the result says how many notes were exported and skipped.
+
+Example: {\"notes\": [...], \"tempo\": 100, \"output_path\": \"exports/groove.mid\"}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "notes": {
+                        "type": "array",
+                        "description": "🎵 Notes in the same format as play_notes; the other play_notes sequence fields apply too",
+                        "items": {"type": "object"}
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM, written to the file (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "📁 File to write (.mid or .midi); missing directories are created"
+                    }
+                },
+                "required": ["notes", "output_path"]
+            }
+        },
+        {
+            "name": "play_midi_file",
+            "description": "📼 Play a Standard MIDI File (.mid, format 0 or 1) through this engine: tempo changes, program changes and every channel are kept. Add channel defaults to put reverb, effects or a different instrument on its parts.
+
+Example: {\"path\": \"songs/theme.mid\", \"channels\": [{\"channel\": 0, \"effects\": [{\"type\": \"reverb\", \"room_size\": 0.7}]}]}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "📁 MIDI file to play"
+                    },
+                    "channels": {
+                        "type": "array",
+                        "description": "🎚️ Channel defaults (same format as play_notes): volume, pan, reverb, chorus and effects per channel. An instrument here only fills channels the file never sends a program change on",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "channel": {"type": "integer", "minimum": 0, "maximum": 15, "description": "MIDI channel these defaults apply to"},
+                                "instrument": {"type": "integer", "minimum": 0, "maximum": 127, "description": "GM instrument (not on channel 9, drums)"},
+                                "volume": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "pan": {"type": "integer", "minimum": 0, "maximum": 127, "description": "0 = left, 64 = center, 127 = right"},
+                                "reverb": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "chorus": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "effects": {"type": "array", "items": {"type": "object"}, "description": "Effects chain (same format as note effects)"}
+                            },
+                            "required": ["channel"]
+                        }
+                    }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "play_abc",
+            "description": "🎼 Play a tune written in ABC notation. Supports a single melody line with the K: (key and mode), M: (meter), L: (unit note length) and Q: (tempo) fields, accidentals, octave marks, note lengths, rests, ties, broken rhythms (> <), tuplets and |: :| repeats. Chords, grace notes, decorations and multiple voices are rejected with the line and column of the offending token.
+
+Example: {\"abc\": \"X:1\\nM:6/8\\nL:1/8\\nQ:3/8=100\\nK:D\\n|: A | d2 f a2 f | g2 e c2 A :|\", \"instrument\": 73}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "abc": {
+                        "type": "string",
+                        "description": "🎼 The ABC tune, header fields first"
+                    },
+                    "instrument": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 127,
+                        "description": "🎹 GM instrument for the melody (default: 0, piano)"
+                    },
+                    "channel": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 15,
+                        "description": "📻 MIDI channel for the melody (default: 0)"
+                    }
+                },
+                "required": ["abc"]
+            }
+        },
+        {
+            "name": "rearrange",
+            "description": "✂️ Breakbeat-style editing: renders the sequence, cuts the audio into equal slices and plays them back in a new order. Slices are numbered from 0 and may be repeated or left out.
+
+Example: {\"notes\": [...], \"slices\": 8, \"order\": [0, 1, 0, 3, 4, 4, 6, 7]}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "notes": {
+                        "type": "array",
+                        "description": "🎵 Notes in the same format as play_notes",
+                        "items": {"type": "object"}
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    },
+                    "slices": {
+                        "type": "integer",
+                        "description": "Number of equal slices to cut the render into",
+                        "minimum": 1,
+                        "maximum": 64
+                    },
+                    "order": {
+                        "type": "array",
+                        "description": "Slice indices (0-based) in playback order, e.g. [3, 2, 1, 0] reverses a 4-slice loop",
+                        "items": {"type": "integer", "minimum": 0},
+                        "minItems": 1,
+                        "maxItems": 256
+                    }
+                },
+                "required": ["notes", "slices", "order"]
+            }
+        },
+        {
+            "name": "concat_patterns",
+            "description": "🔗 Chain defined patterns end to end into one sequence without bar math: each pattern starts on the bar where the previous one ends. Returns a sequence to pass to play_sequence (nothing is played).
+
+Example: {\"names\": [\"intro\", \"verse\", {\"pattern_name\": \"verse\", \"transpose\": 5}, \"outro\"], \"tempo\": 110}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "names": {
+                        "type": "array",
+                        "description": "🎼 Patterns in playback order: a pattern name, or {pattern_name, transpose, instrument_override} to vary one segment",
+                        "items": {
+                            "oneOf": [
+                                {"type": "string"},
+                                {
                                     "type": "object",
-                                    "description": "🎼 Musical timing (bar.beat.tick) - Alternative to start_time for precise timing",
                                     "properties": {
-                                        "bar": {"type": "integer", "minimum": 1, "description": "Bar number (1-based)"},
-                                        "beat": {"type": "integer", "minimum": 1, "maximum": 4, "description": "Beat within bar (1-4)"},
-                                        "tick": {"type": "integer", "minimum": 0, "maximum": 479, "description": "Tick within beat (0-479)"}
-                                    },
-                                    "required": ["bar", "beat", "tick"]
-                                },
-                                "musical_duration": {
-                                    "type": "object",
-                                    "description": "🎵 Musical duration - Alternative to duration for precise timing",
-                                    "oneOf": [
-                                        {"type": "number", "description": "Duration in bars (e.g., 1.5 for one and a half bars)"},
-                                        {"type": "string", "enum": ["whole", "half", "quarter", "eighth", "sixteenth", "triplet"], "description": "Note values"}
-                                    ]
-                                },
-                                "channel": {
-                                    "type": "integer",
-                                    "description": "📻 MIDI channel (0-15): Use different channels for different instruments in complex arrangements. Each channel can have unique instrument/effects",
-                                    "minimum": 0,
-                                    "maximum": 15
-                                },
-                                "instrument": {
-                                    "type": "integer",
-                                    "description": "🎹 GM Instrument: 0=Piano, 1=Bright Piano, 25=Steel Guitar, 40=Violin, 42=Cello, 56=Trumpet, 60=French Horn, 68=Oboe, 73=Flute, 80=Square Lead, 104=Sitar. Use variety for rich orchestration!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "reverb": {
-                                    "type": "integer",
-                                    "description": "🏛️ Reverb depth (0-127): Simulates acoustic spaces. Try 0=dry, 30=small room, 60=hall, 100=cathedral. Essential for realistic orchestral sound!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "chorus": {
-                                    "type": "integer",
-                                    "description": "✨ Chorus depth (0-127): Adds shimmer and richness. Try 0=off, 30=subtle, 60=lush, 100=ethereal. Great for strings, pads, and vocals!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "volume": {
-                                    "type": "integer",
-                                    "description": "🔊 Channel volume (0-127): Master volume per channel. Use for mixing balance - lead melody at 100-127, accompaniment at 60-90, bass at 80-100",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "pan": {
-                                    "type": "integer",
-                                    "description": "↔️ Pan position (0-127): For MONO instruments like trumpet, flute. 0=hard left, 64=center, 127=hard right. Create stereo width in arrangements!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "balance": {
-                                    "type": "integer",
-                                    "description": "⚖️ Balance control (0-127): For STEREO instruments like piano, strings. 0=left, 64=center, 127=right. Use this instead of pan for piano!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "expression": {
-                                    "type": "integer",
-                                    "description": "🎭 Expression control (0-127): Dynamic musical expression beyond velocity. 40=pianissimo, 80=normal, 110=forte, 127=fortissimo. Creates emotional phrasing!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "sustain": {
-                                    "type": "integer",
-                                    "description": "🎹 Sustain pedal (0-127): Piano-style sustain. 0=off (staccato), 127=on (legato). Use for flowing passages and rich harmonic resonance!",
-                                    "minimum": 0,
-                                    "maximum": 127
-                                },
-                                "note_type": {
-                                    "type": "string",
-                                    "description": "🎭 Note type: 'midi' for musical notes, 'r2d2' for robotic expressions. Defaults to 'midi'",
-                                    "enum": ["midi", "r2d2"],
-                                    "default": "midi"
-                                },
-                                "r2d2_emotion": {
-                                    "type": "string",
-                                    "description": "🤖 R2D2 emotion when note_type='r2d2': Choose from 9 distinct emotional expressions. **REQUIRED when note_type='r2d2'**",
-                                    "enum": ["Happy", "Sad", "Excited", "Worried", "Curious", "Affirmative", "Negative", "Surprised", "Thoughtful"]
-                                },
-                                "r2d2_intensity": {
-                                    "type": "number",
-                                    "description": "🔥 R2D2 emotional intensity (0.0-1.0): 0.3=subtle, 0.6=moderate, 0.9=dramatic. **REQUIRED when note_type='r2d2'**",
-                                    "minimum": 0.0,
-                                    "maximum": 1.0
-                                },
-                                "r2d2_complexity": {
-                                    "type": "integer",
-                                    "description": "🗣️ R2D2 phrase complexity (1-5 syllables): 1=simple beep, 3=conversational, 5=complex phrase. **REQUIRED when note_type='r2d2'**",
-                                    "minimum": 1,
-                                    "maximum": 5
-                                },
-                                "r2d2_pitch_range": {
-                                    "type": "array",
-                                    "description": "🎵 R2D2 frequency range [min_hz, max_hz]: [200,600]=low, [300,800]=normal, [400,1000]=high",
-                                    "items": {
-                                        "type": "number"
+                                        "pattern_name": {"type": "string"},
+                                        "transpose": {"type": "integer", "minimum": -12, "maximum": 12, "description": "Semitones to shift this segment"},
+                                        "instrument_override": {"type": "integer", "minimum": 0, "maximum": 127, "description": "GM instrument for this segment's MIDI notes"}
                                     },
-                                    "minItems": 2,
-                                    "maxItems": 2
-                                },
-                                "r2d2_context": {
-                                    "type": "string",
-                                    "description": "💭 R2D2 context: Optional conversation context for enhanced expression adaptation"
-                                },
-                                "synth_type": {
-                                    "type": "string",
-                                    "description": "🎛️ Synthesis type: 'sine', 'square', 'sawtooth', 'triangle', 'noise', 'fm', 'granular', 'wavetable', 'kick', 'snare', 'hihat', 'cymbal', 'swoosh', 'zap', 'chime', 'burst', 'pad', 'texture', 'drone' (optional)"
-                                },
-                                "synth_frequency": {
-                                    "type": "number",
-                                    "description": "🎵 Synthesis frequency in Hz (20-20000, optional, overrides MIDI note if present)",
-                                    "minimum": 20,
-                                    "maximum": 20000
-                                },
-                                "synth_amplitude": {
-                                    "type": "number",
-                                    "description": "🔊 Synthesis amplitude (0.0-1.0, optional, defaults to 0.7)",
-                                    "minimum": 0.0,
-                                    "maximum": 1.0
-                                },
-                                "synth_attack": {
-                                    "type": "number",
-                                    "description": "⚡ Attack time in seconds (0.0-5.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 5.0
+                                    "required": ["pattern_name"]
+                                }
+                            ]
+                        }
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "🎵 Tempo in BPM for the sequence (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200,
+                        "default": 120
+                    }
+                },
+                "required": ["names"]
+            }
+        },
+        {
+            "name": "save_project",
+            "description": "💾 Save the session as one project file: every defined pattern, the working tempo and the last few sequences played with play_sequence. Reload it later with load_project to pick up where you left off.
+
+Example: {\"path\": \"projects/space-opera.json\", \"tempo\": 96}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "📁 Where to write the project (JSON); missing directories are created"
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "🎵 Working tempo to record in BPM (default: the tempo of the last played sequence)",
+                        "minimum": 60,
+                        "maximum": 200
+                    }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "load_project",
+            "description": "📂 Load a project saved with save_project: its patterns are validated and defined for play_sequence, and the last sequence played is returned so it can be played again.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "📁 Project file to load"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace patterns that are already defined with the same names (default: false, which refuses the load instead)",
+                        "default": false
+                    }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "list_variations",
+            "description": "🎨 List the variations a classic preset supports for `preset_variation` (e.g., 'squelchy' for 'TB-303 Acid'), with a short description of each.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "preset_name": {
+                        "type": "string",
+                        "description": "🎹 Exact preset name (e.g., 'TB-303 Acid', 'Jupiter Pad')"
+                    }
+                },
+                "required": ["preset_name"]
+            }
+        },
+        {
+            "name": "audition_preset",
+            "description": "🎧 Quick listen: play a single note with a classic preset (default C4 for 1.5s) without building a sequence. Great for browsing sounds before using one in a composition.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "preset_name": {
+                        "type": "string",
+                        "description": "🎹 Exact preset name (e.g., 'JP-8 Strings', 'TB-303 Acid')"
+                    },
+                    "note": {
+                        "type": "integer",
+                        "description": "🎵 MIDI note to play (default: 60 = C4)",
+                        "minimum": 0,
+                        "maximum": 127,
+                        "default": 60
+                    },
+                    "duration": {
+                        "type": "number",
+                        "description": "⏱️ Note length in seconds (default: 1.5)",
+                        "exclusiveMinimum": 0,
+                        "maximum": 10,
+                        "default": 1.5
+                    }
+                },
+                "required": ["preset_name"]
+            }
+        },
+        {
+            "name": "engine_status",
+            "description": "🩺 Debug stuck or silent audio: reports whether anything is playing right now, how many playbacks and voices are active, the master volume and whether a SoundFont is available for MIDI notes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "generate_melody",
+            "description": "🎲 Sketch a melody for idea generation: seeded, in-key notes following a contour hint. The same settings and seed always give the same melody. Returns a sequence to pass to play_sequence (nothing is played).
+
+Example: {\"root\": 62, \"scale\": \"dorian\", \"length\": 16, \"density\": 0.6, \"contour\": \"arch\", \"seed\": 42}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "root": {
+                        "type": "integer",
+                        "description": "🎵 Root note of the key (MIDI, default: 60 = C4)",
+                        "minimum": 0,
+                        "maximum": 127
+                    },
+                    "scale": {
+                        "type": "string",
+                        "description": "🎼 Scale to stay in: major, minor, harmonic_minor, melodic_minor, dorian, phrygian, lydian, mixolydian, locrian, major_pentatonic, minor_pentatonic, blues, chromatic (default: major)"
+                    },
+                    "length": {
+                        "type": "integer",
+                        "description": "Number of notes",
+                        "minimum": 1,
+                        "maximum": 256
+                    },
+                    "density": {
+                        "type": "number",
+                        "description": "🥁 Rhythmic density: 0.0=long held notes, 0.5=quarters and eighths, 1.0=busy sixteenths (default: 0.5)",
+                        "minimum": 0.0,
+                        "maximum": 1.0
+                    },
+                    "contour": {
+                        "type": "string",
+                        "enum": ["rising", "falling", "arch", "random"],
+                        "description": "📈 Shape of the pitch line (default: random)"
+                    },
+                    "range": {
+                        "type": "integer",
+                        "description": "Span above the root in octaves (default: 2)",
+                        "minimum": 1,
+                        "maximum": 4
+                    },
+                    "seed": {
+                        "type": "integer",
+                        "description": "🎲 Seed; reuse it to get the same melody again, change it for a new idea",
+                        "minimum": 0
+                    },
+                    "velocity": {
+                        "type": "integer",
+                        "description": "Velocity of every note (default: 80)",
+                        "minimum": 1,
+                        "maximum": 127
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM for the note timings (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    },
+                    "instrument": {
+                        "type": "integer",
+                        "description": "GM instrument for the notes (optional)",
+                        "minimum": 0,
+                        "maximum": 127
+                    }
+                },
+                "required": ["length", "seed"]
+            }
+        },
+        {
+            "name": "progression_voiced",
+            "description": "🎹 Play a chord progression as block chords with smooth voice leading: each chord takes the inversion that moves the voices least from the one before, keeping common tones instead of jumping back to root position. Returns the voicings and the sequence that was played.
+
+Example: {\"key\": 60, \"chords\": [\"C\", \"G\", \"Am\", \"F\"], \"instrument\": 0}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "key": {
+                        "type": "integer",
+                        "description": "🎵 Key as a MIDI note (default: 60 = C4). Roman numerals count from it and the first chord is voiced in its octave",
+                        "minimum": 24,
+                        "maximum": 96
+                    },
+                    "chords": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "🎼 Chord names (C, Am, G7, Bbmaj7, F#dim, Dsus4) or roman numerals in the key (I, vi, ii7, V7, bVII). Suffixes: m, dim, aug, sus2, sus4, 7, maj7, m7, m7b5, dim7",
+                        "minItems": 1,
+                        "maxItems": 64
+                    },
+                    "beats_per_chord": {
+                        "type": "number",
+                        "description": "Length of each chord in beats (default: 4)",
+                        "minimum": 0.25,
+                        "maximum": 16
+                    },
+                    "velocity": {
+                        "type": "integer",
+                        "description": "Velocity of every note (default: 80)",
+                        "minimum": 1,
+                        "maximum": 127
+                    },
+                    "instrument": {
+                        "type": "integer",
+                        "description": "GM instrument for the chords (default: 0 = piano)",
+                        "minimum": 0,
+                        "maximum": 127
+                    },
+                    "channel": {
+                        "type": "integer",
+                        "description": "MIDI channel (default: 0)",
+                        "minimum": 0,
+                        "maximum": 15
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (default: 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    }
+                },
+                "required": ["chords"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "get_capabilities",
+            "description": "🧭 Report what this server supports: version, every synthesis type and effect type, preset and effects-preset counts, and whether a SoundFont is available for MIDI playback. Check this before requesting optional features.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "play_notes",
+            "description": "Play quick sounds, effects, and simple melodies. Supports MIDI (128 instruments), R2D2 expressions (9 emotions), and synthesis (19 types). For complex compositions with 3+ notes, use define_sequence_pattern + play_sequence instead.
+
+Examples:
+- Success chime: [{\"note\": 72, \"instrument\": 9, \"duration\": 0.5}]
+- R2D2 happy: [{\"note_type\": \"r2d2\", \"r2d2_emotion\": \"Happy\", \"r2d2_intensity\": 0.8, \"r2d2_complexity\": 2, \"duration\": 1.0}]
+- Kick drum: [{\"synth_type\": \"kick\", \"synth_frequency\": 60, \"duration\": 0.5}]",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "notes": {
+                        "type": "array",
+                        "description": "Array of notes to play",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "note": {
+                                    "type": "integer",
+                                    "description": "🎵 MIDI note number: 60=C4(middle C), 64=E4, 67=G4. Range: C0(12) to G9(127). Use chromatic scales: C=0,2,4,5,7,9,11 pattern",
+                                    "minimum": 0,
+                                    "maximum": 127
                                 },
-                                "synth_decay": {
-                                    "type": "number",
-                                    "description": "📉 Decay time in seconds (0.0-5.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 5.0
+                                "velocity": {
+                                    "type": "integer",
+                                    "description": "🔊 Note attack velocity (intensity): 40=soft, 80=medium, 110=forte, 127=maximum. Affects both volume and timbre",
+                                    "minimum": 0,
+                                    "maximum": 127
                                 },
-                                "synth_sustain": {
+                                "start_time": {
                                     "type": "number",
-                                    "description": "🎹 Sustain level (0.0-1.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 1.0
+                                    "description": "⏰ Start time in seconds. Use 0.0 for simultaneous notes (chords), incremental timing for melodies. The first MIDI note starts exactly on time; later MIDI notes off its 64-frame block grid can sound up to 63 frames (~1.4ms) late. DEPRECATED: Consider using musical_time for better sync."
                                 },
-                                "synth_release": {
+                                "duration": {
                                     "type": "number",
-                                    "description": "🌊 Release time in seconds (0.0-10.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 10.0
-                                },
-                                "synth_filter_type": {
-                                    "type": "string",
-                                    "description": "🎚️ Filter type: 'lowpass', 'highpass', 'bandpass' (optional)",
-                                    "enum": ["lowpass", "highpass", "bandpass"]
+                                    "description": "⏳ Note duration in seconds. Try: 0.25=16th, 0.5=8th, 1.0=quarter, 2.0=half, 4.0=whole note. DEPRECATED: Consider using musical_duration for better sync."
                                 },
-                                "synth_filter_cutoff": {
+                                "beats": {
                                     "type": "number",
-                                    "description": "🔧 Filter cutoff frequency in Hz (20-20000, optional)",
-                                    "minimum": 20,
-                                    "maximum": 20000
+                                    "description": "🥁 Duration in beats at the sequence tempo (1.0 = quarter note in 4/4, 0.5 = eighth). Quick rhythm shorthand; duration and musical_duration take precedence",
+                                    "exclusiveMinimum": 0
                                 },
-                                "synth_filter_resonance": {
+                                "pre_roll": {
                                     "type": "number",
-                                    "description": "✨ Filter resonance (0.0-1.0, optional)",
+                                    "description": "⏮️ Start the sound this many seconds early so a slow attack (pads, swells) peaks exactly on start_time. The note still ends where it would have (0.0-4.0, optional)",
                                     "minimum": 0.0,
-                                    "maximum": 1.0
+                                    "maximum": 4.0
                                 },
-                                "synth_reverb": {
-                                    "type": "number",
-                                    "description": "🏛️ Synthesis reverb intensity (0.0-1.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 1.0
+                                "articulation": {
+                                    "type": "string",
+                                    "enum": ["staccato", "legato", "tenuto", "accent", "marcato"],
+                                    "description": "🎻 Articulation instead of hand-tuned duration/velocity: staccato=half length, legato=slight overlap into the next note, tenuto=full value, accent=+20% velocity, marcato=+35% velocity and shorter (optional)"
                                 },
-                                "synth_chorus": {
-                                    "type": "number",
-                                    "description": "✨ Synthesis chorus intensity (0.0-1.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 1.0
+                                "roll_accelerate": {
+                                    "type": "object",
+                                    "description": "🥁 Accelerating roll: turn this note into repeated hits that speed up, e.g. a snare build into a drop. Hits start at start_time",
+                                    "properties": {
+                                        "start_rate": {
+                                            "type": "number",
+                                            "description": "Hits per second at the start of the roll",
+                                            "exclusiveMinimum": 0,
+                                            "maximum": 64
+                                        },
+                                        "end_rate": {
+                                            "type": "number",
+                                            "description": "Hits per second at the end of the roll; must be faster than start_rate",
+                                            "exclusiveMinimum": 0,
+                                            "maximum": 64
+                                        },
+                                        "duration": {
+                                            "type": "number",
+                                            "description": "Length of the roll in seconds",
+                                            "exclusiveMinimum": 0,
+                                            "maximum": 8
+                                        },
+                                        "shape": {
+                                            "type": "string",
+                                            "enum": ["linear", "exponential"],
+                                            "description": "Rate curve: linear speeds up steadily, exponential holds back and rushes at the end (default linear)"
+                                        }
+                                    },
+                                    "required": ["start_rate", "end_rate", "duration"],
+                                    "additionalProperties": false
                                 },
-                                "synth_delay": {
-                                    "type": "number",
-                                    "description": "🔄 Synthesis delay intensity (0.0-1.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 1.0
+                                "tremolo_pick": {
+                                    "type": "object",
+                                    "description": "🪕 Tremolo picking: re-attack this melodic note at a steady rate for its whole duration (mandolin, balalaika). Unlike amplitude tremolo, every stroke is a new attack",
+                                    "properties": {
+                                        "rate_beats": {
+                                            "type": "number",
+                                            "description": "Gap between strokes in beats at the sequence tempo: 0.5=eighths, 0.25=sixteenths",
+                                            "minimum": 0.03125
+                                        },
+                                        "velocity_variation": {
+                                            "type": "number",
+                                            "description": "Largest velocity change per stroke as a fraction of the note's velocity (default 0.1)",
+                                            "minimum": 0.0,
+                                            "maximum": 1.0
+                                        }
+                                    },
+                                    "required": ["rate_beats"],
+                                    "additionalProperties": false
                                 },
-                                "synth_delay_time": {
-                                    "type": "number",
-                                    "description": "⏰ Synthesis delay time in seconds (0.0-2.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 2.0
+                                "snap_to_chord": {
+                                    "type": "boolean",
+                                    "description": "🎯 Snap this note (or synth frequency) to the nearest tone of the chord_track chord at its bar",
+                                    "default": false
                                 },
-                                "synth_pulse_width": {
-                                    "type": "number",
-                                    "description": "📊 Pulse width for square wave (0.1-0.9, optional)",
-                                    "minimum": 0.1,
-                                    "maximum": 0.9
+                                "musical_time": {
+                                    "type": "object",
+                                    "description": "🎼 Musical timing (bar.beat.tick) - Alternative to start_time for precise timing",
+                                    "properties": {
+                                        "bar": {"type": "integer", "minimum": 1, "description": "Bar number (1-based)"},
+                                        "beat": {"type": "integer", "minimum": 1, "description": "Beat within bar (1 to beats_per_bar)"},
+                                        "tick": {"type": "integer", "minimum": 0, "maximum": 479, "description": "Tick within beat (0-479)"}
+                                    },
+                                    "required": ["bar", "beat", "tick"]
                                 },
-                                "synth_modulator_freq": {
-                                    "type": "number",
-                                    "description": "🌀 FM modulator frequency in Hz (0.1-1000.0, optional)",
-                                    "minimum": 0.1,
-                                    "maximum": 1000.0
+                                "musical_duration": {
+                                    "description": "🎵 Musical duration - Alternative to duration for precise timing",
+                                    "oneOf": [
+                                        {"type": "number", "description": "Duration in bars (e.g., 1.5 for one and a half bars)"},
+                                        {"type": "string", "enum": ["whole", "half", "quarter", "eighth", "sixteenth", "triplet"], "description": "Note values"}
+                                    ]
                                 },
-                                "synth_modulation_index": {
-                                    "type": "number",
-                                    "description": "🎛️ FM modulation index (0.0-10.0, optional)",
-                                    "minimum": 0.0,
-                                    "maximum": 10.0
+                                "channel": {
+                                    "type": "integer",
+                                    "description": "📻 MIDI channel (0-15): Use different channels for different instruments in complex arrangements. Each channel can have unique instrument/effects",
+                                    "minimum": 0,
+                                    "maximum": 15
+                                },
+                                "instrument": {
+                                    "type": "integer",
+                                    "description": "🎹 GM Instrument: 0=Piano, 1=Bright Piano, 25=Steel Guitar, 40=Violin, 42=Cello, 56=Trumpet, 60=French Horn, 68=Oboe, 73=Flute, 80=Square Lead, 104=Sitar. Use variety for rich orchestration!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "reverb": {
+                                    "type": "integer",
+                                    "description": "🏛️ Reverb depth (0-127): Simulates acoustic spaces. Try 0=dry, 30=small room, 60=hall, 100=cathedral. Essential for realistic orchestral sound!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "chorus": {
+                                    "type": "integer",
+                                    "description": "✨ Chorus depth (0-127): Adds shimmer and richness. Try 0=off, 30=subtle, 60=lush, 100=ethereal. Great for strings, pads, and vocals!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "volume": {
+                                    "type": "integer",
+                                    "description": "🔊 Channel volume (0-127): Master volume per channel. Use for mixing balance - lead melody at 100-127, accompaniment at 60-90, bass at 80-100",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "pan": {
+                                    "type": "integer",
+                                    "description": "↔️ Pan position (0-127): For MONO instruments like trumpet, flute, and for synthesis and preset notes (equal-power). 0=hard left, 64=center, 127=hard right. Create stereo width in arrangements!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "balance": {
+                                    "type": "integer",
+                                    "description": "⚖️ Balance control (0-127): For STEREO instruments like piano, strings. 0=left, 64=center, 127=right. Use this instead of pan for piano!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "expression": {
+                                    "type": "integer",
+                                    "description": "🎭 Expression control (0-127): Dynamic musical expression beyond velocity. 40=pianissimo, 80=normal, 110=forte, 127=fortissimo. Creates emotional phrasing!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "sustain": {
+                                    "type": "integer",
+                                    "description": "🎹 Sustain pedal (0-127): Piano-style sustain. 0=off (staccato), 127=on (legato). Use for flowing passages and rich harmonic resonance!",
+                                    "minimum": 0,
+                                    "maximum": 127
+                                },
+                                "note_type": {
+                                    "type": "string",
+                                    "description": "🎭 Note type: 'midi' for musical notes, 'r2d2' for robotic expressions. Defaults to 'midi'",
+                                    "enum": ["midi", "r2d2"],
+                                    "default": "midi"
+                                },
+                                "r2d2_emotion": {
+                                    "type": "string",
+                                    "description": "🤖 R2D2 emotion when note_type='r2d2': Choose from 9 distinct emotional expressions. **REQUIRED when note_type='r2d2'**",
+                                    "enum": ["Happy", "Sad", "Excited", "Worried", "Curious", "Affirmative", "Negative", "Surprised", "Thoughtful"]
+                                },
+                                "r2d2_intensity": {
+                                    "type": "number",
+                                    "description": "🔥 R2D2 emotional intensity (0.0-1.0): 0.3=subtle, 0.6=moderate, 0.9=dramatic. **REQUIRED when note_type='r2d2'**",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "r2d2_complexity": {
+                                    "type": "integer",
+                                    "description": "🗣️ R2D2 phrase complexity (1-5 syllables): 1=simple beep, 3=conversational, 5=complex phrase. **REQUIRED when note_type='r2d2'**",
+                                    "minimum": 1,
+                                    "maximum": 5
+                                },
+                                "r2d2_pitch_range": {
+                                    "type": "array",
+                                    "description": "🎵 R2D2 frequency range [min_hz, max_hz]: [200,600]=low, [300,800]=normal, [400,1000]=high",
+                                    "items": {
+                                        "type": "number"
+                                    },
+                                    "minItems": 2,
+                                    "maxItems": 2
+                                },
+                                "r2d2_context": {
+                                    "type": "string",
+                                    "description": "💭 R2D2 context: Optional conversation context for enhanced expression adaptation"
+                                },
+                                "r2d2_voice": {
+                                    "type": "object",
+                                    "description": "🤖 R2D2 voice character: give each droid its own timbre. Omit for the classic voice",
+                                    "properties": {
+                                        "pitch_center": {
+                                            "type": "number",
+                                            "description": "Pitch relative to the classic voice (0.5 = an octave lower, 2.0 = an octave higher; default 1.0)",
+                                            "minimum": 0.25,
+                                            "maximum": 4.0
+                                        },
+                                        "ring_mod_amount": {
+                                            "type": "number",
+                                            "description": "Ring modulation: 1.0 = metallic classic droid, 0.0 = pure whistled tone (default 1.0)",
+                                            "minimum": 0.0,
+                                            "maximum": 1.0
+                                        },
+                                        "formant_shift": {
+                                            "type": "number",
+                                            "description": "Vocal formant position: below 1.0 sounds larger and throatier, above 1.0 smaller and nasal (default 1.0)",
+                                            "minimum": 0.5,
+                                            "maximum": 2.0
+                                        }
+                                    },
+                                    "additionalProperties": false
+                                },
+                                "synth_type": {
+                                    "type": "string",
+                                    "description": "🎛️ Synthesis type: 'sine', 'square', 'sawtooth', 'triangle', 'noise', 'morph', 'fm', 'granular', 'wavetable', 'kick', 'snare', 'hihat', 'cymbal', 'swoosh', 'zap', 'chime', 'burst', 'pad', 'texture', 'drone' (optional)"
+                                },
+                                "synth_frequency": {
+                                    "type": "number",
+                                    "description": "🎵 Synthesis frequency in Hz (20-20000, optional, overrides MIDI note if present)",
+                                    "minimum": 20,
+                                    "maximum": 20000
+                                },
+                                "synth_amplitude": {
+                                    "type": "number",
+                                    "description": "🔊 Synthesis amplitude (0.0-1.0, optional, defaults to 0.7)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_attack": {
+                                    "type": "number",
+                                    "description": "⚡ Attack time in seconds (0.0-5.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 5.0
+                                },
+                                "synth_decay": {
+                                    "type": "number",
+                                    "description": "📉 Decay time in seconds (0.0-5.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 5.0
+                                },
+                                "synth_sustain": {
+                                    "type": "number",
+                                    "description": "🎹 Sustain level (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_release": {
+                                    "type": "number",
+                                    "description": "🌊 Release time in seconds (0.0-10.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 10.0
+                                },
+                                "synth_envelope_curve": {
+                                    "type": "string",
+                                    "description": "📈 Shape of the attack, decay and release (optional): 'linear' (default) straight ramps, 'exponential' fast-then-settling analog curves, 'logarithmic' slow-then-rushing curves",
+                                    "enum": ["linear", "exponential", "logarithmic"]
+                                },
+                                "synth_filter_type": {
+                                    "type": "string",
+                                    "description": "🎚️ Filter type: 'lowpass', 'highpass', 'bandpass' (optional)",
+                                    "enum": ["lowpass", "highpass", "bandpass"]
+                                },
+                                "synth_filter_cutoff": {
+                                    "type": "number",
+                                    "description": "🔧 Filter cutoff frequency in Hz (20-20000, optional)",
+                                    "minimum": 20,
+                                    "maximum": 20000
+                                },
+                                "synth_filter_resonance": {
+                                    "type": "number",
+                                    "description": "✨ Filter resonance (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_filter_soft_start": {
+                                    "type": "number",
+                                    "description": "🎚️ Seconds the filter takes to open to full resonance at note start, so screaming-resonance notes don't thump (0.0-0.1, default 0.005; 0 disables)",
+                                    "minimum": 0.0,
+                                    "maximum": 0.1
+                                },
+                                "synth_reverb": {
+                                    "type": "number",
+                                    "description": "🏛️ Synthesis reverb intensity (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_chorus": {
+                                    "type": "number",
+                                    "description": "✨ Synthesis chorus intensity (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_delay": {
+                                    "type": "number",
+                                    "description": "🔄 Synthesis delay intensity (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_delay_time": {
+                                    "type": "number",
+                                    "description": "⏰ Synthesis delay time in seconds (0.0-2.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 2.0
+                                },
+                                "synth_noise_color": {
+                                    "type": "string",
+                                    "description": "🌫️ Noise color for synth_type 'noise' (optional): 'white' (default) bright hiss, 'pink' (-3dB/octave) rain and surf, 'brown' (-6dB/octave) wind and distant ocean rumble",
+                                    "enum": ["white", "pink", "brown"]
+                                },
+                                "synth_pulse_width": {
+                                    "type": "number",
+                                    "description": "📊 Pulse width for square wave (0.1-0.9, optional)",
+                                    "minimum": 0.1,
+                                    "maximum": 0.9
+                                },
+                                "synth_pwm": {
+                                    "type": "object",
+                                    "description": "🌊 Pulse-width modulation for square wave: a sine LFO swings the pulse width around synth_pulse_width for the moving, chorused sound of analog string and pad patches (optional)",
+                                    "properties": {
+                                        "rate": {
+                                            "type": "number",
+                                            "description": "LFO rate in Hz (0.05-20.0)",
+                                            "minimum": 0.05,
+                                            "maximum": 20.0
+                                        },
+                                        "depth": {
+                                            "type": "number",
+                                            "description": "How far the pulse width swings either way (0.0-0.4)",
+                                            "minimum": 0.0,
+                                            "maximum": 0.4
+                                        }
+                                    },
+                                    "required": ["rate", "depth"]
+                                },
+                                "synth_phase": {
+                                    "type": "number",
+                                    "description": "🔁 Starting oscillator phase as a fraction of a cycle (0.0-1.0, default 0.0). Sine, square, sawtooth and triangle notes start at this point of their waveform, so stacked voices line up reproducibly; 0.5 on one of two identical notes cancels them",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_vibrato_rate": {
+                                    "type": "number",
+                                    "description": "〰️ Vibrato LFO rate in Hz (0.0-20.0, optional). Around 5-6 Hz suits leads and strings; 0 turns vibrato off",
+                                    "minimum": 0.0,
+                                    "maximum": 20.0
+                                },
+                                "synth_vibrato_depth": {
+                                    "type": "number",
+                                    "description": "〰️ Vibrato depth in semitones either side of the note (0.0-2.0, optional). 0.1-0.3 is subtle, 1.0 is a wide operatic wobble",
+                                    "minimum": 0.0,
+                                    "maximum": 2.0
+                                },
+                                "synth_tremolo_rate": {
+                                    "type": "number",
+                                    "description": "📳 Tremolo LFO rate in Hz (0.0-20.0, optional); 0 turns tremolo off",
+                                    "minimum": 0.0,
+                                    "maximum": 20.0
+                                },
+                                "synth_tremolo_depth": {
+                                    "type": "number",
+                                    "description": "📳 Tremolo depth as the fraction of the level each cycle dips by (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_modulator_freq": {
+                                    "type": "number",
+                                    "description": "🌀 FM modulator frequency in Hz (0.1-1000.0, optional)",
+                                    "minimum": 0.1,
+                                    "maximum": 1000.0
+                                },
+                                "synth_modulation_index": {
+                                    "type": "number",
+                                    "description": "🎛️ FM modulation index (0.0-10.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 10.0
                                 },
                                 "synth_grain_size": {
                                     "type": "number",
@@ -601,9 +1796,49 @@ Examples:
                                     "minimum": 0.0,
                                     "maximum": 1.0
                                 },
+                                "synth_morph_position": {
+                                    "type": "number",
+                                    "description": "🔀 Morph position for 'morph': 0.0 sine → 0.33 triangle → 0.67 sawtooth → 1.0 square (optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_morph_end": {
+                                    "type": "number",
+                                    "description": "🌊 Morph position at the end of the note - sweeps from synth_morph_position for evolving timbres (0.0-1.0, optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_drone_harmonics": {
+                                    "type": "integer",
+                                    "description": "🕯️ Harmonics in a 'drone' (1-16, default: 6). More harmonics = richer, brighter bed",
+                                    "minimum": 1,
+                                    "maximum": 16
+                                },
+                                "synth_drone_evolution_rate": {
+                                    "type": "number",
+                                    "description": "🌅 How fast a 'drone' shifts its harmonic balance in Hz: 0.0=static, 0.05=default slow drift, 0.3+=restless (optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "synth_drone_detune": {
+                                    "type": "number",
+                                    "description": "🎐 Detune in cents between each 'drone' harmonic's paired oscillators for slow beating: 0=pure, 4=default, 20+=wide shimmer (optional)",
+                                    "minimum": 0.0,
+                                    "maximum": 50.0
+                                },
                                 "preset_name": {
                                     "type": "string",
-                                    "description": "🎹 Classic synthesizer preset name: Load specific authentic vintage preset (e.g., 'Minimoog Bass', 'TB-303 Acid', 'Jupiter Bass', 'Prophet Lead', 'DX7 E.Piano'). Use for instant access to iconic synthesizer sounds!"
+                                    "description": "🎹 Classic synthesizer preset name: Load specific authentic vintage preset (e.g., 'Minimoog Bass', 'TB-303 Acid', 'Jupiter Bass', 'Prophet Lead', 'DX7 E.Piano'). Use for instant access to iconic synthesizer sounds! list_presets gives every name. Presets from define_preset win over built-ins with the same name"
+                                },
+                                "preset_morph": {
+                                    "type": "object",
+                                    "description": "🎛️ Blend two presets instead of loading one: envelope, amplitude, filter and shared effects are interpolated `amount` of the way from `from` to `to`; the synth type comes from whichever preset is nearer (e.g., {\"from\": \"Minimoog Bass\", \"to\": \"Jupiter Bass\", \"amount\": 0.6}). Don't combine with preset_name, preset_category or preset_variation",
+                                    "properties": {
+                                        "from": {"type": "string"},
+                                        "to": {"type": "string"},
+                                        "amount": {"type": "number", "minimum": 0.0, "maximum": 1.0}
+                                    },
+                                    "required": ["from", "to", "amount"]
                                 },
                                 "preset_category": {
                                     "type": "string",
@@ -618,6 +1853,17 @@ Examples:
                                     "type": "boolean",
                                     "description": "🎲 Random preset selection: Set to true to randomly select a preset. Optionally combine with preset_category to limit random selection to specific category. Perfect for creative inspiration!"
                                 },
+                                "preset_randomize_amount": {
+                                    "type": "number",
+                                    "description": "🎰 Jitter the preset's envelope, filter and effect parameters for sound-design exploration (0.0 = untouched, 1.0 = wild). Cannot be combined with preset_variation",
+                                    "minimum": 0.0,
+                                    "maximum": 1.0
+                                },
+                                "preset_randomize_seed": {
+                                    "type": "integer",
+                                    "description": "🔑 Seed for preset_randomize_amount: reuse it to recall a variation you liked",
+                                    "minimum": 0
+                                },
                                 "effects": {
                                     "type": "array",
                                     "description": "🎛️ PROFESSIONAL EFFECTS CHAIN: Apply high-quality audio effects to individual notes. Overrides preset signature effects when specified.",
@@ -630,13 +1876,15 @@ Examples:
                                                 "oneOf": [
                                                     {
                                                         "type": "object",
-                                                        "description": "🏛️ REVERB: Schroeder reverb with comb filters + allpass diffusion for realistic spatial effects",
+                                                        "description": "🏛️ REVERB: Schroeder reverb with comb filters + allpass diffusion, or a feedback delay network (FDN) for smoother halls",
                                                         "properties": {
                                                             "type": {"const": "Reverb"},
                                                             "room_size": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "Room size: 0.1=closet, 0.5=studio, 0.8=concert hall, 1.0=cathedral"},
                                                             "dampening": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "High-frequency dampening: 0.0=bright, 0.5=natural, 1.0=dark"},
                                                             "wet_level": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "Reverb amount: 0.1=subtle, 0.3=moderate, 0.6=lush, 0.9=swimming"},
-                                                            "pre_delay": {"type": "number", "minimum": 0.0, "maximum": 0.2, "description": "Pre-delay in seconds: 0.02=small room, 0.05=large hall, 0.1=stadium"}
+                                                            "pre_delay": {"type": "number", "minimum": 0.0, "maximum": 0.2, "description": "Pre-delay in seconds: 0.02=small room, 0.05=large hall, 0.1=stadium"},
+                                                            "modulation": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "Comb delay modulation: 0.0=static (can ring metallic), 0.15=default smooth tail, 0.5+=lush/chorused (schroeder only)"},
+                                                            "algorithm": {"type": "string", "enum": ["schroeder", "fdn"], "description": "Reverb algorithm: 'schroeder' (default, classic character) or 'fdn' (denser, smoother tails for halls)"}
                                                         }
                                                     },
                                                     {
@@ -680,7 +1928,8 @@ Examples:
                                                             "threshold": {"type": "number", "minimum": -60.0, "maximum": 0.0, "description": "Threshold in dB: -20=gentle, -12=moderate, -6=aggressive"},
                                                             "ratio": {"type": "number", "minimum": 1.0, "maximum": 20.0, "description": "Compression ratio: 2=subtle, 4=moderate, 8=heavy, 20=limiter"},
                                                             "attack": {"type": "number", "minimum": 0.001, "maximum": 0.1, "description": "Attack time in seconds: 0.001=fast, 0.01=medium, 0.1=slow"},
-                                                            "release": {"type": "number", "minimum": 0.01, "maximum": 2.0, "description": "Release time in seconds: 0.05=fast, 0.2=medium, 1.0=slow"}
+                                                            "release": {"type": "number", "minimum": 0.01, "maximum": 2.0, "description": "Release time in seconds: 0.05=fast, 0.2=medium, 1.0=slow"},
+                                                            "parallel": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "Parallel (New York) compression blend: 1.0=fully compressed (default), 0.5=heavy squash under the dry hits, 0.0=dry"}
                                                         }
                                                     },
                                                     {
@@ -692,19 +1941,85 @@ Examples:
                                                             "tone": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "Tone control: 0.0=dark, 0.5=neutral, 1.0=bright"},
                                                             "output_level": {"type": "number", "minimum": 0.1, "maximum": 2.0, "description": "Output compensation: 0.5=quiet, 1.0=unity, 1.5=boost"}
                                                         }
-                                                    }
-                                                ]
-                                            },
-                                            "intensity": {
-                                                "type": "number",
-                                                "minimum": 0.0,
-                                                "maximum": 1.0,
-                                                "description": "🔊 Effect intensity/wet-dry mix: 0.0=bypassed, 0.3=subtle, 0.6=moderate, 1.0=maximum effect"
-                                            },
-                                            "enabled": {
-                                                "type": "boolean",
+                                                    },
+                                                    {
+                                                        "type": "object",
+                                                        "description": "🎚️ MULTIBAND COMPRESSOR: Linkwitz-Riley band split with independent compression per band for mastering pads and mixes without full-mix pumping",
+                                                        "properties": {
+                                                            "type": {"const": "MultibandCompressor"},
+                                                            "crossovers": {"type": "array", "items": {"type": "number", "minimum": 20.0, "maximum": 20000.0}, "description": "Ascending crossover frequencies in Hz, e.g. [200, 2000] for low/mid/high"},
+                                                            "bands": {
+                                                                "type": "array",
+                                                                "description": "Compressor settings per band from low to high (one more band than crossovers)",
+                                                                "items": {
+                                                                    "type": "object",
+                                                                    "properties": {
+                                                                        "threshold": {"type": "number", "minimum": -60.0, "maximum": 0.0, "description": "Threshold in dB"},
+                                                                        "ratio": {"type": "number", "minimum": 1.0, "maximum": 50.0, "description": "Compression ratio (1 = untouched)"},
+                                                                        "attack": {"type": "number", "minimum": 0.0001, "maximum": 2.0, "description": "Attack time in seconds"},
+                                                                        "release": {"type": "number", "minimum": 0.001, "maximum": 20.0, "description": "Release time in seconds"},
+                                                                        "makeup_gain_db": {"type": "number", "minimum": -24.0, "maximum": 24.0, "description": "Makeup gain in dB"}
+                                                                    }
+                                                                }
+                                                            }
+                                                        }
+                                                    },
+                                                    {
+                                                        "type": "object",
+                                                        "description": "🥊 TRANSIENT SHAPER: Dual envelope followers boost or cut the attack and sustain of each hit independently, for punch control without compression (e.g., add click or remove boom on 'TR-808 Kick')",
+                                                        "properties": {
+                                                            "type": {"const": "TransientShaper"},
+                                                            "attack_gain": {"type": "number", "minimum": -1.0, "maximum": 1.0, "description": "Attack: -1.0=softened hits, 0.0=unchanged, 1.0=maximum snap/click (±12dB)"},
+                                                            "sustain_gain": {"type": "number", "minimum": -1.0, "maximum": 1.0, "description": "Sustain: -1.0=tight and dry, 0.0=unchanged, 1.0=fuller body/boom (±12dB)"}
+                                                        }
+                                                    },
+                                                    {
+                                                        "type": "object",
+                                                        "description": "✈️ FLANGER: A very short delay swept by an LFO and fed back, for the classic jet-plane comb sweep that chorus cannot make. Negative feedback gives hollow inverted flanging",
+                                                        "properties": {
+                                                            "type": {"const": "Flanger"},
+                                                            "rate": {"type": "number", "minimum": 0.05, "maximum": 10.0, "description": "Sweep rate in Hz: 0.1=slow jet, 0.5=default, 5+=warble"},
+                                                            "depth": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "How far the sweep moves around the manual delay (default: 0.7)"},
+                                                            "feedback": {"type": "number", "minimum": -0.95, "maximum": 0.95, "description": "Resonance of the sweep: 0.5=default, 0.9=metallic whoosh, negative=inverted flanging"},
+                                                            "manual": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "Center delay of the sweep: 0.0=0.5ms (high, airy) to 1.0=8ms (low, throaty), default 0.5"}
+                                                        }
+                                                    },
+                                                    {
+                                                        "type": "object",
+                                                        "description": "🌀 PHASER: All-pass stages swept by an LFO, mixed with the dry signal for a few notches gliding through the spectrum. Smoother and more vocal than a flanger's dense comb; great on pads and leads",
+                                                        "properties": {
+                                                            "type": {"const": "Phaser"},
+                                                            "rate": {"type": "number", "minimum": 0.05, "maximum": 10.0, "description": "Sweep rate in Hz: 0.1=slow swirl, 0.5=default, 5+=bubbly"},
+                                                            "depth": {"type": "number", "minimum": 0.0, "maximum": 1.0, "description": "How far the notches sweep, up to 100Hz-4kHz (default: 0.7)"},
+                                                            "feedback": {"type": "number", "minimum": 0.0, "maximum": 0.95, "description": "Sharpens the notches: 0.3=default, 0.8=resonant whistle"},
+                                                            "stages": {"type": "integer", "minimum": 2, "maximum": 12, "description": "All-pass stages: 2=one gentle notch, 4=classic (default), 12=deep swirl. Every two stages add a notch"}
+                                                        }
+                                                    },
+                                                    {
+                                                        "type": "object",
+                                                        "description": "🎯 BASS MONO: Sum the low end to mono below the crossover while highs stay stereo, for tight bass under stereo chorus or wide pads. Works on a channel's stereo mix",
+                                                        "properties": {
+                                                            "type": {"const": "BassMono"},
+                                                            "crossover": {"type": "number", "minimum": 40.0, "maximum": 300.0, "description": "Crossover in Hz: 80=sub only, 120=typical (default), 200=whole bass register"}
+                                                        }
+                                                    }
+                                                ]
+                                            },
+                                            "intensity": {
+                                                "type": "number",
+                                                "minimum": 0.0,
+                                                "maximum": 1.0,
+                                                "description": "🔊 Effect intensity/wet-dry mix: 0.0=bypassed, 0.3=subtle, 0.6=moderate, 1.0=maximum effect"
+                                            },
+                                            "enabled": {
+                                                "type": "boolean",
                                                 "description": "🔛 Enable/disable this effect",
                                                 "default": true
+                                            },
+                                            "wet_only": {
+                                                "type": "boolean",
+                                                "description": "🔀 Output only the processed signal without the dry input, for reverb/delay returns on a separate bus",
+                                                "default": false
                                             }
                                         },
                                         "required": ["effect", "intensity"]
@@ -718,319 +2033,2203 @@ Examples:
                             },
                             "anyOf": [
                                 {"required": ["start_time", "duration"]},
-                                {"required": ["musical_time", "musical_duration"]}
+                                {"required": ["musical_time", "musical_duration"]},
+                                {"required": ["start_time", "beats"]}
                             ],
                             "additionalProperties": false
                         }
-                    },
-                    "tempo": {
-                        "type": "integer",
-                        "description": "Tempo in BPM (optional, defaults to 120)",
-                        "minimum": 60,
-                        "maximum": 200
-                    }
-                },
-                "required": ["notes"]
+                    },
+                    "tempo": {
+                        "type": "integer",
+                        "description": "Tempo in BPM (optional, defaults to 120)",
+                        "minimum": 60,
+                        "maximum": 200
+                    },
+                    "beats_per_bar": {
+                        "type": "integer",
+                        "description": "Beats per bar used to convert musical_time and musical_duration (optional, defaults to 4). Notes may use musical_time + musical_duration instead of seconds",
+                        "minimum": 1,
+                        "maximum": 16
+                    },
+                    "master_seed": {
+                        "type": "integer",
+                        "description": "Seed for all randomness in the sequence (random presets, noise). Same seed produces an identical render; omit for non-deterministic playback",
+                        "minimum": 0
+                    },
+                    "min_release": {
+                        "type": "number",
+                        "description": "Minimum length in seconds for short notes on sustaining instruments (strings, pads, organs, winds) so they reach their natural release instead of clicking. Drums and percussive instruments are left alone",
+                        "minimum": 0.0,
+                        "maximum": 2.0
+                    },
+                    "auto_pan_by_pitch": {
+                        "type": "boolean",
+                        "description": "Pan each note by register like a real stage: lows centered, highs spread toward the right. Notes that set pan or balance are left where they are",
+                        "default": false
+                    },
+                    "drum_glue": {
+                        "type": "boolean",
+                        "description": "Sum all channel 9 drums, MIDI and synthesized, through one shared glue compressor for a cohesive kit. Other channels are left as they are",
+                        "default": false
+                    },
+                    "master_limiter": {
+                        "type": "boolean",
+                        "description": "Limit the final mix just under full scale so many overlapping notes cannot hard-clip (default: true)",
+                        "default": true
+                    },
+                    "drum_humanize": {
+                        "type": "number",
+                        "description": "Drum-aware humanization for channel 9 (0.0-1.0): kick and snare stay tight while hi-hats drift more and taper in velocity. Reproducible with master_seed",
+                        "minimum": 0.0,
+                        "maximum": 1.0
+                    },
+                    "target_lufs": {
+                        "type": "number",
+                        "description": "Normalize the render to this integrated loudness in LUFS (-50 to -6, e.g. -16) with a single gain, so pieces play back at a consistent volume. The gain is held back if it would push peaks above -1 dBFS",
+                        "minimum": -50.0,
+                        "maximum": -6.0
+                    },
+                    "fit_duration": {
+                        "type": "number",
+                        "description": "Uniformly scale all note start times and durations so the sequence lasts exactly this many seconds, without changing pitch",
+                        "exclusiveMinimum": 0,
+                        "maximum": 600.0
+                    },
+                    "velocity_brightness": {
+                        "type": "number",
+                        "description": "How far note velocity moves synthesis filter cutoffs (0.0-1.0, default 0 = off), so harder notes sound brighter",
+                        "minimum": 0.0,
+                        "maximum": 1.0,
+                        "default": 0.0
+                    },
+                    "tail_cutoff_db": {
+                        "type": "number",
+                        "description": "Level in dBFS (-120 to -20, default -60) below which rendering stops once every note has ended, so silent reverb and decay tails are not played out",
+                        "minimum": -120.0,
+                        "maximum": -20.0,
+                        "default": -60.0
+                    },
+                    "swing": {
+                        "type": "number",
+                        "description": "Delay every off-beat swing_subdivision by this fraction of a subdivision (0.0-0.75, default 0.0 = straight). Applies to direct notes, arpeggios and patterns without their own swing",
+                        "minimum": 0.0,
+                        "maximum": 0.75,
+                        "default": 0.0
+                    },
+                    "swing_subdivision": {
+                        "type": "string",
+                        "enum": ["8th", "16th"],
+                        "description": "Which off-beats swing delays (default: 8th)",
+                        "default": "8th"
+                    },
+                    "tempo_changes": {
+                        "type": "array",
+                        "description": "Tempo ramps for accelerando and ritardando: the tempo moves linearly from the sequence tempo (or the previous change) to each change's tempo, reached at its bar/beat (ascending). Two changes at the same point jump instantly. Moves notes placed with musical_time, musical_duration or beats",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "at": {
+                                    "type": "object",
+                                    "properties": {
+                                        "bar": {"type": "integer", "minimum": 1},
+                                        "beat": {"type": "integer", "minimum": 1},
+                                        "tick": {"type": "integer", "minimum": 0, "maximum": 479}
+                                    },
+                                    "required": ["bar", "beat", "tick"],
+                                    "description": "Where the tempo is reached"
+                                },
+                                "tempo": {"type": "integer", "minimum": 20, "maximum": 300, "description": "Tempo in BPM at that point"}
+                            },
+                            "required": ["at", "tempo"]
+                        }
+                    },
+                    "negative_start": {
+                        "type": "string",
+                        "enum": ["clamp", "reject"],
+                        "description": "What to do with notes whose resolved timing lands before time zero: clamp them to 0.0 (default) or reject the sequence, listing the affected note numbers (counting from 1)",
+                        "default": "clamp"
+                    },
+                    "chord_track": {
+                        "type": "array",
+                        "description": "Chord changes by bar, in ascending order. Notes with snap_to_chord are moved to the nearest tone (any octave) of the chord active at their bar",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "bar": {"type": "integer", "minimum": 1, "description": "Bar where the chord starts (1-based)"},
+                                "notes": {"type": "array", "items": {"type": "integer", "minimum": 0, "maximum": 127}, "minItems": 1, "description": "Chord tones as MIDI notes; only their pitch classes matter"}
+                            },
+                            "required": ["bar", "notes"]
+                        }
+                    },
+                    "program_changes": {
+                        "type": "array",
+                        "description": "Switch a channel's instrument at given times (in ascending order). Each change affects notes that start after it; notes already sounding keep their instrument",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "at": {"type": "number", "minimum": 0, "description": "Time in seconds from the start of the sequence"},
+                                "instrument": {"type": "integer", "minimum": 0, "maximum": 127, "description": "GM instrument to switch to"},
+                                "channel": {"type": "integer", "minimum": 0, "maximum": 15, "default": 0, "description": "MIDI channel to change (not 9, drums)"}
+                            },
+                            "required": ["at", "instrument"]
+                        }
+                    },
+                    "channels": {
+                        "type": "array",
+                        "description": "Per-channel defaults for instrument, volume, pan, reverb, chorus and effects, applied to every note on the channel that doesn't set its own value",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "channel": {"type": "integer", "minimum": 0, "maximum": 15, "description": "MIDI channel these defaults apply to"},
+                                "instrument": {"type": "integer", "minimum": 0, "maximum": 127, "description": "GM instrument (not on channel 9, drums)"},
+                                "volume": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "pan": {"type": "integer", "minimum": 0, "maximum": 127, "description": "0 = left, 64 = center, 127 = right"},
+                                "reverb": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "chorus": {"type": "integer", "minimum": 0, "maximum": 127},
+                                "effects": {"type": "array", "items": {"type": "object"}, "description": "Effects chain (same format as note effects) for notes without their own effects"}
+                            },
+                            "required": ["channel"]
+                        }
+                    }
+                },
+                "required": ["notes"]
+            }
+        }
+    ]);
+
+    // validate_sequence takes exactly what play_sequence plays
+    if let Some(list) = tools.as_array_mut() {
+        let play_sequence_schema = list
+            .iter()
+            .find(|tool| tool["name"] == "play_sequence")
+            .map(|tool| tool["inputSchema"].clone());
+        if let Some(input_schema) = play_sequence_schema {
+            list.push(json!({
+                "name": "validate_sequence",
+                "description": "🔍 Check a play_sequence payload without playing it: runs the same parsing, validation, pattern resolution against defined patterns and preset lookups, and reports the errors play_sequence would fail with, the warnings it would log and work around (e.g. unknown presets, dropped effects), and how many notes it would schedule. Never touches the audio device; use it before committing to audio, especially in async mode.",
+                "inputSchema": input_schema
+            }));
+        }
+    }
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "tools": tools
+        })),
+        error: None,
+    }
+}
+
+fn handle_resources_list(id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!("Handling resources/list request");
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "resources": []
+        })),
+        error: None,
+    }
+}
+
+fn handle_prompts_list(id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!("Handling prompts/list request");
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "prompts": []
+        })),
+        error: None,
+    }
+}
+
+fn handle_tool_call(params: Option<Value>, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!("Handling tools/call request");
+
+    let params = match params {
+        Some(p) => p,
+        None => {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: "Invalid params".to_string(),
+                    data: None,
+                }),
+            };
+        }
+    };
+
+    let tool_params: ToolCallParams = match serde_json::from_value(params) {
+        Ok(p) => p,
+        Err(e) => {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Invalid tool call params: {}", e),
+                    data: None,
+                }),
+            };
+        }
+    };
+
+    match tool_params.name.as_str() {
+        "play_notes" => handle_play_notes_tool(tool_params.arguments, id),
+        "define_sequence_pattern" => handle_define_pattern_tool(tool_params.arguments, id),
+        "play_sequence" => handle_play_sequence_tool(tool_params.arguments, id),
+        "validate_sequence" => handle_validate_sequence_tool(tool_params.arguments, id),
+        "list_patterns" => handle_list_patterns_tool(id),
+        "list_presets" => handle_list_presets_tool(tool_params.arguments, id),
+        "find_patterns" => handle_find_patterns_tool(tool_params.arguments, id),
+        "play_pattern" => handle_play_pattern_tool(tool_params.arguments, id),
+        "panic" => handle_panic_tool(id),
+        "stop_playback" => handle_stop_playback_tool(tool_params.arguments, id),
+        "get_capabilities" => handle_get_capabilities_tool(id),
+        "engine_status" => handle_engine_status_tool(id),
+        "check_mono_compatibility" => {
+            handle_check_mono_compatibility_tool(tool_params.arguments, id)
+        }
+        "save_effects_preset" => handle_save_effects_preset_tool(tool_params.arguments, id),
+        "define_preset" => handle_define_preset_tool(tool_params.arguments, id),
+        "set_master_volume" => handle_set_master_volume_tool(tool_params.arguments, id),
+        "list_variations" => handle_list_variations_tool(tool_params.arguments, id),
+        "audition_preset" => handle_audition_preset_tool(tool_params.arguments, id),
+        "concat_patterns" => handle_concat_patterns_tool(tool_params.arguments, id),
+        "spectrum_sequence" => handle_spectrum_sequence_tool(tool_params.arguments, id),
+        "render_sequence" => handle_render_sequence_tool(tool_params.arguments, id),
+        "play_midi_file" => handle_play_midi_file_tool(tool_params.arguments, id),
+        "export_midi_file" => handle_export_midi_file_tool(tool_params.arguments, id),
+        "play_abc" => handle_play_abc_tool(tool_params.arguments, id),
+        "rearrange" => handle_rearrange_tool(tool_params.arguments, id),
+        "save_project" => handle_save_project_tool(tool_params.arguments, id),
+        "load_project" => handle_load_project_tool(tool_params.arguments, id),
+        "generate_melody" => handle_generate_melody_tool(tool_params.arguments, id),
+        "progression_voiced" => handle_progression_voiced_tool(tool_params.arguments, id),
+        _ => JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32601,
+                message: format!("Unknown tool: {}", tool_params.name),
+                data: None,
+            }),
+        },
+    }
+}
+
+fn handle_play_notes_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_play_notes_tool called with arguments: {:?}",
+        arguments
+    );
+
+    if let Err(e) = check_tool_arguments("play_notes", &arguments, &["notes"], NOTES_GUIDANCE) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: e,
+                data: None,
+            }),
+        };
+    }
+
+    let detached = arguments
+        .get("async")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    // Parse the simple sequence from JSON
+    let sequence: SimpleSequence = match serde_json::from_value(arguments) {
+        Ok(seq) => seq,
+        Err(e) => {
+            tracing::error!("Failed to parse note sequence: {}", e);
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Failed to parse note sequence: {}", e),
+                    data: None,
+                }),
+            };
+        }
+    };
+
+    if let Err(e) = validate_program_changes(&sequence.program_changes) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid program changes: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_channel_configs(&sequence.channels) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid channel config: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_beats_per_bar(sequence.beats_per_bar) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid time signature: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_drum_humanize(sequence.drum_humanize) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid drum humanize: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_chord_track(&sequence.chord_track) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid chord track: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_target_lufs(sequence.target_lufs) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid loudness target: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_fit_duration(sequence.fit_duration) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid fit_duration: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_min_release(sequence.min_release) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid min_release: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_velocity_brightness(sequence.velocity_brightness) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid velocity_brightness: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_tail_cutoff_db(sequence.tail_cutoff_db) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid tail_cutoff_db: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_swing(sequence.swing) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid swing: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = validate_tempo_changes(&sequence.tempo_changes, sequence.beats_per_bar) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid tempo_changes: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    if sequence.notes.is_empty() {
+        tracing::warn!("Note sequence is empty");
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: "Note sequence cannot be empty".to_string(),
+                data: None,
+            }),
+        };
+    }
+
+    // Analyze the sequence to determine the playback mode
+    let mut has_midi = false;
+    let mut has_r2d2 = false;
+    let mut has_synthesis = false;
+    let mut has_presets = false;
+
+    for note in &sequence.notes {
+        // Validate note parameters first
+        if let Err(e) = note.validate_timing() {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Invalid timing parameters: {}", e),
+                    data: None,
+                }),
+            };
+        }
+
+        if let Err(e) = note.validate_r2d2() {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Invalid R2D2 parameters: {}", e),
+                    data: None,
+                }),
+            };
+        }
+
+        if let Err(e) = note.validate_synthesis() {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Invalid synthesis parameters: {}", e),
+                    data: None,
+                }),
+            };
+        }
+
+        if let Err(e) = note.validate_preset() {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Invalid preset parameters: {}", e),
+                    data: None,
+                }),
+            };
+        }
+
+        // Categorize note types
+        if note.note_type == "r2d2" {
+            has_r2d2 = true;
+        } else if note.is_synthesis() {
+            has_synthesis = true;
+        } else if note.is_preset() {
+            has_presets = true;
+        } else {
+            has_midi = true;
+        }
+    }
+
+    tracing::info!(
+        "Sequence analysis: {} notes, has_midi: {}, has_r2d2: {}, has_synthesis: {}, has_presets: {}",
+        sequence.notes.len(),
+        has_midi,
+        has_r2d2,
+        has_synthesis,
+        has_presets
+    );
+
+    if detached {
+        return match start_detached_playback(sequence) {
+            Ok((playback_id, total_time)) => JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: Some(json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!(
+                                "🚀 Playback {} started in the background.{}",
+                                playback_id,
+                                audible_duration_note(total_time)
+                            )
+                        }
+                    ],
+                    "playback_id": playback_id,
+                    "duration_seconds": total_time.as_secs_f64()
+                })),
+                error: None,
+            },
+            Err(e) => {
+                tracing::error!("Failed to start detached playback: {}", e);
+                JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: None,
+                    error: Some(JsonRpcError {
+                        code: -32603,
+                        message: e,
+                        data: None,
+                    }),
+                }
+            }
+        };
+    }
+
+    // Create MIDI player
+    let player = match MidiPlayer::new() {
+        Ok(p) => {
+            tracing::info!("Successfully created MIDI player");
+            p
+        }
+        Err(e) => {
+            tracing::error!("Failed to create MIDI player: {}", e);
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32603,
+                    message: format!("Failed to create MIDI player: {}", e),
+                    data: None,
+                }),
+            };
+        }
+    };
+
+    // Use universal enhanced mixed playback for ALL sequences (supports everything!)
+    let mode = match (has_midi, has_r2d2, has_synthesis, has_presets) {
+        (true, true, true, true) => "MIDI + R2D2 + Synthesis + Presets",
+        (true, true, true, false) => "MIDI + R2D2 + Synthesis",
+        (true, true, false, true) => "MIDI + R2D2 + Presets",
+        (true, false, true, true) => "MIDI + Synthesis + Presets",
+        (false, true, true, true) => "R2D2 + Synthesis + Presets",
+        (true, false, true, false) => "MIDI + Synthesis",
+        (false, true, true, false) => "R2D2 + Synthesis",
+        (false, false, true, true) => "Synthesis + Presets",
+        (true, false, false, true) => "MIDI + Presets",
+        (false, true, false, true) => "R2D2 + Presets",
+        (false, false, true, false) => "Synthesis Only",
+        (false, true, false, false) => "R2D2 Only",
+        (false, false, false, true) => "Presets Only",
+        (true, false, false, false) => "Pure MIDI",
+        _ => "Mixed",
+    };
+
+    tracing::info!(
+        "Using universal enhanced mixed playback for {} sequence",
+        mode
+    );
+    let playback_result = player.play_enhanced_mixed(sequence);
+
+    // Handle the result
+    match playback_result {
+        Ok(total_time) => {
+            // Leak the player to keep audio stream alive for non-blocking playback
+            Box::leak(Box::new(player));
+            tracing::info!("Player leaked to keep audio alive (non-blocking)");
+            let mode_description = match (has_midi, has_r2d2, has_synthesis, has_presets) {
+                (true, true, true, true) => {
+                    "🎵🤖🎛️🎹 Ultimate audio sequence playback started successfully! MIDI music, R2D2 expressions, custom synthesis, and classic preset sounds are now playing in perfect synchronization."
+                }
+                (true, true, true, false) => {
+                    "🎵🤖🎛️ Universal audio sequence playback started successfully! MIDI music, R2D2 expressions, and custom synthesis are now playing in perfect synchronization."
+                }
+                (true, true, false, true) => {
+                    "🎵🤖🎹 Mixed MIDI, R2D2, and preset sequence playback started successfully! Traditional music, robotic expressions, and vintage synthesizer sounds are now playing together."
+                }
+                (true, false, true, true) => {
+                    "🎵🎛️🎹 Mixed MIDI, synthesis, and preset sequence playback started successfully! Traditional music, custom synthesis, and classic sounds are now playing together."
+                }
+                (false, true, true, true) => {
+                    "🤖🎛️🎹 Mixed R2D2, synthesis, and preset sequence playback started successfully! Robotic expressions, custom synthesis, and vintage sounds are now playing in synchronization."
+                }
+                (true, false, true, false) => {
+                    "🎵🎛️ Mixed MIDI and synthesis sequence playback started successfully! Traditional music and custom synthesized sounds are now playing together."
+                }
+                (false, true, true, false) => {
+                    "🤖🎛️ Mixed R2D2 and synthesis sequence playback started successfully! Robotic expressions and custom sounds are now playing in synchronization."
+                }
+                (true, true, false, false) => {
+                    "🎵🤖 Mixed MIDI and R2D2 sequence playback started successfully! The music and robotic expressions are now playing in perfect synchronization."
+                }
+                (true, false, false, true) => {
+                    "🎵🎹 Mixed MIDI and preset sequence playback started successfully! Traditional music and classic synthesizer sounds are now playing together."
+                }
+                (false, true, false, true) => {
+                    "🤖🎹 Mixed R2D2 and preset sequence playback started successfully! Robotic expressions and vintage synthesizer sounds are now playing together."
+                }
+                (false, false, true, true) => {
+                    "🎛️🎹 Mixed synthesis and preset sequence playback started successfully! Custom synthesis and classic vintage sounds are now playing together."
+                }
+                (false, true, false, false) => {
+                    "🤖 R2D2 expression sequence playback started successfully! The robotic vocalizations are now playing."
+                }
+                (false, false, true, false) => {
+                    "🎛️ Custom synthesis sequence playback started successfully! Your unique synthesized sounds are now playing."
+                }
+                (false, false, false, true) => {
+                    "🎹 Classic synthesizer preset sequence playback started successfully! Authentic vintage synthesizer sounds are now playing."
+                }
+                _ => {
+                    "🎵 Pure MIDI sequence playback started successfully! The music is now playing."
+                }
+            };
+
+            tracing::info!("Playback completed successfully");
+            JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: Some(json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!("{}{}", mode_description, audible_duration_note(total_time))
+                        }
+                    ],
+                    "duration_seconds": total_time.as_secs_f64()
+                })),
+                error: None,
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to play sequence: {}", e);
+            player.control().panic();
+            JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32603,
+                    message: format!("Failed to play sequence: {}", e),
+                    data: None,
+                }),
+            }
+        }
+    }
+}
+
+fn handle_define_pattern_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_define_pattern_tool called with arguments: {:?}",
+        arguments
+    );
+
+    if let Err(e) = check_tool_arguments(
+        "define_sequence_pattern",
+        &arguments,
+        &["notes"],
+        PATTERN_GUIDANCE,
+    ) {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: e,
+                data: None,
+            }),
+        };
+    }
+
+    let overwrite = arguments
+        .get("overwrite")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let normalize = arguments
+        .get("normalize")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    // Parse the sequence pattern from JSON
+    let mut pattern: SequencePattern = match serde_json::from_value(arguments) {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to parse sequence pattern: {}", e);
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message: format!("Failed to parse sequence pattern: {}", e),
+                    data: None,
+                }),
+            };
+        }
+    };
+
+    if pattern.notes.is_empty() {
+        tracing::warn!("Pattern notes are empty");
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: "Pattern notes cannot be empty".to_string(),
+                data: None,
+            }),
+        };
+    }
+
+    if let Err(e) = pattern.validate() {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: format!("Invalid pattern parameters: {}", e),
+                data: None,
+            }),
+        };
+    }
+
+    // Validate all notes in the pattern
+    if let Err(e) = pattern.validate_notes() {
+        return JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32602,
+                message: e,
+                data: None,
+            }),
+        };
+    }
+
+    // Stretch or squeeze the notes to fill pattern_bars exactly, so loops line up
+    let mut normalize_info = String::new();
+    if normalize && let Some(end_beats) = pattern.normalize_to_bars() {
+        let bar_beats = pattern.pattern_bars * pattern.beats_per_bar as f64;
+        if end_beats > bar_beats + 1e-9 {
+            tracing::warn!(
+                "Pattern '{}' notes ran past pattern_bars ({:.2} of {:.2} beats); scaled to fit",
+                pattern.name,
+                end_beats,
+                bar_beats
+            );
+            normalize_info = format!(
+                "\n⚠️ Notes ran past pattern_bars (ending at {:.2} of {:.2} beats) and were scaled down to fit",
+                end_beats, bar_beats
+            );
+        } else if end_beats < bar_beats - 1e-9 {
+            normalize_info = format!(
+                "\n📏 Notes ended at {:.2} of {:.2} beats and were stretched to fill pattern_bars",
+                end_beats, bar_beats
+            );
+        }
+    }
+
+    // Store the pattern
+    let pattern_name = pattern.name.clone();
+    match PATTERN_STORE.lock() {
+        Ok(mut store) => {
+            let replaced = store.contains_key(&pattern_name);
+            if replaced && !overwrite {
+                return JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: None,
+                    error: Some(JsonRpcError {
+                        code: -32602,
+                        message: format!(
+                            "Pattern '{}' already exists. Choose a new name or pass overwrite=true to replace it",
+                            pattern_name
+                        ),
+                        data: None,
+                    }),
+                };
+            }
+
+            let pattern_info = format!(
+                "Pattern '{}' with {} notes, duration: {:.2}s",
+                pattern.name,
+                pattern.notes.len(),
+                pattern.get_pattern_duration()
+            );
+
+            let category_info = pattern
+                .category
+                .as_ref()
+                .map(|c| format!(" (category: {})", c))
+                .unwrap_or_default();
+
+            let tags_info = if !pattern.tags.is_empty() {
+                format!(" [tags: {}]", pattern.tags.join(", "))
+            } else {
+                String::new()
+            };
+
+            store.insert(pattern.name.clone(), pattern);
+            tracing::info!(
+                "{} pattern: {}",
+                if replaced { "Replaced" } else { "Stored" },
+                pattern_name
+            );
+
+            JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: Some(json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!("🎼 Successfully {} sequence pattern: {}{}{}
+
+📋 **Pattern Details:**
+• **Name**: {}
+• **Notes**: {} notes
+• **Duration**: {:.2} seconds
+• **Tempo**: {} BPM
+{}{}{}
+
+✅ Pattern is now stored and ready to use with the `play_sequence` tool!
+
+💡 **Usage Example:**
+```json
+{{
+  \"patterns\": [
+    {{\"pattern_name\": \"{}\", \"start_time_offset\": 0}}
+  ]
+}}
+```",
+                                if replaced { "replaced" } else { "defined" },
+                                pattern_info, category_info, tags_info,
+                                pattern_name,
+                                store.get(&pattern_name).unwrap().notes.len(),
+                                store.get(&pattern_name).unwrap().get_pattern_duration(),
+                                store.get(&pattern_name).unwrap().tempo,
+                                store.get(&pattern_name).unwrap().description.as_ref()
+                                    .map(|d| format!("\n• **Description**: {}", d))
+                                    .unwrap_or_default(),
+                                if !store.get(&pattern_name).unwrap().tags.is_empty() {
+                                    format!("\n• **Tags**: {}", store.get(&pattern_name).unwrap().tags.join(", "))
+                                } else { String::new() },
+                                normalize_info,
+                                pattern_name
+                            )
+                        }
+                    ]
+                })),
+                error: None,
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to lock pattern store: {}", e);
+            JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32603,
+                    message: "Failed to store pattern due to internal error".to_string(),
+                    data: None,
+                }),
+            }
+        }
+    }
+}
+
+fn handle_validate_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_validate_sequence_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let check = check_sequence(arguments);
+    let summary = match check.errors.first() {
+        Some(error) => format!("❌ play_sequence would fail: {}", error),
+        None if check.warnings.is_empty() => format!(
+            "✅ Sequence is valid: {} notes after resolution",
+            check.resolved_notes
+        ),
+        None => format!(
+            "⚠️ Sequence would play {} notes, working around {} problems",
+            check.resolved_notes,
+            check.warnings.len()
+        ),
+    };
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "{}\n{}",
+                        summary,
+                        serde_json::to_string_pretty(&check).unwrap_or_default()
+                    )
+                }
+            ],
+            "valid": check.errors.is_empty(),
+            "errors": check.errors,
+            "warnings": check.warnings,
+            "resolved_notes": check.resolved_notes
+        })),
+        error: None,
+    }
+}
+
+/// Take a play_sequence payload through everything play_sequence does before it opens the
+/// audio device, then resolve its notes as playback would
+fn check_sequence(arguments: Value) -> SequenceCheck {
+    let resolved_sequence = match prepare_sequence(arguments) {
+        Ok((_, resolved_sequence)) => resolved_sequence,
+        Err(rejection) => {
+            return SequenceCheck {
+                errors: rejection.errors,
+                ..Default::default()
+            };
+        }
+    };
+    let mut check = check_playback(&resolved_sequence);
+    for error in &mut check.errors {
+        *error = format!("{}{}", PLAY_SEQUENCE_FAILURE, error);
+    }
+    check
+}
+
+/// Prefix of the errors play_sequence fails with once the audio device is open
+const PLAY_SEQUENCE_FAILURE: &str = "Failed to play enhanced sequence: ";
+
+/// Why play_sequence refuses a payload before opening the audio device
+#[derive(Debug)]
+struct SequenceRejection {
+    /// JSON-RPC error code the tool responds with
+    code: i32,
+    /// Every problem found; play_sequence fails with the first
+    errors: Vec<String>,
+}
+
+/// Check, parse and resolve a play_sequence payload, returning the sequence as sent and
+/// with its patterns resolved. Shared by play_sequence and validate_sequence so both
+/// reject the same payloads with the same messages.
+fn prepare_sequence(
+    arguments: Value,
+) -> Result<(ExtendedSequence, SimpleSequence), SequenceRejection> {
+    let reject = |code: i32, error: String| SequenceRejection {
+        code,
+        errors: vec![error],
+    };
+
+    check_tool_arguments(
+        "play_sequence",
+        &arguments,
+        &["notes", "patterns", "arpeggios"],
+        SEQUENCE_GUIDANCE,
+    )
+    .map_err(|e| reject(-32602, e))?;
+    let extended_sequence: ExtendedSequence = serde_json::from_value(arguments)
+        .map_err(|e| reject(-32602, format!("Failed to parse extended sequence: {}", e)))?;
+    let errors = extended_sequence_errors(&extended_sequence);
+    if !errors.is_empty() {
+        return Err(SequenceRejection {
+            code: -32602,
+            errors,
+        });
+    }
+
+    let resolved_sequence = match PATTERN_STORE.lock() {
+        Ok(store) => extended_sequence
+            .resolve_patterns(&store)
+            .map_err(|e| reject(-32602, format!("Failed to resolve patterns: {}", e)))?,
+        Err(_) => return Err(reject(-32603, "Failed to access pattern store".to_string())),
+    };
+    if resolved_sequence.notes.is_empty() {
+        return Err(reject(
+            -32602,
+            "Resolved sequence cannot be empty".to_string(),
+        ));
+    }
+    Ok((extended_sequence, resolved_sequence))
+}
+
+/// Everything `play_sequence` rejects before resolving patterns, in the order it checks,
+/// with the messages it fails with
+fn extended_sequence_errors(sequence: &ExtendedSequence) -> Vec<String> {
+    if sequence.notes.is_empty() && sequence.patterns.is_empty() && sequence.arpeggios.is_empty() {
+        return vec!["Sequence must contain notes, pattern references or arpeggios".to_string()];
+    }
+
+    let checks = [
+        validate_program_changes(&sequence.program_changes)
+            .map_err(|e| format!("Invalid program changes: {}", e)),
+        validate_channel_configs(&sequence.channels)
+            .map_err(|e| format!("Invalid channel config: {}", e)),
+        validate_beats_per_bar(sequence.beats_per_bar)
+            .map_err(|e| format!("Invalid time signature: {}", e)),
+        validate_drum_humanize(sequence.drum_humanize)
+            .map_err(|e| format!("Invalid drum humanize: {}", e)),
+        validate_chord_track(&sequence.chord_track)
+            .map_err(|e| format!("Invalid chord track: {}", e)),
+        validate_target_lufs(sequence.target_lufs)
+            .map_err(|e| format!("Invalid loudness target: {}", e)),
+        validate_fit_duration(sequence.fit_duration)
+            .map_err(|e| format!("Invalid fit_duration: {}", e)),
+        validate_min_release(sequence.min_release)
+            .map_err(|e| format!("Invalid min_release: {}", e)),
+        validate_velocity_brightness(sequence.velocity_brightness)
+            .map_err(|e| format!("Invalid velocity_brightness: {}", e)),
+        validate_tail_cutoff_db(sequence.tail_cutoff_db)
+            .map_err(|e| format!("Invalid tail_cutoff_db: {}", e)),
+        validate_swing(sequence.swing).map_err(|e| format!("Invalid swing: {}", e)),
+        validate_tempo_changes(&sequence.tempo_changes, sequence.beats_per_bar)
+            .map_err(|e| format!("Invalid tempo_changes: {}", e)),
+    ];
+    let mut errors: Vec<String> = checks.into_iter().filter_map(Result::err).collect();
+
+    for (i, note) in sequence.notes.iter().enumerate() {
+        let checks = [
+            note.validate_timing()
+                .map_err(|e| format!("Invalid timing parameters in note {}: {}", i + 1, e)),
+            note.validate_r2d2()
+                .map_err(|e| format!("Invalid R2D2 parameters in note {}: {}", i + 1, e)),
+            note.validate_synthesis()
+                .map_err(|e| format!("Invalid synthesis parameters in note {}: {}", i + 1, e)),
+            note.validate_preset()
+                .map_err(|e| format!("Invalid preset parameters in note {}: {}", i + 1, e)),
+        ];
+        errors.extend(checks.into_iter().filter_map(Result::err));
+    }
+
+    for (i, reference) in sequence.patterns.iter().enumerate() {
+        if let Err(e) = reference.validate() {
+            errors.push(format!("Invalid pattern reference {}: {}", i + 1, e));
+        }
+    }
+    errors
+}
+
+fn handle_play_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_play_sequence_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let (extended_sequence, resolved_sequence) = match prepare_sequence(arguments) {
+        Ok(sequences) => sequences,
+        Err(mut rejection) => {
+            let message = rejection.errors.remove(0);
+            tracing::warn!("Rejected sequence: {}", message);
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: rejection.code,
+                    message,
+                    data: None,
+                }),
+            };
+        }
+    };
+    remember_sequence(extended_sequence.clone());
+
+    // Create MIDI player
+    let player = match MidiPlayer::new() {
+        Ok(p) => {
+            tracing::info!("Successfully created MIDI player");
+            p
+        }
+        Err(e) => {
+            tracing::error!("Failed to create MIDI player: {}", e);
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32603,
+                    message: format!("Failed to create MIDI player: {}", e),
+                    data: None,
+                }),
+            };
+        }
+    };
+
+    let pattern_count = extended_sequence.patterns.len();
+    let individual_notes_count = extended_sequence.notes.len();
+    let total_resolved_notes = resolved_sequence.notes.len();
+
+    tracing::info!(
+        "Playing sequence with {} pattern references, {} individual notes, {} total resolved notes",
+        pattern_count,
+        individual_notes_count,
+        total_resolved_notes
+    );
+
+    match player.play_enhanced_mixed(resolved_sequence) {
+        Ok(total_time) => {
+            // Leak the player to keep audio stream alive for non-blocking playback
+            Box::leak(Box::new(player));
+            tracing::info!("Player leaked to keep audio alive (non-blocking)");
+
+            let composition_description = match (individual_notes_count > 0, pattern_count > 0) {
+                (true, true) => format!(
+                    "🎼🎵 Enhanced sequence playback started successfully! Playing {} individual notes plus {} pattern references (expanded to {} total notes) in perfect synchronization.",
+                    individual_notes_count, pattern_count, total_resolved_notes
+                ),
+                (false, true) => format!(
+                    "🎼 Pattern-based sequence playback started successfully! Playing {} pattern references (expanded to {} total notes) with all transformations applied.",
+                    pattern_count, total_resolved_notes
+                ),
+                (true, false) => format!(
+                    "🎵 Individual note sequence playback started successfully! Playing {} notes.",
+                    individual_notes_count
+                ),
+                (false, false) => "🎵 Sequence playback started successfully!".to_string(),
+            };
+
+            tracing::info!("Enhanced sequence playback completed successfully");
+            JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: Some(json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!("{}{}", composition_description, audible_duration_note(total_time))
+                        }
+                    ],
+                    "duration_seconds": total_time.as_secs_f64()
+                })),
+                error: None,
             }
         }
-    ]);
+        Err(e) => {
+            tracing::error!("Failed to play enhanced sequence: {}", e);
+            player.control().panic();
+            JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32603,
+                    message: format!("{}{}", PLAY_SEQUENCE_FAILURE, e),
+                    data: None,
+                }),
+            }
+        }
+    }
+}
+
+/// Response suffix reporting how long playback will actually be heard, effect tails included
+fn audible_duration_note(total_time: Duration) -> String {
+    format!(
+        "\n⏱️ Total audible duration: {:.2}s including effect and decay tails. Wait this long before playing a follow-up sequence.",
+        total_time.as_secs_f64()
+    )
+}
+
+fn handle_panic_tool(id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!("handle_panic_tool called");
+
+    MidiPlayer::panic();
 
     JsonRpcResponse {
         jsonrpc: "2.0".to_string(),
         id,
         result: Some(json!({
-            "tools": tools
+            "content": [
+                {
+                    "type": "text",
+                    "text": "🛑 Panic: all notes and sound off on every channel. Synthesis voices cleared and playback stopped."
+                }
+            ]
         })),
         error: None,
     }
 }
 
-fn handle_resources_list(id: Option<Value>) -> JsonRpcResponse {
-    tracing::info!("Handling resources/list request");
+#[derive(Default, Deserialize)]
+struct StopPlaybackArgs {
+    #[serde(default)]
+    playback_id: Option<u64>,
+}
+
+fn handle_stop_playback_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_stop_playback_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    // The playback id is optional, so no arguments at all stops everything
+    let args: StopPlaybackArgs = if arguments.is_null() {
+        StopPlaybackArgs::default()
+    } else {
+        match serde_json::from_value(arguments) {
+            Ok(args) => args,
+            Err(e) => return invalid_params(id, format!("Invalid stop_playback arguments: {}", e)),
+        }
+    };
+
+    let (fade, stopped) = match args.playback_id {
+        Some(playback_id) => match stop_detached_playback(playback_id) {
+            Ok(fade) => (fade, format!("playback {}", playback_id)),
+            Err(e) => return invalid_params(id, e),
+        },
+        None => (MidiPlayer::stop(), "all playback".to_string()),
+    };
 
     JsonRpcResponse {
         jsonrpc: "2.0".to_string(),
         id,
         result: Some(json!({
-            "resources": []
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "⏹️ Stopping {} with a {:.0}ms fade-out; every channel is silenced once it has faded.",
+                        stopped,
+                        fade.as_secs_f64() * 1000.0
+                    )
+                }
+            ],
+            "fade_ms": fade.as_secs_f64() * 1000.0
         })),
         error: None,
     }
 }
 
-fn handle_prompts_list(id: Option<Value>) -> JsonRpcResponse {
-    tracing::info!("Handling prompts/list request");
+#[derive(Deserialize)]
+struct SetMasterVolumeArgs {
+    volume: f32,
+}
+
+fn handle_set_master_volume_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_set_master_volume_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: SetMasterVolumeArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(
+                id,
+                -32602,
+                format!("Invalid set_master_volume arguments: {}", e),
+            );
+        }
+    };
+    if !(0.0..=MAX_MASTER_GAIN).contains(&args.volume) {
+        return error_response(
+            id,
+            -32602,
+            format!(
+                "volume must be between 0.0 and {}, got {}",
+                MAX_MASTER_GAIN, args.volume
+            ),
+        );
+    }
+
+    let mut config = SetupConfig::load().unwrap_or_default();
+    config.master_gain = Some(args.volume);
+    if let Err(e) = config.save() {
+        return error_response(id, -32603, format!("Failed to save master volume: {}", e));
+    }
+    MidiPlayer::set_master_gain(args.volume);
 
+    let level = if args.volume > 0.0 {
+        format!("{:+.1} dB", 20.0 * args.volume.log10())
+    } else {
+        "muted".to_string()
+    };
     JsonRpcResponse {
         jsonrpc: "2.0".to_string(),
         id,
         result: Some(json!({
-            "prompts": []
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!("🔊 Master volume set to {:.2} ({})", args.volume, level)
+                }
+            ]
         })),
         error: None,
     }
 }
 
-fn handle_tool_call(params: Option<Value>, id: Option<Value>) -> JsonRpcResponse {
-    tracing::info!("Handling tools/call request");
+#[derive(Deserialize)]
+struct SaveEffectsPresetArgs {
+    name: String,
+    effects: Vec<EffectConfig>,
+}
 
-    let params = match params {
-        Some(p) => p,
-        None => {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: "Invalid params".to_string(),
-                    data: None,
-                }),
-            };
-        }
+fn handle_save_effects_preset_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_save_effects_preset_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: SaveEffectsPresetArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid effects preset: {}", e)),
+    };
+
+    let name = args.name.trim();
+    if name.is_empty() {
+        return invalid_params(id, "Effects preset name cannot be empty".to_string());
+    }
+    if args.effects.is_empty() {
+        return invalid_params(id, "Effects chain cannot be empty".to_string());
+    }
+
+    // Reuse note-level effect validation for the chain
+    let probe = SimpleNote {
+        effects: Some(args.effects.clone()),
+        ..Default::default()
+    };
+    if let Err(e) = probe.validate_effects() {
+        return invalid_params(id, format!("Invalid effects chain: {}", e));
+    }
+
+    let effect_count = args.effects.len();
+    match EffectsPresetLibrary::new().save_effects_preset(name, args.effects) {
+        Ok(()) => JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(json!({
+                "content": [
+                    {
+                        "type": "text",
+                        "text": format!(
+                            "💾 Saved effects preset '{}' ({} effects). Use it with \"effects_preset\": \"{}\" on any note.",
+                            name, effect_count, name
+                        )
+                    }
+                ]
+            })),
+            error: None,
+        },
+        Err(e) => JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32603,
+                message: format!("Failed to save effects preset: {}", e),
+                data: None,
+            }),
+        },
+    }
+}
+
+#[derive(Deserialize)]
+struct DefinePresetArgs {
+    name: String,
+    category: String,
+    synth_params: Value,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+fn handle_define_preset_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_define_preset_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: DefinePresetArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid preset: {}", e)),
+    };
+
+    let name = args.name.trim();
+    if name.is_empty() {
+        return invalid_params(id, "Preset name cannot be empty".to_string());
+    }
+    let category = match PresetCategory::from_name(&args.category) {
+        Ok(category) => category,
+        Err(e) => return invalid_params(id, e),
+    };
+
+    // Pitch and length come from each note, so presets may leave them out
+    let mut synth_params = args.synth_params;
+    if let Some(params) = synth_params.as_object_mut() {
+        params.entry("frequency").or_insert(json!(440.0));
+        params.entry("duration").or_insert(json!(1.0));
+        params.entry("filter").or_insert(Value::Null);
+        params.entry("effects").or_insert(json!([]));
+    }
+    let synth_params: SynthParams = match serde_json::from_value(synth_params) {
+        Ok(params) => params,
+        Err(e) => return invalid_params(id, format!("Invalid synth_params: {}", e)),
+    };
+
+    let category_name = category.name();
+    let shadows_built_in = PresetLibrary::new()
+        .list_preset_names()
+        .iter()
+        .any(|existing| existing.eq_ignore_ascii_case(name));
+    let preset = ClassicSynthPreset {
+        name: name.to_string(),
+        category,
+        subcategory: "user".to_string(),
+        description: args.description.unwrap_or_default(),
+        inspiration: "User preset".to_string(),
+        tags: args.tags,
+        synth_params,
+        variations: HashMap::new(),
+        signature_effects: Vec::new(),
+    };
+    if let Err(e) = PresetLibrary::define_user_preset(preset) {
+        return invalid_params(id, e);
+    }
+
+    let precedence = if shadows_built_in {
+        format!(" It replaces the preset named '{}' for this session.", name)
+    } else {
+        String::new()
+    };
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "🎛️ Defined {} preset '{}'. Use it with \"preset_name\": \"{}\" on any note.{}",
+                        category_name, name, name, precedence
+                    )
+                }
+            ]
+        })),
+        error: None,
+    }
+}
+
+fn handle_check_mono_compatibility_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_check_mono_compatibility_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    if let Err(e) = check_tool_arguments(
+        "check_mono_compatibility",
+        &arguments,
+        &["notes"],
+        NOTES_GUIDANCE,
+    ) {
+        return invalid_params(id, e);
+    }
+    let sequence: SimpleSequence = match serde_json::from_value(arguments) {
+        Ok(seq) => seq,
+        Err(e) => return invalid_params(id, format!("Failed to parse note sequence: {}", e)),
     };
+    if sequence.notes.is_empty() {
+        return invalid_params(id, "Note sequence cannot be empty".to_string());
+    }
 
-    let tool_params: ToolCallParams = match serde_json::from_value(params) {
-        Ok(p) => p,
+    let rendered = match MidiPlayer::render_samples(sequence) {
+        Ok(rendered) => rendered,
         Err(e) => {
             return JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
                 result: None,
                 error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid tool call params: {}", e),
+                    code: -32603,
+                    message: format!("Failed to render sequence: {}", e),
                     data: None,
                 }),
             };
         }
     };
 
-    match tool_params.name.as_str() {
-        "play_notes" => handle_play_notes_tool(tool_params.arguments, id),
-        "define_sequence_pattern" => handle_define_pattern_tool(tool_params.arguments, id),
-        "play_sequence" => handle_play_sequence_tool(tool_params.arguments, id),
-        "list_patterns" => handle_list_patterns_tool(id),
-        _ => JsonRpcResponse {
+    let report = check_mono_compatibility(&rendered.samples);
+    let verdict = report.warning().unwrap_or_else(|| {
+        "✅ Mono compatible: no significant phase cancellation when summed to mono.".to_string()
+    });
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "{}\nStereo level: {:.1} dBFS, mono sum: {:.1} dBFS, estimated mono loss: {:.1} dB over {:.2}s.",
+                        verdict,
+                        report.stereo_rms_db,
+                        report.mono_rms_db,
+                        report.mono_loss_db,
+                        rendered.duration.as_secs_f64()
+                    )
+                }
+            ]
+        })),
+        error: None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SpectrumArgs {
+    #[serde(default = "default_spectrum_size")]
+    frames: usize,
+    #[serde(default = "default_spectrum_size")]
+    bands: usize,
+}
+
+fn default_spectrum_size() -> usize {
+    64
+}
+
+#[derive(Debug, Deserialize)]
+struct RenderSequenceArgs {
+    #[serde(flatten)]
+    sequence: SimpleSequence,
+    output_path: String,
+    #[serde(flatten)]
+    options: RenderOptions,
+}
+
+fn handle_render_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_render_sequence_tool called with arguments: {:?}",
+        arguments
+    );
+    render_sequence_into(&renders_dir(), arguments, id)
+}
+
+/// Render a `render_sequence` request to its output_path inside `dir`
+fn render_sequence_into(dir: &Path, arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    if let Err(e) = check_tool_arguments("render_sequence", &arguments, &["notes"], NOTES_GUIDANCE)
+    {
+        return invalid_params(id, e);
+    }
+    let args: RenderSequenceArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid render_sequence arguments: {}", e)),
+    };
+    let path = match output_path_in(dir, &args.output_path) {
+        Ok(path) => path,
+        Err(e) => return invalid_params(id, e),
+    };
+    if let Err(e) = ExportFormat::from_path(&path) {
+        return invalid_params(id, e);
+    }
+    // Everything playback would skip with a warning fails up front, before a file is written
+    if let Err(e) = dry_run(&args.sequence) {
+        return invalid_params(id, format!("Invalid note sequence: {}", e));
+    }
+
+    let note_count = args.sequence.notes.len();
+    match MidiPlayer::render_to_file(args.sequence, &path, &args.options) {
+        Ok(duration) => JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(json!({
+                "content": [
+                    {
+                        "type": "text",
+                        "text": format!(
+                            "💿 Rendered {} notes ({:.2}s including effect tails) to {}",
+                            note_count,
+                            duration.as_secs_f64(),
+                            path.display()
+                        )
+                    }
+                ],
+                "output_path": path,
+                "duration_seconds": duration.as_secs_f64()
+            })),
+            error: None,
+        },
+        Err(e) => JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
             id,
             result: None,
             error: Some(JsonRpcError {
-                code: -32601,
-                message: format!("Unknown tool: {}", tool_params.name),
+                code: -32603,
+                message: format!("Failed to render sequence: {}", e),
                 data: None,
             }),
         },
     }
 }
 
-fn handle_play_notes_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+#[derive(Debug, Deserialize)]
+struct ExportMidiFileArgs {
+    #[serde(flatten)]
+    sequence: SimpleSequence,
+    output_path: String,
+}
+
+fn handle_export_midi_file_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
     tracing::info!(
-        "handle_play_notes_tool called with arguments: {:?}",
+        "handle_export_midi_file_tool called with arguments: {:?}",
         arguments
     );
 
-    // Parse the simple sequence from JSON
-    let sequence: SimpleSequence = match serde_json::from_value(arguments) {
-        Ok(seq) => seq,
-        Err(e) => {
-            tracing::error!("Failed to parse note sequence: {}", e);
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Failed to parse note sequence: {}", e),
-                    data: None,
-                }),
-            };
-        }
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
     };
 
-    if sequence.notes.is_empty() {
-        tracing::warn!("Note sequence is empty");
-        return JsonRpcResponse {
+    if let Err(e) = check_tool_arguments("export_midi_file", &arguments, &["notes"], NOTES_GUIDANCE)
+    {
+        return invalid_params(id, e);
+    }
+    let args: ExportMidiFileArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid export_midi_file arguments: {}", e)),
+    };
+    let extension = Path::new(&args.output_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    if !matches!(extension.as_deref(), Some("mid" | "midi")) {
+        return invalid_params(
+            id,
+            format!(
+                "output_path must end in .mid or .midi, got {}",
+                args.output_path
+            ),
+        );
+    }
+    if let Err(e) = dry_run(&args.sequence) {
+        return invalid_params(id, format!("Invalid note sequence: {}", e));
+    }
+
+    match MidiPlayer::export_smf(&args.sequence, Path::new(&args.output_path)) {
+        Ok(export) => JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(json!({
+                "content": [
+                    {
+                        "type": "text",
+                        "text": format!(
+                            "🎹 Exported {} MIDI notes to {} ({} R2D2, synthesis or preset notes skipped)",
+                            export.exported,
+                            args.output_path,
+                            export.skipped
+                        )
+                    }
+                ],
+                "output_path": args.output_path,
+                "exported_notes": export.exported,
+                "skipped_notes": export.skipped
+            })),
+            error: None,
+        },
+        Err(e) => JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
             id,
             result: None,
             error: Some(JsonRpcError {
-                code: -32602,
-                message: "Note sequence cannot be empty".to_string(),
+                code: -32603,
+                message: format!("Failed to export MIDI file: {}", e),
                 data: None,
             }),
-        };
+        },
     }
+}
 
-    // Analyze the sequence to determine the playback mode
-    let mut has_midi = false;
-    let mut has_r2d2 = false;
-    let mut has_synthesis = false;
-    let mut has_presets = false;
+#[derive(Debug, Deserialize)]
+struct PlayMidiFileArgs {
+    path: String,
+    #[serde(default)]
+    channels: Vec<ChannelConfig>,
+}
 
-    for note in &sequence.notes {
-        // Validate note parameters first
-        if let Err(e) = note.validate_r2d2() {
-            return JsonRpcResponse {
+fn handle_play_midi_file_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_play_midi_file_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: PlayMidiFileArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(
+                id,
+                -32602,
+                format!("Invalid play_midi_file arguments: {}", e),
+            );
+        }
+    };
+    if let Err(e) = validate_channel_configs(&args.channels) {
+        return error_response(id, -32602, format!("Invalid channels: {}", e));
+    }
+    let mut sequence = match load_smf(Path::new(&args.path)) {
+        Ok(sequence) => sequence,
+        Err(e) => return error_response(id, -32602, e),
+    };
+    if sequence.notes.is_empty() {
+        return error_response(
+            id,
+            -32602,
+            format!("MIDI file {} has no notes to play", args.path),
+        );
+    }
+    sequence.channels = args.channels;
+    if let Err(e) = dry_run(&sequence) {
+        return error_response(
+            id,
+            -32602,
+            format!("MIDI file {} can't be played: {}", args.path, e),
+        );
+    }
+
+    let note_count = sequence.notes.len();
+    let player = match MidiPlayer::new() {
+        Ok(player) => player,
+        Err(e) => {
+            return error_response(id, -32603, format!("Failed to create MIDI player: {}", e));
+        }
+    };
+    match player.play_enhanced_mixed(sequence) {
+        Ok(total_time) => {
+            // Leak the player to keep audio stream alive for non-blocking playback
+            Box::leak(Box::new(player));
+            JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid R2D2 parameters: {}", e),
-                    data: None,
-                }),
-            };
+                result: Some(json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!(
+                                "📼 Playing {} notes from {}{}",
+                                note_count,
+                                args.path,
+                                audible_duration_note(total_time)
+                            )
+                        }
+                    ],
+                    "note_count": note_count,
+                    "duration_seconds": total_time.as_secs_f64()
+                })),
+                error: None,
+            }
+        }
+        Err(e) => {
+            player.control().panic();
+            error_response(id, -32603, format!("Failed to play MIDI file: {}", e))
         }
+    }
+}
 
-        if let Err(e) = note.validate_synthesis() {
-            return JsonRpcResponse {
+#[derive(Debug, Deserialize)]
+struct PlayAbcArgs {
+    abc: String,
+    #[serde(default)]
+    instrument: Option<u8>,
+    #[serde(default)]
+    channel: u8,
+}
+
+fn handle_play_abc_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_play_abc_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: PlayAbcArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(id, -32602, format!("Invalid play_abc arguments: {}", e));
+        }
+    };
+    if args.instrument.is_some_and(|instrument| instrument > 127) {
+        return error_response(id, -32602, "instrument must be 0-127".to_string());
+    }
+    if args.channel > 15 {
+        return error_response(
+            id,
+            -32602,
+            format!("channel must be 0-15, got {}", args.channel),
+        );
+    }
+    let mut sequence = match parse_abc(&args.abc) {
+        Ok(sequence) => sequence,
+        Err(e) => return error_response(id, -32602, format!("Invalid ABC: {}", e)),
+    };
+    for note in &mut sequence.notes {
+        note.channel = args.channel;
+        note.instrument = args.instrument;
+    }
+    if let Err(e) = dry_run(&sequence) {
+        return error_response(id, -32602, format!("Invalid ABC: {}", e));
+    }
+
+    let note_count = sequence.notes.len();
+    let tempo = sequence.tempo;
+    let player = match MidiPlayer::new() {
+        Ok(player) => player,
+        Err(e) => {
+            return error_response(id, -32603, format!("Failed to create MIDI player: {}", e));
+        }
+    };
+    match player.play_enhanced_mixed(sequence) {
+        Ok(total_time) => {
+            // Leak the player to keep audio stream alive for non-blocking playback
+            Box::leak(Box::new(player));
+            JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid synthesis parameters: {}", e),
-                    data: None,
-                }),
-            };
+                result: Some(json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": format!(
+                                "🎼 Playing {} notes of ABC at {} BPM{}",
+                                note_count,
+                                tempo,
+                                audible_duration_note(total_time)
+                            )
+                        }
+                    ],
+                    "note_count": note_count,
+                    "duration_seconds": total_time.as_secs_f64()
+                })),
+                error: None,
+            }
         }
+        Err(e) => {
+            player.control().panic();
+            error_response(id, -32603, format!("Failed to play ABC tune: {}", e))
+        }
+    }
+}
 
-        if let Err(e) = note.validate_preset() {
+fn handle_spectrum_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_spectrum_sequence_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    if let Err(e) =
+        check_tool_arguments("spectrum_sequence", &arguments, &["notes"], NOTES_GUIDANCE)
+    {
+        return invalid_params(id, e);
+    }
+    let spectrum_args: SpectrumArgs = match serde_json::from_value(arguments.clone()) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid spectrum arguments: {}", e)),
+    };
+    let sequence: SimpleSequence = match serde_json::from_value(arguments) {
+        Ok(seq) => seq,
+        Err(e) => return invalid_params(id, format!("Failed to parse note sequence: {}", e)),
+    };
+    if sequence.notes.is_empty() {
+        return invalid_params(id, "Note sequence cannot be empty".to_string());
+    }
+
+    let rendered = match MidiPlayer::render_samples(sequence) {
+        Ok(rendered) => rendered,
+        Err(e) => {
             return JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
                 result: None,
                 error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid preset parameters: {}", e),
+                    code: -32603,
+                    message: format!("Failed to render sequence: {}", e),
                     data: None,
                 }),
             };
         }
+    };
 
-        // Categorize note types
-        if note.note_type == "r2d2" {
-            has_r2d2 = true;
-        } else if note.is_synthesis() {
-            has_synthesis = true;
-        } else if note.is_preset() {
-            has_presets = true;
-        } else {
-            has_midi = true;
-        }
+    let spectrogram = spectrogram(
+        &rendered.samples,
+        rendered.sample_rate,
+        spectrum_args.frames,
+        spectrum_args.bands,
+    );
+    let spectrogram_json = serde_json::to_value(&spectrogram).unwrap_or(Value::Null);
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "📊 Spectrogram of {:.2}s: {} frames every {:.3}s, {} bands of {:.1}Hz (magnitudes in dBFS):\n{}",
+                        rendered.duration.as_secs_f64(),
+                        spectrogram.frames.len(),
+                        spectrogram.frame_interval,
+                        spectrogram.frames.first().map_or(0, Vec::len),
+                        spectrogram.band_hz,
+                        spectrogram_json
+                    )
+                }
+            ],
+            "spectrogram": spectrogram_json
+        })),
+        error: None,
     }
+}
+
+#[derive(Debug, Deserialize)]
+struct RearrangeArgs {
+    slices: u32,
+    order: Vec<u32>,
+}
 
+fn handle_rearrange_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
     tracing::info!(
-        "Sequence analysis: {} notes, has_midi: {}, has_r2d2: {}, has_synthesis: {}, has_presets: {}",
-        sequence.notes.len(),
-        has_midi,
-        has_r2d2,
-        has_synthesis,
-        has_presets
+        "handle_rearrange_tool called with arguments: {:?}",
+        arguments
     );
 
-    // Create MIDI player
-    let player = match MidiPlayer::new() {
-        Ok(p) => {
-            tracing::info!("Successfully created MIDI player");
-            p
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    if let Err(e) = check_tool_arguments("rearrange", &arguments, &["notes"], NOTES_GUIDANCE) {
+        return error_response(id, -32602, e);
+    }
+    let args: RearrangeArgs = match serde_json::from_value(arguments.clone()) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(id, -32602, format!("Invalid rearrange arguments: {}", e));
         }
+    };
+    if let Err(e) = validate_slice_order(args.slices, &args.order) {
+        return error_response(id, -32602, e);
+    }
+    let sequence: SimpleSequence = match serde_json::from_value(arguments) {
+        Ok(seq) => seq,
         Err(e) => {
-            tracing::error!("Failed to create MIDI player: {}", e);
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32603,
-                    message: format!("Failed to create MIDI player: {}", e),
-                    data: None,
-                }),
-            };
+            return error_response(id, -32602, format!("Failed to parse note sequence: {}", e));
         }
     };
+    if sequence.notes.is_empty() {
+        return error_response(id, -32602, "Note sequence cannot be empty".to_string());
+    }
 
-    // Use universal enhanced mixed playback for ALL sequences (supports everything!)
-    let mode = match (has_midi, has_r2d2, has_synthesis, has_presets) {
-        (true, true, true, true) => "MIDI + R2D2 + Synthesis + Presets",
-        (true, true, true, false) => "MIDI + R2D2 + Synthesis",
-        (true, true, false, true) => "MIDI + R2D2 + Presets",
-        (true, false, true, true) => "MIDI + Synthesis + Presets",
-        (false, true, true, true) => "R2D2 + Synthesis + Presets",
-        (true, false, true, false) => "MIDI + Synthesis",
-        (false, true, true, false) => "R2D2 + Synthesis",
-        (false, false, true, true) => "Synthesis + Presets",
-        (true, false, false, true) => "MIDI + Presets",
-        (false, true, false, true) => "R2D2 + Presets",
-        (false, false, true, false) => "Synthesis Only",
-        (false, true, false, false) => "R2D2 Only",
-        (false, false, false, true) => "Presets Only",
-        (true, false, false, false) => "Pure MIDI",
-        _ => "Mixed",
+    let master_limiter = sequence.master_limiter;
+    // Playback runs the master limiter over the rearranged audio
+    let rearranged = match MidiPlayer::render_for_playback(sequence)
+        .and_then(|rendered| rearrange(&rendered, args.slices, &args.order))
+    {
+        Ok(rearranged) => rearranged,
+        Err(e) => {
+            return error_response(id, -32603, format!("Failed to rearrange sequence: {}", e));
+        }
     };
 
-    tracing::info!(
-        "Using universal enhanced mixed playback for {} sequence",
-        mode
-    );
-    let playback_result = player.play_enhanced_mixed(sequence);
+    let player = match MidiPlayer::new() {
+        Ok(player) => player,
+        Err(e) => {
+            return error_response(id, -32603, format!("Failed to create MIDI player: {}", e));
+        }
+    };
 
-    // Handle the result
-    match playback_result {
-        Ok(()) => {
+    match player.play_rendered(rearranged, master_limiter) {
+        Ok(total_time) => {
             // Leak the player to keep audio stream alive for non-blocking playback
             Box::leak(Box::new(player));
-            tracing::info!("Player leaked to keep audio alive (non-blocking)");
-            let mode_description = match (has_midi, has_r2d2, has_synthesis, has_presets) {
-                (true, true, true, true) => {
-                    "🎵🤖🎛️🎹 Ultimate audio sequence playback started successfully! MIDI music, R2D2 expressions, custom synthesis, and classic preset sounds are now playing in perfect synchronization."
-                }
-                (true, true, true, false) => {
-                    "🎵🤖🎛️ Universal audio sequence playback started successfully! MIDI music, R2D2 expressions, and custom synthesis are now playing in perfect synchronization."
-                }
-                (true, true, false, true) => {
-                    "🎵🤖🎹 Mixed MIDI, R2D2, and preset sequence playback started successfully! Traditional music, robotic expressions, and vintage synthesizer sounds are now playing together."
-                }
-                (true, false, true, true) => {
-                    "🎵🎛️🎹 Mixed MIDI, synthesis, and preset sequence playback started successfully! Traditional music, custom synthesis, and classic sounds are now playing together."
-                }
-                (false, true, true, true) => {
-                    "🤖🎛️🎹 Mixed R2D2, synthesis, and preset sequence playback started successfully! Robotic expressions, custom synthesis, and vintage sounds are now playing in synchronization."
-                }
-                (true, false, true, false) => {
-                    "🎵🎛️ Mixed MIDI and synthesis sequence playback started successfully! Traditional music and custom synthesized sounds are now playing together."
-                }
-                (false, true, true, false) => {
-                    "🤖🎛️ Mixed R2D2 and synthesis sequence playback started successfully! Robotic expressions and custom sounds are now playing in synchronization."
-                }
-                (true, true, false, false) => {
-                    "🎵🤖 Mixed MIDI and R2D2 sequence playback started successfully! The music and robotic expressions are now playing in perfect synchronization."
-                }
-                (true, false, false, true) => {
-                    "🎵🎹 Mixed MIDI and preset sequence playback started successfully! Traditional music and classic synthesizer sounds are now playing together."
-                }
-                (false, true, false, true) => {
-                    "🤖🎹 Mixed R2D2 and preset sequence playback started successfully! Robotic expressions and vintage synthesizer sounds are now playing together."
-                }
-                (false, false, true, true) => {
-                    "🎛️🎹 Mixed synthesis and preset sequence playback started successfully! Custom synthesis and classic vintage sounds are now playing together."
-                }
-                (false, true, false, false) => {
-                    "🤖 R2D2 expression sequence playback started successfully! The robotic vocalizations are now playing."
-                }
-                (false, false, true, false) => {
-                    "🎛️ Custom synthesis sequence playback started successfully! Your unique synthesized sounds are now playing."
-                }
-                (false, false, false, true) => {
-                    "🎹 Classic synthesizer preset sequence playback started successfully! Authentic vintage synthesizer sounds are now playing."
-                }
-                _ => {
-                    "🎵 Pure MIDI sequence playback started successfully! The music is now playing."
-                }
-            };
-
-            tracing::info!("Playback completed successfully");
             JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
@@ -1038,378 +4237,693 @@ fn handle_play_notes_tool(arguments: Value, id: Option<Value>) -> JsonRpcRespons
                     "content": [
                         {
                             "type": "text",
-                            "text": mode_description
+                            "text": format!(
+                                "✂️ Playing {} of {} slices in order {:?}{}",
+                                args.order.len(),
+                                args.slices,
+                                args.order,
+                                audible_duration_note(total_time)
+                            )
                         }
                     ]
                 })),
                 error: None,
             }
         }
+        Err(e) => error_response(id, -32603, format!("Failed to play rearrangement: {}", e)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ConcatPatternsArgs {
+    names: Vec<ConcatSegment>,
+    #[serde(default = "default_concat_tempo")]
+    tempo: u32,
+}
+
+fn default_concat_tempo() -> u32 {
+    120
+}
+
+fn handle_concat_patterns_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_concat_patterns_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: ConcatPatternsArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid concat_patterns arguments: {}", e)),
+    };
+    if args.tempo == 0 {
+        return invalid_params(id, "Tempo must be greater than 0".to_string());
+    }
+
+    let concatenated = match PATTERN_STORE.lock() {
+        Ok(store) => ExtendedSequence::concat_patterns(&store, &args.names, args.tempo),
         Err(e) => {
-            tracing::error!("Failed to play sequence: {}", e);
-            JsonRpcResponse {
+            tracing::error!("Failed to lock pattern store: {}", e);
+            return JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
                 result: None,
                 error: Some(JsonRpcError {
                     code: -32603,
-                    message: format!("Failed to play sequence: {}", e),
+                    message: "Failed to access pattern store".to_string(),
                     data: None,
                 }),
+            };
+        }
+    };
+    let (sequence, total_bars) = match concatenated {
+        Ok(concatenated) => concatenated,
+        Err(e) => return invalid_params(id, format!("Failed to concatenate patterns: {}", e)),
+    };
+
+    let layout: Vec<String> = sequence
+        .patterns
+        .iter()
+        .map(|reference| {
+            format!(
+                "bar {}: {}",
+                reference.start_bar.unwrap_or(1),
+                reference.pattern_name
+            )
+        })
+        .collect();
+    let sequence_json = serde_json::to_value(&sequence).unwrap_or(Value::Null);
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "🔗 Concatenated {} patterns into {} bars ({}). Pass this sequence to play_sequence:\n{}",
+                        sequence.patterns.len(),
+                        total_bars,
+                        layout.join(", "),
+                        sequence_json
+                    )
+                }
+            ],
+            "sequence": sequence_json
+        })),
+        error: None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateMelodyArgs {
+    #[serde(flatten)]
+    spec: MelodySpec,
+    #[serde(default = "default_concat_tempo")]
+    tempo: u32,
+    instrument: Option<u8>,
+}
+
+fn handle_generate_melody_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_generate_melody_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: GenerateMelodyArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return invalid_params(id, format!("Invalid generate_melody arguments: {}", e));
+        }
+    };
+    if !(60..=200).contains(&args.tempo) {
+        return invalid_params(id, format!("Tempo must be 60-200 BPM, got {}", args.tempo));
+    }
+    if args.instrument.is_some_and(|instrument| instrument > 127) {
+        return invalid_params(id, "Instrument must be 0-127".to_string());
+    }
+    let notes = match args.spec.generate(args.tempo) {
+        Ok(notes) => notes,
+        Err(e) => return invalid_params(id, format!("Failed to generate melody: {}", e)),
+    };
+
+    // Only the fields the melody sets, so the sequence stays short enough to edit by hand
+    let note_json: Vec<Value> = notes
+        .iter()
+        .map(|note| {
+            let mut value = json!({
+                "note": note.note,
+                "velocity": note.velocity,
+                "start_time": note.start_time,
+                "duration": note.duration
+            });
+            if let Some(instrument) = args.instrument {
+                value["instrument"] = json!(instrument);
+            }
+            value
+        })
+        .collect();
+    let sequence_json = json!({"tempo": args.tempo, "notes": note_json});
+    let length: f64 = notes.iter().filter_map(|note| note.duration).sum();
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "🎲 Generated a {}-note {:?} melody in {} (root {}, seed {}), {:.2}s long. Pass this sequence to play_sequence:\n{}",
+                        notes.len(),
+                        args.spec.contour,
+                        args.spec.scale,
+                        args.spec.root,
+                        args.spec.seed,
+                        length,
+                        sequence_json
+                    )
+                }
+            ],
+            "sequence": sequence_json
+        })),
+        error: None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProgressionVoicedArgs {
+    #[serde(flatten)]
+    progression: VoicedProgression,
+    #[serde(default = "default_concat_tempo")]
+    tempo: u32,
+}
+
+fn handle_progression_voiced_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_progression_voiced_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: ProgressionVoicedArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return invalid_params(id, format!("Invalid progression_voiced arguments: {}", e));
+        }
+    };
+    if !(60..=200).contains(&args.tempo) {
+        return invalid_params(id, format!("Tempo must be 60-200 BPM, got {}", args.tempo));
+    }
+    let (voicings, notes) = match args
+        .progression
+        .voicings()
+        .and_then(|voicings| Ok((voicings, args.progression.generate(args.tempo)?)))
+    {
+        Ok(voiced) => voiced,
+        Err(e) => return invalid_params(id, format!("Failed to voice progression: {}", e)),
+    };
+
+    // Only the fields the chords set, so the returned sequence is easy to edit and replay
+    let note_json: Vec<Value> = notes
+        .iter()
+        .map(|note| {
+            let mut value = json!({
+                "note": note.note,
+                "velocity": note.velocity,
+                "start_time": note.start_time,
+                "duration": note.duration,
+                "channel": note.channel
+            });
+            if let Some(instrument) = note.instrument {
+                value["instrument"] = json!(instrument);
             }
+            value
+        })
+        .collect();
+    let sequence_json = json!({"tempo": args.tempo, "notes": note_json});
+    let mut response = handle_play_sequence_tool(sequence_json.clone(), id);
+    if let Some(result) = response.result.as_mut() {
+        let summary: Vec<String> = args
+            .progression
+            .chords
+            .iter()
+            .zip(&voicings)
+            .map(|(chord, voicing)| format!("{} {:?}", chord, voicing))
+            .collect();
+        if let Some(text) = result["content"][0]["text"].as_str() {
+            result["content"][0]["text"] =
+                json!(format!("{}\n🎹 Voicings: {}", text, summary.join(" → ")));
         }
+        result["voicings"] = json!(voicings);
+        result["sequence"] = sequence_json;
     }
+    response
 }
 
-fn handle_define_pattern_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+#[derive(Debug, Deserialize)]
+struct SaveProjectArgs {
+    path: String,
+    tempo: Option<u32>,
+}
+
+fn handle_save_project_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
     tracing::info!(
-        "handle_define_pattern_tool called with arguments: {:?}",
+        "handle_save_project_tool called with arguments: {:?}",
         arguments
     );
 
-    // Parse the sequence pattern from JSON
-    let pattern: SequencePattern = match serde_json::from_value(arguments) {
-        Ok(p) => p,
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: SaveProjectArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
         Err(e) => {
-            tracing::error!("Failed to parse sequence pattern: {}", e);
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Failed to parse sequence pattern: {}", e),
-                    data: None,
-                }),
-            };
+            return error_response(id, -32602, format!("Invalid save_project arguments: {}", e));
         }
     };
+    if args.path.trim().is_empty() {
+        return error_response(id, -32602, "Project path cannot be empty".to_string());
+    }
 
-    if pattern.notes.is_empty() {
-        tracing::warn!("Pattern notes are empty");
-        return JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            id,
-            result: None,
-            error: Some(JsonRpcError {
-                code: -32602,
-                message: "Pattern notes cannot be empty".to_string(),
-                data: None,
-            }),
-        };
+    let mut patterns: Vec<SequencePattern> = match PATTERN_STORE.lock() {
+        Ok(store) => store.values().cloned().collect(),
+        Err(_) => {
+            return error_response(id, -32603, "Failed to access pattern store".to_string());
+        }
+    };
+    patterns.sort_by(|a, b| a.name.cmp(&b.name));
+    let recent_sequences: Vec<ExtendedSequence> = RECENT_SEQUENCES
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default();
+
+    let project = Project {
+        version: PROJECT_VERSION,
+        tempo: args
+            .tempo
+            .or_else(|| recent_sequences.last().map(|sequence| sequence.tempo)),
+        patterns,
+        recent_sequences,
+    };
+    if let Err(e) = project.save(Path::new(&args.path)) {
+        return error_response(id, -32603, format!("Failed to save project: {}", e));
+    }
+
+    let names: Vec<&str> = project.patterns.iter().map(|p| p.name.as_str()).collect();
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "💾 Saved project to {}: {} patterns{}, {} recent sequences{}. Reload it with load_project.",
+                        args.path,
+                        names.len(),
+                        if names.is_empty() { String::new() } else { format!(" ({})", names.join(", ")) },
+                        project.recent_sequences.len(),
+                        project.tempo.map(|tempo| format!(", tempo {} BPM", tempo)).unwrap_or_default()
+                    )
+                }
+            ]
+        })),
+        error: None,
     }
+}
+
+#[derive(Debug, Deserialize)]
+struct LoadProjectArgs {
+    path: String,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+fn handle_load_project_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_load_project_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
 
-    // Validate all notes in the pattern
-    for (i, note) in pattern.notes.iter().enumerate() {
-        if let Err(e) = note.validate_r2d2() {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid R2D2 parameters in note {}: {}", i + 1, e),
-                    data: None,
-                }),
-            };
+    let args: LoadProjectArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(id, -32602, format!("Invalid load_project arguments: {}", e));
         }
+    };
+    let project = match Project::load(Path::new(&args.path)) {
+        Ok(project) => project,
+        Err(e) => return error_response(id, -32602, format!("Failed to load project: {}", e)),
+    };
 
-        if let Err(e) = note.validate_synthesis() {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid synthesis parameters in note {}: {}", i + 1, e),
-                    data: None,
-                }),
-            };
+    let names: Vec<String> = project.patterns.iter().map(|p| p.name.clone()).collect();
+    match PATTERN_STORE.lock() {
+        Ok(mut store) => {
+            let clashes: Vec<&str> = names
+                .iter()
+                .filter(|name| store.contains_key(*name))
+                .map(String::as_str)
+                .collect();
+            if !clashes.is_empty() && !args.overwrite {
+                return error_response(
+                    id,
+                    -32602,
+                    format!(
+                        "Patterns already defined: {}. Pass overwrite=true to replace them with the project's",
+                        clashes.join(", ")
+                    ),
+                );
+            }
+            for pattern in project.patterns {
+                store.insert(pattern.name.clone(), pattern);
+            }
         }
-
-        if let Err(e) = note.validate_preset() {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid preset parameters in note {}: {}", i + 1, e),
-                    data: None,
-                }),
-            };
+        Err(_) => {
+            return error_response(id, -32603, "Failed to access pattern store".to_string());
         }
     }
 
-    // Store the pattern
-    let pattern_name = pattern.name.clone();
-    match PATTERN_STORE.lock() {
-        Ok(mut store) => {
-            let pattern_info = format!(
-                "Pattern '{}' with {} notes, duration: {:.2}s",
-                pattern.name,
-                pattern.notes.len(),
-                pattern.get_pattern_duration()
-            );
+    let recent_count = project.recent_sequences.len();
+    let last_sequence = project
+        .recent_sequences
+        .last()
+        .and_then(|sequence| serde_json::to_string_pretty(sequence).ok());
+    for sequence in project.recent_sequences {
+        remember_sequence(sequence);
+    }
+    tracing::info!(
+        "Loaded project {:?} with {} patterns",
+        args.path,
+        names.len()
+    );
 
-            let category_info = pattern
-                .category
-                .as_ref()
-                .map(|c| format!(" (category: {})", c))
-                .unwrap_or_default();
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "📂 Loaded project from {}: {} patterns{}, {} recent sequences{}.{}",
+                        args.path,
+                        names.len(),
+                        if names.is_empty() { String::new() } else { format!(" ({})", names.join(", ")) },
+                        recent_count,
+                        project.tempo.map(|tempo| format!(", tempo {} BPM", tempo)).unwrap_or_default(),
+                        last_sequence
+                            .map(|json| format!("\n\nLast sequence played (pass to play_sequence to hear it again):\n```json\n{}\n```", json))
+                            .unwrap_or_default()
+                    )
+                }
+            ]
+        })),
+        error: None,
+    }
+}
 
-            let tags_info = if !pattern.tags.is_empty() {
-                format!(" [tags: {}]", pattern.tags.join(", "))
-            } else {
-                String::new()
-            };
+#[derive(Debug, Default, Deserialize)]
+struct ListPresetsArgs {
+    #[serde(default)]
+    category: Option<String>,
+}
 
-            store.insert(pattern.name.clone(), pattern);
-            tracing::info!("Stored pattern: {}", pattern_name);
+fn handle_list_presets_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_list_presets_tool called with arguments: {:?}",
+        arguments
+    );
 
-            JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: Some(json!({
-                    "content": [
-                        {
-                            "type": "text",
-                            "text": format!("🎼 Successfully defined sequence pattern: {}{}{}
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
+    };
 
-📋 **Pattern Details:**
-• **Name**: {}
-• **Notes**: {} notes
-• **Duration**: {:.2} seconds
-• **Tempo**: {} BPM
-{}{}
+    // The filter is optional, so no arguments at all lists everything
+    let args: ListPresetsArgs = if arguments.is_null() {
+        ListPresetsArgs::default()
+    } else {
+        match serde_json::from_value(arguments) {
+            Ok(args) => args,
+            Err(e) => return invalid_params(id, format!("Invalid list_presets arguments: {}", e)),
+        }
+    };
+    let category = match args.category.as_deref().map(PresetCategory::from_name) {
+        None => None,
+        Some(Ok(category)) => Some(category),
+        Some(Err(e)) => return invalid_params(id, e),
+    };
 
-✅ Pattern is now stored and ready to use with the `play_sequence` tool!
+    let library = PresetLibrary::new();
+    let presets = library.list_presets(category);
 
-💡 **Usage Example:**
-```json
-{{
-  \"patterns\": [
-    {{\"pattern_name\": \"{}\", \"start_time_offset\": 0}}
-  ]
-}}
-```",
-                                pattern_info, category_info, tags_info,
-                                pattern_name,
-                                store.get(&pattern_name).unwrap().notes.len(),
-                                store.get(&pattern_name).unwrap().get_pattern_duration(),
-                                store.get(&pattern_name).unwrap().tempo,
-                                store.get(&pattern_name).unwrap().description.as_ref()
-                                    .map(|d| format!("\n• **Description**: {}", d))
-                                    .unwrap_or_default(),
-                                if !store.get(&pattern_name).unwrap().tags.is_empty() {
-                                    format!("\n• **Tags**: {}", store.get(&pattern_name).unwrap().tags.join(", "))
-                                } else { String::new() },
-                                pattern_name
-                            )
-                        }
-                    ]
-                })),
-                error: None,
-            }
+    let mut output = String::from("🎹 **Synthesizer Presets**\n\n");
+    output.push_str(&format!("🎼 **{}** presets available:\n", presets.len()));
+    for category in PresetCategory::ALL {
+        let in_category: Vec<_> = presets
+            .iter()
+            .filter(|preset| preset.category == category)
+            .collect();
+        if in_category.is_empty() {
+            continue;
         }
-        Err(e) => {
-            tracing::error!("Failed to lock pattern store: {}", e);
-            JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32603,
-                    message: "Failed to store pattern due to internal error".to_string(),
-                    data: None,
-                }),
-            }
+        output.push_str(&format!(
+            "\n## **{}** ({})\n",
+            category.name(),
+            in_category.len()
+        ));
+        for preset in in_category {
+            output.push_str(&format!("- **{}**: {}\n", preset.name, preset.description));
         }
     }
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": output
+                }
+            ],
+            "count": presets.len(),
+            "presets": presets
+                .iter()
+                .map(|preset| json!({
+                    "name": preset.name,
+                    "category": preset.category.name(),
+                    "description": preset.description
+                }))
+                .collect::<Vec<_>>()
+        })),
+        error: None,
+    }
 }
 
-fn handle_play_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+#[derive(Debug, Deserialize)]
+struct ListVariationsArgs {
+    preset_name: String,
+}
+
+fn handle_list_variations_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
     tracing::info!(
-        "handle_play_sequence_tool called with arguments: {:?}",
+        "handle_list_variations_tool called with arguments: {:?}",
         arguments
     );
 
-    // Parse the extended sequence from JSON
-    let extended_sequence: ExtendedSequence = match serde_json::from_value(arguments) {
-        Ok(seq) => seq,
-        Err(e) => {
-            tracing::error!("Failed to parse extended sequence: {}", e);
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Failed to parse extended sequence: {}", e),
-                    data: None,
-                }),
-            };
-        }
+    let invalid_params = |id: Option<Value>, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: -32602,
+            message,
+            data: None,
+        }),
     };
 
-    if extended_sequence.notes.is_empty() && extended_sequence.patterns.is_empty() {
-        tracing::warn!("Extended sequence has no notes or patterns");
-        return JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            id,
-            result: None,
-            error: Some(JsonRpcError {
-                code: -32602,
-                message: "Sequence must contain either notes or pattern references".to_string(),
-                data: None,
-            }),
-        };
-    }
+    let args: ListVariationsArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => return invalid_params(id, format!("Invalid list_variations arguments: {}", e)),
+    };
 
-    // Validate individual notes
-    for (i, note) in extended_sequence.notes.iter().enumerate() {
-        if let Err(e) = note.validate_r2d2() {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid R2D2 parameters in note {}: {}", i + 1, e),
-                    data: None,
-                }),
-            };
-        }
+    let variations = match PresetLibrary::new().list_variations(&args.preset_name) {
+        Ok(variations) => variations,
+        Err(e) => return invalid_params(id, e),
+    };
 
-        if let Err(e) = note.validate_synthesis() {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid synthesis parameters in note {}: {}", i + 1, e),
-                    data: None,
-                }),
-            };
-        }
+    let text = if variations.is_empty() {
+        format!("🎨 '{}' has no variations.", args.preset_name)
+    } else {
+        let lines: Vec<String> = variations
+            .iter()
+            .map(|(name, description)| format!("- {}: {}", name, description))
+            .collect();
+        format!(
+            "🎨 Variations for '{}' (use as `preset_variation`):\n{}",
+            args.preset_name,
+            lines.join("\n")
+        )
+    };
 
-        if let Err(e) = note.validate_preset() {
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602,
-                    message: format!("Invalid preset parameters in note {}: {}", i + 1, e),
-                    data: None,
-                }),
-            };
-        }
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": text
+                }
+            ],
+            "variations": variations
+                .iter()
+                .map(|(name, description)| json!({"name": name, "description": description}))
+                .collect::<Vec<_>>()
+        })),
+        error: None,
     }
+}
 
-    // Resolve pattern references to get final sequence
-    let resolved_sequence = match PATTERN_STORE.lock() {
-        Ok(store) => match extended_sequence.resolve_patterns(&store) {
-            Ok(seq) => seq,
-            Err(e) => {
-                tracing::error!("Failed to resolve patterns: {}", e);
-                return JsonRpcResponse {
-                    jsonrpc: "2.0".to_string(),
-                    id,
-                    result: None,
-                    error: Some(JsonRpcError {
-                        code: -32602,
-                        message: format!("Failed to resolve patterns: {}", e),
-                        data: None,
-                    }),
-                };
-            }
-        },
-        Err(e) => {
-            tracing::error!("Failed to lock pattern store: {}", e);
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32603,
-                    message: "Failed to access pattern store".to_string(),
-                    data: None,
-                }),
-            };
-        }
+#[derive(Debug, Deserialize)]
+struct AuditionPresetArgs {
+    preset_name: String,
+    #[serde(default = "default_audition_note")]
+    note: u8,
+    #[serde(default = "default_audition_duration")]
+    duration: f64,
+}
+
+fn default_audition_note() -> u8 {
+    60
+}
+
+fn default_audition_duration() -> f64 {
+    1.5
+}
+
+fn handle_audition_preset_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_audition_preset_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
     };
 
-    if resolved_sequence.notes.is_empty() {
-        tracing::warn!("Resolved sequence is empty");
-        return JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
+    let args: AuditionPresetArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(
+                id,
+                -32602,
+                format!("Invalid audition_preset arguments: {}", e),
+            );
+        }
+    };
+    if args.note > 127 {
+        return error_response(id, -32602, format!("note must be 0-127, got {}", args.note));
+    }
+    if !(args.duration > 0.0 && args.duration <= 10.0) {
+        return error_response(
             id,
-            result: None,
-            error: Some(JsonRpcError {
-                code: -32602,
-                message: "Resolved sequence cannot be empty".to_string(),
-                data: None,
-            }),
-        };
+            -32602,
+            format!(
+                "duration must be greater than 0 and at most 10 seconds, got {}",
+                args.duration
+            ),
+        );
     }
 
-    // Create MIDI player
+    let sequence = match MidiPlayer::audition_sequence(
+        &PresetLibrary::new(),
+        &EffectsPresetLibrary::new(),
+        &args.preset_name,
+        args.note,
+        args.duration,
+    ) {
+        Ok(sequence) => sequence,
+        Err(e) => return error_response(id, -32602, e),
+    };
+
     let player = match MidiPlayer::new() {
-        Ok(p) => {
-            tracing::info!("Successfully created MIDI player");
-            p
-        }
+        Ok(player) => player,
         Err(e) => {
-            tracing::error!("Failed to create MIDI player: {}", e);
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32603,
-                    message: format!("Failed to create MIDI player: {}", e),
-                    data: None,
-                }),
-            };
+            return error_response(id, -32603, format!("Failed to create MIDI player: {}", e));
         }
     };
 
-    let pattern_count = extended_sequence.patterns.len();
-    let individual_notes_count = extended_sequence.notes.len();
-    let total_resolved_notes = resolved_sequence.notes.len();
-
-    tracing::info!(
-        "Playing sequence with {} pattern references, {} individual notes, {} total resolved notes",
-        pattern_count,
-        individual_notes_count,
-        total_resolved_notes
-    );
-
-    match player.play_enhanced_mixed(resolved_sequence) {
-        Ok(()) => {
+    match player.play_enhanced_mixed(sequence) {
+        Ok(total_time) => {
             // Leak the player to keep audio stream alive for non-blocking playback
             Box::leak(Box::new(player));
-            tracing::info!("Player leaked to keep audio alive (non-blocking)");
-
-            let composition_description = match (individual_notes_count > 0, pattern_count > 0) {
-                (true, true) => format!(
-                    "🎼🎵 Enhanced sequence playback started successfully! Playing {} individual notes plus {} pattern references (expanded to {} total notes) in perfect synchronization.",
-                    individual_notes_count, pattern_count, total_resolved_notes
-                ),
-                (false, true) => format!(
-                    "🎼 Pattern-based sequence playback started successfully! Playing {} pattern references (expanded to {} total notes) with all transformations applied.",
-                    pattern_count, total_resolved_notes
-                ),
-                (true, false) => format!(
-                    "🎵 Individual note sequence playback started successfully! Playing {} notes.",
-                    individual_notes_count
-                ),
-                (false, false) => "🎵 Sequence playback started successfully!".to_string(),
-            };
-
-            tracing::info!("Enhanced sequence playback completed successfully");
             JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
@@ -1417,7 +4931,13 @@ fn handle_play_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResp
                     "content": [
                         {
                             "type": "text",
-                            "text": composition_description
+                            "text": format!(
+                                "🎧 Auditioning '{}' on note {} for {:.2}s{}",
+                                args.preset_name,
+                                args.note,
+                                args.duration,
+                                audible_duration_note(total_time)
+                            )
                         }
                     ]
                 })),
@@ -1425,21 +4945,76 @@ fn handle_play_sequence_tool(arguments: Value, id: Option<Value>) -> JsonRpcResp
             }
         }
         Err(e) => {
-            tracing::error!("Failed to play enhanced sequence: {}", e);
-            JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32603,
-                    message: format!("Failed to play enhanced sequence: {}", e),
-                    data: None,
-                }),
-            }
+            player.control().panic();
+            error_response(id, -32603, format!("Failed to audition preset: {}", e))
         }
     }
 }
 
+/// Server capabilities enumerated from the synthesis and effect enums
+fn capabilities() -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "synth_types": SynthType::all().iter().map(SynthType::name).collect::<Vec<_>>(),
+        "effect_types": EffectType::all().iter().map(EffectType::name).collect::<Vec<_>>(),
+        "preset_count": PresetLibrary::new().list_preset_names().len(),
+        "effects_preset_count": EffectsPresetLibrary::new().get_preset_names().len(),
+        "soundfont_loaded": MidiPlayer::soundfont_available()
+    })
+}
+
+fn handle_get_capabilities_tool(id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!("handle_get_capabilities_tool called");
+
+    let capabilities = capabilities();
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": serde_json::to_string_pretty(&capabilities).unwrap_or_default()
+                }
+            ]
+        })),
+        error: None,
+    }
+}
+
+fn handle_engine_status_tool(id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!("handle_engine_status_tool called");
+
+    let status = MidiPlayer::engine_status();
+    let summary = if status.playing {
+        format!(
+            "▶️ Playing: {} playbacks, {} voices sounding",
+            status.playbacks, status.voices
+        )
+    } else {
+        "⏸️ Nothing is playing".to_string()
+    };
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "{}\n{}",
+                        summary,
+                        serde_json::to_string_pretty(&status).unwrap_or_default()
+                    )
+                }
+            ]
+        })),
+        error: None,
+    }
+}
+
 fn handle_list_patterns_tool(id: Option<Value>) -> JsonRpcResponse {
     tracing::info!("handle_list_patterns_tool called");
 
@@ -1573,6 +5148,163 @@ Use the `define_sequence_pattern` tool to create reusable musical patterns that
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct FindPatternsArgs {
+    #[serde(default)]
+    tags: Vec<String>,
+    category: Option<String>,
+}
+
+fn handle_find_patterns_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_find_patterns_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: FindPatternsArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(
+                id,
+                -32602,
+                format!("Invalid find_patterns arguments: {}", e),
+            );
+        }
+    };
+    if args.tags.is_empty() && args.category.is_none() {
+        return error_response(
+            id,
+            -32602,
+            "Give at least one tag or a category to search by; list_patterns shows every pattern"
+                .to_string(),
+        );
+    }
+
+    let mut matches: Vec<SequencePattern> = match PATTERN_STORE.lock() {
+        Ok(store) => store
+            .values()
+            .filter(|pattern| pattern.matches_filter(&args.tags, args.category.as_deref()))
+            .cloned()
+            .collect(),
+        Err(_) => {
+            return error_response(id, -32603, "Failed to access pattern store".to_string());
+        }
+    };
+    matches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut criteria = Vec::new();
+    if !args.tags.is_empty() {
+        criteria.push(format!("tags [{}]", args.tags.join(", ")));
+    }
+    if let Some(category) = &args.category {
+        criteria.push(format!("category '{}'", category));
+    }
+    let mut output = format!(
+        "🔎 **{}** patterns match {}\n",
+        matches.len(),
+        criteria.join(" and ")
+    );
+    for pattern in &matches {
+        output.push_str(&format!(
+            "\n• **{}** ({}) - {} notes, {:.1}s duration",
+            pattern.name,
+            pattern.category.as_deref().unwrap_or("uncategorized"),
+            pattern.notes.len(),
+            pattern.get_pattern_duration()
+        ));
+        if !pattern.tags.is_empty() {
+            output.push_str(&format!(" [{}]", pattern.tags.join(", ")));
+        }
+    }
+
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: Some(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": output
+                }
+            ],
+            "patterns": matches.iter().map(|pattern| &pattern.name).collect::<Vec<_>>()
+        })),
+        error: None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PlayPatternArgs {
+    name: String,
+    #[serde(default)]
+    transpose: i8,
+    instrument_override: Option<u8>,
+    tempo: Option<u32>,
+}
+
+fn handle_play_pattern_tool(arguments: Value, id: Option<Value>) -> JsonRpcResponse {
+    tracing::info!(
+        "handle_play_pattern_tool called with arguments: {:?}",
+        arguments
+    );
+
+    let error_response = |id: Option<Value>, code: i32, message: String| JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    };
+
+    let args: PlayPatternArgs = match serde_json::from_value(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            return error_response(id, -32602, format!("Invalid play_pattern arguments: {}", e));
+        }
+    };
+    if let Some(tempo) = args.tempo.filter(|tempo| !(60..=200).contains(tempo)) {
+        return error_response(
+            id,
+            -32602,
+            format!("Tempo must be 60-200 BPM, got {}", tempo),
+        );
+    }
+
+    let sequence = match PATTERN_STORE.lock() {
+        Ok(store) => ExtendedSequence::solo_pattern(
+            &store,
+            &args.name,
+            args.transpose,
+            args.instrument_override,
+            args.tempo,
+        ),
+        Err(_) => {
+            return error_response(id, -32603, "Failed to access pattern store".to_string());
+        }
+    };
+    match sequence.and_then(|sequence| {
+        serde_json::to_value(sequence).map_err(|e| format!("Failed to build sequence: {}", e))
+    }) {
+        // Validation and playback are the same as for a one-pattern play_sequence
+        Ok(sequence_json) => handle_play_sequence_tool(sequence_json, id),
+        Err(e) => error_response(id, -32602, e),
+    }
+}
+
 pub fn run_stdio_server() {
     tracing::info!("MCP server starting");
 
@@ -1652,3 +5384,549 @@ pub fn run_stdio_server() {
 
     tracing::info!("MCP server shutting down");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::VALID_SYNTH_TYPES;
+
+    #[test]
+    fn test_capabilities_synth_types_match_validation() {
+        let capabilities = capabilities();
+        let synth_types: Vec<&str> = capabilities["synth_types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_str().unwrap())
+            .collect();
+        assert_eq!(synth_types, VALID_SYNTH_TYPES);
+
+        let effect_types = capabilities["effect_types"].as_array().unwrap();
+        assert!(effect_types.contains(&json!("reverb")));
+        assert!(effect_types.contains(&json!("multiband_compressor")));
+    }
+
+    #[test]
+    fn test_multiband_compressor_schema_ranges_match_validation() {
+        fn find_variant<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+            match value {
+                Value::Object(map)
+                    if map.get("properties").and_then(|p| p.get("type"))
+                        == Some(&json!({"const": name})) =>
+                {
+                    Some(value)
+                }
+                Value::Object(map) => map.values().find_map(|v| find_variant(v, name)),
+                Value::Array(items) => items.iter().find_map(|v| find_variant(v, name)),
+                _ => None,
+            }
+        }
+        let tools = handle_tools_list(None).result.unwrap();
+        let variant = find_variant(&tools, "MultibandCompressor").unwrap();
+        let band_properties = variant["properties"]["bands"]["items"]["properties"]
+            .as_object()
+            .unwrap();
+        let validate = |property: &str, value: f64| {
+            let note: SimpleNote = serde_json::from_value(json!({
+                "note": 60,
+                "effects": [{
+                    "type": "multiband_compressor",
+                    "crossovers": [200.0],
+                    "bands": [{property: value}, {}]
+                }]
+            }))
+            .unwrap();
+            note.validate_effects()
+        };
+
+        for (property, schema) in band_properties {
+            let minimum = schema["minimum"].as_f64().unwrap();
+            let maximum = schema["maximum"].as_f64().unwrap();
+            let step = (maximum - minimum) * 1e-3;
+            assert!(
+                validate(property, minimum).is_ok(),
+                "{} at {}",
+                property,
+                minimum
+            );
+            assert!(
+                validate(property, maximum).is_ok(),
+                "{} at {}",
+                property,
+                maximum
+            );
+            assert!(
+                validate(property, minimum - step).is_err(),
+                "{} below",
+                property
+            );
+            assert!(
+                validate(property, maximum + step).is_err(),
+                "{} above",
+                property
+            );
+        }
+    }
+
+    fn define_riff(velocity: u8, overwrite: Option<bool>) -> JsonRpcResponse {
+        let mut arguments = json!({
+            "name": "overwrite_test_riff",
+            "notes": [{"note": 60, "velocity": velocity, "start_time": 0.0, "duration": 0.5}]
+        });
+        if let Some(overwrite) = overwrite {
+            arguments["overwrite"] = json!(overwrite);
+        }
+        handle_define_pattern_tool(arguments, Some(json!(1)))
+    }
+
+    fn stored_riff_velocity() -> Option<u8> {
+        PATTERN_STORE.lock().unwrap()["overwrite_test_riff"].notes[0].velocity
+    }
+
+    #[test]
+    fn test_redefining_a_pattern_requires_overwrite() {
+        let created = define_riff(70, None);
+        assert!(created.error.is_none());
+        let text = created.result.unwrap()["content"][0]["text"].to_string();
+        assert!(text.contains("Successfully defined"), "{}", text);
+
+        let rejected = define_riff(100, Some(false));
+        let error = rejected.error.expect("duplicate name should be rejected");
+        assert_eq!(error.code, -32602);
+        assert!(
+            error.message.contains("overwrite=true"),
+            "{}",
+            error.message
+        );
+        assert_eq!(stored_riff_velocity(), Some(70));
+
+        let replaced = define_riff(100, Some(true));
+        assert!(replaced.error.is_none());
+        let text = replaced.result.unwrap()["content"][0]["text"].to_string();
+        assert!(text.contains("Successfully replaced"), "{}", text);
+        assert_eq!(stored_riff_velocity(), Some(100));
+    }
+
+    #[test]
+    fn test_empty_or_missing_arguments_get_actionable_errors() {
+        for arguments in [json!({}), json!({"notes": []}), Value::Null] {
+            let response = handle_play_notes_tool(arguments.clone(), Some(json!(1)));
+            let error = response.error.expect("empty arguments should be rejected");
+            assert_eq!(error.code, -32602);
+            assert!(
+                error.message.contains("non-empty 'notes' array")
+                    && error.message.contains("get_capabilities"),
+                "{}: {}",
+                arguments,
+                error.message
+            );
+        }
+
+        // A tools/call without an arguments key reaches the tool as null
+        let response = handle_tool_call(Some(json!({"name": "play_sequence"})), Some(json!(1)));
+        let error = response
+            .error
+            .expect("missing arguments should be rejected");
+        assert!(
+            error
+                .message
+                .contains("play_sequence was called without arguments"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn test_normalize_scales_a_five_beat_pattern_into_one_bar() {
+        let response = handle_define_pattern_tool(
+            json!({
+                "name": "normalize_test_overlong",
+                "pattern_bars": 1,
+                "tempo": 120,
+                "notes": [
+                    {"note": 60, "start_time": 0.0, "duration": 0.5},
+                    {"note": 64, "start_time": 1.0, "duration": 0.5},
+                    {"note": 67, "start_time": 2.0, "duration": 0.5}
+                ],
+                "normalize": true,
+                "overwrite": true
+            }),
+            Some(json!(1)),
+        );
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("ran past pattern_bars"), "{}", text);
+
+        // The last note ended at 5 beats (2.5s); one bar at 120 BPM is 2s
+        let store = PATTERN_STORE.lock().unwrap();
+        let pattern = &store["normalize_test_overlong"];
+        let last = &pattern.notes[2];
+        let end = last.start_time.unwrap() + last.duration.unwrap();
+        assert!((end - 2.0).abs() < 1e-9, "pattern ends at {}", end);
+        assert!((pattern.notes[1].start_time.unwrap() - 0.8).abs() < 1e-9);
+        assert!((pattern.notes[0].duration.unwrap() - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_saved_project_reloads_both_patterns() {
+        let define = |name: &str, note: u8| {
+            let response = handle_define_pattern_tool(
+                json!({
+                    "name": name,
+                    "notes": [{"note": note, "start_time": 0.0, "duration": 0.5}],
+                    "overwrite": true
+                }),
+                Some(json!(1)),
+            );
+            assert!(response.error.is_none(), "{:?}", response.error);
+        };
+        define("project_test_bass", 36);
+        define("project_test_lead", 72);
+
+        let path =
+            std::env::temp_dir().join(format!("mcp-muse-project-{}.json", std::process::id()));
+        let path_arg = path.to_string_lossy().to_string();
+        let saved =
+            handle_save_project_tool(json!({"path": path_arg, "tempo": 96}), Some(json!(1)));
+        assert!(saved.error.is_none(), "{:?}", saved.error);
+
+        {
+            let mut store = PATTERN_STORE.lock().unwrap();
+            store.remove("project_test_bass");
+            store.remove("project_test_lead");
+        }
+        // Other tests' patterns are saved too and still defined, so replace them
+        let loaded =
+            handle_load_project_tool(json!({"path": path_arg, "overwrite": true}), Some(json!(1)));
+        assert!(loaded.error.is_none(), "{:?}", loaded.error);
+        {
+            let store = PATTERN_STORE.lock().unwrap();
+            assert_eq!(store["project_test_bass"].notes[0].note, Some(36));
+            assert_eq!(store["project_test_lead"].notes[0].note, Some(72));
+        }
+        assert_eq!(Project::load(&path).unwrap().tempo, Some(96));
+
+        // Loading again would clobber the live patterns, so it needs overwrite
+        let again = handle_load_project_tool(json!({"path": path_arg}), Some(json!(1)));
+        let error = again.error.expect("clashing names should be refused");
+        assert!(
+            error.message.contains("project_test_bass"),
+            "{}",
+            error.message
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_find_patterns_matches_all_tags_and_the_category() {
+        let define = |name: &str, category: &str, tags: &[&str]| {
+            let response = handle_define_pattern_tool(
+                json!({
+                    "name": name,
+                    "category": category,
+                    "tags": tags,
+                    "notes": [{"note": 60, "start_time": 0.0, "duration": 0.5}],
+                    "overwrite": true
+                }),
+                Some(json!(1)),
+            );
+            assert!(response.error.is_none(), "{:?}", response.error);
+        };
+        define("find_test_funky_drums", "drums", &["funky", "groove"]);
+        define("find_test_funky_bass", "bass", &["Funky"]);
+        define("find_test_straight_drums", "drums", &["straight"]);
+
+        let found = |arguments: Value| -> Vec<String> {
+            let response = handle_find_patterns_tool(arguments, Some(json!(1)));
+            assert!(response.error.is_none(), "{:?}", response.error);
+            serde_json::from_value(response.result.unwrap()["patterns"].clone()).unwrap()
+        };
+        assert_eq!(
+            found(json!({"tags": ["funky"]})),
+            ["find_test_funky_bass", "find_test_funky_drums"]
+        );
+        assert_eq!(
+            found(json!({"tags": ["funky"], "category": "drums"})),
+            ["find_test_funky_drums"]
+        );
+        assert!(found(json!({"tags": ["funky", "straight"]})).is_empty());
+
+        let error = handle_find_patterns_tool(json!({}), Some(json!(1)))
+            .error
+            .unwrap();
+        assert_eq!(error.code, -32602);
+    }
+
+    #[test]
+    fn test_out_of_range_reference_scales_are_rejected() {
+        for (field, value, range) in [
+            ("velocity_scale", 0.0, "between 0.1 and 2.0"),
+            ("duration_scale", 10.0, "between 0.1 and 4.0"),
+        ] {
+            let mut reference = json!({"pattern_name": "scale_test_riff", "start_bar": 1});
+            reference[field] = json!(value);
+            let response = handle_play_sequence_tool(
+                json!({
+                    "notes": [{"note": 60, "start_time": 0.0, "duration": 0.5}],
+                    "patterns": [reference]
+                }),
+                Some(json!(1)),
+            );
+            let error = response
+                .error
+                .expect("out-of-range scale should be rejected");
+            assert_eq!(error.code, -32602);
+            assert!(
+                error.message.contains("pattern reference 1")
+                    && error.message.contains(&format!("{} {}", field, value))
+                    && error.message.contains(range),
+                "{}",
+                error.message
+            );
+        }
+    }
+
+    #[test]
+    fn test_play_sequence_places_patterns_along_tempo_changes() {
+        let pattern: SequencePattern = serde_json::from_value(json!({
+            "name": "tempo_test_hit",
+            "notes": [{"note": 38, "channel": 9, "start_time": 0.0, "duration": 0.1}]
+        }))
+        .unwrap();
+        PATTERN_STORE
+            .lock()
+            .unwrap()
+            .insert("tempo_test_hit".to_string(), pattern);
+        let start = |tempo_changes: Value| {
+            let arguments = json!({
+                "patterns": [{"pattern_name": "tempo_test_hit", "start_bar": 4}],
+                "tempo": 120,
+                "tempo_changes": tempo_changes
+            });
+            let (_, resolved) = prepare_sequence(arguments).unwrap();
+            resolved.notes[0].start_time.unwrap()
+        };
+
+        assert!((start(json!([])) - 6.0).abs() < 1e-9);
+        // Slowing from 120 to 60 BPM by bar 3 takes 8 ln 2 seconds, then a bar at 60 BPM
+        let slowed = start(json!([{"at": {"bar": 3, "beat": 1, "tick": 0}, "tempo": 60}]));
+        let expected = 8.0 * std::f64::consts::LN_2 + 4.0;
+        assert!(
+            (slowed - expected).abs() < 1e-6,
+            "bar 4 starts at {}",
+            slowed
+        );
+
+        let invalid = json!({
+            "patterns": [{"pattern_name": "tempo_test_hit", "start_bar": 4}],
+            "tempo_changes": [{"at": {"bar": 2, "beat": 1, "tick": 0}, "tempo": 500}]
+        });
+        let report = handle_validate_sequence_tool(invalid, None).result.unwrap();
+        assert_eq!(report["valid"], false);
+        assert!(
+            report["errors"][0]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid tempo_changes:"),
+            "{}",
+            report["errors"][0]
+        );
+    }
+
+    #[test]
+    fn test_validate_sequence_reports_what_play_sequence_would_do() {
+        let validate = |arguments: Value| {
+            let response = handle_validate_sequence_tool(arguments, Some(json!(1)));
+            assert!(response.error.is_none());
+            response.result.unwrap()
+        };
+
+        // The same message play_sequence fails with, plus every other problem
+        let invalid = json!({
+            "notes": [{"note": 60, "start_time": 0.0, "duration": 0.5}],
+            "swing": 0.9,
+            "fit_duration": -1.0
+        });
+        let played = handle_play_sequence_tool(invalid.clone(), Some(json!(1)));
+        let report = validate(invalid);
+        assert_eq!(report["valid"], false);
+        assert_eq!(report["errors"][0], played.error.unwrap().message);
+        assert_eq!(report["errors"].as_array().unwrap().len(), 2);
+
+        let missing = validate(json!({
+            "patterns": [{"pattern_name": "validate_test_missing", "start_bar": 1}]
+        }));
+        assert_eq!(
+            missing["errors"][0],
+            "Failed to resolve patterns: Pattern 'validate_test_missing' not found"
+        );
+
+        // An unknown preset is logged and skipped by playback, so it is only a warning
+        let report = validate(json!({
+            "notes": [
+                {"note": 60, "start_time": 0.0, "duration": 0.5},
+                {"note": 64, "start_time": 0.5, "duration": 0.5, "preset_name": "No Such Preset"}
+            ]
+        }));
+        assert_eq!(report["valid"], true);
+        assert_eq!(report["resolved_notes"], 2);
+        let warnings = report["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].as_str().unwrap().starts_with("Note 2:")
+                && warnings[0].as_str().unwrap().contains("No Such Preset"),
+            "{}",
+            warnings[0]
+        );
+
+        // Errors found in the resolved notes carry play_sequence's playback prefix
+        let pattern: SequencePattern = serde_json::from_value(json!({
+            "name": "validate_test_early",
+            "notes": [{"note": 42, "channel": 9, "start_time": 0.0, "duration": 0.1}]
+        }))
+        .unwrap();
+        PATTERN_STORE
+            .lock()
+            .unwrap()
+            .insert("validate_test_early".to_string(), pattern);
+        let report = validate(json!({
+            "patterns": [{"pattern_name": "validate_test_early", "start_time_offset": -0.5}],
+            "negative_start": "reject"
+        }));
+        assert_eq!(
+            report["errors"][0],
+            "Failed to play enhanced sequence: Notes 1 start before time zero after pattern offsets and timing transforms"
+        );
+    }
+
+    #[test]
+    fn test_render_sequence_writes_a_wav_without_an_audio_device() {
+        let dir = std::env::temp_dir().join(format!("mcp-muse-render-{}", std::process::id()));
+        let path = dir.join("bells").join("chime.wav");
+        let notes = json!([{
+            "start_time": 0.0,
+            "duration": 0.5,
+            "synth_type": "sine",
+            "synth_frequency": 880.0
+        }]);
+
+        let response = render_sequence_into(
+            &dir,
+            json!({"notes": notes, "output_path": "bells/chime.wav"}),
+            Some(json!(1)),
+        );
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let result = response.result.unwrap();
+        assert_eq!(result["output_path"], json!(path));
+        let duration = result["duration_seconds"].as_f64().unwrap();
+        assert!(duration >= 0.5, "{}", duration);
+
+        let wav = hound::WavReader::open(&path).unwrap();
+        assert_eq!(wav.spec().channels, 2);
+        assert_eq!(wav.spec().sample_rate, 44100);
+        assert!(wav.duration() as f64 >= 0.5 * 44100.0);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let unsupported = render_sequence_into(
+            &dir,
+            json!({"notes": notes, "output_path": "chime.mp3"}),
+            Some(json!(1)),
+        );
+        let error = unsupported.error.expect("mp3 is not an export format");
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("mp3"), "{}", error.message);
+
+        // Nothing is written outside the renders directory
+        let outside =
+            std::env::temp_dir().join(format!("mcp-muse-escape-{}.wav", std::process::id()));
+        for output_path in [
+            outside.to_string_lossy().to_string(),
+            format!("../{}", outside.file_name().unwrap().to_string_lossy()),
+        ] {
+            let escaping = render_sequence_into(
+                &dir,
+                json!({"notes": notes, "output_path": output_path}),
+                Some(json!(1)),
+            );
+            let error = escaping
+                .error
+                .expect("paths outside the directory are rejected");
+            assert_eq!(error.code, -32602);
+            assert!(!outside.exists());
+        }
+    }
+
+    #[test]
+    fn test_list_presets_filters_by_category_and_names_resolve() {
+        let all = handle_list_presets_tool(Value::Null, Some(json!(1)))
+            .result
+            .unwrap();
+        let count = all["count"].as_u64().unwrap() as usize;
+        assert!(count > 0);
+        assert_eq!(all["presets"].as_array().unwrap().len(), count);
+
+        let bass = handle_list_presets_tool(json!({"category": "bass"}), Some(json!(1)))
+            .result
+            .unwrap();
+        let presets = bass["presets"].as_array().unwrap();
+        assert!(!presets.is_empty() && presets.len() < count);
+        let library = PresetLibrary::new();
+        for preset in presets {
+            assert_eq!(preset["category"], "bass");
+            // Every listed name loads as preset_name
+            let name = preset["name"].as_str().unwrap();
+            assert_eq!(library.load_preset(name).unwrap().name, name);
+        }
+
+        let error = handle_list_presets_tool(json!({"category": "strings"}), Some(json!(1)))
+            .error
+            .expect("unknown categories are rejected");
+        assert!(
+            error.message.contains("Valid categories"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn test_stop_playback_by_id_follows_the_registry() {
+        // Far from the ids handed out to real playbacks
+        let playback_id = u64::MAX - 1;
+        let unknown = handle_stop_playback_tool(json!({"playback_id": playback_id}), None);
+        assert_eq!(unknown.error.unwrap().code, -32602);
+
+        register_playback(playback_id, PlaybackControl::default());
+        let stopped = handle_stop_playback_tool(json!({"playback_id": playback_id}), None);
+        let result = stopped
+            .result
+            .expect("a registered playback can be stopped");
+        assert!(result["fade_ms"].as_f64().is_some());
+        assert!(
+            result["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains(&format!("playback {}", playback_id))
+        );
+
+        finish_playback(playback_id);
+        assert!(stop_detached_playback(playback_id).is_err());
+    }
+
+    #[test]
+    fn test_play_abc_rejects_unplayable_lengths_before_playback() {
+        for (abc, message) in [
+            ("K:C\nC/0", "Unsupported note length '/0'"),
+            ("K:C\nC0", "Unsupported note length '0'"),
+            // Every length is in range, but the unit makes the note last for days
+            ("L:1000000/1\nK:C\nC", "duration must be"),
+        ] {
+            let response = handle_play_abc_tool(json!({"abc": abc}), None);
+            let error = response.error.expect("the tune is rejected");
+            assert_eq!(error.code, -32602);
+            assert!(error.message.contains(message), "{}", error.message);
+        }
+    }
+}
@@ -2,13 +2,33 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
+use std::time::Duration;
 
 const CONFIG_FILE: &str = "config.json";
+/// Fade-out applied when playback is stopped, unless configured otherwise
+pub const DEFAULT_STOP_FADE_MS: u64 = 10;
+/// Frames rendered per internal block when playback is buffered
+pub const DEFAULT_AUDIO_BLOCK_FRAMES: usize = 512;
+/// Loudest allowed master gain (+6 dB)
+pub const MAX_MASTER_GAIN: f32 = 2.0;
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct SetupConfig {
     pub hosts: Vec<HostConfig>,
     pub soundfont_path: Option<String>,
+    /// Fade-out length in milliseconds when playback is stopped (default: 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_fade_ms: Option<u64>,
+    /// Audio pre-rendered before playback starts, in milliseconds; raise on slow machines
+    /// that glitch (default: 0, unbuffered)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_buffer_ms: Option<u64>,
+    /// Frames rendered per block when `audio_buffer_ms` is set (default: 512)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_block_frames: Option<usize>,
+    /// Listening volume applied to everything played on the audio device (0.0-2.0, default: 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub master_gain: Option<f32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -40,6 +60,28 @@ impl SetupConfig {
         config_dir.join(CONFIG_FILE)
     }
 
+    /// Fade-out applied by `MidiPlayer::stop()` to avoid a click
+    pub fn stop_fade(&self) -> Duration {
+        Duration::from_millis(self.stop_fade_ms.unwrap_or(DEFAULT_STOP_FADE_MS))
+    }
+
+    /// Pre-roll for buffered playback, or zero to play unbuffered
+    pub fn audio_buffer(&self) -> Duration {
+        Duration::from_millis(self.audio_buffer_ms.unwrap_or(0))
+    }
+
+    /// Block size for buffered playback
+    pub fn audio_block_frames(&self) -> usize {
+        self.audio_block_frames
+            .unwrap_or(DEFAULT_AUDIO_BLOCK_FRAMES)
+            .max(1)
+    }
+
+    /// Master gain for device playback, clamped to 0.0-2.0
+    pub fn master_gain(&self) -> f32 {
+        self.master_gain.unwrap_or(1.0).clamp(0.0, MAX_MASTER_GAIN)
+    }
+
     pub fn load() -> io::Result<Self> {
         let path = Self::config_path();
         if !path.exists() {
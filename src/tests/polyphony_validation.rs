@@ -2,7 +2,7 @@ use std::time::Duration;
 use anyhow::Result;
 
 use crate::midi::{SimpleNote, SimpleSequence, MidiPlayer};
-use crate::expressive::{PolyphonicVoiceManager, SynthParams, SynthType, EnvelopeParams};
+use crate::expressive::{PolyphonicVoiceManager, SynthParams, SynthType, EnvelopeCurve, EnvelopeParams};
 
 /// Comprehensive polyphony validation tests
 pub struct PolyphonyValidator {
@@ -266,20 +266,24 @@ impl PolyphonyValidator {
             frequency: 440.0,
             amplitude: 0.5,
             duration: 1.0,
+            phase: 0.0,
             envelope: EnvelopeParams {
                 attack: 0.1,
                 decay: 0.2,
                 sustain: 0.7,
                 release: 0.3,
+                curve: EnvelopeCurve::Linear,
             },
             filter: None,
             effects: Vec::new(),
+            vibrato: None,
+            tremolo: None,
         };
 
         println!("  🧪 Testing basic voice allocation...");
-        let voice_id = voice_manager.allocate_voice(synth_params.clone(), 0.0, Some(60), 0, 100)
+        let allocation = voice_manager.allocate_voice(synth_params.clone(), 0.0, Some(60), 0, 100)
             .map_err(|e| format!("Voice allocation failed: {}", e))?;
-        println!("     ✅ Allocated voice ID: {}", voice_id);
+        println!("     ✅ Allocated voice ID: {}", allocation.voice_id);
 
         // Test 2: Voice count tracking
         println!("  🧪 Testing voice count tracking...");
@@ -432,14 +436,18 @@ mod tests {
             frequency: 440.0,
             amplitude: 0.5,
             duration: 1.0,
+            phase: 0.0,
             envelope: EnvelopeParams {
                 attack: 0.1,
                 decay: 0.2,
                 sustain: 0.7,
                 release: 0.3,
+                curve: EnvelopeCurve::Linear,
             },
             filter: None,
             effects: Vec::new(),
+            vibrato: None,
+            tremolo: None,
         };
 
         let _voice_id = voice_manager.allocate_voice(synth_params, 0.0, Some(60), 0, 100)?;
@@ -457,14 +465,18 @@ mod tests {
             frequency: 440.0,
             amplitude: 0.5,
             duration: 1.0,
+            phase: 0.0,
             envelope: EnvelopeParams {
                 attack: 0.1,
                 decay: 0.2,
                 sustain: 0.7,
                 release: 0.3,
+                curve: EnvelopeCurve::Linear,
             },
             filter: None,
             effects: Vec::new(),
+            vibrato: None,
+            tremolo: None,
         };
 
         let _voice_id = voice_manager.allocate_voice(synth_params, 0.0, Some(60), 0, 100)?;
@@ -106,7 +106,7 @@ fn test_mcp_tools_list() {
     assert!(response["result"]["tools"].is_array());
 
     let tools = response["result"]["tools"].as_array().unwrap();
-    assert_eq!(tools.len(), 4);
+    assert_eq!(tools.len(), 29);
 
     // Check that all tools are present
     let tool_names: Vec<&str> = tools
@@ -117,6 +117,31 @@ fn test_mcp_tools_list() {
     assert!(tool_names.contains(&"define_sequence_pattern"));
     assert!(tool_names.contains(&"play_sequence"));
     assert!(tool_names.contains(&"list_patterns"));
+    assert!(tool_names.contains(&"panic"));
+    assert!(tool_names.contains(&"stop_playback"));
+    assert!(tool_names.contains(&"get_capabilities"));
+    assert!(tool_names.contains(&"save_effects_preset"));
+    assert!(tool_names.contains(&"define_preset"));
+    assert!(tool_names.contains(&"check_mono_compatibility"));
+    assert!(tool_names.contains(&"list_variations"));
+    assert!(tool_names.contains(&"audition_preset"));
+    assert!(tool_names.contains(&"set_master_volume"));
+    assert!(tool_names.contains(&"concat_patterns"));
+    assert!(tool_names.contains(&"spectrum_sequence"));
+    assert!(tool_names.contains(&"rearrange"));
+    assert!(tool_names.contains(&"save_project"));
+    assert!(tool_names.contains(&"load_project"));
+    assert!(tool_names.contains(&"engine_status"));
+    assert!(tool_names.contains(&"generate_melody"));
+    assert!(tool_names.contains(&"find_patterns"));
+    assert!(tool_names.contains(&"play_pattern"));
+    assert!(tool_names.contains(&"progression_voiced"));
+    assert!(tool_names.contains(&"render_sequence"));
+    assert!(tool_names.contains(&"list_presets"));
+    assert!(tool_names.contains(&"play_midi_file"));
+    assert!(tool_names.contains(&"play_abc"));
+    assert!(tool_names.contains(&"validate_sequence"));
+    assert!(tool_names.contains(&"export_midi_file"));
 
     // Verify the play_notes tool supports all the functionality
     let play_notes_tool = tools
@@ -301,6 +326,179 @@ fn test_play_notes_tool_with_valid_notes() {
     child.kill().expect("Failed to kill child process");
 }
 
+#[test]
+#[allow(clippy::zombie_processes)]
+fn test_async_play_notes_returns_a_playback_id() {
+    // ALSA's null device stands in for a sound card, so playback starts on machines
+    // without one
+    let alsa_config = std::env::temp_dir().join("mcp-muse-null-asound.conf");
+    std::fs::write(&alsa_config, "pcm.!default { type null }\n")
+        .expect("Failed to write ALSA config");
+    let mut child = Command::new("cargo")
+        .args(["run", "--"])
+        .env("ALSA_CONFIG_PATH", &alsa_config)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start MCP server");
+
+    let mut stdin = child.stdin.take().expect("Failed to open stdin");
+    let stdout = child.stdout.take().expect("Failed to open stdout");
+    let mut reader = BufReader::new(stdout);
+
+    // Initialize first
+    let init_request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {"name": "test-client", "version": "1.0.0"}
+        }
+    });
+
+    writeln!(stdin, "{}", init_request).expect("Failed to write to stdin");
+    let mut response_line = String::new();
+    reader
+        .read_line(&mut response_line)
+        .expect("Failed to read init response");
+
+    // A long synthesized note, which needs no SoundFont, played in the background
+    let play_request = json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "play_notes",
+            "arguments": {
+                "notes": [{
+                    "synth_type": "sine",
+                    "synth_frequency": 440.0,
+                    "start_time": 0.0,
+                    "duration": 5.0
+                }],
+                "async": true
+            }
+        }
+    });
+
+    let requested = std::time::Instant::now();
+    writeln!(stdin, "{}", play_request).expect("Failed to write to stdin");
+
+    response_line.clear();
+    reader
+        .read_line(&mut response_line)
+        .expect("Failed to read play response");
+
+    let response: Value =
+        serde_json::from_str(&response_line).expect("Failed to parse JSON response");
+
+    // The response arrives while the note is still sounding and carries its handle and
+    // how long it will sound
+    let waited = requested.elapsed();
+    assert_eq!(response["id"], 2);
+    assert!(response["error"].is_null(), "{}", response["error"]);
+    assert!(response["result"]["playback_id"].is_u64(), "{}", response);
+    let duration = response["result"]["duration_seconds"].as_f64().unwrap();
+    assert!(duration > 5.0, "duration_seconds {}", duration);
+    assert!(
+        waited.as_secs_f64() < 5.0,
+        "play_notes returned after {:?}, once the note was over",
+        waited
+    );
+
+    // The handle stops that playback while it is still sounding
+    let stop_request = json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": {
+            "name": "stop_playback",
+            "arguments": {"playback_id": response["result"]["playback_id"]}
+        }
+    });
+    writeln!(stdin, "{}", stop_request).expect("Failed to write to stdin");
+    response_line.clear();
+    reader
+        .read_line(&mut response_line)
+        .expect("Failed to read stop response");
+    let stop_response: Value =
+        serde_json::from_str(&response_line).expect("Failed to parse JSON response");
+    assert!(
+        stop_response["error"].is_null(),
+        "{}",
+        stop_response["error"]
+    );
+    assert!(stop_response["result"]["fade_ms"].is_f64());
+
+    child.kill().expect("Failed to kill child process");
+}
+
+#[test]
+#[allow(clippy::zombie_processes)]
+fn test_stop_playback_fades_out_instead_of_cutting() {
+    let mut child = Command::new("cargo")
+        .args(["run", "--"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start MCP server");
+
+    let mut stdin = child.stdin.take().expect("Failed to open stdin");
+    let stdout = child.stdout.take().expect("Failed to open stdout");
+    let mut reader = BufReader::new(stdout);
+
+    // Initialize first
+    let init_request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {"name": "test-client", "version": "1.0.0"}
+        }
+    });
+
+    writeln!(stdin, "{}", init_request).expect("Failed to write to stdin");
+    let mut response_line = String::new();
+    reader
+        .read_line(&mut response_line)
+        .expect("Failed to read init response");
+
+    // Stopping needs no audio device; it reaches MidiPlayer::stop() and reports the fade
+    let stop_request = json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "stop_playback",
+            "arguments": {}
+        }
+    });
+
+    writeln!(stdin, "{}", stop_request).expect("Failed to write to stdin");
+
+    response_line.clear();
+    reader
+        .read_line(&mut response_line)
+        .expect("Failed to read stop response");
+
+    let response: Value =
+        serde_json::from_str(&response_line).expect("Failed to parse JSON response");
+
+    assert_eq!(response["id"], 2);
+    let fade_ms = response["result"]["fade_ms"].as_f64().unwrap();
+    assert!(fade_ms > 0.0, "fade_ms {}", fade_ms);
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("fade-out"), "{}", text);
+
+    child.kill().expect("Failed to kill child process");
+}
+
 #[test]
 #[allow(clippy::zombie_processes)]
 fn test_define_sequence_pattern_valid() {